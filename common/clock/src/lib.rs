@@ -0,0 +1,143 @@
+#![cfg_attr(docsrs, feature(doc_cfg))]
+#![cfg_attr(docsrs, feature(doc_auto_cfg))]
+#![doc = include_str!("../README.md")]
+#![deny(missing_docs)]
+
+use core::time::Duration;
+use std::{
+  sync::{Arc, Mutex},
+  time::SystemTime,
+};
+
+use tokio::sync::Notify;
+
+/// A source of time.
+#[async_trait::async_trait]
+pub trait Clock: Send + Sync + Clone + 'static {
+  /// The current time, as the duration since the Unix epoch.
+  fn now(&self) -> Duration;
+
+  /// Sleep for the specified duration.
+  async fn sleep(&self, duration: Duration);
+
+  /// The current time, as seconds since the Unix epoch.
+  fn unix_time(&self) -> u64 {
+    self.now().as_secs()
+  }
+}
+
+/// The system's clock.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct SystemClock;
+
+#[async_trait::async_trait]
+impl Clock for SystemClock {
+  fn now(&self) -> Duration {
+    SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).expect("system clock is wrong")
+  }
+
+  async fn sleep(&self, duration: Duration) {
+    tokio::time::sleep(duration).await
+  }
+}
+
+/// A clock which only advances when told to.
+///
+/// Clones of a `TestClock` share the same time.
+#[derive(Clone, Debug)]
+pub struct TestClock(Arc<(Mutex<Duration>, Notify)>);
+
+impl TestClock {
+  /// Create a new clock, starting at the specified time (as the duration since the Unix epoch).
+  pub fn new(start: Duration) -> Self {
+    TestClock(Arc::new((Mutex::new(start), Notify::new())))
+  }
+
+  /// Advance the clock by the specified duration, waking any sleeps which have elapsed.
+  pub fn advance(&self, duration: Duration) {
+    {
+      let mut now = self.0 .0.lock().unwrap();
+      *now = now.saturating_add(duration);
+    }
+    self.0 .1.notify_waiters();
+  }
+
+  /// Set the time of this clock.
+  ///
+  /// This may set the clock backwards, as the system clock may move backwards.
+  pub fn set(&self, time: Duration) {
+    *self.0 .0.lock().unwrap() = time;
+    self.0 .1.notify_waiters();
+  }
+}
+
+#[async_trait::async_trait]
+impl Clock for TestClock {
+  fn now(&self) -> Duration {
+    *self.0 .0.lock().unwrap()
+  }
+
+  async fn sleep(&self, duration: Duration) {
+    let until = self.now().saturating_add(duration);
+    loop {
+      // Create the notification before checking the time so an advance between the check and the
+      // await isn't missed
+      let notified = self.0 .1.notified();
+      if self.now() >= until {
+        return;
+      }
+      notified.await;
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_clock() {
+    let clock = TestClock::new(Duration::from_secs(1_000));
+    assert_eq!(clock.now(), Duration::from_secs(1_000));
+    assert_eq!(clock.unix_time(), 1_000);
+
+    // Clones should share the same time
+    let cloned = clock.clone();
+    clock.advance(Duration::from_millis(1_500));
+    assert_eq!(cloned.now(), Duration::from_millis(1_001_500));
+    assert_eq!(cloned.unix_time(), 1_001);
+
+    // The clock may be set backwards
+    cloned.set(Duration::from_secs(10));
+    assert_eq!(clock.now(), Duration::from_secs(10));
+
+    // Advancing shouldn't overflow
+    clock.advance(Duration::MAX);
+    assert_eq!(clock.now(), Duration::MAX);
+  }
+
+  #[tokio::test]
+  async fn test_clock_sleep() {
+    let clock = TestClock::new(Duration::ZERO);
+
+    // Sleeping for no time should immediately return
+    clock.sleep(Duration::ZERO).await;
+
+    let sleep = tokio::spawn({
+      let clock = clock.clone();
+      async move { clock.sleep(Duration::from_secs(10)).await }
+    });
+
+    // Advancing by less than the duration shouldn't wake the sleep
+    for _ in 0 .. 9 {
+      tokio::task::yield_now().await;
+      clock.advance(Duration::from_secs(1));
+      tokio::task::yield_now().await;
+      assert!(!sleep.is_finished());
+    }
+
+    // Advancing the rest of the way should
+    clock.advance(Duration::from_secs(1));
+    tokio::time::timeout(std::time::Duration::from_secs(5), sleep).await.unwrap().unwrap();
+  }
+}
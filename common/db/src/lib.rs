@@ -37,3 +37,10 @@ pub trait Db: 'static + Send + Sync + Clone + Get {
   }
   fn txn(&mut self) -> Self::Transaction<'_>;
 }
+
+/// A database whose contents may be iterated over.
+pub trait Iterate: Db {
+  /// Call the specified function with every key-value pair within the database, as of a single
+  /// point in time.
+  fn iterate(&self, f: impl FnMut(&[u8], &[u8]));
+}
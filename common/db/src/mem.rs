@@ -78,3 +78,10 @@ impl Db for MemDb {
     MemDbTxn(self, HashMap::new(), HashSet::new())
   }
 }
+impl Iterate for MemDb {
+  fn iterate(&self, mut f: impl FnMut(&[u8], &[u8])) {
+    for (key, value) in self.0.read().unwrap().iter() {
+      f(key, value);
+    }
+  }
+}
@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
 use rocksdb::{
-  DBCompressionType, ThreadMode, SingleThreaded, LogLevel, WriteOptions,
+  DBCompressionType, ThreadMode, SingleThreaded, LogLevel, WriteOptions, IteratorMode,
   Transaction as RocksTransaction, Options, OptimisticTransactionDB,
 };
 
@@ -46,6 +46,17 @@ impl<T: Send + ThreadMode + 'static> Db for Arc<OptimisticTransactionDB<T>> {
   }
 }
 
+impl<T: Send + ThreadMode + 'static> Iterate for Arc<OptimisticTransactionDB<T>> {
+  fn iterate(&self, mut f: impl FnMut(&[u8], &[u8])) {
+    // Iterate over a snapshot so writes made while iterating aren't observed
+    let snapshot = self.snapshot();
+    for entry in snapshot.iterator(IteratorMode::Start) {
+      let (key, value) = entry.expect("couldn't iterate over RocksDB");
+      f(&key, &value);
+    }
+  }
+}
+
 pub type RocksDB = Arc<OptimisticTransactionDB<SingleThreaded>>;
 pub fn new_rocksdb(path: &str) -> RocksDB {
   let mut options = Options::default();
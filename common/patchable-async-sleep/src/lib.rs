@@ -5,6 +5,27 @@
 use core::time::Duration;
 
 /// Sleep for the specified duration.
+#[cfg(not(all(target_arch = "wasm32", feature = "wasm")))]
 pub fn sleep(duration: Duration) -> impl core::future::Future<Output = ()> {
   tokio::time::sleep(duration)
 }
+
+/// Sleep for the specified duration.
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+pub fn sleep(duration: Duration) -> impl core::future::Future<Output = ()> {
+  gloo_timers::future::sleep(duration)
+}
+
+/// An instant, as measured by the clock `sleep` is timed against.
+///
+/// Under `tokio`, this is `tokio`'s `Instant`, which respects time being paused (as possible
+/// within tests).
+#[cfg(not(all(target_arch = "wasm32", feature = "wasm")))]
+pub use tokio::time::Instant;
+
+/// An instant, as measured by the clock `sleep` is timed against.
+///
+/// `std`'s `Instant` panics on `wasm32-unknown-unknown`, so this is `web-time`'s, which is backed
+/// by the browser's `performance.now()`.
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+pub use web_time::Instant;
@@ -31,6 +31,8 @@ create_db! {
     ReceivedCosign: (set: ExternalValidatorSet, block: [u8; 32]) -> CosignedBlock,
     LatestCosign: (network: ExternalNetworkId) -> CosignedBlock,
     DistinctChain: (set: ExternalValidatorSet) -> (),
+    // The latest cosign a network produced for a block distinct from the one on our chain
+    DistinctCosign: (network: ExternalNetworkId) -> CosignedBlock,
   }
 }
 
@@ -200,6 +202,7 @@ impl<D: Db> CosignEvaluator<D> {
       // Save this set as being on a different chain
       let mut txn = db.txn();
       DistinctChain::set(&mut txn, set_with_keys, &());
+      DistinctCosign::set(&mut txn, set_with_keys.network, &cosign);
       txn.commit();
 
       let mut total_stake = 0;
@@ -14,13 +14,25 @@ use serai_client::{
 pub use serai_db::*;
 
 use ::tributary::ReadWrite;
-use crate::tributary::{TributarySpec, Transaction, scanner::RecognizedIdType};
+use crate::tributary::{
+  TributarySpec, LegacyTributarySpec, UnversionedTributarySpec, Transaction,
+  scanner::RecognizedIdType,
+};
 
 create_db!(
   MainDb {
     HandledMessageDb: (network: ExternalNetworkId) -> u64,
+    // The transactions created by handling processor messages which have yet to be published,
+    // with the sessions of the Tributaries they're for
+    PendingPublicationDb: (network: ExternalNetworkId) -> Vec<(Session, Vec<u8>)>,
     ActiveTributaryDb: () -> Vec<u8>,
+    // The version of the encoding of the specs within ActiveTributaryDb
+    ActiveTributaryVersionDb: () -> u8,
     RetiredTributaryDb: (set: ExternalValidatorSet) -> (),
+    // The specs of retired Tributaries, retained so their data remains discoverable
+    ArchivedTributaryDb: (set: ExternalValidatorSet) -> TributarySpec,
+    // Retired Tributaries whose blocks have been pruned
+    PrunedTributaryDb: (set: ExternalValidatorSet) -> (),
     FirstPreprocessDb: (
       network: ExternalNetworkId,
       id_type: RecognizedIdType,
@@ -36,7 +48,38 @@ create_db!(
   }
 );
 
+// The specs were versioned when attempt windows were added, with version 2 adding protocol
+// versions
+const TRIBUTARY_SPEC_VERSION: u8 = 2;
+
 impl ActiveTributaryDb {
+  /// Migrate the active Tributaries' specs to the latest encoding.
+  pub fn migrate(txn: &mut impl DbTxn) {
+    let version = ActiveTributaryVersionDb::get(txn);
+    if version == Some(TRIBUTARY_SPEC_VERSION) {
+      return;
+    }
+
+    let bytes = Self::get(txn).unwrap_or_default();
+    let mut bytes_ref: &[u8] = bytes.as_ref();
+    let mut migrated = vec![];
+    while !bytes_ref.is_empty() {
+      let spec = match version {
+        // Specs without a version predate attempt windows
+        None => {
+          TributarySpec::from(LegacyTributarySpec::deserialize_reader(&mut bytes_ref).unwrap())
+        }
+        Some(1) => {
+          TributarySpec::from(UnversionedTributarySpec::deserialize_reader(&mut bytes_ref).unwrap())
+        }
+        Some(version) => panic!("unknown Tributary spec version {version}"),
+      };
+      spec.serialize(&mut migrated).unwrap();
+    }
+    Self::set(txn, &migrated);
+    ActiveTributaryVersionDb::set(txn, &TRIBUTARY_SPEC_VERSION);
+  }
+
   pub fn active_tributaries<G: Get>(getter: &G) -> (Vec<u8>, Vec<TributarySpec>) {
     let bytes = Self::get(getter).unwrap_or_default();
     let mut bytes_ref: &[u8] = bytes.as_ref();
@@ -65,7 +108,7 @@ impl ActiveTributaryDb {
     let mut active = Self::active_tributaries(txn).1;
     for i in 0 .. active.len() {
       if active[i].set() == set {
-        active.remove(i);
+        ArchivedTributaryDb::set(txn, set, &active.remove(i));
         break;
       }
     }
@@ -79,6 +122,27 @@ impl ActiveTributaryDb {
   }
 }
 
+impl PendingPublicationDb {
+  pub fn queue(
+    txn: &mut impl DbTxn,
+    network: ExternalNetworkId,
+    session: Session,
+    tx: &Transaction,
+  ) {
+    let mut pending = Self::get(txn, network).unwrap_or_default();
+    pending.push((session, tx.serialize()));
+    Self::set(txn, network, &pending);
+  }
+
+  pub fn pending(getter: &impl Get, network: ExternalNetworkId) -> Vec<(Session, Transaction)> {
+    Self::get(getter, network)
+      .unwrap_or_default()
+      .into_iter()
+      .map(|(session, tx)| (session, Transaction::read(&mut tx.as_slice()).unwrap()))
+      .collect()
+  }
+}
+
 impl FirstPreprocessDb {
   pub fn save_first_preprocess(
     txn: &mut impl DbTxn,
@@ -1,6 +1,7 @@
-use core::ops::Deref;
+use core::{ops::Deref, future::Future};
 use std::{
   sync::{OnceLock, Arc},
+  path::PathBuf,
   time::Duration,
   collections::{VecDeque, HashSet, HashMap},
 };
@@ -19,6 +20,7 @@ use schnorr::SchnorrSignature;
 use frost::Participant;
 
 use serai_db::{DbTxn, Db};
+use serai_clock::{Clock, SystemClock};
 
 use scale::Encode;
 use borsh::BorshSerialize;
@@ -28,8 +30,6 @@ use serai_client::{
   Public, Serai, SeraiInInstructions,
 };
 
-use message_queue::{Service, client::MessageQueue};
-
 use tokio::{
   sync::{Mutex, RwLock, mpsc, broadcast},
   time::sleep,
@@ -48,6 +48,8 @@ use db::*;
 mod p2p;
 pub use p2p::*;
 
+mod tor;
+
 use processor_messages::{
   key_gen, sign,
   coordinator::{self, SubstrateSignableId},
@@ -55,7 +57,7 @@ use processor_messages::{
 };
 
 pub mod processors;
-use processors::Processors;
+use processors::{Processors, NetworkProcessors};
 
 mod substrate;
 use substrate::CosignTransactions;
@@ -63,6 +65,12 @@ use substrate::CosignTransactions;
 mod cosign_evaluator;
 use cosign_evaluator::CosignEvaluator;
 
+mod status;
+use status::StatusApi;
+
+#[cfg(any(feature = "rocksdb", test))]
+mod snapshot;
+
 #[cfg(test)]
 pub mod tests;
 
@@ -93,6 +101,7 @@ async fn add_tributary<D: Db, Pro: Processors, P: P2p>(
 ) {
   if RetiredTributaryDb::get(&db, spec.set()).is_some() {
     log::info!("not adding tributary {:?} since it's been retired", spec.set());
+    return;
   }
 
   log::info!("adding tributary {:?}", spec.set());
@@ -105,6 +114,7 @@ async fn add_tributary<D: Db, Pro: Processors, P: P2p>(
     spec.start_time(),
     key.clone(),
     spec.validators(),
+    spec.proposer_schedule(),
     p2p,
   )
   .await
@@ -148,15 +158,16 @@ async fn handle_processor_message<D: Db, P: P2p>(
   network: ExternalNetworkId,
   msg: &processors::Message,
 ) -> bool {
-  #[allow(clippy::nonminimal_bool)]
+  // Messages which weren't authenticated as from our processor are rejected without being
+  // handled, so the IDs of the messages handled may skip, yet their sequence numbers don't
   if let Some(already_handled) = HandledMessageDb::get(db, msg.network) {
-    assert!(!(already_handled > msg.id));
-    assert!((already_handled == msg.id) || (already_handled == msg.id - 1));
-    if already_handled == msg.id {
+    assert!(already_handled <= msg.sequence);
+    assert!((already_handled == msg.sequence) || (already_handled == msg.sequence - 1));
+    if already_handled == msg.sequence {
       return true;
     }
   } else {
-    assert_eq!(msg.id, 0);
+    assert_eq!(msg.sequence, 0);
   }
 
   let _hvq_lock = HANDOVER_VERIFY_QUEUE_LOCK.get_or_init(|| Mutex::new(())).lock().await;
@@ -219,23 +230,7 @@ async fn handle_processor_message<D: Db, P: P2p>(
             hex::encode(tx.hash()),
             &tx
           );
-          log::trace!("providing transaction {}", hex::encode(tx.hash()));
-          let res = tributary.tributary.provide_transaction(tx).await;
-          if !(res.is_ok() || (res == Err(ProvidedError::AlreadyProvided))) {
-            if res == Err(ProvidedError::LocalMismatchesOnChain) {
-              // Spin, since this is a crit for this Tributary
-              loop {
-                log::error!(
-                  "{}. tributary: {}, provided: SubstrateBlock({})",
-                  "tributary added distinct provided to delayed locally provided TX",
-                  hex::encode(tributary.spec.genesis()),
-                  block,
-                );
-                sleep(Duration::from_secs(60)).await;
-              }
-            }
-            panic!("provided an invalid transaction: {res:?}");
-          }
+          PendingPublicationDb::queue(&mut txn, network, session, &tx);
         }
 
         None
@@ -408,7 +403,7 @@ async fn handle_processor_message<D: Db, P: P2p>(
     // Per the reasoning above, we only return a Tributary as relevant if we're a participant
     // Accordingly, we do *need* to have this Tributary now to handle it UNLESS the Tributary has
     // already completed and this is simply an old message (which we prior checked)
-    let Some(ActiveTributary { spec, tributary }) = tributaries.get(&relevant_tributary) else {
+    let Some(ActiveTributary { spec, .. }) = tributaries.get(&relevant_tributary) else {
       // Since we don't, sleep for a fraction of a second and return false, signaling we didn't
       // handle this message
       // At the start of the loop which calls this function, we'll check for new tributaries,
@@ -730,46 +725,93 @@ async fn handle_processor_message<D: Db, P: P2p>(
       },
     };
 
-    // If this created transactions, publish them
+    // Write the transactions this created to the log of transactions pending publication
+    // Signed transactions are signed now so they're published identically if this is replayed
     for mut tx in txs {
       log::trace!("processor message effected transaction {} {:?}", hex::encode(tx.hash()), &tx);
+      if let TransactionKind::Signed(_, _) = tx.kind() {
+        tx.sign(&mut OsRng, genesis, key);
+      }
+      PendingPublicationDb::queue(&mut txn, network, relevant_tributary, &tx);
+    }
+  }
 
-      match tx.kind() {
-        TransactionKind::Provided(_) => {
-          log::trace!("providing transaction {}", hex::encode(tx.hash()));
-          let res = tributary.provide_transaction(tx.clone()).await;
-          if !(res.is_ok() || (res == Err(ProvidedError::AlreadyProvided))) {
-            if res == Err(ProvidedError::LocalMismatchesOnChain) {
-              // Spin, since this is a crit for this Tributary
-              loop {
-                log::error!(
-                  "{}. tributary: {}, provided: {:?}",
-                  "tributary added distinct provided to delayed locally provided TX",
-                  hex::encode(spec.genesis()),
-                  &tx,
-                );
-                sleep(Duration::from_secs(60)).await;
-              }
+  HandledMessageDb::set(&mut txn, msg.network, &msg.sequence);
+  txn.commit();
+
+  // Publish the transactions this created
+  // If we don't have a Tributary they're for, they'll be published before the next message is
+  // handled
+  publish_pending_transactions(db, tributaries, network).await;
+
+  true
+}
+
+// Publish the transactions created by handling processor messages.
+//
+// Handling a processor message writes the transactions it creates to a log within the same DB
+// transaction which marks the message as handled, with them only published, and removed from the
+// log, once that's committed. This ensures a crash at any point won't cause them to be lost or
+// recreated, with any transactions still within the log published after rebooting, before any
+// further messages are handled. Publication is idempotent, making it safe to re-publish
+// transactions which were published before a crash yet not removed from the log.
+//
+// Returns false if we don't have a Tributary which transactions are pending publication for.
+async fn publish_pending_transactions<D: Db, P: P2p>(
+  db: &mut D,
+  tributaries: &HashMap<Session, ActiveTributary<D, P>>,
+  network: ExternalNetworkId,
+) -> bool {
+  let pending = PendingPublicationDb::pending(db, network);
+  if pending.is_empty() {
+    return true;
+  }
+
+  let mut txn = db.txn();
+  for (session, tx) in pending {
+    // If this Tributary has since been retired, there's no need to publish this
+    if RetiredTributaryDb::get(&txn, ExternalValidatorSet { network, session }).is_some() {
+      continue;
+    }
+    let Some(ActiveTributary { spec, tributary }) = tributaries.get(&session) else {
+      // Sleep for a fraction of a second, letting the caller check for new Tributaries
+      sleep(Duration::from_millis(100)).await;
+      return false;
+    };
+
+    match tx.kind() {
+      TransactionKind::Provided(_) => {
+        log::trace!("providing transaction {}", hex::encode(tx.hash()));
+        let res = tributary.provide_transaction(tx.clone()).await;
+        if !(res.is_ok() || (res == Err(ProvidedError::AlreadyProvided))) {
+          if res == Err(ProvidedError::LocalMismatchesOnChain) {
+            // Spin, since this is a crit for this Tributary
+            loop {
+              log::error!(
+                "{}. tributary: {}, provided: {:?}",
+                "tributary added distinct provided to delayed locally provided TX",
+                hex::encode(spec.genesis()),
+                &tx,
+              );
+              sleep(Duration::from_secs(60)).await;
             }
-            panic!("provided an invalid transaction: {res:?}");
           }
+          panic!("provided an invalid transaction: {res:?}");
         }
-        TransactionKind::Unsigned => {
-          log::trace!("publishing unsigned transaction {}", hex::encode(tx.hash()));
-          match tributary.add_transaction(tx.clone()).await {
-            Ok(_) => {}
-            Err(e) => panic!("created an invalid unsigned transaction: {e:?}"),
-          }
-        }
-        TransactionKind::Signed(_, _) => {
-          tx.sign(&mut OsRng, genesis, key);
-          tributary::publish_signed_transaction(&mut txn, tributary, tx).await;
+      }
+      TransactionKind::Unsigned => {
+        log::trace!("publishing unsigned transaction {}", hex::encode(tx.hash()));
+        match tributary.add_transaction(tx.clone()).await {
+          Ok(_) => {}
+          Err(e) => panic!("created an invalid unsigned transaction: {e:?}"),
         }
       }
+      TransactionKind::Signed(_, _) => {
+        tributary::publish_signed_transaction(&mut txn, tributary, tx).await;
+      }
     }
   }
-
-  HandledMessageDb::set(&mut txn, msg.network, &msg.id);
+  PendingPublicationDb::del(&mut txn, network);
   txn.commit();
 
   true
@@ -805,6 +847,11 @@ async fn handle_processor_messages<D: Db, Pro: Processors, P: P2p>(
       }
     }
 
+    // Publish any transactions still pending publication before handling further messages
+    if !publish_pending_transactions(&mut db, &tributaries, network).await {
+      continue;
+    }
+
     // TODO: Check this ID is sane (last handled ID or expected next ID)
     let Ok(msg) = tokio::time::timeout(Duration::from_secs(1), processors.recv(network)).await
     else {
@@ -962,6 +1009,21 @@ async fn handle_cosigns_and_batch_publication<D: Db, P: P2p>(
   }
 }
 
+tokio::task_local! {
+  // The network the current task is isolated to, if it's isolated to one
+  static ISOLATED_NETWORK: ExternalNetworkId;
+}
+
+// Spawn a task for a single network, isolated so it panicking solely halts this network
+fn spawn_isolated(network: ExternalNetworkId, task: impl 'static + Send + Future<Output = ()>) {
+  let task = tokio::spawn(ISOLATED_NETWORK.scope(network, task));
+  tokio::spawn(async move {
+    if let Err(e) = task.await {
+      log::error!("halting {network:?} due to its task panicking: {e}");
+    }
+  });
+}
+
 pub async fn handle_processors<D: Db, Pro: Processors, P: P2p>(
   db: D,
   key: Zeroizing<<Ristretto as Ciphersuite>::F>,
@@ -974,45 +1036,59 @@ pub async fn handle_processors<D: Db, Pro: Processors, P: P2p>(
   let mut channels = HashMap::new();
   for network in serai_client::primitives::EXTERNAL_NETWORKS {
     let (processor_send, processor_recv) = mpsc::unbounded_channel();
-    tokio::spawn(handle_processor_messages(
-      db.clone(),
-      key.clone(),
-      serai.clone(),
-      processors.clone(),
-      p2p.clone(),
-      cosign_channel.clone(),
+    spawn_isolated(
       network,
-      processor_recv,
-    ));
+      handle_processor_messages(
+        db.clone(),
+        key.clone(),
+        serai.clone(),
+        processors.clone(),
+        p2p.clone(),
+        cosign_channel.clone(),
+        network,
+        processor_recv,
+      ),
+    );
     let (cosign_send, cosign_recv) = mpsc::unbounded_channel();
-    tokio::spawn(handle_cosigns_and_batch_publication(db.clone(), network, cosign_recv));
+    spawn_isolated(network, handle_cosigns_and_batch_publication(db.clone(), network, cosign_recv));
     channels.insert(network, (processor_send, cosign_send));
   }
 
   // Listen to new tributary events
+  // If a network halted, its tasks will have dropped their receivers, so sending to them may fail
   loop {
     match tributary_event.recv().await.unwrap() {
       TributaryEvent::NewTributary(tributary) => {
         let (c1, c2) = &channels[&tributary.spec.set().network];
-        c1.send(TributaryEvent::NewTributary(tributary.clone())).unwrap();
-        c2.send(TributaryEvent::NewTributary(tributary)).unwrap();
+        let _ = c1.send(TributaryEvent::NewTributary(tributary.clone()));
+        let _ = c2.send(TributaryEvent::NewTributary(tributary));
       }
       TributaryEvent::TributaryRetired(set) => {
         let (c1, c2) = &channels[&set.network];
-        c1.send(TributaryEvent::TributaryRetired(set)).unwrap();
-        c2.send(TributaryEvent::TributaryRetired(set)).unwrap();
+        let _ = c1.send(TributaryEvent::TributaryRetired(set));
+        let _ = c2.send(TributaryEvent::TributaryRetired(set));
       }
     };
   }
 }
 
-pub async fn run<D: Db, Pro: Processors, P: P2p>(
-  raw_db: D,
+#[allow(clippy::too_many_arguments)]
+pub async fn run<D: Db, Pro: Processors, P: P2p, C: Clock>(
+  mut raw_db: D,
   key: Zeroizing<<Ristretto as Ciphersuite>::F>,
   p2p: P,
   processors: Pro,
   serai: Arc<Serai>,
+  clock: C,
+  status_api: Option<StatusApi>,
+  archive_path: Option<PathBuf>,
 ) {
+  {
+    let mut txn = raw_db.txn();
+    ActiveTributaryDb::migrate(&mut txn);
+    txn.commit();
+  }
+
   let (new_tributary_spec_send, mut new_tributary_spec_recv) = mpsc::unbounded_channel();
   // Reload active tributaries from the database
   for spec in ActiveTributaryDb::active_tributaries(&raw_db).1 {
@@ -1043,6 +1119,9 @@ pub async fn run<D: Db, Pro: Processors, P: P2p>(
   let tributary_event_listener_3 = tributary_event.subscribe();
   let tributary_event_listener_4 = tributary_event.subscribe();
   let tributary_event_listener_5 = tributary_event.subscribe();
+  let tributary_event_listener_6 = tributary_event.subscribe();
+  let tributary_event_listener_7 = tributary_event.subscribe();
+  let tributary_event_listener_8 = tributary_event.subscribe();
 
   // Emit TributaryEvent::TributaryRetired
   tokio::spawn({
@@ -1125,7 +1204,7 @@ pub async fn run<D: Db, Pro: Processors, P: P2p>(
         'task_loop: loop {
           match perform_slash_report_recv.recv().await {
             Some(set) => {
-              let (genesis, validators) = loop {
+              let (genesis, validators, reader) = loop {
                 let specs = specs.read().await;
                 let Some(spec) = specs.get(&set) else {
                   // If we don't have this Tributary because it's retired, break and move on
@@ -1137,7 +1216,9 @@ pub async fn run<D: Db, Pro: Processors, P: P2p>(
                   log::warn!("tributary we don't have yet is supposed to perform a slash report");
                   continue;
                 };
-                break (spec.genesis(), spec.validators());
+                // Tributaries are added before their specs and removed after, so this is present
+                let reader = tributaries.read().await[&spec.genesis()].reader();
+                break (spec.genesis(), spec.validators(), reader);
               };
 
               let mut slashes = vec![];
@@ -1281,13 +1362,42 @@ pub async fn run<D: Db, Pro: Processors, P: P2p>(
 
   // Spawn the heartbeat task, which will trigger syncing if there hasn't been a Tributary block
   // in a while (presumably because we're behind)
-  tokio::spawn(p2p::heartbeat_tributaries_task(p2p.clone(), tributary_event_listener_3));
+  tokio::spawn(p2p::heartbeat_tributaries_task(
+    clock.clone(),
+    p2p.clone(),
+    tributary_event_listener_3,
+  ));
+
+  // Publish heartbeats onto our Tributaries so other validators know we're live
+  tokio::spawn(tributary::liveness::publish_heartbeats_task(
+    clock.clone(),
+    key.clone(),
+    tributary_event_listener_7,
+  ));
+
+  // Prune Tributaries once they've been retired for long enough
+  tokio::spawn(tributary::retention::prune_retired_tributaries_task(
+    raw_db.clone(),
+    archive_path,
+    tributary_event_listener_8,
+  ));
+
+  // Serve the status API, if configured
+  if let Some(status_api) = status_api {
+    tokio::spawn(status::status_api_task(
+      raw_db.clone(),
+      p2p.clone(),
+      status_api,
+      tributary_event_listener_6,
+    ));
+  }
 
   // Create the Cosign evaluator
   let cosign_channel = CosignEvaluator::new(raw_db.clone(), p2p.clone(), serai.clone());
 
   // Handle P2P messages
   tokio::spawn(p2p::handle_p2p_task(
+    clock,
     p2p.clone(),
     cosign_channel.clone(),
     tributary_event_listener_4,
@@ -1309,10 +1419,14 @@ pub async fn run<D: Db, Pro: Processors, P: P2p>(
 #[tokio::main]
 async fn main() {
   // Override the panic handler with one which will panic if any tokio task panics
+  // Tasks isolated to a network are exempted, as they solely halt their network
   {
     let existing = std::panic::take_hook();
     std::panic::set_hook(Box::new(move |panic| {
       existing(panic);
+      if ISOLATED_NETWORK.try_with(|_| ()).is_ok() {
+        return;
+      }
       const MSG: &str = "exiting the process due to a task panicking";
       println!("{MSG}");
       log::error!("{MSG}");
@@ -1340,6 +1454,39 @@ async fn main() {
     db
   };
 
+  // Handle the commands to export and import snapshots, used to move the coordinator to another
+  // machine, and to export and import Tributaries, used to quickly sync a Tributary, which are run
+  // while the coordinator is stopped
+  if let Some(command) = std::env::args().nth(1) {
+    #[cfg(not(feature = "rocksdb"))]
+    panic!("{command} is only supported when built with rocksdb");
+    #[cfg(feature = "rocksdb")]
+    {
+      let path = std::env::args().nth(2).unwrap_or_else(|| panic!("{command} requires a path"));
+      let path = std::path::Path::new(&path);
+      // The Tributary commands additionally take the genesis of the Tributary
+      let genesis = || {
+        let genesis =
+          std::env::args().nth(3).unwrap_or_else(|| panic!("{command} requires a genesis"));
+        hex::decode(genesis)
+          .ok()
+          .and_then(|genesis| <[u8; 32]>::try_from(genesis).ok())
+          .unwrap_or_else(|| panic!("{command} was passed an invalid genesis"))
+      };
+      let mut db = db;
+      let entries = match command.as_str() {
+        "export-snapshot" => snapshot::export(&db, path),
+        "import-snapshot" => snapshot::import(&mut db, path),
+        "export-tributary" => snapshot::export_tributary(&db, genesis(), path),
+        "import-tributary" => snapshot::import_tributary(&mut db, genesis(), path),
+        _ => panic!("unrecognized command {command}"),
+      }
+      .unwrap_or_else(|e| panic!("couldn't {command} with {}: {e}", path.display()));
+      log::info!("completed {command} with {entries} entries, using {}", path.display());
+      return;
+    }
+  }
+
   let key = {
     let mut key_hex = serai_env::var("SERAI_KEY").expect("Serai key wasn't provided");
     let mut key_vec = hex::decode(&key_hex).map_err(|_| ()).expect("Serai key wasn't hex-encoded");
@@ -1356,7 +1503,7 @@ async fn main() {
     key
   };
 
-  let processors = Arc::new(MessageQueue::from_env(Service::Coordinator));
+  let processors = NetworkProcessors::from_env();
 
   let serai = (async {
     loop {
@@ -1375,6 +1522,23 @@ async fn main() {
     }
   })
   .await;
-  let p2p = LibP2p::new(serai.clone());
-  run(db, key, p2p, processors, serai).await
+  // If we're reachable via an onion service, publish its address for fellow coordinators to dial
+  if let Some(onion) = serai_env::var("TOR_ONION_ADDRESS") {
+    let onion = onion.parse().expect("TOR_ONION_ADDRESS wasn't a valid multiaddr");
+    tokio::spawn({
+      let serai = serai.clone();
+      let key = key.clone();
+      async move { tor::publish_onion_address(&serai, &key, onion).await }
+    });
+  }
+  let tor_proxy = serai_env::var("TOR_SOCKS_PROXY")
+    .map(|proxy| proxy.parse().expect("TOR_SOCKS_PROXY wasn't a valid socket address"));
+  let p2p = LibP2p::new(serai.clone(), tor_proxy);
+  let status_api = serai_env::var("STATUS_API_ADDRESS").map(|address| StatusApi {
+    address: address.parse().expect("STATUS_API_ADDRESS wasn't a valid socket address"),
+    key: serai_env::var("STATUS_API_KEY").expect("status API enabled without a key"),
+  });
+  // If set, Tributaries are exported to this directory before being pruned
+  let archive_path = serai_env::var("TRIBUTARY_ARCHIVE_PATH").map(PathBuf::from);
+  run(db, key, p2p, processors, serai, SystemClock, status_api, archive_path).await
 }
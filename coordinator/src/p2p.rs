@@ -1,9 +1,10 @@
-use core::{time::Duration, fmt};
+use core::{num::NonZeroU32, time::Duration, fmt};
 use std::{
   sync::Arc,
   io::{self, Read},
   collections::{HashSet, HashMap},
-  time::{SystemTime, Instant},
+  net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+  time::Instant,
 };
 
 use async_trait::async_trait;
@@ -13,18 +14,22 @@ use scale::{Decode, Encode};
 use borsh::{BorshSerialize, BorshDeserialize};
 use serai_client::{
   primitives::ExternalNetworkId, validator_sets::primitives::ExternalValidatorSet, Serai,
+  SeraiError,
 };
 
 use serai_db::Db;
+use serai_clock::Clock;
 
 use futures_util::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, StreamExt};
-use tokio::{
-  sync::{Mutex, RwLock, mpsc, broadcast},
-  time::sleep,
-};
+use tokio::sync::{Mutex, RwLock, mpsc, broadcast};
 
 use libp2p::{
-  core::multiaddr::{Protocol, Multiaddr},
+  core::{
+    multiaddr::{Protocol, Multiaddr, Onion3Addr},
+    transport::OptionalTransport,
+    upgrade::Version,
+    Transport as _,
+  },
   identity::Keypair,
   PeerId,
   tcp::Config as TcpConfig,
@@ -34,17 +39,30 @@ use libp2p::{
     Behaviour as RrBehavior, ProtocolSupport,
   },
   gossipsub::{
-    IdentTopic, FastMessageId, MessageId, MessageAuthenticity, ValidationMode, ConfigBuilder,
-    IdentityTransform, AllowAllSubscriptionFilter, Event as GsEvent, PublishError,
-    Behaviour as GsBehavior,
+    IdentTopic, FastMessageId, MessageId, MessageAuthenticity, MessageAcceptance, ValidationMode,
+    ConfigBuilder, IdentityTransform, AllowAllSubscriptionFilter, PeerScoreParams,
+    PeerScoreThresholds, TopicScoreParams, Event as GsEvent, PublishError, Behaviour as GsBehavior,
+  },
+  connection_limits::{ConnectionLimits, Behaviour as LimitsBehavior},
+  identify::{Config as IdentifyConfig, Behaviour as IdentifyBehavior},
+  autonat::{
+    Config as AutonatConfig, Event as AutonatEvent, NatStatus, Behaviour as AutonatBehavior,
   },
-  swarm::{NetworkBehaviour, SwarmEvent},
-  SwarmBuilder,
+  relay::{
+    client::Behaviour as RelayClientBehavior, Config as RelayConfig, Behaviour as RelayBehavior,
+  },
+  dcutr::Behaviour as DcutrBehavior,
+  swarm::{NetworkBehaviour, SwarmEvent, ListenerId, ConnectionId, DialError, dial_opts::DialOpts},
+  Swarm, SwarmBuilder,
 };
 
 pub(crate) use tributary::{ReadWrite, P2p as TributaryP2p};
+use tributary::{TransactionKind, TransactionTrait, MessageValidity};
 
-use crate::{Transaction, Block, Tributary, ActiveTributary, TributaryEvent};
+use crate::{
+  Transaction, Block, Tributary, ActiveTributary, TributaryEvent,
+  tor::{self, TorTransport},
+};
 
 // Block size limit + 1 KB of space for signatures/metadata
 const MAX_LIBP2P_GOSSIP_MESSAGE_SIZE: usize = tributary::BLOCK_SIZE_LIMIT + 1024;
@@ -61,7 +79,38 @@ const MAX_LIBP2P_MESSAGE_SIZE: usize = {
   }
 };
 
-const LIBP2P_TOPIC: &str = "serai-coordinator";
+// The protocol and topics our messages are sent under, versioned as prior versions couldn't
+// decode our messages since we prefix them with if they were compressed
+const LIBP2P_PROTOCOL: &str = "/coordinator/2";
+const LIBP2P_TOPIC: &str = "serai-coordinator-2";
+
+// The port we listen on, over both TCP and QUIC
+const PORT: u16 = 30563; // 5132 ^ (('c' << 8) | 'o')
+
+// Messages at least this large are compressed before being sent
+const COMPRESSION_THRESHOLD: usize = 1024;
+// The prefixes for messages, specifying if they were compressed
+const UNCOMPRESSED: u8 = 0;
+const COMPRESSED: u8 = 1;
+
+// The maximum amount of connections being established at once, in each direction
+const MAX_PENDING_CONNECTIONS: u32 = 32;
+// The maximum amount of established incoming connections
+const MAX_INCOMING_CONNECTIONS: u32 = 128;
+// The maximum amount of established connections to a single peer, allowing for simultaneous dials
+const MAX_CONNECTIONS_PER_PEER: u32 = 2;
+
+// The amount of relays to listen via when behind a NAT
+const TARGET_RELAYS: usize = 3;
+// The maximum amount of peers we'll hold relay reservations for
+//
+// Peer IDs aren't bound to validators, so anyone may request a reservation. This bounds how much
+// of our bandwidth may be used by peers who aren't fellow coordinators.
+const MAX_RELAY_RESERVATIONS: usize = 32;
+// The maximum amount of connections we'll relay at once
+const MAX_RELAY_CIRCUITS: usize = 32;
+// The amount of reservations/circuits a single IP may request per minute
+const RELAY_REQUESTS_PER_IP_PER_MINUTE: u32 = 4;
 
 // Amount of blocks in a minute
 const BLOCKS_PER_MINUTE: usize = (60 / (tributary::tendermint::TARGET_BLOCK_TIME / 1000)) as usize;
@@ -69,6 +118,113 @@ const BLOCKS_PER_MINUTE: usize = (60 / (tributary::tendermint::TARGET_BLOCK_TIME
 // Maximum amount of blocks to send in a batch
 const BLOCKS_PER_BATCH: usize = BLOCKS_PER_MINUTE + 1;
 
+// The amount of recent blocks whose signing protocols we request missing transactions for
+const MISSING_TRANSACTIONS_WINDOW: usize = 5 * BLOCKS_PER_MINUTE;
+// The maximum amount of orders which may be requested at once
+const MAX_REQUESTED_ORDERS: usize = 64;
+// The maximum size of an order, which is at most a label, a 32-byte ID, and an attempt
+const MAX_ORDER_SIZE: usize = 64;
+
+/// A transport connections with peers are made over.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum P2pTransport {
+  Tcp,
+  Quic,
+  Tor,
+  Relayed,
+}
+
+impl P2pTransport {
+  pub const ALL: [P2pTransport; 4] =
+    [P2pTransport::Tcp, P2pTransport::Quic, P2pTransport::Tor, P2pTransport::Relayed];
+
+  pub(crate) fn of(addr: &Multiaddr) -> P2pTransport {
+    if addr.iter().any(|protocol| protocol == Protocol::P2pCircuit) {
+      P2pTransport::Relayed
+    } else if tor::is_onion(addr) {
+      P2pTransport::Tor
+    } else if addr.iter().any(|protocol| protocol == Protocol::QuicV1) {
+      P2pTransport::Quic
+    } else {
+      P2pTransport::Tcp
+    }
+  }
+
+  pub fn name(&self) -> &'static str {
+    match self {
+      P2pTransport::Tcp => "tcp",
+      P2pTransport::Quic => "quic",
+      P2pTransport::Tor => "tor",
+      P2pTransport::Relayed => "relayed",
+    }
+  }
+}
+
+/// Metrics on the connections made over a transport, since the coordinator started.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct TransportMetrics {
+  /// The amount of connections currently open.
+  pub connections: usize,
+  /// The amount of connections established, incoming or outgoing.
+  pub established: u64,
+  /// The amount of our dials to peers which failed.
+  pub failed_dials: u64,
+}
+
+#[derive(Default, Debug)]
+struct TransportMetricsCollector(std::sync::Mutex<HashMap<P2pTransport, TransportMetrics>>);
+
+impl TransportMetricsCollector {
+  fn update(&self, transport: P2pTransport, f: impl FnOnce(&mut TransportMetrics)) {
+    f(self.0.lock().unwrap().entry(transport).or_default());
+  }
+
+  fn metrics(&self) -> HashMap<P2pTransport, TransportMetrics> {
+    self.0.lock().unwrap().clone()
+  }
+}
+
+// The latency presumed for peers we haven't dialed yet, by if they're an onion service
+const CLEARNET_LATENCY: Duration = Duration::from_millis(250);
+const ONION_LATENCY: Duration = Duration::from_secs(2);
+
+// The latencies of the peers we've dialed, as measured by how long our dials to them took
+#[derive(Default, Debug)]
+pub(crate) struct Latencies(std::sync::Mutex<HashMap<Multiaddr, Duration>>);
+
+impl Latencies {
+  // Record the latency of a dial, smoothed with the latency previously measured for the address
+  pub(crate) fn record(&self, addr: Multiaddr, latency: Duration) {
+    let mut latencies = self.0.lock().unwrap();
+    let latency = match latencies.get(&addr) {
+      Some(prior) => ((*prior * 3) + latency) / 4,
+      None => latency,
+    };
+    latencies.insert(addr, latency);
+  }
+
+  // Rank peers by their expected latency, lowest first, with ties broken randomly
+  //
+  // Peers we haven't dialed yet are presumed to have the latency typical of their transport, so
+  // clearnet peers are preferred over onion services until we learn otherwise.
+  pub(crate) fn rank(&self, mut nodes: Vec<Multiaddr>) -> Vec<Multiaddr> {
+    for i in (1 .. nodes.len()).rev() {
+      let j = usize::try_from(OsRng.next_u64() % u64::try_from(i + 1).unwrap()).unwrap();
+      nodes.swap(i, j);
+    }
+
+    let latencies = self.0.lock().unwrap();
+    nodes.sort_by_cached_key(|node| {
+      latencies.get(node).copied().unwrap_or(if tor::is_onion(node) {
+        ONION_LATENCY
+      } else {
+        CLEARNET_LATENCY
+      })
+    });
+    nodes
+  }
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, BorshSerialize, BorshDeserialize)]
 pub struct CosignedBlock {
   pub network: ExternalNetworkId,
@@ -82,6 +238,9 @@ pub enum ReqResMessageKind {
   KeepAlive,
   Heartbeat([u8; 32]),
   Block([u8; 32]),
+  BlockRange([u8; 32]),
+  MissingTransactions([u8; 32]),
+  Transactions([u8; 32]),
 }
 
 impl ReqResMessageKind {
@@ -100,6 +259,21 @@ impl ReqResMessageKind {
         reader.read_exact(&mut genesis).ok()?;
         ReqResMessageKind::Block(genesis)
       }),
+      3 => Some({
+        let mut genesis = [0; 32];
+        reader.read_exact(&mut genesis).ok()?;
+        ReqResMessageKind::BlockRange(genesis)
+      }),
+      4 => Some({
+        let mut genesis = [0; 32];
+        reader.read_exact(&mut genesis).ok()?;
+        ReqResMessageKind::MissingTransactions(genesis)
+      }),
+      5 => Some({
+        let mut genesis = [0; 32];
+        reader.read_exact(&mut genesis).ok()?;
+        ReqResMessageKind::Transactions(genesis)
+      }),
       _ => None,
     }
   }
@@ -117,6 +291,21 @@ impl ReqResMessageKind {
         res.extend(genesis);
         res
       }
+      ReqResMessageKind::BlockRange(genesis) => {
+        let mut res = vec![3];
+        res.extend(genesis);
+        res
+      }
+      ReqResMessageKind::MissingTransactions(genesis) => {
+        let mut res = vec![4];
+        res.extend(genesis);
+        res
+      }
+      ReqResMessageKind::Transactions(genesis) => {
+        let mut res = vec![5];
+        res.extend(genesis);
+        res
+      }
     }
   }
 }
@@ -168,13 +357,41 @@ impl P2pMessageKind {
       P2pMessageKind::ReqRes(ReqResMessageKind::KeepAlive) |
       P2pMessageKind::Gossip(GossipMessageKind::CosignedBlock) => None,
       P2pMessageKind::ReqRes(
-        ReqResMessageKind::Heartbeat(genesis) | ReqResMessageKind::Block(genesis),
+        ReqResMessageKind::Heartbeat(genesis) |
+        ReqResMessageKind::Block(genesis) |
+        ReqResMessageKind::BlockRange(genesis) |
+        ReqResMessageKind::MissingTransactions(genesis) |
+        ReqResMessageKind::Transactions(genesis),
       ) |
       P2pMessageKind::Gossip(GossipMessageKind::Tributary(genesis)) => Some(*genesis),
     }
   }
 }
 
+impl P2pMessageKind {
+  /// The maximum size of a message of this kind, excluding the serialization of its kind.
+  ///
+  /// Peers who send messages exceeding this are penalized.
+  pub fn max_size(&self) -> usize {
+    match self {
+      P2pMessageKind::ReqRes(ReqResMessageKind::KeepAlive) => 0,
+      // The hash of our tip and the current time
+      P2pMessageKind::ReqRes(ReqResMessageKind::Heartbeat(_)) => 32 + 8,
+      P2pMessageKind::ReqRes(ReqResMessageKind::Block(_) | ReqResMessageKind::Transactions(_)) => {
+        MAX_LIBP2P_REQRES_MESSAGE_SIZE
+      }
+      P2pMessageKind::ReqRes(ReqResMessageKind::BlockRange(_)) => 8 + 4,
+      // Each order is prefixed by its length, as is the list of orders itself
+      P2pMessageKind::ReqRes(ReqResMessageKind::MissingTransactions(_)) => {
+        4 + (MAX_REQUESTED_ORDERS * (4 + MAX_ORDER_SIZE))
+      }
+      P2pMessageKind::Gossip(GossipMessageKind::Tributary(_)) => MAX_LIBP2P_GOSSIP_MESSAGE_SIZE,
+      // The network, block number, block hash, and signature
+      P2pMessageKind::Gossip(GossipMessageKind::CosignedBlock) => 1 + 8 + 32 + 64,
+    }
+  }
+}
+
 impl From<ReqResMessageKind> for P2pMessageKind {
   fn from(kind: ReqResMessageKind) -> P2pMessageKind {
     P2pMessageKind::ReqRes(kind)
@@ -206,6 +423,70 @@ pub struct HeartbeatBatch {
   pub timestamp: u64,
 }
 
+/// A request for a range of a Tributary's blocks, by number, with their commits.
+///
+/// This is responded to with a `HeartbeatBatch` of up to `BLOCKS_PER_BATCH` blocks.
+#[derive(Clone, Copy, Debug, Encode, Decode)]
+pub struct BlockRangeRequest {
+  pub start: u64,
+  pub count: u32,
+}
+
+impl BlockRangeRequest {
+  // Request the blocks after our tip
+  fn after<D: Db>(tributary: &tributary::TributaryReader<D, Transaction>) -> Self {
+    BlockRangeRequest {
+      start: tributary.block_number() + 1,
+      count: u32::try_from(BLOCKS_PER_BATCH).unwrap(),
+    }
+  }
+}
+
+/// A request for the transactions within a peer's mempool for the specified orders.
+///
+/// An order is the `TransactionKind::Signed` order of a transaction, which for the transactions of
+/// a signing protocol is its topic and attempt (such as the `SignId`). This allows filling in
+/// preprocesses and shares which were missed, without waiting for the protocol to be re-attempted.
+/// This is responded to with a `Vec<Vec<u8>>` of serialized transactions, if any were found.
+#[derive(Clone, Debug, Encode, Decode)]
+pub struct MissingTransactionsRequest {
+  pub orders: Vec<Vec<u8>>,
+}
+
+impl MissingTransactionsRequest {
+  // Request the transactions for the signing protocols present within recent blocks
+  fn recent<D: Db>(tributary: &tributary::TributaryReader<D, Transaction>) -> Self {
+    let mut orders = vec![];
+    let mut hash = tributary.tip();
+    for _ in 0 .. MISSING_TRANSACTIONS_WINDOW {
+      let Some(block) = tributary.block(&hash) else { break };
+      for tx in &block.transactions {
+        let tributary::Transaction::Application(tx) = tx else { continue };
+        if !matches!(
+          tx,
+          Transaction::DkgCommitments { .. } |
+            Transaction::DkgShares { .. } |
+            Transaction::DkgConfirmed { .. } |
+            Transaction::SubstrateSign(_) |
+            Transaction::Sign(_)
+        ) {
+          continue;
+        }
+        let TransactionKind::Signed(order, _) = tx.kind() else { continue };
+        if !orders.contains(&order) {
+          orders.push(order);
+        }
+      }
+      if orders.len() >= MAX_REQUESTED_ORDERS {
+        orders.truncate(MAX_REQUESTED_ORDERS);
+        break;
+      }
+      hash = block.parent();
+    }
+    MissingTransactionsRequest { orders }
+  }
+}
+
 #[async_trait]
 pub trait P2p: Send + Sync + Clone + fmt::Debug + TributaryP2p {
   type Id: Send + Sync + Clone + Copy + fmt::Debug;
@@ -216,6 +497,18 @@ pub trait P2p: Send + Sync + Clone + fmt::Debug + TributaryP2p {
   async fn send_raw(&self, to: Self::Id, msg: Vec<u8>);
   async fn broadcast_raw(&self, kind: P2pMessageKind, msg: Vec<u8>);
   async fn receive(&self) -> Message<Self>;
+  /// The amount of peers we're connected to for a network.
+  async fn peers(&self, network: ExternalNetworkId) -> usize;
+  /// Metrics on the connections made over each transport.
+  async fn transports(&self) -> HashMap<P2pTransport, TransportMetrics>;
+  /// Report the validity of a received Tributary gossip message, propagating it if valid.
+  ///
+  /// By default, this re-broadcasts valid messages.
+  async fn report_validity(&self, msg: Message<Self>, validity: MessageValidity) {
+    if validity == MessageValidity::Valid {
+      P2p::broadcast(self, msg.kind, msg.msg).await;
+    }
+  }
 
   async fn send(&self, to: Self::Id, kind: ReqResMessageKind, msg: Vec<u8>) {
     let mut actual_msg = kind.serialize();
@@ -245,6 +538,49 @@ pub trait P2p: Send + Sync + Clone + fmt::Debug + TributaryP2p {
   }
 }
 
+/// Prepare a message to be sent over the wire, compressing it if it's sufficiently large.
+pub(crate) fn compress(msg: Vec<u8>) -> Vec<u8> {
+  if msg.len() >= COMPRESSION_THRESHOLD {
+    // Only use the compressed message if compression actually reduced its size
+    if let Ok(compressed) = zstd::bulk::compress(&msg, zstd::DEFAULT_COMPRESSION_LEVEL) {
+      if compressed.len() < msg.len() {
+        let mut res = Vec::with_capacity(1 + compressed.len());
+        res.push(COMPRESSED);
+        res.extend(compressed);
+        return res;
+      }
+    }
+  }
+
+  let mut res = Vec::with_capacity(1 + msg.len());
+  res.push(UNCOMPRESSED);
+  res.extend(msg);
+  res
+}
+
+/// Read a message received over the wire, decompressing it if necessary.
+///
+/// Returns None if the message was malformed or exceeded the specified size once decompressed.
+pub(crate) fn decompress(msg: &[u8], max_size: usize) -> Option<Vec<u8>> {
+  let (prefix, msg) = msg.split_first()?;
+  let res = match *prefix {
+    UNCOMPRESSED => msg.to_vec(),
+    COMPRESSED => {
+      // Decompress at most one byte past the max size, so we don't perform an unbounded
+      // allocation for a message which decompresses to a massive size
+      let mut res = vec![];
+      zstd::stream::read::Decoder::with_buffer(msg)
+        .ok()?
+        .take(u64::try_from(max_size).unwrap() + 1)
+        .read_to_end(&mut res)
+        .ok()?;
+      res
+    }
+    _ => return None,
+  };
+  (res.len() <= max_size).then_some(res)
+}
+
 #[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
 struct RrCodec;
 #[async_trait]
@@ -304,8 +640,18 @@ impl RrCodecTrait for RrCodec {
 
 #[derive(NetworkBehaviour)]
 struct Behavior {
+  limits: LimitsBehavior,
   reqres: RrBehavior<RrCodec>,
   gossipsub: GsBehavior,
+  // Learns our observed addresses, which AutoNAT probes and DCUtR punches holes with
+  identify: IdentifyBehavior,
+  autonat: AutonatBehavior,
+  // Relays connections for peers behind NATs, if we're publicly reachable
+  relay: RelayBehavior,
+  // Listens via relays if we're behind a NAT
+  relay_client: RelayClientBehavior,
+  // Upgrades relayed connections to direct connections via hole punching
+  dcutr: DcutrBehavior,
 }
 
 #[allow(clippy::type_complexity)]
@@ -314,7 +660,10 @@ pub struct LibP2p {
   subscribe: Arc<Mutex<mpsc::UnboundedSender<(bool, ExternalValidatorSet, [u8; 32])>>>,
   send: Arc<Mutex<mpsc::UnboundedSender<(PeerId, Vec<u8>)>>>,
   broadcast: Arc<Mutex<mpsc::UnboundedSender<(P2pMessageKind, Vec<u8>)>>>,
+  validity: Arc<Mutex<mpsc::UnboundedSender<([u8; 32], MessageValidity)>>>,
   receive: Arc<Mutex<mpsc::UnboundedReceiver<Message<Self>>>>,
+  connected_peers: Arc<RwLock<HashMap<Multiaddr, HashSet<ExternalNetworkId>>>>,
+  transport_metrics: Arc<TransportMetricsCollector>,
 }
 impl fmt::Debug for LibP2p {
   fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -322,87 +671,348 @@ impl fmt::Debug for LibP2p {
   }
 }
 
+// The key a Tributary gossip message's pending validation is tracked under
+fn gossip_key(kind: GossipMessageKind, msg: &[u8]) -> [u8; 32] {
+  use blake2::{Digest, Blake2s256};
+  Blake2s256::digest([kind.serialize().as_slice(), msg].concat()).into()
+}
+
+fn yamux_config() -> yamux::Config {
+  let mut config = yamux::Config::default();
+  // 1 MiB default + max message size
+  config.set_max_buffer_size((1024 * 1024) + MAX_LIBP2P_MESSAGE_SIZE);
+  // 256 KiB default + max message size
+  config.set_receive_window_size(((256 * 1024) + MAX_LIBP2P_MESSAGE_SIZE).try_into().unwrap());
+  config
+}
+
+// The scoring of peers within a topic
+//
+// Tributaries are idle for long periods of time, so peers aren't penalized for failing to deliver
+// messages. They're solely penalized for delivering invalid messages, with the penalty growing
+// quadratically with the amount delivered and decaying by 1% every second.
+pub(crate) fn topic_score_params() -> TopicScoreParams {
+  TopicScoreParams {
+    topic_weight: 1.0,
+    time_in_mesh_weight: 0.01,
+    time_in_mesh_quantum: Duration::from_secs(1),
+    time_in_mesh_cap: 3600.0,
+    first_message_deliveries_weight: 0.1,
+    first_message_deliveries_cap: 100.0,
+    mesh_message_deliveries_weight: 0.0,
+    mesh_failure_penalty_weight: 0.0,
+    invalid_message_deliveries_weight: -10.0,
+    invalid_message_deliveries_decay: 0.99,
+    ..Default::default()
+  }
+}
+
+fn peer_score_params() -> PeerScoreParams {
+  PeerScoreParams {
+    // Bound how much good behavior can offset delivering invalid messages
+    topic_score_cap: 50.0,
+    // Connections via our onion service are forwarded by the local Tor daemon, so they share the
+    // loopback address
+    ip_colocation_factor_whitelist: HashSet::from([
+      IpAddr::V4(Ipv4Addr::LOCALHOST),
+      IpAddr::V6(Ipv6Addr::LOCALHOST),
+    ]),
+    ..Default::default()
+  }
+}
+
+pub(crate) fn gossipsub(key: &Keypair) -> GsBehavior {
+  let heartbeat_interval = tributary::tendermint::LATENCY_TIME / 2;
+  let heartbeats_per_block =
+    usize::try_from(tributary::tendermint::TARGET_BLOCK_TIME / heartbeat_interval).unwrap();
+
+  use blake2::{Digest, Blake2s256};
+  let config = ConfigBuilder::default()
+    .heartbeat_interval(Duration::from_millis(heartbeat_interval.into()))
+    .history_length(heartbeats_per_block * 2)
+    .history_gossip(heartbeats_per_block)
+    .max_transmit_size(MAX_LIBP2P_GOSSIP_MESSAGE_SIZE)
+    // We send KeepAlive after 80s
+    .idle_timeout(Duration::from_secs(85))
+    .validation_mode(ValidationMode::Strict)
+    // Messages are only propagated once we've validated them, letting us penalize peers
+    // who deliver invalid messages
+    .validate_messages()
+    // Uses a content based message ID to avoid duplicates as much as possible
+    .message_id_fn(|msg| {
+      MessageId::new(&Blake2s256::digest([msg.topic.as_str().as_bytes(), &msg.data].concat()))
+    })
+    // Re-defines for fast ID to prevent needing to convert into a Message to run
+    // message_id_fn
+    // This function is valid for both
+    .fast_message_id_fn(|msg| {
+      FastMessageId::new(&Blake2s256::digest([msg.topic.as_str().as_bytes(), &msg.data].concat()))
+    })
+    .build();
+  let mut gossipsub = GsBehavior::<IdentityTransform, AllowAllSubscriptionFilter>::new(
+    MessageAuthenticity::Signed(key.clone()),
+    config.unwrap(),
+  )
+  .unwrap();
+  gossipsub.with_peer_score(peer_score_params(), PeerScoreThresholds::default()).unwrap();
+
+  // Subscribe to the base topic
+  let topic = IdentTopic::new(LIBP2P_TOPIC);
+  gossipsub.set_topic_params(topic.clone(), topic_score_params()).unwrap();
+  gossipsub.subscribe(&topic).unwrap();
+
+  gossipsub
+}
+
+// Map the validity of a Tributary message to how Gossipsub should handle it
+//
+// Only invalid messages penalize the peer who delivered them. Messages which are merely duplicates,
+// or which the Tributary propagates by other means, are ignored so honest relayers aren't
+// penalized.
+pub(crate) fn acceptance(validity: MessageValidity) -> MessageAcceptance {
+  match validity {
+    MessageValidity::Valid => MessageAcceptance::Accept,
+    MessageValidity::Ignore => MessageAcceptance::Ignore,
+    MessageValidity::Invalid => MessageAcceptance::Reject,
+  }
+}
+
+pub(crate) fn topic_for_set(set: ExternalValidatorSet) -> IdentTopic {
+  IdentTopic::new(format!("{LIBP2P_TOPIC}-{}", hex::encode(set.encode())))
+}
+
+// Listen via peers we've directly dialed, acting as relays, until we're listening via
+// TARGET_RELAYS of them
+fn reserve_relays(
+  swarm: &mut Swarm<Behavior>,
+  candidates: &HashMap<PeerId, Multiaddr>,
+  relays: &mut HashMap<PeerId, ListenerId>,
+) {
+  for (peer, addr) in candidates {
+    if relays.len() >= TARGET_RELAYS {
+      break;
+    }
+    if relays.contains_key(peer) {
+      continue;
+    }
+    let circuit = addr.clone().with(Protocol::P2p(*peer)).with(Protocol::P2pCircuit);
+    match swarm.listen_on(circuit) {
+      Ok(listener) => {
+        log::info!("listening via relay {peer}");
+        relays.insert(*peer, listener);
+      }
+      Err(e) => log::warn!("couldn't listen via relay {peer}: {e:?}"),
+    }
+  }
+}
+
+// The QUIC address for a TCP address, which we prefer to dial as QUIC has a faster handshake and no
+// head-of-line blocking across streams
+//
+// Onion and relayed addresses are solely reachable over TCP.
+pub(crate) fn quic_addr(addr: &Multiaddr) -> Option<Multiaddr> {
+  let mut protocols = addr.iter();
+  let ip = protocols.next().filter(|ip| matches!(ip, Protocol::Ip4(_) | Protocol::Ip6(_)))?;
+  let Some(Protocol::Tcp(port)) = protocols.next() else { return None };
+
+  let mut res = Multiaddr::empty().with(ip).with(Protocol::Udp(port)).with(Protocol::QuicV1);
+  for protocol in protocols {
+    match protocol {
+      Protocol::P2p(_) => res.push(protocol),
+      _ => return None,
+    }
+  }
+  Some(res)
+}
+
+// The dials we have in progress
+#[derive(Default)]
+struct Dials {
+  // The address each dial is for, if it's over QUIC, and when it was started
+  pending: HashMap<ConnectionId, (Multiaddr, bool, Instant)>,
+  // The addresses we failed to dial over QUIC, which we now solely dial over TCP
+  quic_failed: HashSet<Multiaddr>,
+}
+
+impl Dials {
+  // Dial an address, over QUIC if possible, falling back to TCP if dialing over QUIC fails
+  fn dial(
+    &mut self,
+    swarm: &mut Swarm<Behavior>,
+    metrics: &TransportMetricsCollector,
+    addr: Multiaddr,
+  ) {
+    let quic = Some(&addr).filter(|addr| !self.quic_failed.contains(*addr)).and_then(quic_addr);
+    self.dial_via(swarm, metrics, addr, quic);
+  }
+
+  fn dial_via(
+    &mut self,
+    swarm: &mut Swarm<Behavior>,
+    metrics: &TransportMetricsCollector,
+    addr: Multiaddr,
+    quic: Option<Multiaddr>,
+  ) {
+    let is_quic = quic.is_some();
+    let opts = DialOpts::unknown_peer_id().address(quic.unwrap_or_else(|| addr.clone())).build();
+    let connection_id = opts.connection_id();
+    match swarm.dial(opts) {
+      Ok(()) => {
+        self.pending.insert(connection_id, (addr, is_quic, Instant::now()));
+      }
+      Err(e) => self.failed(swarm, metrics, addr, is_quic, &e),
+    }
+  }
+
+  fn failed(
+    &mut self,
+    swarm: &mut Swarm<Behavior>,
+    metrics: &TransportMetricsCollector,
+    addr: Multiaddr,
+    quic: bool,
+    e: &DialError,
+  ) {
+    let transport = if quic { P2pTransport::Quic } else { P2pTransport::of(&addr) };
+    metrics.update(transport, |metrics| metrics.failed_dials += 1);
+    log::warn!("dialing {addr} over {transport:?} failed: {e}");
+
+    // Only fall back if the transport itself failed, not if we hit a connection limit
+    if quic && matches!(e, DialError::Transport(_)) {
+      self.quic_failed.insert(addr.clone());
+      self.dial_via(swarm, metrics, addr, None);
+    }
+  }
+}
+
+// Transform the address of a validator's Serai node into the address of their coordinator, which is
+// presumed to be on the same host
+fn coordinator_addr(addr: &Multiaddr) -> Multiaddr {
+  let protocols = addr.iter().filter_map(|piece| match piece {
+    // Drop PeerIds from the Substrate P2p network
+    Protocol::P2p(_) => None,
+    // Use our own TCP port
+    Protocol::Tcp(_) => Some(Protocol::Tcp(PORT)),
+    // Onion services are expected to expose our port as well
+    Protocol::Onion3(onion) => Some(Protocol::Onion3(Onion3Addr::from((*onion.hash(), PORT)))),
+    other => Some(other),
+  });
+
+  let mut res = Multiaddr::empty();
+  for protocol in protocols {
+    res.push(protocol);
+  }
+  res
+}
+
+// The addresses the current validators for a network published on-chain for their coordinators
+async fn published_addrs(
+  serai: &Serai,
+  network: ExternalNetworkId,
+) -> Result<Vec<Multiaddr>, SeraiError> {
+  let serai = serai.as_of_latest_finalized_block().await?;
+  let serai = serai.validator_sets();
+  let mut res = vec![];
+  for (validator, _) in serai.participants(network.into()).await?.unwrap_or_default() {
+    let Some(addr) = serai.network_address(validator).await? else { continue };
+    match Multiaddr::try_from(addr) {
+      Ok(addr) => res.push(addr),
+      Err(e) => log::debug!("{network:?} validator published an invalid address: {e}"),
+    }
+  }
+  Ok(res)
+}
+
 impl LibP2p {
+  /// Create a new libp2p instance.
+  ///
+  /// If a Tor SOCKS5 proxy is specified, peers with onion addresses will be dialed through it, in
+  /// addition to peers with clearnet addresses. Receiving connections via an onion service is
+  /// done by having the local Tor daemon forward the onion service to our TCP listener.
+  ///
+  /// If AutoNAT finds we're behind a NAT, we listen via relays (fellow coordinators we've dialed),
+  /// with relayed connections upgraded to direct connections via hole punching where possible.
+  ///
+  /// We listen over both TCP and QUIC. Peers are dialed over QUIC when their address has a QUIC
+  /// form, falling back to TCP if that fails, with peers we failed to dial over QUIC solely dialed
+  /// over TCP from then on.
   #[allow(clippy::new_without_default)]
-  pub fn new(serai: Arc<Serai>) -> Self {
+  pub fn new(serai: Arc<Serai>, tor_proxy: Option<SocketAddr>) -> Self {
     log::info!("creating a libp2p instance");
 
     let throwaway_key_pair = Keypair::generate_ed25519();
 
-    let behavior = Behavior {
-      reqres: { RrBehavior::new([("/coordinator", ProtocolSupport::Full)], RrConfig::default()) },
-      gossipsub: {
-        let heartbeat_interval = tributary::tendermint::LATENCY_TIME / 2;
-        let heartbeats_per_block =
-          usize::try_from(tributary::tendermint::TARGET_BLOCK_TIME / heartbeat_interval).unwrap();
-
-        use blake2::{Digest, Blake2s256};
-        let config = ConfigBuilder::default()
-          .heartbeat_interval(Duration::from_millis(heartbeat_interval.into()))
-          .history_length(heartbeats_per_block * 2)
-          .history_gossip(heartbeats_per_block)
-          .max_transmit_size(MAX_LIBP2P_GOSSIP_MESSAGE_SIZE)
-          // We send KeepAlive after 80s
-          .idle_timeout(Duration::from_secs(85))
-          .validation_mode(ValidationMode::Strict)
-          // Uses a content based message ID to avoid duplicates as much as possible
-          .message_id_fn(|msg| {
-            MessageId::new(&Blake2s256::digest([msg.topic.as_str().as_bytes(), &msg.data].concat()))
-          })
-          // Re-defines for fast ID to prevent needing to convert into a Message to run
-          // message_id_fn
-          // This function is valid for both
-          .fast_message_id_fn(|msg| {
-            FastMessageId::new(&Blake2s256::digest(
-              [msg.topic.as_str().as_bytes(), &msg.data].concat(),
-            ))
-          })
-          .build();
-        let mut gossipsub = GsBehavior::<IdentityTransform, AllowAllSubscriptionFilter>::new(
-          MessageAuthenticity::Signed(throwaway_key_pair.clone()),
-          config.unwrap(),
+    let local_peer_id = throwaway_key_pair.public().to_peer_id();
+    let behavior = |key: &Keypair, relay_client| Behavior {
+      limits: LimitsBehavior::new(
+        ConnectionLimits::default()
+          .with_max_pending_incoming(Some(MAX_PENDING_CONNECTIONS))
+          .with_max_pending_outgoing(Some(MAX_PENDING_CONNECTIONS))
+          .with_max_established_incoming(Some(MAX_INCOMING_CONNECTIONS))
+          .with_max_established_per_peer(Some(MAX_CONNECTIONS_PER_PEER)),
+      ),
+      reqres: { RrBehavior::new([(LIBP2P_PROTOCOL, ProtocolSupport::Full)], RrConfig::default()) },
+      gossipsub: gossipsub(key),
+      identify: IdentifyBehavior::new(
+        IdentifyConfig::new("/serai/coordinator/2.0.0".to_string(), key.public())
+          // Inform peers of the addresses we listen on via relays as soon as we listen on them
+          .with_push_listen_addr_updates(true),
+      ),
+      autonat: AutonatBehavior::new(local_peer_id, AutonatConfig::default()),
+      relay: {
+        let per_ip = NonZeroU32::new(RELAY_REQUESTS_PER_IP_PER_MINUTE).unwrap();
+        let minute = Duration::from_secs(60);
+        RelayBehavior::new(
+          local_peer_id,
+          RelayConfig {
+            max_reservations: MAX_RELAY_RESERVATIONS,
+            max_reservations_per_peer: 1,
+            max_circuits: MAX_RELAY_CIRCUITS,
+            max_circuits_per_peer: usize::try_from(MAX_CONNECTIONS_PER_PEER).unwrap(),
+            ..RelayConfig::default()
+          }
+          .reservation_rate_per_ip(per_ip, minute)
+          .circuit_src_per_ip(per_ip, minute),
         )
-        .unwrap();
-
-        // Subscribe to the base topic
-        let topic = IdentTopic::new(LIBP2P_TOPIC);
-        gossipsub.subscribe(&topic).unwrap();
-
-        gossipsub
       },
+      relay_client,
+      dcutr: DcutrBehavior::new(local_peer_id),
     };
 
     // Uses noise for authentication, yamux for multiplexing
     // TODO: Do we want to add a custom authentication protocol to only accept connections from
     // fellow validators? Doing so would reduce the potential for spam
-    // TODO: Relay client?
     let mut swarm = SwarmBuilder::with_existing_identity(throwaway_key_pair)
       .with_tokio()
-      .with_tcp(TcpConfig::default().nodelay(true), noise::Config::new, || {
-        let mut config = yamux::Config::default();
-        // 1 MiB default + max message size
-        config.set_max_buffer_size((1024 * 1024) + MAX_LIBP2P_MESSAGE_SIZE);
-        // 256 KiB default + max message size
-        config
-          .set_receive_window_size(((256 * 1024) + MAX_LIBP2P_MESSAGE_SIZE).try_into().unwrap());
-        config
+      .with_tcp(TcpConfig::default().nodelay(true), noise::Config::new, yamux_config)
+      .unwrap()
+      .with_quic()
+      // Onion addresses are dialed via Tor, if configured
+      .with_other_transport(|key| {
+        Ok::<_, noise::Error>(
+          OptionalTransport::from(tor_proxy.map(TorTransport::new))
+            .upgrade(Version::V1)
+            .authenticate(noise::Config::new(key)?)
+            .multiplex(yamux_config()),
+        )
       })
       .unwrap()
-      .with_behaviour(|_| behavior)
+      .with_relay_client(noise::Config::new, yamux_config)
+      .unwrap()
+      .with_behaviour(|key, relay_client| behavior(key, relay_client))
       .unwrap()
       .build();
-    const PORT: u16 = 30563; // 5132 ^ (('c' << 8) | 'o')
     swarm.listen_on(format!("/ip4/0.0.0.0/tcp/{PORT}").parse().unwrap()).unwrap();
+    // If UDP is unavailable, we're still reachable over TCP
+    if let Err(e) = swarm.listen_on(format!("/ip4/0.0.0.0/udp/{PORT}/quic-v1").parse().unwrap()) {
+      log::warn!("couldn't listen over QUIC: {e:?}");
+    }
 
     let (send_send, mut send_recv) = mpsc::unbounded_channel();
     let (broadcast_send, mut broadcast_recv) = mpsc::unbounded_channel();
+    let (validity_send, mut validity_recv) = mpsc::unbounded_channel();
     let (receive_send, receive_recv) = mpsc::unbounded_channel();
     let (subscribe_send, mut subscribe_recv) = mpsc::unbounded_channel();
 
-    fn topic_for_set(set: ExternalValidatorSet) -> IdentTopic {
-      IdentTopic::new(format!("{LIBP2P_TOPIC}-{}", hex::encode(set.encode())))
-    }
-
     // TODO: If a network has less than TARGET_PEERS, this will cause retries ad infinitum
     const TARGET_PEERS: usize = 5;
 
@@ -411,6 +1021,8 @@ impl LibP2p {
     // The peers we're currently connected to, and the networks associated with them
     let connected_peers =
       Arc::new(RwLock::new(HashMap::<Multiaddr, HashSet<ExternalNetworkId>>::new()));
+    let transport_metrics = Arc::new(TransportMetricsCollector::default());
+    let latencies = Arc::new(Latencies::default());
 
     // Find and connect to peers
     let (connect_to_network_send, mut connect_to_network_recv) =
@@ -419,6 +1031,7 @@ impl LibP2p {
     tokio::spawn({
       let dialing_peers = dialing_peers.clone();
       let connected_peers = connected_peers.clone();
+      let latencies = latencies.clone();
 
       let connect_to_network_send = connect_to_network_send.clone();
       async move {
@@ -429,22 +1042,7 @@ impl LibP2p {
             let to_dial_send = to_dial_send.clone();
             let connect_to_network_send = connect_to_network_send.clone();
             async move {
-              log::info!("found peer from substrate: {addr}");
-
-              let protocols = addr.iter().filter_map(|piece| match piece {
-                // Drop PeerIds from the Substrate P2p network
-                Protocol::P2p(_) => None,
-                // Use our own TCP port
-                Protocol::Tcp(_) => Some(Protocol::Tcp(PORT)),
-                other => Some(other),
-              });
-
-              let mut new_addr = Multiaddr::empty();
-              for protocol in protocols {
-                new_addr.push(protocol);
-              }
-              let addr = new_addr;
-              log::debug!("transformed found peer: {addr}");
+              log::info!("found peer: {addr}");
 
               let (is_fresh_dial, nets) = {
                 let mut dialing_peers = dialing_peers.write().await;
@@ -510,33 +1108,47 @@ impl LibP2p {
             connect_to_network_networks.insert(network);
           }
           for network in connect_to_network_networks {
-            if let Ok(mut nodes) = serai.p2p_validators(network.into()).await {
-              // If there's an insufficient amount of nodes known, connect to all yet add it
-              // back and break
-              if nodes.len() < TARGET_PEERS {
-                log::warn!(
-                  "insufficient amount of P2P nodes known for {:?}: {}",
-                  network,
-                  nodes.len()
-                );
-                // Retry this later
-                connect_to_network_send.send(network).unwrap();
-                for node in nodes {
-                  connect(network, node).await;
-                }
-                continue;
+            // Prefer the addresses validators published for their coordinators, yet also use the
+            // addresses of their Serai nodes so validators who haven't published one are found
+            let mut nodes = match published_addrs(&serai, network).await {
+              Ok(nodes) => nodes,
+              Err(e) => {
+                log::warn!("couldn't get the published addresses of {network:?} validators: {e}");
+                vec![]
               }
+            };
+            match serai.p2p_validators(network.into()).await {
+              Ok(found) => nodes.extend(found.iter().map(coordinator_addr)),
+              Err(e) => log::warn!("couldn't get the {network:?} validators' Serai nodes: {e}"),
+            }
+            let mut unique = HashSet::new();
+            nodes.retain(|node| unique.insert(node.clone()));
 
-              // Randomly select up to 150% of the TARGET_PEERS
-              for _ in 0 .. ((3 * TARGET_PEERS) / 2) {
-                if !nodes.is_empty() {
-                  let to_connect = nodes.swap_remove(
-                    usize::try_from(OsRng.next_u64() % u64::try_from(nodes.len()).unwrap())
-                      .unwrap(),
-                  );
-                  connect(network, to_connect).await;
-                }
+            // Onion addresses are only dialable if we have a Tor proxy
+            if tor_proxy.is_none() {
+              nodes.retain(|node| !tor::is_onion(node));
+            }
+
+            // If there's an insufficient amount of nodes known, connect to all yet add it
+            // back and break
+            if nodes.len() < TARGET_PEERS {
+              log::warn!(
+                "insufficient amount of P2P nodes known for {:?}: {}",
+                network,
+                nodes.len()
+              );
+              // Retry this later
+              connect_to_network_send.send(network).unwrap();
+              for node in nodes {
+                connect(network, node).await;
               }
+              continue;
+            }
+
+            // Select up to 150% of the TARGET_PEERS, preferring those with the lowest latency as
+            // high latency would harm consensus timing
+            for to_connect in latencies.rank(nodes).into_iter().take((3 * TARGET_PEERS) / 2) {
+              connect(network, to_connect).await;
             }
           }
           // Sleep 60 seconds before moving to the next iteration
@@ -549,10 +1161,23 @@ impl LibP2p {
     tokio::spawn({
       let mut time_of_last_p2p_message = Instant::now();
 
+      let transport_metrics = transport_metrics.clone();
       async move {
         let connected_peers = connected_peers.clone();
 
         let mut set_for_genesis = HashMap::new();
+        // Tributary messages awaiting validation by their Tributary, before we propagate them
+        let mut pending_validation = HashMap::new();
+
+        // If we're behind a NAT, per AutoNAT
+        let mut behind_nat = false;
+        // The peers we've directly dialed, who may act as relays for us, and their addresses
+        let mut relay_candidates = HashMap::new();
+        // The relays we're listening via
+        let mut relays = HashMap::new();
+        let mut dials = Dials::default();
+        // The address each established connection is tracked under, and its transport
+        let mut connections = HashMap::new();
         loop {
           let time_since_last = Instant::now().duration_since(time_of_last_p2p_message);
           tokio::select! {
@@ -567,14 +1192,34 @@ impl LibP2p {
                 log::info!("subscribing to p2p messages for {set:?}");
                 connect_to_network_send.send(set.network).unwrap();
                 set_for_genesis.insert(genesis, set);
-                swarm.behaviour_mut().gossipsub.subscribe(&topic).unwrap();
+                let gossipsub = &mut swarm.behaviour_mut().gossipsub;
+                gossipsub.set_topic_params(topic.clone(), topic_score_params()).unwrap();
+                gossipsub.subscribe(&topic).unwrap();
               } else {
                 log::info!("unsubscribing to p2p messages for {set:?}");
                 set_for_genesis.remove(&genesis);
+                // The Tributary will no longer validate these
+                pending_validation.retain(|_, (msg_genesis, _, _)| *msg_genesis != genesis);
                 swarm.behaviour_mut().gossipsub.unsubscribe(&topic).unwrap();
               }
             }
 
+            // Report the validity of Tributary messages, propagating them if valid
+            validity = validity_recv.recv() => {
+              let (key, validity): ([u8; 32], MessageValidity) =
+                validity.expect("validity_recv closed. are we shutting down?");
+              let Some((_, message_id, source)) = pending_validation.remove(&key) else {
+                continue;
+              };
+              if let Err(e) = swarm.behaviour_mut().gossipsub.report_message_validation_result(
+                &message_id,
+                &source,
+                acceptance(validity),
+              ) {
+                log::warn!("couldn't report the validity of a p2p message: {e:?}");
+              }
+            }
+
             msg = send_recv.recv() => {
               let (peer, msg): (PeerId, Vec<u8>) =
                 msg.expect("send_recv closed. are we shutting down?");
@@ -640,9 +1285,34 @@ impl LibP2p {
                   }
 
                   let addr = endpoint.get_remote_address();
+                  let transport = P2pTransport::of(addr);
+                  transport_metrics.update(transport, |metrics| {
+                    metrics.connections += 1;
+                    metrics.established += 1;
+                  });
+                  // Connections we dialed over QUIC are tracked under the TCP address we were told
+                  // to dial
+                  let tracked = match dials.pending.remove(&connection_id) {
+                    Some((tracked, _, started)) => {
+                      latencies.record(tracked.clone(), started.elapsed());
+                      tracked
+                    }
+                    None => addr.clone(),
+                  };
+                  connections.insert(connection_id, (tracked.clone(), transport));
+
+                  // Peers we've dialed directly, and not via Tor, are able to act as our relays
+                  let relayed = addr.iter().any(|protocol| protocol == Protocol::P2pCircuit);
+                  if endpoint.is_dialer() && (!relayed) && (!tor::is_onion(addr)) {
+                    relay_candidates.insert(peer_id, addr.clone());
+                    if behind_nat {
+                      reserve_relays(&mut swarm, &relay_candidates, &mut relays);
+                    }
+                  }
+
                   let nets = {
                     let mut dialing_peers = dialing_peers.write().await;
-                    if let Some(nets) = dialing_peers.remove(addr) {
+                    if let Some(nets) = dialing_peers.remove(&tracked) {
                       nets
                     } else {
                       log::debug!("connected to a peer who we didn't have within dialing");
@@ -651,7 +1321,7 @@ impl LibP2p {
                   };
                   {
                     let mut connected_peers = connected_peers.write().await;
-                    connected_peers.insert(addr.clone(), nets);
+                    connected_peers.insert(tracked, nets);
 
                     log::debug!(
                       "connection established to peer {} in connection ID {}, connected peers: {}",
@@ -661,9 +1331,31 @@ impl LibP2p {
                     );
                   }
                 }
-                Some(SwarmEvent::ConnectionClosed { peer_id, endpoint, .. }) => {
+                Some(SwarmEvent::OutgoingConnectionError { connection_id, error, .. }) => {
+                  // Dials not made by us, such as those for hole punching, are left to their
+                  // behaviors
+                  let Some((addr, quic, _)) = dials.pending.remove(&connection_id) else {
+                    continue;
+                  };
+                  dials.failed(&mut swarm, &transport_metrics, addr, quic, &error);
+                }
+                Some(SwarmEvent::ConnectionClosed {
+                  peer_id,
+                  connection_id,
+                  num_established,
+                  ..
+                }) => {
+                  if num_established == 0 {
+                    relay_candidates.remove(&peer_id);
+                  }
+
+                  let Some((addr, transport)) = connections.remove(&connection_id) else {
+                    continue;
+                  };
+                  transport_metrics.update(transport, |metrics| metrics.connections -= 1);
+
                   let mut connected_peers = connected_peers.write().await;
-                  let Some(nets) = connected_peers.remove(endpoint.get_remote_address()) else {
+                  let Some(nets) = connected_peers.remove(&addr) else {
                     log::debug!("closed connection to peer which wasn't in connected_peers");
                     continue;
                   };
@@ -694,6 +1386,34 @@ impl LibP2p {
                     connected_peers.len(),
                   );
                 }
+                Some(SwarmEvent::Behaviour(BehaviorEvent::Autonat(
+                  AutonatEvent::StatusChanged { new, .. },
+                ))) => {
+                  log::info!("NAT status is now {new:?}");
+                  behind_nat = matches!(new, NatStatus::Private);
+                  if behind_nat {
+                    reserve_relays(&mut swarm, &relay_candidates, &mut relays);
+                  } else {
+                    // We're publicly reachable (or unsure), so stop listening via relays
+                    for (_, listener) in relays.drain() {
+                      swarm.remove_listener(listener);
+                    }
+                  }
+                }
+                Some(SwarmEvent::ListenerClosed { listener_id, .. }) => {
+                  // If this was a relay, replace it
+                  let relays_len = relays.len();
+                  relays.retain(|_, listener| *listener != listener_id);
+                  if behind_nat && (relays.len() != relays_len) {
+                    reserve_relays(&mut swarm, &relay_candidates, &mut relays);
+                  }
+                }
+                Some(SwarmEvent::Behaviour(BehaviorEvent::RelayClient(event))) => {
+                  log::debug!("relay client event: {event:?}");
+                }
+                Some(SwarmEvent::Behaviour(BehaviorEvent::Dcutr(event))) => {
+                  log::debug!("hole punching event: {event:?}");
+                }
                 Some(SwarmEvent::Behaviour(BehaviorEvent::Reqres(
                   RrEvent::Message { peer, message },
                 ))) => {
@@ -702,8 +1422,16 @@ impl LibP2p {
                     RrMessage::Response { response, .. } => response,
                   };
 
-                  let mut msg_ref = message.as_slice();
-                  let Some(kind) = ReqResMessageKind::read(&mut msg_ref) else { continue };
+                  let message = decompress(&message, MAX_LIBP2P_REQRES_MESSAGE_SIZE);
+                  let mut msg_ref = message.as_deref().unwrap_or(&[]);
+                  let kind = ReqResMessageKind::read(&mut msg_ref)
+                    .filter(|kind| msg_ref.len() <= P2pMessageKind::ReqRes(*kind).max_size());
+                  // Disconnect from peers who send malformed or oversized messages
+                  let Some(kind) = kind else {
+                    log::warn!("peer {peer} sent an invalid request/response, disconnecting");
+                    let _ = swarm.disconnect_peer_id(peer);
+                    continue;
+                  };
                   let message = Message {
                     sender: peer,
                     kind: P2pMessageKind::ReqRes(kind),
@@ -712,10 +1440,61 @@ impl LibP2p {
                   receive_send.send(message).expect("receive_send closed. are we shutting down?");
                 }
                 Some(SwarmEvent::Behaviour(BehaviorEvent::Gossipsub(
-                  GsEvent::Message { propagation_source, message, .. },
+                  GsEvent::Message { propagation_source, message_id, message },
                 ))) => {
-                  let mut msg_ref = message.data.as_slice();
-                  let Some(kind) = GossipMessageKind::read(&mut msg_ref) else { continue };
+                  let data = decompress(&message.data, MAX_LIBP2P_GOSSIP_MESSAGE_SIZE);
+                  let mut msg_ref = data.as_deref().unwrap_or(&[]);
+                  let kind = GossipMessageKind::read(&mut msg_ref)
+                    .filter(|kind| msg_ref.len() <= P2pMessageKind::Gossip(*kind).max_size());
+
+                  // Messages must be well-formed, within the size limit for their kind, and sent
+                  // to the topic for what they're for
+                  // Rejected messages penalize the peer's score
+                  let acceptance = match kind {
+                    None => MessageAcceptance::Reject,
+                    Some(GossipMessageKind::Tributary(genesis)) => {
+                      match set_for_genesis.get(&genesis) {
+                        // The Tributary itself decides the validity of these, as reported via
+                        // `report_validity`
+                        Some(set) if topic_for_set(*set).hash() == message.topic => {
+                          pending_validation.insert(
+                            gossip_key(GossipMessageKind::Tributary(genesis), msg_ref),
+                            (genesis, message_id, propagation_source),
+                          );
+                          receive_send
+                            .send(Message {
+                              sender: propagation_source,
+                              kind: P2pMessageKind::Gossip(GossipMessageKind::Tributary(genesis)),
+                              msg: msg_ref.to_vec(),
+                            })
+                            .expect("receive_send closed. are we shutting down?");
+                          continue;
+                        }
+                        Some(_) => MessageAcceptance::Reject,
+                        // We may have unsubscribed after this was received
+                        None => MessageAcceptance::Ignore,
+                      }
+                    }
+                    Some(GossipMessageKind::CosignedBlock) => {
+                      let mut cosign = msg_ref;
+                      let well_formed = CosignedBlock::deserialize_reader(&mut cosign).is_ok();
+                      if well_formed && (IdentTopic::new(LIBP2P_TOPIC).hash() == message.topic) {
+                        MessageAcceptance::Accept
+                      } else {
+                        MessageAcceptance::Reject
+                      }
+                    }
+                  };
+                  let accepted = acceptance == MessageAcceptance::Accept;
+                  if let Err(e) = swarm.behaviour_mut().gossipsub.report_message_validation_result(
+                    &message_id,
+                    &propagation_source,
+                    acceptance,
+                  ) {
+                    log::warn!("couldn't report the validity of a p2p message: {e:?}");
+                  }
+                  let (true, Some(kind)) = (accepted, kind) else { continue };
+
                   let message = Message {
                     sender: propagation_source,
                     kind: P2pMessageKind::Gossip(kind),
@@ -740,9 +1519,7 @@ impl LibP2p {
                 continue;
               }
 
-              if let Err(e) = swarm.dial(addr) {
-                log::warn!("dialing peer failed: {e:?}");
-              }
+              dials.dial(&mut swarm, &transport_metrics, addr);
             }
 
             // If it's been >80s since we've published a message, publish a KeepAlive since we're
@@ -758,7 +1535,7 @@ impl LibP2p {
                 swarm
                   .behaviour_mut()
                   .reqres
-                  .send_request(&peer_id, ReqResMessageKind::KeepAlive.serialize());
+                  .send_request(&peer_id, compress(ReqResMessageKind::KeepAlive.serialize()));
               }
             }
           }
@@ -770,7 +1547,10 @@ impl LibP2p {
       subscribe: Arc::new(Mutex::new(subscribe_send)),
       send: Arc::new(Mutex::new(send_send)),
       broadcast: Arc::new(Mutex::new(broadcast_send)),
+      validity: Arc::new(Mutex::new(validity_send)),
       receive: Arc::new(Mutex::new(receive_recv)),
+      connected_peers,
+      transport_metrics,
     }
   }
 }
@@ -798,7 +1578,12 @@ impl P2p for LibP2p {
   }
 
   async fn send_raw(&self, peer: Self::Id, msg: Vec<u8>) {
-    self.send.lock().await.send((peer, msg)).expect("send_send closed. are we shutting down?");
+    self
+      .send
+      .lock()
+      .await
+      .send((peer, compress(msg)))
+      .expect("send_send closed. are we shutting down?");
   }
 
   async fn broadcast_raw(&self, kind: P2pMessageKind, msg: Vec<u8>) {
@@ -806,7 +1591,7 @@ impl P2p for LibP2p {
       .broadcast
       .lock()
       .await
-      .send((kind, msg))
+      .send((kind, compress(msg)))
       .expect("broadcast_send closed. are we shutting down?");
   }
 
@@ -815,6 +1600,25 @@ impl P2p for LibP2p {
   async fn receive(&self) -> Message<Self> {
     self.receive.lock().await.recv().await.expect("receive_recv closed. are we shutting down?")
   }
+
+  async fn peers(&self, network: ExternalNetworkId) -> usize {
+    self.connected_peers.read().await.values().filter(|nets| nets.contains(&network)).count()
+  }
+
+  async fn transports(&self) -> HashMap<P2pTransport, TransportMetrics> {
+    self.transport_metrics.metrics()
+  }
+
+  async fn report_validity(&self, msg: Message<Self>, validity: MessageValidity) {
+    // Gossipsub propagates the message once accepted, so this doesn't re-broadcast it
+    let P2pMessageKind::Gossip(kind) = msg.kind else { return };
+    self
+      .validity
+      .lock()
+      .await
+      .send((gossip_key(kind, &msg.msg), validity))
+      .expect("validity_send closed. are we shutting down?");
+  }
 }
 
 #[async_trait]
@@ -824,7 +1628,8 @@ impl TributaryP2p for LibP2p {
   }
 }
 
-pub async fn heartbeat_tributaries_task<D: Db, P: P2p>(
+pub async fn heartbeat_tributaries_task<D: Db, P: P2p, C: Clock>(
+  clock: C,
   p2p: P,
   mut tributary_event: broadcast::Receiver<TributaryEvent<D, P>>,
 ) {
@@ -836,7 +1641,12 @@ pub async fn heartbeat_tributaries_task<D: Db, P: P2p>(
     loop {
       match tributary_event.try_recv() {
         Ok(TributaryEvent::NewTributary(ActiveTributary { spec, tributary })) => {
-          readers.insert(spec.set(), tributary.reader());
+          // Request the blocks after our tip, in case we're joining late or recovering
+          let reader = tributary.reader();
+          let request = BlockRangeRequest::after(&reader);
+          P2p::broadcast(&p2p, ReqResMessageKind::BlockRange(spec.genesis()), request.encode())
+            .await;
+          readers.insert(spec.set(), reader);
         }
         Ok(TributaryEvent::TributaryRetired(set)) => {
           readers.remove(&set);
@@ -851,28 +1661,36 @@ pub async fn heartbeat_tributaries_task<D: Db, P: P2p>(
 
     for tributary in readers.values() {
       let tip = tributary.tip();
-      let block_time =
-        SystemTime::UNIX_EPOCH + Duration::from_secs(tributary.time_of_block(&tip).unwrap_or(0));
+      let block_time = Duration::from_secs(tributary.time_of_block(&tip).unwrap_or(0));
 
       // Only trigger syncing if the block is more than a minute behind
-      if SystemTime::now() > (block_time + Duration::from_secs(60)) {
+      if clock.now() > (block_time + Duration::from_secs(60)) {
         log::warn!("last known tributary block was over a minute ago");
         let mut msg = tip.to_vec();
-        let time: u64 = SystemTime::now()
-          .duration_since(SystemTime::UNIX_EPOCH)
-          .expect("system clock is wrong")
-          .as_secs();
-        msg.extend(time.to_le_bytes());
+        msg.extend(clock.unix_time().to_le_bytes());
         P2p::broadcast(&p2p, ReqResMessageKind::Heartbeat(tributary.genesis()), msg).await;
+      } else {
+        // If we're synced, request any transactions for recent signing protocols which we may
+        // have missed
+        let request = MissingTransactionsRequest::recent(tributary);
+        if !request.orders.is_empty() {
+          P2p::broadcast(
+            &p2p,
+            ReqResMessageKind::MissingTransactions(tributary.genesis()),
+            request.encode(),
+          )
+          .await;
+        }
       }
     }
 
     // Only check once every 10 blocks of time
-    sleep(ten_blocks_of_time).await;
+    clock.sleep(ten_blocks_of_time).await;
   }
 }
 
-pub async fn handle_p2p_task<D: Db, P: P2p>(
+pub async fn handle_p2p_task<D: Db, P: P2p, C: Clock>(
+  clock: C,
   p2p: P,
   cosign_channel: mpsc::UnboundedSender<CosignedBlock>,
   mut tributary_event: broadcast::Receiver<TributaryEvent<D, P>>,
@@ -900,6 +1718,7 @@ pub async fn handle_p2p_task<D: Db, P: P2p>(
             // Per-Tributary P2P message handler
             tokio::spawn({
               let p2p = p2p.clone();
+              let clock = clock.clone();
               async move {
                 loop {
                   let Some(msg) = recv.recv().await else {
@@ -922,13 +1741,7 @@ pub async fn handle_p2p_task<D: Db, P: P2p>(
                       let msg_time = u64::from_le_bytes(msg.msg[32 .. 40].try_into().expect(
                         "length-checked heartbeat message didn't have 8 bytes for the u64",
                       ));
-                      if SystemTime::now()
-                        .duration_since(SystemTime::UNIX_EPOCH)
-                        .expect("system clock is wrong")
-                        .as_secs()
-                        .saturating_sub(msg_time) >
-                        10
-                      {
+                      if clock.unix_time().saturating_sub(msg_time) > 10 {
                         continue;
                       }
 
@@ -978,6 +1791,10 @@ pub async fn handle_p2p_task<D: Db, P: P2p>(
                         continue;
                       };
 
+                      // If a full batch advances our chain, the sender likely has further blocks
+                      let full = batch.blocks.len() >= BLOCKS_PER_BATCH;
+                      let mut advanced = false;
+
                       // sync blocks
                       for bc in batch.blocks {
                         // TODO: why do we use ReadWrite instead of Encode/Decode for blocks?
@@ -993,15 +1810,120 @@ pub async fn handle_p2p_task<D: Db, P: P2p>(
                           msg.sender,
                           res
                         );
+                        advanced |= res;
+                      }
+
+                      if full && advanced {
+                        let request = BlockRangeRequest::after(&tributary.tributary.reader());
+                        p2p
+                          .send(
+                            msg.sender,
+                            ReqResMessageKind::BlockRange(genesis),
+                            request.encode(),
+                          )
+                          .await;
+                      }
+                    }
+
+                    P2pMessageKind::ReqRes(ReqResMessageKind::BlockRange(msg_genesis)) => {
+                      assert_eq!(msg_genesis, genesis);
+                      let Ok(request) = BlockRangeRequest::decode(&mut msg.msg.as_ref()) else {
+                        log::error!("received invalidly serialized BlockRangeRequest");
+                        continue;
+                      };
+
+                      let reader = tributary.tributary.reader();
+                      let timestamp = clock.unix_time();
+                      let p2p = p2p.clone();
+                      // Spawn a dedicated task as this may require loading large amounts of data
+                      // from disk
+                      tokio::spawn(async move {
+                        let count = usize::try_from(request.count)
+                          .unwrap_or(BLOCKS_PER_BATCH)
+                          .min(BLOCKS_PER_BATCH);
+                        let mut blocks = vec![];
+                        let mut number = request.start;
+                        while blocks.len() < count {
+                          let Some(hash) = reader.block_hash(number) else { break };
+                          blocks.push(BlockCommit {
+                            block: reader.block(&hash).unwrap().serialize(),
+                            commit: reader.commit(&hash).unwrap(),
+                          });
+                          number += 1;
+                        }
+                        if blocks.is_empty() {
+                          return;
+                        }
+
+                        let batch = HeartbeatBatch { blocks, timestamp };
+                        p2p
+                          .send(msg.sender, ReqResMessageKind::Block(genesis), batch.encode())
+                          .await;
+                      });
+                    }
+
+                    P2pMessageKind::ReqRes(ReqResMessageKind::MissingTransactions(msg_genesis)) => {
+                      assert_eq!(msg_genesis, genesis);
+                      let Ok(request) = MissingTransactionsRequest::decode(&mut msg.msg.as_ref())
+                      else {
+                        log::error!("received invalidly serialized MissingTransactionsRequest");
+                        continue;
+                      };
+                      if request.orders.len() > MAX_REQUESTED_ORDERS {
+                        log::error!("received MissingTransactionsRequest with too many orders");
+                        continue;
+                      }
+
+                      // The response is sent prefixed by its kind, and then by whether it was
+                      // compressed, all of which must fit within the max message size
+                      let kind = ReqResMessageKind::Transactions(genesis);
+                      let budget = P2pMessageKind::ReqRes(kind)
+                        .max_size()
+                        .min(MAX_LIBP2P_REQRES_MESSAGE_SIZE - kind.serialize().len() - 1);
+                      let mut txs = vec![];
+                      for tx in tributary.tributary.mempool_transactions().await {
+                        let TransactionKind::Signed(order, _) = tx.kind() else { continue };
+                        if !request.orders.contains(&order) {
+                          continue;
+                        }
+                        // Budget against the encoding, which includes the length prefixes
+                        txs.push(tx.serialize());
+                        if txs.encoded_size() > budget {
+                          txs.pop();
+                          break;
+                        }
+                      }
+                      if txs.is_empty() {
+                        continue;
+                      }
+
+                      p2p.send(msg.sender, kind, txs.encode()).await;
+                    }
+
+                    P2pMessageKind::ReqRes(ReqResMessageKind::Transactions(msg_genesis)) => {
+                      assert_eq!(msg_genesis, genesis);
+                      let Ok(txs) = Vec::<Vec<u8>>::decode(&mut msg.msg.as_ref()) else {
+                        log::error!("received invalidly serialized Transactions message");
+                        continue;
+                      };
+                      for tx in txs {
+                        let Ok(tx) = Transaction::read(&mut tx.as_slice()) else {
+                          log::error!("received Transactions message with an invalid transaction");
+                          break;
+                        };
+                        let res = tributary.tributary.add_received_transaction(tx).await;
+                        log::debug!(
+                          "received requested transaction from {:?}, added: {res:?}",
+                          msg.sender
+                        );
                       }
                     }
 
                     P2pMessageKind::Gossip(GossipMessageKind::Tributary(msg_genesis)) => {
                       assert_eq!(msg_genesis, genesis);
                       log::trace!("handling message for tributary {:?}", spec_set);
-                      if tributary.tributary.handle_message(&msg.msg).await {
-                        P2p::broadcast(&p2p, msg.kind, msg.msg).await;
-                      }
+                      let validity = tributary.tributary.handle_message(&msg.msg).await;
+                      p2p.report_validity(msg, validity).await;
                     }
 
                     P2pMessageKind::Gossip(GossipMessageKind::CosignedBlock) => unreachable!(),
@@ -1027,7 +1949,11 @@ pub async fn handle_p2p_task<D: Db, P: P2p>(
       P2pMessageKind::ReqRes(ReqResMessageKind::KeepAlive) => {}
       P2pMessageKind::Gossip(GossipMessageKind::Tributary(genesis)) |
       P2pMessageKind::ReqRes(
-        ReqResMessageKind::Heartbeat(genesis) | ReqResMessageKind::Block(genesis),
+        ReqResMessageKind::Heartbeat(genesis) |
+        ReqResMessageKind::Block(genesis) |
+        ReqResMessageKind::BlockRange(genesis) |
+        ReqResMessageKind::MissingTransactions(genesis) |
+        ReqResMessageKind::Transactions(genesis),
       ) => {
         if let Some(channel) = channels.read().await.get(&genesis) {
           channel.send(msg).unwrap();
@@ -1,6 +1,6 @@
-use std::sync::Arc;
+use std::{sync::Arc, collections::HashMap};
 
-use serai_client::primitives::ExternalNetworkId;
+use serai_client::primitives::{ExternalNetworkId, EXTERNAL_NETWORKS};
 use processor_messages::{ProcessorMessage, CoordinatorMessage};
 
 use message_queue::{Service, Metadata, client::MessageQueue};
@@ -8,6 +8,9 @@ use message_queue::{Service, Metadata, client::MessageQueue};
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct Message {
   pub id: u64,
+  // The sequence number our processor sealed this message with, which is contiguous across the
+  // messages our processor sent, unlike the IDs of the messages we receive
+  pub sequence: u64,
   pub network: ExternalNetworkId,
   pub msg: ProcessorMessage,
 }
@@ -29,18 +32,73 @@ impl Processors for Arc<MessageQueue> {
     self.queue(metadata, msg).await;
   }
   async fn recv(&self, network: ExternalNetworkId) -> Message {
-    let msg = self.next(Service::Processor(network)).await;
-    assert_eq!(msg.from, Service::Processor(network));
+    let processor = Service::Processor(network);
+    loop {
+      let msg = self.next_sealed(processor).await;
 
-    let id = msg.id;
+      // Verify this message was actually sealed to us by our processor, and isn't a replay
+      // If it wasn't, it was injected by the message-queue, so we reject it, acknowledging it so
+      // we move on to the following messages
+      let unsealed =
+        if msg.from == processor { self.unseal(processor, msg.id, &msg.msg) } else { None };
+      let Some((sequence, plaintext)) = unsealed else {
+        log::error!(
+          "rejecting message {} as it wasn't freshly sealed by the {:?} processor {}",
+          msg.id,
+          network,
+          "(is the message-queue compromised?)",
+        );
+        MessageQueue::ack(self, processor, msg.id).await;
+        continue;
+      };
 
-    // Deserialize it into a ProcessorMessage
-    let msg: ProcessorMessage =
-      borsh::from_slice(&msg.msg).expect("message wasn't a borsh-encoded ProcessorMessage");
+      // Deserialize it into a ProcessorMessage
+      let msg_id = msg.id;
+      let msg: ProcessorMessage =
+        borsh::from_slice(&plaintext).expect("message wasn't a borsh-encoded ProcessorMessage");
 
-    return Message { id, network, msg };
+      return Message { id: msg_id, sequence, network, msg };
+    }
   }
   async fn ack(&self, msg: Message) {
     MessageQueue::ack(self, Service::Processor(msg.network), msg.id).await
   }
 }
+
+/// The processors for every network, each reached via its own connection to a message-queue.
+///
+/// A network's processor is reached via the message-queue specified by
+/// `MESSAGE_QUEUE_RPC_{NETWORK}` (such as `MESSAGE_QUEUE_RPC_BITCOIN`), if set, or else the one
+/// specified by `MESSAGE_QUEUE_RPC`. This lets a single coordinator serve processors for several
+/// networks, even if they're behind distinct message-queues, without an outage of one network's
+/// message-queue affecting the others.
+#[derive(Clone)]
+pub struct NetworkProcessors(Arc<HashMap<ExternalNetworkId, Arc<MessageQueue>>>);
+
+impl NetworkProcessors {
+  pub fn from_env() -> NetworkProcessors {
+    let default = serai_env::var("MESSAGE_QUEUE_RPC");
+    let mut queues = HashMap::new();
+    for network in EXTERNAL_NETWORKS {
+      let var = format!("MESSAGE_QUEUE_RPC_{}", format!("{network:?}").to_uppercase());
+      let url = serai_env::var(&var).or_else(|| default.clone()).unwrap_or_else(|| {
+        panic!("neither {var} nor MESSAGE_QUEUE_RPC were specified");
+      });
+      queues.insert(network, Arc::new(MessageQueue::from_env_with_url(Service::Coordinator, url)));
+    }
+    NetworkProcessors(Arc::new(queues))
+  }
+}
+
+#[async_trait::async_trait]
+impl Processors for NetworkProcessors {
+  async fn send(&self, network: ExternalNetworkId, msg: impl Send + Into<CoordinatorMessage>) {
+    self.0[&network].send(network, msg).await
+  }
+  async fn recv(&self, network: ExternalNetworkId) -> Message {
+    self.0[&network].recv(network).await
+  }
+  async fn ack(&self, msg: Message) {
+    Processors::ack(&self.0[&msg.network], msg).await
+  }
+}
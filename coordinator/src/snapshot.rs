@@ -0,0 +1,195 @@
+use std::{
+  fs,
+  io::{self, Read, Write, BufReader, BufWriter},
+  path::Path,
+};
+
+use blake2::{Digest, Blake2s256};
+
+use serai_db::{DbTxn, Db, Iterate};
+
+use crate::{ActiveTributaryDb, tributary::Transaction};
+
+const MAGIC: &[u8] = b"serai-coordinator-snapshot-v1";
+// The key length marking the end of the entries
+const END: u32 = u32::MAX;
+// The amount of entries imported per database transaction
+const IMPORT_BATCH: usize = 1024;
+
+fn invalid(msg: &str) -> io::Error {
+  io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+// A writer which hashes everything written
+struct HashingWriter<W: Write>(W, Blake2s256);
+impl<W: Write> Write for HashingWriter<W> {
+  fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+    let written = self.0.write(buf)?;
+    self.1.update(&buf[.. written]);
+    Ok(written)
+  }
+  fn flush(&mut self) -> io::Result<()> {
+    self.0.flush()
+  }
+}
+
+// A reader which hashes everything read
+struct HashingReader<R: Read>(R, Blake2s256);
+impl<R: Read> Read for HashingReader<R> {
+  fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+    let read = self.0.read(buf)?;
+    self.1.update(&buf[.. read]);
+    Ok(read)
+  }
+}
+
+fn write_vec<W: Write>(writer: &mut W, data: &[u8]) -> io::Result<()> {
+  let len = u32::try_from(data.len()).ok().filter(|len| *len != END);
+  writer.write_all(&len.ok_or_else(|| invalid("entry was too large"))?.to_le_bytes())?;
+  writer.write_all(data)
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+  let mut len = [0; 4];
+  reader.read_exact(&mut len)?;
+  Ok(u32::from_le_bytes(len))
+}
+
+// This doesn't allocate the claimed length up front, so a corrupted length won't cause an OOM
+fn read_vec<R: Read>(reader: &mut R, len: u32) -> io::Result<Vec<u8>> {
+  let mut data = vec![];
+  reader.by_ref().take(u64::from(len)).read_to_end(&mut data)?;
+  if data.len() != usize::try_from(len).unwrap() {
+    Err(io::Error::from(io::ErrorKind::UnexpectedEof))?;
+  }
+  Ok(data)
+}
+
+// Read the snapshot's entries, calling the specified function with each, then verify its checksum
+fn read_snapshot<R: Read>(reader: R, mut f: impl FnMut(Vec<u8>, Vec<u8>)) -> io::Result<u64> {
+  let mut reader = HashingReader(reader, Blake2s256::new());
+  let mut magic = vec![0; MAGIC.len()];
+  reader.read_exact(&mut magic)?;
+  if magic != MAGIC {
+    Err(invalid("file wasn't a coordinator snapshot"))?;
+  }
+
+  let mut entries = 0u64;
+  loop {
+    let key_len = read_u32(&mut reader)?;
+    if key_len == END {
+      break;
+    }
+    let key = read_vec(&mut reader, key_len)?;
+    let value_len = read_u32(&mut reader)?;
+    let value = read_vec(&mut reader, value_len)?;
+    f(key, value);
+    entries += 1;
+  }
+
+  let mut claimed_entries = [0; 8];
+  reader.read_exact(&mut claimed_entries)?;
+  if u64::from_le_bytes(claimed_entries) != entries {
+    Err(invalid("snapshot had a distinct amount of entries than claimed"))?;
+  }
+
+  let HashingReader(mut reader, hasher) = reader;
+  let mut checksum = [0; 32];
+  reader.read_exact(&mut checksum)?;
+  if checksum != <[u8; 32]>::from(hasher.finalize()) {
+    Err(invalid("snapshot's checksum was invalid"))?;
+  }
+  if reader.read(&mut [0])? != 0 {
+    Err(invalid("snapshot had trailing data"))?;
+  }
+
+  Ok(entries)
+}
+
+/// Export a snapshot of the coordinator's database to the specified file.
+///
+/// This is every Tributary, the metadata on every session, and the state of every pending signing
+/// protocol, as of a single point in time, allowing a validator to move their coordinator to
+/// another machine without having to sync from their peers. The coordinator's key isn't included,
+/// and must be moved separately.
+pub fn export(db: &impl Iterate, path: &Path) -> io::Result<u64> {
+  let mut writer = HashingWriter(BufWriter::new(fs::File::create(path)?), Blake2s256::new());
+  writer.write_all(MAGIC)?;
+
+  let mut res = Ok(());
+  let mut entries = 0u64;
+  db.iterate(|key, value| {
+    if res.is_ok() {
+      res = write_vec(&mut writer, key).and_then(|()| write_vec(&mut writer, value));
+      entries += 1;
+    }
+  });
+  res?;
+  writer.write_all(&END.to_le_bytes())?;
+  writer.write_all(&entries.to_le_bytes())?;
+
+  let HashingWriter(mut writer, hasher) = writer;
+  writer.write_all(&hasher.finalize())?;
+  writer.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+  Ok(entries)
+}
+
+/// Import a snapshot, exported with `export`, into an empty database.
+///
+/// The snapshot is verified in its entirety before any of it is imported. If the import is
+/// interrupted, the database must be deleted before the import is retried.
+pub fn import<D: Db + Iterate>(db: &mut D, path: &Path) -> io::Result<u64> {
+  let mut empty = true;
+  db.iterate(|_, _| empty = false);
+  if !empty {
+    Err(io::Error::new(io::ErrorKind::AlreadyExists, "database wasn't empty"))?;
+  }
+
+  read_snapshot(BufReader::new(fs::File::open(path)?), |_, _| {})?;
+
+  let mut batch = vec![];
+  let mut write = |batch: &mut Vec<(Vec<u8>, Vec<u8>)>| {
+    let mut txn = db.txn();
+    for (key, value) in batch.drain(..) {
+      txn.put(key, value);
+    }
+    txn.commit();
+  };
+  let entries = read_snapshot(BufReader::new(fs::File::open(path)?), |key, value| {
+    batch.push((key, value));
+    if batch.len() == IMPORT_BATCH {
+      write(&mut batch);
+    }
+  })?;
+  write(&mut batch);
+  Ok(entries)
+}
+
+/// Export a Tributary's blocks, and their commits, to the specified file.
+///
+/// Another validator may import this, with `import_tributary`, to sync the Tributary without
+/// fetching every block from their peers.
+pub fn export_tributary<D: Db>(db: &D, genesis: [u8; 32], path: &Path) -> io::Result<u64> {
+  let mut writer = BufWriter::new(fs::File::create(path)?);
+  let blocks = ::tributary::export::<_, Transaction, _>(db, genesis, &mut writer)?;
+  writer.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+  Ok(blocks)
+}
+
+/// Sync an active Tributary to the tip of an export made with `export_tributary`.
+///
+/// The export may come from any validator, as every block's commit is verified.
+pub fn import_tributary<D: Db>(db: &mut D, genesis: [u8; 32], path: &Path) -> io::Result<u64> {
+  // The specs may not have been migrated yet, if the coordinator was just updated
+  let mut txn = db.txn();
+  ActiveTributaryDb::migrate(&mut txn);
+  txn.commit();
+
+  let spec = ActiveTributaryDb::active_tributaries(db)
+    .1
+    .into_iter()
+    .find(|spec| spec.genesis() == genesis)
+    .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Tributary wasn't active"))?;
+  let mut reader = BufReader::new(fs::File::open(path)?);
+  ::tributary::import::<_, Transaction, _>(db.clone(), genesis, spec.validators(), &mut reader)
+}
@@ -0,0 +1,464 @@
+use core::fmt::Write;
+use std::{net::SocketAddr, collections::HashMap};
+
+use blake2::{Digest, Blake2s256};
+
+use scale::Decode;
+
+use ciphersuite::group::GroupEncoding;
+
+use serde_json::{json, Value};
+
+use http_body_util::Full;
+use hyper::{
+  body::{Bytes, Incoming},
+  header::{AUTHORIZATION, HeaderMap},
+  server::conn::http1,
+  service::service_fn,
+  Method, Request, Response, StatusCode,
+};
+use hyper_util::rt::TokioIo;
+use tokio::{net::TcpListener, sync::broadcast};
+
+use serai_client::{
+  primitives::{ExternalNetworkId, EXTERNAL_NETWORKS},
+  validator_sets::primitives::ExternalValidatorSet,
+};
+
+use tributary::Metrics;
+
+use processor_messages::coordinator::SubstrateSignableId;
+
+use serai_db::{Get, Db};
+
+use crate::{
+  P2p, P2pTransport, TransportMetrics, ActiveTributary, TributaryEvent, LastReceivedBatchDb,
+  LastVerifiedBatchDb,
+  p2p::CosignedBlock,
+  cosign_evaluator::{LatestCosign, DistinctCosign},
+  substrate::LatestCosignedBlock,
+  tributary::{
+    Topic, RecognizedTopics, AttemptDb, SeraiDkgCompleted, DkgKeyPair, DkgLocallyCompleted,
+    CompletedPlans, SeraiBlockNumber, SlashReport, LastHeartbeat, Heartbeats, liveness,
+  },
+};
+
+/// The configuration of the status API.
+#[derive(Clone)]
+pub struct StatusApi {
+  /// The address to serve the API on.
+  pub address: SocketAddr,
+  /// The key requests must present as a bearer token.
+  pub key: String,
+}
+
+// If a topic has yet to be completed
+pub(crate) fn pending(
+  getter: &impl Get,
+  set: ExternalValidatorSet,
+  genesis: [u8; 32],
+  topic: Topic,
+) -> bool {
+  // Once the key is set on Serai, the DKG is complete, regardless of our local view
+  if matches!(topic, Topic::Dkg | Topic::DkgConfirmation) &&
+    SeraiDkgCompleted::get(getter, set).is_some()
+  {
+    return false;
+  }
+  match topic {
+    // The key is generated once the current attempt has a key pair
+    Topic::Dkg => AttemptDb::attempt(getter, genesis, topic)
+      .map_or(true, |attempt| DkgKeyPair::get(getter, genesis, attempt).is_none()),
+    Topic::DkgConfirmation => DkgLocallyCompleted::get(getter, genesis).is_none(),
+    Topic::SubstrateSign(SubstrateSignableId::CosigningSubstrateBlock(hash)) => {
+      SeraiBlockNumber::get(getter, hash)
+        .map_or(true, |number| number > LatestCosignedBlock::latest_cosigned_block(getter))
+    }
+    Topic::SubstrateSign(SubstrateSignableId::Batch(batch)) => {
+      LastVerifiedBatchDb::get(getter, set.network).map_or(true, |last| batch > last)
+    }
+    Topic::SubstrateSign(SubstrateSignableId::SlashReport) => {
+      SlashReport::get(getter, set).is_none()
+    }
+    Topic::Sign(plan) => !CompletedPlans::get(getter, genesis)
+      .unwrap_or_default()
+      .iter()
+      .any(|(completed, _)| completed == &plan),
+  }
+}
+
+fn cosign_json(cosign: Option<CosignedBlock>) -> Value {
+  let Some(cosign) = cosign else { return Value::Null };
+  json!({ "block_number": cosign.block_number, "block": hex::encode(cosign.block) })
+}
+
+fn topic_json(topic: Topic) -> Value {
+  match topic {
+    Topic::Dkg => json!({ "kind": "dkg" }),
+    Topic::DkgConfirmation => json!({ "kind": "dkg_confirmation" }),
+    Topic::SubstrateSign(SubstrateSignableId::CosigningSubstrateBlock(hash)) => {
+      json!({ "kind": "cosign", "id": hex::encode(hash) })
+    }
+    Topic::SubstrateSign(SubstrateSignableId::Batch(batch)) => {
+      json!({ "kind": "batch", "id": batch })
+    }
+    Topic::SubstrateSign(SubstrateSignableId::SlashReport) => json!({ "kind": "slash_report" }),
+    Topic::Sign(plan) => json!({ "kind": "sign", "id": hex::encode(plan) }),
+  }
+}
+
+// The protocol, and its attempt, a Tributary transaction's order is for
+fn order_topic(order: &[u8]) -> Option<(Topic, u32)> {
+  if let Some(mut data) = order.strip_prefix(b"sign") {
+    let (plan, attempt) = <([u8; 32], u32)>::decode(&mut data).ok()?;
+    return Some((Topic::Sign(plan), attempt));
+  }
+  if let Some(mut data) = order.strip_prefix(b"substrate") {
+    let (id, attempt) = <(SubstrateSignableId, u32)>::decode(&mut data).ok()?;
+    return Some((Topic::SubstrateSign(id), attempt));
+  }
+  if let Some(mut data) = order.strip_prefix(b"dkg") {
+    return Some((Topic::Dkg, u32::decode(&mut data).ok()?));
+  }
+  None
+}
+
+pub(crate) async fn status<D: Db, P: P2p>(
+  db: &D,
+  p2p: &P,
+  tributaries: &HashMap<ExternalValidatorSet, ActiveTributary<D, P>>,
+) -> Value {
+  let mut sets = tributaries.values().collect::<Vec<_>>();
+  sets.sort_by_key(|tributary| (tributary.spec.set().network, tributary.spec.set().session.0));
+
+  let mut tributaries_json = vec![];
+  for ActiveTributary { spec, tributary } in sets {
+    let set = spec.set();
+    let genesis = spec.genesis();
+    let reader = tributary.reader();
+
+    // The DKG isn't explicitly recognized, as it starts with the Tributary
+    let signing = [Topic::Dkg, Topic::DkgConfirmation]
+      .into_iter()
+      .chain(RecognizedTopics::get(db, genesis).unwrap_or_default())
+      .filter(|topic| pending(db, set, genesis, *topic))
+      .map(|topic| {
+        let mut topic_json = topic_json(topic);
+        topic_json["attempt"] = json!(AttemptDb::attempt(db, genesis, topic));
+        topic_json
+      })
+      .collect::<Vec<_>>();
+
+    let queue = tributary
+      .mempool_queues()
+      .await
+      .into_iter()
+      .map(|queue| {
+        let mut queue_json = match order_topic(&queue.order) {
+          Some((topic, attempt)) => {
+            let mut topic_json = topic_json(topic);
+            topic_json["attempt"] = json!(attempt);
+            topic_json
+          }
+          None => json!({ "kind": "other", "order": hex::encode(&queue.order) }),
+        };
+        queue_json["signer"] = json!(hex::encode(queue.signer));
+        queue_json["transactions"] = json!(queue.transactions);
+        queue_json
+      })
+      .collect::<Vec<_>>();
+
+    let block_number = u32::try_from(reader.block_number()).unwrap();
+    let validators = spec
+      .validators()
+      .into_iter()
+      .map(|(validator, _)| {
+        // Solely our own observation, so this isn't included in the slash report
+        let mempool_violations = reader.mempool_violations(&validator);
+        let validator = validator.to_bytes();
+        json!({
+          "validator": hex::encode(validator),
+          "heartbeats": Heartbeats::get(db, genesis, validator).unwrap_or(0),
+          "last_heartbeat": LastHeartbeat::get(db, genesis, validator),
+          "online": !liveness::offline(db, genesis, validator, block_number),
+          "mempool_violations": mempool_violations,
+        })
+      })
+      .collect::<Vec<_>>();
+
+    tributaries_json.push(json!({
+      "network": format!("{:?}", set.network),
+      "session": set.session.0,
+      "genesis": hex::encode(genesis),
+      "block_number": reader.block_number(),
+      "tip": hex::encode(reader.tip()),
+      "signing": signing,
+      "queue": queue,
+      "validators": validators,
+    }));
+  }
+
+  let mut networks = serde_json::Map::new();
+  for network in EXTERNAL_NETWORKS {
+    networks.insert(
+      format!("{network:?}"),
+      json!({
+        "peers": p2p.peers(network).await,
+        "last_received_batch": LastReceivedBatchDb::get(db, network),
+        "last_published_batch": LastVerifiedBatchDb::get(db, network),
+        "latest_cosign": cosign_json(LatestCosign::get(db, network)),
+        "distinct_cosign": cosign_json(DistinctCosign::get(db, network)),
+      }),
+    );
+  }
+
+  let transports = p2p.transports().await;
+  let mut transports_json = serde_json::Map::new();
+  for transport in P2pTransport::ALL {
+    let metrics = transports.get(&transport).copied().unwrap_or_default();
+    transports_json.insert(
+      transport.name().to_string(),
+      json!({
+        "connections": metrics.connections,
+        "established": metrics.established,
+        "failed_dials": metrics.failed_dials,
+      }),
+    );
+  }
+
+  json!({
+    "tributaries": tributaries_json,
+    "networks": networks,
+    "transports": transports_json,
+    "latest_cosigned_block": LatestCosignedBlock::latest_cosigned_block(db),
+  })
+}
+
+// The Tributary metrics which are solely a single value, with their type and description
+const TRIBUTARY_METRICS: [(&str, &str, &str, fn(&Metrics) -> u64); 7] = [
+  ("tributary_blocks_total", "counter", "Blocks added to the Tributary.", |m| m.blocks),
+  (
+    "tributary_rounds_total",
+    "counter",
+    "Rounds it took to decide the blocks added to the Tributary.",
+    |m| m.rounds,
+  ),
+  ("tributary_round", "gauge", "The round of the block currently being decided.", |m| {
+    u64::from(m.round)
+  }),
+  (
+    "tributary_consensus_messages_received_total",
+    "counter",
+    "Consensus messages received over the P2P network.",
+    |m| m.consensus_messages_received,
+  ),
+  (
+    "tributary_consensus_messages_broadcast_total",
+    "counter",
+    "Consensus messages we broadcast.",
+    |m| m.consensus_messages_broadcast,
+  ),
+  (
+    "tributary_transactions_received_total",
+    "counter",
+    "Transactions received over the P2P network.",
+    |m| m.transactions_received,
+  ),
+  ("tributary_mempool_transactions", "gauge", "Transactions within the mempool.", |m| {
+    u64::try_from(m.mempool_size).unwrap()
+  }),
+];
+
+// The metrics for each P2P transport, with their type and description
+const TRANSPORT_METRICS: [(&str, &str, &str, fn(&TransportMetrics) -> u64); 3] = [
+  ("p2p_connections", "gauge", "Connections currently open over the transport.", |m| {
+    u64::try_from(m.connections).unwrap()
+  }),
+  (
+    "p2p_connections_established_total",
+    "counter",
+    "Connections established over the transport.",
+    |m| m.established,
+  ),
+  ("p2p_dials_failed_total", "counter", "Dials over the transport which failed.", |m| {
+    m.failed_dials
+  }),
+];
+
+fn header(res: &mut String, name: &str, kind: &str, help: &str) {
+  writeln!(res, "# HELP {name} {help}").unwrap();
+  writeln!(res, "# TYPE {name} {kind}").unwrap();
+}
+
+// Render the metrics of the P2P transports and active Tributaries in the Prometheus text format
+pub(crate) async fn metrics<D: Db, P: P2p>(
+  p2p: &P,
+  tributaries: &HashMap<ExternalValidatorSet, ActiveTributary<D, P>>,
+) -> String {
+  let mut sets = tributaries.values().collect::<Vec<_>>();
+  sets.sort_by_key(|tributary| (tributary.spec.set().network, tributary.spec.set().session.0));
+
+  let mut collected = vec![];
+  for ActiveTributary { spec, tributary } in sets {
+    let set = spec.set();
+    let labels = format!("network=\"{:?}\",session=\"{}\"", set.network, set.session.0);
+    collected.push((labels, tributary.metrics().await));
+  }
+
+  let mut res = String::new();
+  let transports = p2p.transports().await;
+  for (name, kind, help, value) in TRANSPORT_METRICS {
+    header(&mut res, name, kind, help);
+    for transport in P2pTransport::ALL {
+      let metrics = transports.get(&transport).copied().unwrap_or_default();
+      writeln!(res, "{name}{{transport=\"{}\"}} {}", transport.name(), value(&metrics)).unwrap();
+    }
+  }
+
+  for (name, kind, help, value) in TRIBUTARY_METRICS {
+    header(&mut res, name, kind, help);
+    for (labels, metrics) in &collected {
+      writeln!(res, "{name}{{{labels}}} {}", value(metrics)).unwrap();
+    }
+  }
+
+  let name = "tributary_proposal_latency_seconds";
+  header(
+    &mut res,
+    name,
+    "summary",
+    "Time from adding a block to receiving the first proposal for the next block.",
+  );
+  for (labels, metrics) in &collected {
+    let latency = metrics.proposal_latency.as_secs_f64();
+    writeln!(res, "{name}_sum{{{labels}}} {latency}").unwrap();
+    writeln!(res, "{name}_count{{{labels}}} {}", metrics.proposals).unwrap();
+  }
+
+  let name = "tributary_commits_signed_total";
+  header(&mut res, name, "counter", "Commits for added blocks each validator participated in.");
+  for (labels, metrics) in &collected {
+    let mut commits_signed = metrics.commits_signed.iter().collect::<Vec<_>>();
+    commits_signed.sort();
+    for (validator, signed) in commits_signed {
+      let validator = hex::encode(validator);
+      writeln!(res, "{name}{{{labels},validator=\"{validator}\"}} {signed}").unwrap();
+    }
+  }
+
+  res
+}
+
+fn respond(status: StatusCode, body: &Value) -> Response<Full<Bytes>> {
+  Response::builder()
+    .status(status)
+    .header("content-type", "application/json")
+    .body(Full::new(Bytes::from(body.to_string())))
+    .unwrap()
+}
+
+// Check the request presents the key, comparing hashes so the comparison doesn't leak the key
+pub(crate) fn authenticated(key: &str, headers: &HeaderMap) -> bool {
+  let Some(presented) = headers
+    .get(AUTHORIZATION)
+    .and_then(|header| header.to_str().ok())
+    .and_then(|header| header.strip_prefix("Bearer "))
+  else {
+    return false;
+  };
+  Blake2s256::digest(presented.as_bytes()) == Blake2s256::digest(key.as_bytes())
+}
+
+/// Serve the status API, reporting on the Tributaries announced via `tributary_event`.
+///
+/// `GET /status` returns the active Tributaries, with their heights and the signing protocols
+/// they're yet to complete (with the current attempt of each), and, for each network, the amount
+/// of connected peers, the last Batches received and published, and the latest cosign it produced.
+/// If a network cosigned a block distinct from the one on our chain, that cosign is reported so
+/// operators may be alerted before enough networks do so for the coordinator to halt. Each
+/// Tributary also reports the liveness of its validators, as evidenced by their heartbeats, how
+/// often each exceeded our mempool's quotas, and the queues of signed transactions within its
+/// mempool, per signer and protocol. Blocks include a transaction from each queue in turn,
+/// starting with the queue which has been waiting the longest, which is the order the queues are
+/// reported in.
+///
+/// `GET /status` also reports, for each P2P transport (TCP, QUIC, Tor, and relays), the amount of
+/// open connections, established connections, and failed dials.
+///
+/// `GET /metrics` returns those transport metrics, labeled with the transport, and metrics on each
+/// active Tributary's consensus and gossip, labeled with the Tributary's network and session, in
+/// the Prometheus text format.
+///
+/// Requests must present the configured key as a bearer token.
+pub async fn status_api_task<D: Db, P: P2p>(
+  db: D,
+  p2p: P,
+  config: StatusApi,
+  mut tributary_event: broadcast::Receiver<TributaryEvent<D, P>>,
+) {
+  let listener = TcpListener::bind(config.address)
+    .await
+    .unwrap_or_else(|e| panic!("couldn't bind the status API to {}: {e}", config.address));
+  log::info!("serving the status API on {}", config.address);
+
+  let mut tributaries = HashMap::new();
+  loop {
+    let accepted = tokio::select! {
+      event = tributary_event.recv() => {
+        match event {
+          Ok(TributaryEvent::NewTributary(tributary)) => {
+            tributaries.insert(tributary.spec.set(), tributary);
+          }
+          Ok(TributaryEvent::TributaryRetired(set)) => {
+            tributaries.remove(&set);
+          }
+          Err(broadcast::error::RecvError::Lagged(_)) => {
+            panic!("status_api_task lagged to handle tributary_event")
+          }
+          Err(broadcast::error::RecvError::Closed) => panic!("tributary_event sender closed"),
+        }
+        continue;
+      }
+      accepted = listener.accept() => accepted,
+    };
+    let stream = match accepted {
+      Ok((stream, _)) => stream,
+      Err(e) => {
+        log::warn!("couldn't accept a connection to the status API: {e}");
+        continue;
+      }
+    };
+
+    let db = db.clone();
+    let p2p = p2p.clone();
+    let key = config.key.clone();
+    let tributaries = tributaries.clone();
+    tokio::spawn(async move {
+      let service = service_fn(move |request: Request<Incoming>| {
+        let db = db.clone();
+        let p2p = p2p.clone();
+        let key = key.clone();
+        let tributaries = tributaries.clone();
+        async move {
+          let response = if !authenticated(&key, request.headers()) {
+            respond(StatusCode::UNAUTHORIZED, &json!({ "error": "unauthorized" }))
+          } else if (request.method() == Method::GET) && (request.uri().path() == "/status") {
+            respond(StatusCode::OK, &status(&db, &p2p, &tributaries).await)
+          } else if (request.method() == Method::GET) && (request.uri().path() == "/metrics") {
+            Response::builder()
+              .status(StatusCode::OK)
+              .header("content-type", "text/plain; version=0.0.4")
+              .body(Full::new(Bytes::from(metrics(&p2p, &tributaries).await)))
+              .unwrap()
+          } else {
+            respond(StatusCode::NOT_FOUND, &json!({ "error": "unrecognized route" }))
+          };
+          Ok::<_, core::convert::Infallible>(response)
+        }
+      });
+      if let Err(e) = http1::Builder::new().serve_connection(TokioIo::new(stream), service).await {
+        log::debug!("error serving a connection to the status API: {e}");
+      }
+    });
+  }
+}
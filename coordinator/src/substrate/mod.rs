@@ -16,7 +16,7 @@ use serai_client::{
     primitives::{ExternalValidatorSet, ValidatorSet},
     ValidatorSetsEvent,
   },
-  Block, Serai, SeraiError, TemporalSerai,
+  Block, Serai, SeraiError, RuntimeMismatch, TemporalSerai,
 };
 
 use serai_db::DbTxn;
@@ -57,6 +57,12 @@ async fn handle_new_set<D: Db>(
   block: &Block,
   set: ExternalValidatorSet,
 ) -> Result<(), SeraiError> {
+  // Don't create a Tributary for a set which has already retired
+  if crate::RetiredTributaryDb::get(txn, set).is_some() {
+    log::info!("not creating a tributary for {:?} since it's been retired", set);
+    return Ok(());
+  }
+
   if in_set(key, &serai.as_of(block.hash()), set.into())
     .await?
     .expect("NewSet for set which doesn't exist")
@@ -72,6 +78,10 @@ async fn handle_new_set<D: Db>(
       set_participants.into_iter().map(|(k, w)| (k, u16::try_from(w).unwrap())).collect::<Vec<_>>()
     };
 
+    // Read the attempt window as of this block, so every coordinator uses the same value
+    let attempt_window =
+      serai.as_of(block.hash()).validator_sets().attempt_window(set.network).await?;
+
     let time = if let Ok(time) = block.time() {
       time
     } else {
@@ -93,7 +103,7 @@ async fn handle_new_set<D: Db>(
     const SUBSTRATE_TO_TRIBUTARY_TIME_DELAY: u64 = 120;
     let time = time + SUBSTRATE_TO_TRIBUTARY_TIME_DELAY;
 
-    let spec = TributarySpec::new(block.hash(), time, set, set_data);
+    let spec = TributarySpec::new(block.hash(), time, set, set_data, attempt_window);
 
     log::info!("creating new tributary for {:?}", spec.set());
 
@@ -108,11 +118,56 @@ async fn handle_new_set<D: Db>(
     new_tributary_spec.send(spec).unwrap();
   } else {
     log::info!("not present in new set {:?}", set);
+    // If we're in the set this set succeeds, we continue to participate in it, including its
+    // handover, until it retires
   }
 
   Ok(())
 }
 
+// Retire a set's Tributary, informing the rest of the coordinator so it's torn down
+fn retire_set(
+  txn: &mut impl DbTxn,
+  tributary_retired: &mpsc::UnboundedSender<ExternalValidatorSet>,
+  set: ExternalValidatorSet,
+) {
+  crate::ActiveTributaryDb::retire_tributary(txn, set);
+  tributary_retired.send(set).unwrap();
+}
+
+// Retire the Tributaries for any sets the chain has moved past, yet whose retirement event we
+// never handled
+//
+// A set retires once it reports its slashes, or, if it fails to, once its successor retires.
+// Accordingly, once the session two sessions after a set has begun, the set has certainly retired.
+async fn retire_stale_tributaries<D: Db>(
+  db: &mut D,
+  tributary_retired: &mpsc::UnboundedSender<ExternalValidatorSet>,
+  serai: &Serai,
+  block: &Block,
+) -> Result<(), SeraiError> {
+  let serai = serai.as_of(block.hash());
+  for spec in crate::ActiveTributaryDb::active_tributaries(db).1 {
+    let set = spec.set();
+    let Some(session) = serai.validator_sets().session(set.network.into()).await? else {
+      continue;
+    };
+    if session.0 < set.session.0.saturating_add(2) {
+      continue;
+    }
+
+    log::warn!(
+      "{:?} retired without us handling its retirement event, retiring it as of block {}",
+      set,
+      block.number()
+    );
+    let mut txn = db.txn();
+    retire_set(&mut txn, tributary_retired, set);
+    txn.commit();
+  }
+  Ok(())
+}
+
 async fn handle_batch_and_burns<Pro: Processors>(
   txn: &mut impl DbTxn,
   processors: &Pro,
@@ -156,13 +211,21 @@ async fn handle_batch_and_burns<Pro: Processors>(
     }
   }
 
-  for burn in serai.coins().burn_with_instruction_events().await? {
+  for (index, burn) in serai.coins().burn_with_instruction_events_by_extrinsic().await? {
     if let CoinsEvent::BurnWithInstruction { from: _, instruction } = burn {
       let network = instruction.balance.coin.network();
       network_had_event(&mut burns, &mut batches, network);
 
+      let extrinsic = usize::try_from(index)
+        .ok()
+        .and_then(|index| block.transactions.get(index))
+        .ok_or_else(|| {
+          SeraiError::InvalidNode("burn was made by an extrinsic not in the block".to_string())
+        })?
+        .hash();
+
       // network_had_event should register an entry in burns
-      burns.get_mut(&network).unwrap().push(instruction);
+      burns.get_mut(&network).unwrap().push((extrinsic, instruction));
     } else {
       panic!("Burn event wasn't Burn: {burn:?}");
     }
@@ -182,6 +245,7 @@ async fn handle_batch_and_burns<Pro: Processors>(
         .expect("network had a batch/burn yet never set a latest block")
     };
 
+    let (burn_extrinsics, network_burns) = burns.remove(&network).unwrap().into_iter().unzip();
     processors
       .send(
         network,
@@ -191,7 +255,8 @@ async fn handle_batch_and_burns<Pro: Processors>(
             network_latest_finalized_block,
           },
           block: block.number(),
-          burns: burns.remove(&network).unwrap(),
+          burns: network_burns,
+          burn_extrinsics,
           batches: batches.remove(&network).unwrap(),
         },
       )
@@ -307,14 +372,16 @@ async fn handle_block<D: Db, Pro: Processors>(
     if HandledEvent::is_unhandled(db, hash, event_id) {
       log::info!("found fresh set retired event {:?}", retired_set);
       let mut txn = db.txn();
-      crate::ActiveTributaryDb::retire_tributary(&mut txn, set);
-      tributary_retired.send(set).unwrap();
+      retire_set(&mut txn, tributary_retired, set);
       HandledEvent::handle_event(&mut txn, hash, event_id);
       txn.commit();
     }
     event_id += 1;
   }
 
+  // This isn't an event, and is safe to repeat, so it doesn't consume an event ID
+  retire_stale_tributaries(db, tributary_retired, serai, &block).await?;
+
   // Finally, tell the processor of acknowledged blocks/burns
   // This uses a single event as unlike prior events which individually executed code, all
   // following events share data collection
@@ -328,6 +395,64 @@ async fn handle_block<D: Db, Pro: Processors>(
   Ok(())
 }
 
+// Check the runtime which executed this block is compatible with our client before we interpret
+// its events, refreshing our decoding from the runtime's metadata if it was upgraded
+async fn check_runtime(
+  serai: &Serai,
+  block: &Block,
+  spec_version: &mut Option<u32>,
+) -> Result<(), SeraiError> {
+  // The runtime as of a block is the runtime after its execution, making the runtime which
+  // executed a block the runtime as of its parent
+  let executor = if block.number() == 0 { block.hash() } else { block.header.parent_hash.into() };
+  let version = serai.runtime_version(executor).await?.spec_version;
+  if *spec_version == Some(version) {
+    return Ok(());
+  }
+
+  if let Some(previous) = *spec_version {
+    log::warn!(
+      "serai's runtime was upgraded from spec version {previous} to {version} as of block {}",
+      block.number()
+    );
+  }
+  let versions = compatible(serai.check_compatibility_as_of(executor).await)?;
+  if !versions.is_empty() {
+    log::warn!(
+      "serai's runtime as of block {} differs from the one this coordinator was built for, yet \
+       its calls and events are unchanged: {}",
+      block.number(),
+      versions.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+    );
+  }
+  // Rebuild the error registry, as pallets' errors may have been changed by the upgrade
+  serai.error_registry(executor).await?;
+  *spec_version = Some(version);
+  Ok(())
+}
+
+// Reduce the result of a compatibility check to the mismatches which change the encoding of calls
+// or events, returning the mismatches which don't
+//
+// A runtime upgrade is only an issue if we'd misinterpret its events or build invalid calls for
+// it, so differing versions alone don't pause the scanner.
+pub(crate) fn compatible(
+  check: Result<(), SeraiError>,
+) -> Result<Vec<RuntimeMismatch>, SeraiError> {
+  match check {
+    Ok(()) => Ok(vec![]),
+    Err(SeraiError::IncompatibleRuntime(mismatches)) => {
+      let (incompatible, versions) =
+        mismatches.into_iter().partition::<Vec<_>, _>(RuntimeMismatch::changes_encoding);
+      if !incompatible.is_empty() {
+        Err(SeraiError::IncompatibleRuntime(incompatible))?;
+      }
+      Ok(versions)
+    }
+    Err(e) => Err(e),
+  }
+}
+
 #[allow(clippy::too_many_arguments)]
 async fn handle_new_blocks<D: Db, Pro: Processors>(
   db: &mut D,
@@ -338,6 +463,7 @@ async fn handle_new_blocks<D: Db, Pro: Processors>(
   processors: &Pro,
   serai: &Serai,
   next_block: &mut u64,
+  spec_version: &mut Option<u32>,
 ) -> Result<(), SeraiError> {
   // Check if there's been a new Substrate block
   let latest_number = serai.latest_finalized_block().await?.number();
@@ -358,6 +484,8 @@ async fn handle_new_blocks<D: Db, Pro: Processors>(
       .await?
       .expect("couldn't get block before the latest finalized block");
 
+    check_runtime(serai, &block, spec_version).await?;
+
     log::info!("handling substrate block {b}");
     handle_block(
       db,
@@ -393,6 +521,8 @@ pub async fn scan_task<D: Db, Pro: Processors>(
 ) {
   log::info!("scanning substrate");
   let mut next_substrate_block = NextBlock::get(&db).unwrap_or_default();
+  // The spec version of the runtime we last checked our compatibility with
+  let mut spec_version = None;
 
   /*
   let new_substrate_block_notifier = {
@@ -469,10 +599,20 @@ pub async fn scan_task<D: Db, Pro: Processors>(
       &processors,
       &serai,
       &mut next_substrate_block,
+      &mut spec_version,
     )
     .await
     {
       Ok(()) => {}
+      // Pause until we're updated, as we'd otherwise misinterpret the runtime's events
+      Err(SeraiError::IncompatibleRuntime(mismatches)) => {
+        log::error!(
+          "serai's runtime as of block {next_substrate_block} is incompatible with this \
+           coordinator, which must be updated. pausing. mismatches: {}",
+          mismatches.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+        );
+        sleep(Duration::from_secs(60)).await;
+      }
       Err(e) => {
         log::error!("couldn't communicate with serai node: {e}");
         sleep(Duration::from_secs(5)).await;
@@ -15,10 +15,23 @@ use tokio::sync::RwLock;
 use crate::{
   processors::{Message, Processors},
   TributaryP2p, ReqResMessageKind, GossipMessageKind, P2pMessageKind, Message as P2pMessage, P2p,
+  P2pTransport, TransportMetrics,
 };
 
 pub mod tributary;
 
+pub mod sim;
+
+mod p2p;
+
+mod tor;
+
+mod snapshot;
+
+mod status;
+
+mod substrate;
+
 #[derive(Clone)]
 pub struct MemProcessors(pub Arc<RwLock<HashMap<ExternalNetworkId, VecDeque<CoordinatorMessage>>>>);
 impl MemProcessors {
@@ -110,6 +123,14 @@ impl P2p for LocalP2p {
       tokio::time::sleep(std::time::Duration::from_millis(100)).await;
     }
   }
+
+  async fn peers(&self, _network: ExternalNetworkId) -> usize {
+    self.1.read().await.1.len() - 1
+  }
+
+  async fn transports(&self) -> HashMap<P2pTransport, TransportMetrics> {
+    HashMap::new()
+  }
 }
 
 #[async_trait]
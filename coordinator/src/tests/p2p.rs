@@ -0,0 +1,191 @@
+use core::time::Duration;
+
+use rand_core::{RngCore, OsRng};
+
+use futures_util::{task::Poll, poll, StreamExt};
+use tokio::time::{Instant, sleep};
+
+use libp2p::{
+  tcp::Config as TcpConfig,
+  noise, yamux,
+  gossipsub::{Event as GsEvent, Behaviour as GsBehavior},
+  swarm::SwarmEvent,
+  Multiaddr, Swarm, SwarmBuilder,
+};
+
+use tributary::MessageValidity;
+
+use crate::{
+  P2p,
+  p2p::{
+    P2pTransport, Latencies, compress, decompress, quic_addr, gossipsub, acceptance, topic_for_set,
+    topic_score_params,
+  },
+  tests::tributary::{new_keys, new_spec, new_tributaries},
+};
+
+#[test]
+fn compression() {
+  // Small messages aren't compressed
+  let small = vec![0; 16];
+  assert_eq!(compress(small.clone()).len(), 1 + small.len());
+  assert_eq!(decompress(&compress(small.clone()), 16).unwrap(), small);
+
+  // Large, compressible messages are
+  let large = vec![0; 64 * 1024];
+  let compressed = compress(large.clone());
+  assert!(compressed.len() < (large.len() / 8));
+  assert_eq!(decompress(&compressed, large.len()).unwrap(), large);
+
+  // Large, incompressible messages are sent as-is
+  let mut random = vec![0; 64 * 1024];
+  OsRng.fill_bytes(&mut random);
+  assert_eq!(compress(random.clone()).len(), 1 + random.len());
+  assert_eq!(decompress(&compress(random.clone()), random.len()).unwrap(), random);
+
+  // Messages exceeding the size limit are rejected, whether or not they were compressed
+  assert!(decompress(&compressed, large.len() - 1).is_none());
+  assert!(decompress(&compress(random.clone()), random.len() - 1).is_none());
+  assert!(decompress(&compress(small), 15).is_none());
+
+  // As are malformed messages
+  assert!(decompress(&[], 16).is_none());
+  assert!(decompress(&[2, 0], 16).is_none());
+  let mut corrupted = compressed;
+  corrupted.truncate(corrupted.len() / 2);
+  assert!(decompress(&corrupted, large.len()).is_none());
+}
+
+#[test]
+fn transports() {
+  let addr = |addr: &str| addr.parse::<Multiaddr>().unwrap();
+
+  // TCP addresses are dialed over QUIC on the same port
+  let tcp = addr("/ip4/1.2.3.4/tcp/30563");
+  let quic = addr("/ip4/1.2.3.4/udp/30563/quic-v1");
+  assert_eq!(quic_addr(&tcp).unwrap(), quic);
+  assert_eq!(quic_addr(&addr("/ip6/::1/tcp/30563")).unwrap(), addr("/ip6/::1/udp/30563/quic-v1"));
+  let peer = "/p2p/12D3KooWD3eckifWpRn9wQpMG9R9hX3sD158z7EqHWmweQAJU5SA";
+  assert_eq!(
+    quic_addr(&addr(&format!("/ip4/1.2.3.4/tcp/30563{peer}"))).unwrap(),
+    addr(&format!("/ip4/1.2.3.4/udp/30563/quic-v1{peer}"))
+  );
+
+  // Addresses which aren't direct TCP addresses don't have a QUIC form
+  let onion = addr("/onion3/vww6ybal4bd7szmgncyruucpgfkqahzddi37ktceo3ah7ngmcopnpyyd:30563");
+  let relayed = addr(&format!("/ip4/1.2.3.4/tcp/30563{peer}/p2p-circuit"));
+  assert!(quic_addr(&quic).is_none());
+  assert!(quic_addr(&onion).is_none());
+  assert!(quic_addr(&relayed).is_none());
+
+  assert_eq!(P2pTransport::of(&tcp), P2pTransport::Tcp);
+  assert_eq!(P2pTransport::of(&quic), P2pTransport::Quic);
+  assert_eq!(P2pTransport::of(&onion), P2pTransport::Tor);
+  assert_eq!(P2pTransport::of(&relayed), P2pTransport::Relayed);
+}
+
+#[test]
+fn latency_ranking() {
+  let addr = |addr: &str| addr.parse::<Multiaddr>().unwrap();
+  let fast = addr("/ip4/1.2.3.4/tcp/30563");
+  let slow = addr("/ip4/5.6.7.8/tcp/30563");
+  let unmeasured = addr("/ip4/9.10.11.12/tcp/30563");
+  let onion = addr("/onion3/vww6ybal4bd7szmgncyruucpgfkqahzddi37ktceo3ah7ngmcopnpyyd:30563");
+  let nodes = vec![onion.clone(), slow.clone(), unmeasured.clone(), fast.clone()];
+
+  // Without any measurements, clearnet peers are preferred over onion services
+  let latencies = Latencies::default();
+  let ranked = latencies.rank(nodes.clone());
+  assert_eq!(ranked.len(), nodes.len());
+  assert_eq!(ranked.last().unwrap(), &onion);
+
+  // Measured peers are ranked by their latency
+  latencies.record(fast.clone(), Duration::from_millis(10));
+  latencies.record(slow.clone(), Duration::from_secs(1));
+  assert_eq!(latencies.rank(nodes.clone()), vec![fast.clone(), unmeasured, slow.clone(), onion]);
+
+  // Measurements are smoothed, so a single slow dial doesn't immediately demote a peer
+  latencies.record(fast.clone(), Duration::from_millis(510));
+  assert_eq!(latencies.rank(nodes)[0], fast);
+}
+
+// A swarm solely running Gossipsub, as configured for the coordinator, listening on localhost
+async fn gossipsub_swarm() -> (Swarm<GsBehavior>, Multiaddr) {
+  let mut swarm = SwarmBuilder::with_new_identity()
+    .with_tokio()
+    .with_tcp(TcpConfig::default().nodelay(true), noise::Config::new, yamux::Config::default)
+    .unwrap()
+    .with_behaviour(gossipsub)
+    .unwrap()
+    .build();
+  swarm.listen_on("/ip4/127.0.0.1/tcp/0".parse().unwrap()).unwrap();
+  loop {
+    if let SwarmEvent::NewListenAddr { address, .. } = swarm.select_next_some().await {
+      return (swarm, address);
+    }
+  }
+}
+
+#[tokio::test]
+async fn honest_relayers_arent_penalized() {
+  let keys = new_keys(&mut OsRng);
+  let spec = new_spec(&mut OsRng, &keys);
+  let tributaries = new_tributaries(&keys, &spec).await;
+
+  // The first two validators also relay the Tendermint messages they receive over Gossipsub, as
+  // honest peers would, causing each to receive every message twice
+  let topic = topic_for_set(spec.set());
+  let mut swarms = vec![];
+  for _ in 0 .. 2 {
+    let (mut swarm, addr) = gossipsub_swarm().await;
+    swarm.behaviour_mut().set_topic_params(topic.clone(), topic_score_params()).unwrap();
+    swarm.behaviour_mut().subscribe(&topic).unwrap();
+    swarms.push((swarm, addr));
+  }
+  let addr = swarms[1].1.clone();
+  swarms[0].0.dial(addr).unwrap();
+  let mut swarms = swarms.into_iter().map(|(swarm, _)| swarm).collect::<Vec<_>>();
+
+  let start = Instant::now();
+  let mut relayed = [0; 2];
+  while relayed.iter().any(|relayed| *relayed < 10) {
+    assert!(start.elapsed() < Duration::from_secs(120), "Tendermint messages weren't relayed");
+
+    for (i, (_, p2p, tributary)) in tributaries.iter().enumerate() {
+      while let Poll::Ready(msg) = poll!(p2p.receive()) {
+        let validity = tributary.handle_message(&msg.msg).await;
+        assert_ne!(validity, MessageValidity::Invalid);
+        if let Some(swarm) = swarms.get_mut(i) {
+          // This may error if the message was already received over Gossipsub
+          let _ = swarm.behaviour_mut().publish(topic.clone(), msg.msg.clone());
+        }
+        p2p.report_validity(msg, validity).await;
+      }
+    }
+
+    for (i, swarm) in swarms.iter_mut().enumerate() {
+      while let Poll::Ready(Some(event)) = poll!(swarm.next()) {
+        let SwarmEvent::Behaviour(GsEvent::Message { propagation_source, message_id, message }) =
+          event
+        else {
+          continue;
+        };
+        let validity = tributaries[i].2.handle_message(&message.data).await;
+        assert_eq!(validity, MessageValidity::Ignore);
+        swarm
+          .behaviour_mut()
+          .report_message_validation_result(&message_id, &propagation_source, acceptance(validity))
+          .unwrap();
+        relayed[i] += 1;
+      }
+    }
+
+    sleep(Duration::from_millis(50)).await;
+  }
+
+  // Neither peer was penalized for relaying valid messages
+  for i in 0 .. 2 {
+    let peer = *swarms[1 - i].local_peer_id();
+    assert!(swarms[i].behaviour().peer_score(&peer).unwrap() >= 0.0);
+  }
+}
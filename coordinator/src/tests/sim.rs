@@ -0,0 +1,382 @@
+use core::time::Duration;
+use std::{
+  sync::{Arc, Mutex},
+  collections::{HashSet, HashMap, BTreeMap},
+};
+
+use zeroize::Zeroizing;
+use rand_core::{RngCore, CryptoRng};
+
+use ciphersuite::{Ciphersuite, Ristretto};
+
+use serai_client::{primitives::ExternalNetworkId, validator_sets::primitives::ExternalValidatorSet};
+
+use async_trait::async_trait;
+
+use tokio::sync::Notify;
+
+use serai_db::MemDb;
+use serai_clock::{Clock, SystemClock, TestClock};
+
+use tributary::Tributary;
+
+use crate::{
+  TributaryP2p, ReqResMessageKind, GossipMessageKind, P2pMessageKind, Message as P2pMessage, P2p,
+  P2pTransport, TransportMetrics,
+  tributary::{Transaction, TributarySpec},
+  tests::tributary::{new_keys_for, new_spec},
+};
+
+/// How often `Simulation::run_for` advances the simulated clock.
+pub const STEP: Duration = Duration::from_millis(10);
+
+/// A deterministic RNG, seeded by the test, so simulations can be reproduced.
+///
+/// This is SplitMix64, which is in no way cryptographically secure, yet is marked as such so it
+/// may be used to generate the validators' keys.
+#[derive(Clone, Debug)]
+pub struct SimRng(u64);
+
+impl SimRng {
+  pub fn new(seed: u64) -> SimRng {
+    SimRng(seed)
+  }
+}
+
+impl RngCore for SimRng {
+  fn next_u32(&mut self) -> u32 {
+    u32::try_from(self.next_u64() >> 32).unwrap()
+  }
+  fn next_u64(&mut self) -> u64 {
+    self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+    let mut z = self.0;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+  }
+  fn fill_bytes(&mut self, dest: &mut [u8]) {
+    for chunk in dest.chunks_mut(8) {
+      chunk.copy_from_slice(&self.next_u64().to_le_bytes()[.. chunk.len()]);
+    }
+  }
+  fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+    self.fill_bytes(dest);
+    Ok(())
+  }
+}
+// Solely so tests may generate keys from a seed. This MUST NOT be used outside of tests.
+impl CryptoRng for SimRng {}
+
+/// The conditions of every link within a simulated network.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct LinkConditions {
+  /// The minimum time a message takes to be delivered.
+  pub latency: Duration,
+  /// The maximum additional time a message may take to be delivered, chosen uniformly per message.
+  pub jitter: Duration,
+  /// The amount of messages, out of a million, which are dropped.
+  pub loss: u32,
+}
+
+impl LinkConditions {
+  /// Perfect links, delivering every message immediately.
+  pub const PERFECT: LinkConditions =
+    LinkConditions { latency: Duration::ZERO, jitter: Duration::ZERO, loss: 0 };
+}
+
+type InFlight = BTreeMap<(Duration, u64), (usize, P2pMessageKind, Vec<u8>)>;
+
+#[derive(Debug)]
+struct SimState {
+  rng: SimRng,
+  conditions: LinkConditions,
+  // The partition each node is within, with nodes solely able to reach nodes within their partition
+  partitions: Vec<usize>,
+  // The messages in flight to each node, by when they'll be delivered and the order they were sent
+  in_flight: Vec<InFlight>,
+  sent: u64,
+  // The gossip each node has been sent, so rebroadcasts of a message don't loop
+  seen: HashSet<(usize, Vec<u8>)>,
+  dropped: u64,
+}
+
+/// A simulated network, delivering messages between nodes per the simulated clock.
+///
+/// Every decision the network makes (if a message is dropped, and how long it takes to deliver)
+/// is drawn from an RNG seeded by the test, and messages are delivered in order of when they're
+/// due, so delivery is deterministic for a given seed and sequence of sends.
+#[derive(Clone, Debug)]
+pub struct SimNetwork {
+  clock: TestClock,
+  state: Arc<Mutex<SimState>>,
+  notify: Arc<Notify>,
+}
+
+impl SimNetwork {
+  pub fn new(seed: u64, nodes: usize, conditions: LinkConditions) -> SimNetwork {
+    SimNetwork {
+      clock: TestClock::new(SystemClock.now()),
+      state: Arc::new(Mutex::new(SimState {
+        rng: SimRng::new(seed),
+        conditions,
+        partitions: vec![0; nodes],
+        in_flight: vec![BTreeMap::new(); nodes],
+        sent: 0,
+        seen: HashSet::new(),
+        dropped: 0,
+      })),
+      notify: Arc::new(Notify::new()),
+    }
+  }
+
+  /// The clock messages are delivered by.
+  pub fn clock(&self) -> &TestClock {
+    &self.clock
+  }
+
+  /// The handle for a node to use this network with.
+  pub fn p2p(&self, id: usize) -> SimP2p {
+    assert!(id < self.state.lock().unwrap().partitions.len());
+    SimP2p { id, network: self.clone() }
+  }
+
+  /// Set the conditions of every link.
+  pub fn set_conditions(&self, conditions: LinkConditions) {
+    self.state.lock().unwrap().conditions = conditions;
+  }
+
+  /// Partition the network, with nodes solely able to reach nodes within their group.
+  ///
+  /// Nodes not within any group are isolated from every other node. Messages already in flight
+  /// are still delivered.
+  pub fn partition(&self, groups: &[&[usize]]) {
+    let mut state = self.state.lock().unwrap();
+    let nodes = state.partitions.len();
+    for (i, partition) in state.partitions.iter_mut().enumerate() {
+      *partition = groups.iter().position(|group| group.contains(&i)).unwrap_or(nodes + i);
+    }
+  }
+
+  /// Isolate a node from every other node.
+  pub fn isolate(&self, node: usize) {
+    let mut state = self.state.lock().unwrap();
+    let nodes = state.partitions.len();
+    state.partitions[node] = nodes + node;
+  }
+
+  /// Heal any partitions, letting every node reach every other node.
+  pub fn heal(&self) {
+    for partition in &mut self.state.lock().unwrap().partitions {
+      *partition = 0;
+    }
+  }
+
+  /// The amount of messages dropped, whether due to loss or partitions.
+  pub fn dropped(&self) -> u64 {
+    self.state.lock().unwrap().dropped
+  }
+
+  fn route(&self, from: usize, to: usize, kind: P2pMessageKind, msg: Vec<u8>) {
+    {
+      let mut state = self.state.lock().unwrap();
+      let gossip = matches!(kind, P2pMessageKind::Gossip(_));
+      if gossip && !state.seen.insert((to, msg.clone())) {
+        return;
+      }
+
+      let conditions = state.conditions;
+      let lost = (state.rng.next_u64() % 1_000_000) < u64::from(conditions.loss);
+      if lost || (state.partitions[from] != state.partitions[to]) {
+        // Allow this message to be retried
+        state.seen.remove(&(to, msg));
+        state.dropped += 1;
+        return;
+      }
+
+      let jitter = u64::try_from(conditions.jitter.as_micros()).unwrap();
+      let jitter = Duration::from_micros(state.rng.next_u64() % (jitter + 1));
+      let deliver_at = self.clock.now() + conditions.latency + jitter;
+
+      let sent = state.sent;
+      state.sent += 1;
+      state.in_flight[to].insert((deliver_at, sent), (from, kind, msg));
+    }
+    self.notify.notify_waiters();
+  }
+}
+
+/// A node's handle to a `SimNetwork`.
+#[derive(Clone, Debug)]
+pub struct SimP2p {
+  id: usize,
+  network: SimNetwork,
+}
+
+#[async_trait]
+impl P2p for SimP2p {
+  type Id = usize;
+
+  async fn subscribe(&self, _set: ExternalValidatorSet, _genesis: [u8; 32]) {}
+  async fn unsubscribe(&self, _set: ExternalValidatorSet, _genesis: [u8; 32]) {}
+
+  async fn send_raw(&self, to: Self::Id, msg: Vec<u8>) {
+    let mut msg_ref = msg.as_slice();
+    let kind = ReqResMessageKind::read(&mut msg_ref).unwrap();
+    self.network.route(self.id, to, P2pMessageKind::ReqRes(kind), msg_ref.to_vec());
+  }
+
+  async fn broadcast_raw(&self, kind: P2pMessageKind, msg: Vec<u8>) {
+    let kind_len = (match kind {
+      P2pMessageKind::ReqRes(kind) => kind.serialize(),
+      P2pMessageKind::Gossip(kind) => kind.serialize(),
+    })
+    .len();
+    let msg = &msg[kind_len ..];
+
+    let nodes = self.network.state.lock().unwrap().partitions.len();
+    for to in 0 .. nodes {
+      if to != self.id {
+        self.network.route(self.id, to, kind, msg.to_vec());
+      }
+    }
+  }
+
+  async fn receive(&self) -> P2pMessage<Self> {
+    loop {
+      // Create the notification before checking for messages so a send in between isn't missed
+      let notified = self.network.notify.notified();
+      let due_in = {
+        let mut state = self.network.state.lock().unwrap();
+        let now = self.network.clock.now();
+        let in_flight = &mut state.in_flight[self.id];
+        match in_flight.first_key_value().map(|((deliver_at, _), _)| *deliver_at) {
+          Some(deliver_at) if deliver_at <= now => {
+            let (_, (sender, kind, msg)) = in_flight.pop_first().unwrap();
+            return P2pMessage { sender, kind, msg };
+          }
+          Some(deliver_at) => Some(deliver_at.saturating_sub(now)),
+          None => None,
+        }
+      };
+
+      if let Some(due_in) = due_in {
+        tokio::select! {
+          () = notified => {},
+          () = self.network.clock.sleep(due_in) => {},
+        }
+      } else {
+        notified.await;
+      }
+    }
+  }
+
+  async fn peers(&self, _network: ExternalNetworkId) -> usize {
+    let state = self.network.state.lock().unwrap();
+    let ours = state.partitions[self.id];
+    // Exclude ourselves
+    state.partitions.iter().filter(|partition| **partition == ours).count() - 1
+  }
+
+  async fn transports(&self) -> HashMap<P2pTransport, TransportMetrics> {
+    HashMap::new()
+  }
+}
+
+#[async_trait]
+impl TributaryP2p for SimP2p {
+  async fn broadcast(&self, genesis: [u8; 32], msg: Vec<u8>) {
+    <Self as P2p>::broadcast(
+      self,
+      P2pMessageKind::Gossip(GossipMessageKind::Tributary(genesis)),
+      msg,
+    )
+    .await
+  }
+}
+
+/// A coordinator within a `Simulation`.
+pub struct SimNode {
+  pub key: Zeroizing<<Ristretto as Ciphersuite>::F>,
+  pub tributary: Arc<Tributary<MemDb, Transaction, SimP2p>>,
+}
+
+/// A set of coordinators running a Tributary over a `SimNetwork`.
+///
+/// Everything the harness chooses, from the validators' keys to the network's conditions, is
+/// derived from the seed. Tendermint times its rounds by `tokio`'s clock, which the simulation
+/// pauses, so time only passes within `run_for`, which advances the simulated clock in step with
+/// it. Tests may also advance the simulated clock themselves to deliver messages early.
+///
+/// As `tokio`'s clock may only be paused within a current-thread runtime, simulations must be run
+/// within one (as `#[tokio::test]` is by default).
+pub struct Simulation {
+  pub network: SimNetwork,
+  pub spec: TributarySpec,
+  pub nodes: Vec<SimNode>,
+}
+
+impl Simulation {
+  pub async fn new(seed: u64, nodes: usize, conditions: LinkConditions) -> Simulation {
+    tokio::time::pause();
+
+    let mut rng = SimRng::new(seed);
+    let keys = new_keys_for(&mut rng, nodes);
+    let spec = new_spec(&mut rng, &keys);
+    let network = SimNetwork::new(rng.next_u64(), nodes, conditions);
+
+    let mut res = vec![];
+    for (i, key) in keys.into_iter().enumerate() {
+      let p2p = network.p2p(i);
+      let tributary = Arc::new(
+        Tributary::<_, Transaction, _>::new(
+          MemDb::new(),
+          spec.genesis(),
+          spec.start_time(),
+          key.clone(),
+          spec.validators(),
+          spec.proposer_schedule(),
+          p2p.clone(),
+        )
+        .await
+        .unwrap(),
+      );
+
+      // Handle the Tributary's messages as they're delivered
+      tokio::spawn({
+        let tributary = tributary.clone();
+        async move {
+          loop {
+            let msg = p2p.receive().await;
+            if let P2pMessageKind::Gossip(GossipMessageKind::Tributary(genesis)) = msg.kind {
+              assert_eq!(genesis, tributary.genesis());
+              let validity = tributary.handle_message(&msg.msg).await;
+              p2p.report_validity(msg, validity).await;
+            }
+          }
+        }
+      });
+
+      res.push(SimNode { key, tributary });
+    }
+
+    Simulation { network, spec, nodes: res }
+  }
+
+  /// Run the simulation for the specified duration.
+  ///
+  /// As `tokio`'s clock is paused, each step's sleep only completes once every node is idle, at
+  /// which point the clock jumps ahead, so no real time is spent waiting.
+  pub async fn run_for(&self, duration: Duration) {
+    let mut ran = Duration::ZERO;
+    while ran < duration {
+      self.network.clock.advance(STEP);
+      tokio::time::sleep(STEP).await;
+      ran += STEP;
+    }
+  }
+
+  /// The amount of blocks each node has.
+  pub fn block_numbers(&self) -> Vec<u64> {
+    self.nodes.iter().map(|node| node.tributary.reader().block_number()).collect()
+  }
+}
@@ -0,0 +1,116 @@
+use core::time::Duration;
+use std::{fs, path::PathBuf};
+
+use rand_core::{RngCore, OsRng};
+
+use tokio::time::sleep;
+
+use serai_db::{DbTxn, Db, MemDb};
+
+use ::tributary::Tributary;
+
+use crate::{
+  ActiveTributaryDb,
+  tributary::Transaction,
+  snapshot::{export, import, export_tributary, import_tributary},
+  tests::{
+    LocalP2p,
+    tributary::{new_keys, new_spec, new_tributaries, run_tributaries},
+  },
+};
+
+fn snapshot_path() -> PathBuf {
+  std::env::temp_dir().join(format!("serai-coordinator-snapshot-{}", OsRng.next_u64()))
+}
+
+#[test]
+fn snapshot() {
+  let mut db = MemDb::new();
+  let mut txn = db.txn();
+  for i in 0 .. 2000u32 {
+    let mut value = vec![0; usize::try_from(OsRng.next_u32() % 128).unwrap()];
+    OsRng.fill_bytes(&mut value);
+    txn.put(i.to_le_bytes(), value);
+  }
+  txn.put([], []);
+  txn.commit();
+
+  let path = snapshot_path();
+  assert_eq!(export(&db, &path).unwrap(), 2001);
+
+  let mut imported = MemDb::new();
+  assert_eq!(import(&mut imported, &path).unwrap(), 2001);
+  assert_eq!(imported, db);
+
+  // Importing into a non-empty database should fail
+  assert!(import(&mut imported, &path).is_err());
+
+  // As should importing a corrupted snapshot, without importing anything
+  let mut snapshot = fs::read(&path).unwrap();
+  let i = usize::try_from(OsRng.next_u64() % u64::try_from(snapshot.len()).unwrap()).unwrap();
+  snapshot[i] ^= 1;
+  fs::write(&path, &snapshot).unwrap();
+  let mut corrupted = MemDb::new();
+  assert!(import(&mut corrupted, &path).is_err());
+  assert_eq!(corrupted, MemDb::new());
+
+  // As should importing a truncated snapshot
+  fs::write(&path, &snapshot[.. (snapshot.len() - 1)]).unwrap();
+  assert!(import(&mut corrupted, &path).is_err());
+  assert_eq!(corrupted, MemDb::new());
+
+  fs::remove_file(path).unwrap();
+}
+
+#[tokio::test]
+async fn tributary_snapshot() {
+  let keys = new_keys(&mut OsRng);
+  let spec = new_spec(&mut OsRng, &keys);
+  let genesis = spec.genesis();
+
+  let tributaries = new_tributaries(&keys, &spec).await;
+  let db = tributaries[0].0.clone();
+  let reader = tributaries[0].2.reader();
+  tokio::spawn(run_tributaries(
+    tributaries.into_iter().map(|(_, p2p, tributary)| (p2p, tributary)).collect(),
+  ));
+
+  let block_time = u64::from(Tributary::<MemDb, Transaction, LocalP2p>::block_time());
+  while reader.block_number() < 2 {
+    sleep(Duration::from_secs(block_time)).await;
+  }
+
+  let path = snapshot_path();
+  let blocks = export_tributary(&db, genesis, &path).unwrap();
+  assert!(blocks >= 2);
+
+  // The Tributary can't be imported if it isn't active
+  let mut imported = MemDb::new();
+  assert!(import_tributary(&mut imported, genesis, &path).is_err());
+
+  let mut txn = imported.txn();
+  ActiveTributaryDb::add_participating_in_tributary(&mut txn, &spec);
+  txn.commit();
+  assert_eq!(import_tributary(&mut imported, genesis, &path).unwrap(), blocks);
+
+  // The imported Tributary should resume from the export's tip
+  let imported = Tributary::<_, Transaction, _>::new(
+    imported,
+    genesis,
+    spec.start_time(),
+    keys[0].clone(),
+    spec.validators(),
+    spec.proposer_schedule(),
+    LocalP2p::new(1).remove(0),
+  )
+  .await
+  .unwrap();
+  assert_eq!(imported.block_number().await, blocks);
+  let imported_reader = imported.reader();
+  let tip = imported_reader.tip();
+  assert_eq!(Some(tip), reader.block_hash(blocks));
+  assert_eq!(imported_reader.block(&tip), reader.block(&tip));
+  assert_eq!(imported_reader.commit(&tip), reader.commit(&tip));
+
+  fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,181 @@
+use std::{sync::Arc, collections::HashMap};
+
+use rand_core::OsRng;
+
+use ciphersuite::{group::GroupEncoding, Ciphersuite, Ristretto};
+
+use serde_json::json;
+
+use hyper::header::{AUTHORIZATION, HeaderMap, HeaderValue};
+
+use serai_client::{
+  primitives::ExternalNetworkId,
+  validator_sets::primitives::{Session, ExternalValidatorSet, KeyPair},
+};
+
+use processor_messages::coordinator::SubstrateSignableId;
+
+use serai_db::{DbTxn, Db, MemDb};
+
+use crate::{
+  ActiveTributary, LastVerifiedBatchDb,
+  p2p::CosignedBlock,
+  cosign_evaluator::{LatestCosign, DistinctCosign},
+  status::{pending, status, metrics, authenticated},
+  tributary::{
+    Topic, AttemptDb, SeraiDkgCompleted, DkgKeyPair, DkgLocallyCompleted, CompletedPlans,
+    SlashReport, Heartbeats,
+  },
+  tests::tributary::{new_keys, new_spec, new_tributaries},
+};
+
+fn set() -> ExternalValidatorSet {
+  ExternalValidatorSet { network: ExternalNetworkId::Bitcoin, session: Session(0) }
+}
+
+#[test]
+fn pending_topics() {
+  let mut db = MemDb::new();
+  let set = set();
+  let genesis = [0xff; 32];
+
+  // The DKG is pending until its key is generated, and then until it's confirmed
+  assert!(pending(&db, set, genesis, Topic::Dkg));
+  assert!(pending(&db, set, genesis, Topic::DkgConfirmation));
+  let mut txn = db.txn();
+  let key_pair = KeyPair(serai_client::Public([0; 32]), vec![0; 32].try_into().unwrap());
+  DkgKeyPair::set(&mut txn, genesis, 0, &key_pair);
+  txn.commit();
+  assert!(!pending(&db, set, genesis, Topic::Dkg));
+  assert!(pending(&db, set, genesis, Topic::DkgConfirmation));
+
+  // A new attempt needs its own key
+  let mut txn = db.txn();
+  AttemptDb::start_next_attempt(&mut txn, genesis, Topic::Dkg);
+  txn.commit();
+  assert!(pending(&db, set, genesis, Topic::Dkg));
+
+  let mut txn = db.txn();
+  DkgLocallyCompleted::set(&mut txn, genesis, &());
+  txn.commit();
+  assert!(!pending(&db, set, genesis, Topic::DkgConfirmation));
+
+  // Once the key is set on Serai, the DKG is complete regardless of the current attempt
+  let mut txn = db.txn();
+  SeraiDkgCompleted::set(&mut txn, set, &[0; 32]);
+  txn.commit();
+  assert!(!pending(&db, set, genesis, Topic::Dkg));
+
+  // Batches are complete once published
+  let batch = |id| Topic::SubstrateSign(SubstrateSignableId::Batch(id));
+  assert!(pending(&db, set, genesis, batch(0)));
+  let mut txn = db.txn();
+  LastVerifiedBatchDb::set(&mut txn, set.network, &1);
+  txn.commit();
+  assert!(!pending(&db, set, genesis, batch(0)));
+  assert!(!pending(&db, set, genesis, batch(1)));
+  assert!(pending(&db, set, genesis, batch(2)));
+
+  // Slash reports are complete once the report is decided
+  let slash_report = Topic::SubstrateSign(SubstrateSignableId::SlashReport);
+  assert!(pending(&db, set, genesis, slash_report));
+  let mut txn = db.txn();
+  SlashReport::set(&mut txn, set, &vec![]);
+  txn.commit();
+  assert!(!pending(&db, set, genesis, slash_report));
+
+  // Plans are complete once completed
+  assert!(pending(&db, set, genesis, Topic::Sign([1; 32])));
+  let mut txn = db.txn();
+  CompletedPlans::set(&mut txn, genesis, &vec![([1; 32], vec![])]);
+  txn.commit();
+  assert!(!pending(&db, set, genesis, Topic::Sign([1; 32])));
+  assert!(pending(&db, set, genesis, Topic::Sign([2; 32])));
+}
+
+#[test]
+fn authentication() {
+  let headers = |value: &str| {
+    let mut headers = HeaderMap::new();
+    headers.insert(AUTHORIZATION, HeaderValue::from_str(value).unwrap());
+    headers
+  };
+  assert!(authenticated("key", &headers("Bearer key")));
+  assert!(!authenticated("key", &headers("Bearer other")));
+  assert!(!authenticated("key", &headers("key")));
+  assert!(!authenticated("key", &headers("Bearer ")));
+  assert!(!authenticated("key", &HeaderMap::new()));
+}
+
+#[tokio::test]
+async fn status_and_metrics() {
+  let keys = new_keys(&mut OsRng);
+  let spec = new_spec(&mut OsRng, &keys);
+  let (mut db, p2p, tributary) = new_tributaries(&keys, &spec).await.swap_remove(0);
+  let set = spec.set();
+  let genesis = spec.genesis();
+  let validator = (<Ristretto as Ciphersuite>::generator() * *keys[0]).to_bytes();
+
+  let cosign = |block| CosignedBlock {
+    network: set.network,
+    block_number: 5,
+    block: [block; 32],
+    signature: [0; 64],
+  };
+  let mut txn = db.txn();
+  Heartbeats::set(&mut txn, genesis, validator, &3);
+  LastVerifiedBatchDb::set(&mut txn, set.network, &2);
+  LatestCosign::set(&mut txn, set.network, &cosign(1));
+  DistinctCosign::set(&mut txn, set.network, &cosign(2));
+  txn.commit();
+
+  let tributary = ActiveTributary { spec, tributary: Arc::new(tributary) };
+  let tributaries = HashMap::from([(set, tributary)]);
+  let status = status(&db, &p2p, &tributaries).await;
+
+  let tributary = &status["tributaries"][0];
+  assert_eq!(tributary["network"], json!("Bitcoin"));
+  assert_eq!(tributary["session"], json!(0));
+  assert_eq!(tributary["genesis"], json!(hex::encode(genesis)));
+  // The DKG is reported as pending despite not being explicitly recognized
+  assert_eq!(
+    tributary["signing"],
+    json!([{ "kind": "dkg", "attempt": 0 }, { "kind": "dkg_confirmation", "attempt": 0 }])
+  );
+  assert_eq!(tributary["queue"], json!([]));
+
+  // Every validator is reported, and presumed online absent a full window without heartbeats
+  let validators = tributary["validators"].as_array().unwrap();
+  assert_eq!(validators.len(), keys.len());
+  assert_eq!(validators[0]["validator"], json!(hex::encode(validator)));
+  assert_eq!(validators[0]["heartbeats"], json!(3));
+  assert!(validators.iter().all(|validator| validator["online"] == json!(true)));
+  assert!(validators.iter().all(|validator| validator["mempool_violations"] == json!(0)));
+
+  let network = &status["networks"]["Bitcoin"];
+  assert_eq!(network["peers"], json!(keys.len() - 1));
+  assert_eq!(network["last_published_batch"], json!(2));
+  assert_eq!(network["latest_cosign"], json!({ "block_number": 5, "block": hex::encode([1; 32]) }));
+  assert_eq!(
+    network["distinct_cosign"],
+    json!({ "block_number": 5, "block": hex::encode([2; 32]) })
+  );
+  assert_eq!(status["networks"]["Ethereum"]["latest_cosign"], json!(null));
+
+  // Every transport is reported, even those without connections
+  for transport in ["tcp", "quic", "tor", "relayed"] {
+    assert_eq!(
+      status["transports"][transport],
+      json!({ "connections": 0, "established": 0, "failed_dials": 0 })
+    );
+  }
+
+  let metrics = metrics(&p2p, &tributaries).await;
+  let labels = "network=\"Bitcoin\",session=\"0\"";
+  assert!(metrics.contains("# TYPE p2p_connections gauge\n"));
+  assert!(metrics.contains("p2p_connections{transport=\"quic\"} 0\n"));
+  assert!(metrics.contains("# TYPE tributary_blocks_total counter\n"));
+  assert!(metrics.contains(&format!("tributary_blocks_total{{{labels}}} 0\n")));
+  assert!(metrics.contains(&format!("tributary_mempool_transactions{{{labels}}} 0\n")));
+  assert!(metrics.contains("# TYPE tributary_proposal_latency_seconds summary\n"));
+}
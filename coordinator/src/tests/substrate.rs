@@ -0,0 +1,41 @@
+use serai_client::{SeraiError, RuntimeMismatch};
+
+use crate::substrate::compatible;
+
+fn check(mismatches: &[RuntimeMismatch]) -> Result<Vec<RuntimeMismatch>, SeraiError> {
+  compatible(Err(SeraiError::IncompatibleRuntime(mismatches.to_vec())))
+}
+
+#[test]
+fn compatible_runtimes() {
+  assert_eq!(compatible(Ok(())).unwrap(), vec![]);
+
+  // Differing versions alone are compatible
+  let versions = [
+    RuntimeMismatch::SpecVersion { expected: 1, found: 2 },
+    RuntimeMismatch::TransactionVersion { expected: 1, found: 2 },
+  ];
+  assert_eq!(check(&versions).unwrap(), versions);
+
+  // Changes to the calls we build or the events we decode aren't, with solely those reported
+  let changes = [
+    RuntimeMismatch::EventIndex {
+      pallet: "InInstructions".to_string(),
+      event: "Batch".to_string(),
+      expected: 0,
+      found: 1,
+    },
+    RuntimeMismatch::CallFields {
+      pallet: "InInstructions".to_string(),
+      call: "execute_batch".to_string(),
+    },
+  ];
+  let Err(SeraiError::IncompatibleRuntime(mismatches)) = check(&[&versions[..], &changes].concat())
+  else {
+    panic!("runtime with changed calls and events was considered compatible")
+  };
+  assert_eq!(mismatches, changes);
+
+  // Other errors are passed through
+  assert!(matches!(compatible(Err(SeraiError::ConnectionError)), Err(SeraiError::ConnectionError)));
+}
@@ -0,0 +1,87 @@
+use tokio::{
+  io::{AsyncReadExt, AsyncWriteExt},
+  net::TcpListener,
+};
+
+use libp2p::Multiaddr;
+
+use crate::tor::{is_onion, onion_host, socks5_connect};
+
+const ONION: &str = "vww6ybal4bd7szmgncyruucpgfkqahzddi37ktceo3ah7ngmcopnpyyd";
+
+#[test]
+fn onion_addresses() {
+  let addr = |addr: &str| addr.parse::<Multiaddr>().unwrap();
+
+  let onion = addr(&format!("/onion3/{ONION}:30563"));
+  assert!(is_onion(&onion));
+  assert_eq!(onion_host(&onion).unwrap(), (format!("{ONION}.onion"), 30563));
+
+  // Onion addresses with further protocols aren't dialable
+  let with_peer = addr(&format!(
+    "/onion3/{ONION}:30563/p2p/12D3KooWD3eckifWpRn9wQpMG9R9hX3sD158z7EqHWmweQAJU5SA"
+  ));
+  assert!(is_onion(&with_peer));
+  assert!(onion_host(&with_peer).is_none());
+
+  let tcp = addr("/ip4/1.2.3.4/tcp/30563");
+  assert!(!is_onion(&tcp));
+  assert!(onion_host(&tcp).is_none());
+}
+
+#[tokio::test]
+async fn socks5() {
+  let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+  let proxy = listener.local_addr().unwrap();
+
+  // A SOCKS5 proxy which accepts a single connection, replying with a domain name as its bound
+  // address to check we read past it, before relaying a message from the destination
+  let server = tokio::spawn(async move {
+    let (mut stream, _) = listener.accept().await.unwrap();
+
+    let mut greeting = [0; 3];
+    stream.read_exact(&mut greeting).await.unwrap();
+    assert_eq!(greeting, [5, 1, 0]);
+    stream.write_all(&[5, 0]).await.unwrap();
+
+    let mut request = [0; 5];
+    stream.read_exact(&mut request).await.unwrap();
+    assert_eq!(request[.. 4], [5, 1, 0, 3]);
+    let mut host = vec![0; usize::from(request[4])];
+    stream.read_exact(&mut host).await.unwrap();
+    let mut port = [0; 2];
+    stream.read_exact(&mut port).await.unwrap();
+
+    let mut reply = vec![5, 0, 0, 3, 9];
+    reply.extend(b"localhost");
+    reply.extend([0, 0]);
+    stream.write_all(&reply).await.unwrap();
+    stream.write_all(b"hello").await.unwrap();
+
+    (String::from_utf8(host).unwrap(), u16::from_be_bytes(port))
+  });
+
+  let host = format!("{ONION}.onion");
+  let mut stream = socks5_connect(proxy, host.clone(), 30563).await.unwrap();
+  let mut message = [0; 5];
+  stream.read_exact(&mut message).await.unwrap();
+  assert_eq!(&message, b"hello");
+  assert_eq!(server.await.unwrap(), (host, 30563));
+
+  // Proxies which fail to connect are reported as errors
+  let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+  let proxy = listener.local_addr().unwrap();
+  tokio::spawn(async move {
+    let (mut stream, _) = listener.accept().await.unwrap();
+    let mut greeting = [0; 3];
+    stream.read_exact(&mut greeting).await.unwrap();
+    stream.write_all(&[5, 0]).await.unwrap();
+    let mut request = [0; 5];
+    stream.read_exact(&mut request).await.unwrap();
+    let mut rest = vec![0; usize::from(request[4]) + 2];
+    stream.read_exact(&mut rest).await.unwrap();
+    // Host unreachable
+    stream.write_all(&[5, 4, 0, 1, 0, 0, 0, 0, 0, 0]).await.unwrap();
+  });
+  assert!(socks5_connect(proxy, format!("{ONION}.onion"), 30563).await.is_err());
+}
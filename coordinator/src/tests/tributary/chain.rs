@@ -16,31 +16,38 @@ use sp_application_crypto::sr25519;
 use borsh::BorshDeserialize;
 use serai_client::{
   primitives::ExternalNetworkId,
-  validator_sets::primitives::{ExternalValidatorSet, Session},
+  validator_sets::primitives::{ExternalValidatorSet, Session, AttemptWindow},
 };
 
 use tokio::time::sleep;
 
-use serai_db::MemDb;
+use serai_db::{DbTxn, Db, MemDb};
 
-use tributary::Tributary;
+use tributary::{tendermint::ProposerSchedule, Tributary};
 
 use crate::{
-  GossipMessageKind, P2pMessageKind, P2p,
+  ActiveTributaryDb, ActiveTributaryVersionDb, GossipMessageKind, P2pMessageKind, P2p,
   tributary::{Transaction, TributarySpec},
   tests::LocalP2p,
 };
 
-pub fn new_keys<R: RngCore + CryptoRng>(
+pub fn new_keys_for<R: RngCore + CryptoRng>(
   rng: &mut R,
+  validators: usize,
 ) -> Vec<Zeroizing<<Ristretto as Ciphersuite>::F>> {
   let mut keys = vec![];
-  for _ in 0 .. 5 {
+  for _ in 0 .. validators {
     keys.push(Zeroizing::new(<Ristretto as Ciphersuite>::F::random(&mut *rng)));
   }
   keys
 }
 
+pub fn new_keys<R: RngCore + CryptoRng>(
+  rng: &mut R,
+) -> Vec<Zeroizing<<Ristretto as Ciphersuite>::F>> {
+  new_keys_for(rng, 5)
+}
+
 pub fn new_spec<R: RngCore + CryptoRng>(
   rng: &mut R,
   keys: &[Zeroizing<<Ristretto as Ciphersuite>::F>],
@@ -57,7 +64,8 @@ pub fn new_spec<R: RngCore + CryptoRng>(
     .map(|key| (sr25519::Public((<Ristretto as Ciphersuite>::generator() * **key).to_bytes()), 1))
     .collect::<Vec<_>>();
 
-  let res = TributarySpec::new(serai_block, start_time, set, set_participants);
+  let res =
+    TributarySpec::new(serai_block, start_time, set, set_participants, AttemptWindow::DEFAULT);
   assert_eq!(
     TributarySpec::deserialize_reader(&mut borsh::to_vec(&res).unwrap().as_slice()).unwrap(),
     res,
@@ -82,6 +90,7 @@ pub async fn new_tributaries(
         spec.start_time(),
         key.clone(),
         spec.validators(),
+        spec.proposer_schedule(),
         p2p[i].clone(),
       )
       .await
@@ -100,9 +109,8 @@ pub async fn run_tributaries(
         match msg.kind {
           P2pMessageKind::Gossip(GossipMessageKind::Tributary(genesis)) => {
             assert_eq!(genesis, tributary.genesis());
-            if tributary.handle_message(&msg.msg).await {
-              p2p.broadcast(msg.kind, msg.msg).await;
-            }
+            let validity = tributary.handle_message(&msg.msg).await;
+            p2p.report_validity(msg, validity).await;
           }
           _ => panic!("unexpected p2p message found"),
         }
@@ -235,3 +243,55 @@ async fn tributary_test() {
   }
   panic!("tributary had different tip with a variance exceeding one block");
 }
+
+#[test]
+fn migrate_legacy_specs() {
+  let keys = new_keys(&mut OsRng);
+  let specs = [new_spec(&mut OsRng, &keys), new_spec(&mut OsRng, &keys)];
+
+  // The legacy encoding lacked the trailing attempt window and protocol version, while the first
+  // versioned encoding solely lacked the protocol version
+  let encoded_attempt_window_len = borsh::to_vec(&Some(AttemptWindow::DEFAULT)).unwrap().len();
+  let mut legacy = vec![];
+  let mut unversioned = vec![];
+  for spec in &specs {
+    let encoded = borsh::to_vec(spec).unwrap();
+    legacy.extend(&encoded[.. (encoded.len() - 1 - encoded_attempt_window_len)]);
+    unversioned.extend(&encoded[.. (encoded.len() - 1)]);
+  }
+
+  for (version, encoded) in [(None, legacy), (Some(1), unversioned)] {
+    let mut db = MemDb::new();
+    let mut txn = db.txn();
+    ActiveTributaryDb::set(&mut txn, &encoded);
+    if let Some(version) = version {
+      ActiveTributaryVersionDb::set(&mut txn, &version);
+    }
+    ActiveTributaryDb::migrate(&mut txn);
+    txn.commit();
+
+    let migrated = ActiveTributaryDb::active_tributaries(&db).1;
+    assert_eq!(migrated.len(), specs.len());
+    for (migrated, spec) in migrated.iter().zip(&specs) {
+      assert_eq!(migrated.set(), spec.set());
+      assert_eq!(migrated.start_time(), spec.start_time());
+      assert_eq!(migrated.validators(), spec.validators());
+      assert_eq!(migrated.attempt_window(), AttemptWindow::DEFAULT);
+      // Existing Tributaries keep their proposer schedule and DKG re-attempts
+      assert_eq!(migrated.proposer_schedule(), ProposerSchedule::Shuffled);
+      assert!(!migrated.dkg_attempts_time_out());
+      // And their genesis, which didn't bind to the fields they lacked
+      assert!(migrated.genesis() != spec.genesis());
+    }
+
+    // Migrating again is a no-op
+    let mut txn = db.txn();
+    ActiveTributaryDb::migrate(&mut txn);
+    txn.commit();
+    assert_eq!(ActiveTributaryDb::active_tributaries(&db).1, migrated);
+  }
+
+  // New Tributaries spread their proposers and time out stalled DKG attempts
+  assert_eq!(specs[0].proposer_schedule(), ProposerSchedule::Spread);
+  assert!(specs[0].dkg_attempts_time_out());
+}
@@ -8,6 +8,7 @@ use tokio::{
   time::sleep,
 };
 
+use serai_clock::SystemClock;
 use serai_db::MemDb;
 
 use tributary::Tributary;
@@ -40,7 +41,7 @@ async fn handle_p2p_test() {
     tributary_arcs.push(tributary.clone());
     let (new_tributary_send, new_tributary_recv) = broadcast::channel(5);
     let (cosign_send, _) = mpsc::unbounded_channel();
-    tokio::spawn(handle_p2p_task(p2p, cosign_send, new_tributary_recv));
+    tokio::spawn(handle_p2p_task(SystemClock, p2p, cosign_send, new_tributary_recv));
     new_tributary_send
       .send(TributaryEvent::NewTributary(ActiveTributary { spec: spec.clone(), tributary }))
       .map_err(|_| "failed to send ActiveTributary")
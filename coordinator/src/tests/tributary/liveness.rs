@@ -0,0 +1,31 @@
+use serai_db::{DbTxn, Db, MemDb};
+
+use crate::tributary::{
+  HeartbeatsActivated, LastHeartbeat,
+  liveness::{LIVENESS_WINDOW, offline},
+};
+
+#[test]
+fn liveness_window() {
+  let mut db = MemDb::new();
+  let genesis = [0xff; 32];
+  let validator = [0; 32];
+
+  // Until heartbeats activate, no one is offline, no matter how many blocks have passed
+  assert!(!offline(&db, genesis, validator, 10 * LIVENESS_WINDOW));
+
+  // Once activated, validators have a full window from then to publish their first heartbeat
+  let activated = 5 * LIVENESS_WINDOW;
+  let mut txn = db.txn();
+  HeartbeatsActivated::set(&mut txn, genesis, &activated);
+  txn.commit();
+  assert!(!offline(&db, genesis, validator, activated + LIVENESS_WINDOW));
+  assert!(offline(&db, genesis, validator, activated + LIVENESS_WINDOW + 1));
+
+  // And a full window after every heartbeat
+  let mut txn = db.txn();
+  LastHeartbeat::set(&mut txn, genesis, validator, &(activated + LIVENESS_WINDOW));
+  txn.commit();
+  assert!(!offline(&db, genesis, validator, activated + (2 * LIVENESS_WINDOW)));
+  assert!(offline(&db, genesis, validator, activated + (2 * LIVENESS_WINDOW) + 1));
+}
@@ -11,7 +11,10 @@ use serai_client::{
 };
 use processor_messages::coordinator::SubstrateSignableId;
 
-use tributary::{ReadWrite, tests::random_signed_with_nonce};
+use tributary::{
+  ReadWrite,
+  tests::{random_signed, random_signed_with_nonce},
+};
 
 use crate::tributary::{Label, SignData, Transaction, scanner::PublishSeraiTransaction};
 
@@ -26,6 +29,12 @@ mod dkg;
 mod handle_p2p;
 mod sync;
 
+mod simulation;
+
+mod reattempt;
+
+mod liveness;
+
 #[async_trait::async_trait]
 impl PublishSeraiTransaction for () {
   async fn publish_set_keys(
@@ -290,4 +299,16 @@ fn serialize_transaction() {
     },
     random_signed_with_nonce(&mut OsRng, 0),
   ));
+
+  {
+    let mut hash = [0; 32];
+    OsRng.fill_bytes(&mut hash);
+    test_read_write(&Transaction::SessionSummary(hash, random_signed_with_nonce(&mut OsRng, 0)));
+  }
+
+  {
+    let period = OsRng.next_u32();
+    // Heartbeats have incrementing nonces, which are serialized
+    test_read_write(&Transaction::Heartbeat(period, random_signed(&mut OsRng)));
+  }
 }
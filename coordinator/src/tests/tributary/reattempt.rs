@@ -0,0 +1,71 @@
+use serai_client::validator_sets::primitives::AttemptWindow;
+
+use serai_db::{DbTxn, Db, MemDb};
+
+use crate::tributary::{
+  Topic, AttemptDb, ReattemptDb, ScheduledReattempt, StalledAttempts, MAX_STALL_BACKOFF,
+};
+
+#[test]
+fn stalled_reattempts() {
+  let mut db = MemDb::new();
+  let mut txn = db.txn();
+  let genesis = [0xff; 32];
+  let attempt_window = AttemptWindow {
+    initial_delay: 60,
+    spacing: 0,
+    attempts_before_escalation: 1,
+    max_escalations: 0,
+  };
+  let topic = Topic::Sign([0; 32]);
+  AttemptDb::recognize_topic(&mut txn, genesis, topic);
+
+  ReattemptDb::schedule_stall_reattempt(&mut txn, genesis, attempt_window, 0, topic);
+  let delay = ScheduledReattempt::get(&txn, genesis, &topic).unwrap();
+  assert!(delay > 0);
+
+  // Every consecutive stall should double the time allowed, replacing the prior re-attempt
+  for stalls in 1 ..= MAX_STALL_BACKOFF {
+    StalledAttempts::set(&mut txn, genesis, &topic, &stalls);
+    ReattemptDb::schedule_stall_reattempt(&mut txn, genesis, attempt_window, 0, topic);
+    assert_eq!(ScheduledReattempt::get(&txn, genesis, &topic), Some(delay << stalls));
+    assert!(ReattemptDb::take(&mut txn, genesis, delay << (stalls - 1)).is_empty());
+  }
+
+  // Up to the maximum backoff
+  StalledAttempts::set(&mut txn, genesis, &topic, &(MAX_STALL_BACKOFF + 1));
+  ReattemptDb::schedule_stall_reattempt(&mut txn, genesis, attempt_window, 0, topic);
+  assert_eq!(ScheduledReattempt::get(&txn, genesis, &topic), Some(delay << MAX_STALL_BACKOFF));
+
+  // Once a threshold participates, the stall re-attempt should be replaced
+  ReattemptDb::schedule_reattempt(&mut txn, genesis, attempt_window, 1, topic);
+  assert!(ReattemptDb::take(&mut txn, genesis, delay << MAX_STALL_BACKOFF).is_empty());
+  assert_eq!(ReattemptDb::take(&mut txn, genesis, 1 + delay), vec![topic]);
+  assert_eq!(ScheduledReattempt::get(&txn, genesis, &topic), None);
+  txn.commit();
+}
+
+#[test]
+fn postponed_reattempts() {
+  let mut db = MemDb::new();
+  let mut txn = db.txn();
+  let genesis = [0xff; 32];
+  let attempt_window = AttemptWindow::DEFAULT;
+  let topic = Topic::Dkg;
+  AttemptDb::recognize_topic(&mut txn, genesis, topic);
+
+  ReattemptDb::schedule_reattempt(&mut txn, genesis, attempt_window, 0, topic);
+  let delay = ScheduledReattempt::get(&txn, genesis, &topic).unwrap();
+
+  // Progress should push the re-attempt back to a full window after it
+  ReattemptDb::postpone_reattempt(&mut txn, genesis, attempt_window, 5, topic);
+  assert!(ReattemptDb::take(&mut txn, genesis, delay).is_empty());
+  assert_eq!(ScheduledReattempt::get(&txn, genesis, &topic), Some(5 + delay));
+
+  // Yet not undo a re-attempt expedited to the next block
+  ReattemptDb::expedite_reattempt(&mut txn, genesis, topic, 10);
+  ReattemptDb::postpone_reattempt(&mut txn, genesis, attempt_window, 9, topic);
+  assert!(ReattemptDb::take(&mut txn, genesis, 5 + delay).is_empty());
+  assert_eq!(ReattemptDb::take(&mut txn, genesis, 10), vec![topic]);
+  txn.commit();
+}
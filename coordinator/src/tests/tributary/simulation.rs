@@ -0,0 +1,186 @@
+use core::time::Duration;
+
+use futures_util::{task::Poll, poll};
+
+use tributary::{TransactionTrait, TributaryReader, tendermint::TARGET_BLOCK_TIME};
+
+use serai_db::MemDb;
+
+use crate::{
+  GossipMessageKind, P2pMessageKind, P2p,
+  tributary::{Label, SignData, Transaction},
+  tests::sim::{SimRng, LinkConditions, SimNetwork, Simulation},
+};
+
+fn blocks(blocks: u32) -> Duration {
+  Duration::from_millis(u64::from(blocks * TARGET_BLOCK_TIME))
+}
+
+// Check every node has the blocks the node with the fewest blocks has
+fn assert_consistent(sim: &Simulation) {
+  let shortest =
+    sim.nodes.iter().min_by_key(|node| node.tributary.reader().block_number()).unwrap();
+  let tip = shortest.tributary.reader().tip();
+  if tip == sim.spec.genesis() {
+    return;
+  }
+  for node in &sim.nodes {
+    assert!(node.tributary.reader().block(&tip).is_some());
+  }
+}
+
+// If a transaction is included within the chain a reader is for
+fn included(reader: &TributaryReader<MemDb, Transaction>, hash: [u8; 32]) -> bool {
+  let mut block = reader.tip();
+  while block != reader.genesis() {
+    let this = reader.block(&block).unwrap();
+    if this.transactions.iter().any(|tx| tx.hash() == hash) {
+      return true;
+    }
+    block = this.parent();
+  }
+  false
+}
+
+#[tokio::test]
+async fn delivery_follows_clock() {
+  let network = SimNetwork::new(
+    0,
+    2,
+    LinkConditions { latency: Duration::from_secs(1), jitter: Duration::ZERO, loss: 0 },
+  );
+  let (sender, recipient) = (network.p2p(0), network.p2p(1));
+
+  let kind = P2pMessageKind::Gossip(GossipMessageKind::Tributary([0; 32]));
+  P2p::broadcast(&sender, kind, vec![1]).await;
+  assert!(matches!(poll!(Box::pin(recipient.receive())), Poll::Pending));
+
+  network.clock().advance(Duration::from_secs(1));
+  let msg = recipient.receive().await;
+  assert_eq!(msg.sender, 0);
+  assert_eq!(msg.kind, kind);
+  assert_eq!(msg.msg, vec![1]);
+
+  // Messages aren't delivered across partitions
+  network.isolate(1);
+  P2p::broadcast(&sender, kind, vec![2]).await;
+  assert_eq!(network.dropped(), 1);
+}
+
+#[tokio::test]
+async fn lossy_network() {
+  let sim = Simulation::new(
+    0,
+    5,
+    LinkConditions {
+      latency: Duration::from_millis(50),
+      jitter: Duration::from_millis(100),
+      loss: 10_000,
+    },
+  )
+  .await;
+
+  sim.run_for(blocks(10)).await;
+  assert!(sim.network.dropped() > 0);
+  for number in sim.block_numbers() {
+    assert!(number >= 3);
+  }
+  assert_consistent(&sim);
+}
+
+#[tokio::test]
+async fn isolated_node() {
+  let sim = Simulation::new(1, 5, LinkConditions::PERFECT).await;
+  let start = sim.block_numbers();
+
+  // The remaining four validators still form a supermajority
+  sim.network.isolate(4);
+  sim.run_for(blocks(10)).await;
+  let numbers = sim.block_numbers();
+  for number in &numbers[.. 4] {
+    assert!(*number >= 3);
+  }
+  assert_eq!(numbers[4], start[4]);
+  assert_consistent(&sim);
+}
+
+#[tokio::test]
+async fn split_network() {
+  let sim = Simulation::new(2, 5, LinkConditions::PERFECT).await;
+  let start = sim.block_numbers();
+
+  // Neither side of the partition has a supermajority
+  sim.network.partition(&[&[0, 1, 2], &[3, 4]]);
+  sim.run_for(blocks(5)).await;
+  assert_eq!(sim.block_numbers(), start);
+
+  // Once healed, the network should resume producing blocks
+  sim.network.heal();
+  sim.run_for(blocks(10)).await;
+  for (number, start) in sim.block_numbers().into_iter().zip(start) {
+    assert!(number > start);
+  }
+  assert_consistent(&sim);
+}
+
+#[tokio::test]
+async fn transactions_propagate() {
+  let sim = Simulation::new(3, 5, LinkConditions::PERFECT).await;
+  sim.network.set_conditions(LinkConditions {
+    latency: Duration::from_millis(100),
+    jitter: Duration::from_millis(100),
+    loss: 50_000,
+  });
+
+  let node = &sim.nodes[0];
+  let mut tx = Transaction::Heartbeat(0, Transaction::empty_signed());
+  tx.sign(&mut SimRng::new(3), sim.spec.genesis(), &node.key);
+  let hash = tx.hash();
+  assert!(node.tributary.add_transaction(tx).await.unwrap());
+
+  sim.run_for(blocks(5)).await;
+  for node in &sim.nodes {
+    assert!(included(&node.tributary.reader(), hash));
+  }
+}
+
+#[tokio::test]
+async fn signing_session() {
+  let sim = Simulation::new(
+    4,
+    5,
+    LinkConditions {
+      latency: Duration::from_millis(50),
+      jitter: Duration::from_millis(100),
+      loss: 10_000,
+    },
+  )
+  .await;
+  let mut rng = SimRng::new(4);
+
+  // Every validator publishes their preprocess, and once every preprocess is included, their
+  // share, as they would when signing a plan
+  for label in [Label::Preprocess, Label::Share] {
+    let mut hashes = vec![];
+    for node in &sim.nodes {
+      let mut tx = Transaction::Sign(SignData {
+        plan: [0xaa; 32],
+        attempt: 0,
+        label,
+        data: vec![vec![0; 64]],
+        signed: Transaction::empty_signed(),
+      });
+      tx.sign(&mut rng, sim.spec.genesis(), &node.key);
+      hashes.push(tx.hash());
+      assert!(node.tributary.add_transaction(tx).await.unwrap());
+    }
+
+    sim.run_for(blocks(5)).await;
+    for node in &sim.nodes {
+      for hash in &hashes {
+        assert!(included(&node.tributary.reader(), *hash));
+      }
+    }
+  }
+  assert_consistent(&sim);
+}
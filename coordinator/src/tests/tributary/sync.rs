@@ -1,7 +1,9 @@
 use core::time::Duration;
 use std::{sync::Arc, collections::HashSet};
 
-use rand_core::OsRng;
+use rand_core::{RngCore, OsRng};
+
+use scale::Encode;
 
 use ciphersuite::{group::GroupEncoding, Ciphersuite, Ristretto};
 
@@ -10,14 +12,17 @@ use tokio::{
   time::sleep,
 };
 
+use serai_clock::SystemClock;
 use serai_db::MemDb;
 
-use tributary::Tributary;
+use tributary::{TransactionKind, TransactionTrait, Tributary};
 
 use crate::{
   tributary::Transaction,
-  ActiveTributary, TributaryEvent,
-  p2p::{heartbeat_tributaries_task, handle_p2p_task},
+  ActiveTributary, TributaryEvent, P2p, ReqResMessageKind,
+  p2p::{
+    BlockRangeRequest, MissingTransactionsRequest, heartbeat_tributaries_task, handle_p2p_task,
+  },
   tests::{
     LocalP2p,
     tributary::{new_keys, new_spec, new_tributaries},
@@ -50,7 +55,7 @@ async fn sync_test() {
     tributary_arcs.push(tributary.clone());
     let (new_tributary_send, new_tributary_recv) = broadcast::channel(5);
     let (cosign_send, _) = mpsc::unbounded_channel();
-    let thread = tokio::spawn(handle_p2p_task(p2p, cosign_send, new_tributary_recv));
+    let thread = tokio::spawn(handle_p2p_task(SystemClock, p2p, cosign_send, new_tributary_recv));
     new_tributary_send
       .send(TributaryEvent::NewTributary(ActiveTributary { spec: spec.clone(), tributary }))
       .map_err(|_| "failed to send ActiveTributary")
@@ -86,7 +91,12 @@ async fn sync_test() {
   let syncer_tributary = Arc::new(syncer_tributary);
   let (syncer_tributary_send, syncer_tributary_recv) = broadcast::channel(5);
   let (cosign_send, _) = mpsc::unbounded_channel();
-  tokio::spawn(handle_p2p_task(syncer_p2p.clone(), cosign_send, syncer_tributary_recv));
+  tokio::spawn(handle_p2p_task(
+    SystemClock,
+    syncer_p2p.clone(),
+    cosign_send,
+    syncer_tributary_recv,
+  ));
   syncer_tributary_send
     .send(TributaryEvent::NewTributary(ActiveTributary {
       spec: spec.clone(),
@@ -107,7 +117,11 @@ async fn sync_test() {
 
   // Start the heartbeat protocol
   let (syncer_heartbeat_tributary_send, syncer_heartbeat_tributary_recv) = broadcast::channel(5);
-  tokio::spawn(heartbeat_tributaries_task(syncer_p2p, syncer_heartbeat_tributary_recv));
+  tokio::spawn(heartbeat_tributaries_task(
+    SystemClock,
+    syncer_p2p,
+    syncer_heartbeat_tributary_recv,
+  ));
   syncer_heartbeat_tributary_send
     .send(TributaryEvent::NewTributary(ActiveTributary {
       spec: spec.clone(),
@@ -163,3 +177,132 @@ async fn sync_test() {
 
   panic!("synced tributary didn't start participating in consensus");
 }
+
+#[tokio::test]
+async fn block_range_sync_test() {
+  let keys = new_keys(&mut OsRng);
+  let spec = new_spec(&mut OsRng, &keys);
+
+  let mut tributaries = new_tributaries(&keys, &spec)
+    .await
+    .into_iter()
+    .map(|(_, p2p, tributary)| (p2p, tributary))
+    .collect::<Vec<_>>();
+
+  // Keep a Tributary back, effectively having it offline
+  let (syncer_p2p, syncer_tributary) = tributaries.pop().unwrap();
+
+  let mut tributary_senders = vec![];
+  let mut tributary_arcs = vec![];
+  for (p2p, tributary) in tributaries.drain(..) {
+    let tributary = Arc::new(tributary);
+    tributary_arcs.push(tributary.clone());
+    let (new_tributary_send, new_tributary_recv) = broadcast::channel(5);
+    let (cosign_send, _) = mpsc::unbounded_channel();
+    tokio::spawn(handle_p2p_task(SystemClock, p2p, cosign_send, new_tributary_recv));
+    new_tributary_send
+      .send(TributaryEvent::NewTributary(ActiveTributary { spec: spec.clone(), tributary }))
+      .map_err(|_| "failed to send ActiveTributary")
+      .unwrap();
+    tributary_senders.push(new_tributary_send);
+  }
+  let tributaries = tributary_arcs;
+
+  // Wait for a few blocks to be produced, as in sync_test
+  let block_time = u64::from(Tributary::<MemDb, Transaction, LocalP2p>::block_time());
+  sleep(Duration::from_secs(4 * block_time)).await;
+  assert!(tributaries[0].reader().block_number() >= 1);
+
+  // Have the syncer join the net, without the heartbeat protocol
+  syncer_p2p.1.write().await.1.last_mut().unwrap().clear();
+  let syncer_tributary = Arc::new(syncer_tributary);
+  let (syncer_tributary_send, syncer_tributary_recv) = broadcast::channel(5);
+  let (cosign_send, _) = mpsc::unbounded_channel();
+  tokio::spawn(handle_p2p_task(
+    SystemClock,
+    syncer_p2p.clone(),
+    cosign_send,
+    syncer_tributary_recv,
+  ));
+  syncer_tributary_send
+    .send(TributaryEvent::NewTributary(ActiveTributary {
+      spec: spec.clone(),
+      tributary: syncer_tributary.clone(),
+    }))
+    .map_err(|_| "failed to send ActiveTributary to syncer")
+    .unwrap();
+  sleep(Duration::from_secs(1)).await;
+
+  // Request solely the first block
+  let request = BlockRangeRequest { start: 1, count: 1 };
+  syncer_p2p.send(0, ReqResMessageKind::BlockRange(spec.genesis()), request.encode()).await;
+  sleep(Duration::from_secs(1)).await;
+  assert_eq!(syncer_tributary.reader().block_number(), 1);
+  assert_eq!(Some(syncer_tributary.tip().await), tributaries[0].reader().block_hash(1));
+
+  // Request the rest, with an excessive count which should be bounded
+  let request = BlockRangeRequest { start: 2, count: u32::MAX };
+  syncer_p2p.send(0, ReqResMessageKind::BlockRange(spec.genesis()), request.encode()).await;
+  sleep(Duration::from_secs(1)).await;
+
+  // Allow a one block tolerance in case of race conditions
+  let tip = tributaries[0].tip().await;
+  let syncer_tip = syncer_tributary.tip().await;
+  assert!(HashSet::from([tip, tributaries[0].reader().block(&tip).unwrap().parent()])
+    .contains(&syncer_tip));
+}
+
+#[tokio::test]
+async fn missing_transactions_test() {
+  let keys = new_keys(&mut OsRng);
+  let spec = new_spec(&mut OsRng, &keys);
+
+  let tributaries = new_tributaries(&keys, &spec)
+    .await
+    .into_iter()
+    .map(|(_, p2p, tributary)| (p2p, Arc::new(tributary)))
+    .collect::<Vec<_>>();
+
+  // Add a transaction to the first Tributary
+  let mut commitments = vec![0; 256];
+  OsRng.fill_bytes(&mut commitments);
+  let mut tx = Transaction::DkgCommitments {
+    attempt: 0,
+    commitments: vec![commitments],
+    signed: Transaction::empty_signed(),
+  };
+  tx.sign(&mut OsRng, spec.genesis(), &keys[0]);
+  assert_eq!(tributaries[0].1.add_transaction(tx.clone()).await, Ok(true));
+
+  // Drop the gossip of it, as if everyone else missed it
+  for queue in tributaries[0].0 .1.write().await.1.iter_mut() {
+    queue.clear();
+  }
+
+  let mut tributary_senders = vec![];
+  for (p2p, tributary) in &tributaries {
+    let (new_tributary_send, new_tributary_recv) = broadcast::channel(5);
+    let (cosign_send, _) = mpsc::unbounded_channel();
+    tokio::spawn(handle_p2p_task(SystemClock, p2p.clone(), cosign_send, new_tributary_recv));
+    new_tributary_send
+      .send(TributaryEvent::NewTributary(ActiveTributary {
+        spec: spec.clone(),
+        tributary: tributary.clone(),
+      }))
+      .map_err(|_| "failed to send ActiveTributary")
+      .unwrap();
+    tributary_senders.push(new_tributary_send);
+  }
+  sleep(Duration::from_secs(1)).await;
+  assert!(!tributaries[1].1.mempool_transactions().await.contains(&tx));
+
+  // Request the transactions for its order from the first Tributary
+  let TransactionKind::Signed(order, _) = tx.kind() else { panic!("DkgCommitments wasn't signed") };
+  let request = MissingTransactionsRequest { orders: vec![order] };
+  tributaries[1]
+    .0
+    .send(0, ReqResMessageKind::MissingTransactions(spec.genesis()), request.encode())
+    .await;
+  sleep(Duration::from_secs(1)).await;
+  assert!(tributaries[1].1.mempool_transactions().await.contains(&tx));
+}
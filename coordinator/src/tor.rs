@@ -0,0 +1,218 @@
+use core::{
+  pin::Pin,
+  future::Future,
+  task::{Context, Poll},
+};
+use std::{io, net::SocketAddr, time::Duration};
+
+use zeroize::Zeroizing;
+
+use blake2::{
+  digest::{consts::U32, Digest},
+  Blake2b,
+};
+use ciphersuite::{group::ff::PrimeField, Ciphersuite, Ristretto};
+
+use serai_client::{Pair, PairTrait, SeraiAddress, Serai, SeraiValidatorSets};
+
+use futures_util::future::Pending;
+use tokio::{
+  io::{AsyncReadExt, AsyncWriteExt},
+  net::TcpStream,
+};
+
+use libp2p::{
+  core::{
+    multiaddr::{Protocol, Multiaddr},
+    transport::{ListenerId, Transport, TransportError, TransportEvent},
+  },
+  tcp::tokio::TcpStream as LibP2pTcpStream,
+};
+
+/// If an address is for an onion service.
+pub(crate) fn is_onion(addr: &Multiaddr) -> bool {
+  addr.iter().any(|protocol| matches!(protocol, Protocol::Onion(..) | Protocol::Onion3(_)))
+}
+
+// The hostname and port for an onion address
+pub(crate) fn onion_host(addr: &Multiaddr) -> Option<(String, u16)> {
+  let mut protocols = addr.iter();
+  let Some(Protocol::Onion3(onion)) = protocols.next() else { return None };
+  // We don't support dialing onion addresses with further protocols, such as a PeerId
+  if protocols.next().is_some() {
+    return None;
+  }
+
+  // The Display implementation encodes the onion service's public key in base32, as needed for
+  // its hostname
+  let addr = Protocol::Onion3(onion.clone()).to_string();
+  let (host, _) = addr.strip_prefix("/onion3/")?.split_once(':')?;
+  Some((format!("{host}.onion"), onion.port()))
+}
+
+// Connect to the specified host via a SOCKS5 proxy, as defined in RFC 1928
+pub(crate) async fn socks5_connect(
+  proxy: SocketAddr,
+  host: String,
+  port: u16,
+) -> io::Result<TcpStream> {
+  let mut stream = TcpStream::connect(proxy).await?;
+  stream.set_nodelay(true)?;
+
+  // Greet the proxy, only offering to not authenticate
+  stream.write_all(&[5, 1, 0]).await?;
+  let mut method = [0; 2];
+  stream.read_exact(&mut method).await?;
+  if method != [5, 0] {
+    Err(io::Error::other("SOCKS5 proxy didn't accept connecting without authentication"))?;
+  }
+
+  // Request a connection to the host, by its domain name
+  let host_len = u8::try_from(host.len()).map_err(|_| io::Error::other("host was too long"))?;
+  let mut request = vec![5, 1, 0, 3, host_len];
+  request.extend(host.as_bytes());
+  request.extend(port.to_be_bytes());
+  stream.write_all(&request).await?;
+
+  let mut reply = [0; 4];
+  stream.read_exact(&mut reply).await?;
+  if reply[0] != 5 {
+    Err(io::Error::other("SOCKS5 proxy replied with an unknown version"))?;
+  }
+  if reply[1] != 0 {
+    Err(io::Error::other(format!("SOCKS5 proxy failed to connect to {host}: {}", reply[1])))?;
+  }
+  // Read past the address the proxy bound to
+  let bound_addr_len = match reply[3] {
+    1 => 4,
+    3 => {
+      let mut len = [0];
+      stream.read_exact(&mut len).await?;
+      usize::from(len[0])
+    }
+    4 => 16,
+    _ => Err(io::Error::other("SOCKS5 proxy replied with an unknown address type"))?,
+  };
+  // Includes the port
+  let mut bound_addr = vec![0; bound_addr_len + 2];
+  stream.read_exact(&mut bound_addr).await?;
+
+  Ok(stream)
+}
+
+/// A transport which dials onion services via a Tor SOCKS5 proxy.
+///
+/// This only supports dialing. Inbound connections are expected to be received via an onion
+/// service, configured within the local Tor daemon, forwarding to our TCP listener.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct TorTransport {
+  proxy: SocketAddr,
+}
+
+impl TorTransport {
+  pub(crate) fn new(proxy: SocketAddr) -> Self {
+    TorTransport { proxy }
+  }
+}
+
+impl Transport for TorTransport {
+  type Output = LibP2pTcpStream;
+  type Error = io::Error;
+  type ListenerUpgrade = Pending<io::Result<LibP2pTcpStream>>;
+  type Dial = Pin<Box<dyn Send + Future<Output = io::Result<LibP2pTcpStream>>>>;
+
+  fn listen_on(
+    &mut self,
+    _id: ListenerId,
+    addr: Multiaddr,
+  ) -> Result<(), TransportError<io::Error>> {
+    Err(TransportError::MultiaddrNotSupported(addr))
+  }
+
+  fn remove_listener(&mut self, _id: ListenerId) -> bool {
+    false
+  }
+
+  fn dial(&mut self, addr: Multiaddr) -> Result<Self::Dial, TransportError<io::Error>> {
+    let Some((host, port)) = onion_host(&addr) else {
+      Err(TransportError::MultiaddrNotSupported(addr))?
+    };
+    let proxy = self.proxy;
+    Ok(Box::pin(async move { socks5_connect(proxy, host, port).await.map(LibP2pTcpStream) }))
+  }
+
+  fn dial_as_listener(&mut self, addr: Multiaddr) -> Result<Self::Dial, TransportError<io::Error>> {
+    self.dial(addr)
+  }
+
+  fn poll(
+    self: Pin<&mut Self>,
+    _cx: &mut Context<'_>,
+  ) -> Poll<TransportEvent<Self::ListenerUpgrade, io::Error>> {
+    // As we never listen, we never have any events
+    Poll::Pending
+  }
+
+  fn address_translation(&self, _listen: &Multiaddr, _observed: &Multiaddr) -> Option<Multiaddr> {
+    None
+  }
+}
+
+// The Serai keypair for our validator, derived from our key as the Serai node derives it
+fn validator_pair(key: &Zeroizing<<Ristretto as Ciphersuite>::F>) -> Pair {
+  let key = Zeroizing::new(key.to_repr());
+  let nonce = Zeroizing::new(Blake2b::<U32>::digest(key.as_slice()));
+  let secret = Zeroizing::new([key.as_slice(), nonce.as_slice()].concat());
+  Pair::from_seed_slice(&secret).expect("64-byte secret key wasn't a valid sr25519 secret key")
+}
+
+/// Publish our onion service's address on-chain, so fellow coordinators may dial us through it.
+///
+/// This returns once the latest finalized block has our validator's published address as the
+/// specified address, republishing the address if it isn't included in a timely manner.
+pub(crate) async fn publish_onion_address(
+  serai: &Serai,
+  key: &Zeroizing<<Ristretto as Ciphersuite>::F>,
+  addr: Multiaddr,
+) {
+  assert!(onion_host(&addr).is_some(), "onion service address {addr} wasn't an onion3 address");
+  let pair = validator_pair(key);
+  let address = addr.to_vec();
+
+  loop {
+    let published = match serai.as_of_latest_finalized_block().await {
+      Ok(serai) => serai.validator_sets().network_address(pair.public()).await,
+      Err(e) => Err(e),
+    };
+    match published {
+      Ok(Some(published)) if published == address => {
+        log::info!("published onion service address {addr}");
+        return;
+      }
+      Ok(_) => {
+        let call = SeraiValidatorSets::set_network_address(
+          address.clone().try_into().expect("onion service address exceeded the maximum length"),
+        );
+        let tx = match serai.next_nonce(SeraiAddress::from(pair.public())).await {
+          Ok(nonce) => serai.sign(&pair, call, nonce, 0),
+          Err(e) => {
+            log::warn!("couldn't get the nonce to publish our onion service address with: {e}");
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            continue;
+          }
+        };
+        if let Err(e) = serai.publish(&tx).await {
+          log::warn!("couldn't publish our onion service address: {e}");
+          tokio::time::sleep(Duration::from_secs(5)).await;
+          continue;
+        }
+        // Wait for the transaction to be finalized before checking it was included
+        tokio::time::sleep(Duration::from_secs(60)).await;
+      }
+      Err(e) => {
+        log::warn!("couldn't check our published onion service address: {e}");
+        tokio::time::sleep(Duration::from_secs(5)).await;
+      }
+    }
+  }
+}
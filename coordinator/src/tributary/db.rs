@@ -6,7 +6,7 @@ use borsh::{BorshSerialize, BorshDeserialize};
 use ciphersuite::{group::GroupEncoding, Ciphersuite, Ristretto};
 use frost::Participant;
 
-use serai_client::validator_sets::primitives::{KeyPair, ExternalValidatorSet};
+use serai_client::validator_sets::primitives::{KeyPair, ExternalValidatorSet, AttemptWindow};
 
 use processor_messages::coordinator::SubstrateSignableId;
 
@@ -14,7 +14,7 @@ pub use serai_db::*;
 
 use tributary::ReadWrite;
 
-use crate::tributary::{Label, Transaction};
+use crate::tributary::{Label, Transaction, SessionSummary};
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Encode, BorshSerialize, BorshDeserialize)]
 pub enum Topic {
@@ -59,11 +59,23 @@ create_db!(
     FatallySlashed: (genesis: [u8; 32], account: [u8; 32]) -> (),
     SlashPoints: (genesis: [u8; 32], account: [u8; 32]) -> u32,
 
+    // The number of the block the first heartbeat on a Tributary was included in
+    HeartbeatsActivated: (genesis: [u8; 32]) -> u32,
+    // The number of the block an account's latest heartbeat was included in
+    LastHeartbeat: (genesis: [u8; 32], account: [u8; 32]) -> u32,
+    Heartbeats: (genesis: [u8; 32], account: [u8; 32]) -> u32,
+
     VotedToRemove: (genesis: [u8; 32], voter: [u8; 32], to_remove: [u8; 32]) -> (),
     VotesToRemove: (genesis: [u8; 32], to_remove: [u8; 32]) -> u16,
 
     AttemptDb: (genesis: [u8; 32], topic: &Topic) -> u32,
+    // The topics explicitly recognized, in the order they were recognized
+    RecognizedTopics: (genesis: [u8; 32]) -> Vec<Topic>,
     ReattemptDb: (genesis: [u8; 32], block: u32) -> Vec<Topic>,
+    // The block a topic's re-attempt is scheduled for
+    ScheduledReattempt: (genesis: [u8; 32], topic: &Topic) -> u32,
+    // The amount of consecutive attempts of a topic which stalled
+    StalledAttempts: (genesis: [u8; 32], topic: &Topic) -> u32,
     DataReceived: (genesis: [u8; 32], data_spec: &DataSpecification) -> u16,
     DataDb: (genesis: [u8; 32], data_spec: &DataSpecification, signer_bytes: &[u8; 32]) -> Vec<u8>,
 
@@ -81,6 +93,13 @@ create_db!(
     SlashReported: (genesis: [u8; 32]) -> u16,
     SlashReportCutOff: (genesis: [u8; 32]) -> u64,
     SlashReport: (set: ExternalValidatorSet) -> Vec<([u8; 32], u32)>,
+
+    SessionBatches: (genesis: [u8; 32]) -> Vec<u32>,
+    CompletedPlans: (genesis: [u8; 32]) -> Vec<([u8; 32], Vec<u8>)>,
+    Participation: (genesis: [u8; 32], account: [u8; 32]) -> u32,
+    SessionSummaryDb: (set: ExternalValidatorSet) -> SessionSummary,
+    SessionSummaryAttestation: (genesis: [u8; 32], signer: [u8; 32]) -> [u8; 32],
+    SessionSummaryAttested: (genesis: [u8; 32]) -> u16,
   }
 );
 
@@ -112,6 +131,9 @@ impl FatallySlashed {
 impl AttemptDb {
   pub fn recognize_topic(txn: &mut impl DbTxn, genesis: [u8; 32], topic: Topic) {
     Self::set(txn, genesis, &topic, &0u32);
+    let mut recognized = RecognizedTopics::get(txn, genesis).unwrap_or_default();
+    recognized.push(topic);
+    RecognizedTopics::set(txn, genesis, &recognized);
   }
 
   pub fn start_next_attempt(txn: &mut impl DbTxn, genesis: [u8; 32], topic: Topic) -> u32 {
@@ -137,39 +159,111 @@ impl AttemptDb {
   }
 }
 
-impl ReattemptDb {
-  pub fn schedule_reattempt(
-    txn: &mut impl DbTxn,
-    genesis: [u8; 32],
-    current_block_number: u32,
-    topic: Topic,
-  ) {
-    // 5 minutes
-    #[cfg(not(feature = "longer-reattempts"))]
-    const BASE_REATTEMPT_DELAY: u32 = (5 * 60 * 1000) / tributary::tendermint::TARGET_BLOCK_TIME;
+/// The maximum amount of times the time allowed for a stalled signing protocol doubles.
+pub const MAX_STALL_BACKOFF: u32 = 4;
+
+/// The amount of consecutive attempts of a signing protocol which may stall before we alert.
+pub const STALLED_ATTEMPTS_ALERT: u32 = 8;
 
-    // 10 minutes, intended for latent environments like the GitHub CI
+impl ReattemptDb {
+  // The delay, in blocks, before re-attempting a topic currently on the specified attempt
+  fn delay(attempt_window: AttemptWindow, topic: Topic, attempt: u32) -> u32 {
+    // The attempt window is in seconds, yet the Tributary's block time is in milliseconds
+    let mut reattempt_delay =
+      attempt_window.delay(attempt).saturating_mul(1000) / tributary::tendermint::TARGET_BLOCK_TIME;
+    // Double the delay for latent environments like the GitHub CI
     #[cfg(feature = "longer-reattempts")]
-    const BASE_REATTEMPT_DELAY: u32 = (10 * 60 * 1000) / tributary::tendermint::TARGET_BLOCK_TIME;
-
-    // 5 minutes for attempts 0 ..= 2, 10 minutes for attempts 3 ..= 5, 15 minutes for attempts > 5
-    // Assumes no event will take longer than 15 minutes, yet grows the time in case there are
-    // network bandwidth issues
-    let mut reattempt_delay = BASE_REATTEMPT_DELAY *
-      ((AttemptDb::attempt(txn, genesis, topic)
-        .expect("scheduling re-attempt for unknown topic") /
-        3) +
-        1)
-      .min(3);
+    {
+      reattempt_delay *= 2;
+    }
     // Allow more time for DKGs since they have an extra round and much more data
     if matches!(topic, Topic::Dkg) {
       reattempt_delay *= 4;
     }
-    let upon_block = current_block_number + reattempt_delay;
+    reattempt_delay
+  }
+
+  // Schedule a re-attempt for the specified block, replacing any currently scheduled
+  fn schedule(txn: &mut impl DbTxn, genesis: [u8; 32], topic: Topic, upon_block: u32) {
+    if let Some(scheduled) = ScheduledReattempt::get(txn, genesis, &topic) {
+      let mut reattempts = Self::get(txn, genesis, scheduled).unwrap_or(vec![]);
+      reattempts.retain(|reattempt| *reattempt != topic);
+      Self::set(txn, genesis, scheduled, &reattempts);
+    }
 
     let mut reattempts = Self::get(txn, genesis, upon_block).unwrap_or(vec![]);
     reattempts.push(topic);
     Self::set(txn, genesis, upon_block, &reattempts);
+    ScheduledReattempt::set(txn, genesis, &topic, &upon_block);
+  }
+
+  pub fn schedule_reattempt(
+    txn: &mut impl DbTxn,
+    genesis: [u8; 32],
+    attempt_window: AttemptWindow,
+    current_block_number: u32,
+    topic: Topic,
+  ) {
+    let attempt =
+      AttemptDb::attempt(txn, genesis, topic).expect("scheduling re-attempt for unknown topic");
+    let upon_block = current_block_number + Self::delay(attempt_window, topic, attempt);
+    Self::schedule(txn, genesis, topic, upon_block);
+  }
+
+  // Schedule a re-attempt for a signing protocol's attempt in case it stalls, never having a
+  // threshold of validators participate
+  //
+  // Once a threshold participates, this is replaced by the re-attempt `schedule_reattempt`
+  // schedules. The time allowed doubles with every consecutive attempt which stalled, up to
+  // `MAX_STALL_BACKOFF` times.
+  pub fn schedule_stall_reattempt(
+    txn: &mut impl DbTxn,
+    genesis: [u8; 32],
+    attempt_window: AttemptWindow,
+    current_block_number: u32,
+    topic: Topic,
+  ) {
+    let attempt =
+      AttemptDb::attempt(txn, genesis, topic).expect("scheduling re-attempt for unknown topic");
+    let stalls = StalledAttempts::get(txn, genesis, &topic).unwrap_or(0);
+    let delay = Self::delay(attempt_window, topic, attempt)
+      .saturating_mul(1 << stalls.min(MAX_STALL_BACKOFF));
+    Self::schedule(txn, genesis, topic, current_block_number.saturating_add(delay));
+  }
+
+  // Postpone a scheduled re-attempt to a full window after the current block, as the protocol is
+  // still making progress
+  //
+  // Re-attempts expedited to the next block are left as-is.
+  pub fn postpone_reattempt(
+    txn: &mut impl DbTxn,
+    genesis: [u8; 32],
+    attempt_window: AttemptWindow,
+    current_block_number: u32,
+    topic: Topic,
+  ) {
+    if ScheduledReattempt::get(txn, genesis, &topic)
+      .is_some_and(|scheduled| scheduled <= (current_block_number + 1))
+    {
+      return;
+    }
+    Self::schedule_reattempt(txn, genesis, attempt_window, current_block_number, topic);
+  }
+
+  // Move a scheduled re-attempt up to the specified block, if it's scheduled for later, or
+  // schedule it for the specified block if it isn't scheduled
+  pub fn expedite_reattempt(
+    txn: &mut impl DbTxn,
+    genesis: [u8; 32],
+    topic: Topic,
+    upon_block: u32,
+  ) {
+    if ScheduledReattempt::get(txn, genesis, &topic)
+      .is_some_and(|scheduled| scheduled <= upon_block)
+    {
+      return;
+    }
+    Self::schedule(txn, genesis, topic, upon_block);
   }
 
   pub fn take(txn: &mut impl DbTxn, genesis: [u8; 32], block_number: u32) -> Vec<Topic> {
@@ -177,10 +271,33 @@ impl ReattemptDb {
     if !res.is_empty() {
       Self::del(txn, genesis, block_number);
     }
+    for topic in &res {
+      ScheduledReattempt::del(txn, genesis, topic);
+    }
     res
   }
 }
 
+impl SessionBatches {
+  pub fn add(txn: &mut impl DbTxn, genesis: [u8; 32], batch: u32) {
+    let mut batches = Self::get(txn, genesis).unwrap_or_default();
+    batches.push(batch);
+    Self::set(txn, genesis, &batches);
+  }
+}
+
+impl CompletedPlans {
+  pub fn complete(txn: &mut impl DbTxn, genesis: [u8; 32], plan: [u8; 32], tx_hash: &[u8]) {
+    let mut completed = Self::get(txn, genesis).unwrap_or_default();
+    // Only record the first completion reported for a plan
+    if completed.iter().any(|(existing, _)| existing == &plan) {
+      return;
+    }
+    completed.push((plan, tx_hash.to_vec()));
+    Self::set(txn, genesis, &completed);
+  }
+}
+
 impl SignedTransactionDb {
   pub fn take_signed_transaction(
     txn: &mut impl DbTxn,
@@ -116,8 +116,12 @@ impl<
     // If 2/3rds of the network participated in this preprocess, queue it for an automatic
     // re-attempt
     // DkgConfirmation doesn't have a re-attempt as it's just an extension for Dkg
+    // If DKG attempts time out, their re-attempts are instead scheduled as they start, so they
+    // time out even if 2/3rds of the network never participates
+    let dkg_times_out = self.spec.dkg_attempts_time_out();
     if (data_spec.label == Label::Preprocess) &&
       received_range.contains(&self.spec.t()) &&
+      (!((data_spec.topic == Topic::Dkg) && dkg_times_out)) &&
       (data_spec.topic != Topic::DkgConfirmation)
     {
       // Double check the attempt on this entry, as we don't want to schedule a re-attempt if this
@@ -125,7 +129,26 @@ impl<
       // This is an assert, not part of the if check, as old data shouldn't be here in the first
       // place
       assert_eq!(AttemptDb::attempt(self.txn, genesis, data_spec.topic), Some(data_spec.attempt));
-      ReattemptDb::schedule_reattempt(self.txn, genesis, self.block_number, data_spec.topic);
+      StalledAttempts::del(self.txn, genesis, &data_spec.topic);
+      ReattemptDb::schedule_reattempt(
+        self.txn,
+        genesis,
+        self.spec.attempt_window(),
+        self.block_number,
+        data_spec.topic,
+      );
+    }
+
+    // A DKG attempt only times out once it stalls, so push back its timeout while it progresses
+    // This includes its confirmation, which is an extension of it
+    if matches!(data_spec.topic, Topic::Dkg | Topic::DkgConfirmation) && dkg_times_out {
+      ReattemptDb::postpone_reattempt(
+        self.txn,
+        genesis,
+        self.spec.attempt_window(),
+        self.block_number,
+        Topic::Dkg,
+      );
     }
 
     // If we have all the needed commitments/preprocesses/shares, tell the processor
@@ -212,6 +235,11 @@ impl<
 
     // TODO: If this is shares, we need to check they are part of the selected signing set
 
+    // Track their participation for the session's summary
+    let participation =
+      Participation::get(self.txn, genesis, signed.signer.to_bytes()).unwrap_or(0) + 1;
+    Participation::set(self.txn, genesis, signed.signer.to_bytes(), &participation);
+
     // Accumulate this data
     self.accumulate(removed, data_spec, signed.signer, bytes)
   }
@@ -278,7 +306,15 @@ impl<
         let new_votes = prior_votes + u16::from(signer_votes.end) - u16::from(signer_votes.start);
         VotesToRemove::set(self.txn, genesis, participant, &new_votes);
         if ((prior_votes + 1) ..= new_votes).contains(&self.spec.t()) {
-          self.fatal_slash(participant, "RemoveParticipantDueToDkg vote")
+          self.fatal_slash(participant, "RemoveParticipantDueToDkg vote");
+          // Restart the DKG without them, instead of waiting for the current attempt to time out
+          if self.spec.dkg_attempts_time_out() {
+            log::info!(
+              "re-attempting the DKG for {:?} without a removed participant",
+              self.spec.set()
+            );
+            ReattemptDb::expedite_reattempt(self.txn, genesis, Topic::Dkg, self.block_number + 1);
+          }
         }
       }
 
@@ -565,10 +601,14 @@ impl<
       }
 
       Transaction::CosignSubstrateBlock(hash) => {
-        AttemptDb::recognize_topic(
+        let topic = Topic::SubstrateSign(SubstrateSignableId::CosigningSubstrateBlock(hash));
+        AttemptDb::recognize_topic(self.txn, genesis, topic);
+        ReattemptDb::schedule_stall_reattempt(
           self.txn,
           genesis,
-          Topic::SubstrateSign(SubstrateSignableId::CosigningSubstrateBlock(hash)),
+          self.spec.attempt_window(),
+          self.block_number,
+          topic,
         );
 
         let block_number = SeraiBlockNumber::get(self.txn, hash)
@@ -586,11 +626,16 @@ impl<
 
       Transaction::Batch { block: _, batch } => {
         // Because this Batch has achieved synchrony, its batch ID should be authorized
-        AttemptDb::recognize_topic(
+        let topic = Topic::SubstrateSign(SubstrateSignableId::Batch(batch));
+        AttemptDb::recognize_topic(self.txn, genesis, topic);
+        ReattemptDb::schedule_stall_reattempt(
           self.txn,
           genesis,
-          Topic::SubstrateSign(SubstrateSignableId::Batch(batch)),
+          self.spec.attempt_window(),
+          self.block_number,
+          topic,
         );
+        SessionBatches::add(self.txn, genesis, batch);
         self
           .recognized_id
           .recognized_id(
@@ -610,6 +655,13 @@ impl<
 
         for id in plan_ids {
           AttemptDb::recognize_topic(self.txn, genesis, Topic::Sign(id));
+          ReattemptDb::schedule_stall_reattempt(
+            self.txn,
+            genesis,
+            self.spec.attempt_window(),
+            self.block_number,
+            Topic::Sign(id),
+          );
           self
             .recognized_id
             .recognized_id(self.spec.set(), genesis, RecognizedIdType::Plan, id.to_vec())
@@ -731,6 +783,8 @@ impl<
 
         // TODO: Confirm this signer hasn't prior published a completion
 
+        CompletedPlans::complete(self.txn, genesis, plan, &tx_hash);
+
         let msg = sign::CoordinatorMessage::Completed {
           session: self.spec.set().session,
           id: plan,
@@ -771,6 +825,68 @@ impl<
           );
         }
       }
+
+      Transaction::SessionSummary(hash, signed) => {
+        let signer = signed.signer.to_bytes();
+        // The summary is only defined once the slash report has been locked in
+        let Some(summary) = SessionSummaryDb::get(self.txn, self.spec.set()) else {
+          self.fatal_slash(signer, "attested to a session summary before the slash report");
+          return;
+        };
+        if SessionSummaryAttestation::get(self.txn, genesis, signer).is_some() {
+          self.fatal_slash(signer, "attested to multiple session summaries");
+          return;
+        }
+        SessionSummaryAttestation::set(self.txn, genesis, signer, &hash);
+
+        // The summary isn't consensus-critical, so we solely note the distinct view
+        if hash != summary.hash() {
+          log::warn!(
+            "{} attested to a distinct session summary for {:?}",
+            hex::encode(signer),
+            self.spec.set(),
+          );
+          return;
+        }
+
+        // Uses &[] as we only need the length which is independent to who else was removed
+        let signer_range = self.spec.i(&[], signed.signer).unwrap();
+        let signer_len = u16::from(signer_range.end) - u16::from(signer_range.start);
+        let prior_attested = SessionSummaryAttested::get(self.txn, genesis).unwrap_or(0);
+        let now_attested = prior_attested + signer_len;
+        SessionSummaryAttested::set(self.txn, genesis, &now_attested);
+        if (prior_attested < self.spec.t()) && (now_attested >= self.spec.t()) {
+          log::info!(
+            "session summary {} for {:?} was attested to by a threshold",
+            hex::encode(hash),
+            self.spec.set(),
+          );
+        }
+      }
+
+      Transaction::Heartbeat(period, signed) => {
+        // Only heartbeats for the current or prior period evidence liveness, as heartbeats for
+        // other periods may have been published long before their inclusion
+        let current = liveness::heartbeat_period(self.block_number);
+        if (period != current) && (period.saturating_add(1) != current) {
+          log::debug!("ignoring heartbeat for period {period} during period {current}");
+          return;
+        }
+
+        // Validators are only expected to publish heartbeats once they've activated
+        if HeartbeatsActivated::get(self.txn, genesis).is_none() {
+          HeartbeatsActivated::set(self.txn, genesis, &self.block_number);
+        }
+
+        let signer = signed.signer.to_bytes();
+        let last = LastHeartbeat::get(self.txn, genesis, signer);
+        LastHeartbeat::set(self.txn, genesis, signer, &self.block_number);
+        // Only count one heartbeat per period
+        if last.map(liveness::heartbeat_period) != Some(current) {
+          let heartbeats = Heartbeats::get(self.txn, genesis, signer).unwrap_or(0) + 1;
+          Heartbeats::set(self.txn, genesis, signer, &heartbeats);
+        }
+      }
     }
   }
 }
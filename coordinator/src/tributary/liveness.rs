@@ -0,0 +1,121 @@
+use core::{ops::Deref, time::Duration};
+use std::collections::HashMap;
+
+use zeroize::Zeroizing;
+use rand_core::OsRng;
+
+use ciphersuite::{Ciphersuite, Ristretto};
+
+use tokio::sync::broadcast;
+
+use serai_clock::Clock;
+
+use crate::{
+  P2p, ActiveTributary, TributaryEvent,
+  tributary::{Get, Db, Transaction, HeartbeatsActivated, LastHeartbeat},
+};
+
+/// The amount of Tributary blocks in each heartbeat period, with validators expected to publish
+/// one heartbeat per period.
+pub const HEARTBEAT_INTERVAL: u32 = (60 * 1000) / tributary::tendermint::TARGET_BLOCK_TIME;
+
+/// The amount of Tributary blocks after their last heartbeat a validator is considered offline.
+pub const LIVENESS_WINDOW: u32 = 3 * HEARTBEAT_INTERVAL;
+
+/// The order all heartbeats are published under.
+pub const HEARTBEAT_ORDER: &[u8] = b"heartbeat";
+
+/// The heartbeat period the block with the specified number is within.
+pub fn heartbeat_period(block_number: u32) -> u32 {
+  block_number / HEARTBEAT_INTERVAL
+}
+
+/// If a validator is known to be offline as of the block with the specified number.
+///
+/// This is solely a function of the Tributary, and accordingly deterministic across validators.
+/// Validators are presumed online until a full window has passed without a heartbeat from them,
+/// counted from when heartbeats activated on this Tributary if they've yet to publish one.
+///
+/// Liveness may only be used to shorten waits, never to remove a validator.
+pub fn offline(
+  getter: &impl Get,
+  genesis: [u8; 32],
+  validator: [u8; 32],
+  block_number: u32,
+) -> bool {
+  let Some(activated) = HeartbeatsActivated::get(getter, genesis) else { return false };
+  let last = LastHeartbeat::get(getter, genesis, validator).unwrap_or(activated);
+  block_number.saturating_sub(last) > LIVENESS_WINDOW
+}
+
+/// Publish a heartbeat onto every Tributary we're a validator for, once per heartbeat period.
+///
+/// Heartbeats are only published while the Tributary is synced, as a heartbeat for a prior period
+/// is ignored.
+pub async fn publish_heartbeats_task<D: Db, P: P2p, C: Clock>(
+  clock: C,
+  key: Zeroizing<<Ristretto as Ciphersuite>::F>,
+  mut tributary_event: broadcast::Receiver<TributaryEvent<D, P>>,
+) {
+  let our_key = Ristretto::generator() * key.deref();
+
+  let mut tributaries = HashMap::new();
+  // The last period we published a heartbeat for, per Tributary
+  let mut published = HashMap::new();
+  loop {
+    loop {
+      match tributary_event.try_recv() {
+        Ok(TributaryEvent::NewTributary(tributary)) => {
+          if tributary.spec.i(&[], our_key).is_some() {
+            tributaries.insert(tributary.spec.set(), tributary);
+          }
+        }
+        Ok(TributaryEvent::TributaryRetired(set)) => {
+          tributaries.remove(&set);
+          published.remove(&set);
+        }
+        Err(broadcast::error::TryRecvError::Empty) => break,
+        Err(broadcast::error::TryRecvError::Lagged(_)) => {
+          panic!("publish_heartbeats lagged to handle tributary_event")
+        }
+        Err(broadcast::error::TryRecvError::Closed) => panic!("tributary_event sender closed"),
+      }
+    }
+
+    for (set, ActiveTributary { spec, tributary }) in &tributaries {
+      let reader = tributary.reader();
+
+      // Don't publish heartbeats while syncing
+      let block_time = Duration::from_secs(reader.time_of_block(&reader.tip()).unwrap_or(0));
+      if clock.now() > (block_time + Duration::from_secs(60)) {
+        continue;
+      }
+
+      let period = heartbeat_period(u32::try_from(reader.block_number()).unwrap());
+      if published.get(set).is_some_and(|published| *published >= period) {
+        continue;
+      }
+
+      // Heartbeats share a single order, with their nonces incrementing
+      let Some(nonce) = tributary.next_nonce(&our_key, HEARTBEAT_ORDER).await else {
+        continue;
+      };
+      let mut signed = Transaction::empty_signed();
+      signed.nonce = nonce;
+      let mut tx = Transaction::Heartbeat(period, signed);
+      tx.sign(&mut OsRng, spec.genesis(), &key);
+      // If this fails, the mempool is presumably full, and we'll publish a heartbeat next period
+      if let Err(e) = tributary.add_transaction(tx).await {
+        log::debug!("couldn't publish heartbeat for {set:?}: {e:?}");
+      }
+      published.insert(*set, period);
+    }
+
+    // Check a few times per period so we don't publish late into it
+    clock
+      .sleep(Duration::from_millis(
+        (u64::from(HEARTBEAT_INTERVAL) * u64::from(tributary::tendermint::TARGET_BLOCK_TIME)) / 4,
+      ))
+      .await;
+  }
+}
@@ -13,12 +13,20 @@ pub use db::*;
 
 mod spec;
 pub use spec::TributarySpec;
+pub(crate) use spec::{LegacyTributarySpec, UnversionedTributarySpec};
 
 mod transaction;
 pub use transaction::{Label, SignData, Transaction};
 
 mod signing_protocol;
 
+mod summary;
+pub use summary::SessionSummary;
+
+pub mod liveness;
+
+pub mod retention;
+
 mod handle;
 pub use handle::*;
 
@@ -0,0 +1,84 @@
+use std::{
+  fs,
+  io::BufWriter,
+  path::{Path, PathBuf},
+};
+
+use tokio::sync::broadcast;
+
+use serai_client::validator_sets::primitives::{ExternalValidatorSet, Session};
+
+use crate::{
+  P2p, TributaryEvent, ArchivedTributaryDb, PrunedTributaryDb,
+  tributary::{DbTxn, Db, Transaction},
+};
+
+/// The amount of sessions after a Tributary's retirement its blocks are retained for.
+///
+/// This leaves time for any disputes over the session to be raised while its Tributary can still
+/// be served to peers.
+pub const RETAINED_SESSIONS: u32 = 2;
+
+// Export a retired Tributary to a file within the archive directory
+fn archive<D: Db>(db: &D, path: &Path, set: ExternalValidatorSet, genesis: [u8; 32]) -> bool {
+  let file =
+    path.join(format!("{:?}-{}-{}.tributary", set.network, set.session.0, hex::encode(genesis)));
+  let res = fs::File::create(&file).and_then(|file| {
+    let mut writer = BufWriter::new(file);
+    ::tributary::export::<_, Transaction, _>(db, genesis, &mut writer)?;
+    writer.into_inner().map_err(|e| e.into_error())?.sync_all()
+  });
+  if let Err(e) = res {
+    log::error!("couldn't archive the Tributary for {set:?} to {}: {e}", file.display());
+    return false;
+  }
+  log::info!("archived the Tributary for {set:?} to {}", file.display());
+  true
+}
+
+/// Prune retired Tributaries once `RETAINED_SESSIONS` sessions have been retired after them.
+///
+/// If an archive directory is specified, Tributaries are exported to it before being pruned. A
+/// Tributary which fails to be archived isn't pruned, and is retried upon the next retirement.
+/// The Tributary's tip and its commit, along with the data the coordinator derived from it (such
+/// as slash evidence and the session's summary), are always retained.
+pub async fn prune_retired_tributaries_task<D: Db, P: P2p>(
+  mut db: D,
+  archive_path: Option<PathBuf>,
+  mut tributary_event: broadcast::Receiver<TributaryEvent<D, P>>,
+) {
+  loop {
+    match tributary_event.recv().await {
+      Ok(TributaryEvent::NewTributary(_)) => {}
+      Ok(TributaryEvent::TributaryRetired(retired)) => {
+        let Some(cutoff) = retired.session.0.checked_sub(RETAINED_SESSIONS) else { continue };
+        // Check every prior session, in case a prior prune was interrupted or failed to archive
+        for session in 0 ..= cutoff {
+          let set = ExternalValidatorSet { network: retired.network, session: Session(session) };
+          if PrunedTributaryDb::get(&db, set).is_some() {
+            continue;
+          }
+          // If we weren't in this set, we won't have its Tributary
+          let Some(spec) = ArchivedTributaryDb::get(&db, set) else { continue };
+          let genesis = spec.genesis();
+
+          if let Some(path) = &archive_path {
+            if !archive(&db, path, set, genesis) {
+              continue;
+            }
+          }
+
+          ::tributary::prune::<_, Transaction>(&mut db, genesis);
+          let mut txn = db.txn();
+          PrunedTributaryDb::set(&mut txn, set, &());
+          txn.commit();
+          log::info!("pruned the Tributary for {set:?}");
+        }
+      }
+      Err(broadcast::error::RecvError::Lagged(_)) => {
+        panic!("prune_retired_tributaries lagged to handle tributary_event")
+      }
+      Err(broadcast::error::RecvError::Closed) => panic!("tributary_event sender closed"),
+    }
+  }
+}
@@ -2,6 +2,7 @@ use core::{marker::PhantomData, ops::Deref, future::Future, time::Duration};
 use std::{sync::Arc, collections::HashSet};
 
 use zeroize::Zeroizing;
+use rand_core::OsRng;
 
 use ciphersuite::{group::GroupEncoding, Ciphersuite, Ristretto};
 
@@ -208,6 +209,16 @@ impl<
     // TODO: disconnect the node from network/ban from further participation in all Tributaries
   }
 
+  // If a participant in the specified DKG attempt has since been voted to be removed
+  fn dkg_participant_voted_out(&self, removed: &[<Ristretto as Ciphersuite>::G]) -> bool {
+    let genesis = self.spec.genesis();
+    let t = self.spec.t();
+    self.spec.validators().into_iter().any(|(validator, _)| {
+      (!removed.contains(&validator)) &&
+        (VotesToRemove::get(&*self.txn, genesis, validator.to_bytes()).unwrap_or(0) >= t)
+    })
+  }
+
   // TODO: Once Substrate confirms a key, we need to rotate our validator set OR form a second
   // Tributary post-DKG
   // https://github.com/serai-dex/serai/issues/426
@@ -280,12 +291,80 @@ impl<
       present_shares
     };
 
+    // The DKG starts with the Tributary, so its first attempt times out relative to the first block
+    let dkg_times_out = self.spec.dkg_attempts_time_out();
+    if dkg_times_out && (self.block_number == 1) {
+      ReattemptDb::schedule_reattempt(
+        self.txn,
+        genesis,
+        self.spec.attempt_window(),
+        self.block_number,
+        Topic::Dkg,
+      );
+    }
+
     for topic in ReattemptDb::take(self.txn, genesis, self.block_number) {
+      // DKG attempts time out, even once confirmed, so check the DKG actually needs to be
+      // re-attempted
+      let mut aborted_dkg = false;
+      if (topic == Topic::Dkg) && dkg_times_out {
+        let attempt = AttemptDb::attempt(self.txn, genesis, topic).unwrap();
+        let removed = crate::tributary::removed_as_of_dkg_attempt(self.txn, genesis, attempt)
+          .expect("current attempt didn't have its removed saved to disk");
+        // If a participant was voted out, the attempt was aborted due to a faulty participant
+        // It needs to be re-attempted without them, yet no one was offline
+        aborted_dkg = self.dkg_participant_voted_out(&removed);
+        let confirmation_spec =
+          DataSpecification { topic: Topic::DkgConfirmation, label: Label::Share, attempt };
+        let confirmed = DataReceived::get(self.txn, genesis, &confirmation_spec).unwrap_or(0) ==
+          self.spec.n(&removed);
+        if confirmed && (!aborted_dkg) {
+          log::debug!("DKG for {:?} was confirmed, not re-attempting", self.spec.set());
+          continue;
+        }
+      }
+
       let attempt = AttemptDb::start_next_attempt(self.txn, genesis, topic);
       log::info!("re-attempting {topic:?} with attempt {attempt}");
 
+      // Time out this attempt of a signing protocol in case it stalls, unless the protocol
+      // completed or the prior attempt received a threshold of shares and presumably completed
+      // Once a threshold of validators preprocess for this attempt, this is replaced by the
+      // re-attempt scheduled then
+      let completed = match topic {
+        Topic::Sign(plan) => CompletedPlans::get(self.txn, genesis)
+          .unwrap_or_default()
+          .iter()
+          .any(|(completed, _)| *completed == plan),
+        _ => false,
+      };
+      if matches!(topic, Topic::SubstrateSign(_) | Topic::Sign(_)) && (!completed) {
+        let prior = |label| DataSpecification { topic, label, attempt: attempt - 1 };
+        let t = self.spec.t();
+        if DataReceived::get(self.txn, genesis, &prior(Label::Share)).unwrap_or(0) < t {
+          if DataReceived::get(self.txn, genesis, &prior(Label::Preprocess)).unwrap_or(0) < t {
+            let stalls = StalledAttempts::get(self.txn, genesis, &topic).unwrap_or(0) + 1;
+            StalledAttempts::set(self.txn, genesis, &topic, &stalls);
+            if stalls >= STALLED_ATTEMPTS_ALERT {
+              let set = self.spec.set();
+              log::error!(
+                "{topic:?} for {set:?} has stalled for {stalls} consecutive attempts, without a \
+                 threshold participating",
+              );
+            }
+          }
+          ReattemptDb::schedule_stall_reattempt(
+            self.txn,
+            genesis,
+            self.spec.attempt_window(),
+            self.block_number,
+            topic,
+          );
+        }
+      }
+
       // Slash people who failed to participate as expected in the prior attempt
-      {
+      if !aborted_dkg {
         let prior_attempt = attempt - 1;
         let (removed, expected_participants) = match topic {
           Topic::Dkg => {
@@ -455,6 +534,16 @@ impl<
             attempt,
             &removed.iter().map(<Ristretto as Ciphersuite>::G::to_bytes).collect(),
           );
+          // Time out this attempt as well, should it stall
+          if dkg_times_out {
+            ReattemptDb::schedule_reattempt(
+              self.txn,
+              genesis,
+              self.spec.attempt_window(),
+              self.block_number,
+              Topic::Dkg,
+            );
+          }
 
           if DkgLocallyCompleted::get(self.txn, genesis).is_none() {
             let Some(our_i) = self.spec.i(&removed, Ristretto::generator() * self.our_key.deref())
@@ -636,6 +725,13 @@ impl<
       // They still have value to be locally tracked due to local decisions made based off
       // accumulated slash reports
       SlashReport::set(self.txn, self.spec.set(), &report);
+      ReattemptDb::schedule_stall_reattempt(
+        self.txn,
+        genesis,
+        self.spec.attempt_window(),
+        self.block_number,
+        Topic::SubstrateSign(SubstrateSignableId::SlashReport),
+      );
 
       // Start a signing protocol for this
       self
@@ -652,6 +748,17 @@ impl<
           },
         )
         .await;
+
+      // With the slash report locked in, the session's summary is defined
+      // Attest to it on the Tributary so auditors have a signed record of this session
+      let summary = SessionSummary::new(self.txn, self.spec)
+        .expect("couldn't build the session summary despite setting the slash report");
+      SessionSummaryDb::set(self.txn, self.spec.set(), &summary);
+      log::info!("session summary for {:?}: {:?}", self.spec.set(), summary);
+
+      let mut tx = Transaction::SessionSummary(summary.hash(), Transaction::empty_signed());
+      tx.sign(&mut OsRng, genesis, self.our_key);
+      self.publish_tributary_tx.publish_tributary_tx(tx).await;
     }
   }
 }
@@ -9,7 +9,12 @@ use frost::Participant;
 use scale::Encode;
 use borsh::{BorshSerialize, BorshDeserialize};
 
-use serai_client::{primitives::PublicKey, validator_sets::primitives::ExternalValidatorSet};
+use serai_client::{
+  primitives::PublicKey,
+  validator_sets::primitives::{ExternalValidatorSet, AttemptWindow},
+};
+
+use tributary::tendermint::ProposerSchedule;
 
 fn borsh_serialize_validators<W: io::Write>(
   validators: &Vec<(<Ristretto as Ciphersuite>::G, u16)>,
@@ -39,6 +44,13 @@ fn borsh_deserialize_validators<R: io::Read>(
   Ok(res)
 }
 
+/// The version of the Tributary protocol new Tributaries are created with.
+///
+/// Existing Tributaries keep the behavior of the version they were created with.
+// 1: Proposers are scheduled by Tendermint's proposer priority algorithm
+// 2: DKG attempts time out once stalled
+const TRIBUTARY_PROTOCOL_VERSION: u8 = 2;
+
 #[derive(Clone, PartialEq, Eq, Debug, BorshSerialize, BorshDeserialize)]
 pub struct TributarySpec {
   serai_block: [u8; 32],
@@ -49,6 +61,53 @@ pub struct TributarySpec {
     deserialize_with = "borsh_deserialize_validators"
   )]
   validators: Vec<(<Ristretto as Ciphersuite>::G, u16)>,
+  // None if this Tributary was created before attempt windows were defined on-chain
+  attempt_window: Option<AttemptWindow>,
+  // 0 if this Tributary was created before protocol versions were defined
+  protocol_version: u8,
+}
+
+/// A TributarySpec as encoded before attempt windows were defined on-chain.
+#[derive(BorshDeserialize)]
+pub(crate) struct LegacyTributarySpec {
+  serai_block: [u8; 32],
+  start_time: u64,
+  set: ExternalValidatorSet,
+  #[borsh(deserialize_with = "borsh_deserialize_validators")]
+  validators: Vec<(<Ristretto as Ciphersuite>::G, u16)>,
+}
+
+impl From<LegacyTributarySpec> for TributarySpec {
+  fn from(spec: LegacyTributarySpec) -> TributarySpec {
+    let LegacyTributarySpec { serai_block, start_time, set, validators } = spec;
+    TributarySpec {
+      serai_block,
+      start_time,
+      set,
+      validators,
+      attempt_window: None,
+      protocol_version: 0,
+    }
+  }
+}
+
+/// A TributarySpec as encoded before protocol versions were defined.
+#[derive(BorshDeserialize)]
+pub(crate) struct UnversionedTributarySpec {
+  serai_block: [u8; 32],
+  start_time: u64,
+  set: ExternalValidatorSet,
+  #[borsh(deserialize_with = "borsh_deserialize_validators")]
+  validators: Vec<(<Ristretto as Ciphersuite>::G, u16)>,
+  attempt_window: Option<AttemptWindow>,
+}
+
+impl From<UnversionedTributarySpec> for TributarySpec {
+  fn from(spec: UnversionedTributarySpec) -> TributarySpec {
+    let UnversionedTributarySpec { serai_block, start_time, set, validators, attempt_window } =
+      spec;
+    TributarySpec { serai_block, start_time, set, validators, attempt_window, protocol_version: 0 }
+  }
 }
 
 impl TributarySpec {
@@ -57,6 +116,7 @@ impl TributarySpec {
     start_time: u64,
     set: ExternalValidatorSet,
     set_participants: Vec<(PublicKey, u16)>,
+    attempt_window: AttemptWindow,
   ) -> TributarySpec {
     let mut validators = vec![];
     for (participant, shares) in set_participants {
@@ -65,13 +125,39 @@ impl TributarySpec {
       validators.push((participant, shares));
     }
 
-    Self { serai_block, start_time, set, validators }
+    Self {
+      serai_block,
+      start_time,
+      set,
+      validators,
+      attempt_window: Some(attempt_window),
+      protocol_version: TRIBUTARY_PROTOCOL_VERSION,
+    }
   }
 
   pub fn set(&self) -> ExternalValidatorSet {
     self.set
   }
 
+  pub fn attempt_window(&self) -> AttemptWindow {
+    self.attempt_window.unwrap_or(AttemptWindow::DEFAULT)
+  }
+
+  /// If DKG attempts time out once they stall.
+  ///
+  /// Prior, DKG attempts were only re-attempted once a threshold of validators participated.
+  pub fn dkg_attempts_time_out(&self) -> bool {
+    self.protocol_version >= 2
+  }
+
+  pub fn proposer_schedule(&self) -> ProposerSchedule {
+    if self.protocol_version >= 1 {
+      ProposerSchedule::Spread
+    } else {
+      ProposerSchedule::Shuffled
+    }
+  }
+
   pub fn genesis(&self) -> [u8; 32] {
     // Calculate the genesis for this Tributary
     let mut genesis = RecommendedTranscript::new(b"Serai Tributary Genesis");
@@ -79,6 +165,16 @@ impl TributarySpec {
     genesis.append_message(b"serai_block", self.serai_block);
     genesis.append_message(b"session", self.set.session.0.to_le_bytes());
     genesis.append_message(b"network", self.set.network.encode());
+    // This ensures all coordinators use identical attempt windows, as any coordinator with a
+    // distinct attempt window will be on a distinct Tributary
+    // Tributaries created before attempt windows were defined on-chain keep their prior genesis
+    if let Some(attempt_window) = self.attempt_window {
+      genesis.append_message(b"attempt_window", attempt_window.encode());
+    }
+    // This ensures all coordinators run the same protocol for this Tributary
+    if self.protocol_version != 0 {
+      genesis.append_message(b"protocol_version", [self.protocol_version]);
+    }
     let genesis = genesis.challenge(b"genesis");
     let genesis_ref: &[u8] = genesis.as_ref();
     genesis_ref[.. 32].try_into().unwrap()
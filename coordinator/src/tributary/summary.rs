@@ -0,0 +1,63 @@
+use blake2::{Digest, Blake2s256};
+
+use borsh::{BorshSerialize, BorshDeserialize};
+
+use ciphersuite::group::GroupEncoding;
+
+use serai_client::validator_sets::primitives::ExternalValidatorSet;
+
+use crate::tributary::{
+  Get, TributarySpec, SessionBatches, CompletedPlans, FatalSlashes, SlashReport, Participation,
+};
+
+/// A summary of a session's activity, as recorded on its Tributary.
+///
+/// This is deterministically derived from the Tributary once its slash report is locked in. Every
+/// validator then attests to it by publishing its hash onto the Tributary, giving auditors a
+/// compact, signed record of the session without having to replay it.
+#[derive(Clone, PartialEq, Eq, Debug, BorshSerialize, BorshDeserialize)]
+pub struct SessionSummary {
+  pub set: ExternalValidatorSet,
+  /// The Batches the set achieved synchrony on, and was accordingly expected to sign.
+  pub batches: Vec<u32>,
+  /// The plans completed, with the hash of the external transaction which completed them.
+  pub transactions: Vec<([u8; 32], Vec<u8>)>,
+  /// The validators fatally slashed for faults detected on the Tributary.
+  pub faults: Vec<[u8; 32]>,
+  /// The slash report for this session.
+  pub slashes: Vec<([u8; 32], u32)>,
+  /// The amount of messages each validator contributed to signing protocols.
+  pub participation: Vec<([u8; 32], u32)>,
+}
+
+impl SessionSummary {
+  /// Build the summary for a Tributary, if its slash report has been locked in.
+  pub fn new(getter: &impl Get, spec: &TributarySpec) -> Option<SessionSummary> {
+    let genesis = spec.genesis();
+    let slashes = SlashReport::get(getter, spec.set())?;
+    let participation = spec
+      .validators()
+      .into_iter()
+      .map(|(validator, _)| {
+        let validator = validator.to_bytes();
+        (validator, Participation::get(getter, genesis, validator).unwrap_or(0))
+      })
+      .collect();
+    Some(SessionSummary {
+      set: spec.set(),
+      batches: SessionBatches::get(getter, genesis).unwrap_or_default(),
+      transactions: CompletedPlans::get(getter, genesis).unwrap_or_default(),
+      faults: FatalSlashes::get(getter, genesis).unwrap_or_default(),
+      slashes,
+      participation,
+    })
+  }
+
+  /// The hash of this summary, as attested to on the Tributary.
+  pub fn hash(&self) -> [u8; 32] {
+    Blake2s256::digest(
+      [b"Coordinator Session Summary".as_slice(), &borsh::to_vec(self).unwrap()].concat(),
+    )
+    .into()
+  }
+}
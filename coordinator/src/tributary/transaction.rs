@@ -22,6 +22,8 @@ use tributary::{
   transaction::{Signed, TransactionError, TransactionKind, Transaction as TransactionTrait},
 };
 
+use crate::tributary::liveness::HEARTBEAT_ORDER;
+
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Encode)]
 pub enum Label {
   Preprocess,
@@ -192,6 +194,10 @@ pub enum Transaction {
   },
 
   SlashReport(Vec<u32>, Signed),
+  // An attestation to the hash of the session's summary
+  SessionSummary([u8; 32], Signed),
+  // A heartbeat for the specified period, used to track which validators are live
+  Heartbeat(u32, Signed),
 }
 
 impl Debug for Transaction {
@@ -251,6 +257,16 @@ impl Debug for Transaction {
         .field("points", points)
         .field("signed", signed)
         .finish(),
+      Transaction::SessionSummary(hash, signed) => fmt
+        .debug_struct("Transaction::SessionSummary")
+        .field("hash", &hex::encode(hash))
+        .field("signer", &hex::encode(signed.signer.to_bytes()))
+        .finish_non_exhaustive(),
+      Transaction::Heartbeat(period, signed) => fmt
+        .debug_struct("Transaction::Heartbeat")
+        .field("period", period)
+        .field("signer", &hex::encode(signed.signer.to_bytes()))
+        .finish_non_exhaustive(),
     }
   }
 }
@@ -439,6 +455,19 @@ impl ReadWrite for Transaction {
         Ok(Transaction::SlashReport(points, Signed::read_without_nonce(reader, 0)?))
       }
 
+      12 => {
+        let mut hash = [0; 32];
+        reader.read_exact(&mut hash)?;
+        Ok(Transaction::SessionSummary(hash, Signed::read_without_nonce(reader, 0)?))
+      }
+
+      13 => {
+        let mut period = [0; 4];
+        reader.read_exact(&mut period)?;
+        let period = u32::from_le_bytes(period);
+        Ok(Transaction::Heartbeat(period, Signed::read(reader)?))
+      }
+
       _ => Err(io::Error::other("invalid transaction type")),
     }
   }
@@ -563,6 +592,16 @@ impl ReadWrite for Transaction {
         }
         signed.write_without_nonce(writer)
       }
+      Transaction::SessionSummary(hash, signed) => {
+        writer.write_all(&[12])?;
+        writer.write_all(hash)?;
+        signed.write_without_nonce(writer)
+      }
+      Transaction::Heartbeat(period, signed) => {
+        writer.write_all(&[13])?;
+        writer.write_all(&period.to_le_bytes())?;
+        signed.write(writer)
+      }
     }
   }
 }
@@ -597,6 +636,12 @@ impl TransactionTrait for Transaction {
       Transaction::SlashReport(_, signed) => {
         TransactionKind::Signed(b"slash_report".to_vec(), signed)
       }
+      Transaction::SessionSummary(_, signed) => {
+        TransactionKind::Signed(b"session_summary".to_vec(), signed)
+      }
+      Transaction::Heartbeat(_, signed) => {
+        TransactionKind::Signed(HEARTBEAT_ORDER.to_vec(), signed)
+      }
     }
   }
 
@@ -662,6 +707,9 @@ impl Transaction {
         Transaction::SignCompleted { .. } => panic!("signing SignCompleted"),
 
         Transaction::SlashReport(_, _) => 0,
+        Transaction::SessionSummary(_, _) => 0,
+        // Heartbeats are published with the next nonce for their order, as already set
+        Transaction::Heartbeat(_, ref signed) => signed.nonce,
       };
 
       (
@@ -685,6 +733,8 @@ impl Transaction {
           Transaction::SignCompleted { .. } => panic!("signing SignCompleted"),
 
           Transaction::SlashReport(_, ref mut signed) => signed,
+          Transaction::SessionSummary(_, ref mut signed) => signed,
+          Transaction::Heartbeat(_, ref mut signed) => signed,
         },
       )
     }
@@ -110,7 +110,8 @@ impl<T: TransactionTrait> ReadWrite for Block<T> {
 impl<T: TransactionTrait> Block<T> {
   /// Create a new block.
   ///
-  /// mempool is expected to only have valid, non-conflicting transactions, sorted by nonce.
+  /// mempool is expected to only have valid, non-conflicting transactions, with each signer's
+  /// transactions for an order sorted by nonce.
   pub(crate) fn new(parent: [u8; 32], provided: Vec<T>, mempool: Vec<Transaction<T>>) -> Self {
     let mut txs = vec![];
     for tx in provided {
@@ -132,21 +133,14 @@ impl<T: TransactionTrait> Block<T> {
     // then signed
     txs.extend(signed);
 
-    // Check TXs are sorted by nonce.
-    let nonce = |tx: &Transaction<T>| {
-      if let TransactionKind::Signed(_, Signed { nonce, .. }) = tx.kind() {
-        *nonce
-      } else {
-        0
-      }
-    };
-    let mut last = 0;
+    // Check TXs are sorted by nonce, per signer and order
+    let mut last = HashMap::new();
     for tx in &txs {
-      let nonce = nonce(tx);
-      if nonce < last {
-        panic!("TXs in mempool weren't ordered by nonce");
+      if let TransactionKind::Signed(order, Signed { signer, nonce, .. }) = tx.kind() {
+        if last.insert((*signer, order), *nonce).is_some_and(|last| *nonce <= last) {
+          panic!("TXs in mempool weren't ordered by nonce");
+        }
       }
-      last = nonce;
     }
 
     let mut res =
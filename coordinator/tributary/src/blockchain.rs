@@ -1,4 +1,7 @@
-use std::collections::{VecDeque, HashSet};
+use std::{
+  io::{self, Read},
+  collections::{VecDeque, HashSet, HashMap},
+};
 
 use ciphersuite::{group::GroupEncoding, Ciphersuite, Ristretto};
 
@@ -6,13 +9,32 @@ use serai_db::{Get, DbTxn, Db};
 
 use scale::Decode;
 
-use tendermint::ext::{Network, Commit};
+use tendermint::ext::{Network, Commit, verify_commit};
 
 use crate::{
-  ReadWrite, ProvidedError, ProvidedTransactions, BlockError, Block, Mempool, Transaction,
+  ReadWrite, ProvidedError, ProvidedTransactions, BlockError, Block, Mempool, MempoolQueue,
+  Transaction, Validators, merkle,
   transaction::{Signed, TransactionKind, TransactionError, Transaction as TransactionTrait},
 };
 
+// Read a length-prefixed entry, as written by `export`, returning None if the reader has ended
+fn read_entry<R: io::Read>(reader: &mut R) -> io::Result<Option<Vec<u8>>> {
+  let mut len = [0; 4];
+  if reader.read(&mut len[.. 1])? == 0 {
+    return Ok(None);
+  }
+  reader.read_exact(&mut len[1 ..])?;
+  let len = u32::from_le_bytes(len);
+
+  // This doesn't allocate the claimed length up front, so a corrupted length won't cause an OOM
+  let mut entry = vec![];
+  reader.by_ref().take(u64::from(len)).read_to_end(&mut entry)?;
+  if entry.len() != usize::try_from(len).unwrap() {
+    Err(io::Error::from(io::ErrorKind::UnexpectedEof))?;
+  }
+  Ok(Some(entry))
+}
+
 #[derive(Debug)]
 pub(crate) struct Blockchain<D: Db, T: TransactionTrait> {
   db: Option<D>,
@@ -32,8 +54,8 @@ impl<D: Db, T: TransactionTrait> Blockchain<D, T> {
   fn tip_key(genesis: [u8; 32]) -> Vec<u8> {
     D::key(b"tributary_blockchain", b"tip", genesis)
   }
-  fn block_number_key(&self) -> Vec<u8> {
-    D::key(b"tributary_blockchain", b"block_number", self.genesis)
+  fn block_number_key(genesis: [u8; 32]) -> Vec<u8> {
+    D::key(b"tributary_blockchain", b"block_number", genesis)
   }
   fn block_key(genesis: &[u8], hash: &[u8; 32]) -> Vec<u8> {
     D::key(b"tributary_blockchain", b"block", [genesis, hash].concat())
@@ -86,7 +108,8 @@ impl<D: Db, T: TransactionTrait> Blockchain<D, T> {
 
     if let Some((block_number, tip)) = {
       let db = res.db.as_ref().unwrap();
-      db.get(res.block_number_key()).map(|number| (number, db.get(Self::tip_key(genesis)).unwrap()))
+      db.get(Self::block_number_key(genesis))
+        .map(|number| (number, db.get(Self::tip_key(genesis)).unwrap()))
     } {
       res.block_number = u64::from_le_bytes(block_number.try_into().unwrap());
       res.tip.copy_from_slice(&tip);
@@ -103,6 +126,20 @@ impl<D: Db, T: TransactionTrait> Blockchain<D, T> {
     self.block_number
   }
 
+  pub(crate) fn mempool_size(&self) -> usize {
+    self.mempool.len()
+  }
+
+  pub(crate) fn mempool_queues(&self) -> Vec<MempoolQueue> {
+    self.mempool.queue_state()
+  }
+
+  pub(crate) fn block_number_from_db(db: &D, genesis: [u8; 32]) -> u64 {
+    db.get(Self::block_number_key(genesis))
+      .map(|number| u64::from_le_bytes(number.try_into().unwrap()))
+      .unwrap_or(0)
+  }
+
   pub(crate) fn block_from_db(db: &D, genesis: [u8; 32], block: &[u8; 32]) -> Option<Block<T>> {
     db.get(Self::block_key(&genesis, block))
       .map(|bytes| Block::<T>::read::<&[u8]>(&mut bytes.as_ref()).unwrap())
@@ -151,6 +188,139 @@ impl<D: Db, T: TransactionTrait> Blockchain<D, T> {
     db.get(Self::tip_key(genesis)).map_or(genesis, |bytes| bytes.try_into().unwrap())
   }
 
+  pub(crate) fn export<W: io::Write>(db: &D, genesis: [u8; 32], writer: &mut W) -> io::Result<u64> {
+    let block_number = Self::block_number_from_db(db, genesis);
+    for number in 1 ..= block_number {
+      let Some(hash) = Self::block_hash_from_db(db, genesis, number) else {
+        Err(io::Error::other("exporting a pruned Tributary"))?
+      };
+      let block = Self::block_from_db(db, genesis, &hash).unwrap().serialize();
+      let commit = Self::commit_from_db(db, genesis, &hash).unwrap();
+      for bytes in [block, commit] {
+        writer.write_all(&u32::try_from(bytes.len()).unwrap().to_le_bytes())?;
+        writer.write_all(&bytes)?;
+      }
+    }
+    Ok(block_number)
+  }
+
+  pub(crate) fn import<R: io::Read>(
+    &mut self,
+    validators: &Validators,
+    reader: &mut R,
+  ) -> io::Result<u64> {
+    let invalid = |msg| io::Error::new(io::ErrorKind::InvalidData, msg);
+
+    let mut number = 0;
+    let mut parent = self.genesis;
+    let mut imported = 0;
+    while let Some(block) = read_entry(reader)? {
+      let commit =
+        read_entry(reader)?.ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+      let block = Block::<T>::read::<&[u8]>(&mut block.as_ref())?;
+      number += 1;
+
+      if block.parent() != parent {
+        Err(invalid("block didn't build off the prior block"))?;
+      }
+      let hash = block.hash();
+      parent = hash;
+
+      // Skip the blocks we already have, which may have been pruned
+      if number <= self.block_number {
+        if self.block_hash(number).is_some_and(|existing| existing != hash) {
+          Err(invalid("block conflicted with our own"))?;
+        }
+        continue;
+      }
+
+      if merkle(&block.transactions.iter().map(Transaction::hash).collect::<Vec<_>>()) !=
+        block.header.transactions
+      {
+        Err(invalid("block's transactions didn't match its header"))?;
+      }
+
+      // The commit proves the validators finalized this block, and accordingly that its
+      // transactions were valid, so they aren't individually verified
+      let mut commit_ref = commit.as_ref();
+      let valid_commit = Commit::<Validators>::decode(&mut commit_ref)
+        .is_ok_and(|decoded| verify_commit(validators, validators, &hash, &decoded));
+      if !(valid_commit && commit_ref.is_empty()) {
+        Err(invalid("block had an invalid commit"))?;
+      }
+
+      // Check the provided transactions against those locally provided, as `complete` expects
+      let mut provided = HashMap::new();
+      for tx in &block.transactions {
+        if let TransactionKind::Provided(order) = tx.kind() {
+          let i = provided.entry(order).or_insert(0);
+          if self
+            .provided
+            .transactions
+            .get(order)
+            .and_then(|txs| txs.get(*i))
+            .is_some_and(|local| local.hash() != tx.hash())
+          {
+            Err(invalid("block had a distinct provided transaction"))?;
+          }
+          *i += 1;
+        }
+      }
+
+      self.apply_block(&block, commit);
+      imported += 1;
+    }
+    Ok(imported)
+  }
+
+  pub(crate) fn prune(db: &mut D, genesis: [u8; 32]) {
+    let tip_number = Self::block_number_from_db(db, genesis);
+    let mut parent = genesis;
+    for number in 1 ..= tip_number {
+      // Each block is pruned with its own transaction, with blocks already pruned skipped, so an
+      // interrupted prune may simply be resumed
+      let Some(hash) = Self::block_hash_from_db(db, genesis, number) else { continue };
+      let block = Self::block_from_db(db, genesis, &hash).unwrap();
+
+      let mut txn = db.txn();
+      let mut provided = HashMap::new();
+      for tx in &block.transactions {
+        match tx.kind() {
+          TransactionKind::Provided(order) => {
+            *provided.entry(order).or_insert(0) += 1;
+            txn.del(Self::provided_included_key(&genesis, &tx.hash()));
+          }
+          TransactionKind::Unsigned => txn.del(Self::unsigned_included_key(&genesis, &tx.hash())),
+          TransactionKind::Signed(order, Signed { signer, .. }) => {
+            txn.del(Self::next_nonce_key(&genesis, signer, &order))
+          }
+        }
+      }
+      for (order, amount) in provided {
+        let block_key =
+          ProvidedTransactions::<D, T>::block_provided_quantity_key(&genesis, &hash, order);
+        // The quantity provided on-chain as of this block, making the IDs of the transactions
+        // provided by this block the prior `amount` IDs
+        let quantity = u32::from_le_bytes(txn.get(&block_key).unwrap().try_into().unwrap());
+        for id in (quantity - amount) .. quantity {
+          txn.del(ProvidedTransactions::<D, T>::on_chain_provided_key(&genesis, order, id));
+        }
+        txn.del(block_key);
+      }
+
+      // Only retain the tip, and its commit
+      if number != tip_number {
+        txn.del(Self::block_key(&genesis, &hash));
+        txn.del(Self::commit_key(&genesis, &hash));
+        txn.del(Self::block_hash_key(&genesis, number));
+      }
+      txn.del(Self::block_after_key(&genesis, &parent));
+      txn.commit();
+
+      parent = hash;
+    }
+  }
+
   pub(crate) fn add_transaction<N: Network>(
     &mut self,
     internal: bool,
@@ -189,6 +359,11 @@ impl<D: Db, T: TransactionTrait> Blockchain<D, T> {
     )
   }
 
+  /// The transactions within the mempool, which may have been loaded from the DB.
+  pub(crate) fn mempool_transactions(&mut self) -> Vec<Transaction<T>> {
+    self.mempool.block()
+  }
+
   pub(crate) fn provide_transaction(&mut self, tx: T) -> Result<(), ProvidedError> {
     self.provided.provide(tx)
   }
@@ -279,7 +454,30 @@ impl<D: Db, T: TransactionTrait> Blockchain<D, T> {
     schema: &N::SignatureScheme,
   ) -> Result<(), BlockError> {
     self.verify_block::<N>(block, schema, true)?;
+    // None of the assertions within should be reachable since we verified the block
+    self.apply_block(block, commit);
+
+    // Retry the transactions deferred for exceeding their signer's quotas, as this block may have
+    // freed them
+    for tx in self.mempool.deferred() {
+      let hash = tx.hash();
+      match self.add_transaction::<N>(false, tx, schema) {
+        // Still over quota, so leave it deferred
+        Err(
+          TransactionError::TooManyInMempool |
+          TransactionError::TooLargeForMempool |
+          TransactionError::TooFrequent,
+        ) => {}
+        // Either added or no longer valid (such as if it was included on-chain)
+        _ => self.mempool.undefer(&hash),
+      }
+    }
 
+    Ok(())
+  }
+
+  // Apply a block, which must already be known to be valid, to the chain
+  fn apply_block(&mut self, block: &Block<T>, commit: Vec<u8>) {
     log::info!(
       "adding block {} to tributary {} with {} TXs",
       hex::encode(block.hash()),
@@ -287,8 +485,6 @@ impl<D: Db, T: TransactionTrait> Blockchain<D, T> {
       block.transactions.len(),
     );
 
-    // None of the following assertions should be reachable since we verified the block
-
     // Take it from the Option so Rust doesn't consider self as mutably borrowed thanks to the
     // existence of the txn
     let mut db = self.db.take().unwrap();
@@ -298,7 +494,7 @@ impl<D: Db, T: TransactionTrait> Blockchain<D, T> {
     txn.put(Self::tip_key(self.genesis), self.tip);
 
     self.block_number += 1;
-    txn.put(self.block_number_key(), self.block_number.to_le_bytes());
+    txn.put(Self::block_number_key(self.genesis), self.block_number.to_le_bytes());
 
     txn.put(Self::block_hash_key(&self.genesis, self.block_number), self.tip);
 
@@ -335,7 +531,5 @@ impl<D: Db, T: TransactionTrait> Blockchain<D, T> {
     for tx in self.next_block_notifications.drain(..) {
       let _ = tx.send(());
     }
-
-    Ok(())
   }
 }
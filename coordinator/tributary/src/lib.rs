@@ -12,7 +12,7 @@ use futures_channel::mpsc::UnboundedReceiver;
 use futures_util::{StreamExt, SinkExt};
 use ::tendermint::{
   ext::{BlockNumber, Commit, Block as BlockTrait, Network},
-  SignedMessageFor, SyncedBlock, SyncedBlockSender, SyncedBlockResultReceiver, MessageSender,
+  Data, SignedMessageFor, SyncedBlock, SyncedBlockSender, SyncedBlockResultReceiver, MessageSender,
   TendermintMachine, TendermintHandle,
 };
 
@@ -42,6 +42,11 @@ pub(crate) use blockchain::*;
 
 mod mempool;
 pub(crate) use mempool::*;
+pub use mempool::MempoolQueue;
+
+mod metrics;
+pub use metrics::Metrics;
+pub(crate) use metrics::MetricsCollector;
 
 pub mod tendermint;
 pub(crate) use crate::tendermint::*;
@@ -53,6 +58,13 @@ pub mod tests;
 pub const TRANSACTION_SIZE_LIMIT: usize = 3_000_000;
 /// Amount of transactions a single account may have in the mempool.
 pub const ACCOUNT_MEMPOOL_LIMIT: u32 = 50;
+/// Total size of the transactions a single account may have in the mempool.
+pub const ACCOUNT_MEMPOOL_SIZE_LIMIT: usize = 2 * TRANSACTION_SIZE_LIMIT;
+/// Amount of transactions a single account may have added to the mempool within the last
+/// `ACCOUNT_RATE_LIMIT_WINDOW`.
+pub const ACCOUNT_RATE_LIMIT: u32 = 2 * ACCOUNT_MEMPOOL_LIMIT;
+/// The window, in seconds, `ACCOUNT_RATE_LIMIT` applies over.
+pub const ACCOUNT_RATE_LIMIT_WINDOW: u64 = 60;
 /// Block size limit.
 // This targets a growth limit of roughly 45 GB a day, under load, in order to prevent a malicious
 // participant from flooding disks and causing out of space errors in order processes.
@@ -61,6 +73,21 @@ pub const BLOCK_SIZE_LIMIT: usize = 3_001_000;
 pub(crate) const TENDERMINT_MESSAGE: u8 = 0;
 pub(crate) const TRANSACTION_MESSAGE: u8 = 1;
 
+/// The validity of a message received over the P2P network.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MessageValidity {
+  /// The message was new and valid, and should be propagated.
+  Valid,
+  /// The message wasn't invalid, yet shouldn't be propagated.
+  ///
+  /// This is for duplicates, messages which can't currently be added, and messages which are
+  /// propagated by other means (such as the Tendermint machine re-broadcasting consensus
+  /// messages).
+  Ignore,
+  /// The message was malformed or incorrectly signed.
+  Invalid,
+}
+
 #[allow(clippy::large_enum_variant)]
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub enum Transaction<T: TransactionTrait> {
@@ -163,6 +190,7 @@ impl<D: Db, T: TransactionTrait, P: P2p> Tributary<D, T, P> {
     start_time: u64,
     key: Zeroizing<<Ristretto as Ciphersuite>::F>,
     validators: Vec<(<Ristretto as Ciphersuite>::G, u64)>,
+    proposer_schedule: ProposerSchedule,
     p2p: P,
   ) -> Option<Self> {
     log::info!("new Tributary with genesis {}", hex::encode(genesis));
@@ -170,7 +198,7 @@ impl<D: Db, T: TransactionTrait, P: P2p> Tributary<D, T, P> {
     let validators_vec = validators.iter().map(|validator| validator.0).collect::<Vec<_>>();
 
     let signer = Arc::new(Signer::new(genesis, key));
-    let validators = Arc::new(Validators::new(genesis, validators)?);
+    let validators = Arc::new(Validators::new(genesis, validators, proposer_schedule)?);
 
     let mut blockchain = Blockchain::new(db.clone(), genesis, &validators_vec);
     let block_number = BlockNumber(blockchain.block_number());
@@ -183,9 +211,13 @@ impl<D: Db, T: TransactionTrait, P: P2p> Tributary<D, T, P> {
     let proposal = TendermintBlock(
       blockchain.build_block::<TendermintNetwork<D, T, P>>(&validators).serialize(),
     );
+    // Transactions in the mempool were persisted before a restart, yet may never have reached the
+    // other validators, so they're rebroadcast once the network is created
+    let mempool = blockchain.mempool_transactions();
     let blockchain = Arc::new(RwLock::new(blockchain));
 
-    let network = TendermintNetwork { genesis, signer, validators, blockchain, p2p };
+    let metrics = Arc::new(MetricsCollector::new(block_number.0));
+    let network = TendermintNetwork { genesis, signer, validators, blockchain, metrics, p2p };
 
     let TendermintHandle { synced_block, synced_block_result, messages, machine } =
       TendermintMachine::new(
@@ -199,6 +231,12 @@ impl<D: Db, T: TransactionTrait, P: P2p> Tributary<D, T, P> {
       .await;
     tokio::spawn(machine.run());
 
+    for tx in mempool {
+      let mut to_broadcast = vec![TRANSACTION_MESSAGE];
+      tx.write(&mut to_broadcast).unwrap();
+      network.p2p.broadcast(genesis, to_broadcast).await;
+    }
+
     Some(Self {
       db,
       genesis,
@@ -228,6 +266,19 @@ impl<D: Db, T: TransactionTrait, P: P2p> Tributary<D, T, P> {
     TributaryReader(self.db.clone(), self.genesis, PhantomData)
   }
 
+  /// Metrics on this Tributary's consensus and gossip.
+  pub async fn metrics(&self) -> Metrics {
+    let mut metrics = self.network.metrics.metrics();
+    metrics.mempool_size = self.network.blockchain.read().await.mempool_size();
+    metrics
+  }
+
+  /// The queues of signed transactions within this Tributary's mempool, with the queue which has
+  /// been waiting the longest first.
+  pub async fn mempool_queues(&self) -> Vec<MempoolQueue> {
+    self.network.blockchain.read().await.mempool_queues()
+  }
+
   pub async fn provide_transaction(&self, tx: T) -> Result<(), ProvidedError> {
     self.network.blockchain.write().await.provide_transaction(tx)
   }
@@ -244,11 +295,26 @@ impl<D: Db, T: TransactionTrait, P: P2p> Tributary<D, T, P> {
   // Safe to be &self since the only meaningful usage of self is self.network.blockchain which
   // successfully acquires its own write lock
   pub async fn add_transaction(&self, tx: T) -> Result<bool, TransactionError> {
+    self.add_application_transaction(true, tx).await
+  }
+
+  // Add a transaction received from a peer outside of the gossip protocol, such as in response to
+  // a request for it.
+  // Returns Ok(true) if new, Ok(false) if an already present unsigned, or the error.
+  pub async fn add_received_transaction(&self, tx: T) -> Result<bool, TransactionError> {
+    self.add_application_transaction(false, tx).await
+  }
+
+  async fn add_application_transaction(
+    &self,
+    internal: bool,
+    tx: T,
+  ) -> Result<bool, TransactionError> {
     let tx = Transaction::Application(tx);
     let mut to_broadcast = vec![TRANSACTION_MESSAGE];
     tx.write(&mut to_broadcast).unwrap();
     let res = self.network.blockchain.write().await.add_transaction::<TendermintNetwork<D, T, P>>(
-      true,
+      internal,
       tx,
       &self.network.signature_scheme(),
     );
@@ -258,6 +324,22 @@ impl<D: Db, T: TransactionTrait, P: P2p> Tributary<D, T, P> {
     res
   }
 
+  /// The application transactions within the mempool, which have yet to be included on-chain.
+  pub async fn mempool_transactions(&self) -> Vec<T> {
+    self
+      .network
+      .blockchain
+      .write()
+      .await
+      .mempool_transactions()
+      .into_iter()
+      .filter_map(|tx| match tx {
+        Transaction::Application(tx) => Some(tx),
+        Transaction::Tendermint(_) => None,
+      })
+      .collect()
+  }
+
   async fn sync_block_internal(
     &self,
     block: Block<T>,
@@ -303,14 +385,15 @@ impl<D: Db, T: TransactionTrait, P: P2p> Tributary<D, T, P> {
     self.sync_block_internal(block, commit, &mut result).await
   }
 
-  // Return true if the message should be rebroadcasted.
-  pub async fn handle_message(&self, msg: &[u8]) -> bool {
+  // Return if the message should be propagated, ignored, or if the sender should be penalized.
+  pub async fn handle_message(&self, msg: &[u8]) -> MessageValidity {
     match msg.first() {
       Some(&TRANSACTION_MESSAGE) => {
         let Ok(tx) = Transaction::read::<&[u8]>(&mut &msg[1 ..]) else {
           log::error!("received invalid transaction message");
-          return false;
+          return MessageValidity::Invalid;
         };
+        self.network.metrics.received_transaction();
 
         // TODO: Sync mempools with fellow peers
         // Can we just rebroadcast transactions not included for at least two blocks?
@@ -321,7 +404,13 @@ impl<D: Db, T: TransactionTrait, P: P2p> Tributary<D, T, P> {
             &self.network.signature_scheme(),
           );
         log::debug!("received transaction message. valid new transaction: {res:?}");
-        res == Ok(true)
+        match res {
+          Ok(true) => MessageValidity::Valid,
+          Err(TransactionError::InvalidSignature) => MessageValidity::Invalid,
+          // Duplicates, and transactions which are only invalid due to our current state (such as
+          // their nonce), may have been honestly relayed
+          Ok(false) | Err(_) => MessageValidity::Ignore,
+        }
       }
 
       Some(&TENDERMINT_MESSAGE) => {
@@ -329,14 +418,24 @@ impl<D: Db, T: TransactionTrait, P: P2p> Tributary<D, T, P> {
           SignedMessageFor::<TendermintNetwork<D, T, P>>::decode::<&[u8]>(&mut &msg[1 ..])
         else {
           log::error!("received invalid tendermint message");
-          return false;
+          return MessageValidity::Invalid;
         };
+        if !msg.verify_signature(&self.network.signature_scheme()) {
+          log::error!("received tendermint message with an invalid signature");
+          return MessageValidity::Invalid;
+        }
+        self.network.metrics.received_consensus_message(
+          msg.msg.block.0,
+          msg.msg.round.0,
+          matches!(msg.msg.data, Data::Proposal(..)),
+        );
 
         self.messages.write().await.send(msg).await.unwrap();
-        false
+        // The Tendermint machine re-broadcasts the messages it needs to
+        MessageValidity::Ignore
       }
 
-      _ => false,
+      _ => MessageValidity::Invalid,
     }
   }
 
@@ -371,6 +470,10 @@ impl<D: Db, T: TransactionTrait> TributaryReader<D, T> {
   pub fn block_after(&self, hash: &[u8; 32]) -> Option<[u8; 32]> {
     Blockchain::<D, T>::block_after(&self.0, self.1, hash)
   }
+  /// The hash of the block with the specified number, where the first block is numbered 1.
+  pub fn block_hash(&self, number: u64) -> Option<[u8; 32]> {
+    Blockchain::<D, T>::block_hash_from_db(&self.0, self.1, number)
+  }
   pub fn time_of_block(&self, hash: &[u8; 32]) -> Option<u64> {
     self
       .commit(hash)
@@ -381,8 +484,63 @@ impl<D: Db, T: TransactionTrait> TributaryReader<D, T> {
     Blockchain::<D, T>::locally_provided_txs_in_block(&self.0, &self.1, hash, order)
   }
 
+  /// The amount of times a signer's transactions were rejected from our mempool for exceeding
+  /// its quotas.
+  ///
+  /// This is a local observation, not agreed upon by the other validators.
+  pub fn mempool_violations(&self, signer: &<Ristretto as Ciphersuite>::G) -> u32 {
+    Mempool::<D, T>::violations_from_db(&self.0, self.1, signer)
+  }
+
   // This isn't static, yet can be read with only minor discrepancy risks
   pub fn tip(&self) -> [u8; 32] {
     Blockchain::<D, T>::tip_from_db(&self.0, self.1)
   }
+  pub fn block_number(&self) -> u64 {
+    Blockchain::<D, T>::block_number_from_db(&self.0, self.1)
+  }
+}
+
+/// Export a Tributary's blocks, in order, each followed by its commit, returning the amount of
+/// blocks exported.
+///
+/// Every block and commit is prefixed by its length, as a little-endian u32. This errors if the
+/// Tributary was pruned.
+pub fn export<D: Db, T: TransactionTrait, W: io::Write>(
+  db: &D,
+  genesis: [u8; 32],
+  writer: &mut W,
+) -> io::Result<u64> {
+  Blockchain::<D, T>::export(db, genesis, writer)
+}
+
+/// Sync a Tributary to the tip of an export, returning the amount of blocks added.
+///
+/// Every block's commit is verified, yet the transactions within aren't re-verified as the
+/// commits prove the validators already agreed on their validity. This makes syncing from an
+/// export much faster than syncing the blocks from peers, which have their transactions verified
+/// and are added one at a time. Blocks already present are skipped, so an interrupted import may
+/// simply be retried.
+///
+/// This MUST NOT be called while the Tributary is running.
+pub fn import<D: Db, T: TransactionTrait, R: io::Read>(
+  db: D,
+  genesis: [u8; 32],
+  validators: Vec<(<Ristretto as Ciphersuite>::G, u64)>,
+  reader: &mut R,
+) -> io::Result<u64> {
+  let participants = validators.iter().map(|validator| validator.0).collect::<Vec<_>>();
+  // The proposer schedule doesn't affect the verification of commits
+  let validators = Validators::new(genesis, validators, ProposerSchedule::Shuffled)
+    .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "validator had a weight of 0"))?;
+  Blockchain::<D, T>::new(db, genesis, &participants).import(&validators, reader)
+}
+
+/// Prune a retired Tributary from the database.
+///
+/// This deletes every block and commit other than the tip and its commit, along with the indexes
+/// over their transactions. The tip's commit is retained as proof of the Tributary's final state.
+/// This MUST NOT be called until the Tributary has been retired.
+pub fn prune<D: Db, T: TransactionTrait>(db: &mut D, genesis: [u8; 32]) {
+  Blockchain::<D, T>::prune(db, genesis)
 }
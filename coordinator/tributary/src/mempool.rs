@@ -1,13 +1,17 @@
-use std::collections::HashMap;
+use std::{
+  time::{Duration, Instant},
+  collections::{VecDeque, HashMap},
+};
 
-use ciphersuite::{Ciphersuite, Ristretto};
+use ciphersuite::{group::GroupEncoding, Ciphersuite, Ristretto};
 
-use serai_db::{DbTxn, Db};
+use serai_db::{Get, DbTxn, Db};
 
 use tendermint::ext::{Network, Commit};
 
 use crate::{
-  ACCOUNT_MEMPOOL_LIMIT, ReadWrite,
+  ACCOUNT_MEMPOOL_LIMIT, ACCOUNT_MEMPOOL_SIZE_LIMIT, ACCOUNT_RATE_LIMIT, ACCOUNT_RATE_LIMIT_WINDOW,
+  ReadWrite,
   transaction::{
     Signed, TransactionKind, TransactionError, Transaction as TransactionTrait, verify_transaction,
   },
@@ -15,6 +19,31 @@ use crate::{
   Transaction,
 };
 
+/// The transactions within the mempool from a signer, for a specific order.
+///
+/// A queue's transactions are included in order of their nonces, with blocks including a
+/// transaction from each queue in turn.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct MempoolQueue {
+  /// The signer of the queued transactions.
+  pub signer: [u8; 32],
+  /// The order the queued transactions are within.
+  pub order: Vec<u8>,
+  /// The amount of queued transactions.
+  pub transactions: usize,
+}
+
+fn nonce<T: TransactionTrait>(tx: &Transaction<T>) -> u32 {
+  if let TransactionKind::Signed(_, Signed { nonce, .. }) = tx.kind() {
+    *nonce
+  } else {
+    unreachable!()
+  }
+}
+
+// A queue of signed transactions, keyed by their signer and order
+type Queue<'a, T> = ((<Ristretto as Ciphersuite>::G, Vec<u8>), Vec<&'a Transaction<T>>);
+
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub(crate) struct Mempool<D: Db, T: TransactionTrait> {
   db: D,
@@ -22,7 +51,15 @@ pub(crate) struct Mempool<D: Db, T: TransactionTrait> {
 
   last_nonce_in_mempool: HashMap<(<Ristretto as Ciphersuite>::G, Vec<u8>), u32>,
   txs: HashMap<[u8; 32], Transaction<T>>,
+  // The hashes of the transactions, in the order they were added to the mempool
+  added: Vec<[u8; 32]>,
   txs_per_signer: HashMap<<Ristretto as Ciphersuite>::G, u32>,
+  bytes_per_signer: HashMap<<Ristretto as Ciphersuite>::G, usize>,
+  // When each signer's transactions were added to the mempool, within the rate limit's window
+  additions_per_signer: HashMap<<Ristretto as Ciphersuite>::G, VecDeque<Instant>>,
+  // Transactions received which exceeded their signer's quotas, to be retried once the quotas
+  // have freed
+  deferred: HashMap<[u8; 32], Transaction<T>>,
 }
 
 impl<D: Db, T: TransactionTrait> Mempool<D, T> {
@@ -32,6 +69,37 @@ impl<D: Db, T: TransactionTrait> Mempool<D, T> {
   fn current_mempool_key(&self) -> Vec<u8> {
     D::key(b"tributary_mempool", b"current", self.genesis)
   }
+  fn violations_key(genesis: &[u8; 32], signer: &<Ristretto as Ciphersuite>::G) -> Vec<u8> {
+    D::key(b"tributary_mempool", b"violations", [genesis.as_ref(), &signer.to_bytes()].concat())
+  }
+
+  pub(crate) fn violations_from_db(
+    getter: &impl Get,
+    genesis: [u8; 32],
+    signer: &<Ristretto as Ciphersuite>::G,
+  ) -> u32 {
+    getter
+      .get(Self::violations_key(&genesis, signer))
+      .map_or(0, |bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+  }
+
+  // Record a signer exceeded one of their quotas, returning the error to reject their transaction
+  // with
+  fn violation(
+    &mut self,
+    signer: &<Ristretto as Ciphersuite>::G,
+    error: TransactionError,
+  ) -> TransactionError {
+    let violations = Self::violations_from_db(&self.db, self.genesis, signer) + 1;
+    log::warn!(
+      "{} exceeded their mempool quota ({error}), {violations} violations so far",
+      hex::encode(signer.to_bytes()),
+    );
+    let mut txn = self.db.txn();
+    txn.put(Self::violations_key(&self.genesis, signer), violations.to_le_bytes());
+    txn.commit();
+    error
+  }
 
   // save given tx to the mempool db
   fn save_tx(&mut self, tx: Transaction<T>) {
@@ -46,6 +114,7 @@ impl<D: Db, T: TransactionTrait> Mempool<D, T> {
     txn.put(current_mempool_key, current_mempool);
     txn.commit();
 
+    self.added.push(tx_hash);
     self.txs.insert(tx_hash, tx);
   }
 
@@ -63,7 +132,11 @@ impl<D: Db, T: TransactionTrait> Mempool<D, T> {
       genesis,
       last_nonce_in_mempool: HashMap::new(),
       txs: HashMap::new(),
+      added: vec![],
       txs_per_signer: HashMap::new(),
+      bytes_per_signer: HashMap::new(),
+      additions_per_signer: HashMap::new(),
+      deferred: HashMap::new(),
     };
 
     let current_mempool = res.db.get(res.current_mempool_key()).unwrap_or(vec![]);
@@ -74,6 +147,8 @@ impl<D: Db, T: TransactionTrait> Mempool<D, T> {
         Transaction::read::<&[u8]>(&mut res.db.get(res.transaction_key(&hash)).unwrap().as_ref())
           .unwrap();
       debug_assert_eq!(tx.hash(), hash);
+      let len = tx.serialize().len();
+      res.added.push(hash);
 
       match tx {
         Transaction::Tendermint(tx) => {
@@ -83,6 +158,7 @@ impl<D: Db, T: TransactionTrait> Mempool<D, T> {
           TransactionKind::Signed(order, Signed { signer, nonce, .. }) => {
             let amount = *res.txs_per_signer.get(signer).unwrap_or(&0) + 1;
             res.txs_per_signer.insert(*signer, amount);
+            *res.bytes_per_signer.entry(*signer).or_insert(0) += len;
 
             if let Some(prior_nonce) =
               res.last_nonce_in_mempool.insert((*signer, order.clone()), *nonce)
@@ -146,16 +222,62 @@ impl<D: Db, T: TransactionTrait> Mempool<D, T> {
               next_nonce = *mempool_last_nonce + 1;
             }
 
+            // The transaction is verified before the quotas are checked so a violation is only
+            // attributed to a signer who actually signed the transaction
+            verify_transaction(app_tx, self.genesis, &mut |_, _| Some(next_nonce))?;
+
             // If we have too many transactions from this sender, don't add this yet UNLESS we are
             // this sender
             let amount_in_pool = *self.txs_per_signer.get(signer).unwrap_or(&0) + 1;
-            if !internal && (amount_in_pool > ACCOUNT_MEMPOOL_LIMIT) {
-              Err(TransactionError::TooManyInMempool)?;
+            let bytes_in_pool =
+              *self.bytes_per_signer.get(signer).unwrap_or(&0) + tx.serialize().len();
+            let now = Instant::now();
+            let additions = self.additions_per_signer.entry(*signer).or_default();
+            while additions.front().is_some_and(|addition| {
+              now.duration_since(*addition) >= Duration::from_secs(ACCOUNT_RATE_LIMIT_WINDOW)
+            }) {
+              additions.pop_front();
+            }
+            let recent_additions = additions.len();
+            if !internal {
+              let exceeded = if amount_in_pool > ACCOUNT_MEMPOOL_LIMIT {
+                Some(TransactionError::TooManyInMempool)
+              } else if bytes_in_pool > ACCOUNT_MEMPOOL_SIZE_LIMIT {
+                Some(TransactionError::TooLargeForMempool)
+              } else if recent_additions >= usize::try_from(ACCOUNT_RATE_LIMIT).unwrap() {
+                Some(TransactionError::TooFrequent)
+              } else {
+                None
+              };
+              if let Some(error) = exceeded {
+                // An honest signer may exceed their quotas when the chain is slow to include
+                // their transactions, so defer the transaction instead of dropping it. A retry of
+                // an already deferred transaction isn't another violation.
+                let hash = tx.hash();
+                if self.deferred.contains_key(&hash) {
+                  Err(error)?;
+                }
+                let deferred_from_signer = self
+                  .deferred
+                  .values()
+                  .filter(|deferred| {
+                    matches!(
+                      deferred.kind(),
+                      TransactionKind::Signed(_, Signed { signer: other, .. }) if other == signer
+                    )
+                  })
+                  .count();
+                if deferred_from_signer < usize::try_from(ACCOUNT_MEMPOOL_LIMIT).unwrap() {
+                  self.deferred.insert(hash, tx.clone());
+                }
+                Err(self.violation(signer, error))?;
+              }
             }
 
-            verify_transaction(app_tx, self.genesis, &mut |_, _| Some(next_nonce))?;
             self.last_nonce_in_mempool.insert((*signer, order.clone()), next_nonce);
             self.txs_per_signer.insert(*signer, amount_in_pool);
+            self.bytes_per_signer.insert(*signer, bytes_in_pool);
+            self.additions_per_signer.entry(*signer).or_default().push_back(now);
           }
           TransactionKind::Unsigned => {
             // check we have the tx in the pool/chain
@@ -184,39 +306,74 @@ impl<D: Db, T: TransactionTrait> Mempool<D, T> {
     self.last_nonce_in_mempool.get(&(*signer, order)).copied().map(|nonce| nonce + 1)
   }
 
+  // The queues of signed transactions within the mempool, each sorted by nonce
+  //
+  // The queues are sorted by when the transaction at their head was added, so the queue which has
+  // been waiting the longest is first.
+  fn queues(&self) -> Vec<Queue<'_, T>> {
+    let added =
+      self.added.iter().enumerate().map(|(i, hash)| (*hash, i)).collect::<HashMap<_, _>>();
+
+    let mut queues = HashMap::<_, Vec<_>>::new();
+    for tx in self.txs.values() {
+      if let TransactionKind::Signed(order, Signed { signer, .. }) = tx.kind() {
+        queues.entry((*signer, order)).or_default().push(tx);
+      }
+    }
+
+    let mut queues = queues.into_iter().collect::<Vec<_>>();
+    for (_, queue) in &mut queues {
+      queue.sort_by_key(|tx| nonce(tx));
+    }
+    queues.sort_by_key(|(_, queue)| added[&queue[0].hash()]);
+    queues
+  }
+
   /// Get transactions to include in a block.
+  ///
+  /// Signed transactions are taken from each queue in turn, so a signer's transactions for one
+  /// order (such as a signing session) don't delay their transactions for other orders. If the
+  /// block would be too large, the transactions from the latest turns are the ones omitted.
   pub(crate) fn block(&mut self) -> Vec<Transaction<T>> {
     let mut unsigned = vec![];
-    let mut signed = vec![];
-    for hash in self.txs.keys().copied().collect::<Vec<_>>() {
-      let tx = &self.txs[&hash];
-
+    for tx in self.txs.values() {
       match tx.kind() {
-        TransactionKind::Signed(_, Signed { .. }) => {
-          signed.push(tx.clone());
-        }
-        TransactionKind::Unsigned => {
-          unsigned.push(tx.clone());
-        }
+        TransactionKind::Signed(_, _) => {}
+        TransactionKind::Unsigned => unsigned.push(tx.clone()),
         _ => panic!("provided transaction entered mempool"),
       }
     }
 
-    // Sort signed by nonce
-    let nonce = |tx: &Transaction<T>| {
-      if let TransactionKind::Signed(_, Signed { nonce, .. }) = tx.kind() {
-        *nonce
-      } else {
-        unreachable!()
-      }
-    };
-    signed.sort_by(|a, b| nonce(a).partial_cmp(&nonce(b)).unwrap());
+    let mut queues =
+      self.queues().into_iter().map(|(_, queue)| queue.into_iter()).collect::<Vec<_>>();
+    let mut signed = vec![];
+    while !queues.is_empty() {
+      queues.retain_mut(|queue| {
+        let Some(tx) = queue.next() else { return false };
+        signed.push(tx.clone());
+        true
+      });
+    }
 
     // unsigned first, then signed.
     unsigned.append(&mut signed);
     unsigned
   }
 
+  /// The queues of signed transactions within the mempool, with the queue which has been waiting
+  /// the longest first.
+  pub(crate) fn queue_state(&self) -> Vec<MempoolQueue> {
+    self
+      .queues()
+      .into_iter()
+      .map(|((signer, order), queue)| MempoolQueue {
+        signer: signer.to_bytes(),
+        order,
+        transactions: queue.len(),
+      })
+      .collect()
+  }
+
   /// Remove a transaction from the mempool.
   pub(crate) fn remove(&mut self, tx: &[u8; 32]) {
     let transaction_key = self.transaction_key(tx);
@@ -240,10 +397,13 @@ impl<D: Db, T: TransactionTrait> Mempool<D, T> {
     }
     txn.commit();
 
+    self.added.retain(|added| added != tx);
     if let Some(tx) = self.txs.remove(tx) {
       if let TransactionKind::Signed(order, Signed { signer, nonce, .. }) = tx.kind() {
         let amount = *self.txs_per_signer.get(signer).unwrap() - 1;
         self.txs_per_signer.insert(*signer, amount);
+        let bytes = *self.bytes_per_signer.get(signer).unwrap() - tx.serialize().len();
+        self.bytes_per_signer.insert(*signer, bytes);
 
         if self.last_nonce_in_mempool.get(&(*signer, order.clone())) == Some(nonce) {
           self.last_nonce_in_mempool.remove(&(*signer, order));
@@ -252,6 +412,23 @@ impl<D: Db, T: TransactionTrait> Mempool<D, T> {
     }
   }
 
+  /// The transactions deferred for exceeding their signer's quotas, sorted by nonce so they may
+  /// be retried in order.
+  pub(crate) fn deferred(&self) -> Vec<Transaction<T>> {
+    let mut deferred = self.deferred.values().cloned().collect::<Vec<_>>();
+    deferred.sort_by_key(|tx| nonce(tx));
+    deferred
+  }
+
+  /// Stop retrying a deferred transaction.
+  pub(crate) fn undefer(&mut self, tx: &[u8; 32]) {
+    self.deferred.remove(tx);
+  }
+
+  pub(crate) fn len(&self) -> usize {
+    self.txs.len()
+  }
+
   #[cfg(test)]
   pub(crate) fn txs(&self) -> &HashMap<[u8; 32], Transaction<T>> {
     &self.txs
@@ -0,0 +1,93 @@
+use core::time::Duration;
+use std::{sync::Mutex, time::Instant, collections::HashMap};
+
+/// Metrics on a Tributary's consensus and gossip, since it was created.
+#[derive(Clone, PartialEq, Eq, Default, Debug)]
+pub struct Metrics {
+  /// The amount of blocks added.
+  pub blocks: u64,
+  /// The amount of rounds it took to decide the added blocks, summed.
+  pub rounds: u64,
+  /// The round we're in for the block currently being decided.
+  pub round: u32,
+  /// The amount of first proposals received for blocks.
+  pub proposals: u64,
+  /// The time from adding a block to receiving the first proposal for the next block, summed.
+  pub proposal_latency: Duration,
+  /// The amount of consensus messages received over the P2P network.
+  pub consensus_messages_received: u64,
+  /// The amount of consensus messages we broadcast.
+  pub consensus_messages_broadcast: u64,
+  /// The amount of transactions received over the P2P network.
+  pub transactions_received: u64,
+  /// The amount of transactions currently within the mempool.
+  pub mempool_size: usize,
+  /// The amount of the added blocks' commits each validator participated in.
+  pub commits_signed: HashMap<[u8; 32], u64>,
+}
+
+#[derive(Debug)]
+struct State {
+  metrics: Metrics,
+  // The number of the block currently being decided
+  block: u64,
+  // When we started deciding it, and if we've received its first proposal
+  started: Instant,
+  proposed: bool,
+}
+
+#[derive(Debug)]
+pub(crate) struct MetricsCollector(Mutex<State>);
+
+impl MetricsCollector {
+  pub(crate) fn new(block_number: u64) -> Self {
+    MetricsCollector(Mutex::new(State {
+      metrics: Metrics::default(),
+      block: block_number + 1,
+      started: Instant::now(),
+      proposed: false,
+    }))
+  }
+
+  pub(crate) fn received_transaction(&self) {
+    self.0.lock().unwrap().metrics.transactions_received += 1;
+  }
+
+  pub(crate) fn received_consensus_message(&self, block: u64, round: u32, proposal: bool) {
+    let mut state = self.0.lock().unwrap();
+    state.metrics.consensus_messages_received += 1;
+    if proposal && (round == 0) && (block == state.block) && (!state.proposed) {
+      state.proposed = true;
+      let latency = state.started.elapsed();
+      state.metrics.proposals += 1;
+      state.metrics.proposal_latency += latency;
+    }
+  }
+
+  // The round is tracked via our own messages, as they can't be forged by other validators
+  pub(crate) fn broadcast_consensus_message(&self, block: u64, round: u32) {
+    let mut state = self.0.lock().unwrap();
+    state.metrics.consensus_messages_broadcast += 1;
+    if block == state.block {
+      state.metrics.round = state.metrics.round.max(round);
+    }
+  }
+
+  pub(crate) fn added_block(&self, block_number: u64, signers: &[[u8; 32]]) {
+    let mut state = self.0.lock().unwrap();
+    state.metrics.blocks += 1;
+    state.metrics.rounds += u64::from(state.metrics.round) + 1;
+    for signer in signers {
+      *state.metrics.commits_signed.entry(*signer).or_insert(0) += 1;
+    }
+
+    state.metrics.round = 0;
+    state.block = block_number + 1;
+    state.started = Instant::now();
+    state.proposed = false;
+  }
+
+  pub(crate) fn metrics(&self) -> Metrics {
+    self.0.lock().unwrap().metrics.clone()
+  }
+}
@@ -39,7 +39,7 @@ use tokio::sync::RwLock;
 
 use crate::{
   TENDERMINT_MESSAGE, TRANSACTION_MESSAGE, ReadWrite, transaction::Transaction as TransactionTrait,
-  Transaction, BlockHeader, Block, BlockError, Blockchain, P2p,
+  Transaction, BlockHeader, Block, BlockError, Blockchain, MetricsCollector, P2p,
 };
 
 pub mod tx;
@@ -121,6 +121,21 @@ impl SignerTrait for Signer {
   }
 }
 
+/// How a Tributary schedules its proposers.
+///
+/// Both schedules have every validator propose as many times as their weight over any
+/// `total_weight` blocks. They solely differ in how those proposals are spread.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ProposerSchedule {
+  /// A shuffle of every validator, repeated once per unit of weight.
+  ///
+  /// This may cluster a validator's proposals.
+  Shuffled,
+  /// Tendermint's proposer priority algorithm, which spreads every validator's proposals across
+  /// the schedule.
+  Spread,
+}
+
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct Validators {
   genesis: [u8; 32],
@@ -133,12 +148,13 @@ impl Validators {
   pub(crate) fn new(
     genesis: [u8; 32],
     validators: Vec<(<Ristretto as Ciphersuite>::G, u64)>,
+    schedule: ProposerSchedule,
   ) -> Option<Validators> {
     let mut total_weight = 0;
     let mut weights = HashMap::new();
 
     let mut transcript = RecommendedTranscript::new(b"Round Robin Randomization");
-    let mut robin = vec![];
+    let mut order = vec![];
     for (validator, weight) in validators {
       let validator = validator.to_bytes();
       if weight == 0 {
@@ -149,9 +165,44 @@ impl Validators {
 
       transcript.append_message(b"validator", validator);
       transcript.append_message(b"weight", weight.to_le_bytes());
-      robin.extend(vec![validator; usize::try_from(weight).unwrap()]);
+      order.push((validator, i64::try_from(weight).unwrap()));
     }
-    robin.shuffle(&mut ChaCha12Rng::from_seed(transcript.rng_seed(b"robin")));
+    let mut rng = ChaCha12Rng::from_seed(transcript.rng_seed(b"robin"));
+
+    let robin = match schedule {
+      ProposerSchedule::Shuffled => {
+        let mut robin = vec![];
+        for (validator, weight) in order {
+          robin.extend(vec![validator; usize::try_from(weight).unwrap()]);
+        }
+        robin.shuffle(&mut rng);
+        robin
+      }
+      ProposerSchedule::Spread => {
+        // Shuffle the validators so the order they were specified in doesn't decide ties in
+        // priority
+        order.shuffle(&mut rng);
+
+        // Schedule the proposers as Tendermint does. Every validator's priority is incremented by
+        // their weight, with the validator with the highest priority proposing and having their
+        // priority decremented by the total weight. Over total_weight proposals, this has every
+        // validator propose as many times as their weight, with their proposals spread across
+        // the schedule.
+        let total = i64::try_from(total_weight).unwrap();
+        let mut priorities = vec![0; order.len()];
+        let mut robin = Vec::with_capacity(usize::try_from(total_weight).unwrap());
+        for _ in 0 .. total_weight {
+          for (priority, (_, weight)) in priorities.iter_mut().zip(&order) {
+            *priority += weight;
+          }
+          // max_by_key returns the last maximum, so iterate in reverse to select the first
+          let proposer = (0 .. order.len()).rev().max_by_key(|i| priorities[*i]).unwrap();
+          priorities[proposer] -= total;
+          robin.push(order[proposer].0);
+        }
+        robin
+      }
+    };
 
     Some(Validators { genesis, total_weight, weights, robin })
   }
@@ -266,6 +317,7 @@ pub struct TendermintNetwork<D: Db, T: TransactionTrait, P: P2p> {
   pub(crate) signer: Arc<Signer>,
   pub(crate) validators: Arc<Validators>,
   pub(crate) blockchain: Arc<RwLock<Blockchain<D, T>>>,
+  pub(crate) metrics: Arc<MetricsCollector>,
 
   pub(crate) p2p: P,
 }
@@ -301,6 +353,7 @@ impl<D: Db, T: TransactionTrait, P: P2p> Network for TendermintNetwork<D, T, P>
   }
 
   async fn broadcast(&mut self, msg: SignedMessageFor<Self>) {
+    self.metrics.broadcast_consensus_message(msg.msg.block.0, msg.msg.round.0);
     let mut to_broadcast = vec![TENDERMINT_MESSAGE];
     to_broadcast.extend(msg.encode());
     self.p2p.broadcast(self.genesis, to_broadcast).await
@@ -388,6 +441,8 @@ impl<D: Db, T: TransactionTrait, P: P2p> Network for TendermintNetwork<D, T, P>
       );
       match block_res {
         Ok(()) => {
+          let block_number = self.blockchain.read().await.block_number();
+          self.metrics.added_block(block_number, &commit.validators);
           // If we successfully added this block, break
           break;
         }
@@ -14,7 +14,7 @@ use crate::{
   ReadWrite, BlockError, Block, Transaction,
   tests::p2p::DummyP2p,
   transaction::{TransactionError, Signed, TransactionKind, Transaction as TransactionTrait},
-  tendermint::{TendermintNetwork, Validators},
+  tendermint::{TendermintNetwork, Validators, ProposerSchedule},
 };
 
 type N = TendermintNetwork<MemDb, NonceTransaction, DummyP2p>;
@@ -77,7 +77,7 @@ impl TransactionTrait for NonceTransaction {
 fn empty_block() {
   const GENESIS: [u8; 32] = [0xff; 32];
   const LAST: [u8; 32] = [0x01; 32];
-  let validators = Arc::new(Validators::new(GENESIS, vec![]).unwrap());
+  let validators = Arc::new(Validators::new(GENESIS, vec![], ProposerSchedule::Spread).unwrap());
   let commit = |_: u64| -> Option<Commit<Arc<Validators>>> {
     Some(Commit::<Arc<Validators>> { end_time: 0, validators: vec![], signature: vec![] })
   };
@@ -101,7 +101,7 @@ fn duplicate_nonces() {
   const GENESIS: [u8; 32] = [0xff; 32];
   const LAST: [u8; 32] = [0x01; 32];
 
-  let validators = Arc::new(Validators::new(GENESIS, vec![]).unwrap());
+  let validators = Arc::new(Validators::new(GENESIS, vec![], ProposerSchedule::Spread).unwrap());
 
   // Run once without duplicating a nonce, and once with, so that's confirmed to be the faulty
   // component
@@ -10,7 +10,17 @@ use rand::rngs::OsRng;
 
 use blake2::{Digest, Blake2s256};
 
-use ciphersuite::{group::ff::Field, Ciphersuite, Ristretto};
+use ciphersuite::{
+  group::{ff::Field, GroupEncoding},
+  Ciphersuite, Ristretto,
+};
+
+use scale::Encode;
+
+use tendermint::{
+  commit_msg,
+  ext::{Commit, SignatureScheme, Signer as SignerTrait},
+};
 
 use serai_db::{DbTxn, Db, MemDb};
 
@@ -19,7 +29,7 @@ use crate::{
   transaction::Transaction as TransactionTrait,
   TransactionError, Transaction, ProvidedError, ProvidedTransactions, merkle, BlockError, Block,
   Blockchain,
-  tendermint::{TendermintNetwork, Validators, Signer, TendermintBlock},
+  tendermint::{TendermintNetwork, Validators, ProposerSchedule, Signer, TendermintBlock},
   tests::{
     ProvidedTransaction, SignedTransaction, random_provided_transaction, p2p::DummyP2p,
     new_genesis, random_evidence_tx,
@@ -42,7 +52,7 @@ fn new_blockchain<T: TransactionTrait>(
 #[test]
 fn block_addition() {
   let genesis = new_genesis();
-  let validators = Arc::new(Validators::new(genesis, vec![]).unwrap());
+  let validators = Arc::new(Validators::new(genesis, vec![], ProposerSchedule::Spread).unwrap());
   let (db, mut blockchain) = new_blockchain::<SignedTransaction>(genesis, &[]);
   let block = blockchain.build_block::<N>(&validators);
 
@@ -61,7 +71,7 @@ fn block_addition() {
 #[test]
 fn invalid_block() {
   let genesis = new_genesis();
-  let validators = Arc::new(Validators::new(genesis, vec![]).unwrap());
+  let validators = Arc::new(Validators::new(genesis, vec![], ProposerSchedule::Spread).unwrap());
   let (_, mut blockchain) = new_blockchain::<SignedTransaction>(genesis, &[]);
 
   let block = blockchain.build_block::<N>(&validators);
@@ -148,7 +158,7 @@ fn invalid_block() {
 #[test]
 fn signed_transaction() {
   let genesis = new_genesis();
-  let validators = Arc::new(Validators::new(genesis, vec![]).unwrap());
+  let validators = Arc::new(Validators::new(genesis, vec![], ProposerSchedule::Spread).unwrap());
   let key = Zeroizing::new(<Ristretto as Ciphersuite>::F::random(&mut OsRng));
   let tx = crate::tests::signed_transaction(&mut OsRng, genesis, &key, 0);
   let signer = tx.1.signer;
@@ -201,10 +211,160 @@ fn signed_transaction() {
   assert_eq!(blockchain.next_nonce(&signer, &[]), Some(64));
 }
 
+#[test]
+fn export_and_prune() {
+  let genesis = new_genesis();
+  let validators = Arc::new(Validators::new(genesis, vec![], ProposerSchedule::Spread).unwrap());
+  let key = Zeroizing::new(<Ristretto as Ciphersuite>::F::random(&mut OsRng));
+  let signer = <Ristretto as Ciphersuite>::generator() * key.deref();
+
+  let (mut db, mut blockchain) = new_blockchain::<SignedTransaction>(genesis, &[signer]);
+  let mut blocks = vec![];
+  for nonce in 0 .. 3 {
+    let tx = crate::tests::signed_transaction(&mut OsRng, genesis, &key, nonce);
+    blockchain.add_transaction::<N>(true, Transaction::Application(tx), &validators).unwrap();
+    let block = blockchain.build_block::<N>(&validators);
+    let commit = vec![u8::try_from(nonce).unwrap()];
+    assert!(blockchain.add_block::<N>(&block, commit, &validators).is_ok());
+    blocks.push(block);
+  }
+
+  // Every block should be exported, with its commit
+  let mut exported = vec![];
+  Blockchain::<MemDb, SignedTransaction>::export(&db, genesis, &mut exported).unwrap();
+  let mut expected = vec![];
+  for (i, block) in blocks.iter().enumerate() {
+    let block = block.serialize();
+    expected.extend(u32::try_from(block.len()).unwrap().to_le_bytes());
+    expected.extend(block);
+    expected.extend(1u32.to_le_bytes());
+    expected.push(u8::try_from(i).unwrap());
+  }
+  assert_eq!(exported, expected);
+
+  // Pruning should solely retain the tip
+  Blockchain::<MemDb, SignedTransaction>::prune(&mut db, genesis);
+  let tip = blocks.last().unwrap().hash();
+  for (i, block) in blocks.iter().enumerate() {
+    let hash = block.hash();
+    let retained = hash == tip;
+    assert_eq!(
+      Blockchain::<MemDb, SignedTransaction>::block_from_db(&db, genesis, &hash).is_some(),
+      retained
+    );
+    assert_eq!(
+      Blockchain::<MemDb, SignedTransaction>::commit_from_db(&db, genesis, &hash).is_some(),
+      retained
+    );
+    assert_eq!(
+      Blockchain::<MemDb, SignedTransaction>::block_hash_from_db(
+        &db,
+        genesis,
+        u64::try_from(i + 1).unwrap()
+      )
+      .is_some(),
+      retained
+    );
+    assert!(
+      Blockchain::<MemDb, SignedTransaction>::block_after(&db, genesis, &block.parent()).is_none()
+    );
+  }
+  assert_eq!(Blockchain::<MemDb, SignedTransaction>::tip_from_db(&db, genesis), tip);
+  assert_eq!(Blockchain::<MemDb, SignedTransaction>::block_number_from_db(&db, genesis), 3);
+
+  // The nonces tracked for the signer should've been pruned
+  let blockchain = Blockchain::<MemDb, SignedTransaction>::new(db.clone(), genesis, &[signer]);
+  assert_eq!(blockchain.next_nonce(&signer, &[]), Some(0));
+
+  // A pruned Tributary can no longer be exported
+  assert!(Blockchain::<MemDb, SignedTransaction>::export(&db, genesis, &mut vec![]).is_err());
+}
+
+#[tokio::test]
+async fn import() {
+  let genesis = new_genesis();
+  let key = Zeroizing::new(<Ristretto as Ciphersuite>::F::random(&mut OsRng));
+  let signer = <Ristretto as Ciphersuite>::generator() * key.deref();
+  let validators =
+    Arc::new(Validators::new(genesis, vec![(signer, 1)], ProposerSchedule::Spread).unwrap());
+  let tendermint_signer = Signer::new(genesis, key.clone());
+
+  let (db, mut blockchain) = new_blockchain::<SignedTransaction>(genesis, &[signer]);
+  let mut blocks = vec![];
+  for nonce in 0 .. 3 {
+    let tx = crate::tests::signed_transaction(&mut OsRng, genesis, &key, nonce);
+    blockchain.add_transaction::<N>(true, Transaction::Application(tx), &validators).unwrap();
+    let block = blockchain.build_block::<N>(&validators);
+
+    let end_time = u64::from(nonce);
+    let msg = commit_msg(end_time, &block.hash());
+    let signature = tendermint_signer.sign(&msg).await;
+    let commit = Commit::<Validators> {
+      end_time,
+      validators: vec![signer.to_bytes()],
+      signature: validators.aggregate(&[signer.to_bytes()], &msg, &[signature]),
+    };
+    assert!(blockchain.add_block::<N>(&block, commit.encode(), &validators).is_ok());
+    blocks.push(block);
+  }
+
+  let mut exported = vec![];
+  assert_eq!(
+    Blockchain::<MemDb, SignedTransaction>::export(&db, genesis, &mut exported).unwrap(),
+    3
+  );
+
+  // Importing the export should sync a new blockchain to the same tip, with the same state
+  let (imported_db, mut imported) = new_blockchain::<SignedTransaction>(genesis, &[signer]);
+  assert_eq!(imported.import(&validators, &mut exported.as_slice()).unwrap(), 3);
+  assert_eq!(imported.tip(), blockchain.tip());
+  assert_eq!(imported.block_number(), 3);
+  assert_eq!(imported.next_nonce(&signer, &[]), Some(3));
+  for block in &blocks {
+    let hash = block.hash();
+    assert_eq!(
+      Blockchain::<MemDb, SignedTransaction>::block_from_db(&imported_db, genesis, &hash),
+      Some(block.clone())
+    );
+    assert_eq!(
+      Blockchain::<MemDb, SignedTransaction>::commit_from_db(&imported_db, genesis, &hash),
+      blockchain.commit(&hash)
+    );
+  }
+
+  // Importing it again should skip every block
+  assert_eq!(imported.import(&validators, &mut exported.as_slice()).unwrap(), 0);
+
+  // An export with an invalid commit shouldn't be imported
+  let mut corrupted = exported.clone();
+  let block_len = usize::try_from(u32::from_le_bytes(corrupted[.. 4].try_into().unwrap())).unwrap();
+  corrupted[4 + block_len + 4] ^= 1;
+  let (_, mut imported) = new_blockchain::<SignedTransaction>(genesis, &[signer]);
+  assert!(imported.import(&validators, &mut corrupted.as_slice()).is_err());
+  assert_eq!(imported.block_number(), 0);
+
+  // Nor should an export with blocks out of order
+  let mut reordered = vec![];
+  for block in [&blocks[1], &blocks[0]] {
+    let block = block.serialize();
+    reordered.extend(u32::try_from(block.len()).unwrap().to_le_bytes());
+    reordered.extend(block);
+    let commit = blockchain.commit(&blocks[0].hash()).unwrap();
+    reordered.extend(u32::try_from(commit.len()).unwrap().to_le_bytes());
+    reordered.extend(commit);
+  }
+  assert!(imported.import(&validators, &mut reordered.as_slice()).is_err());
+  assert_eq!(imported.block_number(), 0);
+
+  // Nor should a truncated export be fully imported
+  assert!(imported.import(&validators, &mut &exported[.. (exported.len() - 1)]).is_err());
+  assert_eq!(imported.block_number(), 2);
+}
+
 #[test]
 fn provided_transaction() {
   let genesis = new_genesis();
-  let validators = Arc::new(Validators::new(genesis, vec![]).unwrap());
+  let validators = Arc::new(Validators::new(genesis, vec![], ProposerSchedule::Spread).unwrap());
   let (db, mut blockchain) = new_blockchain::<ProvidedTransaction>(genesis, &[]);
 
   let tx = random_provided_transaction(&mut OsRng, "order1");
@@ -341,7 +501,8 @@ async fn tendermint_evidence_tx() {
   let key = Zeroizing::new(<Ristretto as Ciphersuite>::F::random(&mut OsRng));
   let signer = Signer::new(genesis, key.clone());
   let signer_id = Ristretto::generator() * key.deref();
-  let validators = Arc::new(Validators::new(genesis, vec![(signer_id, 1)]).unwrap());
+  let validators =
+    Arc::new(Validators::new(genesis, vec![(signer_id, 1)], ProposerSchedule::Spread).unwrap());
 
   let (_, mut blockchain) = new_blockchain::<SignedTransaction>(genesis, &[]);
 
@@ -388,7 +549,7 @@ async fn tendermint_evidence_tx() {
   }
 
   // update validators
-  let validators = Arc::new(Validators::new(genesis, signers).unwrap());
+  let validators = Arc::new(Validators::new(genesis, signers, ProposerSchedule::Spread).unwrap());
   test(&mut blockchain, mempool, validators);
 }
 
@@ -449,7 +610,8 @@ async fn block_tx_ordering() {
 
   // signer
   let signer = crate::tests::signed_transaction(&mut OsRng, genesis, &key, 0).1.signer;
-  let validators = Arc::new(Validators::new(genesis, vec![(signer, 1)]).unwrap());
+  let validators =
+    Arc::new(Validators::new(genesis, vec![(signer, 1)], ProposerSchedule::Spread).unwrap());
 
   let (_, mut blockchain) = new_blockchain::<SignedTx>(genesis, &[signer]);
   let tip = blockchain.tip();
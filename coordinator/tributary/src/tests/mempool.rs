@@ -3,7 +3,10 @@ use std::{sync::Arc, collections::HashMap};
 use zeroize::Zeroizing;
 use rand::{RngCore, rngs::OsRng};
 
-use ciphersuite::{group::ff::Field, Ciphersuite, Ristretto};
+use ciphersuite::{
+  group::{ff::Field, GroupEncoding},
+  Ciphersuite, Ristretto,
+};
 
 use tendermint::ext::Commit;
 
@@ -11,8 +14,8 @@ use serai_db::MemDb;
 
 use crate::{
   transaction::{TransactionError, Transaction as TransactionTrait},
-  tendermint::{TendermintBlock, Validators, Signer, TendermintNetwork},
-  ACCOUNT_MEMPOOL_LIMIT, Transaction, Mempool,
+  tendermint::{TendermintBlock, Validators, ProposerSchedule, Signer, TendermintNetwork},
+  ACCOUNT_MEMPOOL_LIMIT, ACCOUNT_RATE_LIMIT, Transaction, Mempool,
   tests::{SignedTransaction, signed_transaction, p2p::DummyP2p, random_evidence_tx},
 };
 
@@ -39,7 +42,8 @@ async fn mempool_addition() {
   assert_eq!(mempool.next_nonce_in_mempool(&signer, vec![]), None);
 
   // validators
-  let validators = Arc::new(Validators::new(genesis, vec![(signer, 1)]).unwrap());
+  let validators =
+    Arc::new(Validators::new(genesis, vec![(signer, 1)], ProposerSchedule::Spread).unwrap());
 
   // Add TX 0
   assert!(mempool
@@ -158,8 +162,8 @@ async fn mempool_addition() {
 
 #[test]
 fn too_many_mempool() {
-  let (genesis, _, mut mempool) = new_mempool::<SignedTransaction>();
-  let validators = Arc::new(Validators::new(genesis, vec![]).unwrap());
+  let (genesis, db, mut mempool) = new_mempool::<SignedTransaction>();
+  let validators = Arc::new(Validators::new(genesis, vec![], ProposerSchedule::Spread).unwrap());
   let commit = |_: u64| -> Option<Commit<Arc<Validators>>> {
     Some(Commit::<Arc<Validators>> { end_time: 0, validators: vec![], signature: vec![] })
   };
@@ -180,20 +184,146 @@ fn too_many_mempool() {
       .unwrap());
   }
   // Yet adding more should fail
+  let tx =
+    Transaction::Application(signed_transaction(&mut OsRng, genesis, &key, ACCOUNT_MEMPOOL_LIMIT));
+  assert_eq!(
+    mempool.add::<N, _>(&|_, _| Some(0), false, tx.clone(), &validators, unsigned_in_chain, commit),
+    Err(TransactionError::TooManyInMempool)
+  );
+  // Which should be counted as a violation
+  let signer = Ristretto::generator() * *key;
+  assert_eq!(Mempool::<MemDb, SignedTransaction>::violations_from_db(&db, genesis, &signer), 1);
+  // With the transaction deferred, not dropped
+  assert_eq!(mempool.deferred(), vec![tx.clone()]);
+
+  // Retrying a deferred transaction isn't another violation
+  assert_eq!(
+    mempool.add::<N, _>(&|_, _| Some(0), false, tx.clone(), &validators, unsigned_in_chain, commit),
+    Err(TransactionError::TooManyInMempool)
+  );
+  assert_eq!(Mempool::<MemDb, SignedTransaction>::violations_from_db(&db, genesis, &signer), 1);
+
+  // Once the quota frees, the retry succeeds
+  let included = *mempool.txs().keys().next().unwrap();
+  mempool.remove(&included);
+  assert!(mempool
+    .add::<N, _>(&|_, _| Some(0), false, tx.clone(), &validators, unsigned_in_chain, commit)
+    .unwrap());
+  mempool.undefer(&tx.hash());
+  assert!(mempool.deferred().is_empty());
+}
+
+#[test]
+fn rate_limited_mempool() {
+  let (genesis, db, mut mempool) = new_mempool::<SignedTransaction>();
+  let validators = Arc::new(Validators::new(genesis, vec![], ProposerSchedule::Spread).unwrap());
+  let commit = |_: u64| -> Option<Commit<Arc<Validators>>> {
+    Some(Commit::<Arc<Validators>> { end_time: 0, validators: vec![], signature: vec![] })
+  };
+  let unsigned_in_chain = |_: [u8; 32]| false;
+  let key = Zeroizing::new(<Ristretto as Ciphersuite>::F::random(&mut OsRng));
+  let signer = Ristretto::generator() * *key;
+
+  // Add transactions up to the rate limit, removing them (as if included on-chain) so the limit
+  // on the amount of transactions in the mempool isn't hit
+  let mut nonce = 0;
+  while nonce < ACCOUNT_RATE_LIMIT {
+    let mut hashes = vec![];
+    for _ in 0 .. ACCOUNT_MEMPOOL_LIMIT {
+      let tx = signed_transaction(&mut OsRng, genesis, &key, nonce);
+      hashes.push(tx.hash());
+      assert!(mempool
+        .add::<N, _>(
+          &|_, _| Some(nonce - (nonce % ACCOUNT_MEMPOOL_LIMIT)),
+          false,
+          Transaction::Application(tx),
+          &validators,
+          unsigned_in_chain,
+          commit,
+        )
+        .unwrap());
+      nonce += 1;
+    }
+    for hash in hashes {
+      mempool.remove(&hash);
+    }
+  }
+  assert!(mempool.txs().is_empty());
+
+  // Adding another should fail, despite the mempool being empty
+  let tx = signed_transaction(&mut OsRng, genesis, &key, nonce);
   assert_eq!(
     mempool.add::<N, _>(
-      &|_, _| Some(0),
+      &|_, _| Some(nonce),
       false,
-      Transaction::Application(signed_transaction(
-        &mut OsRng,
-        genesis,
-        &key,
-        ACCOUNT_MEMPOOL_LIMIT
-      )),
+      Transaction::Application(tx.clone()),
       &validators,
       unsigned_in_chain,
       commit,
     ),
-    Err(TransactionError::TooManyInMempool)
+    Err(TransactionError::TooFrequent)
   );
+  assert_eq!(Mempool::<MemDb, SignedTransaction>::violations_from_db(&db, genesis, &signer), 1);
+
+  // Unless it's our own transaction
+  assert!(mempool
+    .add::<N, _>(
+      &|_, _| Some(nonce),
+      true,
+      Transaction::Application(tx),
+      &validators,
+      unsigned_in_chain,
+      commit,
+    )
+    .unwrap());
+}
+
+#[test]
+fn fair_block() {
+  let (genesis, _, mut mempool) = new_mempool::<SignedTransaction>();
+  let validators = Arc::new(Validators::new(genesis, vec![], ProposerSchedule::Spread).unwrap());
+  let commit = |_: u64| -> Option<Commit<Arc<Validators>>> {
+    Some(Commit::<Arc<Validators>> { end_time: 0, validators: vec![], signature: vec![] })
+  };
+  let unsigned_in_chain = |_: [u8; 32]| false;
+
+  // Add three transactions from one signer, then two from another
+  let first_key = Zeroizing::new(<Ristretto as Ciphersuite>::F::random(&mut OsRng));
+  let second_key = Zeroizing::new(<Ristretto as Ciphersuite>::F::random(&mut OsRng));
+  let mut first = vec![];
+  let mut second = vec![];
+  for (key, txs, amount) in [(&first_key, &mut first, 3), (&second_key, &mut second, 2)] {
+    for nonce in 0 .. amount {
+      let tx = signed_transaction(&mut OsRng, genesis, key, nonce);
+      txs.push(tx.hash());
+      assert!(mempool
+        .add::<N, _>(
+          &|_, _| Some(0),
+          true,
+          Transaction::Application(tx),
+          &validators,
+          unsigned_in_chain,
+          commit,
+        )
+        .unwrap());
+    }
+  }
+
+  // The queues should be reported with the one waiting the longest first
+  let queues = mempool.queue_state();
+  assert_eq!(queues.len(), 2);
+  assert_eq!(queues[0].signer, (Ristretto::generator() * *first_key).to_bytes());
+  assert_eq!(queues[0].transactions, 3);
+  assert_eq!(queues[1].signer, (Ristretto::generator() * *second_key).to_bytes());
+  assert_eq!(queues[1].transactions, 2);
+
+  // The block should take a transaction from each queue in turn
+  let block = mempool.block().iter().map(Transaction::hash).collect::<Vec<_>>();
+  assert_eq!(block, vec![first[0], second[0], first[1], second[1], first[2]]);
+
+  // Once the head of the first queue is included, the second queue has been waiting the longest
+  mempool.remove(&first[0]);
+  assert_eq!(mempool.queue_state()[0].signer, (Ristretto::generator() * *second_key).to_bytes());
+  let block = mempool.block().iter().map(Transaction::hash).collect::<Vec<_>>();
+  assert_eq!(block, vec![second[0], first[1], second[1], first[2]]);
 }
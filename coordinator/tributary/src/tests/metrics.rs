@@ -0,0 +1,35 @@
+use crate::MetricsCollector;
+
+#[test]
+fn metrics() {
+  let collector = MetricsCollector::new(0);
+
+  // Only the first proposal for the current block, in its first round, is timed
+  collector.received_consensus_message(2, 0, true);
+  collector.received_consensus_message(1, 1, true);
+  collector.received_consensus_message(1, 0, false);
+  assert_eq!(collector.metrics().proposals, 0);
+  collector.received_consensus_message(1, 0, true);
+  collector.received_consensus_message(1, 0, true);
+  let metrics = collector.metrics();
+  assert_eq!(metrics.proposals, 1);
+  assert_eq!(metrics.consensus_messages_received, 5);
+
+  // The round is tracked via our own messages for the current block
+  collector.broadcast_consensus_message(1, 2);
+  collector.broadcast_consensus_message(2, 5);
+  assert_eq!(collector.metrics().round, 2);
+  assert_eq!(collector.metrics().consensus_messages_broadcast, 2);
+
+  collector.received_transaction();
+  collector.added_block(1, &[[1; 32], [2; 32]]);
+  collector.added_block(2, &[[1; 32]]);
+  let metrics = collector.metrics();
+  assert_eq!(metrics.transactions_received, 1);
+  assert_eq!(metrics.blocks, 2);
+  // Three rounds for the first block, one for the second
+  assert_eq!(metrics.rounds, 4);
+  assert_eq!(metrics.round, 0);
+  assert_eq!(metrics.commits_signed[&[1; 32]], 2);
+  assert_eq!(metrics.commits_signed[&[2; 32]], 1);
+}
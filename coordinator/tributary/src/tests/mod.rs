@@ -15,3 +15,5 @@ mod blockchain;
 mod mempool;
 #[cfg(test)]
 mod p2p;
+#[cfg(test)]
+mod metrics;
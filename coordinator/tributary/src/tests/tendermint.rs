@@ -1,7 +1,16 @@
-use tendermint::ext::Network;
+use std::collections::HashMap;
+
+use rand::rngs::OsRng;
+
+use ciphersuite::{
+  group::{Group, GroupEncoding},
+  Ciphersuite, Ristretto,
+};
+
+use tendermint::ext::{BlockNumber, RoundNumber, Weights, Network};
 use crate::{
   P2p, TendermintTx,
-  tendermint::{TARGET_BLOCK_TIME, TendermintNetwork},
+  tendermint::{TARGET_BLOCK_TIME, ProposerSchedule, Validators, TendermintNetwork},
 };
 
 #[test]
@@ -26,3 +35,39 @@ fn assert_target_block_time() {
     TARGET_BLOCK_TIME / 1000
   )
 }
+
+#[test]
+fn weighted_proposers() {
+  let weights = [1, 2, 3, 7];
+  let total_weight = weights.iter().sum::<u64>();
+  let validators = weights
+    .iter()
+    .map(|weight| (<Ristretto as Ciphersuite>::G::random(&mut OsRng), *weight))
+    .collect::<Vec<_>>();
+
+  // Under either schedule, over any total_weight consecutive blocks, every validator should
+  // propose as many times as their weight
+  for schedule in [ProposerSchedule::Shuffled, ProposerSchedule::Spread] {
+    let schedule = Validators::new([0; 32], validators.clone(), schedule).unwrap();
+    for start in 0 .. total_weight {
+      let mut proposals = HashMap::new();
+      for block in start .. (start + total_weight) {
+        *proposals.entry(schedule.proposer(BlockNumber(block), RoundNumber(0))).or_insert(0) += 1;
+      }
+      for (validator, weight) in &validators {
+        assert_eq!(proposals[&validator.to_bytes()], *weight);
+      }
+    }
+  }
+
+  // When spread, the heaviest validator, with over half the weight, should never go more than one
+  // block without proposing
+  let schedule = Validators::new([0; 32], validators.clone(), ProposerSchedule::Spread).unwrap();
+  let heaviest = validators[3].0.to_bytes();
+  for block in 0 .. total_weight {
+    assert!(
+      (schedule.proposer(BlockNumber(block), RoundNumber(0)) == heaviest) ||
+        (schedule.proposer(BlockNumber(block + 1), RoundNumber(0)) == heaviest)
+    );
+  }
+}
@@ -22,7 +22,7 @@ use ::tendermint::{
 use crate::{
   transaction::{Signed, TransactionError, TransactionKind, Transaction, verify_transaction},
   ReadWrite,
-  tendermint::{tx::TendermintTx, Validators, Signer},
+  tendermint::{tx::TendermintTx, Validators, ProposerSchedule, Signer},
 };
 
 #[cfg(test)]
@@ -185,7 +185,8 @@ pub async fn tendermint_meta() -> ([u8; 32], Signer, [u8; 32], Arc<Validators>)
   // schema
   let signer_pub =
     <Ristretto as Ciphersuite>::read_G::<&[u8]>(&mut validator_id.as_slice()).unwrap();
-  let validators = Arc::new(Validators::new(genesis, vec![(signer_pub, 1)]).unwrap());
+  let validators =
+    Arc::new(Validators::new(genesis, vec![(signer_pub, 1)], ProposerSchedule::Spread).unwrap());
 
   (genesis, signer, validator_id, validators)
 }
@@ -20,7 +20,7 @@ use crate::{
   ReadWrite,
   tendermint::{
     tx::{TendermintTx, verify_tendermint_tx},
-    TendermintBlock, Signer, Validators, TendermintNetwork,
+    TendermintBlock, Signer, Validators, ProposerSchedule, TendermintNetwork,
   },
   tests::{
     p2p::DummyP2p, SignedTransaction, random_evidence_tx, tendermint_meta, signed_from_data,
@@ -281,8 +281,10 @@ async fn conflicting_msgs_evidence_tx() {
       <Ristretto as Ciphersuite>::read_G::<&[u8]>(&mut signer_id.as_slice()).unwrap();
     let signer_pub_2 =
       <Ristretto as Ciphersuite>::read_G::<&[u8]>(&mut signed_id_2.as_slice()).unwrap();
-    let validators =
-      Arc::new(Validators::new(genesis, vec![(signer_pub, 1), (signer_pub_2, 1)]).unwrap());
+    let validators = Arc::new(
+      Validators::new(genesis, vec![(signer_pub, 1), (signer_pub_2, 1)], ProposerSchedule::Spread)
+        .unwrap(),
+    );
 
     assert!(verify_tendermint_tx::<N>(&tx, &validators, commit).is_err());
   }
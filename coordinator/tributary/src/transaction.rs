@@ -34,6 +34,12 @@ pub enum TransactionError {
   /// Transaction's signer has too many transactions in the mempool.
   #[error("signer has too many transactions in the mempool")]
   TooManyInMempool,
+  /// Transaction's signer's transactions in the mempool are too large.
+  #[error("signer's transactions in the mempool are too large")]
+  TooLargeForMempool,
+  /// Transaction's signer has added too many transactions to the mempool recently.
+  #[error("signer has added too many transactions to the mempool recently")]
+  TooFrequent,
   /// Provided Transaction added to mempool.
   #[error("provided transaction added to mempool")]
   ProvidedAddedToMempool,
@@ -1,14 +1,11 @@
-use std::{
-  sync::Arc,
-  collections::{HashSet, HashMap},
-};
+use std::{sync::Arc, collections::HashMap};
 
 use serai_db::{Get, DbTxn, Db};
 
 use crate::{
   time::CanonicalInstant,
   ext::{RoundNumber, BlockNumber, Block, Network},
-  round::RoundData,
+  round::{Latencies, RoundData},
   message_log::MessageLog,
   Step, Data, DataFor, Message, MessageFor,
 };
@@ -22,7 +19,8 @@ pub(crate) struct BlockData<N: Network> {
   pub(crate) our_proposal: Option<N::Block>,
 
   pub(crate) log: MessageLog<N>,
-  pub(crate) slashes: HashSet<N::ValidatorId>,
+  // The validators slashed within this block, and if they were slashed with evidence
+  pub(crate) slashes: HashMap<N::ValidatorId, bool>,
   // We track the end times of each round for two reasons:
   // 1) Knowing the start time of the next round
   // 2) Validating precommits, which include the end time of the round which produced it
@@ -53,7 +51,7 @@ impl<N: Network> BlockData<N> {
       our_proposal,
 
       log: MessageLog::new(weights),
-      slashes: HashSet::new(),
+      slashes: HashMap::new(),
       end_time: HashMap::new(),
 
       // The caller of BlockData::new is expected to be populated after by the caller
@@ -94,6 +92,7 @@ impl<N: Network> BlockData<N> {
     round: RoundNumber,
     proposer: N::ValidatorId,
     time: Option<CanonicalInstant>,
+    latencies: &Latencies,
   ) -> Option<DataFor<N>> {
     debug_assert_eq!(round.0 == 0, time.is_some());
 
@@ -119,7 +118,7 @@ impl<N: Network> BlockData<N> {
       let (round, block) = self.valid.clone().unzip();
       block.or_else(|| self.our_proposal.clone()).map(|block| Data::Proposal(round, block))
     } else {
-      self.round_mut().set_timeout(Step::Propose);
+      self.round_mut().set_timeout(Step::Propose, latencies);
       None
     }
   }
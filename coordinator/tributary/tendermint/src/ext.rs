@@ -151,6 +151,29 @@ impl<S: SignatureScheme> Clone for Commit<S> {
   }
 }
 
+/// Verify a commit for the block with the specified ID, without a `Network`.
+#[must_use]
+pub fn verify_commit<S: SignatureScheme, W: Weights<ValidatorId = S::ValidatorId>>(
+  signature_scheme: &S,
+  weights: &W,
+  id: &[u8],
+  commit: &Commit<S>,
+) -> bool {
+  if commit.validators.iter().collect::<HashSet<_>>().len() != commit.validators.len() {
+    return false;
+  }
+
+  if !signature_scheme.verify_aggregate(
+    &commit.validators,
+    &commit_msg(commit.end_time, id),
+    &commit.signature,
+  ) {
+    return false;
+  }
+
+  commit.validators.iter().map(|v| weights.weight(*v)).sum::<u64>() >= weights.threshold()
+}
+
 /// Weights for the validators present.
 pub trait Weights: Send + Sync {
   type ValidatorId: ValidatorId;
@@ -234,6 +257,15 @@ pub trait Network: Sized + Send + Sync {
   ///
   /// BLOCK_PROCESSING_TIME + (3 * LATENCY_TIME) must be divisible by 1000.
   const LATENCY_TIME: u32;
+  /// The bounds the propose and prevote timeouts are adapted within, as percentages of the
+  /// timeouts implied by BLOCK_PROCESSING_TIME and LATENCY_TIME.
+  ///
+  /// These timeouts adapt to the latencies observed for their steps, yet never exceed the
+  /// following step's timeout. The precommit timeout is never adapted, as it defines the end time
+  /// of the round.
+  ///
+  /// The lower bound must be at most 100, and the upper bound must be at least 100.
+  const TIMEOUT_BOUNDS: (u32, u32) = (50, 150);
 
   /// The block time, in seconds. Defined as the processing time plus three times the latency.
   fn block_time() -> u32 {
@@ -258,20 +290,7 @@ pub trait Network: Sized + Send + Sync {
     id: <Self::Block as Block>::Id,
     commit: &Commit<Self::SignatureScheme>,
   ) -> bool {
-    if commit.validators.iter().collect::<HashSet<_>>().len() != commit.validators.len() {
-      return false;
-    }
-
-    if !self.signature_scheme().verify_aggregate(
-      &commit.validators,
-      &commit_msg(commit.end_time, id.as_ref()),
-      &commit.signature,
-    ) {
-      return false;
-    }
-
-    let weights = self.weights();
-    commit.validators.iter().map(|v| weights.weight(*v)).sum::<u64>() >= weights.threshold()
+    verify_commit(&self.signature_scheme(), &self.weights(), id.as_ref(), commit)
   }
 
   /// Broadcast a message to the other validators.
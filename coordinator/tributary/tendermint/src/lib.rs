@@ -2,7 +2,7 @@ use core::fmt::Debug;
 
 use std::{
   sync::Arc,
-  time::{SystemTime, Instant, Duration},
+  time::{SystemTime, Duration},
   collections::{VecDeque, HashMap},
 };
 
@@ -13,7 +13,7 @@ use futures_util::{
   FutureExt, StreamExt, SinkExt,
   future::{self, Fuse},
 };
-use patchable_async_sleep::sleep;
+use patchable_async_sleep::{sleep, Instant};
 
 use serai_db::{Get, DbTxn, Db};
 
@@ -21,7 +21,7 @@ pub mod time;
 use time::{sys_time, CanonicalInstant};
 
 pub mod round;
-use round::RoundData;
+use round::{Latencies, RoundData};
 
 mod block;
 use block::BlockData;
@@ -278,6 +278,8 @@ pub struct TendermintMachine<N: Network> {
   round_proposals: HashMap<RoundNumber, (Option<RoundNumber>, N::Block)>,
   // TODO: Move this into the Round struct
   upons: Upons,
+  // The latencies observed for prior rounds, used to adapt future timeouts
+  latencies: Latencies,
 }
 
 pub struct SyncedBlock<N: Network> {
@@ -329,7 +331,7 @@ impl<N: Network + 'static> TendermintMachine<N> {
     };
 
     let proposer = self.weights.proposer(self.block.number, round);
-    let res = if let Some(data) = self.block.new_round(round, proposer, time) {
+    let res = if let Some(data) = self.block.new_round(round, proposer, time, &self.latencies) {
       self.broadcast(data);
       true
     } else {
@@ -417,11 +419,16 @@ impl<N: Network + 'static> TendermintMachine<N> {
   }
 
   async fn slash(&mut self, validator: N::ValidatorId, slash_event: SlashEvent) {
-    // TODO: If the new slash event has evidence, emit to prevent a low-importance slash from
-    // cancelling emission of high-importance slashes
-    if !self.block.slashes.contains(&validator) {
+    // Emit slashes with evidence even if this validator was already slashed without evidence, to
+    // prevent a low-importance slash from cancelling emission of high-importance slashes
+    let with_evidence = matches!(slash_event, SlashEvent::WithEvidence(_));
+    let emit = match self.block.slashes.get(&validator) {
+      None => true,
+      Some(had_evidence) => with_evidence && (!had_evidence),
+    };
+    if emit {
       log::info!(target: "tendermint", "Slashing validator {}", hex::encode(validator.encode()));
-      self.block.slashes.insert(validator);
+      self.block.slashes.insert(validator, with_evidence);
       self.network.slash(validator, slash_event).await;
     }
   }
@@ -507,7 +514,7 @@ impl<N: Network + 'static> TendermintMachine<N> {
     }
 
     if self.block.log.has_participation(self.block.round().number, Step::Prevote) {
-      self.block.round_mut().set_timeout(Step::Prevote);
+      self.block.round_mut().set_timeout(Step::Prevote, &self.latencies);
       self.upons.upon_prevotes = true;
     }
   }
@@ -533,6 +540,7 @@ impl<N: Network + 'static> TendermintMachine<N> {
 
     let block = block.clone();
     self.upons.upon_successful_current_round_prevotes = true;
+    self.latencies.observe_prevotes(self.block.round());
 
     if self.block.round().step == Step::Prevote {
       self.block.locked = Some((self.block.round().number, block.id()));
@@ -556,6 +564,7 @@ impl<N: Network + 'static> TendermintMachine<N> {
     }
 
     if self.block.log.has_consensus(self.block.round().number, &Data::Prevote(None)) {
+      self.latencies.observe_prevotes(self.block.round());
       self.broadcast(Data::Precommit(None));
     }
 
@@ -569,7 +578,7 @@ impl<N: Network + 'static> TendermintMachine<N> {
     }
 
     if self.block.log.has_participation(self.block.round().number, Step::Precommit) {
-      self.block.round_mut().set_timeout(Step::Precommit);
+      self.block.round_mut().set_timeout(Step::Precommit, &self.latencies);
       self.upons.upon_precommits = true;
     }
   }
@@ -741,7 +750,7 @@ impl<N: Network + 'static> TendermintMachine<N> {
           self.broadcast(Data::Prevote(None));
         }
         self
-          .slash(msg.sender, SlashEvent::WithEvidence(Evidence::InvalidValidRound(msg.encode())))
+          .slash(msg.sender, SlashEvent::WithEvidence(Evidence::InvalidValidRound(signed.encode())))
           .await;
         Err(TendermintError::Malicious)?;
       }
@@ -766,6 +775,9 @@ impl<N: Network + 'static> TendermintMachine<N> {
 
     // If this is a proposal, insert it
     if let Data::Proposal(vr, block) = &msg.data {
+      if (msg.round == self.block.round().number) && (Some(msg.sender) != self.block.validator_id) {
+        self.latencies.observe_proposal(self.block.round());
+      }
       self.round_proposals.insert(msg.round, (*vr, block.clone()));
     }
 
@@ -917,6 +929,7 @@ impl<N: Network + 'static> TendermintMachine<N> {
             upon_negative_current_round_prevotes: false,
             upon_precommits: false,
           },
+          latencies: Latencies::default(),
         };
 
         // The end time of the last block is the start time for this one
@@ -1,11 +1,7 @@
-use std::{
-  marker::PhantomData,
-  time::{Duration, Instant},
-  collections::HashMap,
-};
+use std::{marker::PhantomData, time::Duration, collections::HashMap};
 
 use futures_util::{FutureExt, future};
-use patchable_async_sleep::sleep;
+use patchable_async_sleep::{sleep, Instant};
 
 use crate::{
   time::CanonicalInstant,
@@ -13,6 +9,35 @@ use crate::{
   ext::{RoundNumber, Network},
 };
 
+// The weight given to prior observations within the moving averages of latencies, out of 8
+const LATENCY_HISTORY_WEIGHT: u32 = 7;
+
+/// Moving averages of how long after the start of a round its messages were received.
+///
+/// These are tracked across blocks, and used to adapt the timeouts for steps to the network.
+#[derive(Clone, Copy, Default, Debug)]
+pub(crate) struct Latencies {
+  proposal: Option<Duration>,
+  prevotes: Option<Duration>,
+}
+
+impl Latencies {
+  fn observe(average: &mut Option<Duration>, observed: Duration) {
+    *average = Some(match average {
+      None => observed,
+      Some(average) => ((*average * LATENCY_HISTORY_WEIGHT) + observed) / 8,
+    });
+  }
+
+  pub(crate) fn observe_proposal(&mut self, round: &RoundData<impl Network>) {
+    Self::observe(&mut self.proposal, round.elapsed());
+  }
+
+  pub(crate) fn observe_prevotes(&mut self, round: &RoundData<impl Network>) {
+    Self::observe(&mut self.prevotes, round.elapsed());
+  }
+}
+
 pub struct RoundData<N: Network> {
   _network: PhantomData<N>,
   pub number: RoundNumber,
@@ -32,26 +57,52 @@ impl<N: Network> RoundData<N> {
     }
   }
 
-  fn timeout(&self, step: Step) -> CanonicalInstant {
+  // The time elapsed since the start of this round
+  fn elapsed(&self) -> Duration {
+    Instant::now().saturating_duration_since(self.start_time.instant())
+  }
+
+  // The offset of a step's timeout from the start of the round, as implied by the network's
+  // configured times
+  fn configured_offset(&self, step: Step) -> Duration {
     let adjusted_block = N::BLOCK_PROCESSING_TIME * (self.number.0 + 1);
     let adjusted_latency = N::LATENCY_TIME * (self.number.0 + 1);
-    let offset = Duration::from_millis(
+    Duration::from_millis(
       (match step {
         Step::Propose => adjusted_block + adjusted_latency,
         Step::Prevote => adjusted_block + (2 * adjusted_latency),
         Step::Precommit => adjusted_block + (3 * adjusted_latency),
       })
       .into(),
-    );
+    )
+  }
+
+  fn timeout(&self, step: Step, latencies: &Latencies) -> CanonicalInstant {
+    let configured = self.configured_offset(step);
+    let (observed, next) = match step {
+      Step::Propose => (latencies.proposal, Step::Prevote),
+      Step::Prevote => (latencies.prevotes, Step::Precommit),
+      // The precommit timeout is the end of the round, which is signed over, so it must be
+      // deterministic and can't be adapted
+      Step::Precommit => return self.start_time + configured,
+    };
+
+    let offset = observed.map_or(configured, |observed| {
+      let (min, max) = N::TIMEOUT_BOUNDS;
+      let min = configured * min / 100;
+      let max = (configured * max / 100).min(self.configured_offset(next));
+      // Allow twice the observed latency, growing with each round as the configured timeouts do
+      (observed * 2 * (self.number.0 + 1)).clamp(min, max)
+    });
     self.start_time + offset
   }
 
   pub fn end_time(&self) -> CanonicalInstant {
-    self.timeout(Step::Precommit)
+    self.timeout(Step::Precommit, &Latencies::default())
   }
 
-  pub(crate) fn set_timeout(&mut self, step: Step) {
-    let timeout = self.timeout(step).instant();
+  pub(crate) fn set_timeout(&mut self, step: Step, latencies: &Latencies) {
+    let timeout = self.timeout(step, latencies).instant();
     self.timeouts.entry(step).or_insert(timeout);
   }
 
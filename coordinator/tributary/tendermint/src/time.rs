@@ -1,5 +1,7 @@
 use core::ops::Add;
-use std::time::{UNIX_EPOCH, SystemTime, Instant, Duration};
+use std::time::{UNIX_EPOCH, SystemTime, Duration};
+
+use patchable_async_sleep::Instant;
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub struct CanonicalInstant {
@@ -1,17 +1,30 @@
 use core::ops::Deref;
+use std::{sync::Mutex, collections::HashMap};
 
 use zeroize::{Zeroize, Zeroizing};
-use rand_core::OsRng;
+use rand_core::{RngCore, OsRng};
 
+use chacha20::{
+  cipher::{crypto_common::KeyIvInit, StreamCipher},
+  Key as Cc20Key, Nonce as Cc20Iv, ChaCha20,
+};
+
+use transcript::{Transcript, RecommendedTranscript};
 use ciphersuite::{
-  group::ff::{Field, PrimeField},
+  group::{
+    ff::{Field, PrimeField},
+    GroupEncoding,
+  },
   Ciphersuite, Ristretto,
 };
 use schnorr_signatures::SchnorrSignature;
 
+use serai_primitives::{ExternalNetworkId, EXTERNAL_NETWORKS};
+
 use tokio::{
   io::{AsyncReadExt, AsyncWriteExt},
   net::TcpStream,
+  sync::Mutex as AsyncMutex,
 };
 
 use serai_env as env;
@@ -19,11 +32,96 @@ use serai_env as env;
 #[rustfmt::skip]
 use crate::{Service, Metadata, QueuedMessage, MessageQueueRequest, message_challenge, ack_challenge};
 
+// The length of a seal's prefix, its salt and signature
+const SEAL_LEN: usize = 32 + 64;
+// The length of a sealed message's sequence number
+const SEQUENCE_LEN: usize = 8;
+
+// The environment variable specifying a service's public key
+fn key_var(service: Service) -> &'static str {
+  match service {
+    Service::Processor(ExternalNetworkId::Bitcoin) => "BITCOIN_KEY",
+    Service::Processor(ExternalNetworkId::Ethereum) => "ETHEREUM_KEY",
+    Service::Processor(ExternalNetworkId::Monero) => "MONERO_KEY",
+    Service::Coordinator => "COORDINATOR_KEY",
+  }
+}
+
+fn seal_challenge(
+  from: Service,
+  from_key: <Ristretto as Ciphersuite>::G,
+  to: Service,
+  to_key: <Ristretto as Ciphersuite>::G,
+  salt: &[u8; 32],
+  ciphertext: &[u8],
+  nonce: <Ristretto as Ciphersuite>::G,
+) -> <Ristretto as Ciphersuite>::F {
+  let mut transcript = RecommendedTranscript::new(b"Serai Message Queue v0.1 Seal");
+  transcript.domain_separate(b"metadata");
+  transcript.append_message(b"from", borsh::to_vec(&from).unwrap());
+  transcript.append_message(b"from_key", from_key.to_bytes());
+  transcript.append_message(b"to", borsh::to_vec(&to).unwrap());
+  transcript.append_message(b"to_key", to_key.to_bytes());
+  transcript.domain_separate(b"message");
+  transcript.append_message(b"salt", salt);
+  transcript.append_message(b"ciphertext", ciphertext);
+  transcript.domain_separate(b"signature");
+  transcript.append_message(b"nonce", nonce.to_bytes());
+  <Ristretto as Ciphersuite>::hash_to_F(b"seal_challenge", &transcript.challenge(b"challenge"))
+}
+
+fn cipher(
+  from: Service,
+  to: Service,
+  salt: &[u8; 32],
+  ecdh: &Zeroizing<<Ristretto as Ciphersuite>::G>,
+) -> ChaCha20 {
+  let mut transcript = RecommendedTranscript::new(b"Serai Message Queue v0.1 Encryption");
+  transcript.append_message(b"from", borsh::to_vec(&from).unwrap());
+  transcript.append_message(b"to", borsh::to_vec(&to).unwrap());
+  transcript.append_message(b"salt", salt);
+
+  transcript.domain_separate(b"encryption_key");
+
+  let mut ecdh = ecdh.to_bytes();
+  transcript.append_message(b"shared_key", ecdh.as_ref());
+  ecdh.as_mut().zeroize();
+
+  let mut key = Cc20Key::default();
+  let mut challenge = transcript.challenge(b"key");
+  key.copy_from_slice(&challenge[.. 32]);
+  challenge.as_mut().zeroize();
+
+  // As the salt is unique to this message, so is the key, making a static IV safe
+  let mut iv = Cc20Iv::default();
+  // The \0 is to satisfy the length requirement (12), not to be null terminated
+  iv.copy_from_slice(b"Serai MQ IV\0");
+
+  let res = ChaCha20::new(&key, &iv);
+  key.as_mut().zeroize();
+  res
+}
+
+/// A client for the message-queue.
+///
+/// Messages are end-to-end encrypted and signed with the static keys of the sending and receiving
+/// services, so the host of the message-queue can neither read nor inject messages.
+///
+/// Each message is sealed with a sequence number, the ID its sender expected it to be queued with.
+/// Messages whose sequence numbers don't increase are rejected, and recipients may check the
+/// sequence numbers are contiguous. While a compromised message-queue may still withhold messages,
+/// it can't reorder or replay them without being detected.
 pub struct MessageQueue {
   pub service: Service,
   priv_key: Zeroizing<<Ristretto as Ciphersuite>::F>,
   pub_key: <Ristretto as Ciphersuite>::G,
+  // The keys of the services we communicate with
+  peer_keys: HashMap<Service, <Ristretto as Ciphersuite>::G>,
   url: String,
+  // Held while queueing a message, so each message is queued with the ID it was sealed with
+  queueing: AsyncMutex<()>,
+  // The ID and sequence number of the last message unsealed from each service
+  unsealed: Mutex<HashMap<Service, (u64, u64)>>,
 }
 
 impl MessageQueue {
@@ -31,6 +129,7 @@ impl MessageQueue {
     service: Service,
     mut url: String,
     priv_key: Zeroizing<<Ristretto as Ciphersuite>::F>,
+    peer_keys: HashMap<Service, <Ristretto as Ciphersuite>::G>,
   ) -> MessageQueue {
     // Allow MESSAGE_QUEUE_RPC to either be a full URL or just a hostname
     // While we could stitch together multiple variables, our control over this service makes this
@@ -39,12 +138,28 @@ impl MessageQueue {
       url += ":2287";
     }
 
-    MessageQueue { service, pub_key: Ristretto::generator() * priv_key.deref(), priv_key, url }
+    MessageQueue {
+      service,
+      pub_key: Ristretto::generator() * priv_key.deref(),
+      priv_key,
+      peer_keys,
+      url,
+      queueing: AsyncMutex::new(()),
+      unsealed: Mutex::new(HashMap::new()),
+    }
   }
 
   pub fn from_env(service: Service) -> MessageQueue {
     let url = env::var("MESSAGE_QUEUE_RPC").expect("message-queue RPC wasn't specified");
+    Self::from_env_with_url(service, url)
+  }
 
+  /// Create a MessageQueue for the specified URL, with the keys specified by the environment.
+  ///
+  /// A processor requires the coordinator's public key (`COORDINATOR_KEY`). The coordinator reads
+  /// the public key of each processor (`BITCOIN_KEY`, `ETHEREUM_KEY`, `MONERO_KEY`) which is
+  /// specified.
+  pub fn from_env_with_url(service: Service, url: String) -> MessageQueue {
     let priv_key: Zeroizing<<Ristretto as Ciphersuite>::F> = {
       let key_str =
         Zeroizing::new(env::var("MESSAGE_QUEUE_KEY").expect("message-queue key wasn't specified"));
@@ -61,7 +176,103 @@ impl MessageQueue {
       key
     };
 
-    Self::new(service, url, priv_key)
+    let read_key = |peer| {
+      let key = hex::decode(env::var(key_var(peer))?)
+        .unwrap_or_else(|_| panic!("invalid key specified for {peer:?} (wasn't hex)"));
+      let mut repr = <<Ristretto as Ciphersuite>::G as GroupEncoding>::Repr::default();
+      if key.len() != repr.as_ref().len() {
+        panic!("invalid key specified for {peer:?} (wasn't a point)");
+      }
+      repr.as_mut().copy_from_slice(&key);
+      Some(
+        Option::from(<Ristretto as Ciphersuite>::G::from_bytes(&repr))
+          .unwrap_or_else(|| panic!("invalid key specified for {peer:?} (wasn't a point)")),
+      )
+    };
+    let mut peer_keys = HashMap::new();
+    if matches!(service, Service::Processor(_)) {
+      let key = read_key(Service::Coordinator).expect("coordinator's key wasn't specified");
+      peer_keys.insert(Service::Coordinator, key);
+    } else {
+      for network in EXTERNAL_NETWORKS {
+        let peer = Service::Processor(network);
+        if let Some(key) = read_key(peer) {
+          peer_keys.insert(peer, key);
+        }
+      }
+    }
+
+    Self::new(service, url, priv_key, peer_keys)
+  }
+
+  fn peer_key(&self, peer: Service) -> <Ristretto as Ciphersuite>::G {
+    *self.peer_keys.get(&peer).unwrap_or_else(|| panic!("key for {peer:?} wasn't specified"))
+  }
+
+  // Encrypt a message, prefixed with its sequence number, to its recipient, and sign the ciphertext
+  fn seal(&self, to: Service, sequence: u64, msg: Vec<u8>) -> Vec<u8> {
+    let to_key = self.peer_key(to);
+    let mut msg = [sequence.to_le_bytes().as_slice(), &msg].concat();
+
+    let mut salt = [0; 32];
+    OsRng.fill_bytes(&mut salt);
+    let ecdh = Zeroizing::new(to_key * self.priv_key.deref());
+    cipher(self.service, to, &salt, &ecdh).apply_keystream(&mut msg);
+
+    let nonce = Zeroizing::new(<Ristretto as Ciphersuite>::F::random(&mut OsRng));
+    let nonce_pub = Ristretto::generator() * nonce.deref();
+    let sig = SchnorrSignature::<Ristretto>::sign(
+      &self.priv_key,
+      nonce,
+      seal_challenge(self.service, self.pub_key, to, to_key, &salt, &msg, nonce_pub),
+    );
+
+    let mut res = Vec::with_capacity(SEAL_LEN + msg.len());
+    res.extend(salt);
+    res.extend(sig.serialize());
+    res.extend(msg);
+    res
+  }
+
+  /// Verify a message, queued with the specified ID, was sealed to us by its sender, and decrypt
+  /// it.
+  ///
+  /// Returns the message's sequence number, with its contents. Messages whose IDs and sequence
+  /// numbers don't increase are rejected, except for the last message unsealed, which is yielded
+  /// again until acknowledged.
+  pub fn unseal(&self, from: Service, id: u64, sealed: &[u8]) -> Option<(u64, Vec<u8>)> {
+    let from_key = self.peer_key(from);
+
+    if sealed.len() < SEAL_LEN {
+      None?;
+    }
+    let salt: [u8; 32] = sealed[.. 32].try_into().unwrap();
+    let sig = SchnorrSignature::<Ristretto>::read(&mut &sealed[32 .. SEAL_LEN]).ok()?;
+    let mut msg = sealed[SEAL_LEN ..].to_vec();
+    if !sig.verify(
+      from_key,
+      seal_challenge(from, from_key, self.service, self.pub_key, &salt, &msg, sig.R),
+    ) {
+      None?;
+    }
+
+    let ecdh = Zeroizing::new(from_key * self.priv_key.deref());
+    cipher(from, self.service, &salt, &ecdh).apply_keystream(&mut msg);
+    if msg.len() < SEQUENCE_LEN {
+      None?;
+    }
+    let sequence = u64::from_le_bytes(msg[.. SEQUENCE_LEN].try_into().unwrap());
+
+    let mut unsealed = self.unsealed.lock().unwrap();
+    if let Some((last_id, last_sequence)) = unsealed.get(&from).copied() {
+      let repeated = (id == last_id) && (sequence == last_sequence);
+      let increasing = (id > last_id) && (sequence > last_sequence);
+      if !(repeated || increasing) {
+        None?;
+      }
+    }
+    unsealed.insert(from, (id, sequence));
+    Some((sequence, msg[SEQUENCE_LEN ..].to_vec()))
   }
 
   #[must_use]
@@ -78,7 +289,33 @@ impl MessageQueue {
     true
   }
 
+  // The ID the next message we queue to the specified service will be assigned
+  async fn next_id(&self, to: Service) -> u64 {
+    let msg = MessageQueueRequest::NextId { from: self.service, to };
+    let mut first = true;
+    loop {
+      if !first {
+        tokio::time::sleep(core::time::Duration::from_secs(5)).await;
+      }
+      first = false;
+
+      let Ok(mut socket) = TcpStream::connect(&self.url).await else { continue };
+      if !Self::send(&mut socket, msg.clone()).await {
+        continue;
+      }
+      let Ok(id) = socket.read_u64_le().await else { continue };
+      break id;
+    }
+  }
+
   pub async fn queue(&self, metadata: Metadata, msg: Vec<u8>) {
+    // Messages are sealed with the ID they'll be queued with, as their sequence number
+    // If this is a message we've already queued, it'll be deduplicated by its intent, leaving the
+    // next ID unused
+    let _queueing = self.queueing.lock().await;
+    let sequence = self.next_id(metadata.to).await;
+    let msg = self.seal(metadata.to, sequence, msg);
+
     // TODO: Should this use OsRng? Deterministic or deterministic + random may be better.
     let nonce = Zeroizing::new(<Ristretto as Ciphersuite>::F::random(&mut OsRng));
     let nonce_pub = Ristretto::generator() * nonce.deref();
@@ -116,7 +353,11 @@ impl MessageQueue {
     }
   }
 
-  pub async fn next(&self, from: Service) -> QueuedMessage {
+  /// Get the next message from the specified service, as queued, without unsealing it.
+  ///
+  /// The message is only claimed to be from the specified service by the message-queue, and must
+  /// be unsealed with `unseal` to verify it actually is.
+  pub async fn next_sealed(&self, from: Service) -> QueuedMessage {
     let msg = MessageQueueRequest::Next { from, to: self.service };
     let mut first = true;
     'outer: loop {
@@ -182,26 +423,41 @@ impl MessageQueue {
         continue;
       }
 
-      let msg: QueuedMessage = borsh::from_slice(msg.as_slice()).unwrap();
-
-      // Verify the message
-      // Verify the sender is sane
-      if matches!(self.service, Service::Processor(_)) {
-        assert_eq!(
-          msg.from,
-          Service::Coordinator,
-          "non-coordinator sent us (a processor) a message"
-        );
-      } else {
-        assert!(
-          matches!(msg.from, Service::Processor(_)),
-          "non-processor sent us (coordinator) a message"
-        );
-      }
-      // TODO: Verify the sender's signature
+      return borsh::from_slice(msg.as_slice()).unwrap();
+    }
+  }
 
-      return msg;
+  pub async fn next(&self, from: Service) -> QueuedMessage {
+    let mut msg = self.next_sealed(from).await;
+
+    // Verify the message
+    // Verify the sender is sane
+    if matches!(self.service, Service::Processor(_)) {
+      assert_eq!(msg.from, Service::Coordinator, "non-coordinator sent us (a processor) a message");
+    } else {
+      assert!(
+        matches!(msg.from, Service::Processor(_)),
+        "non-processor sent us (coordinator) a message"
+      );
     }
+
+    // Verify the sender sealed this message to us, and decrypt it
+    let Some((sequence, plaintext)) = self.unseal(msg.from, msg.id, &msg.msg) else {
+      panic!(
+        "message from {:?} wasn't sealed to us by them (is the message-queue compromised?)",
+        msg.from
+      );
+    };
+    // Senders seal messages with the ID they expect them to be queued with, so the IDs should be
+    // the sequence numbers, which authenticates the IDs
+    assert_eq!(
+      sequence, msg.id,
+      "message from {:?} was queued with an unexpected ID (is the message-queue compromised?)",
+      msg.from
+    );
+    msg.msg = plaintext;
+
+    msg
   }
 
   pub async fn ack(&self, from: Service, id: u64) {
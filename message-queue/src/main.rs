@@ -123,6 +123,17 @@ pub(crate) fn get_next_message(from: Service, to: Service) -> Option<QueuedMessa
   queue.get_message(next)
 }
 
+// next_id RPC method
+/*
+  Gets the ID the next message queued from, and to, the named services will be assigned.
+
+  Senders seal this ID into their messages, letting recipients verify messages are delivered
+  contiguously, without authenticating this server.
+*/
+pub(crate) fn get_next_id(from: Service, to: Service) -> u64 {
+  QUEUES.read().unwrap()[&(from, to)].read().unwrap().message_count()
+}
+
 // ack RPC method
 /*
   Acknowledges a message as received and handled, meaning it'll no longer be returned as the next
@@ -261,6 +272,10 @@ async fn main() {
               let Ok(()) = socket.write_all(&[0]).await else { break };
             }
           },
+          MessageQueueRequest::NextId { from, to } => {
+            let id = get_next_id(from, to);
+            let Ok(()) = socket.write_all(&id.to_le_bytes()).await else { break };
+          }
           MessageQueueRequest::Ack { from, to, id, sig } => {
             ack_message(
               from,
@@ -30,6 +30,7 @@ pub struct Metadata {
 pub enum MessageQueueRequest {
   Queue { meta: Metadata, msg: Vec<u8>, sig: Vec<u8> },
   Next { from: Service, to: Service },
+  NextId { from: Service, to: Service },
   Ack { from: Service, to: Service, id: u64, sig: Vec<u8> },
 }
 
@@ -1 +1,2 @@
 mod crypto;
+mod send;
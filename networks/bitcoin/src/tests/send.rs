@@ -0,0 +1,280 @@
+use std::collections::HashMap;
+
+use rand_core::OsRng;
+
+use proptest::prelude::*;
+
+use secp256k1::{Secp256k1 as BContext, Message, XOnlyPublicKey, schnorr::Signature};
+
+use k256::ProjectivePoint;
+use frost::{
+  curve::Secp256k1,
+  Participant, ThresholdKeys,
+  tests::{THRESHOLD, key_gen, sign_without_caching},
+};
+
+use crate::{
+  bitcoin::{
+    hashes::Hash,
+    absolute::LockTime,
+    consensus::encode::{serialize, deserialize},
+    policy::{DEFAULT_MIN_RELAY_TX_FEE, MAX_STANDARD_TX_WEIGHT, get_virtual_tx_size},
+    script::PushBytesBuf,
+    sighash::{TapSighashType, SighashCache, Prevouts},
+    transaction::Version,
+    Amount, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Witness, WPubkeyHash,
+  },
+  wallet::{
+    DUST, tweak_keys, p2tr_script_buf, ReceivedOutput, Scanner, TransactionError,
+    SignableTransaction,
+  },
+};
+
+fn keys() -> (HashMap<Participant, ThresholdKeys<Secp256k1>>, ProjectivePoint) {
+  let mut keys = key_gen(&mut OsRng);
+  for keys in keys.values_mut() {
+    *keys = tweak_keys(keys);
+  }
+  let key = keys.values().next().unwrap().group_key();
+  (keys, key)
+}
+
+// Fabricate a transaction paying the specified values to the key, and scan it
+fn received(key: ProjectivePoint, values: &[u64]) -> Vec<ReceivedOutput> {
+  let tx = Transaction {
+    version: Version(2),
+    lock_time: LockTime::ZERO,
+    input: vec![TxIn {
+      previous_output: OutPoint::default(),
+      script_sig: ScriptBuf::new(),
+      sequence: Sequence::MAX,
+      witness: Witness::new(),
+    }],
+    output: values
+      .iter()
+      .map(|value| TxOut {
+        value: Amount::from_sat(*value),
+        script_pubkey: p2tr_script_buf(key).unwrap(),
+      })
+      .collect(),
+  };
+  let outputs = Scanner::new(key).unwrap().scan_transaction(&tx);
+  assert_eq!(outputs.len(), values.len());
+  outputs
+}
+
+fn sign(
+  keys: &HashMap<Participant, ThresholdKeys<Secp256k1>>,
+  tx: &SignableTransaction,
+) -> Transaction {
+  let mut machines = HashMap::new();
+  for i in (1 ..= THRESHOLD).map(|i| Participant::new(i).unwrap()) {
+    machines.insert(i, tx.clone().multisig(&keys[&i].clone()).unwrap());
+  }
+  sign_without_caching(&mut OsRng, machines, &[])
+}
+
+// The virtual size of a transaction, as defined by rust-bitcoin's implementation of Bitcoin Core's
+// policy
+fn vbytes(tx: &Transaction) -> u64 {
+  u64::try_from(get_virtual_tx_size(i64::try_from(tx.weight().to_wu()).unwrap(), 0)).unwrap()
+}
+
+// Build the transaction we expect to be created, with dummy signatures, using rust-bitcoin
+fn reference(
+  inputs: &[ReceivedOutput],
+  payments: &[(ScriptBuf, u64)],
+  data: Option<&Vec<u8>>,
+  change: Option<(&ScriptBuf, u64)>,
+) -> Transaction {
+  let mut output = payments
+    .iter()
+    .map(|(script_pubkey, value)| TxOut {
+      value: Amount::from_sat(*value),
+      script_pubkey: script_pubkey.clone(),
+    })
+    .collect::<Vec<_>>();
+  if let Some(data) = data {
+    output.push(TxOut {
+      value: Amount::ZERO,
+      script_pubkey: ScriptBuf::new_op_return(PushBytesBuf::try_from(data.clone()).unwrap()),
+    });
+  }
+  if let Some((script_pubkey, value)) = change {
+    output.push(TxOut { value: Amount::from_sat(value), script_pubkey: script_pubkey.clone() });
+  }
+
+  Transaction {
+    version: Version(2),
+    lock_time: LockTime::ZERO,
+    input: inputs
+      .iter()
+      .map(|input| TxIn {
+        previous_output: *input.outpoint(),
+        script_sig: ScriptBuf::new(),
+        sequence: Sequence::MAX,
+        witness: Witness::from_slice(&[vec![0; 64]]),
+      })
+      .collect(),
+    output,
+  }
+}
+
+proptest! {
+  #![proptest_config(ProptestConfig::with_cases(32))]
+
+  // Differentially test transaction construction against a transaction independently built with
+  // rust-bitcoin, and the signatures produced against libsecp256k1
+  #[test]
+  fn differential(
+    values in prop::collection::vec(1_000u64 ..= 100_000_000, 1 ..= 8),
+    payments in prop::collection::vec((any::<bool>(), 1u64 ..= 50_000_000), 0 ..= 8),
+    change in any::<bool>(),
+    data in prop::option::of(prop::collection::vec(any::<u8>(), 0 ..= 81)),
+    fee_per_vbyte in 0u64 ..= 250,
+  ) {
+    let (keys, key) = keys();
+    let inputs = received(key, &values);
+
+    // Pay to both P2TR and P2WPKH outputs, which have differing sizes
+    let payments = payments
+      .into_iter()
+      .map(|(taproot, amount)| {
+        let script_pubkey = if taproot {
+          p2tr_script_buf(key).unwrap()
+        } else {
+          ScriptBuf::new_p2wpkh(WPubkeyHash::all_zeros())
+        };
+        (script_pubkey, amount)
+      })
+      .collect::<Vec<_>>();
+    let change = change.then(|| p2tr_script_buf(key).unwrap());
+
+    let res = SignableTransaction::new(
+      inputs.clone(),
+      &payments,
+      change.clone(),
+      data.clone(),
+      fee_per_vbyte,
+    );
+
+    // Independently determine if this transaction should be constructible
+    let input_sat = values.iter().sum::<u64>();
+    let payment_sat = payments.iter().map(|(_, amount)| amount).sum::<u64>();
+    let without_change = vbytes(&reference(&inputs, &payments, data.as_ref(), None));
+    let expected_err = if payments.is_empty() && change.is_none() && data.is_none() {
+      Some(TransactionError::NoOutputs)
+    } else if payments.iter().any(|(_, amount)| *amount < DUST) {
+      Some(TransactionError::DustPayment)
+    } else if data.as_ref().is_some_and(|data| data.len() > 80) {
+      Some(TransactionError::TooMuchData)
+    } else if (fee_per_vbyte * without_change) <
+      ((u64::from(DEFAULT_MIN_RELAY_TX_FEE) * without_change) / 1000)
+    {
+      Some(TransactionError::TooLowFee)
+    } else if input_sat < (payment_sat + (fee_per_vbyte * without_change)) {
+      Some(TransactionError::NotEnoughFunds {
+        inputs: input_sat,
+        payments: payment_sat,
+        fee: fee_per_vbyte * without_change,
+      })
+    } else {
+      None
+    };
+
+    let tx = match (res, expected_err) {
+      (Ok(tx), None) => tx,
+      // A transaction solely consisting of change which ends up being dust has no outputs
+      (Err(TransactionError::NoOutputs), None) => {
+        prop_assert!(payments.is_empty() && data.is_none());
+        return Ok(());
+      }
+      (res, expected_err) => {
+        prop_assert_eq!(res.err(), expected_err);
+        return Ok(());
+      }
+    };
+
+    // Check the outputs are as expected
+    let outputs = &tx.transaction().output;
+    for (output, (script_pubkey, amount)) in outputs.iter().zip(&payments) {
+      prop_assert_eq!(&output.script_pubkey, script_pubkey);
+      prop_assert_eq!(output.value.to_sat(), *amount);
+    }
+    if let Some(data) = &data {
+      prop_assert_eq!(
+        &outputs[payments.len()].script_pubkey,
+        &ScriptBuf::new_op_return(PushBytesBuf::try_from(data.clone()).unwrap())
+      );
+      prop_assert_eq!(outputs[payments.len()].value, Amount::ZERO);
+    }
+    let expected_outputs = payments.len() + usize::from(data.is_some());
+    let change_output = outputs.get(expected_outputs);
+    prop_assert!(outputs.len() <= (expected_outputs + 1));
+    if let Some(change_output) = change_output {
+      prop_assert_eq!(Some(&change_output.script_pubkey), change.as_ref());
+    }
+
+    // Every non-OP_RETURN output must not be dust, else it'd be non-standard
+    for output in outputs {
+      if !output.script_pubkey.is_op_return() {
+        prop_assert!(output.value.to_sat() >= DUST);
+      }
+    }
+
+    // Check the fee is as needed for the transaction rust-bitcoin builds, and that any change
+    // wasn't erroneously discarded
+    let expected = reference(
+      &inputs,
+      &payments,
+      data.as_ref(),
+      change_output.map(|output| (&output.script_pubkey, output.value.to_sat())),
+    );
+    prop_assert_eq!(tx.needed_fee(), fee_per_vbyte * vbytes(&expected));
+    prop_assert!(tx.fee() >= tx.needed_fee());
+    prop_assert_eq!(
+      tx.fee(),
+      input_sat - outputs.iter().map(|output| output.value.to_sat()).sum::<u64>()
+    );
+    if change_output.is_some() {
+      prop_assert_eq!(tx.fee(), tx.needed_fee());
+    } else if let Some(change) = &change {
+      let with_change = reference(&inputs, &payments, data.as_ref(), Some((change, 0)));
+      prop_assert!(
+        input_sat.saturating_sub(payment_sat + (fee_per_vbyte * vbytes(&with_change))) < DUST
+      );
+    }
+
+    let signed = sign(&keys, &tx);
+
+    // The signed transaction must have the exact weight estimated
+    prop_assert_eq!(signed.weight(), expected.weight());
+    prop_assert!(signed.weight().to_wu() <= u64::from(MAX_STANDARD_TX_WEIGHT));
+    prop_assert_eq!(signed.output.as_slice(), outputs.as_slice());
+
+    // Check the serialization round-trips and the TX ID is as expected
+    prop_assert_eq!(&deserialize::<Transaction>(&serialize(&signed)).unwrap(), &signed);
+    let mut txid = signed.compute_txid().to_byte_array();
+    txid.reverse();
+    prop_assert_eq!(txid, tx.txid());
+
+    // Verify every input's signature with libsecp256k1, over the sighash rust-bitcoin calculates
+    let prevouts = inputs.iter().map(|input| input.output().clone()).collect::<Vec<_>>();
+    let mut cache = SighashCache::new(&signed);
+    for (i, input) in signed.input.iter().enumerate() {
+      prop_assert_eq!(input.witness.len(), 1);
+      let sighash = cache
+        .taproot_key_spend_signature_hash(i, &Prevouts::All(&prevouts), TapSighashType::Default)
+        .unwrap();
+      // The witness program of a P2TR output is its x-only key
+      let key = XOnlyPublicKey::from_slice(&prevouts[i].script_pubkey.as_bytes()[2 ..]).unwrap();
+      prop_assert!(BContext::new()
+        .verify_schnorr(
+          &Signature::from_slice(&input.witness[0]).unwrap(),
+          &Message::from_digest(sighash.to_byte_array()),
+          &key,
+        )
+        .is_ok());
+    }
+  }
+}
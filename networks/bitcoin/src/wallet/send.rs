@@ -61,7 +61,7 @@ pub struct SignableTransaction {
 impl SignableTransaction {
   fn calculate_weight_vbytes(
     inputs: usize,
-    payments: &[(ScriptBuf, u64)],
+    outputs: &[TxOut],
     change: Option<&ScriptBuf>,
   ) -> (u64, u64) {
     // Expand this a full transaction in order to use the bitcoin library's weight function
@@ -82,15 +82,8 @@ impl SignableTransaction {
         };
         inputs
       ],
-      output: payments
-        .iter()
-        // The payment is a fixed size so we don't have to use it here
-        // The script pub key is not of a fixed size and does have to be used here
-        .map(|payment| TxOut {
-          value: Amount::from_sat(payment.1),
-          script_pubkey: payment.0.clone(),
-        })
-        .collect(),
+      // This includes any OP_RETURN output, which isn't a payment yet still contributes weight
+      output: outputs.to_vec(),
     };
     if let Some(change) = change {
       // Use a 0 value since we're currently unsure what the change amount will be, and since
@@ -201,7 +194,7 @@ impl SignableTransaction {
       })
     }
 
-    let (mut weight, vbytes) = Self::calculate_weight_vbytes(tx_ins.len(), payments, None);
+    let (mut weight, vbytes) = Self::calculate_weight_vbytes(tx_ins.len(), &tx_outs, None);
 
     let mut needed_fee = fee_per_vbyte * vbytes;
     // Technically, if there isn't change, this TX may still pay enough of a fee to pass the
@@ -223,7 +216,7 @@ impl SignableTransaction {
     // If there's a change address, check if there's change to give it
     if let Some(change) = change {
       let (weight_with_change, vbytes_with_change) =
-        Self::calculate_weight_vbytes(tx_ins.len(), payments, Some(&change));
+        Self::calculate_weight_vbytes(tx_ins.len(), &tx_outs, Some(&change));
       let fee_with_change = fee_per_vbyte * vbytes_with_change;
       if let Some(value) = input_sat.checked_sub(payment_sat + fee_with_change) {
         if value >= DUST {
@@ -2,7 +2,10 @@ use std::path::Path;
 
 use zeroize::Zeroizing;
 
-use ciphersuite::{group::ff::PrimeField, Ciphersuite, Ristretto};
+use ciphersuite::{
+  group::{ff::PrimeField, GroupEncoding},
+  Ciphersuite, Ristretto,
+};
 
 use crate::{Network, Os, mimalloc, os, build_serai_service, write_dockerfile};
 
@@ -11,6 +14,9 @@ pub fn coordinator(
   orchestration_path: &Path,
   network: Network,
   coordinator_key: Zeroizing<<Ristretto as Ciphersuite>::F>,
+  bitcoin_key: <Ristretto as Ciphersuite>::G,
+  ethereum_key: <Ristretto as Ciphersuite>::G,
+  monero_key: <Ristretto as Ciphersuite>::G,
   serai_key: &Zeroizing<<Ristretto as Ciphersuite>::F>,
 ) {
   let db = network.db();
@@ -34,6 +40,9 @@ RUN apt install -y ca-certificates
   let env_vars = [
     ("MESSAGE_QUEUE_RPC", format!("serai-{}-message-queue", network.label())),
     ("MESSAGE_QUEUE_KEY", hex::encode(coordinator_key.to_repr())),
+    ("BITCOIN_KEY", hex::encode(bitcoin_key.to_bytes())),
+    ("ETHEREUM_KEY", hex::encode(ethereum_key.to_bytes())),
+    ("MONERO_KEY", hex::encode(monero_key.to_bytes())),
     ("DB_PATH", "/volume/coordinator-db".to_string()),
     ("SERAI_KEY", hex::encode(serai_key.to_repr())),
     ("SERAI_HOSTNAME", format!("serai-{}-serai", network.label())),
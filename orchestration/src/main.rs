@@ -328,7 +328,15 @@ fn dockerfiles(network: Network) {
     Zeroizing::new(<Ristretto as Ciphersuite>::F::from_repr(*serai_key_repr).unwrap())
   };
 
-  coordinator(&orchestration_path, network, coordinator_key.0, &serai_key);
+  coordinator(
+    &orchestration_path,
+    network,
+    coordinator_key.0,
+    bitcoin_key.1,
+    ethereum_key.1,
+    monero_key.1,
+    &serai_key,
+  );
 
   serai(&orchestration_path, network, &serai_key);
 }
@@ -516,8 +524,8 @@ fn start(network: Network, services: HashSet<String>) {
           if network == Network::Dev {
             command
           } else {
-            // Publish the port
-            command.arg("-p").arg("30563:30563")
+            // Publish the port, over both TCP and QUIC (which is over UDP)
+            command.arg("-p").arg("30563:30563").arg("-p").arg("30563:30563/udp")
           }
         }
         "serai" => {
@@ -2,7 +2,10 @@ use std::path::Path;
 
 use zeroize::Zeroizing;
 
-use ciphersuite::{group::ff::PrimeField, Ciphersuite, Ristretto};
+use ciphersuite::{
+  group::{ff::PrimeField, GroupEncoding},
+  Ciphersuite, Ristretto,
+};
 
 use crate::{Network, Os, mimalloc, os, build_serai_service, write_dockerfile};
 
@@ -11,7 +14,7 @@ pub fn processor(
   orchestration_path: &Path,
   network: Network,
   coin: &'static str,
-  _coordinator_key: <Ristretto as Ciphersuite>::G,
+  coordinator_key: <Ristretto as Ciphersuite>::G,
   coin_key: Zeroizing<<Ristretto as Ciphersuite>::F>,
   entropy: Zeroizing<[u8; 32]>,
 ) {
@@ -54,6 +57,7 @@ RUN apt install -y ca-certificates
   let mut env_vars = vec![
     ("MESSAGE_QUEUE_RPC", format!("serai-{}-message-queue", network.label())),
     ("MESSAGE_QUEUE_KEY", hex::encode(coin_key.to_repr())),
+    ("COORDINATOR_KEY", hex::encode(coordinator_key.to_bytes())),
     ("ENTROPY", hex::encode(entropy.as_ref())),
     ("NETWORK", coin.to_string()),
     ("NETWORK_RPC_LOGIN", format!("{RPC_USER}:{RPC_PASS}")),
@@ -233,6 +233,8 @@ pub mod substrate {
       context: SubstrateContext,
       block: u64,
       burns: Vec<OutInstructionWithBalance>,
+      // The hash of the extrinsic which made each burn
+      burn_extrinsics: Vec<[u8; 32]>,
       batches: Vec<u32>,
     },
   }
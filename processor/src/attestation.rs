@@ -0,0 +1,420 @@
+use core::ops::Deref;
+use std::{
+  io::{self, Read, Write},
+  path::PathBuf,
+};
+
+use zeroize::Zeroizing;
+use rand_core::{RngCore, CryptoRng, OsRng};
+
+use ciphersuite::{group::GroupEncoding, Ciphersuite, Ristretto};
+use schnorr::SchnorrSignature;
+
+use log::{info, warn};
+
+use scale::{Encode, Decode};
+use serai_client::{
+  primitives::{ExternalNetworkId, ExternalBalance, EXTERNAL_NETWORKS, EXTERNAL_COINS, Amount},
+  coins::primitives::OutInstructionWithBalance,
+};
+
+use serai_env as env;
+
+use crate::{Get, DbTxn, create_db, Payment, networks::Network};
+
+/*
+  Operational attestations bind each payout made by this processor to the burn on Serai which
+  caused it, for the validator's compliance exports.
+
+  When a Substrate block is acknowledged, its burns are noted as pending, referenced by the block
+  number and their index within the block, along with the hash of the extrinsic which made them.
+  When a Plan is signed for, its payments are matched against the pending burns by the burn each
+  payment fulfills, and the fee of the transaction signed is noted. Once the Plan's completion is
+  seen on-chain, the bundle of the burns, the Plan, the external transaction, and the attribution
+  of the fee to each payout is signed by the validator's operational key and exported.
+
+  Plans which don't fulfill burns (forwards, refunds, and the sweeps of rotation) aren't attested
+  to.
+*/
+
+create_db!(
+  AttestationDb {
+    // The burns yet to be included in a Plan
+    PendingBurnsDb: () -> Vec<u8>,
+    // The fee and payouts for a Plan being signed
+    PlanPayoutsDb: (plan: [u8; 32]) -> Vec<u8>,
+    // The attestation for a completed Plan, as JSON
+    AttestationsDb: (plan: [u8; 32]) -> Vec<u8>,
+  }
+);
+
+const ATTESTATION_DST: &[u8] = b"Serai Processor Operational Attestation";
+
+/// The maximum amount of burns which may be pending.
+///
+/// Burns may never be paid out, such as if the amount remaining after fees is dust. Once this
+/// many burns are pending, the oldest are no longer attested to.
+pub const MAX_PENDING_BURNS: usize = 4096;
+
+/// A payout made by a Plan, fulfilling a burn.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Payout {
+  /// The number of the Substrate block the burn was in.
+  pub block: u64,
+  /// The index of the burn within the Substrate block's burns.
+  pub index: u32,
+  /// The hash of the extrinsic which made the burn.
+  pub extrinsic: [u8; 32],
+  pub address: Vec<u8>,
+  pub data: Option<Vec<u8>>,
+  pub balance: ExternalBalance,
+  /// The share of the transaction's fee attributed to this payout.
+  ///
+  /// The fee is attributed in proportion to the amount paid out, as it's amortized over the
+  /// payments.
+  pub fee: u64,
+}
+
+impl Payout {
+  pub fn read<R: Read>(reader: &mut R) -> io::Result<Self> {
+    let mut block = [0; 8];
+    reader.read_exact(&mut block)?;
+    let mut index = [0; 4];
+    reader.read_exact(&mut index)?;
+    let mut extrinsic = [0; 32];
+    reader.read_exact(&mut extrinsic)?;
+
+    let read_vec = |reader: &mut R| -> io::Result<Vec<u8>> {
+      let mut len = [0; 4];
+      reader.read_exact(&mut len)?;
+      let mut res = vec![0; usize::try_from(u32::from_le_bytes(len)).unwrap()];
+      reader.read_exact(&mut res)?;
+      Ok(res)
+    };
+    let address = read_vec(reader)?;
+    let mut has_data = [0; 1];
+    reader.read_exact(&mut has_data)?;
+    let data = if has_data[0] == 1 { Some(read_vec(reader)?) } else { None };
+
+    let balance = ExternalBalance::decode(&mut scale::IoReader(&mut *reader))
+      .map_err(|_| io::Error::other("invalid balance"))?;
+    let mut fee = [0; 8];
+    reader.read_exact(&mut fee)?;
+
+    Ok(Payout {
+      block: u64::from_le_bytes(block),
+      index: u32::from_le_bytes(index),
+      extrinsic,
+      address,
+      data,
+      balance,
+      fee: u64::from_le_bytes(fee),
+    })
+  }
+
+  pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+    writer.write_all(&self.block.to_le_bytes())?;
+    writer.write_all(&self.index.to_le_bytes())?;
+    writer.write_all(&self.extrinsic)?;
+    writer.write_all(&u32::try_from(self.address.len()).unwrap().to_le_bytes())?;
+    writer.write_all(&self.address)?;
+    writer.write_all(&[u8::from(self.data.is_some())])?;
+    if let Some(data) = &self.data {
+      writer.write_all(&u32::try_from(data.len()).unwrap().to_le_bytes())?;
+      writer.write_all(data)?;
+    }
+    writer.write_all(&self.balance.encode())?;
+    writer.write_all(&self.fee.to_le_bytes())
+  }
+
+  fn to_json(&self) -> serde_json::Value {
+    serde_json::json!({
+      "burn": {
+        "block": self.block,
+        "index": self.index,
+        "extrinsic": hex::encode(self.extrinsic),
+      },
+      "address": hex::encode(&self.address),
+      "data": self.data.as_ref().map(hex::encode),
+      "coin": format!("{:?}", self.balance.coin),
+      "amount": self.balance.amount.0,
+      "fee": self.fee,
+    })
+  }
+
+  fn from_json(json: &serde_json::Value) -> Option<Self> {
+    let from_hex = |value: &serde_json::Value| hex::decode(value.as_str()?).ok();
+    let coin = json["coin"].as_str()?;
+    let coin = *EXTERNAL_COINS.iter().find(|candidate| format!("{candidate:?}") == coin)?;
+    Some(Payout {
+      block: json["burn"]["block"].as_u64()?,
+      index: u32::try_from(json["burn"]["index"].as_u64()?).ok()?,
+      extrinsic: from_hex(&json["burn"]["extrinsic"])?.try_into().ok()?,
+      address: from_hex(&json["address"])?,
+      data: if json["data"].is_null() { None } else { Some(from_hex(&json["data"])?) },
+      balance: ExternalBalance { coin, amount: Amount(json["amount"].as_u64()?) },
+      fee: json["fee"].as_u64()?,
+    })
+  }
+}
+
+fn read_payouts(buf: &[u8]) -> Vec<Payout> {
+  let mut reader = buf;
+  let mut res = vec![];
+  while !reader.is_empty() {
+    res.push(Payout::read(&mut reader).unwrap());
+  }
+  res
+}
+
+fn write_payouts(payouts: &[Payout]) -> Vec<u8> {
+  let mut buf = vec![];
+  for payout in payouts {
+    payout.write(&mut buf).unwrap();
+  }
+  buf
+}
+
+/// The amount of burns yet to be included in a Plan, if attestations have been made.
+pub fn pending_burns(getter: &impl Get) -> Option<usize> {
+  PendingBurnsDb::get(getter).map(|pending| read_payouts(&pending).len())
+}
+
+/// A signed attestation to a completed Plan, and the burns it paid out.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Attestation {
+  pub network: ExternalNetworkId,
+  pub plan: [u8; 32],
+  /// The claim for the external transaction which completed the Plan.
+  pub transaction: Vec<u8>,
+  /// The fee of the transaction signed for the Plan.
+  pub fee: u64,
+  pub payouts: Vec<Payout>,
+  /// The operational key which signed this attestation.
+  pub signer: <Ristretto as Ciphersuite>::G,
+  pub signature: SchnorrSignature<Ristretto>,
+}
+
+impl Attestation {
+  fn challenge(
+    network: ExternalNetworkId,
+    plan: [u8; 32],
+    transaction: &[u8],
+    fee: u64,
+    payouts: &[Payout],
+    signer: <Ristretto as Ciphersuite>::G,
+    nonce: <Ristretto as Ciphersuite>::G,
+  ) -> <Ristretto as Ciphersuite>::F {
+    let mut msg = network.encode();
+    msg.extend(plan);
+    msg.extend(u32::try_from(transaction.len()).unwrap().to_le_bytes());
+    msg.extend(transaction);
+    msg.extend(fee.to_le_bytes());
+    msg.extend(u32::try_from(payouts.len()).unwrap().to_le_bytes());
+    msg.extend(write_payouts(payouts));
+    msg.extend(signer.to_bytes());
+    msg.extend(nonce.to_bytes());
+    Ristretto::hash_to_F(ATTESTATION_DST, &msg)
+  }
+
+  /// Create a signed attestation.
+  pub fn sign<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    key: &Zeroizing<<Ristretto as Ciphersuite>::F>,
+    network: ExternalNetworkId,
+    plan: [u8; 32],
+    transaction: Vec<u8>,
+    fee: u64,
+    payouts: Vec<Payout>,
+  ) -> Attestation {
+    let signer = Ristretto::generator() * key.deref();
+    let nonce = Zeroizing::new(<Ristretto as Ciphersuite>::random_nonzero_F(rng));
+    let challenge = Self::challenge(
+      network,
+      plan,
+      &transaction,
+      fee,
+      &payouts,
+      signer,
+      Ristretto::generator() * nonce.deref(),
+    );
+    let signature = SchnorrSignature::sign(key, nonce, challenge);
+    Attestation { network, plan, transaction, fee, payouts, signer, signature }
+  }
+
+  /// Verify this attestation's signature.
+  ///
+  /// This only verifies the signature was produced by `signer`. The verifier is responsible for
+  /// checking `signer` is the validator's operational key.
+  #[must_use]
+  pub fn verify(&self) -> bool {
+    self.signature.verify(
+      self.signer,
+      Self::challenge(
+        self.network,
+        self.plan,
+        &self.transaction,
+        self.fee,
+        &self.payouts,
+        self.signer,
+        self.signature.R,
+      ),
+    )
+  }
+
+  pub fn to_json(&self) -> serde_json::Value {
+    serde_json::json!({
+      "network": format!("{:?}", self.network),
+      "plan": hex::encode(self.plan),
+      "transaction": hex::encode(&self.transaction),
+      "fee": self.fee,
+      "payouts": self.payouts.iter().map(Payout::to_json).collect::<Vec<_>>(),
+      "signer": hex::encode(self.signer.to_bytes()),
+      "signature": hex::encode(self.signature.serialize()),
+    })
+  }
+
+  pub fn from_json(json: &serde_json::Value) -> Option<Self> {
+    let from_hex = |value: &serde_json::Value| hex::decode(value.as_str()?).ok();
+    let network = json["network"].as_str()?;
+    let network =
+      *EXTERNAL_NETWORKS.iter().find(|candidate| format!("{candidate:?}") == network)?;
+    Some(Attestation {
+      network,
+      plan: from_hex(&json["plan"])?.try_into().ok()?,
+      transaction: from_hex(&json["transaction"])?,
+      fee: json["fee"].as_u64()?,
+      payouts: json["payouts"].as_array()?.iter().map(Payout::from_json).collect::<Option<_>>()?,
+      signer: Ristretto::read_G::<&[u8]>(&mut from_hex(&json["signer"])?.as_ref()).ok()?,
+      signature: SchnorrSignature::read::<&[u8]>(&mut from_hex(&json["signature"])?.as_ref())
+        .ok()?,
+    })
+  }
+}
+
+pub struct Attester {
+  network: ExternalNetworkId,
+  key: Zeroizing<<Ristretto as Ciphersuite>::F>,
+  // Where attestations are exported to, if anywhere
+  path: Option<PathBuf>,
+}
+
+impl Attester {
+  /// Load the attestation configuration from the environment, if one is specified.
+  pub fn from_env(network: ExternalNetworkId) -> Option<Attester> {
+    let key = env::var("ATTESTATION_KEY")?;
+    let key = Zeroizing::new(hex::decode(key).expect("attestation key wasn't hex-formatted"));
+    let key = Zeroizing::new(
+      Ristretto::read_F::<&[u8]>(&mut key.as_ref()).expect("attestation key wasn't a valid scalar"),
+    );
+    let path = env::var("ATTESTATION_PATH").map(PathBuf::from);
+    Some(Attester { network, key, path })
+  }
+
+  /// Note the burns from a Substrate block, and the extrinsics which made them, as pending payout.
+  pub fn burns<N: Network>(
+    &self,
+    txn: &mut impl DbTxn,
+    block: u64,
+    extrinsics: &[[u8; 32]],
+    burns: &[OutInstructionWithBalance],
+  ) {
+    assert_eq!(extrinsics.len(), burns.len(), "burns didn't each have an extrinsic");
+    let mut pending = read_payouts(&PendingBurnsDb::get(txn).unwrap_or_default());
+    for (index, (extrinsic, burn)) in extrinsics.iter().zip(burns).enumerate() {
+      // Burns to addresses we can't pay out to, or of dust, will never be paid out, so don't
+      // bother noting them
+      let Ok(address) = N::Address::try_from(burn.instruction.address.clone().consume()) else {
+        continue;
+      };
+      if burn.balance.amount.0 < N::DUST {
+        continue;
+      }
+      pending.push(Payout {
+        block,
+        index: u32::try_from(index).unwrap(),
+        extrinsic: *extrinsic,
+        address: address.try_into().map_err(|_| ()).expect("couldn't serialize burn's address"),
+        data: burn.instruction.data.clone().map(|data| data.consume()),
+        balance: burn.balance,
+        fee: 0,
+      });
+    }
+
+    if pending.len() > MAX_PENDING_BURNS {
+      let pruned = pending.drain(.. (pending.len() - MAX_PENDING_BURNS)).collect::<Vec<_>>();
+      for burn in pruned {
+        warn!(
+          "burn {} in block {} (extrinsic {}) was never paid out and will not be attested to",
+          burn.index,
+          burn.block,
+          hex::encode(burn.extrinsic),
+        );
+      }
+    }
+    PendingBurnsDb::set(txn, &write_payouts(&pending));
+  }
+
+  /// Note the payouts made by a Plan being signed for.
+  pub fn plan<N: Network>(
+    &self,
+    txn: &mut impl DbTxn,
+    plan: [u8; 32],
+    payments: &[Payment<N>],
+    fee: u64,
+  ) {
+    let mut pending = read_payouts(&PendingBurnsDb::get(txn).unwrap_or_default());
+
+    let mut payouts = vec![];
+    for payment in payments {
+      // Payments which don't fulfill burns, such as branches, aren't attested to
+      let Some(burn) = payment.burn else { continue };
+      let Some(i) = pending.iter().position(|noted| (noted.block, noted.index) == burn) else {
+        warn!("plan {} had a payment for a burn which wasn't pending", hex::encode(plan));
+        continue;
+      };
+      payouts.push(pending.remove(i));
+    }
+    if payouts.is_empty() {
+      return;
+    }
+
+    // Attribute the fee in proportion to each payout's amount
+    let total = payouts.iter().map(|payout| u128::from(payout.balance.amount.0)).sum::<u128>();
+    if total != 0 {
+      for payout in &mut payouts {
+        payout.fee =
+          u64::try_from((u128::from(fee) * u128::from(payout.balance.amount.0)) / total).unwrap();
+      }
+    }
+
+    PendingBurnsDb::set(txn, &write_payouts(&pending));
+    let mut buf = fee.to_le_bytes().to_vec();
+    buf.extend(write_payouts(&payouts));
+    PlanPayoutsDb::set(txn, plan, &buf);
+  }
+
+  /// Attest to a Plan's completion, if it paid out burns.
+  pub fn completed(&self, txn: &mut impl DbTxn, plan: [u8; 32], claim: &[u8]) {
+    let Some(buf) = PlanPayoutsDb::get(txn, plan) else { return };
+    PlanPayoutsDb::del(txn, plan);
+
+    let fee = u64::from_le_bytes(buf[.. 8].try_into().unwrap());
+    let payouts = read_payouts(&buf[8 ..]);
+    let attestation =
+      Attestation::sign(&mut OsRng, &self.key, self.network, plan, claim.to_vec(), fee, payouts);
+    debug_assert!(attestation.verify());
+    debug_assert_eq!(Attestation::from_json(&attestation.to_json()).as_ref(), Some(&attestation));
+    let json = serde_json::to_vec_pretty(&attestation.to_json()).unwrap();
+    AttestationsDb::set(txn, plan, &json);
+
+    if let Some(path) = &self.path {
+      let path = path.join(format!("{}.json", hex::encode(plan)));
+      if let Err(e) = std::fs::write(&path, &json) {
+        warn!("couldn't export attestation to {}: {e:?}", path.display());
+        return;
+      }
+    }
+    info!("attested to the completion of plan {}", hex::encode(plan));
+  }
+}
@@ -21,6 +21,15 @@ use messages::key_gen::*;
 
 use crate::{Get, DbTxn, Db, create_db, networks::Network};
 
+/// What to do with a session's keys once the session has retired.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RetiredKeyPolicy {
+  /// Retain the keys, as may be useful to recover coins mistakenly sent to a retired multisig.
+  Retain,
+  /// Destroy the keys.
+  Destroy,
+}
+
 #[derive(Debug)]
 pub struct KeyConfirmed<C: Ciphersuite> {
   pub substrate_keys: Vec<ThresholdKeys<Ristretto>>,
@@ -40,6 +49,8 @@ create_db!(
     KeysDb: (network_key: &[u8]) -> Vec<u8>,
     SessionDb: (network_key: &[u8]) -> Session,
     NetworkKeyDb: (session: Session) -> Vec<u8>,
+    // The sessions we've confirmed keys for, in order to seal/unseal them as a whole
+    ConfirmedSessionsDb: () -> Vec<Session>,
   }
 );
 
@@ -107,9 +118,65 @@ impl KeysDb {
     txn.put(Self::key(key_pair.1.as_ref()), keys_vec);
     NetworkKeyDb::set(txn, session, &key_pair.1.clone().into_inner());
     SessionDb::set(txn, key_pair.1.as_ref(), &session);
+    let mut sessions = ConfirmedSessionsDb::get(txn).unwrap_or_default();
+    if !sessions.contains(&session) {
+      sessions.push(session);
+    }
+    ConfirmedSessionsDb::set(txn, &sessions);
     keys
   }
 
+  // Every confirmed session's network key and serialized keys.
+  pub fn confirmed_keys(getter: &impl Get) -> Vec<(Session, Vec<u8>, Zeroizing<Vec<u8>>)> {
+    let mut res = vec![];
+    for session in ConfirmedSessionsDb::get(getter).unwrap_or_default() {
+      let network_key = NetworkKeyDb::get(getter, session).unwrap();
+      // These may have already been removed if we were sealed
+      if let Some(keys) = getter.get(Self::key(&network_key)) {
+        res.push((session, network_key, Zeroizing::new(keys)));
+      }
+    }
+    res
+  }
+
+  // Import keys previously confirmed by another instance of this processor.
+  pub fn import_keys<N: Network>(
+    txn: &mut impl DbTxn,
+    session: Session,
+    network_key: &[u8],
+    keys: &[u8],
+  ) {
+    txn.put(Self::key(network_key), keys);
+    // Sanity check these keys are for the claimed network key
+    let (_, network_keys) =
+      GeneratedKeysDb::read_keys::<N>(txn, &Self::key(network_key)).unwrap().1;
+    assert_eq!(network_keys[0].group_key().to_bytes().as_ref(), network_key);
+
+    NetworkKeyDb::set(txn, session, &network_key.to_vec());
+    SessionDb::set(txn, network_key, &session);
+    let mut sessions = ConfirmedSessionsDb::get(txn).unwrap_or_default();
+    if !sessions.contains(&session) {
+      sessions.push(session);
+    }
+    ConfirmedSessionsDb::set(txn, &sessions);
+  }
+
+  // Remove the keys for a session, leaving the rest of its bookkeeping intact.
+  pub fn remove_keys<N: Network>(txn: &mut impl DbTxn, session: Session) {
+    let Some(network_key) = NetworkKeyDb::get(txn, session) else { return };
+    let Some((substrate_keys, network_keys)) =
+      GeneratedKeysDb::read_keys::<N>(txn, &Self::key(&network_key)).map(|keys| keys.1)
+    else {
+      return;
+    };
+    txn.del(GeneratedKeysDb::key(
+      &session,
+      &substrate_keys[0].group_key().to_bytes(),
+      network_keys[0].group_key().to_bytes().as_ref(),
+    ));
+    txn.del(Self::key(&network_key));
+  }
+
   #[allow(clippy::type_complexity)]
   fn keys<N: Network>(
     getter: &impl Get,
@@ -24,11 +24,13 @@ use serai_env as env;
 
 use message_queue::{Service, client::MessageQueue};
 
+use serai_clock::{Clock, SystemClock};
+
 mod plan;
 pub use plan::*;
 
 mod networks;
-use networks::{Block, Network};
+use networks::{Block, SignableTransaction, Eventuality, Network};
 #[cfg(feature = "bitcoin")]
 use networks::Bitcoin;
 #[cfg(feature = "ethereum")]
@@ -46,7 +48,7 @@ mod coordinator;
 pub use coordinator::*;
 
 mod key_gen;
-use key_gen::{SessionDb, KeyConfirmed, KeyGen};
+use key_gen::{SessionDb, KeysDb, KeyConfirmed, RetiredKeyPolicy, KeyGen};
 
 mod signer;
 use signer::Signer;
@@ -63,6 +65,15 @@ use slash_report_signer::SlashReportSigner;
 mod multisigs;
 use multisigs::{MultisigEvent, MultisigManager};
 
+mod standby;
+use standby::{Failover, next_failover_authorization};
+
+mod attestation;
+use attestation::Attester;
+
+mod reconciliation;
+use reconciliation::Reconciler;
+
 #[cfg(test)]
 mod tests;
 
@@ -142,6 +153,7 @@ async fn handle_coordinator_msg<D: Db, N: Network, Co: Coordinator>(
   coordinator: &mut Co,
   tributary_mutable: &mut TributaryMutable<N, D>,
   substrate_mutable: &mut SubstrateMutable<N, D>,
+  attester: Option<&Attester>,
   msg: &Message,
 ) {
   // If this message expects a higher block number than we have, halt until synced
@@ -400,6 +412,7 @@ async fn handle_coordinator_msg<D: Db, N: Network, Co: Coordinator>(
           context,
           block: substrate_block,
           burns,
+          burn_extrinsics,
           batches,
         } => {
           if let Some((block, session, key_pair)) =
@@ -438,8 +451,19 @@ async fn handle_coordinator_msg<D: Db, N: Network, Co: Coordinator>(
             }
           }
 
+          if let Some(attester) = attester {
+            attester.burns::<N>(txn, substrate_block, &burn_extrinsics, &burns);
+          }
+
           let (acquired_lock, to_sign) =
-            substrate_mutable.substrate_block(txn, network, context, burns).await;
+            substrate_mutable.substrate_block(txn, network, context, substrate_block, burns).await;
+
+          if let Some(attester) = attester {
+            for (_, id, tx, _) in &to_sign {
+              let plan = SubstrateMutable::<N, D>::plan(txn, *id).unwrap();
+              attester.plan(txn, *id, &plan.payments, tx.fee());
+            }
+          }
 
           // Send SubstrateBlockAck, with relevant plan IDs, before we trigger the signing of these
           // plans
@@ -480,10 +504,12 @@ async fn handle_coordinator_msg<D: Db, N: Network, Co: Coordinator>(
   }
 }
 
-async fn boot<N: Network, D: Db, Co: Coordinator>(
+async fn boot<N: Network, D: Db, Co: Coordinator, C: Clock>(
   raw_db: &mut D,
   network: &N,
   coordinator: &mut Co,
+  clock: &C,
+  reconciler: Option<&Reconciler>,
 ) -> (D, TributaryMutable<N, D>, SubstrateMutable<N, D>) {
   let mut entropy_transcript = {
     let entropy = Zeroizing::new(env::var("ENTROPY").expect("entropy wasn't specified"));
@@ -524,6 +550,12 @@ async fn boot<N: Network, D: Db, Co: Coordinator>(
   let (multisig_manager, current_keys, actively_signing) =
     MultisigManager::new(raw_db, network).await;
 
+  // Before we sign anything, check our database is consistent with Serai
+  if let Some(reconciler) = reconciler {
+    let signing = actively_signing.iter().map(|(plan, _, _)| plan.clone()).collect::<Vec<_>>();
+    reconciler.reconcile::<N>(raw_db, &signing).await;
+  }
+
   let mut batch_signer = None;
   let mut signers = HashMap::new();
 
@@ -569,7 +601,7 @@ async fn boot<N: Network, D: Db, Co: Coordinator>(
 
   // Spawn a task to rebroadcast signed TXs yet to be mined into a finalized block
   // This hedges against being dropped due to full mempools, temporarily too low of a fee...
-  tokio::spawn(Signer::<N, D>::rebroadcast_task(raw_db.clone(), network.clone()));
+  tokio::spawn(Signer::<N, D>::rebroadcast_task(raw_db.clone(), network.clone(), clock.clone()));
 
   (
     raw_db.clone(),
@@ -579,15 +611,34 @@ async fn boot<N: Network, D: Db, Co: Coordinator>(
 }
 
 #[allow(clippy::await_holding_lock)] // Needed for txn, unfortunately can't be down-scoped
-async fn run<N: Network, D: Db, Co: Coordinator>(mut raw_db: D, network: N, mut coordinator: Co) {
+async fn run<N: Network, D: Db, Co: Coordinator, C: Clock>(
+  mut raw_db: D,
+  network: N,
+  mut coordinator: Co,
+  clock: C,
+  mut failover: Option<Failover>,
+  attester: Option<Attester>,
+  reconciler: Option<Reconciler>,
+) {
   // We currently expect a contextless bidirectional mapping between these two values
   // (which is that any value of A can be interpreted as B and vice versa)
   // While we can write a contextual mapping, we have yet to do so
   // This check ensures no network which doesn't have a bidirectional mapping is defined
   assert_eq!(<N::Block as Block<N>>::Id::default().as_ref().len(), BlockHash([0u8; 32]).0.len());
 
+  // If this is a standby, this will wait until we're authorized to take over
+  if let Some(failover) = failover.as_mut() {
+    failover.boot::<N, _>(&mut raw_db).await;
+  }
+
   let (main_db, mut tributary_mutable, mut substrate_mutable) =
-    boot(&mut raw_db, &network, &mut coordinator).await;
+    boot(&mut raw_db, &network, &mut coordinator, &clock, reconciler.as_ref()).await;
+
+  let retired_key_policy = match env::var("RETIRED_KEY_POLICY").as_deref() {
+    None | Some("retain") => RetiredKeyPolicy::Retain,
+    Some("destroy") => RetiredKeyPolicy::Destroy,
+    Some(policy) => panic!("unknown retired key policy: {policy}"),
+  };
 
   // We can't load this from the DB as we can't guarantee atomic increments with the ack function
   // TODO: Load with a slight tolerance
@@ -599,6 +650,7 @@ async fn run<N: Network, D: Db, Co: Coordinator>(mut raw_db: D, network: N, mut
     log::trace!("new db txn in run");
 
     let mut outer_msg = None;
+    let mut sealed = false;
 
     tokio::select! {
       // This blocks the entire processor until it finishes handling this message
@@ -632,6 +684,7 @@ async fn run<N: Network, D: Db, Co: Coordinator>(mut raw_db: D, network: N, mut
             &mut coordinator,
             &mut tributary_mutable,
             &mut substrate_mutable,
+            attester.as_ref(),
             &msg,
           ).await;
         }
@@ -668,6 +721,10 @@ async fn run<N: Network, D: Db, Co: Coordinator>(mut raw_db: D, network: N, mut
               // Safe to mutate since all signing operations are done and no more will be added
               if let Some(retired_session) = SessionDb::get(&txn, retired_key.to_bytes().as_ref()) {
                 tributary_mutable.signers.remove(&retired_session);
+                if retired_key_policy == RetiredKeyPolicy::Destroy {
+                  info!("destroying the keys for retired session {retired_session:?}");
+                  KeysDb::remove_keys::<N>(&mut txn, retired_session);
+                }
               }
               tributary_mutable.batch_signer.take();
               let keys = tributary_mutable.key_gen.keys(&new_key);
@@ -684,15 +741,32 @@ async fn run<N: Network, D: Db, Co: Coordinator>(mut raw_db: D, network: N, mut
                 coordinator.send(msg).await;
               }
             }
+            if let Some(attester) = attester.as_ref() {
+              attester.completed(&mut txn, id, N::Eventuality::claim(&tx).as_ref());
+            }
           }
         }
       },
+
+      // Another instance was authorized to take over, so seal ourselves
+      authorization = next_failover_authorization(failover.as_ref(), &main_db) => {
+        Failover::seal::<N>(&mut txn, &authorization);
+        sealed = true;
+      },
     }
 
     txn.commit();
     if let Some(msg) = outer_msg {
       coordinator.ack(msg).await;
     }
+    if sealed {
+      return;
+    }
+
+    // Export any newly confirmed keys for the standby
+    if let Some(failover) = failover.as_mut() {
+      failover.export_keys(&main_db);
+    }
   }
 }
 
@@ -744,13 +818,31 @@ async fn main() {
 
   let coordinator = MessageQueue::from_env(Service::Processor(network_id));
 
+  let failover = Failover::from_env(network_id);
+  // Replicating to a standby requires iterating over the database, which is only supported by
+  // RocksDB
+  #[cfg(feature = "rocksdb")]
+  if let Some(replicator) = failover.as_ref().and_then(Failover::replicator) {
+    tokio::spawn(replicator.replicate_task(db.clone()));
+  }
+  #[cfg(not(feature = "rocksdb"))]
+  assert!(
+    failover.as_ref().and_then(Failover::replicator).is_none(),
+    "replicating the database to a standby is only supported when built with rocksdb"
+  );
+  let attester = Attester::from_env(network_id);
+  let reconciler = Reconciler::from_env(network_id).await;
+
   // This allow is necessary since each configuration deletes the other networks from the following
   // match arms. So we match all cases but since all cases already there according to the compiler
   // we put this to allow clippy to get pass this.
   #[allow(unreachable_patterns)]
   match network_id {
     #[cfg(feature = "bitcoin")]
-    ExternalNetworkId::Bitcoin => run(db, Bitcoin::new(url).await, coordinator).await,
+    ExternalNetworkId::Bitcoin => {
+      run(db, Bitcoin::new(url).await, coordinator, SystemClock, failover, attester, reconciler)
+        .await
+    }
     #[cfg(feature = "ethereum")]
     ExternalNetworkId::Ethereum => {
       let relayer_hostname = env::var("ETHEREUM_RELAYER_HOSTNAME")
@@ -759,10 +851,22 @@ async fn main() {
       let relayer_port =
         env::var("ETHEREUM_RELAYER_PORT").expect("ethereum relayer port wasn't specified");
       let relayer_url = relayer_hostname + ":" + &relayer_port;
-      run(db.clone(), Ethereum::new(db, url, relayer_url).await, coordinator).await
+      run(
+        db.clone(),
+        Ethereum::new(db, url, relayer_url).await,
+        coordinator,
+        SystemClock,
+        failover,
+        attester,
+        reconciler,
+      )
+      .await
     }
     #[cfg(feature = "monero")]
-    ExternalNetworkId::Monero => run(db, Monero::new(url).await, coordinator).await,
+    ExternalNetworkId::Monero => {
+      run(db, Monero::new(url).await, coordinator, SystemClock, failover, attester, reconciler)
+        .await
+    }
     _ => panic!("spawning a processor for an unsupported network"),
   }
 }
@@ -15,6 +15,16 @@ use crate::{
   networks::{Output, Transaction, Network},
 };
 
+/// An instruction, with the ID of the output which originally received it.
+///
+/// Outputs forwarded (or otherwise moved) between multisigs carry the ID of the output they
+/// originated from, letting us detect if an instruction has already been reported.
+#[derive(Clone, PartialEq, Eq, Debug, Encode, Decode)]
+pub struct InstructionWithOrigin {
+  pub origin: Vec<u8>,
+  pub instruction: InInstructionWithBalance,
+}
+
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub enum PlanFromScanning<N: Network> {
   Refund(N::Output, N::Address),
@@ -74,7 +84,8 @@ create_db!(
     ResolvedDb: (tx: &[u8]) -> [u8; 32],
     SigningDb: (key: &[u8]) -> Vec<u8>,
     ForwardedOutputDb: (balance: ExternalBalance) -> Vec<u8>,
-    DelayedOutputDb: () -> Vec<u8>
+    DelayedOutputDb: () -> Vec<u8>,
+    ReportedInstructionDb: (origin: &[u8]) -> ()
   }
 );
 
@@ -129,6 +140,13 @@ impl PlanDb {
     res
   }
 
+  pub fn plan<N: Network>(getter: &impl Get, id: [u8; 32]) -> Option<Plan<N>> {
+    let buf = Self::get(getter, &id)?;
+    let plan = Plan::<N>::read::<&[u8]>(&mut &buf[8 ..]).unwrap();
+    assert_eq!(plan.id(), id);
+    Some(plan)
+  }
+
   pub fn plan_by_key_with_self_change<N: Network>(
     getter: &impl Get,
     key: <N::Curve as Ciphersuite>::G,
@@ -220,45 +238,59 @@ impl PlansFromScanningDb {
 }
 
 impl ForwardedOutputDb {
-  pub fn save_forwarded_output(txn: &mut impl DbTxn, instruction: &InInstructionWithBalance) {
-    let mut existing = Self::get(txn, instruction.balance).unwrap_or_default();
+  pub fn save_forwarded_output(txn: &mut impl DbTxn, instruction: &InstructionWithOrigin) {
+    let balance = instruction.instruction.balance;
+    let mut existing = Self::get(txn, balance).unwrap_or_default();
     existing.extend(instruction.encode());
-    Self::set(txn, instruction.balance, &existing);
+    Self::set(txn, balance, &existing);
   }
 
   pub fn take_forwarded_output(
     txn: &mut impl DbTxn,
     balance: ExternalBalance,
-  ) -> Option<InInstructionWithBalance> {
+  ) -> Option<InstructionWithOrigin> {
     let outputs = Self::get(txn, balance)?;
     let mut outputs_ref = outputs.as_slice();
-    let res = InInstructionWithBalance::decode(&mut outputs_ref).unwrap();
+    let res = InstructionWithOrigin::decode(&mut outputs_ref).unwrap();
     assert!(outputs_ref.len() < outputs.len());
     if outputs_ref.is_empty() {
       txn.del(Self::key(balance));
     } else {
-      Self::set(txn, balance, &outputs);
+      Self::set(txn, balance, &outputs_ref.to_vec());
     }
     Some(res)
   }
 }
 
 impl DelayedOutputDb {
-  pub fn save_delayed_output(txn: &mut impl DbTxn, instruction: &InInstructionWithBalance) {
+  pub fn save_delayed_output(txn: &mut impl DbTxn, instruction: &InstructionWithOrigin) {
     let mut existing = Self::get(txn).unwrap_or_default();
     existing.extend(instruction.encode());
     Self::set(txn, &existing);
   }
 
-  pub fn take_delayed_outputs(txn: &mut impl DbTxn) -> Vec<InInstructionWithBalance> {
+  pub fn take_delayed_outputs(txn: &mut impl DbTxn) -> Vec<InstructionWithOrigin> {
     let Some(outputs) = Self::get(txn) else { return vec![] };
     txn.del(Self::key());
 
     let mut outputs_ref = outputs.as_slice();
     let mut res = vec![];
     while !outputs_ref.is_empty() {
-      res.push(InInstructionWithBalance::decode(&mut outputs_ref).unwrap());
+      res.push(InstructionWithOrigin::decode(&mut outputs_ref).unwrap());
     }
     res
   }
 }
+
+impl ReportedInstructionDb {
+  /// Mark the instruction originating from the specified output as reported.
+  ///
+  /// Returns false if it was already reported.
+  pub fn report(txn: &mut impl DbTxn, origin: &[u8]) -> bool {
+    if Self::get(txn, origin).is_some() {
+      return false;
+    }
+    Self::set(txn, origin, &());
+    true
+  }
+}
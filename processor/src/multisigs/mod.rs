@@ -7,7 +7,7 @@ use scale::{Encode, Decode};
 use messages::SubstrateContext;
 
 use serai_client::{
-  primitives::{MAX_DATA_LEN, ExternalAddress, BlockHash, Data},
+  primitives::{MAX_DATA_LEN, ExternalNetworkId, ExternalAddress, BlockHash, Data},
   in_instructions::primitives::{
     InInstructionWithBalance, Batch, RefundableInInstruction, Shorthand, MAX_BATCH_SIZE,
   },
@@ -25,7 +25,7 @@ pub mod scanner;
 
 use scanner::{ScannerEvent, ScannerHandle, Scanner};
 
-mod db;
+pub(crate) mod db;
 use db::*;
 
 pub(crate) mod scheduler;
@@ -92,6 +92,50 @@ fn instruction_from_output<N: Network>(
   )
 }
 
+// Build the Batches for a block from its instructions, incrementing the next batch ID
+//
+// Instructions whose origin has already been reported are dropped, preventing an instruction from
+// being reported again if its output is moved between multisigs (such as when forwarded during a
+// rotation)
+pub(crate) fn build_batches(
+  txn: &mut impl DbTxn,
+  network: ExternalNetworkId,
+  block: BlockHash,
+  instructions: Vec<InstructionWithOrigin>,
+) -> Vec<Batch> {
+  let mut batch_id = NextBatchDb::get(txn).unwrap_or_default();
+
+  // start with empty batch
+  let mut batches = vec![Batch { network, id: batch_id, block, instructions: vec![] }];
+
+  for InstructionWithOrigin { origin, instruction } in instructions {
+    if !ReportedInstructionDb::report(txn, &origin) {
+      error!("instruction from output {} was already reported. skipping", hex::encode(origin));
+      continue;
+    }
+
+    let batch = batches.last_mut().unwrap();
+    batch.instructions.push(instruction);
+
+    // check if batch is over-size
+    if batch.encode().len() > MAX_BATCH_SIZE {
+      // pop the last instruction so it's back in size
+      let instruction = batch.instructions.pop().unwrap();
+
+      // bump the id for the new batch
+      batch_id += 1;
+
+      // make a new batch with this instruction included
+      batches.push(Batch { network, id: batch_id, block, instructions: vec![instruction] });
+    }
+  }
+
+  // Save the next batch ID
+  NextBatchDb::set(txn, &(batch_id + 1));
+
+  batches
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 enum RotationStep {
   // Use the existing multisig for all actions (steps 1-3)
@@ -296,16 +340,22 @@ impl<D: Db, N: Network> MultisigManager<D, N> {
     &mut self,
     txn: &mut D::Transaction<'_>,
     step: RotationStep,
+    serai_block: u64,
     burns: Vec<OutInstructionWithBalance>,
   ) -> (Vec<Payment<N>>, Vec<Payment<N>>) {
     let mut payments = vec![];
-    for out in burns {
+    for (index, out) in burns.into_iter().enumerate() {
       let OutInstructionWithBalance { instruction: OutInstruction { address, data }, balance } =
         out;
       assert_eq!(balance.coin.network(), N::NETWORK);
 
       if let Ok(address) = N::Address::try_from(address.consume()) {
-        payments.push(Payment { address, data: data.map(Data::consume), balance });
+        payments.push(Payment {
+          address,
+          data: data.map(Data::consume),
+          balance,
+          burn: Some((serai_block, u32::try_from(index).unwrap())),
+        });
       }
     }
 
@@ -561,9 +611,11 @@ impl<D: Db, N: Network> MultisigManager<D, N> {
     block_number: usize,
     block_id: <N::Block as Block<N>>::Id,
     step: &mut RotationStep,
+    serai_block: u64,
     burns: Vec<OutInstructionWithBalance>,
   ) -> (bool, Vec<Plan<N>>, HashSet<[u8; 32]>) {
-    let (mut existing_payments, mut new_payments) = self.burns_to_payments(txn, *step, burns);
+    let (mut existing_payments, mut new_payments) =
+      self.burns_to_payments(txn, *step, serai_block, burns);
 
     let mut plans = vec![];
     let mut plans_from_scanning = HashSet::new();
@@ -695,6 +747,7 @@ impl<D: Db, N: Network> MultisigManager<D, N> {
     txn: &mut D::Transaction<'_>,
     network: &N,
     context: SubstrateContext,
+    serai_block: u64,
     burns: Vec<OutInstructionWithBalance>,
   ) -> (bool, Vec<(<N::Curve as Ciphersuite>::G, [u8; 32], N::SignableTransaction, N::Eventuality)>)
   {
@@ -708,7 +761,7 @@ impl<D: Db, N: Network> MultisigManager<D, N> {
 
     // Get the Plans from this block
     let (acquired_lock, plans, plans_from_scanning) =
-      self.plans_from_block(txn, block_number, block_id, &mut step, burns).await;
+      self.plans_from_block(txn, block_number, block_id, &mut step, serai_block, burns).await;
 
     let res = {
       let mut res = Vec::with_capacity(plans.len());
@@ -797,6 +850,11 @@ impl<D: Db, N: Network> MultisigManager<D, N> {
     (acquired_lock, res)
   }
 
+  /// The Plan with the specified ID, if it's ever been signed for.
+  pub fn plan(getter: &impl Get, id: [u8; 32]) -> Option<Plan<N>> {
+    PlanDb::plan::<N>(getter, id)
+  }
+
   pub async fn release_scanner_lock(&mut self) {
     self.scanner.release_lock().await;
   }
@@ -898,7 +956,10 @@ impl<D: Db, N: Network> MultisigManager<D, N> {
                     interpret an Forwarded output which has an amount associated with an
                     InInstruction which was forwarded as having been forwarded.
                   */
-                  ForwardedOutputDb::save_forwarded_output(txn, &instruction);
+                  ForwardedOutputDb::save_forwarded_output(
+                    txn,
+                    &InstructionWithOrigin { origin: output.id().as_ref().to_vec(), instruction },
+                  );
                 }
               } else if let Some(refund_to) = refund_to {
                 if let Ok(refund_to) = refund_to.consume().try_into() {
@@ -940,6 +1001,8 @@ impl<D: Db, N: Network> MultisigManager<D, N> {
             }
             continue;
           };
+          let instruction =
+            InstructionWithOrigin { origin: output.id().as_ref().to_vec(), instruction };
 
           // Delay External outputs received to new multisig earlier than expected
           if Some(output.key()) == self.new.as_ref().map(|new| new.key) {
@@ -974,40 +1037,7 @@ impl<D: Db, N: Network> MultisigManager<D, N> {
 
         let mut block_hash = [0; 32];
         block_hash.copy_from_slice(block.as_ref());
-        let mut batch_id = NextBatchDb::get(txn).unwrap_or_default();
-
-        // start with empty batch
-        let mut batches = vec![Batch {
-          network: N::NETWORK,
-          id: batch_id,
-          block: BlockHash(block_hash),
-          instructions: vec![],
-        }];
-
-        for instruction in instructions {
-          let batch = batches.last_mut().unwrap();
-          batch.instructions.push(instruction);
-
-          // check if batch is over-size
-          if batch.encode().len() > MAX_BATCH_SIZE {
-            // pop the last instruction so it's back in size
-            let instruction = batch.instructions.pop().unwrap();
-
-            // bump the id for the new batch
-            batch_id += 1;
-
-            // make a new batch with this instruction included
-            batches.push(Batch {
-              network: N::NETWORK,
-              id: batch_id,
-              block: BlockHash(block_hash),
-              instructions: vec![instruction],
-            });
-          }
-        }
-
-        // Save the next batch ID
-        NextBatchDb::set(txn, &(batch_id + 1));
+        let batches = build_batches(txn, N::NETWORK, BlockHash(block_hash), instructions);
 
         (
           block_number,
@@ -4,6 +4,8 @@ use ciphersuite::{group::GroupEncoding, Ciphersuite};
 
 use serai_client::primitives::{ExternalBalance, ExternalCoin, ExternalNetworkId};
 
+use serai_env as env;
+
 use crate::{
   Get, DbTxn, Db, Payment, Plan, create_db,
   networks::{Output, Network},
@@ -14,6 +16,7 @@ use crate::{
 pub struct Scheduler<N: Network> {
   key: <N::Curve as Ciphersuite>::G,
   coins: HashSet<ExternalCoin>,
+  gas: GasConfig,
   rotated: bool,
 }
 
@@ -63,6 +66,144 @@ create_db! {
   SchedulerDb {
     LastNonce: () -> u64,
     RotatedTo: (key: &[u8]) -> Vec<u8>,
+    // The balance of the network's fee coin, as tracked by the outputs received and the payments
+    // (and their gas) scheduled
+    FeeCoinBalance: (key: &[u8]) -> u64,
+    // Token payments blocked until there's enough of the fee coin to pay their gas
+    GasBlockedPayments: (key: &[u8]) -> Vec<u8>,
+    // The amount of the fee coin requested to replenish the gas reserve
+    GasReplenishment: (key: &[u8]) -> u64,
+  }
+}
+
+/// The costs the gas of token payments are budgeted with.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct GasConfig {
+  /// The estimated cost, in the fee coin, of the gas for a single token payment.
+  pub per_token_payment: u64,
+  /// The balance of the fee coin below which replenishment is requested.
+  pub reserve_minimum: u64,
+}
+
+impl Default for GasConfig {
+  fn default() -> GasConfig {
+    // Token payments are executed via a sandboxed call allotted 350k gas. At 30 gwei, this is
+    // 0.0105 ETH, which is 1_050_000 atomic units (with 8 decimals).
+    let per_token_payment = 1_050_000;
+    // Sufficient for a full plan of token payments
+    GasConfig { per_token_payment, reserve_minimum: 256 * per_token_payment }
+  }
+}
+
+impl GasConfig {
+  /// Load the gas costs from the environment, defaulting any not specified.
+  pub fn from_env() -> GasConfig {
+    let var = |name| {
+      env::var(name)
+        .map(|value| value.parse::<u64>().unwrap_or_else(|_| panic!("{name} wasn't a u64")))
+    };
+    let default = GasConfig::default();
+    GasConfig {
+      per_token_payment: var("TOKEN_PAYMENT_GAS_COST").unwrap_or(default.per_token_payment),
+      reserve_minimum: var("GAS_RESERVE_MINIMUM").unwrap_or(default.reserve_minimum),
+    }
+  }
+
+  /// Budget the gas for a series of payments, out of the specified balance of the fee coin.
+  ///
+  /// Payments of the fee coin pay their own gas via amortization, yet still require the fee coin
+  /// be present. Token payments have their gas paid out of the fee coin. Payments which can't be
+  /// afforded are deferred, and so are all further payments sharing their reliance, maintaining
+  /// their order.
+  pub(crate) fn budget(
+    &self,
+    fee_coin: ExternalCoin,
+    mut balance: u64,
+    payments: &[ExternalBalance],
+  ) -> GasBudget {
+    let mut scheduled = vec![];
+    let mut deferred = vec![];
+    let mut fee_coin_deferred = false;
+    let mut tokens_deferred = false;
+    for (i, payment) in payments.iter().enumerate() {
+      let (cost, already_deferred) = if payment.coin == fee_coin {
+        (payment.amount.0, &mut fee_coin_deferred)
+      } else {
+        (self.per_token_payment, &mut tokens_deferred)
+      };
+
+      match balance.checked_sub(cost).filter(|_| !*already_deferred) {
+        Some(remaining) => {
+          balance = remaining;
+          scheduled.push(i);
+        }
+        None => {
+          *already_deferred = true;
+          deferred.push(i);
+        }
+      }
+    }
+    GasBudget { scheduled, deferred, balance }
+  }
+
+  /// The amount of the fee coin to request to replenish the specified balance, if any.
+  pub(crate) fn replenishment(&self, balance: u64) -> Option<u64> {
+    Some(self.reserve_minimum.saturating_sub(balance)).filter(|amount| *amount != 0)
+  }
+}
+
+/// The result of budgeting the gas for a series of payments.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct GasBudget {
+  /// The indexes of the payments which may be scheduled.
+  pub(crate) scheduled: Vec<usize>,
+  /// The indexes of the payments which must be deferred.
+  pub(crate) deferred: Vec<usize>,
+  /// The balance of the fee coin remaining.
+  pub(crate) balance: u64,
+}
+
+fn write_payments<N: Network>(payments: &[Payment<N>]) -> Vec<u8> {
+  let mut buf = vec![];
+  for payment in payments {
+    payment.write(&mut buf).unwrap();
+  }
+  buf
+}
+
+fn read_payments<N: Network>(mut buf: &[u8]) -> Vec<Payment<N>> {
+  let mut payments = vec![];
+  while !buf.is_empty() {
+    payments.push(Payment::read(&mut buf).unwrap());
+  }
+  payments
+}
+
+impl<N: Network> Scheduler<N> {
+  // Request the gas reserve be replenished if it's below the minimum, or clear the request if it
+  // no longer is
+  fn request_replenishment(&self, txn: &mut impl DbTxn, fee_coin: ExternalCoin, balance: u64) {
+    let key_bytes = self.key.to_bytes();
+    let existing = GasReplenishment::get(txn, key_bytes.as_ref());
+    match self.gas.replenishment(balance) {
+      Some(amount) => {
+        // Only alert when the request grows, not on every block it remains outstanding
+        if existing.map_or(true, |existing| amount > existing) {
+          log::warn!(
+            "balance of {fee_coin:?} available for gas ({balance}) is below the minimum ({}), \
+              requesting replenishment of {amount}",
+            self.gas.reserve_minimum,
+          );
+        }
+        GasReplenishment::set(txn, key_bytes.as_ref(), &amount);
+      }
+      None => {
+        if existing.is_some() {
+          log::info!("balance of {fee_coin:?} available for gas was replenished");
+          GasReplenishment::del(txn, key_bytes.as_ref());
+        }
+      }
+    }
   }
 }
 
@@ -84,7 +225,12 @@ impl<N: Network<Scheduler = Self>> SchedulerTrait<N> for Scheduler<N> {
     assert!(N::change_address(key).is_none());
     assert!(N::forward_address(key).is_none());
 
-    Scheduler { key, coins: network.coins().iter().copied().collect(), rotated: false }
+    Scheduler {
+      key,
+      coins: network.coins().iter().copied().collect(),
+      gas: GasConfig::from_env(),
+      rotated: false,
+    }
   }
 
   /// Load a Scheduler from the DB.
@@ -96,6 +242,7 @@ impl<N: Network<Scheduler = Self>> SchedulerTrait<N> for Scheduler<N> {
     Ok(Scheduler {
       key,
       coins: network.coins().iter().copied().collect(),
+      gas: GasConfig::from_env(),
       rotated: RotatedTo::get(db, key.to_bytes().as_ref()).is_some(),
     })
   }
@@ -112,10 +259,46 @@ impl<N: Network<Scheduler = Self>> SchedulerTrait<N> for Scheduler<N> {
     key_for_any_change: <N::Curve as Ciphersuite>::G,
     force_spend: bool,
   ) -> Vec<Plan<N>> {
+    // The fee coin is the network's native coin, which is always its first coin
+    let fee_coin = N::NETWORK.coins()[0];
+
+    let key_bytes = self.key.to_bytes();
+    let mut fee_balance = FeeCoinBalance::get(txn, key_bytes.as_ref()).unwrap_or(0);
     for utxo in utxos {
-      assert!(self.coins.contains(&utxo.balance().coin));
+      let balance = utxo.balance();
+      assert!(self.coins.contains(&balance.coin));
+      if balance.coin == fee_coin {
+        fee_balance = fee_balance.saturating_add(balance.amount.0);
+      }
     }
 
+    // Handle any payments previously blocked before these payments, preserving their order
+    let mut queued = GasBlockedPayments::get(txn, key_bytes.as_ref())
+      .map(|payments| read_payments::<N>(&payments))
+      .unwrap_or(vec![]);
+    queued.extend(payments);
+
+    let budget = self.gas.budget(
+      fee_coin,
+      fee_balance,
+      &queued.iter().map(|payment| payment.balance).collect::<Vec<_>>(),
+    );
+    let fee_balance = budget.balance;
+    let mut queued = queued.into_iter().map(Some).collect::<Vec<_>>();
+    let mut take =
+      |indexes: &[usize]| indexes.iter().map(|i| queued[*i].take().unwrap()).collect::<Vec<_>>();
+    let payments = take(&budget.scheduled);
+    let blocked = take(&budget.deferred);
+
+    if blocked.is_empty() {
+      GasBlockedPayments::del(txn, key_bytes.as_ref());
+    } else {
+      log::error!("{} payment(s) are blocked until {fee_coin:?} is replenished", blocked.len());
+      GasBlockedPayments::set(txn, key_bytes.as_ref(), &write_payments(&blocked));
+    }
+    FeeCoinBalance::set(txn, key_bytes.as_ref(), &fee_balance);
+    self.request_replenishment(txn, fee_coin, fee_balance);
+
     let mut nonce = LastNonce::get(txn).unwrap_or(1);
     let mut plans = vec![];
     for chunk in payments.as_slice().chunks(N::MAX_OUTPUTS) {
@@ -148,6 +331,16 @@ impl<N: Network<Scheduler = Self>> SchedulerTrait<N> for Scheduler<N> {
         self.key.to_bytes().as_ref(),
         &key_for_any_change.to_bytes().as_ref().to_vec(),
       );
+
+      // The contract's funds, and accordingly its balance of the fee coin, are now controlled by
+      // the new key
+      let new_key_bytes = key_for_any_change.to_bytes();
+      let new_balance = FeeCoinBalance::get(txn, new_key_bytes.as_ref())
+        .unwrap_or(0)
+        .saturating_add(FeeCoinBalance::get(txn, key_bytes.as_ref()).unwrap_or(0));
+      FeeCoinBalance::set(txn, new_key_bytes.as_ref(), &new_balance);
+      FeeCoinBalance::del(txn, key_bytes.as_ref());
+      GasReplenishment::del(txn, key_bytes.as_ref());
     }
 
     LastNonce::set(txn, &nonce);
@@ -155,8 +348,12 @@ impl<N: Network<Scheduler = Self>> SchedulerTrait<N> for Scheduler<N> {
     plans
   }
 
-  fn consume_payments<D: Db>(&mut self, _txn: &mut D::Transaction<'_>) -> Vec<Payment<N>> {
-    vec![]
+  fn consume_payments<D: Db>(&mut self, txn: &mut D::Transaction<'_>) -> Vec<Payment<N>> {
+    // The only payments which may be pending are those blocked due to a lack of gas
+    let key_bytes = self.key.to_bytes();
+    let Some(blocked) = GasBlockedPayments::get(txn, key_bytes.as_ref()) else { return vec![] };
+    GasBlockedPayments::del(txn, key_bytes.as_ref());
+    read_payments::<N>(&blocked)
   }
 
   fn created_output<D: Db>(
@@ -184,7 +381,12 @@ impl<N: Network<Scheduler = Self>> SchedulerTrait<N> for Scheduler<N> {
     Plan {
       key: current_key,
       inputs: vec![],
-      payments: vec![Payment { address: refund_to, data: None, balance: output.balance() }],
+      payments: vec![Payment {
+        address: refund_to,
+        data: None,
+        balance: output.balance(),
+        burn: None,
+      }],
       change: None,
       scheduler_addendum: Addendum::Nonce(nonce),
     }
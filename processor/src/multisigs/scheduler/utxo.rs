@@ -250,6 +250,7 @@ impl<N: UtxoNetwork<Scheduler = Self>> Scheduler<N> {
           address: branch_address.clone(),
           data: None,
           balance: ExternalBalance { coin: self.coin, amount: Amount(amount) },
+          burn: None,
         },
       );
     }
@@ -595,7 +596,12 @@ impl<N: UtxoNetwork<Scheduler = Self>> SchedulerTrait<N> for Scheduler<N> {
       key: output.key(),
       // Uses a payment as this will still be successfully sent due to fee amortization,
       // and because change is currently always a Serai key
-      payments: vec![Payment { address: refund_to, data: None, balance: output.balance() }],
+      payments: vec![Payment {
+        address: refund_to,
+        data: None,
+        balance: output.balance(),
+        burn: None,
+      }],
       inputs: vec![output],
       change: None,
       scheduler_addendum: (),
@@ -611,6 +617,7 @@ impl<N: UtxoNetwork<Scheduler = Self>> SchedulerTrait<N> for Scheduler<N> {
         address: N::forward_address(to).unwrap(),
         data: None,
         balance: output.balance(),
+        burn: None,
       }],
       inputs: vec![output],
       change: None,
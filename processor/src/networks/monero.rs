@@ -365,6 +365,7 @@ impl Monero {
         .unwrap(),
         balance: ExternalBalance { coin: ExternalCoin::Monero, amount: Amount(0) },
         data: None,
+        burn: None,
       });
     }
 
@@ -18,6 +18,9 @@ pub struct Payment<N: Network> {
   pub address: N::Address,
   pub data: Option<Vec<u8>>,
   pub balance: ExternalBalance,
+  /// The burn on Serai this payment fulfills, if it fulfills one, as the number of the Substrate
+  /// block it was in and its index within the block's burns.
+  pub burn: Option<(u64, u32)>,
 }
 
 impl<N: Network> Payment<N> {
@@ -41,13 +44,21 @@ impl<N: Network> Payment<N> {
     writer.write_all(&u32::try_from(address.len()).unwrap().to_le_bytes())?;
     writer.write_all(&address)?;
 
-    writer.write_all(&[u8::from(self.data.is_some())])?;
+    // The second bit flags the presence of the burn, keeping payments without one encoded as
+    // they were before payments referenced their burns
+    writer.write_all(&[u8::from(self.data.is_some()) | (u8::from(self.burn.is_some()) << 1)])?;
     if let Some(data) = &self.data {
       writer.write_all(&u32::try_from(data.len()).unwrap().to_le_bytes())?;
       writer.write_all(data)?;
     }
 
-    writer.write_all(&self.balance.encode())
+    writer.write_all(&self.balance.encode())?;
+
+    if let Some((block, index)) = self.burn {
+      writer.write_all(&block.to_le_bytes())?;
+      writer.write_all(&index.to_le_bytes())?;
+    }
+    Ok(())
   }
 
   pub fn read<R: io::Read>(reader: &mut R) -> io::Result<Self> {
@@ -57,9 +68,12 @@ impl<N: Network> Payment<N> {
     reader.read_exact(&mut address)?;
     let address = N::Address::try_from(address).map_err(|_| io::Error::other("invalid address"))?;
 
-    let mut buf = [0; 1];
-    reader.read_exact(&mut buf)?;
-    let data = if buf[0] == 1 {
+    let mut flags = [0; 1];
+    reader.read_exact(&mut flags)?;
+    if flags[0] > 0b11 {
+      Err(io::Error::other("invalid payment flags"))?;
+    }
+    let data = if (flags[0] & 1) == 1 {
       let mut buf = [0; 4];
       reader.read_exact(&mut buf)?;
       let mut data = vec![0; usize::try_from(u32::from_le_bytes(buf)).unwrap()];
@@ -72,7 +86,17 @@ impl<N: Network> Payment<N> {
     let balance = ExternalBalance::decode(&mut scale::IoReader(reader))
       .map_err(|_| io::Error::other("invalid balance"))?;
 
-    Ok(Payment { address, data, balance })
+    let burn = if (flags[0] & 0b10) == 0b10 {
+      let mut block = [0; 8];
+      reader.read_exact(&mut block)?;
+      let mut index = [0; 4];
+      reader.read_exact(&mut index)?;
+      Some((u64::from_le_bytes(block), u32::from_le_bytes(index)))
+    } else {
+      None
+    };
+
+    Ok(Payment { address, data, balance, burn })
   }
 }
 
@@ -0,0 +1,289 @@
+use std::{time::Duration, collections::HashMap};
+
+use transcript::{Transcript, RecommendedTranscript};
+use ciphersuite::group::GroupEncoding;
+
+use log::{info, warn, error};
+use tokio::time::sleep;
+
+use serai_client::{
+  primitives::ExternalNetworkId,
+  validator_sets::primitives::{Session, ExternalValidatorSet, KeyPair},
+  Serai, SeraiError,
+};
+
+use serai_env as env;
+
+use crate::{
+  Get, Plan,
+  key_gen::{KeysDb, ConfirmedSessionsDb, NetworkKeyDb},
+  multisigs::db::NextBatchDb,
+  attestation::pending_burns,
+  networks::Network,
+};
+
+/*
+  On boot, the processor reconciles its database against Serai, as a database restored from a
+  stale backup, or otherwise corrupted, may cause it to sign conflicting Batches or with the wrong
+  keys.
+
+  The findings are logged as a report. If any are critical, the processor refuses to sign anything
+  until the operator acknowledges the report, by setting RECONCILIATION_ACKNOWLEDGED to its ID.
+  The ID solely commits to the critical findings, so informational findings changing don't
+  require re-acknowledgement.
+*/
+
+const REPORT_DST: &[u8] = b"Serai Processor Reconciliation Report";
+
+// How long to wait before re-checking a report which was refused
+const RECHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Severity {
+  Info,
+  /// The processor won't sign until this is acknowledged.
+  Critical,
+}
+
+/// A finding from reconciling the processor's database against Serai.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Finding {
+  /// The check which made this finding.
+  pub check: &'static str,
+  pub severity: Severity,
+  pub description: String,
+}
+
+/// Serai's view of this processor's network.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct OnChainState {
+  /// The ID of the last Batch executed.
+  pub last_batch: Option<u32>,
+  /// The key pairs present on Serai for the sessions we have confirmed keys for.
+  pub keys: HashMap<Session, KeyPair>,
+}
+
+/// The processor's view of its network, as present in its database.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct LocalState {
+  /// The ID of the next Batch we'll create.
+  pub next_batch: u32,
+  /// The network keys we have confirmed, by session.
+  pub network_keys: Vec<(Session, Vec<u8>)>,
+  /// The amount of Plans being signed.
+  pub signing_plans: usize,
+  /// The Plans being signed with keys we don't have shares for.
+  pub unsignable_plans: Vec<[u8; 32]>,
+  /// The amount of burns yet to be included in a Plan, if known.
+  pub pending_burns: Option<usize>,
+}
+
+impl LocalState {
+  pub fn load<N: Network>(getter: &impl Get, signing: &[Plan<N>]) -> LocalState {
+    let network_keys = ConfirmedSessionsDb::get(getter)
+      .unwrap_or_default()
+      .into_iter()
+      .map(|session| (session, NetworkKeyDb::get(getter, session).unwrap()))
+      .collect();
+    let unsignable_plans = signing
+      .iter()
+      .filter(|plan| KeysDb::get(getter, plan.key.to_bytes().as_ref()).is_none())
+      .map(Plan::id)
+      .collect();
+    LocalState {
+      next_batch: NextBatchDb::get(getter).unwrap_or(0),
+      network_keys,
+      signing_plans: signing.len(),
+      unsignable_plans,
+      pending_burns: pending_burns(getter),
+    }
+  }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct ReconciliationReport {
+  pub findings: Vec<Finding>,
+}
+
+impl ReconciliationReport {
+  pub fn new(on_chain: &OnChainState, local: &LocalState) -> ReconciliationReport {
+    let mut findings = vec![];
+    let mut finding =
+      |check, severity, description| findings.push(Finding { check, severity, description });
+
+    // Serai should never have executed a Batch we have yet to create
+    let expected_next_batch = on_chain.last_batch.map_or(0, |last| last + 1);
+    if local.next_batch < expected_next_batch {
+      finding(
+        "batches",
+        Severity::Critical,
+        format!(
+          "Serai executed batch {}, yet our next batch is {}. our database is behind Serai",
+          expected_next_batch - 1,
+          local.next_batch,
+        ),
+      );
+    } else {
+      finding(
+        "batches",
+        Severity::Info,
+        format!(
+          "{} batch(es) created yet to be executed on Serai",
+          local.next_batch - expected_next_batch
+        ),
+      );
+    }
+
+    for (session, network_key) in &local.network_keys {
+      match on_chain.keys.get(session).map(|key_pair| key_pair.1.to_vec()) {
+        Some(on_chain_key) if &on_chain_key != network_key => finding(
+          "keys",
+          Severity::Critical,
+          format!(
+            "{session:?} has the key {} on Serai, yet we have {}",
+            hex::encode(on_chain_key),
+            hex::encode(network_key),
+          ),
+        ),
+        Some(_) => {
+          finding("keys", Severity::Info, format!("{session:?}'s key matches Serai's"));
+        }
+        None => finding(
+          "keys",
+          Severity::Info,
+          format!("{session:?} no longer has a key on Serai, presumably as it retired"),
+        ),
+      }
+    }
+
+    for plan in &local.unsignable_plans {
+      finding(
+        "plans",
+        Severity::Critical,
+        format!("plan {} is being signed with a key we don't have shares for", hex::encode(plan)),
+      );
+    }
+    finding(
+      "plans",
+      Severity::Info,
+      match local.pending_burns {
+        Some(burns) => format!(
+          "{} plan(s) being signed, {burns} burn(s) yet to be included in a plan",
+          local.signing_plans
+        ),
+        None => format!("{} plan(s) being signed", local.signing_plans),
+      },
+    );
+
+    ReconciliationReport { findings }
+  }
+
+  pub fn critical(&self) -> bool {
+    self.findings.iter().any(|finding| finding.severity == Severity::Critical)
+  }
+
+  /// The ID of this report, as used to acknowledge it.
+  pub fn id(&self) -> [u8; 32] {
+    let mut transcript = RecommendedTranscript::new(REPORT_DST);
+    for finding in &self.findings {
+      if finding.severity == Severity::Critical {
+        transcript.append_message(b"check", finding.check);
+        transcript.append_message(b"description", &finding.description);
+      }
+    }
+    transcript.challenge(b"id")[.. 32].try_into().unwrap()
+  }
+
+  pub fn to_json(&self) -> serde_json::Value {
+    serde_json::json!({
+      "id": hex::encode(self.id()),
+      "critical": self.critical(),
+      "findings": self.findings.iter().map(|finding| serde_json::json!({
+        "check": finding.check,
+        "severity": format!("{:?}", finding.severity),
+        "description": finding.description,
+      })).collect::<Vec<_>>(),
+    })
+  }
+}
+
+pub struct Reconciler {
+  network: ExternalNetworkId,
+  serai: Serai,
+  acknowledged: Option<[u8; 32]>,
+}
+
+impl Reconciler {
+  /// Connect to the Serai node to reconcile against, if one was configured.
+  pub async fn from_env(network: ExternalNetworkId) -> Option<Reconciler> {
+    let url = format!("http://{}:9944", env::var("SERAI_HOSTNAME")?);
+    let acknowledged = env::var("RECONCILIATION_ACKNOWLEDGED").map(|id| {
+      hex::decode(id)
+        .ok()
+        .and_then(|id| id.try_into().ok())
+        .expect("acknowledged reconciliation report ID wasn't 32 hex-encoded bytes")
+    });
+    loop {
+      match Serai::new(url.clone()).await {
+        Ok(serai) => return Some(Reconciler { network, serai, acknowledged }),
+        // Retrying won't resolve the node's runtime being incompatible with the calls we build
+        Err(e @ SeraiError::IncompatibleRuntime(_)) => {
+          error!("not reconciling as the Serai node is incompatible: {e}");
+          return None;
+        }
+        Err(e) => {
+          error!("couldn't connect to the Serai node to reconcile against: {e:?}");
+          sleep(Duration::from_secs(5)).await;
+        }
+      }
+    }
+  }
+
+  async fn on_chain(&self, local: &LocalState) -> Result<OnChainState, SeraiError> {
+    let serai = self.serai.as_of_latest_finalized_block().await?;
+    let last_batch = serai.in_instructions().last_batch_for_network(self.network).await?;
+    let mut keys = HashMap::new();
+    for (session, _) in &local.network_keys {
+      let set = ExternalValidatorSet { network: self.network, session: *session };
+      if let Some(key_pair) = serai.validator_sets().keys(set).await? {
+        keys.insert(*session, key_pair);
+      }
+    }
+    Ok(OnChainState { last_batch, keys })
+  }
+
+  /// Reconcile the database against Serai, only returning once there are no critical findings or
+  /// the report has been acknowledged.
+  pub async fn reconcile<N: Network>(&self, getter: &impl Get, signing: &[Plan<N>]) {
+    let local = LocalState::load(getter, signing);
+    loop {
+      let on_chain = match self.on_chain(&local).await {
+        Ok(on_chain) => on_chain,
+        Err(e) => {
+          warn!("couldn't fetch Serai's state to reconcile against: {e:?}");
+          sleep(Duration::from_secs(5)).await;
+          continue;
+        }
+      };
+
+      let report = ReconciliationReport::new(&on_chain, &local);
+      info!("reconciliation report: {}", report.to_json());
+      if !report.critical() {
+        return;
+      }
+      if self.acknowledged == Some(report.id()) {
+        warn!(
+          "proceeding despite critical reconciliation findings, as the report was acknowledged"
+        );
+        return;
+      }
+
+      error!(
+        "refusing to sign due to critical reconciliation findings. {} {}",
+        "set RECONCILIATION_ACKNOWLEDGED to the report's ID to proceed regardless:",
+        hex::encode(report.id()),
+      );
+      sleep(RECHECK_INTERVAL).await;
+    }
+  }
+}
@@ -13,6 +13,7 @@ use serai_client::validator_sets::primitives::Session;
 use messages::sign::*;
 
 pub use serai_db::*;
+use serai_clock::Clock;
 
 use crate::{
   Get, DbTxn, Db,
@@ -187,7 +188,7 @@ impl<N: Network, D: Db> fmt::Debug for Signer<N, D> {
 impl<N: Network, D: Db> Signer<N, D> {
   /// Rebroadcast already signed TXs which haven't had their completions mined into a sufficiently
   /// confirmed block.
-  pub async fn rebroadcast_task(db: D, network: N) {
+  pub async fn rebroadcast_task<C: Clock>(db: D, network: N, clock: C) {
     log::info!("rebroadcasting transactions for plans whose completions yet to be confirmed...");
     loop {
       for active in ActiveSignsDb::get(&db).unwrap_or_default() {
@@ -200,7 +201,7 @@ impl<N: Network, D: Db> Signer<N, D> {
       }
       // Only run every five minutes so we aren't frequently loading tens to hundreds of KB from
       // the DB
-      tokio::time::sleep(core::time::Duration::from_secs(5 * 60)).await;
+      clock.sleep(core::time::Duration::from_secs(5 * 60)).await;
     }
   }
   pub fn new(network: N, session: Session, keys: Vec<ThresholdKeys<N::Curve>>) -> Signer<N, D> {
@@ -0,0 +1,603 @@
+use core::ops::Deref;
+use std::{
+  io::{self, Read, Write},
+  path::PathBuf,
+  time::Duration,
+};
+
+use zeroize::{Zeroize, Zeroizing};
+use rand_core::{RngCore, CryptoRng, OsRng};
+
+use chacha20poly1305::{
+  aead::{Aead, KeyInit, Payload},
+  Key, Nonce, ChaCha20Poly1305,
+};
+
+use transcript::{Transcript, RecommendedTranscript};
+use ciphersuite::{group::GroupEncoding, Ciphersuite, Ristretto};
+use schnorr::SchnorrSignature;
+
+use log::{info, warn};
+use tokio::time::sleep;
+
+use scale::{Encode, Decode};
+use serai_client::{primitives::ExternalNetworkId, validator_sets::primitives::Session};
+
+use serai_env as env;
+
+use crate::{Get, DbTxn, Db, Iterate, serai_db_key, create_db, key_gen::KeysDb, networks::Network};
+
+/*
+  A warm standby is a second instance of a processor, holding a sealed replica of the primary's
+  database *without* its key shares and a sealed copy of the key shares, both encrypted under a
+  sealing key it doesn't have. This means compromising the standby doesn't compromise the shares,
+  so running a standby doesn't double the attack surface of the live shares.
+
+  The primary periodically writes the replica of its database, and writes the sealed keys whenever
+  it confirms new keys. The replica is written every REPLICATION_INTERVAL, so a standby may take
+  over with a replica slightly behind the primary's database.
+
+  The sealing key is only delivered to the standby within a failover authorization, which must be
+  signed by a threshold of the validator's operational keys (configured on both instances). Once
+  the standby has a valid authorization, it unseals the replica and the shares, and operates as the
+  primary.
+
+  Every instance is configured with the epoch of the authorization it's currently operating under
+  (0 for the original primary). The primary watches for failover authorizations, and once it sees
+  one for a later epoch, it removes its key shares from its database and stops, preventing both
+  instances from signing at the same time.
+*/
+
+create_db!(
+  StandbyDb {
+    // The epoch of the failover authorization which made this processor the primary
+    FailoverEpochDb: () -> u32,
+    // The epoch of the failover authorization which sealed this processor
+    SealedDb: () -> u32,
+  }
+);
+
+const AUTHORIZATION_DST: &[u8] = b"Serai Processor Failover Authorization";
+
+// How often the primary writes the replica of its database
+const REPLICATION_INTERVAL: Duration = Duration::from_secs(10);
+
+// The labels for what's sealed, which are authenticated as the associated data
+const KEYS_LABEL: &[u8] = b"keys";
+const REPLICA_LABEL: &[u8] = b"replica";
+
+// The cipher for a seal, keyed by the sealing key and a random salt
+//
+// As the salt is random, each key derived is only used once, so the nonce doesn't matter.
+fn cipher(sealing_key: &[u8; 32], salt: &[u8; 32]) -> ChaCha20Poly1305 {
+  let mut transcript = RecommendedTranscript::new(b"Serai Processor Sealed Data");
+  transcript.append_message(b"sealing_key", sealing_key);
+  transcript.append_message(b"salt", salt);
+
+  let mut key = Key::default();
+  let mut challenge = transcript.challenge(b"encryption_key");
+  key.copy_from_slice(&challenge[.. 32]);
+  challenge.zeroize();
+
+  let res = ChaCha20Poly1305::new(&key);
+  key.zeroize();
+  res
+}
+
+fn encrypt<R: RngCore + CryptoRng>(
+  rng: &mut R,
+  sealing_key: &[u8; 32],
+  label: &[u8],
+  plaintext: &[u8],
+) -> Vec<u8> {
+  let mut salt = [0; 32];
+  rng.fill_bytes(&mut salt);
+  let mut res = salt.to_vec();
+  res.extend(
+    cipher(sealing_key, &salt)
+      .encrypt(&Nonce::default(), Payload { msg: plaintext, aad: label })
+      .expect("couldn't encrypt with ChaCha20Poly1305"),
+  );
+  res
+}
+
+fn decrypt(sealing_key: &[u8; 32], label: &[u8], sealed: &[u8]) -> Option<Zeroizing<Vec<u8>>> {
+  if sealed.len() < 32 {
+    None?;
+  }
+  let mut salt = [0; 32];
+  salt.copy_from_slice(&sealed[.. 32]);
+  cipher(sealing_key, &salt)
+    .decrypt(&Nonce::default(), Payload { msg: &sealed[32 ..], aad: label })
+    .ok()
+    .map(Zeroizing::new)
+}
+
+fn read_u32(reader: &mut &[u8]) -> Option<u32> {
+  let mut buf = [0; 4];
+  reader.read_exact(&mut buf).ok()?;
+  Some(u32::from_le_bytes(buf))
+}
+
+fn read_vec(reader: &mut &[u8]) -> Option<Zeroizing<Vec<u8>>> {
+  let len = usize::try_from(read_u32(reader)?).ok()?;
+  if reader.len() < len {
+    None?;
+  }
+  let res = Zeroizing::new(reader[.. len].to_vec());
+  *reader = &reader[len ..];
+  Some(res)
+}
+
+fn write_vec(writer: &mut Vec<u8>, data: &[u8]) {
+  writer.extend(u32::try_from(data.len()).unwrap().to_le_bytes());
+  writer.extend(data);
+}
+
+/// The keys of a session, as stored in the database.
+pub type SessionKeys = (Session, Vec<u8>, Zeroizing<Vec<u8>>);
+
+/// Seal the keys for a standby.
+pub fn seal<R: RngCore + CryptoRng>(
+  rng: &mut R,
+  sealing_key: &[u8; 32],
+  keys: &[SessionKeys],
+) -> Vec<u8> {
+  let mut plaintext = Zeroizing::new(vec![]);
+  for (session, network_key, keys) in keys {
+    plaintext.extend(session.0.to_le_bytes());
+    write_vec(&mut plaintext, network_key);
+    write_vec(&mut plaintext, keys);
+  }
+  encrypt(rng, sealing_key, KEYS_LABEL, &plaintext)
+}
+
+/// Unseal keys sealed with `seal`, returning None if the sealing key is incorrect.
+pub fn unseal(sealing_key: &[u8; 32], sealed: &[u8]) -> Option<Vec<SessionKeys>> {
+  let plaintext = decrypt(sealing_key, KEYS_LABEL, sealed)?;
+
+  let mut res = vec![];
+  let mut reader: &[u8] = plaintext.as_ref();
+  while !reader.is_empty() {
+    let session = Session(read_u32(&mut reader)?);
+    let network_key = read_vec(&mut reader)?.to_vec();
+    let keys = read_vec(&mut reader)?;
+    res.push((session, network_key, keys));
+  }
+  Some(res)
+}
+
+// If an entry is excluded from the replica, as it contains key shares or is specific to this
+// instance
+fn excluded(key: &[u8]) -> bool {
+  [
+    serai_db_key(b"KeyGenDb", b"GeneratedKeysDb", []),
+    serai_db_key(b"KeyGenDb", b"KeysDb", []),
+    serai_db_key(b"StandbyDb", b"FailoverEpochDb", []),
+    serai_db_key(b"StandbyDb", b"SealedDb", []),
+  ]
+  .iter()
+  .any(|prefix| key.starts_with(prefix))
+}
+
+/// Seal a replica of the database for a standby, excluding the key shares.
+pub fn seal_replica<R: RngCore + CryptoRng>(
+  rng: &mut R,
+  sealing_key: &[u8; 32],
+  db: &impl Iterate,
+) -> Vec<u8> {
+  let mut plaintext = Zeroizing::new(vec![]);
+  db.iterate(|key, value| {
+    if !excluded(key) {
+      write_vec(&mut plaintext, key);
+      write_vec(&mut plaintext, value);
+    }
+  });
+  encrypt(rng, sealing_key, REPLICA_LABEL, &plaintext)
+}
+
+/// Unseal a replica sealed with `seal_replica` into the database, returning None if the sealing
+/// key is incorrect.
+pub fn unseal_replica(txn: &mut impl DbTxn, sealing_key: &[u8; 32], sealed: &[u8]) -> Option<()> {
+  let plaintext = decrypt(sealing_key, REPLICA_LABEL, sealed)?;
+  let mut entries = vec![];
+  let mut reader: &[u8] = plaintext.as_ref();
+  while !reader.is_empty() {
+    let key = read_vec(&mut reader)?;
+    let value = read_vec(&mut reader)?;
+    if excluded(&key) {
+      None?;
+    }
+    entries.push((key, value));
+  }
+  // Only write the entries once the entire replica has been read
+  for (key, value) in entries {
+    txn.put(key.as_slice(), value.as_slice());
+  }
+  Some(())
+}
+
+/// A commitment to a set of sealed keys, as signed for by a failover authorization.
+pub fn sealed_commitment(sealed: &[u8]) -> [u8; 32] {
+  let mut transcript = RecommendedTranscript::new(b"Serai Processor Sealed Keys Commitment");
+  transcript.append_message(b"sealed", sealed);
+  let mut res = [0; 32];
+  res.copy_from_slice(&transcript.challenge(b"commitment")[.. 32]);
+  res
+}
+
+/// An authorization, signed by a threshold of the validator's operational keys, for a standby to
+/// take over as the primary.
+///
+/// This contains the sealing key and MUST only be delivered over a confidential channel.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct FailoverAuthorization {
+  pub network: ExternalNetworkId,
+  /// The epoch of this authorization. Each failover MUST use a higher epoch than the last.
+  pub epoch: u32,
+  /// The commitment to the sealed keys being unsealed.
+  pub sealed_keys: [u8; 32],
+  /// The key the keys were sealed with. This isn't signed over, as it's checked on unsealing.
+  pub sealing_key: Zeroizing<[u8; 32]>,
+  /// The signatures from the operational keys, labelled by their index.
+  pub signatures: Vec<(u16, SchnorrSignature<Ristretto>)>,
+}
+
+impl FailoverAuthorization {
+  fn challenge(
+    &self,
+    operator: <Ristretto as Ciphersuite>::G,
+    nonce: <Ristretto as Ciphersuite>::G,
+  ) -> <Ristretto as Ciphersuite>::F {
+    let mut msg = self.network.encode();
+    msg.extend(self.epoch.to_le_bytes());
+    msg.extend(self.sealed_keys);
+    msg.extend(operator.to_bytes());
+    msg.extend(nonce.to_bytes());
+    Ristretto::hash_to_F(AUTHORIZATION_DST, &msg)
+  }
+
+  /// Sign this authorization with an operational key.
+  pub fn sign<R: RngCore + CryptoRng>(
+    &mut self,
+    rng: &mut R,
+    index: u16,
+    key: &Zeroizing<<Ristretto as Ciphersuite>::F>,
+  ) {
+    let nonce = Zeroizing::new(<Ristretto as Ciphersuite>::random_nonzero_F(rng));
+    let challenge =
+      self.challenge(Ristretto::generator() * key.deref(), Ristretto::generator() * nonce.deref());
+    self.signatures.push((index, SchnorrSignature::sign(key, nonce, challenge)));
+  }
+
+  /// Verify this authorization was signed by `threshold` of the specified operators.
+  #[must_use]
+  pub fn verify(&self, operators: &[<Ristretto as Ciphersuite>::G], threshold: usize) -> bool {
+    let mut signed = vec![];
+    for (index, signature) in &self.signatures {
+      let Some(operator) = operators.get(usize::from(*index)) else { return false };
+      if signed.contains(index) ||
+        (!signature.verify(*operator, self.challenge(*operator, signature.R)))
+      {
+        return false;
+      }
+      signed.push(*index);
+    }
+    signed.len() >= threshold
+  }
+
+  pub fn read<R: Read>(reader: &mut R) -> io::Result<Self> {
+    let mut network = [0; 1];
+    reader.read_exact(&mut network)?;
+    let network = ExternalNetworkId::decode(&mut network.as_ref())
+      .map_err(|_| io::Error::other("unrecognized network"))?;
+
+    let mut epoch = [0; 4];
+    reader.read_exact(&mut epoch)?;
+    let epoch = u32::from_le_bytes(epoch);
+
+    let mut sealed_keys = [0; 32];
+    reader.read_exact(&mut sealed_keys)?;
+    let mut sealing_key = Zeroizing::new([0; 32]);
+    reader.read_exact(sealing_key.as_mut())?;
+
+    let mut signatures_len = [0; 2];
+    reader.read_exact(&mut signatures_len)?;
+    let mut signatures = vec![];
+    for _ in 0 .. u16::from_le_bytes(signatures_len) {
+      let mut index = [0; 2];
+      reader.read_exact(&mut index)?;
+      signatures.push((u16::from_le_bytes(index), SchnorrSignature::<Ristretto>::read(reader)?));
+    }
+
+    Ok(FailoverAuthorization { network, epoch, sealed_keys, sealing_key, signatures })
+  }
+
+  pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+    writer.write_all(&self.network.encode())?;
+    writer.write_all(&self.epoch.to_le_bytes())?;
+    writer.write_all(&self.sealed_keys)?;
+    writer.write_all(self.sealing_key.as_ref())?;
+    writer.write_all(&u16::try_from(self.signatures.len()).unwrap().to_le_bytes())?;
+    for (index, signature) in &self.signatures {
+      writer.write_all(&index.to_le_bytes())?;
+      signature.write(writer)?;
+    }
+    Ok(())
+  }
+
+  pub fn serialize(&self) -> Vec<u8> {
+    let mut buf = vec![];
+    self.write(&mut buf).unwrap();
+    buf
+  }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Role {
+  Primary,
+  Standby,
+}
+
+pub struct Failover {
+  network: ExternalNetworkId,
+  role: Role,
+  operators: Vec<<Ristretto as Ciphersuite>::G>,
+  threshold: usize,
+  // The epoch of the authorization this instance was configured as operating under
+  epoch: u32,
+  // Where failover authorizations are delivered to, as hex
+  authorization_path: PathBuf,
+  // Where the sealed keys are exported to/imported from
+  sealed_keys_path: PathBuf,
+  // Where the sealed replica of the database is exported to/imported from
+  replica_path: PathBuf,
+  // The key to seal our keys with, if we're exporting them for a standby
+  sealing_key: Option<Zeroizing<[u8; 32]>>,
+  // The amount of sessions we've exported the keys for
+  exported: usize,
+}
+
+impl Failover {
+  /// Load the failover configuration from the environment, if one is specified.
+  pub fn from_env(network: ExternalNetworkId) -> Option<Failover> {
+    let role = match env::var("PROCESSOR_ROLE").as_deref() {
+      None | Some("primary") => Role::Primary,
+      Some("standby") => Role::Standby,
+      Some(_) => panic!("unrecognized processor role"),
+    };
+
+    let Some(operators) = env::var("FAILOVER_OPERATORS") else {
+      assert_eq!(role, Role::Primary, "standby processor didn't have failover operators specified");
+      return None;
+    };
+    let operators = operators
+      .split(',')
+      .map(|key| {
+        let key = hex::decode(key.trim()).expect("failover operator wasn't hex-formatted");
+        Ristretto::read_G::<&[u8]>(&mut key.as_ref()).expect("failover operator wasn't a valid key")
+      })
+      .collect::<Vec<_>>();
+    let threshold = env::var("FAILOVER_THRESHOLD")
+      .expect("failover threshold wasn't specified")
+      .parse::<usize>()
+      .expect("failover threshold wasn't a number");
+    assert!(
+      (threshold != 0) && (threshold <= operators.len()),
+      "failover threshold wasn't in the range of operators"
+    );
+
+    // This is required, instead of defaulting to 0, so an instance which has yet to receive any
+    // authorization is never considered authorized under every epoch it sees
+    let epoch = env::var("FAILOVER_EPOCH")
+      .expect("failover epoch wasn't specified")
+      .parse::<u32>()
+      .expect("failover epoch wasn't a number");
+
+    let authorization_path = env::var("FAILOVER_AUTHORIZATION_PATH")
+      .expect("failover authorization path wasn't specified")
+      .into();
+    let sealed_keys_path =
+      env::var("SEALED_KEYS_PATH").expect("sealed keys path wasn't specified").into();
+    let replica_path =
+      env::var("STANDBY_REPLICA_PATH").expect("standby replica path wasn't specified").into();
+
+    let sealing_key = env::var("STANDBY_SEALING_KEY").map(|key| {
+      assert_eq!(role, Role::Primary, "standby was given the sealing key");
+      let key = Zeroizing::new(hex::decode(key).expect("sealing key wasn't hex-formatted"));
+      let mut res = Zeroizing::new([0; 32]);
+      assert_eq!(key.len(), 32, "sealing key wasn't 32 bytes");
+      res.copy_from_slice(&key);
+      res
+    });
+
+    Some(Failover {
+      network,
+      role,
+      operators,
+      threshold,
+      epoch,
+      authorization_path,
+      sealed_keys_path,
+      replica_path,
+      sealing_key,
+      exported: 0,
+    })
+  }
+
+  fn read_authorization(&self, getter: &impl Get) -> Option<FailoverAuthorization> {
+    let authorization = std::fs::read_to_string(&self.authorization_path).ok()?;
+    let Ok(authorization) = hex::decode(authorization.trim()) else {
+      warn!("failover authorization wasn't hex-formatted");
+      return None;
+    };
+    let Ok(authorization) = FailoverAuthorization::read::<&[u8]>(&mut authorization.as_ref())
+    else {
+      warn!("failover authorization was malformed");
+      return None;
+    };
+
+    // Ignore authorizations for the epoch we're operating under, or prior epochs
+    if authorization.epoch <= current_epoch(getter, self.epoch) {
+      return None;
+    }
+
+    if authorization.network != self.network {
+      warn!("failover authorization was for {:?}", authorization.network);
+      return None;
+    }
+    if !authorization.verify(&self.operators, self.threshold) {
+      warn!("failover authorization (epoch {}) didn't verify", authorization.epoch);
+      return None;
+    }
+    Some(authorization)
+  }
+
+  /// Wait for a new, valid failover authorization.
+  async fn next_authorization(&self, getter: &impl Get) -> FailoverAuthorization {
+    loop {
+      if let Some(authorization) = self.read_authorization(getter) {
+        return authorization;
+      }
+      sleep(Duration::from_secs(5)).await;
+    }
+  }
+
+  /// Prepare this processor to run, unsealing the keys if this is a standby and waiting for a
+  /// failover authorization to do so.
+  pub async fn boot<N: Network, D: Db>(&mut self, db: &mut D) {
+    if let Some(epoch) = SealedDb::get(db) {
+      panic!("this processor was sealed by the failover authorization for epoch {epoch}");
+    }
+
+    // If we're a standby which has yet to take over, wait until we're authorized to
+    if (self.role == Role::Standby) && FailoverEpochDb::get(db).is_none() {
+      info!("waiting for a failover authorization to unseal our keys");
+      loop {
+        let authorization = self.next_authorization(db).await;
+        let (Ok(sealed), Ok(replica)) =
+          (std::fs::read(&self.sealed_keys_path), std::fs::read(&self.replica_path))
+        else {
+          warn!("couldn't read the sealed keys and replica to unseal");
+          sleep(Duration::from_secs(5)).await;
+          continue;
+        };
+        if sealed_commitment(&sealed) != authorization.sealed_keys {
+          warn!("failover authorization wasn't for the sealed keys we have");
+          sleep(Duration::from_secs(5)).await;
+          continue;
+        }
+        let Some(keys) = unseal(&authorization.sealing_key, &sealed) else {
+          warn!("failover authorization had an invalid sealing key");
+          sleep(Duration::from_secs(5)).await;
+          continue;
+        };
+
+        let mut txn = db.txn();
+        if unseal_replica(&mut txn, &authorization.sealing_key, &replica).is_none() {
+          warn!("couldn't unseal the replica of the primary's database");
+          drop(txn);
+          sleep(Duration::from_secs(5)).await;
+          continue;
+        }
+        for (session, network_key, keys) in &keys {
+          KeysDb::import_keys::<N>(&mut txn, *session, network_key, keys);
+        }
+        FailoverEpochDb::set(&mut txn, &authorization.epoch);
+        txn.commit();
+        info!("unsealed keys for failover epoch {}", authorization.epoch);
+        break;
+      }
+    }
+
+    self.export_keys(db);
+  }
+
+  /// Export our keys, sealed, if we have new keys to export.
+  pub fn export_keys(&mut self, getter: &impl Get) {
+    let Some(sealing_key) = self.sealing_key.as_ref() else { return };
+    let keys = KeysDb::confirmed_keys(getter);
+    if keys.len() == self.exported {
+      return;
+    }
+    let sealed = seal(&mut OsRng, sealing_key, &keys);
+    std::fs::write(&self.sealed_keys_path, &sealed).expect("couldn't write the sealed keys");
+    info!(
+      "exported sealed keys for {} sessions, with commitment {}",
+      keys.len(),
+      hex::encode(sealed_commitment(&sealed))
+    );
+    self.exported = keys.len();
+  }
+
+  /// The replicator of our database, if we're exporting our keys for a standby.
+  pub fn replicator(&self) -> Option<Replicator> {
+    Some(Replicator {
+      sealing_key: self.sealing_key.clone()?,
+      replica_path: self.replica_path.clone(),
+    })
+  }
+
+  /// Seal this processor, removing its keys, as another instance has been authorized to
+  /// take over.
+  pub fn seal<N: Network>(txn: &mut impl DbTxn, authorization: &FailoverAuthorization) {
+    for (session, _, _) in KeysDb::confirmed_keys(txn) {
+      KeysDb::remove_keys::<N>(txn, session);
+    }
+    SealedDb::set(txn, &authorization.epoch);
+    warn!(
+      "sealed this processor due to the failover authorization for epoch {}",
+      authorization.epoch
+    );
+  }
+}
+
+/// Writes sealed replicas of the primary's database for the standby.
+pub struct Replicator {
+  sealing_key: Zeroizing<[u8; 32]>,
+  replica_path: PathBuf,
+}
+
+impl Replicator {
+  /// Write a sealed replica of the database every `REPLICATION_INTERVAL`.
+  ///
+  /// This is only supported with databases which may be iterated over.
+  pub async fn replicate_task<D: Iterate>(self, db: D) {
+    // The replica is written to a temporary file and then moved, so the standby never reads a
+    // partially written replica
+    let mut temporary = self.replica_path.clone().into_os_string();
+    temporary.push(".tmp");
+    loop {
+      // Don't overwrite the replica once we've been sealed, as the standby may have taken over
+      if SealedDb::get(&db).is_some() {
+        return;
+      }
+      let replica = seal_replica(&mut OsRng, &self.sealing_key, &db);
+      if let Err(e) = std::fs::write(&temporary, replica)
+        .and_then(|()| std::fs::rename(&temporary, &self.replica_path))
+      {
+        warn!("couldn't write the replica of our database: {e}");
+      }
+      sleep(REPLICATION_INTERVAL).await;
+    }
+  }
+}
+
+/// The epoch of the authorization we're operating under.
+///
+/// This is the epoch of the authorization we took over under, if we have, or the epoch we were
+/// configured with.
+pub fn current_epoch(getter: &impl Get, configured: u32) -> u32 {
+  FailoverEpochDb::get(getter).unwrap_or(configured).max(configured)
+}
+
+/// Wait for a failover authorization, if failover is configured.
+pub async fn next_failover_authorization(
+  failover: Option<&Failover>,
+  getter: &impl Get,
+) -> FailoverAuthorization {
+  match failover {
+    Some(failover) => failover.next_authorization(getter).await,
+    None => core::future::pending().await,
+  }
+}
@@ -0,0 +1,72 @@
+use zeroize::Zeroizing;
+
+use rand_core::OsRng;
+
+use ciphersuite::{Ciphersuite, Ristretto};
+
+use serai_client::primitives::{ExternalNetworkId, ExternalCoin, Amount, ExternalBalance};
+
+use crate::attestation::*;
+
+#[test]
+fn attestation() {
+  let key = Zeroizing::new(<Ristretto as Ciphersuite>::random_nonzero_F(&mut OsRng));
+  let payouts = vec![
+    Payout {
+      block: 5,
+      index: 0,
+      extrinsic: [4; 32],
+      address: vec![1; 20],
+      data: None,
+      balance: ExternalBalance { coin: ExternalCoin::Bitcoin, amount: Amount(30_000) },
+      fee: 750,
+    },
+    Payout {
+      block: 7,
+      index: 2,
+      extrinsic: [5; 32],
+      address: vec![2; 32],
+      data: Some(vec![3; 10]),
+      balance: ExternalBalance { coin: ExternalCoin::Bitcoin, amount: Amount(10_000) },
+      fee: 250,
+    },
+  ];
+  let attestation = Attestation::sign(
+    &mut OsRng,
+    &key,
+    ExternalNetworkId::Bitcoin,
+    [0xaa; 32],
+    vec![0xbb; 32],
+    1000,
+    payouts,
+  );
+  assert_eq!(attestation.signer, Ristretto::generator() * *key);
+  assert!(attestation.verify());
+
+  // The JSON export should be verifiable on its own
+  let json = serde_json::to_string(&attestation.to_json()).unwrap();
+  let decoded = Attestation::from_json(&serde_json::from_str(&json).unwrap()).unwrap();
+  assert_eq!(decoded, attestation);
+  assert!(decoded.verify());
+
+  // Modifying any part of the bundle should invalidate the signature
+  let mut modified = attestation.clone();
+  modified.transaction[0] ^= 1;
+  assert!(!modified.verify());
+
+  let mut modified = attestation.clone();
+  modified.payouts[1].fee += 1;
+  assert!(!modified.verify());
+
+  let mut modified = attestation.clone();
+  modified.payouts[0].index = 1;
+  assert!(!modified.verify());
+
+  let mut modified = attestation.clone();
+  modified.payouts[1].extrinsic[0] ^= 1;
+  assert!(!modified.verify());
+
+  let mut modified = attestation;
+  modified.signer = Ristretto::generator();
+  assert!(!modified.verify());
+}
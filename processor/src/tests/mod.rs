@@ -12,8 +12,18 @@ mod batch_signer;
 
 mod wallet;
 
+mod scheduler;
+
 mod addresses;
 
+mod standby;
+
+mod attestation;
+
+mod reconciliation;
+
+mod replay;
+
 // Effective Once
 static INIT_LOGGER_CELL: OnceLock<()> = OnceLock::new();
 fn init_logger() {
@@ -0,0 +1,65 @@
+use std::collections::HashMap;
+
+use serai_client::{
+  validator_sets::primitives::{Session, KeyPair},
+  Public,
+};
+
+use crate::reconciliation::*;
+
+fn key_pair(network_key: &[u8]) -> KeyPair {
+  KeyPair(Public([0; 32]), network_key.to_vec().try_into().unwrap())
+}
+
+#[test]
+fn reconciliation_report() {
+  let local = LocalState {
+    next_batch: 3,
+    network_keys: vec![(Session(0), vec![1; 33]), (Session(1), vec![2; 33])],
+    signing_plans: 1,
+    unsignable_plans: vec![],
+    pending_burns: Some(2),
+  };
+  let on_chain =
+    OnChainState { last_batch: Some(1), keys: HashMap::from([(Session(1), key_pair(&[2; 33]))]) };
+
+  // A consistent database should only have informational findings
+  let report = ReconciliationReport::new(&on_chain, &local);
+  assert!(!report.critical());
+  assert!(report.findings.iter().all(|finding| finding.severity == Severity::Info));
+  // An informational change shouldn't change the report's ID
+  let id = report.id();
+  let mut more_burns = local.clone();
+  more_burns.pending_burns = Some(5);
+  assert_eq!(ReconciliationReport::new(&on_chain, &more_burns).id(), id);
+
+  // Serai having executed a batch we have yet to create is critical
+  let mut ahead = on_chain.clone();
+  ahead.last_batch = Some(3);
+  let report = ReconciliationReport::new(&ahead, &local);
+  assert!(report.critical());
+  assert_eq!(
+    report
+      .findings
+      .iter()
+      .filter(|finding| finding.severity == Severity::Critical)
+      .map(|finding| finding.check)
+      .collect::<Vec<_>>(),
+    vec!["batches"]
+  );
+  assert!(report.id() != id);
+
+  // As is a key mismatch
+  let mut mismatched = on_chain.clone();
+  mismatched.keys.insert(Session(0), key_pair(&[3; 33]));
+  let report = ReconciliationReport::new(&mismatched, &local);
+  assert!(report.critical());
+  let mismatch_id = report.id();
+
+  // And signing with keys we don't have
+  let mut unsignable = local.clone();
+  unsignable.unsignable_plans.push([0xff; 32]);
+  let report = ReconciliationReport::new(&on_chain, &unsignable);
+  assert!(report.critical());
+  assert!(report.id() != mismatch_id);
+}
@@ -0,0 +1,67 @@
+use serai_db::{DbTxn, Db, MemDb};
+
+use serai_client::{
+  primitives::{ExternalNetworkId, ExternalCoin, Amount, ExternalBalance, BlockHash, SeraiAddress},
+  in_instructions::primitives::{InInstruction, InInstructionWithBalance},
+};
+
+use crate::multisigs::{
+  build_batches,
+  db::{InstructionWithOrigin, ForwardedOutputDb, DelayedOutputDb},
+};
+
+fn instruction(origin: u8, amount: u64) -> InstructionWithOrigin {
+  InstructionWithOrigin {
+    origin: vec![origin; 32],
+    instruction: InInstructionWithBalance {
+      instruction: InInstruction::Transfer(SeraiAddress([origin; 32])),
+      balance: ExternalBalance { coin: ExternalCoin::Bitcoin, amount: Amount(amount) },
+    },
+  }
+}
+
+fn reported(txn: &mut impl DbTxn, instructions: Vec<InstructionWithOrigin>) -> usize {
+  build_batches(txn, ExternalNetworkId::Bitcoin, BlockHash([0; 32]), instructions)
+    .iter()
+    .map(|batch| batch.instructions.len())
+    .sum()
+}
+
+#[test]
+fn instruction_replay_across_rotation() {
+  let mut db = MemDb::new();
+  let mut txn = db.txn();
+
+  // An output to the existing multisig is reported
+  assert_eq!(reported(&mut txn, vec![instruction(1, 100)]), 1);
+  // If it's somehow scanned again, it isn't reported again
+  assert_eq!(reported(&mut txn, vec![instruction(1, 100)]), 0);
+
+  // Two outputs with identical amounts are forwarded to the new multisig
+  ForwardedOutputDb::save_forwarded_output(&mut txn, &instruction(2, 50));
+  ForwardedOutputDb::save_forwarded_output(&mut txn, &instruction(3, 50));
+  let balance = instruction(2, 50).instruction.balance;
+  let first = ForwardedOutputDb::take_forwarded_output(&mut txn, balance).unwrap();
+  let second = ForwardedOutputDb::take_forwarded_output(&mut txn, balance).unwrap();
+  // Each forwarded output's instruction should be taken exactly once
+  assert_eq!(first, instruction(2, 50));
+  assert_eq!(second, instruction(3, 50));
+  assert!(ForwardedOutputDb::take_forwarded_output(&mut txn, balance).is_none());
+  assert_eq!(reported(&mut txn, vec![first.clone(), second]), 2);
+  // Re-receiving an already forwarded output doesn't re-report its instruction
+  assert_eq!(reported(&mut txn, vec![first]), 0);
+
+  // An output to the new multisig is delayed until the new multisig is used
+  DelayedOutputDb::save_delayed_output(&mut txn, &instruction(4, 25));
+  let delayed = DelayedOutputDb::take_delayed_outputs(&mut txn);
+  assert_eq!(delayed, vec![instruction(4, 25)]);
+  assert!(DelayedOutputDb::take_delayed_outputs(&mut txn).is_empty());
+  // Duplicates within a single block are also only reported once
+  assert_eq!(reported(&mut txn, vec![delayed[0].clone(), delayed[0].clone()]), 1);
+
+  // Batch IDs should still increment as normal, even for batches with skipped instructions
+  let batches = build_batches(&mut txn, ExternalNetworkId::Bitcoin, BlockHash([0; 32]), vec![]);
+  assert_eq!(batches.len(), 1);
+  assert_eq!(batches[0].id, 5);
+  assert!(batches[0].instructions.is_empty());
+}
@@ -0,0 +1,52 @@
+use serai_client::primitives::{ExternalCoin, Amount, ExternalBalance};
+
+use crate::multisigs::scheduler::smart_contract::{GasConfig, GasBudget};
+
+const GAS: GasConfig = GasConfig { per_token_payment: 10, reserve_minimum: 100 };
+
+fn eth(amount: u64) -> ExternalBalance {
+  ExternalBalance { coin: ExternalCoin::Ether, amount: Amount(amount) }
+}
+
+fn dai(amount: u64) -> ExternalBalance {
+  ExternalBalance { coin: ExternalCoin::Dai, amount: Amount(amount) }
+}
+
+#[test]
+fn gas_budget() {
+  // Nothing to budget
+  assert_eq!(
+    GAS.budget(ExternalCoin::Ether, 5, &[]),
+    GasBudget { scheduled: vec![], deferred: vec![], balance: 5 }
+  );
+
+  // Payments of the fee coin which can't be afforded are deferred, as are all after them
+  assert_eq!(
+    GAS.budget(ExternalCoin::Ether, 100, &[eth(60), eth(50), eth(30)]),
+    GasBudget { scheduled: vec![0], deferred: vec![1, 2], balance: 40 }
+  );
+
+  // Token payments are deferred once their gas can't be afforded
+  assert_eq!(
+    GAS.budget(ExternalCoin::Ether, 25, &[dai(1_000), dai(1_000), dai(1)]),
+    GasBudget { scheduled: vec![0, 1], deferred: vec![2], balance: 5 }
+  );
+
+  // Deferring payments of the fee coin doesn't defer token payments, and vice versa
+  assert_eq!(
+    GAS.budget(ExternalCoin::Ether, 15, &[eth(20), dai(1)]),
+    GasBudget { scheduled: vec![1], deferred: vec![0], balance: 5 }
+  );
+  assert_eq!(
+    GAS.budget(ExternalCoin::Ether, 15, &[dai(1), dai(1), eth(5)]),
+    GasBudget { scheduled: vec![0, 2], deferred: vec![1], balance: 0 }
+  );
+}
+
+#[test]
+fn gas_replenishment() {
+  assert_eq!(GAS.replenishment(0), Some(100));
+  assert_eq!(GAS.replenishment(40), Some(60));
+  assert_eq!(GAS.replenishment(100), None);
+  assert_eq!(GAS.replenishment(200), None);
+}
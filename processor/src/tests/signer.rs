@@ -193,6 +193,7 @@ pub async fn test_signer<N: Network>(
         },
         amount: Amount(amount),
       },
+      burn: None,
     }];
     let mut plans = scheduler.schedule::<MemDb>(&mut txn, outputs.clone(), payments, key, false);
     assert_eq!(plans.len(), 1);
@@ -0,0 +1,136 @@
+use zeroize::Zeroizing;
+
+use rand_core::{RngCore, OsRng};
+
+use ciphersuite::{Ciphersuite, Ristretto};
+
+use serai_client::{primitives::ExternalNetworkId, validator_sets::primitives::Session};
+
+use crate::{Get, DbTxn, Db, MemDb, key_gen::KeysDb, standby::*};
+
+#[test]
+fn seal_unseal() {
+  let mut sealing_key = Zeroizing::new([0; 32]);
+  OsRng.fill_bytes(sealing_key.as_mut());
+
+  let keys = vec![
+    (Session(0), vec![1; 33], Zeroizing::new(vec![2; 100])),
+    (Session(3), vec![4; 32], Zeroizing::new(vec![5; 200])),
+  ];
+  let sealed = seal(&mut OsRng, &sealing_key, &keys);
+  assert_eq!(unseal(&sealing_key, &sealed).unwrap(), keys);
+
+  // The wrong key shouldn't unseal the keys
+  let mut wrong_key = *sealing_key;
+  wrong_key[0] ^= 1;
+  assert!(unseal(&wrong_key, &sealed).is_none());
+
+  // Nor should a modified ciphertext
+  let mut modified = sealed.clone();
+  *modified.last_mut().unwrap() ^= 1;
+  assert!(unseal(&sealing_key, &modified).is_none());
+
+  // Nor should a replica, as sealed data is labelled with what it is
+  let replica = seal_replica(&mut OsRng, &sealing_key, &MemDb::new());
+  assert!(unseal(&sealing_key, &replica).is_none());
+  assert!(unseal_replica(&mut MemDb::new().txn(), &sealing_key, &sealed).is_none());
+}
+
+#[test]
+fn replica() {
+  let mut sealing_key = Zeroizing::new([0; 32]);
+  OsRng.fill_bytes(sealing_key.as_mut());
+
+  let mut primary = MemDb::new();
+  let share = KeysDb::key([1; 32].as_ref());
+  let epoch = FailoverEpochDb::key();
+  let mut txn = primary.txn();
+  txn.put(b"state", b"value");
+  txn.put(b"more state", [0xff; 1000]);
+  txn.put(&share, b"key share");
+  FailoverEpochDb::set(&mut txn, &0);
+  txn.commit();
+
+  let replica = seal_replica(&mut OsRng, &sealing_key, &primary);
+
+  // The wrong key shouldn't unseal the replica, leaving the database untouched
+  let mut standby = MemDb::new();
+  let mut wrong_key = *sealing_key;
+  wrong_key[0] ^= 1;
+  let mut txn = standby.txn();
+  assert!(unseal_replica(&mut txn, &wrong_key, &replica).is_none());
+  let mut truncated = replica.clone();
+  truncated.pop();
+  assert!(unseal_replica(&mut txn, &sealing_key, &truncated).is_none());
+  txn.commit();
+  assert!(standby.get(b"state").is_none());
+
+  let mut txn = standby.txn();
+  unseal_replica(&mut txn, &sealing_key, &replica).unwrap();
+  txn.commit();
+  assert_eq!(standby.get(b"state").unwrap(), b"value");
+  assert_eq!(standby.get(b"more state").unwrap(), [0xff; 1000]);
+  // The key shares, and the primary's own failover state, shouldn't be replicated
+  assert!(standby.get(&share).is_none());
+  assert!(standby.get(&epoch).is_none());
+}
+
+#[test]
+fn epochs() {
+  // Without having taken over, we operate under the configured epoch
+  let mut db = MemDb::new();
+  assert_eq!(current_epoch(&db, 0), 0);
+  assert_eq!(current_epoch(&db, 2), 2);
+
+  // Once we've taken over, we operate under the epoch we took over under
+  let mut txn = db.txn();
+  FailoverEpochDb::set(&mut txn, &3);
+  txn.commit();
+  assert_eq!(current_epoch(&db, 0), 3);
+  // Unless we were since configured with a later epoch
+  assert_eq!(current_epoch(&db, 4), 4);
+}
+
+#[test]
+fn failover_authorization() {
+  let keys = (0 .. 3)
+    .map(|_| Zeroizing::new(<Ristretto as Ciphersuite>::random_nonzero_F(&mut OsRng)))
+    .collect::<Vec<_>>();
+  let operators = keys.iter().map(|key| Ristretto::generator() * **key).collect::<Vec<_>>();
+
+  let mut authorization = FailoverAuthorization {
+    network: ExternalNetworkId::Bitcoin,
+    epoch: 1,
+    sealed_keys: sealed_commitment(&[0xff; 96]),
+    sealing_key: Zeroizing::new([0xaa; 32]),
+    signatures: vec![],
+  };
+
+  authorization.sign(&mut OsRng, 0, &keys[0]);
+  assert!(authorization.verify(&operators, 1));
+  assert!(!authorization.verify(&operators, 2));
+
+  // Signing multiple times with the same key shouldn't count towards the threshold
+  let mut duplicated = authorization.clone();
+  duplicated.sign(&mut OsRng, 0, &keys[0]);
+  assert!(!duplicated.verify(&operators, 2));
+
+  // A signature claiming to be from the wrong operator shouldn't verify
+  let mut mislabelled = authorization.clone();
+  mislabelled.sign(&mut OsRng, 1, &keys[2]);
+  assert!(!mislabelled.verify(&operators, 2));
+
+  authorization.sign(&mut OsRng, 2, &keys[2]);
+  assert!(authorization.verify(&operators, 2));
+
+  // Changing the epoch should invalidate the signatures
+  let mut replayed = authorization.clone();
+  replayed.epoch += 1;
+  assert!(!replayed.verify(&operators, 1));
+
+  let serialized = authorization.serialize();
+  assert_eq!(
+    FailoverAuthorization::read::<&[u8]>(&mut serialized.as_ref()).unwrap(),
+    authorization
+  );
+}
@@ -97,6 +97,7 @@ pub async fn test_wallet<N: Network>(
         },
         amount: Amount(amount),
       },
+      burn: None,
     }],
     key,
     false,
@@ -123,7 +124,8 @@ pub async fn test_wallet<N: Network>(
           ExternalNetworkId::Monero => ExternalCoin::Monero,
         },
         amount: Amount(amount),
-      }
+      },
+      burn: None,
     }]
   );
   assert_eq!(plans[0].change, N::change_address(key));
@@ -3,7 +3,14 @@ use sp_consensus_babe::EquivocationProof;
 use serai_primitives::{Header, SeraiAddress};
 
 #[derive(Clone, PartialEq, Eq, Debug, scale::Encode, scale::Decode, scale_info::TypeInfo)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(all(feature = "std", feature = "serde"), derive(serde::Deserialize))]
 pub struct ReportEquivocation {
+  #[cfg_attr(feature = "serde", serde(serialize_with = "crate::scale_hex::serialize"))]
+  #[cfg_attr(
+    all(feature = "std", feature = "serde"),
+    serde(deserialize_with = "crate::scale_hex::deserialize")
+  )]
   pub equivocation_proof: alloc::boxed::Box<EquivocationProof<Header>>,
   pub key_owner_proof: SeraiAddress,
 }
@@ -11,6 +18,8 @@ pub struct ReportEquivocation {
 // We could define a Babe Config here and use the literal pallet_babe::Call
 // The disadvantage to this would be the complexity and presence of junk fields such as `__Ignore`
 #[derive(Clone, PartialEq, Eq, Debug, scale::Encode, scale::Decode, scale_info::TypeInfo)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(all(feature = "std", feature = "serde"), derive(serde::Deserialize))]
 pub enum Call {
   report_equivocation(ReportEquivocation),
   report_equivocation_unsigned(ReportEquivocation),
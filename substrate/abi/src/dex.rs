@@ -38,6 +38,16 @@ pub enum Call {
   },
 }
 
+/// A request to sell some of an external coin for the SRI needed to pay a transaction's fee.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, scale::Encode, scale::Decode, scale_info::TypeInfo)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(all(feature = "std", feature = "serde"), derive(serde::Deserialize))]
+pub struct FeeConversion {
+  pub coin: ExternalCoin,
+  #[codec(compact)]
+  pub max_coin_in: SubstrateAmount,
+}
+
 #[derive(Clone, PartialEq, Eq, Debug, scale::Encode, scale::Decode, scale_info::TypeInfo)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize))]
 #[cfg_attr(all(feature = "std", feature = "serde"), derive(serde::Deserialize))]
@@ -73,3 +83,34 @@ pub enum Event {
     amount_out: SubstrateAmount,
   },
 }
+
+/// An error from the DEX pallet, as encoded within a `DispatchError::Module`.
+// This mirrors the pallet's `Error` enum, which must be kept in the same order
+#[derive(Clone, Copy, PartialEq, Eq, Debug, scale::Encode, scale::Decode, scale_info::TypeInfo)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(all(feature = "std", feature = "serde"), derive(serde::Deserialize))]
+pub enum Error {
+  EqualCoins,
+  PoolExists,
+  WrongDesiredAmount,
+  CoinAmountLessThanMinimum,
+  SriAmountLessThanMinimum,
+  ReserveLeftLessThanMinimum,
+  AmountOutTooHigh,
+  PoolNotFound,
+  Overflow,
+  CoinOneDepositDidNotMeetMinimum,
+  CoinTwoDepositDidNotMeetMinimum,
+  CoinOneWithdrawalDidNotMeetMinimum,
+  CoinTwoWithdrawalDidNotMeetMinimum,
+  OptimalAmountLessThanDesired,
+  InsufficientLiquidityMinted,
+  ZeroLiquidity,
+  ZeroAmount,
+  ProvidedMinimumNotSufficientForSwap,
+  ProvidedMaximumNotSufficientForSwap,
+  InvalidPath,
+  PathError,
+  NonUniquePath,
+  CorrespondenceError,
+}
@@ -3,12 +3,21 @@ use sp_consensus_grandpa::EquivocationProof;
 use serai_primitives::{BlockNumber, SeraiAddress};
 
 #[derive(Clone, PartialEq, Eq, Debug, scale::Encode, scale::Decode, scale_info::TypeInfo)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(all(feature = "std", feature = "serde"), derive(serde::Deserialize))]
 pub struct ReportEquivocation {
+  #[cfg_attr(feature = "serde", serde(serialize_with = "crate::scale_hex::serialize"))]
+  #[cfg_attr(
+    all(feature = "std", feature = "serde"),
+    serde(deserialize_with = "crate::scale_hex::deserialize")
+  )]
   pub equivocation_proof: alloc::boxed::Box<EquivocationProof<[u8; 32], BlockNumber>>,
   pub key_owner_proof: SeraiAddress,
 }
 
 #[derive(Clone, PartialEq, Eq, Debug, scale::Encode, scale::Decode, scale_info::TypeInfo)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(all(feature = "std", feature = "serde"), derive(serde::Deserialize))]
 pub enum Call {
   report_equivocation(ReportEquivocation),
   report_equivocation_unsigned(ReportEquivocation),
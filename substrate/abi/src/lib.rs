@@ -3,6 +3,14 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 #![allow(non_camel_case_types)]
 
+//! The ABI of the Serai runtime.
+//!
+//! With the `serde` feature, calls, events, and transactions may be serialized with `serde`. As
+//! JSON, enums are externally tagged by the name of their variant, as `{ "Variant": .. }` (or
+//! simply `"Variant"` for variants without fields), and fields are named as they are here.
+//! Transactions, and the equivocation proofs within calls to report equivocations, are
+//! represented as the hex encoding of their SCALE encoding, prefixed by `0x`.
+
 extern crate alloc;
 
 pub use serai_primitives as primitives;
@@ -29,9 +37,40 @@ pub mod signals;
 pub mod babe;
 pub mod grandpa;
 
+pub mod utility;
+
 pub mod tx;
 
+// Serialize a value as the hex encoding of its SCALE encoding, for values without their own
+// `serde` representation
+#[cfg(feature = "serde")]
+pub(crate) mod scale_hex {
+  use core::fmt::Write;
+
+  pub(crate) fn serialize<T: scale::Encode, S: serde::Serializer>(
+    value: &T,
+    serializer: S,
+  ) -> Result<S::Ok, S::Error> {
+    let mut hex = alloc::string::String::from("0x");
+    for byte in value.encode() {
+      write!(hex, "{byte:02x}").unwrap();
+    }
+    serializer.serialize_str(&hex)
+  }
+
+  #[cfg(feature = "std")]
+  pub(crate) fn deserialize<'a, T: scale::Decode, D: serde::Deserializer<'a>>(
+    de: D,
+  ) -> Result<T, D::Error> {
+    let bytes = sp_core::bytes::deserialize(de)?;
+    T::decode(&mut bytes.as_slice())
+      .map_err(|e| serde::de::Error::custom(format!("invalid SCALE encoding: {e}")))
+  }
+}
+
 #[derive(Clone, PartialEq, Eq, Debug, scale::Encode, scale::Decode, scale_info::TypeInfo)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(all(feature = "std", feature = "serde"), derive(serde::Deserialize))]
 pub enum Call {
   Timestamp(timestamp::Call),
   Coins(coins::Call),
@@ -43,15 +82,20 @@ pub enum Call {
   Signals(signals::Call),
   Babe(babe::Call),
   Grandpa(grandpa::Call),
+  Utility(utility::Call),
 }
 
 // TODO: Remove this
 #[derive(Clone, PartialEq, Eq, Debug, scale::Encode, scale::Decode, scale_info::TypeInfo)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(all(feature = "std", feature = "serde"), derive(serde::Deserialize))]
 pub enum TransactionPaymentEvent {
   TransactionFeePaid { who: serai_primitives::SeraiAddress, actual_fee: u64, tip: u64 },
 }
 
 #[derive(Clone, PartialEq, Eq, Debug, scale::Encode, scale::Decode, scale_info::TypeInfo)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(all(feature = "std", feature = "serde"), derive(serde::Deserialize))]
 pub enum Event {
   System(system::Event),
   Timestamp,
@@ -67,6 +111,7 @@ pub enum Event {
   Signals(signals::Event),
   Babe,
   Grandpa(grandpa::Event),
+  Utility(utility::Event),
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug, scale::Encode, scale::Decode, scale_info::TypeInfo)]
@@ -76,6 +121,7 @@ pub struct Extra {
   pub era: sp_runtime::generic::Era,
   #[codec(compact)]
   pub nonce: u32,
+  pub fee_conversion: Option<dex::FeeConversion>,
   #[codec(compact)]
   pub tip: u64,
 }
@@ -3,11 +3,89 @@ use frame_support::dispatch::{DispatchInfo, DispatchError};
 use serai_primitives::SeraiAddress;
 
 #[derive(Clone, PartialEq, Eq, Debug, scale::Encode, scale::Decode, scale_info::TypeInfo)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(all(feature = "std", feature = "serde"), derive(serde::Deserialize))]
 pub enum Event {
-  ExtrinsicSuccess { dispatch_info: DispatchInfo },
-  ExtrinsicFailed { dispatch_error: DispatchError, dispatch_info: DispatchInfo },
+  ExtrinsicSuccess {
+    #[cfg_attr(feature = "serde", serde(with = "dispatch_info"))]
+    dispatch_info: DispatchInfo,
+  },
+  ExtrinsicFailed {
+    dispatch_error: DispatchError,
+    #[cfg_attr(feature = "serde", serde(with = "dispatch_info"))]
+    dispatch_info: DispatchInfo,
+  },
   CodeUpdated,
-  NewAccount { account: SeraiAddress },
-  KilledAccount { account: SeraiAddress },
-  Remarked { sender: SeraiAddress, hash: [u8; 32] },
+  NewAccount {
+    account: SeraiAddress,
+  },
+  KilledAccount {
+    account: SeraiAddress,
+  },
+  Remarked {
+    sender: SeraiAddress,
+    hash: [u8; 32],
+  },
+}
+
+// The `serde` representation of `DispatchInfo`, which doesn't have its own
+#[cfg(feature = "serde")]
+mod dispatch_info {
+  use serde::{Serialize, Serializer};
+  #[cfg(feature = "std")]
+  use serde::{Deserialize, Deserializer};
+
+  use frame_support::{
+    weights::Weight,
+    dispatch::{DispatchInfo, DispatchClass, Pays},
+  };
+
+  #[derive(Serialize)]
+  #[cfg_attr(feature = "std", derive(Deserialize))]
+  #[serde(rename_all = "snake_case")]
+  enum Class {
+    Normal,
+    Operational,
+    Mandatory,
+  }
+
+  #[derive(Serialize)]
+  #[cfg_attr(feature = "std", derive(Deserialize))]
+  struct Info {
+    ref_time: u64,
+    proof_size: u64,
+    class: Class,
+    pays_fee: bool,
+  }
+
+  pub(super) fn serialize<S: Serializer>(
+    info: &DispatchInfo,
+    serializer: S,
+  ) -> Result<S::Ok, S::Error> {
+    Info {
+      ref_time: info.weight.ref_time(),
+      proof_size: info.weight.proof_size(),
+      class: match info.class {
+        DispatchClass::Normal => Class::Normal,
+        DispatchClass::Operational => Class::Operational,
+        DispatchClass::Mandatory => Class::Mandatory,
+      },
+      pays_fee: info.pays_fee == Pays::Yes,
+    }
+    .serialize(serializer)
+  }
+
+  #[cfg(feature = "std")]
+  pub(super) fn deserialize<'a, D: Deserializer<'a>>(de: D) -> Result<DispatchInfo, D::Error> {
+    let info = Info::deserialize(de)?;
+    Ok(DispatchInfo {
+      weight: Weight::from_parts(info.ref_time, info.proof_size),
+      class: match info.class {
+        Class::Normal => DispatchClass::Normal,
+        Class::Operational => DispatchClass::Operational,
+        Class::Mandatory => DispatchClass::Mandatory,
+      },
+      pays_fee: if info.pays_fee { Pays::Yes } else { Pays::No },
+    })
+  }
 }
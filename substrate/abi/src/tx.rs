@@ -27,6 +27,10 @@ type TransactionEncodeAs<'a, Extra> =
   (&'a crate::Call, &'a Option<(SeraiAddress, Signature, Extra)>);
 type TransactionDecodeAs<Extra> = (crate::Call, Option<(SeraiAddress, Signature, Extra)>);
 
+// The maximum depth a transaction may be decoded with, bounding the recursion possible via batches
+// (mirroring the limit Substrate uses for extrinsics)
+const MAX_DECODE_DEPTH: u32 = 256;
+
 // We use our own Transaction struct, over UncheckedExtrinsic, for more control, a bit more
 // simplicity, and in order to be immune to https://github.com/paritytech/polkadot-sdk/issues/2947
 #[allow(private_bounds)]
@@ -50,6 +54,19 @@ impl<Call: 'static + TransactionMember + From<crate::Call>, Extra: 'static + Tra
   pub fn call(&self) -> &crate::Call {
     &self.call
   }
+
+  /// The signer of this transaction, if it's signed.
+  pub fn signer(&self) -> Option<SeraiAddress> {
+    self.signature.as_ref().map(|(signer, _, _)| *signer)
+  }
+
+  /// The hash of this transaction, as Substrate refers to extrinsics by.
+  ///
+  /// Unsigned transactions may share hashes, so this only uniquely identifies signed
+  /// transactions.
+  pub fn hash(&self) -> [u8; 32] {
+    sp_core::hashing::blake2_256(&self.encode())
+  }
 }
 
 impl<Call: 'static + TransactionMember + From<crate::Call>, Extra: 'static + TransactionMember>
@@ -64,7 +81,11 @@ impl<Call: 'static + TransactionMember + From<crate::Call>, Extra: 'static + Tra
   scale::Decode for Transaction<Call, Extra>
 {
   fn decode<I: scale::Input>(input: &mut I) -> Result<Self, scale::Error> {
-    let (call, signature) = TransactionDecodeAs::decode(input)?;
+    let (call, signature) =
+      <TransactionDecodeAs<Extra> as scale::DecodeLimit>::decode_with_depth_limit(
+        MAX_DECODE_DEPTH,
+        input,
+      )?;
     let mapped_call = Call::from(call.clone());
     Ok(Self { call, mapped_call, signature })
   }
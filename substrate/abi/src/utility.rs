@@ -0,0 +1,23 @@
+use frame_support::dispatch::DispatchError;
+
+#[derive(Clone, PartialEq, Eq, Debug, scale::Encode, scale::Decode, scale_info::TypeInfo)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(all(feature = "std", feature = "serde"), derive(serde::Deserialize))]
+pub enum Call {
+  /// Execute the calls atomically, reverting all of them if any fail.
+  ///
+  /// Batches may not be nested.
+  batch_all { calls: alloc::vec::Vec<crate::Call> },
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, scale::Encode, scale::Decode, scale_info::TypeInfo)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(all(feature = "std", feature = "serde"), derive(serde::Deserialize))]
+pub enum Event {
+  BatchInterrupted { index: u32, error: DispatchError },
+  BatchCompleted,
+  BatchCompletedWithErrors,
+  ItemCompleted,
+  ItemFailed { error: DispatchError },
+  DispatchedAs { result: Result<(), DispatchError> },
+}
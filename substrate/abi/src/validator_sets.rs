@@ -32,6 +32,24 @@ pub enum Call {
     network: NetworkId,
     session: Session,
   },
+  report_loss {
+    network: ExternalNetworkId,
+    session: Session,
+    evidence: BatchEquivocation,
+    losses: BoundedVec<(SeraiAddress, ExternalBalance), ConstU32<MAX_LOSS_REPORT_ACCOUNTS>>,
+    signature: Signature,
+  },
+  claim_compensation,
+  fund_compensation {
+    amount: Amount,
+  },
+  set_network_address {
+    address: BoundedVec<u8, ConstU32<MAX_NETWORK_ADDRESS_LEN>>,
+  },
+  set_attempt_window {
+    network: ExternalNetworkId,
+    window: AttemptWindow,
+  },
 }
 
 #[derive(Clone, PartialEq, Eq, Debug, scale::Encode, scale::Decode, scale_info::TypeInfo)]
@@ -72,4 +90,19 @@ pub enum Event {
     network: NetworkId,
     session: Session,
   },
+  LossReported {
+    set: ExternalValidatorSet,
+    compensation: Amount,
+  },
+  CompensationClaimed {
+    account: SeraiAddress,
+    amount: Amount,
+  },
+  NetworkAddressSet {
+    validator: SeraiAddress,
+  },
+  AttemptWindowSet {
+    network: ExternalNetworkId,
+    window: AttemptWindow,
+  },
 }
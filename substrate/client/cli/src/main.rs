@@ -0,0 +1,319 @@
+use core::time::Duration;
+
+use zeroize::Zeroizing;
+
+use clap::{Parser, Subcommand};
+
+use futures_util::StreamExt;
+
+use serai_client::{
+  primitives::{
+    Amount, Coin, Balance, ExternalCoin, NetworkId, SeraiAddress, COINS, EXTERNAL_NETWORKS,
+  },
+  validator_sets::primitives::Session,
+  abi::Call,
+  helpers, Pair, PairTrait, Serai, SeraiCoins, SeraiDex, SeraiValidatorSets, TransactionStatus,
+};
+
+// How often to poll the node when watching for events and transactions
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// A command-line interface to the Serai network.
+///
+/// Commands which submit transactions sign with the secret URI in the `SERAI_KEY` environment
+/// variable, which may be a mnemonic, a hex-encoded seed, or a derivation such as `//Alice`.
+/// Amounts are specified in whole units of their coin, such as `1.5` for one and a half BTC.
+#[derive(Parser)]
+#[command(name = "serai", version)]
+struct Cli {
+  /// The URL of the node's RPC.
+  #[arg(long, env = "SERAI_RPC", default_value = "http://127.0.0.1:9944")]
+  rpc: String,
+  #[command(subcommand)]
+  command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+  /// The address for the key in `SERAI_KEY`.
+  Address,
+  /// The balance of an address.
+  Balance {
+    #[arg(value_parser = parse_address)]
+    address: SeraiAddress,
+    #[arg(value_parser = parse_coin)]
+    coin: Coin,
+  },
+  /// The reserves of the pool for a coin.
+  Reserves {
+    #[arg(value_parser = parse_external_coin)]
+    coin: ExternalCoin,
+  },
+  /// The amount an address has allocated to validating a network.
+  Allocation {
+    #[arg(value_parser = parse_network)]
+    network: NetworkId,
+    #[arg(value_parser = parse_address)]
+    address: SeraiAddress,
+  },
+  /// Transfer a coin to an address.
+  Transfer {
+    #[arg(value_parser = parse_address)]
+    to: SeraiAddress,
+    #[arg(value_parser = parse_coin)]
+    coin: Coin,
+    amount: String,
+  },
+  /// Swap an amount of one coin for at least an amount of another.
+  Swap {
+    #[arg(value_parser = parse_coin)]
+    from: Coin,
+    #[arg(value_parser = parse_coin)]
+    to: Coin,
+    amount_in: String,
+    min_amount_out: String,
+  },
+  /// Add liquidity to the pool for a coin.
+  AddLiquidity {
+    #[arg(value_parser = parse_external_coin)]
+    coin: ExternalCoin,
+    coin_amount: String,
+    sri_amount: String,
+    #[arg(long, default_value = "0")]
+    min_coin_amount: String,
+    #[arg(long, default_value = "0")]
+    min_sri_amount: String,
+  },
+  /// Remove liquidity from the pool for a coin, burning the specified amount of liquidity tokens.
+  RemoveLiquidity {
+    #[arg(value_parser = parse_external_coin)]
+    coin: ExternalCoin,
+    lp_tokens: String,
+    #[arg(long, default_value = "0")]
+    min_coin_amount: String,
+    #[arg(long, default_value = "0")]
+    min_sri_amount: String,
+  },
+  /// Allocate SRI to validating a network.
+  Allocate {
+    #[arg(value_parser = parse_network)]
+    network: NetworkId,
+    amount: String,
+  },
+  /// Deallocate SRI from validating a network.
+  Deallocate {
+    #[arg(value_parser = parse_network)]
+    network: NetworkId,
+    amount: String,
+  },
+  /// Claim a deallocation which has become claimable.
+  ClaimDeallocation {
+    #[arg(value_parser = parse_network)]
+    network: NetworkId,
+    session: u32,
+  },
+  /// Print the events within every finalized block, starting with the specified block.
+  Watch {
+    /// The block to start from, defaulting to the latest finalized block.
+    #[arg(long)]
+    from: Option<u64>,
+  },
+}
+
+fn parse_address(address: &str) -> Result<SeraiAddress, String> {
+  if let Some(hex) = address.strip_prefix("0x") {
+    let key = hex::decode(hex).map_err(|_| "address wasn't SS58 nor hex".to_string())?;
+    return <[u8; 32]>::try_from(key)
+      .map(SeraiAddress)
+      .map_err(|_| "hex address wasn't 32 bytes".to_string());
+  }
+  helpers::parse_serai_address(address).map_err(|e| e.to_string())
+}
+
+fn parse_coin(coin: &str) -> Result<Coin, String> {
+  COINS
+    .into_iter()
+    .find(|candidate| candidate.symbol().eq_ignore_ascii_case(coin))
+    .ok_or_else(|| format!("unrecognized coin {coin}"))
+}
+
+fn parse_external_coin(coin: &str) -> Result<ExternalCoin, String> {
+  ExternalCoin::try_from(parse_coin(coin)?).map_err(|()| "SRI doesn't have a pool".to_string())
+}
+
+fn parse_network(network: &str) -> Result<NetworkId, String> {
+  if network.eq_ignore_ascii_case("serai") {
+    return Ok(NetworkId::Serai);
+  }
+  EXTERNAL_NETWORKS
+    .into_iter()
+    .find(|candidate| format!("{candidate:?}").eq_ignore_ascii_case(network))
+    .map(NetworkId::from)
+    .ok_or_else(|| format!("unrecognized network {network}"))
+}
+
+// Parse an amount of whole units of a coin into its atomic units
+fn parse_amount(coin: Coin, amount: &str) -> Result<Amount, String> {
+  let decimals = usize::try_from(coin.decimals()).unwrap();
+  let (whole, fractional) = amount.split_once('.').unwrap_or((amount, ""));
+  if fractional.len() > decimals {
+    Err(format!("{} only has {decimals} decimals", coin.symbol()))?;
+  }
+  let atomic = format!("{whole}{fractional:0<decimals$}");
+  atomic.parse().map(Amount).map_err(|_| format!("invalid amount {amount}"))
+}
+
+fn format_amount(coin: Coin, amount: Amount) -> String {
+  let decimals = coin.decimals();
+  let unit = 10u64.pow(decimals);
+  let decimals = usize::try_from(decimals).unwrap();
+  format!("{}.{:0>decimals$} {}", amount.0 / unit, amount.0 % unit, coin.symbol())
+}
+
+fn key() -> Result<Pair, String> {
+  let uri = Zeroizing::new(
+    std::env::var("SERAI_KEY").map_err(|_| "SERAI_KEY wasn't set to a secret URI".to_string())?,
+  );
+  Pair::from_string(&uri, None).map_err(|e| format!("SERAI_KEY wasn't a valid secret URI: {e:?}"))
+}
+
+// Sign and submit a call, reporting its progress until it's finalized
+async fn submit(serai: &Serai, call: Call) -> Result<(), String> {
+  let pair = key()?;
+  let nonce = serai.next_nonce(pair.public().into()).await.map_err(|e| e.to_string())?;
+  let tx = serai.sign(&pair, call, nonce, 0);
+
+  let mut statuses = Box::pin(serai.submit_and_watch(&tx, POLL_INTERVAL));
+  while let Some(status) = statuses.next().await {
+    let status = status.map_err(|e| e.to_string())?;
+    if let Some(error) = status.dispatch_error() {
+      let error = serai.decode_dispatch_error(error).await.map_err(|e| e.to_string())?;
+      Err(format!("transaction failed: {error}"))?;
+    }
+    match status {
+      TransactionStatus::Broadcast => println!("submitted"),
+      TransactionStatus::InBlock { block, .. } => println!("included in 0x{}", hex::encode(block)),
+      TransactionStatus::Retracted { block } => println!("retracted from 0x{}", hex::encode(block)),
+      TransactionStatus::Finalized { block, .. } => {
+        println!("finalized in 0x{}", hex::encode(block))
+      }
+      TransactionStatus::Dropped => Err("transaction was dropped".to_string())?,
+    }
+  }
+  Ok(())
+}
+
+async fn run(cli: Cli) -> Result<(), String> {
+  if let Command::Address = cli.command {
+    println!("{}", helpers::format_serai_address(key()?.public().into()));
+    return Ok(());
+  }
+
+  let serai = Serai::new(cli.rpc).await.map_err(|e| e.to_string())?;
+  let latest = serai.as_of_latest_finalized_block().await.map_err(|e| e.to_string())?;
+  let address = || key().map(|pair| SeraiAddress::from(pair.public()));
+
+  match cli.command {
+    Command::Address => unreachable!("address command wasn't handled before connecting"),
+    Command::Balance { address, coin } => {
+      let balance = latest.coins().coin_balance(coin, address).await.map_err(|e| e.to_string())?;
+      println!("{}", format_amount(coin, balance));
+    }
+    Command::Reserves { coin } => {
+      match latest.dex().reserves(coin).await.map_err(|e| e.to_string())? {
+        Some((coin_reserve, sri_reserve)) => println!(
+          "{}, {}",
+          format_amount(coin.into(), coin_reserve),
+          format_amount(Coin::Serai, sri_reserve)
+        ),
+        None => println!("the pool is empty or doesn't exist"),
+      }
+    }
+    Command::Allocation { network, address } => {
+      let allocation = latest
+        .validator_sets()
+        .allocation(network, address.into())
+        .await
+        .map_err(|e| e.to_string())?
+        .unwrap_or(Amount(0));
+      println!("{}", format_amount(Coin::Serai, allocation));
+    }
+    Command::Transfer { to, coin, amount } => {
+      let balance = Balance { coin, amount: parse_amount(coin, &amount)? };
+      submit(&serai, SeraiCoins::transfer(to, balance)).await?;
+    }
+    Command::Swap { from, to, amount_in, min_amount_out } => {
+      let call = SeraiDex::swap(
+        from,
+        to,
+        parse_amount(from, &amount_in)?,
+        parse_amount(to, &min_amount_out)?,
+        address()?,
+      )
+      .ok_or_else(|| format!("can't swap {} for {}", from.symbol(), to.symbol()))?;
+      submit(&serai, call).await?;
+    }
+    Command::AddLiquidity { coin, coin_amount, sri_amount, min_coin_amount, min_sri_amount } => {
+      let call = SeraiDex::add_liquidity(
+        coin,
+        parse_amount(coin.into(), &coin_amount)?,
+        parse_amount(Coin::Serai, &sri_amount)?,
+        parse_amount(coin.into(), &min_coin_amount)?,
+        parse_amount(Coin::Serai, &min_sri_amount)?,
+        address()?,
+      );
+      submit(&serai, call).await?;
+    }
+    Command::RemoveLiquidity { coin, lp_tokens, min_coin_amount, min_sri_amount } => {
+      // Liquidity tokens have the same amount of decimals as SRI
+      let call = SeraiDex::remove_liquidity(
+        coin,
+        parse_amount(Coin::Serai, &lp_tokens)?,
+        parse_amount(coin.into(), &min_coin_amount)?,
+        parse_amount(Coin::Serai, &min_sri_amount)?,
+        address()?,
+      );
+      submit(&serai, call).await?;
+    }
+    Command::Allocate { network, amount } => {
+      let call = SeraiValidatorSets::allocate(network, parse_amount(Coin::Serai, &amount)?);
+      submit(&serai, call).await?;
+    }
+    Command::Deallocate { network, amount } => {
+      let call = SeraiValidatorSets::deallocate(network, parse_amount(Coin::Serai, &amount)?);
+      submit(&serai, call).await?;
+    }
+    Command::ClaimDeallocation { network, session } => {
+      submit(&serai, SeraiValidatorSets::claim_deallocation(network, Session(session))).await?;
+    }
+    Command::Watch { from } => {
+      let from = match from {
+        Some(from) => from,
+        None => serai.latest_finalized_block().await.map_err(|e| e.to_string())?.number(),
+      };
+      let mut blocks = Box::pin(serai.all_events_stream(from, POLL_INTERVAL));
+      while let Some(block) = blocks.next().await {
+        // The stream retries upon errors, so they're solely reported
+        let block = match block {
+          Ok(block) => block,
+          Err(e) => {
+            eprintln!("error fetching events: {e}");
+            continue;
+          }
+        };
+        for event in block.events {
+          println!("{} 0x{}: {event:?}", block.number, hex::encode(block.hash));
+        }
+      }
+    }
+  }
+  Ok(())
+}
+
+#[tokio::main]
+async fn main() {
+  if let Err(e) = run(Cli::parse()).await {
+    eprintln!("{e}");
+    std::process::exit(1);
+  }
+}
@@ -0,0 +1,265 @@
+use core::net::SocketAddr;
+
+use scale::{Encode, Decode};
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use clap::Parser;
+
+use futures_util::StreamExt;
+
+use http_body_util::{BodyExt, Full, Limited};
+use hyper::{
+  body::{Bytes, Incoming},
+  server::conn::http1,
+  service::service_fn,
+  Method, Request, Response, StatusCode,
+};
+use hyper_util::rt::TokioIo;
+use tokio::net::TcpListener;
+
+use serai_client::{
+  primitives::{Amount, Coin, ExternalCoin, SeraiAddress, COINS},
+  helpers, Serai, SeraiDex, SeraiError, Signature, UnsignedTransaction,
+};
+
+// The maximum size of a request's body
+const MAX_BODY_LEN: usize = 64 * 1024;
+// The amount of blocks after the latest finalized block a built transaction may be included until
+const DEADLINE: u64 = 64;
+
+/// A JSON API to the Serai network, for web and mobile frontends.
+///
+/// Amounts are in atomic units, encoded as decimal strings as they may exceed the precision of a
+/// JavaScript number. Addresses may be SS58 or 0x-prefixed hex, and coins are specified by their
+/// symbol. Transactions are built by the gateway yet signed by the frontend, so the gateway never
+/// holds any keys.
+#[derive(Parser)]
+#[command(name = "serai-gateway", version)]
+struct Cli {
+  /// The URL of the node's RPC.
+  #[arg(long, env = "SERAI_RPC", default_value = "http://127.0.0.1:9944")]
+  rpc: String,
+  /// The address to serve the API on.
+  #[arg(long, env = "SERAI_GATEWAY_ADDRESS", default_value = "127.0.0.1:8080")]
+  address: SocketAddr,
+}
+
+// An error to respond with, and its status code
+struct ApiError(StatusCode, String);
+
+fn bad_request(e: impl ToString) -> ApiError {
+  ApiError(StatusCode::BAD_REQUEST, e.to_string())
+}
+
+fn node_error(e: SeraiError) -> ApiError {
+  ApiError(StatusCode::BAD_GATEWAY, e.to_string())
+}
+
+fn parse_address(address: &str) -> Result<SeraiAddress, ApiError> {
+  if let Some(hex) = address.strip_prefix("0x") {
+    let key = hex::decode(hex).map_err(|_| bad_request("address wasn't SS58 nor hex"))?;
+    return <[u8; 32]>::try_from(key)
+      .map(SeraiAddress)
+      .map_err(|_| bad_request("hex address wasn't 32 bytes"));
+  }
+  helpers::parse_serai_address(address).map_err(bad_request)
+}
+
+fn parse_coin(coin: &str) -> Result<Coin, ApiError> {
+  COINS
+    .into_iter()
+    .find(|candidate| candidate.symbol().eq_ignore_ascii_case(coin))
+    .ok_or_else(|| bad_request(format!("unrecognized coin {coin}")))
+}
+
+fn parse_external_coin(coin: &str) -> Result<ExternalCoin, ApiError> {
+  ExternalCoin::try_from(parse_coin(coin)?).map_err(|()| bad_request("SRI doesn't have a pool"))
+}
+
+fn parse_amount(amount: &str) -> Result<Amount, ApiError> {
+  amount.parse().map(Amount).map_err(|_| bad_request(format!("invalid amount {amount}")))
+}
+
+fn parse_hex(hex: &str) -> Result<Vec<u8>, ApiError> {
+  hex::decode(hex.strip_prefix("0x").unwrap_or(hex)).map_err(bad_request)
+}
+
+fn encode_hex(bytes: impl AsRef<[u8]>) -> String {
+  format!("0x{}", hex::encode(bytes))
+}
+
+#[derive(Deserialize)]
+struct SwapRequest {
+  signer: String,
+  from: String,
+  to: String,
+  amount_in: String,
+  min_amount_out: String,
+}
+
+#[derive(Deserialize)]
+struct SubmitRequest {
+  signer: String,
+  transaction: String,
+  signature: String,
+}
+
+fn parse_body<T: for<'de> Deserialize<'de>>(body: &[u8]) -> Result<T, ApiError> {
+  serde_json::from_slice(body).map_err(bad_request)
+}
+
+/// Route a request.
+///
+/// - `GET /health`: The node's health.
+/// - `GET /balance/{address}/{coin}`: An address's balance of a coin.
+/// - `GET /reserves/{coin}`: The reserves of the pool for a coin, or `null` if it has none.
+/// - `GET /quote/{from}/{to}/{amount_in}`: The amount received for swapping `amount_in`, or `null`
+///   if the swap couldn't be quoted.
+/// - `GET /events/{block}`: The events within a finalized block.
+/// - `POST /swap`: Build a swap for the signer, returning the SCALE-encoded transaction and the
+///   payload for the signer to sign with sr25519.
+/// - `POST /submit`: Attach the signer's signature to a transaction built by `/swap` and publish
+///   it.
+///
+/// Everything is read as of the latest finalized block. Malformed requests, and transactions the
+/// node rejects, are responded to with a 400.
+async fn route(serai: &Serai, method: Method, path: &str, body: &[u8]) -> Result<Value, ApiError> {
+  let path = path.trim_matches('/').split('/').collect::<Vec<_>>();
+
+  match (method, path.as_slice()) {
+    (Method::GET, ["health"]) => Ok(json!(serai.health().await.map_err(node_error)?)),
+    (Method::GET, ["balance", address, coin]) => {
+      let (address, coin) = (parse_address(address)?, parse_coin(coin)?);
+      let latest = serai.as_of_latest_finalized_block().await.map_err(node_error)?;
+      let balance = latest.coins().coin_balance(coin, address).await.map_err(node_error)?;
+      Ok(json!({ "coin": coin.symbol(), "amount": balance.0.to_string() }))
+    }
+    (Method::GET, ["reserves", coin]) => {
+      let coin = parse_external_coin(coin)?;
+      let latest = serai.as_of_latest_finalized_block().await.map_err(node_error)?;
+      Ok(match latest.dex().reserves(coin).await.map_err(node_error)? {
+        Some((coin, sri)) => json!({ "coin": coin.0.to_string(), "sri": sri.0.to_string() }),
+        None => Value::Null,
+      })
+    }
+    (Method::GET, ["quote", from, to, amount_in]) => {
+      let (from, to, amount_in) = (parse_coin(from)?, parse_coin(to)?, parse_amount(amount_in)?);
+      let latest = serai.as_of_latest_finalized_block().await.map_err(node_error)?;
+      let amount_out =
+        latest.dex().quote_amount_out(from, to, amount_in).await.map_err(node_error)?;
+      Ok(json!({ "amount_out": amount_out.map(|amount| amount.0.to_string()) }))
+    }
+    (Method::GET, ["events", block]) => {
+      let block = block.parse::<u64>().map_err(|_| bad_request("invalid block number"))?;
+      let mut events = Box::pin(serai.events_between(block, block, |_| true));
+      let page = events.next().await.transpose().map_err(node_error)?;
+      let Some(block) = page.and_then(|mut page| page.pop()) else {
+        Err(node_error(SeraiError::InvalidNode("no events were yielded".to_string())))?
+      };
+      Ok(json!({ "number": block.number, "hash": encode_hex(block.hash), "events": block.events }))
+    }
+    (Method::POST, ["swap"]) => {
+      let request = parse_body::<SwapRequest>(body)?;
+      let signer = parse_address(&request.signer)?;
+      let (from, to) = (parse_coin(&request.from)?, parse_coin(&request.to)?);
+      if from == to {
+        Err(bad_request("can't swap a coin for itself"))?;
+      }
+      let call = SeraiDex::swap(
+        from,
+        to,
+        parse_amount(&request.amount_in)?,
+        parse_amount(&request.min_amount_out)?,
+        signer,
+      )
+      .ok_or_else(|| bad_request("no path to swap along"))?;
+
+      let nonce = serai.next_nonce(signer).await.map_err(node_error)?;
+      let checkpoint = serai.latest_finalized_block().await.map_err(node_error)?.header;
+      let deadline = checkpoint.number + DEADLINE;
+      let tx = serai
+        .unsigned_transaction_with_deadline(call, nonce, 0, None, &checkpoint, deadline)
+        .expect("deadline wasn't far enough after the checkpoint");
+      Ok(json!({
+        "transaction": encode_hex(tx.encode()),
+        "signing_payload": encode_hex(tx.signing_payload()),
+        "deadline": deadline,
+      }))
+    }
+    (Method::POST, ["submit"]) => {
+      let request = parse_body::<SubmitRequest>(body)?;
+      let signer = parse_address(&request.signer)?;
+      let tx = UnsignedTransaction::decode(&mut parse_hex(&request.transaction)?.as_slice())
+        .map_err(|_| bad_request("transaction wasn't a valid unsigned transaction"))?;
+      let signature = <[u8; 64]>::try_from(parse_hex(&request.signature)?)
+        .map_err(|_| bad_request("signature wasn't 64 bytes"))?;
+      let Some(tx) = tx.with_signature(signer, Signature::from_raw(signature)) else {
+        Err(bad_request("signature wasn't valid for the transaction and signer"))?
+      };
+      serai.publish(&tx).await.map_err(|e| match e {
+        // The node rejects invalid transactions, such as those with a stale nonce
+        SeraiError::ErrorInResponse(e) => bad_request(format!("transaction was rejected: {e}")),
+        e => node_error(e),
+      })?;
+      Ok(json!({ "extrinsic": encode_hex(tx.encode()) }))
+    }
+    _ => Err(ApiError(StatusCode::NOT_FOUND, "unrecognized route".to_string())),
+  }
+}
+
+async fn respond(serai: Serai, request: Request<Incoming>) -> Response<Full<Bytes>> {
+  let method = request.method().clone();
+  let path = request.uri().path().to_string();
+  let res = match Limited::new(request.into_body(), MAX_BODY_LEN).collect().await {
+    Ok(body) => route(&serai, method, &path, &body.to_bytes()).await,
+    Err(e) => Err(bad_request(format!("couldn't read the request's body: {e}"))),
+  };
+  let (status, body) = match res {
+    Ok(body) => (StatusCode::OK, body),
+    Err(ApiError(status, error)) => (status, json!({ "error": error })),
+  };
+  Response::builder()
+    .status(status)
+    .header("content-type", "application/json")
+    .body(Full::new(Bytes::from(body.to_string())))
+    .unwrap()
+}
+
+async fn run(cli: Cli) -> Result<(), String> {
+  let serai = Serai::new(cli.rpc).await.map_err(|e| e.to_string())?;
+  let listener = TcpListener::bind(cli.address).await.map_err(|e| e.to_string())?;
+  println!("serving on {}", cli.address);
+
+  loop {
+    let (stream, _) = match listener.accept().await {
+      Ok(connection) => connection,
+      Err(e) => {
+        eprintln!("couldn't accept a connection: {e}");
+        continue;
+      }
+    };
+    let serai = serai.clone();
+    tokio::spawn(async move {
+      let service = service_fn(move |request| {
+        let serai = serai.clone();
+        async move { Ok::<_, core::convert::Infallible>(respond(serai, request).await) }
+      });
+      if let Err(e) = http1::Builder::new().serve_connection(TokioIo::new(stream), service).await {
+        eprintln!("error serving a connection: {e}");
+      }
+    });
+  }
+}
+
+#[cfg(test)]
+mod tests;
+
+#[tokio::main]
+async fn main() {
+  if let Err(e) = run(Cli::parse()).await {
+    eprintln!("{e}");
+    std::process::exit(1);
+  }
+}
@@ -0,0 +1,106 @@
+use std::sync::Arc;
+
+use scale::Encode;
+
+use serde_json::{json, Value};
+
+use hyper::{Method, StatusCode};
+
+use serai_client::{primitives::insecure_pair_from_name, helpers, Pair, PairTrait, Serai, MockSerai};
+
+use crate::{ApiError, route};
+
+async fn request(
+  serai: &Serai,
+  method: Method,
+  path: &str,
+  body: Value,
+) -> Result<Value, StatusCode> {
+  route(serai, method, path, body.to_string().as_bytes())
+    .await
+    .map_err(|ApiError(status, _)| status)
+}
+
+fn address(pair: &Pair) -> String {
+  helpers::format_serai_address(pair.public().into())
+}
+
+#[tokio::test]
+async fn routes() {
+  let mock = Arc::new(MockSerai::new());
+  let serai = Serai::with_rpc(mock).await.unwrap();
+
+  assert_eq!(request(&serai, Method::GET, "/health", Value::Null).await.unwrap()["chain"], "Mock");
+  assert_eq!(
+    request(&serai, Method::GET, "/unrecognized", Value::Null).await,
+    Err(StatusCode::NOT_FOUND)
+  );
+  assert_eq!(
+    request(&serai, Method::POST, "/health", Value::Null).await,
+    Err(StatusCode::NOT_FOUND)
+  );
+
+  let alice = address(&insecure_pair_from_name("Alice"));
+  for path in
+    [format!("/balance/{alice}/XYZ"), "/balance/0x00/SRI".to_string(), "/reserves/SRI".to_string()]
+  {
+    assert_eq!(
+      request(&serai, Method::GET, &path, Value::Null).await,
+      Err(StatusCode::BAD_REQUEST)
+    );
+  }
+}
+
+#[tokio::test]
+async fn swap() {
+  let mock = Arc::new(MockSerai::new());
+  let serai = Serai::with_rpc(mock.clone()).await.unwrap();
+
+  let pair = insecure_pair_from_name("Alice");
+  let signer = address(&pair);
+  let swap = |from: &str, to: &str, amount_in: &str| {
+    json!({
+      "signer": signer,
+      "from": from,
+      "to": to,
+      "amount_in": amount_in,
+      "min_amount_out": "1",
+    })
+  };
+
+  // Malformed swaps are rejected
+  for malformed in [
+    swap("BTC", "BTC", "100"),
+    swap("BTC", "XYZ", "100"),
+    swap("BTC", "SRI", "-1"),
+    json!({ "signer": signer }),
+    Value::Null,
+  ] {
+    assert_eq!(
+      request(&serai, Method::POST, "/swap", malformed).await,
+      Err(StatusCode::BAD_REQUEST)
+    );
+  }
+
+  let built = request(&serai, Method::POST, "/swap", swap("BTC", "SRI", "100")).await.unwrap();
+  let payload = built["signing_payload"].as_str().unwrap().strip_prefix("0x").unwrap();
+  let signature = pair.sign(&hex::decode(payload).unwrap());
+  let submit = |signer: &str| {
+    json!({
+      "signer": signer,
+      "transaction": built["transaction"],
+      "signature": format!("0x{}", hex::encode(signature.encode())),
+    })
+  };
+
+  // The signature must be by the signer
+  let bob = address(&insecure_pair_from_name("Bob"));
+  assert_eq!(
+    request(&serai, Method::POST, "/submit", submit(&bob)).await,
+    Err(StatusCode::BAD_REQUEST)
+  );
+  assert!(mock.pending().is_empty());
+
+  request(&serai, Method::POST, "/submit", submit(&signer)).await.unwrap();
+  assert_eq!(mock.pending().len(), 1);
+}
@@ -0,0 +1,264 @@
+use core::fmt;
+
+use scale::{Encode, DecodeAll};
+
+use serai_abi::{
+  Call, Transaction,
+  primitives::{
+    MAX_DATA_LEN, Coin, Amount, Balance, ExternalNetworkId, ExternalAddress, ExternalBalance,
+    SeraiAddress,
+  },
+  coins::primitives::{OutInstruction, OutInstructionWithBalance},
+  in_instructions::primitives::{
+    Shorthand, RefundableInInstruction, InInstruction, DexCall, OutAddress,
+  },
+};
+
+/// An error when parsing an address.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AddressError {
+  /// The address wasn't a valid SS58-encoded Serai address.
+  InvalidSeraiAddress,
+  /// The address wasn't a valid address for the network.
+  InvalidAddress(ExternalNetworkId),
+  /// The address was valid, yet of a type Serai doesn't support sending to.
+  UnsupportedAddress(ExternalNetworkId),
+  /// Support for the network's addresses wasn't enabled.
+  UnsupportedNetwork(ExternalNetworkId),
+  /// The address exceeded the maximum length of an external address.
+  AddressTooLong,
+}
+
+impl fmt::Display for AddressError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      AddressError::InvalidSeraiAddress => write!(f, "invalid Serai address"),
+      AddressError::InvalidAddress(network) => write!(f, "invalid {network:?} address"),
+      AddressError::UnsupportedAddress(network) => {
+        write!(f, "{network:?} address was of an unsupported type")
+      }
+      AddressError::UnsupportedNetwork(network) => {
+        write!(f, "support for {network:?} addresses wasn't enabled")
+      }
+      AddressError::AddressTooLong => write!(f, "address exceeded the maximum length"),
+    }
+  }
+}
+
+impl std::error::Error for AddressError {}
+
+/// An error when building the data for an instruction accompanying a deposit.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InstructionError {
+  /// An address within the instruction was invalid.
+  Address(AddressError),
+  /// The encoded instruction exceeded the amount of data which may accompany a deposit on the
+  /// network.
+  TooLarge { network: ExternalNetworkId, len: usize, limit: usize },
+}
+
+impl From<AddressError> for InstructionError {
+  fn from(error: AddressError) -> Self {
+    InstructionError::Address(error)
+  }
+}
+
+impl fmt::Display for InstructionError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      InstructionError::Address(error) => error.fmt(f),
+      InstructionError::TooLarge { network, len, limit } => {
+        write!(f, "instruction was {len} bytes, yet {network:?} deposits only allow {limit}")
+      }
+    }
+  }
+}
+
+impl std::error::Error for InstructionError {}
+
+/// Parse an SS58-encoded Serai address.
+#[cfg(feature = "serai")]
+pub fn parse_serai_address(address: &str) -> Result<SeraiAddress, AddressError> {
+  use sp_core::crypto::Ss58Codec;
+  crate::Public::from_ss58check(address)
+    .map(SeraiAddress::from)
+    .map_err(|_| AddressError::InvalidSeraiAddress)
+}
+
+/// Encode a Serai address as SS58.
+#[cfg(feature = "serai")]
+pub fn format_serai_address(address: SeraiAddress) -> String {
+  use sp_core::crypto::Ss58Codec;
+  crate::Public::from(address).to_ss58check()
+}
+
+/// Parse an address for an external network into the encoding Serai uses for it.
+///
+/// Bitcoin addresses may be any of P2PKH, P2SH, P2WPKH, P2WSH, or P2TR. Monero addresses may be
+/// standard addresses or subaddresses, yet not integrated addresses. Ethereum addresses with
+/// mixed case must have a valid EIP-55 checksum.
+#[cfg_attr(not(feature = "networks"), allow(unused_variables))]
+pub fn parse_external_address(
+  network: ExternalNetworkId,
+  address: &str,
+) -> Result<ExternalAddress, AddressError> {
+  let encoded: Vec<u8> = match network {
+    #[cfg(feature = "bitcoin")]
+    ExternalNetworkId::Bitcoin => address
+      .parse::<crate::networks::bitcoin::Address>()
+      .map_err(|()| AddressError::InvalidAddress(network))?
+      .into(),
+    #[cfg(feature = "ethereum")]
+    ExternalNetworkId::Ethereum => address
+      .parse::<crate::networks::ethereum::Address>()
+      .map_err(|()| AddressError::InvalidAddress(network))?
+      .into(),
+    #[cfg(feature = "monero")]
+    ExternalNetworkId::Monero => {
+      let address = address
+        .parse::<crate::networks::monero::Address>()
+        .map_err(|_| AddressError::InvalidAddress(network))?;
+      // Integrated addresses are rejected, as Serai doesn't support payment IDs
+      crate::networks::monero::Address::new(address.into())
+        .ok_or(AddressError::UnsupportedAddress(network))?
+        .into()
+    }
+    #[allow(unreachable_patterns)]
+    _ => Err(AddressError::UnsupportedNetwork(network))?,
+  };
+  ExternalAddress::new(encoded).map_err(|_| AddressError::AddressTooLong)
+}
+
+/// Format an address for an external network, as encoded by Serai, in its network's canonical
+/// form.
+#[cfg_attr(not(feature = "networks"), allow(unused_variables))]
+pub fn format_external_address(
+  network: ExternalNetworkId,
+  address: &ExternalAddress,
+) -> Result<String, AddressError> {
+  let address = address.address().to_vec();
+  Ok(match network {
+    #[cfg(feature = "bitcoin")]
+    ExternalNetworkId::Bitcoin => crate::networks::bitcoin::Address::try_from(address)
+      .map_err(|()| AddressError::InvalidAddress(network))?
+      .to_string(),
+    #[cfg(feature = "ethereum")]
+    ExternalNetworkId::Ethereum => crate::networks::ethereum::Address::try_from(address)
+      .map_err(|()| AddressError::InvalidAddress(network))?
+      .to_string(),
+    #[cfg(feature = "monero")]
+    ExternalNetworkId::Monero => crate::networks::monero::Address::try_from(address)
+      .map_err(|()| AddressError::InvalidAddress(network))?
+      .to_string(),
+    #[allow(unreachable_patterns)]
+    _ => Err(AddressError::UnsupportedNetwork(network))?,
+  })
+}
+
+/// Normalize an address for an external network to its canonical form.
+pub fn normalize_external_address(
+  network: ExternalNetworkId,
+  address: &str,
+) -> Result<String, AddressError> {
+  format_external_address(network, &parse_external_address(network, address)?)
+}
+
+/// Build an instruction to send a balance to an address on its coin's network.
+pub fn out_instruction(
+  address: &str,
+  balance: ExternalBalance,
+) -> Result<OutInstructionWithBalance, AddressError> {
+  let address = parse_external_address(balance.coin.network(), address)?;
+  Ok(OutInstructionWithBalance { instruction: OutInstruction { address, data: None }, balance })
+}
+
+/// The maximum length of the data accompanying a deposit on an external network.
+///
+/// For Bitcoin, this is the data within an OP_RETURN output. For Monero, this is the first blob
+/// of arbitrary data within the transaction's extra. For Ethereum, this is the data passed to the
+/// router.
+pub fn max_instruction_len(network: ExternalNetworkId) -> usize {
+  let max_data_len = usize::try_from(MAX_DATA_LEN).unwrap();
+  match network {
+    // The standardness limit for OP_RETURN outputs
+    ExternalNetworkId::Bitcoin => 80,
+    // The maximum length of a blob of arbitrary data, as one byte of the extra nonce is its marker
+    ExternalNetworkId::Monero => 254,
+    ExternalNetworkId::Ethereum => max_data_len,
+  }
+  .min(max_data_len)
+}
+
+// Encode an instruction as the data for a deposit on the specified network
+fn encode_instruction(
+  network: ExternalNetworkId,
+  refund: Option<&str>,
+  instruction: InInstruction,
+) -> Result<Vec<u8>, InstructionError> {
+  let origin = refund.map(|refund| parse_external_address(network, refund)).transpose()?;
+  let encoded = Shorthand::Raw(RefundableInInstruction { origin, instruction }).encode();
+  let limit = max_instruction_len(network);
+  if encoded.len() > limit {
+    Err(InstructionError::TooLarge { network, len: encoded.len(), limit })?;
+  }
+  Ok(encoded)
+}
+
+/// Build the data for a deposit on `network` which swaps the deposited coin for at least the
+/// `minimum` balance, sending it to an address on its coin's network.
+///
+/// If the instruction fails, the deposit is refunded to the `refund` address on `network`, if
+/// one is specified.
+pub fn swap_instruction(
+  network: ExternalNetworkId,
+  refund: Option<&str>,
+  minimum: ExternalBalance,
+  to: &str,
+) -> Result<Vec<u8>, InstructionError> {
+  let to = parse_external_address(minimum.coin.network(), to)?;
+  encode_instruction(
+    network,
+    refund,
+    InInstruction::Dex(DexCall::Swap(minimum.into(), OutAddress::External(to))),
+  )
+}
+
+/// Build the data for a deposit on `network` which swaps the deposited coin for at least the
+/// `minimum` amount of SRI, sending it to a Serai address.
+///
+/// If the instruction fails, the deposit is refunded to the `refund` address on `network`, if
+/// one is specified.
+pub fn swap_to_sri_instruction(
+  network: ExternalNetworkId,
+  refund: Option<&str>,
+  minimum: Amount,
+  to: SeraiAddress,
+) -> Result<Vec<u8>, InstructionError> {
+  let minimum = Balance { coin: Coin::Serai, amount: minimum };
+  encode_instruction(
+    network,
+    refund,
+    InInstruction::Dex(DexCall::Swap(minimum, OutAddress::Serai(to))),
+  )
+}
+
+/// Build the data for a deposit on `network` which swaps half of the deposited coin for SRI and
+/// adds both as liquidity to the coin's pool, sending the liquidity tokens to a Serai address.
+///
+/// If the instruction fails, the deposit is refunded to the `refund` address on `network`, if
+/// one is specified.
+pub fn add_liquidity_instruction(
+  network: ExternalNetworkId,
+  refund: Option<&str>,
+  to: SeraiAddress,
+) -> Result<Vec<u8>, InstructionError> {
+  encode_instruction(network, refund, InInstruction::Dex(DexCall::SwapAndAddLiquidity(to)))
+}
+
+/// Decode an extrinsic, as included within a block, into its call.
+///
+/// The extrinsic's signature isn't verified. Its signer may be read by decoding it as a
+/// `Transaction`.
+pub fn decode_extrinsic(mut extrinsic: &[u8]) -> Result<Call, scale::Error> {
+  Ok(Transaction::decode_all(&mut extrinsic)?.call().clone())
+}
@@ -1,6 +1,8 @@
 #[cfg(feature = "networks")]
 pub mod networks;
 
+pub mod helpers;
+
 #[cfg(feature = "serai")]
 mod serai;
 #[cfg(feature = "serai")]
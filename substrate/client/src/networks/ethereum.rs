@@ -0,0 +1,74 @@
+use core::{str::FromStr, fmt};
+
+use sha3::{Digest, Keccak256};
+
+/// An Ethereum address.
+///
+/// Addresses are parsed from their hex encoding, requiring a valid EIP-55 checksum if they have
+/// mixed case, and displayed with their checksum.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Address([u8; 20]);
+
+impl Address {
+  pub fn new(address: [u8; 20]) -> Address {
+    Address(address)
+  }
+
+  // The address, hex-encoded with its EIP-55 checksum, without the `0x` prefix
+  fn checksummed(&self) -> String {
+    let lowercase = hex::encode(self.0);
+    let hash = Keccak256::digest(lowercase.as_bytes());
+    lowercase
+      .chars()
+      .enumerate()
+      .map(|(i, char)| {
+        let nibble = (hash[i / 2] >> (if (i % 2) == 0 { 4 } else { 0 })) & 0xf;
+        if nibble >= 8 {
+          char.to_ascii_uppercase()
+        } else {
+          char
+        }
+      })
+      .collect()
+  }
+}
+
+impl From<Address> for [u8; 20] {
+  fn from(address: Address) -> [u8; 20] {
+    address.0
+  }
+}
+
+impl FromStr for Address {
+  type Err = ();
+  fn from_str(str: &str) -> Result<Address, ()> {
+    let hex = str.strip_prefix("0x").ok_or(())?;
+    let address = Address(hex::decode(hex).map_err(|_| ())?.try_into().map_err(|_| ())?);
+    // Addresses without mixed case don't have a checksum
+    let mixed_case = hex.chars().any(|char| char.is_ascii_lowercase()) &&
+      hex.chars().any(|char| char.is_ascii_uppercase());
+    if mixed_case && (address.checksummed() != hex) {
+      Err(())?;
+    }
+    Ok(address)
+  }
+}
+
+impl fmt::Display for Address {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "0x{}", self.checksummed())
+  }
+}
+
+impl TryFrom<Vec<u8>> for Address {
+  type Error = ();
+  fn try_from(data: Vec<u8>) -> Result<Address, ()> {
+    data.try_into().map(Address).map_err(|_| ())
+  }
+}
+
+impl From<Address> for Vec<u8> {
+  fn from(address: Address) -> Vec<u8> {
+    address.0.to_vec()
+  }
+}
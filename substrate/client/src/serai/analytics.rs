@@ -0,0 +1,360 @@
+use core::ops::RangeInclusive;
+use std::collections::{HashSet, HashMap, BTreeMap};
+
+use futures_util::{stream, Stream};
+use patchable_async_sleep::sleep;
+
+use serde::Serialize;
+
+use serai_abi::primitives::{Amount, Coin, ExternalCoin, SeraiAddress};
+
+use crate::{
+  dex::{DexEvent, LP_FEE},
+  Block, Serai, SeraiDex, SeraiError, QueryCost, QueryBudget,
+};
+
+const HOUR: u64 = 60 * 60 * 1000;
+
+// The volume and fees, denominated in the external coin, of each pool a swap traded within
+fn pool_activity(event: &DexEvent) -> Vec<(ExternalCoin, u64, u64)> {
+  let DexEvent::SwapExecuted { path, amount_in, amount_out, .. } = event else { return vec![] };
+
+  let mut res = vec![];
+  for (i, pair) in path.windows(2).enumerate() {
+    // The external coin for the pool this hop is within, and the amount of it bought/sold
+    let (coin, volume, fee) = match (pair[0], pair[1]) {
+      (Coin::External(coin), Coin::Serai) => {
+        // Only the first hop sells an external coin
+        if i != 0 {
+          continue;
+        }
+        (coin, *amount_in, (u128::from(*amount_in) * u128::from(LP_FEE)) / 1000)
+      }
+      (Coin::Serai, Coin::External(coin)) => {
+        // Only the last hop buys an external coin
+        if i != (path.len() - 2) {
+          continue;
+        }
+        (
+          coin,
+          *amount_out,
+          (u128::from(*amount_out) * u128::from(LP_FEE)) / u128::from(1000 - LP_FEE),
+        )
+      }
+      _ => continue,
+    };
+    res.push((coin, volume, u64::try_from(fee).unwrap_or(u64::MAX)));
+  }
+  res
+}
+
+/// A period to summarize a pool's activity over.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Period {
+  Day,
+  Week,
+}
+
+impl Period {
+  fn hours(self) -> u64 {
+    match self {
+      Period::Day => 24,
+      Period::Week => 7 * 24,
+    }
+  }
+}
+
+/// The activity of a pool over some period.
+///
+/// Amounts are denominated in the pool's external coin. The fees earned on hops which sold SRI
+/// into the pool are estimated from the amount out, as the amount of SRI sold isn't evented for
+/// multi-hop swaps.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug, Serialize)]
+pub struct PoolStats {
+  pub volume: u64,
+  pub fees_earned: u64,
+  pub unique_traders: usize,
+}
+
+#[derive(Clone, Default, Debug)]
+struct Bucket {
+  volume: u64,
+  fees_earned: u64,
+  traders: HashSet<SeraiAddress>,
+}
+
+/// Per-pool analytics, materialized incrementally from the blocks fed to it.
+///
+/// Activity is bucketed by hour, with buckets older than a week (relative to the latest block fed)
+/// pruned.
+#[derive(Clone, Default, Debug)]
+pub struct PoolAnalytics {
+  // The latest block time fed, in milliseconds
+  latest: u64,
+  next_block: Option<u64>,
+  buckets: BTreeMap<u64, HashMap<ExternalCoin, Bucket>>,
+}
+
+impl PoolAnalytics {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// The number of the next block expected to be fed, if any have been.
+  pub fn next_block(&self) -> Option<u64> {
+    self.next_block
+  }
+
+  /// Feed the DEX events from a block with the specified time (in milliseconds).
+  pub fn ingest(&mut self, time: u64, events: &[DexEvent]) {
+    self.latest = self.latest.max(time);
+    let bucket = self.buckets.entry(time / HOUR).or_default();
+
+    for event in events {
+      let DexEvent::SwapExecuted { who, .. } = event else { continue };
+      for (coin, volume, fee) in pool_activity(event) {
+        let pool = bucket.entry(coin).or_default();
+        pool.volume = pool.volume.saturating_add(volume);
+        pool.fees_earned = pool.fees_earned.saturating_add(fee);
+        pool.traders.insert(*who);
+      }
+    }
+
+    // Prune buckets we'll never read again
+    let oldest = ((self.latest / HOUR) + 1).saturating_sub(Period::Week.hours());
+    self.buckets = self.buckets.split_off(&oldest);
+  }
+
+  /// Fetch and feed a block.
+  ///
+  /// Blocks should be fed in order, without gaps, for the analytics to be accurate.
+  pub async fn ingest_block(&mut self, serai: &Serai, block: &Block) -> Result<(), SeraiError> {
+    let events = serai.as_of(block.hash()).dex().events().await?;
+    self.ingest(block.time()?, &events);
+    self.next_block = Some(block.number() + 1);
+    Ok(())
+  }
+
+  /// The activity of a pool over the specified period, ending with the latest block fed.
+  pub fn stats(&self, coin: ExternalCoin, period: Period) -> PoolStats {
+    let latest = self.latest / HOUR;
+    // The current hour is included in the period
+    let start = (latest + 1).saturating_sub(period.hours());
+
+    let mut res = PoolStats::default();
+    let mut traders = HashSet::new();
+    for bucket in self.buckets.range(start ..= latest).filter_map(|(_, pools)| pools.get(&coin)) {
+      res.volume = res.volume.saturating_add(bucket.volume);
+      res.fees_earned = res.fees_earned.saturating_add(bucket.fees_earned);
+      traders.extend(&bucket.traders);
+    }
+    res.unique_traders = traders.len();
+    res
+  }
+
+  /// The activity of every pool with activity in the past week, as JSON.
+  pub fn summary_json(&self) -> serde_json::Value {
+    #[derive(Serialize)]
+    struct Summary {
+      #[serde(rename = "24h")]
+      day: PoolStats,
+      #[serde(rename = "7d")]
+      week: PoolStats,
+    }
+
+    let mut coins =
+      self.buckets.values().flat_map(|pools| pools.keys()).copied().collect::<Vec<_>>();
+    coins.sort();
+    coins.dedup();
+
+    let mut res = serde_json::Map::new();
+    for coin in coins {
+      let summary =
+        Summary { day: self.stats(coin, Period::Day), week: self.stats(coin, Period::Week) };
+      res.insert(format!("{coin:?}"), serde_json::to_value(summary).unwrap());
+    }
+    serde_json::Value::Object(res)
+  }
+}
+
+/// A pool's reserves as of the end of a block.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize)]
+pub struct ReserveSnapshot {
+  pub block: u64,
+  /// The block's time, in milliseconds.
+  pub time: u64,
+  pub coin_reserve: u64,
+  pub sri_reserve: u64,
+}
+
+/// The activity of a pool over a range of blocks.
+///
+/// Volume and fees are denominated in the pool's external coin, as with `PoolStats`.
+#[derive(Clone, PartialEq, Eq, Default, Debug, Serialize)]
+pub struct PoolHistory {
+  pub volume: u64,
+  pub fees_earned: u64,
+  pub swaps: usize,
+  pub coin_added: u64,
+  pub sri_added: u64,
+  pub coin_removed: u64,
+  pub sri_removed: u64,
+  /// The pool's reserves as of each block within the range for which the pool had liquidity.
+  pub snapshots: Vec<ReserveSnapshot>,
+}
+
+impl PoolHistory {
+  /// Feed the DEX events from a block, along with the pool's reserves as of the end of it.
+  pub fn ingest(
+    &mut self,
+    coin: ExternalCoin,
+    block: u64,
+    time: u64,
+    events: &[DexEvent],
+    reserves: Option<(Amount, Amount)>,
+  ) {
+    for event in events {
+      match event {
+        DexEvent::SwapExecuted { .. } => {
+          let mut within_pool = false;
+          for (pool, volume, fee) in pool_activity(event) {
+            if pool == coin {
+              within_pool = true;
+              self.volume = self.volume.saturating_add(volume);
+              self.fees_earned = self.fees_earned.saturating_add(fee);
+            }
+          }
+          if within_pool {
+            self.swaps += 1;
+          }
+        }
+        DexEvent::LiquidityAdded { pool_id, coin_amount, sri_amount, .. } if *pool_id == coin => {
+          self.coin_added = self.coin_added.saturating_add(*coin_amount);
+          self.sri_added = self.sri_added.saturating_add(*sri_amount);
+        }
+        DexEvent::LiquidityRemoved { pool_id, coin_amount, sri_amount, .. } if *pool_id == coin => {
+          self.coin_removed = self.coin_removed.saturating_add(*coin_amount);
+          self.sri_removed = self.sri_removed.saturating_add(*sri_amount);
+        }
+        _ => {}
+      }
+    }
+
+    if let Some((coin_reserve, sri_reserve)) = reserves {
+      self.snapshots.push(ReserveSnapshot {
+        block,
+        time,
+        coin_reserve: coin_reserve.0,
+        sri_reserve: sri_reserve.0,
+      });
+    }
+  }
+}
+
+// The RPC calls made by `SeraiDex::history` per block
+// Fetching the block takes up to five calls, the events one, and the reserves three
+const HISTORY_CALLS_PER_BLOCK: u64 = 9;
+// A rough estimate of the bytes returned by the node per block, as blocks and their events are
+// expected to be a few kilobytes
+const HISTORY_BYTES_PER_BLOCK: u64 = 16 * 1024;
+
+/// The history of a pool over a chunk of blocks, as yielded by `SeraiDex::history_with_budget`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct HistoryChunk {
+  /// The inclusive range of blocks this chunk is for.
+  pub blocks: RangeInclusive<u64>,
+  pub history: PoolHistory,
+}
+
+impl PoolHistory {
+  /// Merge the history of a later range of blocks into this history.
+  pub fn merge(&mut self, later: PoolHistory) {
+    self.volume = self.volume.saturating_add(later.volume);
+    self.fees_earned = self.fees_earned.saturating_add(later.fees_earned);
+    self.swaps += later.swaps;
+    self.coin_added = self.coin_added.saturating_add(later.coin_added);
+    self.sri_added = self.sri_added.saturating_add(later.sri_added);
+    self.coin_removed = self.coin_removed.saturating_add(later.coin_removed);
+    self.sri_removed = self.sri_removed.saturating_add(later.sri_removed);
+    self.snapshots.extend(later.snapshots);
+  }
+}
+
+async fn ingest_history_block(
+  serai: &Serai,
+  coin: ExternalCoin,
+  number: u64,
+  history: &mut PoolHistory,
+) -> Result<(), SeraiError> {
+  let Some(block) = serai.finalized_block_by_number(number).await? else {
+    Err(SeraiError::InvalidNode(format!("block {number} wasn't finalized")))?
+  };
+  let dex = serai.as_of(block.hash()).dex();
+  let events = dex.events_for_pool(coin).await?;
+  let reserves = dex.reserves(coin).await?;
+  history.ingest(coin, number, block.time()?, &events, reserves);
+  Ok(())
+}
+
+impl<'a> SeraiDex<'a> {
+  /// The activity of the `coin:SRI` pool over the inclusive range of finalized blocks specified.
+  ///
+  /// This fetches every block within the range, and the pool's reserves as of each, making it
+  /// only suitable for ranges of limited size. `history_with_budget` should be preferred when
+  /// querying shared nodes.
+  pub async fn history(
+    &self,
+    coin: ExternalCoin,
+    from_block: u64,
+    to_block: u64,
+  ) -> Result<PoolHistory, SeraiError> {
+    let mut res = PoolHistory::default();
+    for number in from_block ..= to_block {
+      ingest_history_block(self.0.serai, coin, number, &mut res).await?;
+    }
+    Ok(res)
+  }
+
+  /// The estimated cost of fetching the history of a pool over the specified range of blocks.
+  pub fn history_cost(from_block: u64, to_block: u64) -> QueryCost {
+    let blocks = (to_block + 1).saturating_sub(from_block);
+    QueryCost { calls: HISTORY_CALLS_PER_BLOCK, bytes: HISTORY_BYTES_PER_BLOCK }.times(blocks)
+  }
+
+  /// The activity of the `coin:SRI` pool over the inclusive range of finalized blocks specified,
+  /// bounded by a budget.
+  ///
+  /// This errors without making any calls if the estimated cost of the query exceeds the budget.
+  /// Otherwise, this returns a stream yielding the history of each chunk of blocks, in order,
+  /// which may be merged together with `PoolHistory::merge`. The stream ends after the first
+  /// error.
+  pub fn history_with_budget(
+    &self,
+    coin: ExternalCoin,
+    from_block: u64,
+    to_block: u64,
+    budget: QueryBudget,
+  ) -> Result<impl Stream<Item = Result<HistoryChunk, SeraiError>> + 'a, SeraiError> {
+    budget.check(Self::history_cost(from_block, to_block))?;
+
+    let serai = self.0.serai;
+    Ok(stream::unfold(Some(from_block), move |next| async move {
+      let start = next?;
+      if start > to_block {
+        return None;
+      }
+      if start != from_block {
+        sleep(budget.chunk_delay).await;
+      }
+
+      let end = start.saturating_add(budget.chunk_size - 1).min(to_block);
+      let mut history = PoolHistory::default();
+      for number in start ..= end {
+        if let Err(e) = ingest_history_block(serai, coin, number, &mut history).await {
+          return Some((Err(e), None));
+        }
+      }
+      Some((Ok(HistoryChunk { blocks: start ..= end, history }), end.checked_add(1)))
+    }))
+  }
+}
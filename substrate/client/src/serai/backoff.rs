@@ -0,0 +1,49 @@
+use core::{
+  hash::{BuildHasher, Hasher},
+  time::Duration,
+};
+use std::collections::hash_map::RandomState;
+
+/// How to retry requests which failed to reach the node.
+///
+/// Retries are delayed exponentially, with jitter, so a fleet of clients which lost their node at
+/// the same time don't all reconnect at the same time.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Backoff {
+  /// The delay before the first retry.
+  pub initial: Duration,
+  /// The maximum delay between retries.
+  pub max: Duration,
+  /// The amount of retries to make before returning the error.
+  pub retries: u32,
+}
+
+impl Default for Backoff {
+  fn default() -> Self {
+    Backoff { initial: Duration::from_millis(100), max: Duration::from_secs(10), retries: 8 }
+  }
+}
+
+impl Backoff {
+  /// A policy which never retries.
+  pub fn none() -> Self {
+    Backoff { initial: Duration::ZERO, max: Duration::ZERO, retries: 0 }
+  }
+
+  /// The delay before the specified retry, zero-indexed, given a uniformly random value.
+  ///
+  /// The delay is uniformly distributed within the upper half of the exponential delay.
+  pub fn delay(&self, retry: u32, random: u64) -> Duration {
+    let delay = self.initial.saturating_mul(2u32.saturating_pow(retry)).min(self.max);
+    let half = delay / 2;
+    let jitter = u128::from(random).saturating_mul(half.as_nanos()) / (u128::from(u64::MAX) + 1);
+    half + Duration::from_nanos(u64::try_from(jitter).unwrap_or(u64::MAX))
+  }
+
+  pub(crate) fn jittered_delay(&self, retry: u32) -> Duration {
+    // Each RandomState is randomly seeded, making this a sufficient source of jitter
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u32(retry);
+    self.delay(retry, hasher.finish())
+  }
+}
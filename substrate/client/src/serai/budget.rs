@@ -0,0 +1,87 @@
+use core::{ops::Add, time::Duration};
+
+use crate::SeraiError;
+
+/// An estimate of the load a query will place on the node.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct QueryCost {
+  /// The amount of RPC calls made.
+  pub calls: u64,
+  /// The amount of bytes which will be returned by the node.
+  pub bytes: u64,
+}
+
+impl QueryCost {
+  /// The cost of performing this query `times` times.
+  pub fn times(self, times: u64) -> QueryCost {
+    QueryCost { calls: self.calls.saturating_mul(times), bytes: self.bytes.saturating_mul(times) }
+  }
+}
+
+impl Add for QueryCost {
+  type Output = QueryCost;
+  fn add(self, other: QueryCost) -> QueryCost {
+    QueryCost {
+      calls: self.calls.saturating_add(other.calls),
+      bytes: self.bytes.saturating_add(other.bytes),
+    }
+  }
+}
+
+/// A budget for ranged queries, bounding the load they may place on the node.
+///
+/// Queries are executed in chunks of `chunk_size` blocks, with a delay of `chunk_delay` between
+/// each chunk, to avoid monopolizing shared nodes.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct QueryBudget {
+  /// The maximum amount of RPC calls a query may make.
+  pub max_calls: Option<u64>,
+  /// The maximum amount of bytes a query may have returned by the node.
+  pub max_bytes: Option<u64>,
+  /// The amount of blocks to scan per chunk.
+  pub chunk_size: u64,
+  /// The delay between chunks.
+  pub chunk_delay: Duration,
+}
+
+impl Default for QueryBudget {
+  fn default() -> Self {
+    QueryBudget {
+      max_calls: Some(10_000),
+      max_bytes: Some(64 * 1024 * 1024),
+      chunk_size: 100,
+      chunk_delay: Duration::ZERO,
+    }
+  }
+}
+
+impl QueryBudget {
+  /// A budget without any limits, executing queries in chunks of `chunk_size` blocks.
+  pub fn unlimited(chunk_size: u64) -> Self {
+    QueryBudget { max_calls: None, max_bytes: None, chunk_size, chunk_delay: Duration::ZERO }
+  }
+
+  /// Check a query's estimated cost is within this budget.
+  pub fn check(&self, cost: QueryCost) -> Result<(), SeraiError> {
+    if self.chunk_size == 0 {
+      Err(SeraiError::BudgetExceeded("budget had a chunk size of zero".to_string()))?;
+    }
+    if let Some(max_calls) = self.max_calls {
+      if cost.calls > max_calls {
+        Err(SeraiError::BudgetExceeded(format!(
+          "estimated {} RPC calls, exceeding the limit of {max_calls}",
+          cost.calls
+        )))?;
+      }
+    }
+    if let Some(max_bytes) = self.max_bytes {
+      if cost.bytes > max_bytes {
+        Err(SeraiError::BudgetExceeded(format!(
+          "estimated {} bytes, exceeding the limit of {max_bytes}",
+          cost.bytes
+        )))?;
+      }
+    }
+    Ok(())
+  }
+}
@@ -1,12 +1,19 @@
-use scale::Encode;
+use core::time::Duration;
+use std::collections::VecDeque;
+
+use scale::{Encode, Decode};
+
+use futures_util::{stream, Stream};
+
+use patchable_async_sleep::sleep;
 
 use serai_abi::primitives::{SeraiAddress, Amount, Coin, Balance};
 pub use serai_abi::coins::primitives;
 use primitives::OutInstructionWithBalance;
 
-use crate::{TemporalSerai, SeraiError};
+use crate::{TemporalSerai, SeraiError, Serai};
 
-const PALLET: &str = "Coins";
+pub(crate) const PALLET: &str = "Coins";
 
 pub type CoinsEvent = serai_abi::coins::Event;
 
@@ -47,10 +54,42 @@ impl<'a> SeraiCoins<'a> {
       .await
   }
 
+  /// The burns with instructions within this block, with the index of the extrinsic which made
+  /// each.
+  pub async fn burn_with_instruction_events_by_extrinsic(
+    &self,
+  ) -> Result<Vec<(u32, CoinsEvent)>, SeraiError> {
+    self
+      .0
+      .event_records(|record| {
+        let frame_system::Phase::ApplyExtrinsic(index) = record.phase else { return None };
+        if let serai_abi::Event::Coins(event @ CoinsEvent::BurnWithInstruction { .. }) =
+          &record.event
+        {
+          Some((index, event.clone()))
+        } else {
+          None
+        }
+      })
+      .await
+  }
+
   pub async fn coin_supply(&self, coin: Coin) -> Result<Amount, SeraiError> {
     Ok(self.0.storage(PALLET, "Supply", coin).await?.unwrap_or(Amount(0)))
   }
 
+  /// The total amount of a coin ever minted.
+  pub async fn coin_minted(&self, coin: Coin) -> Result<u128, SeraiError> {
+    Ok(self.0.storage(PALLET, "Minted", coin).await?.unwrap_or(0))
+  }
+
+  /// The total amount of a coin ever burned.
+  ///
+  /// The supply of a coin is the amount minted minus the amount burned.
+  pub async fn coin_burned(&self, coin: Coin) -> Result<u128, SeraiError> {
+    Ok(self.0.storage(PALLET, "Burned", coin).await?.unwrap_or(0))
+  }
+
   pub async fn coin_balance(
     &self,
     coin: Coin,
@@ -69,6 +108,22 @@ impl<'a> SeraiCoins<'a> {
     )
   }
 
+  /// The balances of a coin for several addresses.
+  ///
+  /// This batches the queries made, returning the balances in the same order as the addresses.
+  pub async fn coin_balances(
+    &self,
+    coin: Coin,
+    addresses: &[SeraiAddress],
+  ) -> Result<Vec<Amount>, SeraiError> {
+    let keys = addresses
+      .iter()
+      .map(|address| (sp_core::hashing::blake2_128(&address.encode()), address.0, coin))
+      .collect();
+    let balances: Vec<Option<Amount>> = self.0.storage_multi(PALLET, "Balances", keys).await?;
+    Ok(balances.into_iter().map(|balance| balance.unwrap_or(Amount(0))).collect())
+  }
+
   pub fn transfer(to: SeraiAddress, balance: Balance) -> serai_abi::Call {
     serai_abi::Call::Coins(serai_abi::coins::Call::transfer { to, balance })
   }
@@ -81,3 +136,145 @@ impl<'a> SeraiCoins<'a> {
     serai_abi::Call::Coins(serai_abi::coins::Call::burn_with_instruction { instruction })
   }
 }
+
+// The address and coin a key within the Balances map is for
+fn balance_key(key: &[u8]) -> Option<(SeraiAddress, Coin)> {
+  // The key is the storage prefix, the Blake2-128 hash of the address, the address, and the coin
+  let mut key = key.get((16 + 16 + 16) ..)?;
+  let address = <[u8; 32]>::decode(&mut key).ok()?;
+  let coin = Coin::decode(&mut key).ok()?;
+  key.is_empty().then_some((SeraiAddress(address), coin))
+}
+
+// If an event may have changed an address's balance of a coin
+fn changes_balance(event: &serai_abi::Event, coin: Coin, address: SeraiAddress) -> bool {
+  match event {
+    serai_abi::Event::Coins(event) => match event {
+      CoinsEvent::Mint { to, balance } => (*to == address) && (balance.coin == coin),
+      CoinsEvent::Burn { from, balance } => (*from == address) && (balance.coin == coin),
+      CoinsEvent::BurnWithInstruction { from, instruction } => {
+        (*from == address) && (Coin::from(instruction.balance.coin) == coin)
+      }
+      CoinsEvent::Transfer { from, to, balance } => {
+        ((*from == address) || (*to == address)) && (balance.coin == coin)
+      }
+    },
+    // Fees are paid in SRI
+    serai_abi::Event::TransactionPayment(
+      serai_abi::TransactionPaymentEvent::TransactionFeePaid { who, .. },
+    ) => (*who == address) && (coin == Coin::Serai),
+    _ => false,
+  }
+}
+
+impl Serai {
+  /// A stream of every address's balance of a coin, as of the specified block.
+  ///
+  /// Balances are fetched a page at a time and yielded in the order they're stored in, which is
+  /// effectively random. Addresses without a balance of the coin aren't yielded. If an error is
+  /// yielded, the stream ends.
+  pub fn snapshot_balances(
+    &self,
+    coin: Coin,
+    block: [u8; 32],
+  ) -> impl Stream<Item = Result<(SeraiAddress, Amount), SeraiError>> + '_ {
+    // The last key fetched and the balances yet to be yielded, or `None` once every key has been
+    // fetched
+    stream::unfold(Some((None, VecDeque::new())), move |state| async move {
+      let (mut start, mut pending): (Option<Vec<u8>>, VecDeque<_>) = state?;
+      loop {
+        if let Some(balance) = pending.pop_front() {
+          return Some((Ok(balance), Some((start, pending))));
+        }
+
+        let serai = self.as_of(block);
+        let keys = match serai.storage_keys_page(PALLET, "Balances", start.as_ref()).await {
+          Ok(keys) => keys,
+          Err(e) => return Some((Err(e), None)),
+        };
+        if keys.is_empty() {
+          return None;
+        }
+        start = keys.last().cloned();
+
+        let (owners, keys): (Vec<_>, Vec<_>) = keys
+          .into_iter()
+          .filter_map(|key| {
+            let (address, key_coin) = balance_key(&key)?;
+            (key_coin == coin).then_some((address, key))
+          })
+          .unzip();
+        let amounts = match serai.raw_storage_batch::<Amount>(keys).await {
+          Ok(amounts) => amounts,
+          Err(e) => return Some((Err(e), None)),
+        };
+        pending.extend(
+          owners
+            .into_iter()
+            .zip(amounts)
+            .filter_map(|(address, amount)| Some((address, amount?)))
+            .filter(|(_, amount)| amount.0 != 0),
+        );
+      }
+    })
+  }
+  /// A stream of an address's balance of a coin, yielded whenever it changes.
+  ///
+  /// The balance as of the latest finalized block is yielded first, with the number of the block.
+  /// Then, as blocks are finalized, the balance is yielded with the number of every block which
+  /// changed it. Solely blocks with events which may have changed the balance have the balance
+  /// fetched. If an error is yielded, the stream will retry the same block after `poll_interval`.
+  pub fn subscribe_balance(
+    &self,
+    coin: Coin,
+    address: SeraiAddress,
+    poll_interval: Duration,
+  ) -> impl Stream<Item = Result<(u64, Amount), SeraiError>> + '_ {
+    // The next block to check, the number of the latest finalized block, the last balance yielded,
+    // and if the last attempt errored
+    let state = (None, 0, None, false);
+    stream::unfold(state, move |(mut next, mut finalized, mut last, errored)| async move {
+      if errored {
+        sleep(poll_interval).await;
+      }
+      loop {
+        let Some(number) = next.filter(|next| *next <= finalized) else {
+          finalized = match self.latest_finalized_block_number().await {
+            Ok(latest) => latest,
+            Err(e) => return Some((Err(e), (next, finalized, last, true))),
+          };
+          match next {
+            Some(next) if next > finalized => sleep(poll_interval).await,
+            Some(_) => {}
+            None => next = Some(finalized),
+          }
+          continue;
+        };
+
+        let balance = async {
+          let Some(hash) = self.block_hash(number).await? else {
+            Err(SeraiError::InvalidNode("node didn't have a finalized block's hash".to_string()))?
+          };
+          let serai = self.as_of(hash);
+          if last.is_some() {
+            let changes = serai.events(|event| changes_balance(event, coin, address).then_some(()));
+            if changes.await?.is_empty() {
+              return Ok(None);
+            }
+          }
+          serai.coins().coin_balance(coin, address).await.map(Some)
+        };
+        match balance.await {
+          Ok(balance) => {
+            next = Some(number + 1);
+            if let Some(balance) = balance.filter(|balance| Some(*balance) != last) {
+              last = Some(balance);
+              return Some((Ok((number, balance)), (next, finalized, last, false)));
+            }
+          }
+          Err(e) => return Some((Err(e), (next, finalized, last, true))),
+        }
+      }
+    })
+  }
+}
@@ -1,11 +1,83 @@
+use core::time::Duration;
+use std::collections::HashMap;
+
+use scale::{Encode, Decode};
+
+use futures_util::{future, Stream, StreamExt};
+
 use sp_core::bounded_vec::BoundedVec;
-use serai_abi::primitives::{Amount, Coin, ExternalCoin, SeraiAddress};
+use sp_runtime::{DispatchError, ModuleError};
+use serai_abi::{
+  primitives::{Amount, Coin, ExternalCoin, SeraiAddress},
+  system::Event as SystemEvent,
+};
 
-use crate::{SeraiError, TemporalSerai};
+use crate::{
+  Block, Transaction, Pair, PairTrait, ApplyExtrinsicResult, DecodedDispatchError, SeraiError,
+  TemporalSerai,
+};
 
 pub type DexEvent = serai_abi::dex::Event;
+pub type DexError = serai_abi::dex::Error;
+pub use serai_abi::dex::FeeConversion;
 
 const PALLET: &str = "Dex";
+// The index of the DEX pallet within the runtime, which identifies its errors
+pub(crate) const PALLET_INDEX: u8 = 5;
+
+/// How often `subscribe_events` polls for newly finalized blocks.
+const BLOCK_POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+/// The fee liquidity providers take from every swap, in 10ths of a percent.
+///
+/// This mirrors the runtime's configured `LPFee`.
+pub const LP_FEE: u64 = 3;
+
+/// The value of a liquidity position.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PositionValue {
+  /// The amount of liquidity tokens held.
+  pub lp_tokens: Amount,
+  /// The total amount of liquidity tokens issued for the pool.
+  pub total_issuance: Amount,
+  /// The amount of the external coin the position redeems for.
+  pub coin: Amount,
+  /// The amount of SRI the position redeems for.
+  pub sri: Amount,
+}
+
+impl PositionValue {
+  /// The share of the pool this position represents, as a percentage.
+  ///
+  /// This is truncated to a precision of 0.0001%.
+  pub fn share_percentage(&self) -> f64 {
+    if self.total_issuance.0 == 0 {
+      return 0.0;
+    }
+    let parts_per_million =
+      (u128::from(self.lp_tokens.0) * 1_000_000) / u128::from(self.total_issuance.0);
+    f64::from(u32::try_from(parts_per_million).unwrap_or(u32::MAX)) / 10_000.0
+  }
+}
+
+/// The outcome of a swap included on-chain.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum SwapOutcome {
+  /// The swap was executed, with the amounts realized.
+  Executed { path: Vec<Coin>, amount_in: Amount, amount_out: Amount },
+  /// The swap failed to execute, with its error decoded against the runtime's metadata.
+  Failed { error: DecodedDispatchError, dex_error: Option<DexError> },
+}
+
+impl SwapOutcome {
+  /// The DEX error the swap failed with, if it failed due to the DEX.
+  pub fn dex_error(&self) -> Option<DexError> {
+    match self {
+      SwapOutcome::Executed { .. } => None,
+      SwapOutcome::Failed { dex_error, .. } => *dex_error,
+    }
+  }
+}
 
 #[derive(Clone, Copy)]
 pub struct SeraiDex<'a>(pub(crate) &'a TemporalSerai<'a>);
@@ -19,6 +91,121 @@ impl<'a> SeraiDex<'a> {
       .await
   }
 
+  /// Decode a DEX error from a dispatch error.
+  ///
+  /// Returns `None` if the dispatch error wasn't from the DEX pallet.
+  pub fn decode_error(error: &DispatchError) -> Option<DexError> {
+    let DispatchError::Module(ModuleError { index, error, .. }) = error else { return None };
+    if *index != PALLET_INDEX {
+      return None;
+    }
+    DexError::decode(&mut error.as_slice()).ok()
+  }
+
+  /// The DEX errors which caused extrinsics within this block to fail.
+  pub async fn errors(&self) -> Result<Vec<DexError>, SeraiError> {
+    self
+      .0
+      .events(|event| {
+        if let serai_abi::Event::System(SystemEvent::ExtrinsicFailed { dispatch_error, .. }) = event
+        {
+          Self::decode_error(dispatch_error)
+        } else {
+          None
+        }
+      })
+      .await
+  }
+
+  /// If an event involves the specified account, as its instigator, recipient, or the pool.
+  pub fn event_involves_account(event: &DexEvent, address: SeraiAddress) -> bool {
+    match event {
+      DexEvent::PoolCreated { pool_account, .. } => *pool_account == address,
+      DexEvent::LiquidityAdded { who, mint_to, .. } => (*who == address) || (*mint_to == address),
+      DexEvent::LiquidityRemoved { who, withdraw_to, .. } => {
+        (*who == address) || (*withdraw_to == address)
+      }
+      DexEvent::SwapExecuted { who, send_to, .. } => (*who == address) || (*send_to == address),
+    }
+  }
+
+  /// If an event involves the pool for the specified coin.
+  ///
+  /// Swaps involve every pool along their path.
+  pub fn event_involves_pool(event: &DexEvent, coin: ExternalCoin) -> bool {
+    match event {
+      DexEvent::PoolCreated { pool_id, .. } |
+      DexEvent::LiquidityAdded { pool_id, .. } |
+      DexEvent::LiquidityRemoved { pool_id, .. } => *pool_id == coin,
+      DexEvent::SwapExecuted { path, .. } => path.contains(&Coin::External(coin)),
+    }
+  }
+
+  async fn filtered_events(
+    &self,
+    filter: impl Fn(&DexEvent) -> bool,
+  ) -> Result<Vec<DexEvent>, SeraiError> {
+    self
+      .0
+      .events(|event| {
+        if let serai_abi::Event::Dex(event) = event {
+          if filter(event) {
+            Some(event.clone())
+          } else {
+            None
+          }
+        } else {
+          None
+        }
+      })
+      .await
+  }
+
+  /// The events involving the specified account.
+  pub async fn events_for(&self, address: SeraiAddress) -> Result<Vec<DexEvent>, SeraiError> {
+    self.filtered_events(|event| Self::event_involves_account(event, address)).await
+  }
+
+  /// The events involving the pool for the specified coin.
+  pub async fn events_for_pool(&self, coin: ExternalCoin) -> Result<Vec<DexEvent>, SeraiError> {
+    self.filtered_events(|event| Self::event_involves_pool(event, coin)).await
+  }
+
+  pub async fn swap_events(&self) -> Result<Vec<DexEvent>, SeraiError> {
+    self.filtered_events(|event| matches!(event, DexEvent::SwapExecuted { .. })).await
+  }
+
+  pub async fn liquidity_added_events(&self) -> Result<Vec<DexEvent>, SeraiError> {
+    self.filtered_events(|event| matches!(event, DexEvent::LiquidityAdded { .. })).await
+  }
+
+  pub async fn liquidity_removed_events(&self) -> Result<Vec<DexEvent>, SeraiError> {
+    self.filtered_events(|event| matches!(event, DexEvent::LiquidityRemoved { .. })).await
+  }
+
+  /// A stream of the DEX events within each block finalized after this block.
+  ///
+  /// Blocks are yielded in order, with blocks without any DEX events skipped.
+  pub async fn subscribe_events(
+    &self,
+  ) -> Result<impl Stream<Item = Result<(Block, Vec<DexEvent>), SeraiError>> + 'a, SeraiError> {
+    let serai = self.0.serai;
+    let Some(header) = serai.header(self.0.block).await? else {
+      Err(SeraiError::InvalidNode("subscribing to events after a missing block".to_string()))?
+    };
+
+    Ok(
+      serai
+        .finalized_blocks(header.number + 1, BLOCK_POLL_INTERVAL)
+        .then(move |block| async move {
+          let block = block?;
+          let events = serai.as_of(block.hash()).dex().events().await?;
+          Ok((block, events))
+        })
+        .filter(|res| future::ready(!matches!(res, Ok((_, events)) if events.is_empty()))),
+    )
+  }
+
   pub fn add_liquidity(
     coin: ExternalCoin,
     coin_amount: Amount,
@@ -37,27 +224,201 @@ impl<'a> SeraiDex<'a> {
     })
   }
 
+  /// Burn `lp_token_burn` of the `coin:SRI` pool's liquidity tokens, withdrawing at least the
+  /// specified amounts of the coin and SRI.
+  pub fn remove_liquidity(
+    coin: ExternalCoin,
+    lp_token_burn: Amount,
+    min_coin_amount: Amount,
+    min_sri_amount: Amount,
+    address: SeraiAddress,
+  ) -> serai_abi::Call {
+    serai_abi::Call::Dex(serai_abi::dex::Call::remove_liquidity {
+      coin,
+      lp_token_burn: lp_token_burn.0,
+      coin_min_receive: min_coin_amount.0,
+      sri_min_receive: min_sri_amount.0,
+      withdraw_to: address,
+    })
+  }
+
+  /// The path used to swap `from_coin` to `to_coin`, routed through SRI.
+  fn swap_path(from_coin: Coin, to_coin: Coin) -> Vec<Coin> {
+    if to_coin.is_native() {
+      vec![from_coin, Coin::Serai]
+    } else if from_coin.is_native() {
+      vec![Coin::Serai, to_coin]
+    } else {
+      vec![from_coin, Coin::Serai, to_coin]
+    }
+  }
+
+  /// Swap `amount_in` of `from_coin` for at least `amount_out_min` of `to_coin`.
+  ///
+  /// As a transaction may remain pending until the price has moved, swaps should generally be
+  /// signed with `Serai::sign_with_deadline`.
+  ///
+  /// Returns `None` if there's no path to swap along, as when `from_coin` is `to_coin`.
   pub fn swap(
     from_coin: Coin,
     to_coin: Coin,
     amount_in: Amount,
     amount_out_min: Amount,
     address: SeraiAddress,
-  ) -> serai_abi::Call {
-    let path = if to_coin.is_native() {
-      BoundedVec::try_from(vec![from_coin, Coin::Serai]).unwrap()
-    } else if from_coin.is_native() {
-      BoundedVec::try_from(vec![Coin::Serai, to_coin]).unwrap()
-    } else {
-      BoundedVec::try_from(vec![from_coin, Coin::Serai, to_coin]).unwrap()
-    };
+  ) -> Option<serai_abi::Call> {
+    Self::swap_with_path(Self::swap_path(from_coin, to_coin), amount_in, amount_out_min, address)
+  }
+
+  /// If a path is one the DEX will accept for a swap.
+  ///
+  /// This requires the path have at least two coins and only route through existent pool pairs
+  /// (`coin:SRI`) without repeating any pool. As every pool is with SRI, no such path has more than
+  /// three coins.
+  pub fn is_valid_swap_path(path: &[Coin]) -> bool {
+    if path.len() < 2 {
+      return false;
+    }
 
-    serai_abi::Call::Dex(serai_abi::dex::Call::swap_exact_tokens_for_tokens {
-      path,
+    let mut pools = vec![];
+    for pair in path.windows(2) {
+      let pool = match (pair[0], pair[1]) {
+        (Coin::External(coin), Coin::Serai) | (Coin::Serai, Coin::External(coin)) => coin,
+        _ => return false,
+      };
+      if pools.contains(&pool) {
+        return false;
+      }
+      pools.push(pool);
+    }
+    true
+  }
+
+  /// Swap along an arbitrary path.
+  ///
+  /// Returns `None` if the path is invalid, as defined by `is_valid_swap_path`.
+  pub fn swap_with_path(
+    path: Vec<Coin>,
+    amount_in: Amount,
+    amount_out_min: Amount,
+    address: SeraiAddress,
+  ) -> Option<serai_abi::Call> {
+    if !Self::is_valid_swap_path(&path) {
+      return None;
+    }
+    Some(serai_abi::Call::Dex(serai_abi::dex::Call::swap_exact_tokens_for_tokens {
+      path: BoundedVec::try_from(path).ok()?,
       amount_in: amount_in.0,
       amount_out_min: amount_out_min.0,
       send_to: address,
-    })
+    }))
+  }
+
+  /// Reduce `amount` by a slippage tolerance, specified in basis points.
+  ///
+  /// Tolerances exceeding 100% are treated as 100%.
+  pub fn apply_slippage(amount: Amount, max_slippage_bps: u16) -> Amount {
+    let max_slippage_bps = u128::from(max_slippage_bps.min(10_000));
+    let res = (u128::from(amount.0) * (10_000 - max_slippage_bps)) / 10_000;
+    Amount(u64::try_from(res).unwrap())
+  }
+
+  /// Build a swap whose minimum amount out is the current quote, reduced by the tolerated slippage
+  /// (in basis points).
+  ///
+  /// Returns `None` if the swap couldn't be quoted, such as due to a pool lacking liquidity or
+  /// there being no path to swap along.
+  pub async fn swap_with_slippage(
+    &self,
+    from_coin: Coin,
+    to_coin: Coin,
+    amount_in: Amount,
+    max_slippage_bps: u16,
+    address: SeraiAddress,
+  ) -> Result<Option<serai_abi::Call>, SeraiError> {
+    let Some(quote) = self.quote_amount_out(from_coin, to_coin, amount_in).await? else {
+      return Ok(None);
+    };
+    let amount_out_min = Self::apply_slippage(quote, max_slippage_bps);
+    Ok(Self::swap(from_coin, to_coin, amount_in, amount_out_min, address))
+  }
+
+  /// Build a call adding liquidity to the `coin:SRI` pool from solely `coin`.
+  ///
+  /// Half of `amount` is sold for SRI, with the SRI received and the rest of `amount` then added
+  /// as liquidity, atomically (via a batch). The minimum amounts deposited are set such that at
+  /// least `min_lp` liquidity tokens are minted. As the swap moves the pool's price, some of either
+  /// `coin` or the SRI received won't be deposited, and is left with the caller. The swap is only
+  /// allowed to execute at the current quote, with the entire batch failing otherwise.
+  ///
+  /// Returns `None` if the pool lacks liquidity or `min_lp` isn't achievable at the current
+  /// reserves.
+  pub async fn zap_add_liquidity(
+    &self,
+    coin: ExternalCoin,
+    amount: Amount,
+    min_lp: Amount,
+    address: SeraiAddress,
+  ) -> Result<Option<serai_abi::Call>, SeraiError> {
+    let Some(reserves) = self.reserves(coin).await? else { return Ok(None) };
+    let lp_supply = self.lp_total_issuance(coin).await?;
+    Ok(Self::zap_add_liquidity_with_reserves(coin, amount, min_lp, address, reserves, lp_supply))
+  }
+
+  /// `zap_add_liquidity`, with the pool's reserves (as `(coin, SRI)`) and total supply of liquidity
+  /// tokens specified.
+  pub fn zap_add_liquidity_with_reserves(
+    coin: ExternalCoin,
+    amount: Amount,
+    min_lp: Amount,
+    address: SeraiAddress,
+    (coin_reserve, sri_reserve): (Amount, Amount),
+    lp_supply: Amount,
+  ) -> Option<serai_abi::Call> {
+    if lp_supply.0 == 0 {
+      return None;
+    }
+
+    let swapped = Amount(amount.0 / 2);
+    let sri = Self::get_amount_out(swapped, coin_reserve, sri_reserve)?;
+    let coin_desired = u128::from(amount.0 - swapped.0);
+
+    // The reserves once the swap executes
+    let coin_reserve = u128::from(coin_reserve.0) + u128::from(swapped.0);
+    let sri_reserve = u128::from(sri_reserve.0).checked_sub(u128::from(sri.0))?;
+    if sri_reserve == 0 {
+      return None;
+    }
+    let lp_supply = u128::from(lp_supply.0);
+
+    // The amounts which will be deposited, mirroring the DEX's `add_liquidity`
+    let coin_optimal = (u128::from(sri.0) * coin_reserve) / sri_reserve;
+    let (coin_deposited, sri_deposited) = if coin_optimal <= coin_desired {
+      (coin_optimal, u128::from(sri.0))
+    } else {
+      (coin_desired, (coin_desired * sri_reserve) / coin_reserve)
+    };
+    let lp =
+      ((sri_deposited * lp_supply) / sri_reserve).min((coin_deposited * lp_supply) / coin_reserve);
+    if lp < u128::from(min_lp.0) {
+      return None;
+    }
+
+    // Depositing at least these amounts will mint at least `min_lp` liquidity tokens
+    let min_deposit = |reserve: u128| {
+      Amount(u64::try_from((u128::from(min_lp.0) * reserve).div_ceil(lp_supply)).unwrap())
+    };
+
+    Some(crate::Serai::batch(vec![
+      Self::swap(coin.into(), Coin::Serai, swapped, sri, address)?,
+      Self::add_liquidity(
+        coin,
+        Amount(u64::try_from(coin_desired).unwrap()),
+        sri,
+        min_deposit(coin_reserve),
+        min_deposit(sri_reserve),
+        address,
+      ),
+    ]))
   }
 
   /// Returns the reserves of `coin:SRI` pool.
@@ -68,7 +429,374 @@ impl<'a> SeraiDex<'a> {
     self.0.runtime_api("DexApi_get_reserves", (Coin::from(coin), Coin::Serai)).await
   }
 
+  /// The account holding the liquidity of the `coin:SRI` pool.
+  pub fn pool_account(coin: ExternalCoin) -> SeraiAddress {
+    // This mirrors `Pallet::get_pool_account`, which decodes the hash as the account ID
+    SeraiAddress(sp_core::hashing::blake2_256(&coin.encode()))
+  }
+
+  /// If the `coin:SRI` pool has been created.
+  pub async fn pool_exists(&self, coin: ExternalCoin) -> Result<bool, SeraiError> {
+    let created: Option<()> =
+      self.0.storage(PALLET, "Pools", (sp_core::hashing::blake2_128(&coin.encode()), coin)).await?;
+    Ok(created.is_some())
+  }
+
+  /// Returns the reserves of the `coin:SRI` pool, as `(coin, SRI)`, read from storage.
+  ///
+  /// Returns `None` if the pool hasn't been created or either side of it is empty.
+  pub async fn reserves(&self, coin: ExternalCoin) -> Result<Option<(Amount, Amount)>, SeraiError> {
+    if !self.pool_exists(coin).await? {
+      return Ok(None);
+    }
+
+    let pool = Self::pool_account(coin);
+    let coins = self.0.coins();
+    let coin_reserve = coins.coin_balance(coin.into(), pool).await?;
+    let sri_reserve = coins.coin_balance(Coin::Serai, pool).await?;
+    if (coin_reserve.0 == 0) || (sri_reserve.0 == 0) {
+      return Ok(None);
+    }
+    Ok(Some((coin_reserve, sri_reserve)))
+  }
+
+  /// Returns the reserves of the pools for several coins, as `(coin, SRI)`, read from storage.
+  ///
+  /// This batches the queries made, returning the reserves in the same order as the coins. Pools
+  /// which don't exist or are empty on either side are `None`, as with `reserves`.
+  pub async fn reserves_multi(
+    &self,
+    coins: &[ExternalCoin],
+  ) -> Result<Vec<Option<(Amount, Amount)>>, SeraiError> {
+    let pools = coins.iter().map(|coin| (sp_core::hashing::blake2_128(&coin.encode()), *coin));
+    let created: Vec<Option<()>> = self.0.storage_multi(PALLET, "Pools", pools.collect()).await?;
+
+    let mut keys = vec![];
+    for coin in coins {
+      let account = Self::pool_account(*coin);
+      for coin in [Coin::from(*coin), Coin::Serai] {
+        keys.push((sp_core::hashing::blake2_128(&account.encode()), account.0, coin));
+      }
+    }
+    let balances: Vec<Option<u64>> =
+      self.0.storage_multi(crate::coins::PALLET, "Balances", keys).await?;
+
+    Ok(
+      created
+        .into_iter()
+        .zip(balances.chunks(2))
+        .map(|(created, balances)| {
+          created?;
+          let (coin_reserve, sri_reserve) = (balances[0]?, balances[1]?);
+          ((coin_reserve != 0) && (sri_reserve != 0))
+            .then_some((Amount(coin_reserve), Amount(sri_reserve)))
+        })
+        .collect(),
+    )
+  }
+
+  /// Returns the reserves of every pool, as `(coin, SRI)`, read from storage.
+  ///
+  /// Pools which are empty on either side are omitted.
+  pub async fn all_reserves(&self) -> Result<HashMap<ExternalCoin, (Amount, Amount)>, SeraiError> {
+    let mut pools = vec![];
+    for key in self.0.storage_keys(PALLET, "Pools").await? {
+      // The key is the storage prefix, the Blake2-128 hash of the pool ID, and the pool ID itself
+      let pool = key
+        .get((16 + 16 + 16) ..)
+        .and_then(|mut pool| ExternalCoin::decode(&mut pool).ok())
+        .ok_or_else(|| SeraiError::InvalidNode("returned an invalid key for a pool".to_string()))?;
+      pools.push(pool);
+    }
+
+    let mut keys = vec![];
+    for pool in &pools {
+      let account = Self::pool_account(*pool);
+      for coin in [Coin::from(*pool), Coin::Serai] {
+        keys.push((sp_core::hashing::blake2_128(&account.encode()), account.0, coin));
+      }
+    }
+    let balances: Vec<Option<u64>> =
+      self.0.storage_multi(crate::coins::PALLET, "Balances", keys).await?;
+
+    let mut res = HashMap::new();
+    for (pool, balances) in pools.into_iter().zip(balances.chunks(2)) {
+      let (Some(coin_reserve), Some(sri_reserve)) = (balances[0], balances[1]) else { continue };
+      if (coin_reserve == 0) || (sri_reserve == 0) {
+        continue;
+      }
+      res.insert(pool, (Amount(coin_reserve), Amount(sri_reserve)));
+    }
+    Ok(res)
+  }
+
+  /// Dry-run a call signed by `signer`, using their nonce as of this block.
+  ///
+  /// If the call fails, any DEX error, such as the slippage tolerance being exceeded or an
+  /// insufficient balance, can be recovered via `decode_error`.
+  pub async fn dry_run(
+    &self,
+    call: serai_abi::Call,
+    signer: &Pair,
+  ) -> Result<ApplyExtrinsicResult, SeraiError> {
+    let nonce = self.0.nonce(signer.public().into()).await?;
+    self.0.dry_run(&self.0.serai.sign(signer, call, nonce, 0)).await
+  }
+
+  // The outcome of a swap, or the error it failed with, from the events its extrinsic emitted
+  fn swap_outcome(
+    events: &[serai_abi::Event],
+  ) -> Result<Result<SwapOutcome, DispatchError>, SeraiError> {
+    for event in events {
+      match event {
+        serai_abi::Event::Dex(DexEvent::SwapExecuted { path, amount_in, amount_out, .. }) => {
+          return Ok(Ok(SwapOutcome::Executed {
+            path: path.to_vec(),
+            amount_in: Amount(*amount_in),
+            amount_out: Amount(*amount_out),
+          }));
+        }
+        serai_abi::Event::System(SystemEvent::ExtrinsicFailed { dispatch_error, .. }) => {
+          return Ok(Err(*dispatch_error));
+        }
+        _ => {}
+      }
+    }
+    Err(SeraiError::InvalidNode("included swap neither executed nor failed".to_string()))
+  }
+
+  /// Publish a swap, then watch the finalized blocks after this block for its inclusion.
+  ///
+  /// Returns the block the swap was included in with its outcome, or `None` if it wasn't included
+  /// within the next `blocks` finalized blocks.
+  pub async fn publish_swap(
+    &self,
+    tx: &Transaction,
+    blocks: u64,
+  ) -> Result<Option<(Block, SwapOutcome)>, SeraiError> {
+    let serai = self.0.serai;
+    let Some(header) = serai.header(self.0.block).await? else {
+      Err(SeraiError::InvalidNode("publishing a swap after a missing block".to_string()))?
+    };
+    serai.publish(tx).await?;
+
+    let mut finalized =
+      core::pin::pin!(serai.finalized_blocks(header.number + 1, BLOCK_POLL_INTERVAL));
+    for _ in 0 .. blocks {
+      let Some(block) = finalized.next().await else { break };
+      let block = block?;
+      let Some(index) = block.transactions.iter().position(|included| included == tx) else {
+        continue;
+      };
+      let events =
+        serai.as_of(block.hash()).extrinsic_events(u32::try_from(index).unwrap()).await?;
+      let outcome = match Self::swap_outcome(&events)? {
+        Ok(outcome) => outcome,
+        // The runtime which executed the block is the runtime as of its parent
+        Err(error) => SwapOutcome::Failed {
+          error: serai.error_registry(block.header.parent_hash.0).await?.decode(&error),
+          dex_error: Self::decode_error(&error),
+        },
+      };
+      return Ok(Some((block, outcome)));
+    }
+    Ok(None)
+  }
+
+  /// The amount of liquidity tokens `address` holds for the `coin:SRI` pool.
+  pub async fn lp_balance(
+    &self,
+    coin: ExternalCoin,
+    address: SeraiAddress,
+  ) -> Result<Amount, SeraiError> {
+    self.0.liquidity_tokens().token_balance(coin, address).await
+  }
+
+  /// The total amount of liquidity tokens issued for the `coin:SRI` pool.
+  pub async fn lp_total_issuance(&self, coin: ExternalCoin) -> Result<Amount, SeraiError> {
+    self.0.liquidity_tokens().token_supply(coin).await
+  }
+
+  /// Calculate the amounts redeemed for removing `lp_tokens` of liquidity from a pool with the
+  /// specified total issuance and `(coin, SRI)` reserves.
+  ///
+  /// This mirrors the DEX pallet's `remove_liquidity`, returning `None` where it would error.
+  pub fn redeem_value(
+    lp_tokens: Amount,
+    total_issuance: Amount,
+    reserves: (Amount, Amount),
+  ) -> Option<(Amount, Amount)> {
+    let mul_div = |amount: Amount| {
+      let res = u128::from(lp_tokens.0)
+        .checked_mul(u128::from(amount.0))?
+        .checked_div(u128::from(total_issuance.0))?;
+      u64::try_from(res).ok().map(Amount)
+    };
+    Some((mul_div(reserves.0)?, mul_div(reserves.1)?))
+  }
+
+  /// The value of the liquidity position `address` holds in the `coin:SRI` pool.
+  ///
+  /// Returns `None` if the pool doesn't exist.
+  pub async fn position_value(
+    &self,
+    coin: ExternalCoin,
+    address: SeraiAddress,
+  ) -> Result<Option<PositionValue>, SeraiError> {
+    let Some(reserves) = self.reserves(coin).await? else { return Ok(None) };
+    let lp_tokens = self.lp_balance(coin, address).await?;
+    let total_issuance = self.lp_total_issuance(coin).await?;
+    let (coin, sri) = Self::redeem_value(lp_tokens, total_issuance, reserves).ok_or_else(|| {
+      SeraiError::InvalidNode("liquidity position's value overflowed".to_string())
+    })?;
+    Ok(Some(PositionValue { lp_tokens, total_issuance, coin, sri }))
+  }
+
+  /// Calculate the amount received for selling `amount_in` into a pool with the specified
+  /// reserves.
+  ///
+  /// This mirrors the DEX pallet's `get_amount_out`, returning `None` where it would error.
+  pub fn get_amount_out(
+    amount_in: Amount,
+    reserve_in: Amount,
+    reserve_out: Amount,
+  ) -> Option<Amount> {
+    let amount_in = u128::from(amount_in.0);
+    let reserve_in = u128::from(reserve_in.0);
+    let reserve_out = u128::from(reserve_out.0);
+
+    if (reserve_in == 0) || (reserve_out == 0) {
+      return None;
+    }
+
+    let amount_in_with_fee = amount_in.checked_mul(u128::from(1000 - LP_FEE))?;
+    let numerator = amount_in_with_fee.checked_mul(reserve_out)?;
+    let denominator = reserve_in.checked_mul(1000)?.checked_add(amount_in_with_fee)?;
+    u64::try_from(numerator / denominator).ok().map(Amount)
+  }
+
+  /// Calculate the amount which has to be sold into a pool with the specified reserves in order to
+  /// receive `amount_out`.
+  ///
+  /// This mirrors the DEX pallet's `get_amount_in`, returning `None` where it would error.
+  pub fn get_amount_in(
+    amount_out: Amount,
+    reserve_in: Amount,
+    reserve_out: Amount,
+  ) -> Option<Amount> {
+    let amount_out = u128::from(amount_out.0);
+    let reserve_in = u128::from(reserve_in.0);
+    let reserve_out = u128::from(reserve_out.0);
+
+    if (reserve_in == 0) || (reserve_out == 0) || (amount_out >= reserve_out) {
+      return None;
+    }
+
+    let numerator = reserve_in.checked_mul(amount_out)?.checked_mul(1000)?;
+    let denominator = (reserve_out - amount_out).checked_mul(u128::from(1000 - LP_FEE))?;
+    u64::try_from((numerator / denominator).checked_add(1)?).ok().map(Amount)
+  }
+
+  /// Returns the amount of `to_coin` which would be received for selling `amount_in` of
+  /// `from_coin`, following the same route as `swap`.
+  ///
+  /// Each hop is quoted by the runtime itself, ensuring the quote uses the exact on-chain formula
+  /// (even after a runtime upgrade changes it).
+  ///
+  /// Returns `None` if a pool along the route lacks liquidity or the swap would fail.
+  pub async fn quote_amount_out(
+    &self,
+    from_coin: Coin,
+    to_coin: Coin,
+    amount_in: Amount,
+  ) -> Result<Option<Amount>, SeraiError> {
+    if from_coin == to_coin {
+      return Ok(None);
+    }
+
+    let mut amount = amount_in;
+    for pair in Self::swap_path(from_coin, to_coin).windows(2) {
+      let amount_out: Option<u64> = self
+        .0
+        .runtime_api(
+          "DexApi_quote_price_exact_tokens_for_tokens",
+          (pair[0], pair[1], amount.0, true),
+        )
+        .await?;
+      let Some(amount_out) = amount_out else { return Ok(None) };
+      amount = Amount(amount_out);
+    }
+    Ok(Some(amount))
+  }
+
+  /// Returns the amount of `from_coin` which would have to be sold to receive `amount_out` of
+  /// `to_coin`, following the same route as `swap`.
+  ///
+  /// Each hop is quoted by the runtime itself, ensuring the quote uses the exact on-chain formula
+  /// (even after a runtime upgrade changes it).
+  ///
+  /// Returns `None` if a pool along the route lacks liquidity or the swap would fail.
+  pub async fn quote_amount_in(
+    &self,
+    from_coin: Coin,
+    to_coin: Coin,
+    amount_out: Amount,
+  ) -> Result<Option<Amount>, SeraiError> {
+    if from_coin == to_coin {
+      return Ok(None);
+    }
+
+    let mut amount = amount_out;
+    for pair in Self::swap_path(from_coin, to_coin).windows(2).rev() {
+      let amount_in: Option<u64> = self
+        .0
+        .runtime_api(
+          "DexApi_quote_price_tokens_for_exact_tokens",
+          (pair[0], pair[1], amount.0, true),
+        )
+        .await?;
+      let Some(amount_in) = amount_in else { return Ok(None) };
+      amount = Amount(amount_in);
+    }
+    Ok(Some(amount))
+  }
+
+  /// Returns how much of `coin` has to be sold to acquire `fee` SRI.
+  ///
+  /// This is intended to help set the `max_coin_in` of a `FeeConversion`. As only the signer's
+  /// shortfall is converted, this is an upper bound on the amount actually sold (as of the
+  /// current state).
+  pub async fn quote_fee_conversion(
+    &self,
+    coin: ExternalCoin,
+    fee: Amount,
+  ) -> Result<Option<Amount>, SeraiError> {
+    let amount: Option<u64> = self
+      .0
+      .runtime_api(
+        "DexApi_quote_price_tokens_for_exact_tokens",
+        (Coin::from(coin), Coin::Serai, fee.0, true),
+      )
+      .await?;
+    Ok(amount.map(Amount))
+  }
+
   pub async fn oracle_value(&self, coin: ExternalCoin) -> Result<Option<Amount>, SeraiError> {
     self.0.storage(PALLET, "SecurityOracleValue", coin).await
   }
+
+  /// The spot price the oracle recorded for `coin` as of this block, in SRI per whole coin.
+  ///
+  /// A whole coin is considered to be `10 ** max(decimals, 5)` atomic units.
+  pub async fn oracle_price(&self, coin: ExternalCoin) -> Result<Option<Amount>, SeraiError> {
+    let Some(header) = self.0.serai.header(self.0.block).await? else {
+      Err(SeraiError::InvalidNode("couldn't get the header for this block".to_string()))?
+    };
+    self.0.storage(PALLET, "SpotPriceForBlock", (header.number, coin)).await
+  }
+
+  /// The median of the oracle's recent spot prices for `coin`, in SRI per whole coin.
+  pub async fn median_price(&self, coin: ExternalCoin) -> Result<Option<Amount>, SeraiError> {
+    self.0.storage(PALLET, "MedianPrice", coin).await
+  }
 }
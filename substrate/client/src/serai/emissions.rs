@@ -0,0 +1,208 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use sp_core::sr25519::Public;
+
+use frame_system::Phase;
+
+pub use serai_abi::emissions::primitives;
+use primitives::{INITIAL_REWARD_PER_BLOCK, REWARD_PER_BLOCK};
+
+use crate::{
+  primitives::{Amount, NetworkId, ExternalNetworkId, EXTERNAL_NETWORKS, SeraiAddress, MONTHS},
+  validator_sets::{primitives::Session, ValidatorSetsEvent},
+  SeraiError, TemporalSerai,
+};
+
+const PALLET: &str = "Emissions";
+
+// The length of the initial period, for runtimes built without the `fast-epoch` feature
+const INITIAL_PERIOD: u64 = 2 * MONTHS;
+
+/// A reward paid to a validator for a session, which is staked to the network they validated.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+pub struct RewardPayout {
+  pub validator: SeraiAddress,
+  pub network: NetworkId,
+  pub amount: Amount,
+}
+
+/// The phase of emissions.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize)]
+pub enum EmissionPhase {
+  /// Genesis liquidity has yet to complete, so nothing is emitted.
+  Genesis,
+  /// The initial period after genesis, which has a fixed reward per block.
+  Initial,
+  /// An external network has yet to reach economic security, so rewards are set by the stake
+  /// still required.
+  PreEconomicSecurity,
+  /// Every external network has reached economic security, so rewards have a fixed reward per
+  /// block.
+  PostEconomicSecurity,
+}
+
+/// The current parameters of the emission schedule.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+pub struct EmissionSchedule {
+  pub phase: EmissionPhase,
+  /// The block genesis liquidity completed in, after which rewards are emitted.
+  pub genesis_complete_block: Option<u64>,
+  /// The block each external network reached economic security in, if it has.
+  pub economic_security_blocks: HashMap<ExternalNetworkId, u64>,
+}
+
+impl EmissionSchedule {
+  /// The reward emitted per block, if fixed for the current phase.
+  ///
+  /// Rewards are emitted once a session ends, for every block within the session.
+  pub fn reward_per_block(&self) -> Option<Amount> {
+    match self.phase {
+      EmissionPhase::Genesis | EmissionPhase::PreEconomicSecurity => None,
+      EmissionPhase::Initial => Some(Amount(INITIAL_REWARD_PER_BLOCK)),
+      EmissionPhase::PostEconomicSecurity => Some(Amount(REWARD_PER_BLOCK)),
+    }
+  }
+}
+
+#[derive(Clone, Copy)]
+pub struct SeraiEmissions<'a>(pub(crate) &'a TemporalSerai<'a>);
+impl<'a> SeraiEmissions<'a> {
+  /// The session of a network which rewards were last distributed for.
+  pub async fn session(&self, network: NetworkId) -> Result<Session, SeraiError> {
+    Ok(Session(self.0.storage(PALLET, "CurrentSession", network).await?.unwrap_or(0)))
+  }
+
+  /// The validators who'll be rewarded when the current session ends, with their allocations.
+  pub async fn participants(
+    &self,
+    network: NetworkId,
+  ) -> Result<Option<Vec<(Public, u64)>>, SeraiError> {
+    self.0.storage(PALLET, "Participants", network).await
+  }
+
+  /// The share of a network's rewards a validator will receive when the current session ends, as
+  /// their score and the total score of the network's validators.
+  ///
+  /// A validator's score is their allocation, discounted by half of the allocation which didn't
+  /// earn a key share. Returns `None` if the validator won't be rewarded.
+  pub async fn pending_reward_share(
+    &self,
+    network: NetworkId,
+    validator: SeraiAddress,
+  ) -> Result<Option<(u64, u64)>, SeraiError> {
+    let Some(participants) = self.participants(network).await? else { return Ok(None) };
+    let Some(per_share) = self.0.validator_sets().allocation_per_key_share(network).await? else {
+      return Ok(None);
+    };
+    if per_share.0 == 0 {
+      return Ok(None);
+    }
+
+    let score = |amount: u64| amount - ((amount % per_share.0) / 2);
+    let total =
+      participants.iter().fold(0u64, |total, (_, amount)| total.saturating_add(score(*amount)));
+    Ok(
+      participants
+        .iter()
+        .find(|(participant, _)| SeraiAddress::from(*participant) == validator)
+        .map(|(_, amount)| (score(*amount), total)),
+    )
+  }
+
+  /// The current parameters of the emission schedule.
+  ///
+  /// The initial period is assumed to be that of a runtime built without the `fast-epoch`
+  /// feature.
+  pub async fn schedule(&self) -> Result<EmissionSchedule, SeraiError> {
+    let genesis_complete_block = self.0.genesis_liquidity().genesis_complete_block().await?;
+
+    let mut economic_security_blocks = HashMap::new();
+    for network in EXTERNAL_NETWORKS {
+      let block = self.0.storage("EconomicSecurity", "EconomicSecurityBlock", network).await?;
+      if let Some(block) = block {
+        economic_security_blocks.insert(network, block);
+      }
+    }
+
+    let phase = match genesis_complete_block {
+      None => EmissionPhase::Genesis,
+      Some(genesis_complete_block) => {
+        let Some(header) = self.0.serai.header(self.0.block).await? else {
+          Err(SeraiError::InvalidNode("fetching the schedule as of a missing block".to_string()))?
+        };
+        if header.number < (genesis_complete_block + INITIAL_PERIOD) {
+          EmissionPhase::Initial
+        } else if economic_security_blocks.len() < EXTERNAL_NETWORKS.len() {
+          EmissionPhase::PreEconomicSecurity
+        } else {
+          EmissionPhase::PostEconomicSecurity
+        }
+      }
+    };
+
+    Ok(EmissionSchedule { phase, genesis_complete_block, economic_security_blocks })
+  }
+
+  /// The rewards paid for a session of the Serai network, if they've been paid.
+  ///
+  /// Rewards for every network are paid when the Serai network's session ends, as allocations to
+  /// the network each validator validated.
+  pub async fn session_rewards(
+    &self,
+    session: Session,
+  ) -> Result<Option<Vec<RewardPayout>>, SeraiError> {
+    let Some(block) =
+      self.0.validator_sets().session_begin_block(NetworkId::Serai, Session(session.0 + 1)).await?
+    else {
+      return Ok(None);
+    };
+    let Some(hash) = self.0.serai.block_hash(block).await? else {
+      Err(SeraiError::InvalidNode("node didn't have the block rewards were paid in".to_string()))?
+    };
+
+    // Allocations made when initializing a block are solely made for rewards
+    let payouts = self
+      .0
+      .serai
+      .as_of(hash)
+      .event_records(|record| {
+        if record.phase != Phase::Initialization {
+          return None;
+        }
+        let serai_abi::Event::ValidatorSets(ValidatorSetsEvent::AllocationIncreased {
+          validator,
+          network,
+          amount,
+        }) = &record.event
+        else {
+          return None;
+        };
+        Some(RewardPayout { validator: *validator, network: *network, amount: *amount })
+      })
+      .await?;
+    Ok(Some(payouts))
+  }
+
+  /// The rewards paid to a validator for the sessions of the Serai network from `from` through
+  /// `to`, inclusive.
+  ///
+  /// Sessions whose rewards haven't been paid are skipped.
+  pub async fn accrued_rewards(
+    &self,
+    validator: SeraiAddress,
+    from: Session,
+    to: Session,
+  ) -> Result<Amount, SeraiError> {
+    let mut res = Amount(0);
+    for session in from.0 ..= to.0 {
+      for payout in self.session_rewards(Session(session)).await?.unwrap_or_default() {
+        if payout.validator == validator {
+          res = Amount(res.0.saturating_add(payout.amount.0));
+        }
+      }
+    }
+    Ok(res)
+  }
+}
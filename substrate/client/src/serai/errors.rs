@@ -0,0 +1,123 @@
+use core::fmt;
+use std::{sync::Arc, collections::HashMap};
+
+use sp_runtime::{DispatchError, ModuleError};
+
+use crate::{
+  metadata::{decode_metadata, variants},
+  Transaction, SeraiError, Serai, TemporalSerai,
+};
+
+/// An error from a pallet, as described by the runtime's metadata.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct PalletError {
+  /// The name of the pallet.
+  pub pallet: String,
+  /// The name of the error variant.
+  pub error: String,
+  /// The documentation of the error variant.
+  pub docs: Vec<String>,
+}
+
+/// A dispatch error, with errors from pallets decoded against the runtime's metadata.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum DecodedDispatchError {
+  Pallet(PalletError),
+  /// A dispatch error which wasn't from a pallet, or which wasn't present in the metadata.
+  Other(DispatchError),
+}
+
+impl fmt::Display for DecodedDispatchError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      DecodedDispatchError::Pallet(PalletError { pallet, error, docs }) => {
+        write!(f, "{pallet}::{error}")?;
+        if !docs.is_empty() {
+          write!(f, ": {}", docs.join(" "))?;
+        }
+        Ok(())
+      }
+      DecodedDispatchError::Other(error) => write!(f, "{error:?}"),
+    }
+  }
+}
+
+/// The errors of every pallet within a runtime, by pallet index and error index.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct ErrorRegistry(HashMap<(u8, u8), PalletError>);
+
+impl ErrorRegistry {
+  /// Build the registry from SCALE-encoded runtime metadata.
+  pub fn from_metadata(metadata: &[u8]) -> Result<ErrorRegistry, SeraiError> {
+    let (pallets, types, _) = decode_metadata(metadata)?;
+
+    let mut errors = HashMap::new();
+    for pallet in pallets {
+      for variant in variants(&types, pallet.errors) {
+        errors.insert(
+          (pallet.index, variant.index),
+          PalletError {
+            pallet: pallet.name.clone(),
+            error: variant.name.clone(),
+            docs: variant.docs.clone(),
+          },
+        );
+      }
+    }
+    Ok(ErrorRegistry(errors))
+  }
+
+  /// Decode a dispatch error.
+  pub fn decode(&self, error: &DispatchError) -> DecodedDispatchError {
+    if let DispatchError::Module(ModuleError { index, error: bytes, .. }) = error {
+      if let Some(error) = self.0.get(&(*index, bytes[0])) {
+        return DecodedDispatchError::Pallet(error.clone());
+      }
+    }
+    DecodedDispatchError::Other(*error)
+  }
+}
+
+impl Serai {
+  /// The error registry for the runtime as of the specified block.
+  ///
+  /// This is cached per runtime version.
+  pub async fn error_registry(&self, block: [u8; 32]) -> Result<Arc<ErrorRegistry>, SeraiError> {
+    let spec_version = self.runtime_version(block).await?.spec_version;
+    if let Some((cached_version, registry)) = self.errors.read().await.as_ref() {
+      if *cached_version == spec_version {
+        return Ok(registry.clone());
+      }
+    }
+
+    let registry = Arc::new(ErrorRegistry::from_metadata(&self.metadata(block).await?)?);
+    *self.errors.write().await = Some((spec_version, registry.clone()));
+    Ok(registry)
+  }
+
+  /// Decode a dispatch error, such as one from an `ExtrinsicFailed` event, against the metadata
+  /// of the runtime as of the latest finalized block.
+  pub async fn decode_dispatch_error(
+    &self,
+    error: &DispatchError,
+  ) -> Result<DecodedDispatchError, SeraiError> {
+    Ok(self.error_registry(self.latest_finalized_block_hash().await?).await?.decode(error))
+  }
+}
+
+impl<'a> TemporalSerai<'a> {
+  /// Dry-run a transaction on top of this block, returning the decoded error it'd fail with.
+  ///
+  /// If the transaction is invalid, as opposed to failing, `SeraiError::ErrorInResponse` is
+  /// returned.
+  pub async fn dry_run_dispatch(
+    &self,
+    tx: &Transaction,
+  ) -> Result<Result<(), DecodedDispatchError>, SeraiError> {
+    match self.dry_run(tx).await? {
+      Ok(Ok(())) => Ok(Ok(())),
+      Ok(Err(error)) => Ok(Err(self.serai.error_registry(self.block).await?.decode(&error))),
+      Err(error) => Err(SeraiError::ErrorInResponse(format!("transaction was invalid: {error:?}"))),
+    }
+  }
+}
@@ -0,0 +1,145 @@
+use core::time::Duration;
+
+use futures_util::{stream, Stream, StreamExt, TryStreamExt};
+use patchable_async_sleep::sleep;
+
+use serde::Serialize;
+
+use serai_abi::Event;
+
+use crate::{
+  coins::CoinsEvent, dex::DexEvent, in_instructions::InInstructionsEvent,
+  validator_sets::ValidatorSetsEvent, SeraiError, Serai,
+};
+
+// The amount of blocks within each page yielded by `events_between`
+const EVENTS_PAGE_SIZE: u64 = 32;
+// The amount of blocks `events_between` fetches the events of at once
+const EVENTS_CONCURRENCY: usize = 8;
+
+/// An event from any of Serai's pallets.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+pub enum SeraiEvent {
+  Coins(CoinsEvent),
+  Dex(DexEvent),
+  InInstructions(InInstructionsEvent),
+  ValidatorSets(ValidatorSetsEvent),
+  /// An event from any other pallet.
+  Other(Event),
+}
+
+impl From<Event> for SeraiEvent {
+  fn from(event: Event) -> SeraiEvent {
+    match event {
+      Event::Coins(event) => SeraiEvent::Coins(event),
+      Event::Dex(event) => SeraiEvent::Dex(event),
+      Event::InInstructions(event) => SeraiEvent::InInstructions(event),
+      Event::ValidatorSets(event) => SeraiEvent::ValidatorSets(event),
+      event => SeraiEvent::Other(event),
+    }
+  }
+}
+
+/// The events within a finalized block.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+pub struct BlockEvents {
+  pub number: u64,
+  pub hash: [u8; 32],
+  pub events: Vec<SeraiEvent>,
+}
+
+impl Serai {
+  /// A stream of the events within every finalized block, starting with the block with the
+  /// specified number.
+  ///
+  /// Every block is yielded, even if it has no events, letting consumers track their progress. As
+  /// with `finalized_blocks`, this polls every `poll_interval`, and if an error is yielded, the
+  /// stream will retry the same block after `poll_interval`.
+  pub fn all_events_stream(
+    &self,
+    start: u64,
+    poll_interval: Duration,
+  ) -> impl Stream<Item = Result<BlockEvents, SeraiError>> + '_ {
+    stream::unfold((start, false), move |(next, errored)| async move {
+      if errored {
+        sleep(poll_interval).await;
+      }
+      let block = loop {
+        match self.finalized_block_by_number(next).await {
+          Ok(Some(block)) => break block,
+          Ok(None) => sleep(poll_interval).await,
+          Err(e) => return Some((Err(e), (next, true))),
+        }
+      };
+      let hash = block.hash();
+      match self.as_of(hash).events(|event| Some(SeraiEvent::from(event.clone()))).await {
+        Ok(events) => Some((Ok(BlockEvents { number: next, hash, events }), (next + 1, false))),
+        Err(e) => Some((Err(e), (next, true))),
+      }
+    })
+  }
+
+  async fn filtered_block_events(
+    &self,
+    number: u64,
+    filter: &impl Fn(&SeraiEvent) -> bool,
+  ) -> Result<BlockEvents, SeraiError> {
+    let Some(hash) = self.block_hash(number).await? else {
+      Err(SeraiError::InvalidNode("node didn't have a finalized block's hash".to_string()))?
+    };
+    let events = self
+      .as_of(hash)
+      .events(|event| {
+        let event = SeraiEvent::from(event.clone());
+        filter(&event).then_some(event)
+      })
+      .await?;
+    Ok(BlockEvents { number, hash, events })
+  }
+
+  /// The events within the finalized blocks numbered `from` through `to`, inclusive, which pass
+  /// the filter.
+  ///
+  /// Events are yielded in pages of up to 32 blocks, with up to 8 blocks' events fetched at once.
+  /// Every block within the range is yielded, in order, even if none of its events pass the
+  /// filter. If the range isn't entirely finalized, an error is yielded before any events. If an
+  /// error is yielded, the stream ends, and the caller may resume from the first block not yet
+  /// yielded.
+  pub fn events_between<'a>(
+    &'a self,
+    from: u64,
+    to: u64,
+    filter: impl 'a + Fn(&SeraiEvent) -> bool,
+  ) -> impl Stream<Item = Result<Vec<BlockEvents>, SeraiError>> + 'a {
+    stream::unfold(Some((from, filter)), move |state| async move {
+      let (next, filter) = state?;
+      if next > to {
+        return None;
+      }
+
+      if next == from {
+        match self.latest_finalized_block_number().await {
+          Ok(latest) if latest < to => {
+            return Some((
+              Err(SeraiError::ErrorInResponse(format!("block {to} isn't finalized"))),
+              None,
+            ))
+          }
+          Ok(_) => {}
+          Err(e) => return Some((Err(e), None)),
+        }
+      }
+
+      let end = next.saturating_add(EVENTS_PAGE_SIZE - 1).min(to);
+      let page = stream::iter(next ..= end)
+        .map(|number| self.filtered_block_events(number, &filter))
+        .buffered(EVENTS_CONCURRENCY)
+        .try_collect::<Vec<_>>()
+        .await;
+      match page {
+        Ok(page) => Some((Ok(page), end.checked_add(1).map(|next| (next, filter)))),
+        Err(e) => Some((Err(e), None)),
+      }
+    })
+  }
+}
@@ -0,0 +1,135 @@
+use core::any::Any;
+use std::{sync::Arc, collections::HashMap};
+
+use scale::{Decode, Compact};
+
+use frame_system::Phase;
+use serai_abi::{Call, Event};
+
+/// A value decoded by an extension's decoder.
+pub type Decoded = Box<dyn Send + Sync + Any>;
+
+type Decoder = Arc<dyn Send + Sync + Fn(&mut &[u8]) -> Result<Decoded, scale::Error>>;
+
+/// An event, from either one of Serai's pallets or a registered extension pallet.
+#[derive(Debug)]
+pub enum ExtendedEvent {
+  Serai(Event),
+  Extension { pallet: u8, event: Decoded },
+}
+
+impl ExtendedEvent {
+  /// The event, if it's from the specified extension pallet and of the registered type.
+  pub fn extension<E: 'static>(&self, pallet: u8) -> Option<&E> {
+    match self {
+      ExtendedEvent::Serai(_) => None,
+      ExtendedEvent::Extension { pallet: event_pallet, event } => {
+        if *event_pallet != pallet {
+          return None;
+        }
+        event.downcast_ref()
+      }
+    }
+  }
+}
+
+/// A call, to either one of Serai's pallets or a registered extension pallet.
+#[derive(Debug)]
+pub enum ExtendedCall {
+  Serai(Call),
+  Extension { pallet: u8, call: Decoded },
+}
+
+impl ExtendedCall {
+  /// The call, if it's to the specified extension pallet and of the registered type.
+  pub fn extension<C: 'static>(&self, pallet: u8) -> Option<&C> {
+    match self {
+      ExtendedCall::Serai(_) => None,
+      ExtendedCall::Extension { pallet: call_pallet, call } => {
+        if *call_pallet != pallet {
+          return None;
+        }
+        call.downcast_ref()
+      }
+    }
+  }
+}
+
+/// A registry of decoders for pallets which Serai's runtime doesn't have, as added by downstream
+/// runtimes.
+///
+/// Runtime events and calls are encoded as the index of their pallet followed by the pallet's own
+/// event/call. Decoders are registered per pallet index and are passed the bytes following it.
+/// Registered decoders take precedence over Serai's own pallets.
+#[derive(Clone, Default)]
+pub struct Extensions {
+  events: HashMap<u8, Decoder>,
+  calls: HashMap<u8, Decoder>,
+}
+
+fn decoder<T: 'static + Send + Sync + Decode>() -> Decoder {
+  Arc::new(|bytes: &mut &[u8]| Ok(Box::new(T::decode(bytes)?) as Decoded))
+}
+
+impl Extensions {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Register the type of the events emitted by the pallet with the specified index.
+  pub fn register_event<E: 'static + Send + Sync + Decode>(&mut self, pallet: u8) -> &mut Self {
+    self.events.insert(pallet, decoder::<E>());
+    self
+  }
+
+  /// Register the type of the calls to the pallet with the specified index.
+  pub fn register_call<C: 'static + Send + Sync + Decode>(&mut self, pallet: u8) -> &mut Self {
+    self.calls.insert(pallet, decoder::<C>());
+    self
+  }
+
+  // The decoder for the pallet these bytes are for, if it's an extension pallet
+  //
+  // If a decoder is returned, the pallet index is consumed from the bytes.
+  fn extension<'a>(
+    decoders: &'a HashMap<u8, Decoder>,
+    bytes: &mut &[u8],
+  ) -> Option<(u8, &'a Decoder)> {
+    let pallet = *bytes.first()?;
+    let decoder = decoders.get(&pallet)?;
+    *bytes = &bytes[1 ..];
+    Some((pallet, decoder))
+  }
+
+  /// Decode an event, using the registered decoders for extension pallets.
+  pub fn decode_event(&self, bytes: &mut &[u8]) -> Result<ExtendedEvent, scale::Error> {
+    if let Some((pallet, decoder)) = Self::extension(&self.events, bytes) {
+      return Ok(ExtendedEvent::Extension { pallet, event: decoder(bytes)? });
+    }
+    Event::decode(bytes).map(ExtendedEvent::Serai)
+  }
+
+  /// Decode a call, using the registered decoders for extension pallets.
+  pub fn decode_call(&self, bytes: &mut &[u8]) -> Result<ExtendedCall, scale::Error> {
+    if let Some((pallet, decoder)) = Self::extension(&self.calls, bytes) {
+      return Ok(ExtendedCall::Extension { pallet, call: decoder(bytes)? });
+    }
+    Call::decode(bytes).map(ExtendedCall::Serai)
+  }
+
+  /// Decode the events within a block, as stored by the System pallet.
+  pub fn decode_event_records(&self, mut bytes: &[u8]) -> Result<Vec<ExtendedEvent>, scale::Error> {
+    let len = Compact::<u32>::decode(&mut bytes)?.0;
+    let mut res = vec![];
+    for _ in 0 .. len {
+      Phase::decode(&mut bytes)?;
+      res.push(self.decode_event(&mut bytes)?);
+      // The topics
+      Vec::<[u8; 32]>::decode(&mut bytes)?;
+    }
+    if !bytes.is_empty() {
+      Err("trailing bytes after the event records")?;
+    }
+    Ok(res)
+  }
+}
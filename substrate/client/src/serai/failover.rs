@@ -0,0 +1,74 @@
+use core::sync::atomic::Ordering;
+
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::{primitives::Header, SeraiError, Serai};
+
+impl Serai {
+  /// The endpoints this client fails over between.
+  pub fn endpoints(&self) -> &[String] {
+    &self.urls
+  }
+
+  /// The endpoint currently used, if this client is backed by nodes.
+  pub fn current_endpoint(&self) -> Option<&str> {
+    self.urls.get(self.endpoint.load(Ordering::Relaxed)).map(String::as_str)
+  }
+
+  async fn call_endpoint<Res: DeserializeOwned>(
+    &self,
+    endpoint: usize,
+    method: &str,
+    params: impl Serialize,
+  ) -> Result<Res, SeraiError> {
+    if let Some(rate_limiter) = &self.rate_limiter {
+      rate_limiter.wait(method).await;
+    }
+    self.call_with_backoff(endpoint, &Self::request_body(method, params)).await
+  }
+
+  /// Check every endpoint agrees on the finalized chain, detecting a malicious or forked node.
+  ///
+  /// This fetches the hash of the finalized block of the endpoint furthest behind from every
+  /// endpoint, erroring with `SeraiError::InvalidNode` if any disagree. If any endpoint is
+  /// unreachable, its error is returned, as the check can't be completed.
+  pub async fn cross_check_endpoints(&self) -> Result<(), SeraiError> {
+    let mut lowest = u64::MAX;
+    for endpoint in 0 .. self.urls.len() {
+      let hash: String = self.call_endpoint(endpoint, "chain_getFinalizedHead", ()).await?;
+      let header: Option<Header> = self.call_endpoint(endpoint, "chain_getHeader", [hash]).await?;
+      let Some(header) = header else {
+        Err(SeraiError::InvalidNode(format!(
+          "{} didn't have its finalized block's header",
+          self.urls[endpoint]
+        )))?
+      };
+      lowest = lowest.min(header.number);
+    }
+
+    let mut expected = None;
+    for endpoint in 0 .. self.urls.len() {
+      let hash: Option<String> =
+        self.call_endpoint(endpoint, "chain_getBlockHash", [lowest]).await?;
+      let Some(hash) = hash else {
+        Err(SeraiError::InvalidNode(format!(
+          "{} didn't have a finalized block's hash",
+          self.urls[endpoint]
+        )))?
+      };
+      let hash = Self::hex_decode(hash)?;
+      match &expected {
+        None => expected = Some((endpoint, hash)),
+        Some((first, expected)) => {
+          if &hash != expected {
+            Err(SeraiError::InvalidNode(format!(
+              "{} and {} disagree on finalized block {lowest}",
+              self.urls[*first], self.urls[endpoint]
+            )))?;
+          }
+        }
+      }
+    }
+    Ok(())
+  }
+}
@@ -0,0 +1,50 @@
+use scale::{Encode, Decode};
+
+use sp_runtime::{generic::Era, Weight};
+
+use serai_abi::{Call, Extra};
+
+use crate::{SeraiAddress, Signature, Amount, Transaction, SeraiError, TemporalSerai};
+
+/// An estimate of the fee for a transaction.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct FeeEstimate {
+  /// The weight of the transaction's call.
+  pub weight: Weight,
+  /// The fee, in SRI, excluding any tip.
+  pub fee: Amount,
+}
+
+// The RuntimeDispatchInfo returned by TransactionPaymentApi_query_info
+#[derive(Decode)]
+struct RuntimeDispatchInfo {
+  weight: Weight,
+  // The DispatchClass, which is encoded as a single byte
+  _class: u8,
+  partial_fee: u64,
+}
+
+impl<'a> TemporalSerai<'a> {
+  /// Estimate the fee for the signer to publish a transaction with the specified call, as of this
+  /// block.
+  ///
+  /// This doesn't require the signer's key, letting the fee be shown before asking the user to
+  /// sign. The estimate may be inaccurate if the fee multiplier changes before the transaction is
+  /// included.
+  pub async fn estimate_fee(
+    &self,
+    call: Call,
+    signer: SeraiAddress,
+  ) -> Result<FeeEstimate, SeraiError> {
+    // The fee is dependent on the length of the transaction, yet not the validity of its
+    // signature, so a placeholder signature is used
+    let nonce = self.nonce(signer).await?;
+    let extra = Extra { era: Era::Immortal, nonce, fee_conversion: None, tip: 0 };
+    let tx = Transaction::new(call, Some((signer, Signature::from_raw([0; 64]), extra)));
+    let len = u32::try_from(tx.encode().len()).unwrap();
+
+    let info: RuntimeDispatchInfo =
+      self.runtime_api("TransactionPaymentApi_query_info", (tx, len)).await?;
+    Ok(FeeEstimate { weight: info.weight, fee: Amount(info.partial_fee) })
+  }
+}
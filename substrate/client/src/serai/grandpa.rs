@@ -0,0 +1,214 @@
+use std::collections::{HashSet, HashMap};
+
+use scale::{Encode, Decode};
+
+use sp_core::{ed25519, Pair as _};
+use serai_abi::primitives::Header;
+
+use crate::{Serai, SeraiError, TemporalSerai};
+
+pub type GrandpaEvent = serai_abi::grandpa::Event;
+
+/// A GRANDPA authority set.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct AuthoritySet {
+  pub set_id: u64,
+  /// The authorities' Ed25519 keys, with their weights.
+  pub authorities: Vec<([u8; 32], u64)>,
+}
+
+impl AuthoritySet {
+  /// Apply the authority set changes from a block's GRANDPA events.
+  ///
+  /// Feeding this the events of every block, in order, tracks the authority set from a trusted
+  /// starting point.
+  pub fn apply(&mut self, events: &[GrandpaEvent]) {
+    for event in events {
+      if let GrandpaEvent::NewAuthorities { authority_set } = event {
+        self.set_id += 1;
+        self.authorities =
+          authority_set.iter().map(|(authority, weight)| (authority.0, *weight)).collect();
+      }
+    }
+  }
+
+  fn weight(&self, authority: &[u8; 32]) -> Option<u64> {
+    self.authorities.iter().find(|(candidate, _)| candidate == authority).map(|(_, weight)| *weight)
+  }
+
+  // The weight required for a commit to be valid, which is the weight of all authorities minus
+  // the weight which may be faulty
+  fn threshold(&self) -> u64 {
+    let total = self.authorities.iter().map(|(_, weight)| *weight).sum::<u64>();
+    total - (total.saturating_sub(1) / 3)
+  }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Encode, Decode)]
+pub struct Precommit {
+  pub target_hash: [u8; 32],
+  pub target_number: u64,
+}
+
+impl Precommit {
+  /// The message signed by an authority when precommitting.
+  pub fn signature_message(&self, round: u64, set_id: u64) -> Vec<u8> {
+    // This is the encoding of `finality_grandpa::Message::Precommit`, followed by the round and
+    // set ID
+    let mut res = vec![1];
+    self.encode_to(&mut res);
+    (round, set_id).encode_to(&mut res);
+    res
+  }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Encode, Decode)]
+pub struct SignedPrecommit {
+  pub precommit: Precommit,
+  pub signature: [u8; 64],
+  pub id: [u8; 32],
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Encode, Decode)]
+pub struct Commit {
+  pub target_hash: [u8; 32],
+  pub target_number: u64,
+  pub precommits: Vec<SignedPrecommit>,
+}
+
+/// A GRANDPA justification, a commit (and the headers needed to connect its precommits to its
+/// target).
+#[derive(Clone, PartialEq, Eq, Debug, Encode, Decode)]
+pub struct GrandpaJustification {
+  pub round: u64,
+  pub commit: Commit,
+  pub votes_ancestries: Vec<Header>,
+}
+
+/// A proof a block was finalized, as returned by the node.
+///
+/// The justification may be for a descendant of the block, in which case the headers connecting
+/// the block to the justified block are included.
+#[derive(Clone, PartialEq, Eq, Debug, Encode, Decode)]
+pub struct FinalityProof {
+  /// The hash of the block justified.
+  pub block: [u8; 32],
+  /// The encoded `GrandpaJustification`.
+  pub justification: Vec<u8>,
+  pub unknown_headers: Vec<Header>,
+}
+
+// If `block` is `base`, or a descendant of it, as connected by the provided headers
+fn descends_from(
+  headers: &HashMap<[u8; 32], &Header>,
+  base: [u8; 32],
+  mut block: [u8; 32],
+) -> bool {
+  loop {
+    if block == base {
+      return true;
+    }
+    let Some(header) = headers.get(&block) else { return false };
+    block = header.parent_hash.into();
+  }
+}
+
+impl FinalityProof {
+  /// Verify this proves the finality of the specified block under the specified authority set.
+  ///
+  /// The authority set MUST be the one which finalized the block, which is the set prior to any
+  /// authority set change enacted by the block.
+  pub fn verify(&self, block: [u8; 32], set: &AuthoritySet) -> Result<(), SeraiError> {
+    let invalid =
+      |reason: &str| Err(SeraiError::InvalidNode(format!("invalid finality proof: {reason}")));
+
+    let Ok(justification) = GrandpaJustification::decode(&mut self.justification.as_slice()) else {
+      return invalid("justification couldn't be decoded");
+    };
+    if justification.commit.target_hash != self.block {
+      return invalid("justification wasn't for the justified block");
+    }
+
+    // Connect the block to the justified block
+    let unknown_headers =
+      self.unknown_headers.iter().map(|header| (header.hash().into(), header)).collect();
+    if !descends_from(&unknown_headers, block, self.block) {
+      return invalid("justified block wasn't a descendant of the block");
+    }
+
+    let ancestries =
+      justification.votes_ancestries.iter().map(|header| (header.hash().into(), header)).collect();
+    let mut signers = HashSet::new();
+    let mut weight = 0u64;
+    for SignedPrecommit { precommit, signature, id } in &justification.commit.precommits {
+      let Some(authority_weight) = set.weight(id) else {
+        return invalid("precommit from a non-authority");
+      };
+      let message = precommit.signature_message(justification.round, set.set_id);
+      if !ed25519::Pair::verify(
+        &ed25519::Signature::from_raw(*signature),
+        message,
+        &ed25519::Public::from_raw(*id),
+      ) {
+        return invalid("precommit had an invalid signature");
+      }
+      if !descends_from(&ancestries, justification.commit.target_hash, precommit.target_hash) {
+        return invalid("precommit wasn't for a descendant of the commit's target");
+      }
+
+      // Authorities who equivocated are only counted once
+      if signers.insert(*id) {
+        weight += authority_weight;
+      }
+    }
+
+    if weight < set.threshold() {
+      return invalid("commit didn't have a supermajority of the authority set's weight");
+    }
+    Ok(())
+  }
+}
+
+impl Serai {
+  /// Fetch a proof the block with the specified number was finalized.
+  ///
+  /// This will return `None` if the block hasn't been finalized.
+  pub async fn finality_proof(&self, number: u64) -> Result<Option<FinalityProof>, SeraiError> {
+    let proof: Option<String> = self.call("grandpa_proveFinality", [number]).await?;
+    let Some(proof) = proof else { return Ok(None) };
+    let proof = Self::hex_decode(proof)?;
+    FinalityProof::decode(&mut proof.as_slice())
+      .map(Some)
+      .map_err(|_| SeraiError::InvalidNode("returned an invalid finality proof".to_string()))
+  }
+}
+
+#[derive(Clone, Copy)]
+pub struct SeraiGrandpa<'a>(pub(crate) &'a TemporalSerai<'a>);
+impl<'a> SeraiGrandpa<'a> {
+  pub async fn new_authorities_events(&self) -> Result<Vec<GrandpaEvent>, SeraiError> {
+    self
+      .0
+      .events(|event| {
+        if let serai_abi::Event::Grandpa(event) = event {
+          if matches!(event, GrandpaEvent::NewAuthorities { .. }) {
+            Some(event.clone())
+          } else {
+            None
+          }
+        } else {
+          None
+        }
+      })
+      .await
+  }
+
+  /// The authority set as of this block.
+  ///
+  /// This is the set which will finalize the next block.
+  pub async fn authority_set(&self) -> Result<AuthoritySet, SeraiError> {
+    let set_id = self.0.runtime_api("GrandpaApi_current_set_id", ()).await?;
+    let authorities = self.0.runtime_api("GrandpaApi_grandpa_authorities", ()).await?;
+    Ok(AuthoritySet { set_id, authorities })
+  }
+}
@@ -0,0 +1,75 @@
+use serde::{Serialize, Deserialize};
+
+use crate::{upgrades::Compatibility, RuntimeVersion, SeraiError, Serai};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SystemHealth {
+  peers: usize,
+  is_syncing: bool,
+  should_have_peers: bool,
+}
+
+/// The health of a node, as reported by it.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+pub struct NodeHealth {
+  /// The amount of peers the node is connected to.
+  pub peers: usize,
+  /// If the node is syncing with its peers, and accordingly behind the network.
+  pub syncing: bool,
+  /// If the node is expected to have peers, which isn't the case for development chains.
+  pub should_have_peers: bool,
+  /// The name of the chain the node is for.
+  pub chain: String,
+  /// The hash of the chain's genesis block.
+  pub genesis: [u8; 32],
+  /// The version of the runtime as of the latest finalized block.
+  pub runtime: RuntimeVersion,
+  /// The number of the node's best block.
+  pub best_block: u64,
+  /// The number of the node's latest finalized block.
+  pub finalized_block: u64,
+}
+
+impl NodeHealth {
+  /// The amount of blocks on the best chain which have yet to be finalized.
+  pub fn finalization_lag(&self) -> u64 {
+    self.best_block.saturating_sub(self.finalized_block)
+  }
+
+  /// If the node is synced, connected to peers if it should be, running a runtime this library is
+  /// compatible with, and has finalized all but at most `max_lag` blocks of its best chain.
+  pub fn is_healthy(&self, max_lag: u64) -> bool {
+    (!self.syncing) &&
+      ((self.peers != 0) || (!self.should_have_peers)) &&
+      (self.runtime.compatibility() == Compatibility::Compatible) &&
+      (self.finalization_lag() <= max_lag)
+  }
+}
+
+impl Serai {
+  /// The health of the node.
+  ///
+  /// This may be used to wait for a node to be synced before starting services which rely on it.
+  pub async fn health(&self) -> Result<NodeHealth, SeraiError> {
+    let SystemHealth { peers, is_syncing, should_have_peers } =
+      self.call("system_health", ()).await?;
+    let chain = self.call("system_chain", ()).await?;
+    let finalized = self.latest_finalized_block_hash().await?;
+    let Some(finalized_block) = self.header(finalized).await? else {
+      Err(SeraiError::InvalidNode("node didn't have its latest finalized block".to_string()))?
+    };
+    let runtime = self.runtime_version(finalized).await?;
+    let best_block = self.best_header().await?.number;
+    Ok(NodeHealth {
+      peers,
+      syncing: is_syncing,
+      should_have_peers,
+      chain,
+      genesis: self.genesis,
+      runtime,
+      best_block,
+      finalized_block: finalized_block.number,
+    })
+  }
+}
@@ -1,8 +1,16 @@
+use std::collections::HashMap;
+
+use scale::{Encode, Decode};
+
+use serde::Serialize;
+
+use sp_core::hashing::blake2_256;
+
 pub use serai_abi::in_instructions::primitives;
-use primitives::SignedBatch;
+use primitives::{InInstructionWithBalance, Batch, SignedBatch};
 
 use crate::{
-  primitives::{BlockHash, ExternalNetworkId},
+  primitives::{BlockHash, ExternalNetworkId, EXTERNAL_NETWORKS},
   Transaction, SeraiError, Serai, TemporalSerai,
 };
 
@@ -10,6 +18,95 @@ pub type InInstructionsEvent = serai_abi::in_instructions::Event;
 
 const PALLET: &str = "InInstructions";
 
+/// An instruction executed as part of a `Batch`.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+pub struct ExecutedInstruction {
+  pub instruction: InInstructionWithBalance,
+  /// If the instruction succeeded.
+  ///
+  /// Failed instructions are neither refunded nor retried by Serai.
+  pub succeeded: bool,
+}
+
+/// A `Batch` executed by Serai, with its instructions decoded.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+pub struct ExecutedBatch {
+  pub network: ExternalNetworkId,
+  pub id: u32,
+  /// The hash of the external network's block this `Batch` is for.
+  pub block: BlockHash,
+  pub instructions: Vec<ExecutedInstruction>,
+}
+
+/// A `Batch` published to Serai which has yet to be executed by a finalized block.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+pub struct PendingBatch {
+  pub network: ExternalNetworkId,
+  pub id: u32,
+  /// The hash of the external network's block this `Batch` is for.
+  pub block: BlockHash,
+  /// The hash of the block not yet finalized which included this `Batch`, if it's been included.
+  pub included: Option<[u8; 32]>,
+}
+
+// The `Batch` a transaction executes, if it executes one
+fn executed_batch(tx: &Transaction) -> Option<&Batch> {
+  match tx.call() {
+    serai_abi::Call::InInstructions(serai_abi::in_instructions::Call::execute_batch { batch }) => {
+      Some(&batch.batch)
+    }
+    _ => None,
+  }
+}
+
+impl Serai {
+  /// The `Batch`s published for a network which have yet to be executed by a finalized block,
+  /// ordered by their IDs.
+  ///
+  /// This includes `Batch`s within blocks which have yet to be finalized and `Batch`s within the
+  /// node's transaction pool. `Batch`s already executed, or which conflict with a `Batch`
+  /// included by a block, are skipped.
+  pub async fn pending_batches(
+    &self,
+    network: ExternalNetworkId,
+  ) -> Result<Vec<PendingBatch>, SeraiError> {
+    let finalized = self.latest_finalized_block().await?;
+    let next = self.as_of(finalized.hash()).in_instructions().next_batch(network).await?;
+
+    let mut res: Vec<PendingBatch> = vec![];
+    let mut push = |batch: &Batch, included| {
+      if (batch.network == network) &&
+        (batch.id >= next) &&
+        (!res.iter().any(|pending| pending.id == batch.id))
+      {
+        res.push(PendingBatch { network, id: batch.id, block: batch.block, included });
+      }
+    };
+
+    let best = self.best_header().await?.number;
+    for number in (finalized.number() + 1) ..= best {
+      // If the best chain was re-orged, the remaining blocks will no longer be present
+      let Some(hash) = self.block_hash(number).await? else { break };
+      let Some(block) = self.block(hash).await? else { break };
+      for batch in block.transactions.iter().filter_map(executed_batch) {
+        push(batch, Some(hash));
+      }
+    }
+
+    let pool: Vec<String> = self.call("author_pendingExtrinsics", ()).await?;
+    for tx in pool {
+      // Transactions in the pool which don't decode aren't ones we'd have published
+      let Ok(tx) = Transaction::decode(&mut Self::hex_decode(tx)?.as_slice()) else { continue };
+      if let Some(batch) = executed_batch(&tx) {
+        push(batch, None);
+      }
+    }
+
+    res.sort_by_key(|pending| pending.id);
+    Ok(res)
+  }
+}
+
 #[derive(Clone, Copy)]
 pub struct SeraiInInstructions<'a>(pub(crate) &'a TemporalSerai<'a>);
 impl<'a> SeraiInInstructions<'a> {
@@ -27,6 +124,38 @@ impl<'a> SeraiInInstructions<'a> {
     self.0.storage(PALLET, "LastBatch", network).await
   }
 
+  /// The ID of the next `Batch` expected to be executed for a network.
+  pub async fn next_batch(&self, network: ExternalNetworkId) -> Result<u32, SeraiError> {
+    Ok(self.last_batch_for_network(network).await?.map_or(0, |last| last + 1))
+  }
+
+  /// The number of the Serai block the last `Batch` for a network was executed in.
+  ///
+  /// Solely one `Batch` may be executed for a network per block.
+  pub async fn last_batch_block(
+    &self,
+    network: ExternalNetworkId,
+  ) -> Result<Option<u64>, SeraiError> {
+    self.0.storage(PALLET, "LastBatchBlock", network).await
+  }
+
+  /// The latest external block acknowledged for each network which has had a `Batch` executed.
+  pub async fn latest_blocks(&self) -> Result<HashMap<ExternalNetworkId, BlockHash>, SeraiError> {
+    let mut res = HashMap::new();
+    for network in EXTERNAL_NETWORKS {
+      if let Some(block) = self.latest_block_for_network(network).await? {
+        res.insert(network, block);
+      }
+    }
+    Ok(res)
+  }
+
+  /// If the network has been halted, with no further `Batch`s being accepted from it.
+  pub async fn halted(&self, network: ExternalNetworkId) -> Result<bool, SeraiError> {
+    let halted: Option<()> = self.0.storage(PALLET, "Halted", network).await?;
+    Ok(halted.is_some())
+  }
+
   pub async fn batch_events(&self) -> Result<Vec<InInstructionsEvent>, SeraiError> {
     self
       .0
@@ -44,6 +173,59 @@ impl<'a> SeraiInInstructions<'a> {
       .await
   }
 
+  /// The `Batch`s executed within this block, with their instructions.
+  ///
+  /// As the `Batch` events solely commit to the instructions, the instructions are recovered from
+  /// the block's transactions, with each `Batch` checked against its event.
+  pub async fn executed_batches(&self) -> Result<Vec<ExecutedBatch>, SeraiError> {
+    let events = self
+      .0
+      .events(|event| match event {
+        serai_abi::Event::InInstructions(event) => Some(event.clone()),
+        _ => None,
+      })
+      .await?;
+    if !events.iter().any(|event| matches!(event, InInstructionsEvent::Batch { .. })) {
+      return Ok(vec![]);
+    }
+
+    let Some(block) = self.0.serai.block(self.0.block).await? else {
+      Err(SeraiError::InvalidNode("node didn't have the block it had events for".to_string()))?
+    };
+    let batches = block.transactions.iter().filter_map(executed_batch);
+
+    let mut res = vec![];
+    for Batch { network, id, block, instructions } in batches {
+      let event = InInstructionsEvent::Batch {
+        network: *network,
+        id: *id,
+        block: *block,
+        instructions_hash: blake2_256(&instructions.encode()),
+      };
+      if !events.contains(&event) {
+        Err(SeraiError::InvalidNode(format!(
+          "block included batch {id} for {network:?} yet didn't emit an event for it"
+        )))?;
+      }
+
+      let instructions = instructions
+        .iter()
+        .enumerate()
+        .map(|(i, instruction)| {
+          let index = u32::try_from(i).unwrap();
+          let failed = events.contains(&InInstructionsEvent::InstructionFailure {
+            network: *network,
+            id: *id,
+            index,
+          });
+          ExecutedInstruction { instruction: instruction.clone(), succeeded: !failed }
+        })
+        .collect();
+      res.push(ExecutedBatch { network: *network, id: *id, block: *block, instructions });
+    }
+    Ok(res)
+  }
+
   pub fn execute_batch(batch: SignedBatch) -> Transaction {
     Serai::unsigned(serai_abi::Call::InInstructions(
       serai_abi::in_instructions::Call::execute_batch { batch },
@@ -0,0 +1,288 @@
+use core::time::Duration;
+
+use scale::{Encode, Decode};
+
+use patchable_async_sleep::sleep;
+
+use serde::Serialize;
+
+use frame_system::Phase;
+use serai_db::{Get, DbTxn, Db, create_db};
+
+use serai_abi::{
+  primitives::{ExternalNetworkId, SeraiAddress},
+  coins::Event as CoinsEvent,
+  liquidity_tokens::Event as LiquidityTokensEvent,
+  dex::Event as DexEvent,
+  validator_sets::Event as ValidatorSetsEvent,
+  genesis_liquidity::Event as GenesisLiquidityEvent,
+  in_instructions::Event as InInstructionsEvent,
+  signals::Event as SignalsEvent,
+  system, Event,
+};
+
+use crate::{Block, Transaction, SeraiError, Serai};
+
+// What a list of indexed events is for
+#[derive(Encode)]
+enum Topic {
+  Account(SeraiAddress),
+  Batches(ExternalNetworkId),
+}
+
+// An event, as stored: the number of the block it's within, its index within the block, the index
+// of the transaction which emitted it, and the event itself, SCALE-encoded
+type StoredEvent = (u64, u32, Option<u32>, Vec<u8>);
+// A transaction, as stored: the number of the block it's within, its index within the block, and
+// the transaction itself, SCALE-encoded
+type StoredTransaction = (u64, u32, Vec<u8>);
+
+create_db!(
+  SeraiIndexer {
+    NextBlock: () -> u64,
+    IndexedBlockHash: (number: u64) -> [u8; 32],
+    BlockEvents: (number: u64) -> Vec<StoredEvent>,
+    BlockTransactions: (number: u64) -> Vec<StoredTransaction>,
+    TopicEventsLen: (topic: &Topic) -> u64,
+    TopicEvent: (topic: &Topic, index: u64) -> StoredEvent,
+    AccountTransactionsLen: (account: SeraiAddress) -> u64,
+    AccountTransaction: (account: SeraiAddress, index: u64) -> StoredTransaction,
+  }
+);
+
+/// An event indexed by an `Indexer`.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+pub struct IndexedEvent {
+  /// The number of the block the event is within.
+  pub block: u64,
+  /// The index of the event within the block.
+  pub index: u32,
+  /// The index of the transaction which emitted the event, if it was emitted by a transaction.
+  pub transaction: Option<u32>,
+  pub event: Event,
+}
+
+/// A transaction indexed by an `Indexer`.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+pub struct IndexedTransaction {
+  /// The number of the block the transaction is within.
+  pub block: u64,
+  /// The index of the transaction within the block.
+  pub index: u32,
+  pub transaction: Transaction,
+}
+
+// The accounts involved in an event
+fn event_accounts(event: &Event) -> Vec<SeraiAddress> {
+  let mut res = match event {
+    Event::System(
+      system::Event::NewAccount { account } | system::Event::KilledAccount { account },
+    ) => vec![*account],
+    Event::System(system::Event::Remarked { sender, .. }) => vec![*sender],
+    Event::TransactionPayment(serai_abi::TransactionPaymentEvent::TransactionFeePaid {
+      who,
+      ..
+    }) => vec![*who],
+    Event::Coins(event) => match event {
+      CoinsEvent::Mint { to, .. } => vec![*to],
+      CoinsEvent::Burn { from, .. } | CoinsEvent::BurnWithInstruction { from, .. } => vec![*from],
+      CoinsEvent::Transfer { from, to, .. } => vec![*from, *to],
+    },
+    Event::LiquidityTokens(event) => match event {
+      LiquidityTokensEvent::Mint { to, .. } => vec![*to],
+      LiquidityTokensEvent::Burn { from, .. } => vec![*from],
+      LiquidityTokensEvent::Transfer { from, to, .. } => vec![*from, *to],
+    },
+    Event::Dex(event) => match event {
+      DexEvent::PoolCreated { pool_account, .. } => vec![*pool_account],
+      DexEvent::LiquidityAdded { who, mint_to, .. } => vec![*who, *mint_to],
+      DexEvent::LiquidityRemoved { who, withdraw_to, .. } => vec![*who, *withdraw_to],
+      DexEvent::SwapExecuted { who, send_to, .. } => vec![*who, *send_to],
+    },
+    Event::ValidatorSets(event) => match event {
+      ValidatorSetsEvent::ParticipantRemoved { removed, .. } => vec![*removed],
+      ValidatorSetsEvent::AllocationIncreased { validator, .. } |
+      ValidatorSetsEvent::AllocationDecreased { validator, .. } |
+      ValidatorSetsEvent::DeallocationClaimed { validator, .. } |
+      ValidatorSetsEvent::NetworkAddressSet { validator } => vec![*validator],
+      ValidatorSetsEvent::CompensationClaimed { account, .. } => vec![*account],
+      _ => vec![],
+    },
+    Event::GenesisLiquidity(
+      GenesisLiquidityEvent::GenesisLiquidityAdded { by, .. } |
+      GenesisLiquidityEvent::GenesisLiquidityRemoved { by, .. },
+    ) => vec![*by],
+    Event::Signals(event) => match event {
+      SignalsEvent::RetirementSignalRegistered { registrant, .. } => vec![*registrant],
+      SignalsEvent::SignalFavored { by, .. } | SignalsEvent::FavorRevoked { by, .. } => vec![*by],
+      SignalsEvent::AgainstSignal { who, .. } => vec![*who],
+      _ => vec![],
+    },
+    _ => vec![],
+  };
+  // Events to oneself solely involve one account
+  res.sort();
+  res.dedup();
+  res
+}
+
+fn push_event(txn: &mut impl DbTxn, topic: &Topic, event: &StoredEvent) {
+  let len = TopicEventsLen::get(txn, topic).unwrap_or(0);
+  TopicEvent::set(txn, topic, len, event);
+  TopicEventsLen::set(txn, topic, &(len + 1));
+}
+
+// Events and transactions are stored as they were encoded by the library which indexed them, so
+// they may not decode if the database was written by a version of this library for another runtime
+fn decode_event(
+  (block, index, transaction, event): StoredEvent,
+) -> Result<IndexedEvent, SeraiError> {
+  let event = Event::decode(&mut event.as_slice()).map_err(|_| {
+    SeraiError::InvalidRuntime(format!("event {index} indexed within block {block} didn't decode"))
+  })?;
+  Ok(IndexedEvent { block, index, transaction, event })
+}
+
+fn decode_transaction(
+  (block, index, transaction): StoredTransaction,
+) -> Result<IndexedTransaction, SeraiError> {
+  let transaction = Transaction::decode(&mut transaction.as_slice()).map_err(|_| {
+    SeraiError::InvalidRuntime(format!(
+      "transaction {index} indexed within block {block} didn't decode"
+    ))
+  })?;
+  Ok(IndexedTransaction { block, index, transaction })
+}
+
+/// An indexer of the events and transactions within finalized blocks, persisted to a database.
+///
+/// `Indexer::run` follows finalized blocks, indexing each one atomically, so applications may
+/// query history without refetching and decoding it from a node. Indexing resumes from the first
+/// block not yet indexed. As solely finalized blocks are indexed, nothing indexed is ever reverted.
+#[derive(Clone)]
+pub struct Indexer<D: Db>(D);
+
+impl<D: Db> Indexer<D> {
+  pub fn new(db: D) -> Self {
+    Indexer(db)
+  }
+
+  /// The number of the next block to index.
+  pub fn next_block(&self) -> u64 {
+    NextBlock::get(&self.0).unwrap_or(0)
+  }
+
+  fn index(&mut self, block: &Block, events: Vec<frame_system::EventRecord<Event, [u8; 32]>>) {
+    let number = block.number();
+    let mut txn = self.0.txn();
+    IndexedBlockHash::set(&mut txn, number, &block.hash());
+
+    let mut stored_events = vec![];
+    for (index, record) in events.into_iter().enumerate() {
+      let transaction = match record.phase {
+        Phase::ApplyExtrinsic(transaction) => Some(transaction),
+        Phase::Finalization | Phase::Initialization => None,
+      };
+      let event = (number, u32::try_from(index).unwrap(), transaction, record.event.encode());
+      for account in event_accounts(&record.event) {
+        push_event(&mut txn, &Topic::Account(account), &event);
+      }
+      if let Event::InInstructions(InInstructionsEvent::Batch { network, .. }) = &record.event {
+        push_event(&mut txn, &Topic::Batches(*network), &event);
+      }
+      stored_events.push(event);
+    }
+    BlockEvents::set(&mut txn, number, &stored_events);
+
+    let mut stored_transactions = vec![];
+    for (index, transaction) in block.transactions.iter().enumerate() {
+      let stored = (number, u32::try_from(index).unwrap(), transaction.encode());
+      if let Some(signer) = transaction.signer() {
+        let len = AccountTransactionsLen::get(&txn, signer).unwrap_or(0);
+        AccountTransaction::set(&mut txn, signer, len, &stored);
+        AccountTransactionsLen::set(&mut txn, signer, &(len + 1));
+      }
+      stored_transactions.push(stored);
+    }
+    BlockTransactions::set(&mut txn, number, &stored_transactions);
+
+    NextBlock::set(&mut txn, &(number + 1));
+    txn.commit();
+  }
+
+  /// Index the next block, if it's been finalized.
+  ///
+  /// Returns if a block was indexed.
+  pub async fn index_next_block(&mut self, serai: &Serai) -> Result<bool, SeraiError> {
+    let Some(block) = serai.finalized_block_by_number(self.next_block()).await? else {
+      return Ok(false);
+    };
+    let events = serai.as_of(block.hash()).event_records(|record| Some(record.clone())).await?;
+    self.index(&block, events);
+    Ok(true)
+  }
+
+  /// Index finalized blocks as they're finalized, forever.
+  ///
+  /// This polls for the next finalized block every `poll_interval`, and should be spawned as a
+  /// task. Errors are retried after `poll_interval`.
+  pub async fn run(mut self, serai: Serai, poll_interval: Duration) {
+    loop {
+      if !matches!(self.index_next_block(&serai).await, Ok(true)) {
+        sleep(poll_interval).await;
+      }
+    }
+  }
+
+  /// The hash of an indexed block.
+  pub fn block_hash(&self, number: u64) -> Option<[u8; 32]> {
+    IndexedBlockHash::get(&self.0, number)
+  }
+
+  /// The events within an indexed block.
+  ///
+  /// This returns `SeraiError::InvalidRuntime` if the events indexed don't decode, as they would
+  /// if indexed by a version of this library for another runtime. This applies to every query of
+  /// indexed events and transactions.
+  pub fn block_events(&self, number: u64) -> Result<Option<Vec<IndexedEvent>>, SeraiError> {
+    BlockEvents::get(&self.0, number)
+      .map(|events| events.into_iter().map(decode_event).collect())
+      .transpose()
+  }
+
+  /// The transactions within an indexed block.
+  pub fn block_transactions(
+    &self,
+    number: u64,
+  ) -> Result<Option<Vec<IndexedTransaction>>, SeraiError> {
+    BlockTransactions::get(&self.0, number)
+      .map(|transactions| transactions.into_iter().map(decode_transaction).collect())
+      .transpose()
+  }
+
+  fn topic_events(&self, topic: &Topic) -> Result<Vec<IndexedEvent>, SeraiError> {
+    (0 .. TopicEventsLen::get(&self.0, topic).unwrap_or(0))
+      .map(|i| decode_event(TopicEvent::get(&self.0, topic, i).unwrap()))
+      .collect()
+  }
+
+  /// The indexed events involving an account, in the order they were emitted.
+  pub fn events_by_account(&self, account: SeraiAddress) -> Result<Vec<IndexedEvent>, SeraiError> {
+    self.topic_events(&Topic::Account(account))
+  }
+
+  /// The indexed transactions signed by an account, in the order they were included.
+  pub fn transactions_by_account(
+    &self,
+    account: SeraiAddress,
+  ) -> Result<Vec<IndexedTransaction>, SeraiError> {
+    (0 .. AccountTransactionsLen::get(&self.0, account).unwrap_or(0))
+      .map(|i| decode_transaction(AccountTransaction::get(&self.0, account, i).unwrap()))
+      .collect()
+  }
+
+  /// The indexed `Batch` events for an external network, in the order they were emitted.
+  pub fn batches(&self, network: ExternalNetworkId) -> Result<Vec<IndexedEvent>, SeraiError> {
+    self.topic_events(&Topic::Batches(network))
+  }
+}
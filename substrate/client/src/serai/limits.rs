@@ -0,0 +1,51 @@
+use serde::Serialize;
+
+use serai_abi::primitives::{Amount, ExternalCoin, MAX_DATA_LEN};
+
+use crate::{SeraiError, TemporalSerai};
+
+/// The minimum amount of a coin the processors will handle.
+///
+/// This mirrors the processors' dust thresholds, as they aren't published on-chain. Deposits below
+/// this amount are ignored, and payments which are below it after fees are dropped.
+pub fn dust(coin: ExternalCoin) -> Amount {
+  Amount(match coin {
+    ExternalCoin::Bitcoin => 10_000,
+    // The Ethereum processor doesn't yet define a dust threshold
+    ExternalCoin::Ether | ExternalCoin::Dai => 0,
+    // 0.01 XMR
+    ExternalCoin::Monero => 10_000_000_000,
+  })
+}
+
+/// The limits on depositing and withdrawing a coin.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize)]
+pub struct CoinLimits {
+  /// The minimum amount which may be deposited.
+  pub minimum_deposit: Amount,
+  /// The minimum amount which may be burnt for an instruction, before the external network's
+  /// fees are deducted.
+  pub minimum_burn: Amount,
+  /// The maximum length of the data which may accompany an instruction.
+  pub max_instruction_data: usize,
+  /// The amount which may currently be deposited before the network's economic security is
+  /// exhausted.
+  ///
+  /// Deposits exceeding this will not be minted.
+  pub mint_headroom: Amount,
+  /// If the coin's network has been halted, with deposits no longer being accepted.
+  pub halted: bool,
+}
+
+impl<'a> TemporalSerai<'a> {
+  /// The limits on depositing and withdrawing a coin, as of this block.
+  pub async fn limits(&self, coin: ExternalCoin) -> Result<CoinLimits, SeraiError> {
+    Ok(CoinLimits {
+      minimum_deposit: dust(coin),
+      minimum_burn: dust(coin),
+      max_instruction_data: usize::try_from(MAX_DATA_LEN).unwrap(),
+      mint_headroom: self.validator_sets().mint_headroom(coin).await?,
+      halted: self.in_instructions().halted(coin.network()).await?,
+    })
+  }
+}
@@ -0,0 +1,404 @@
+use core::fmt;
+use std::collections::HashMap;
+
+use scale::Decode;
+
+use serde::Serialize;
+
+use scale_info::{
+  form::PortableForm, meta_type, TypeDef, TypeInfo, Variant, Field, Registry, PortableRegistry,
+};
+use frame_metadata::{RuntimeMetadata, RuntimeMetadataPrefixed};
+
+use crate::{
+  upgrades::{SPEC_VERSION, TX_VERSION},
+  SeraiError, Serai,
+};
+
+// A pallet, as described by the runtime's metadata
+pub(crate) struct PalletMetadata {
+  pub(crate) index: u8,
+  pub(crate) name: String,
+  pub(crate) events: Option<u32>,
+  pub(crate) errors: Option<u32>,
+}
+
+// Decode SCALE-encoded runtime metadata into its pallets, the registry of their types, and the
+// type of the calls within transactions, if described
+pub(crate) fn decode_metadata(
+  mut metadata: &[u8],
+) -> Result<(Vec<PalletMetadata>, PortableRegistry, Option<u32>), SeraiError> {
+  let metadata = RuntimeMetadataPrefixed::decode(&mut metadata)
+    .map_err(|_| SeraiError::InvalidNode("returned invalid metadata".to_string()))?;
+
+  macro_rules! pallets {
+    ($metadata: ident) => {
+      $metadata
+        .pallets
+        .into_iter()
+        .map(|pallet| PalletMetadata {
+          index: pallet.index,
+          name: pallet.name,
+          events: pallet.event.map(|event| event.ty.id),
+          errors: pallet.error.map(|error| error.ty.id),
+        })
+        .collect()
+    };
+  }
+  Ok(match metadata.1 {
+    // Serai's transactions are described as a tuple of their call and their signature
+    RuntimeMetadata::V14(metadata) => {
+      let calls = match metadata.types.resolve(metadata.extrinsic.ty.id).map(|ty| &ty.type_def) {
+        Some(TypeDef::Tuple(fields)) => fields.fields.first().map(|call| call.id),
+        _ => None,
+      };
+      (pallets!(metadata), metadata.types, calls)
+    }
+    // V15 solely describes the runtime's own calls, not those within Serai's transactions
+    RuntimeMetadata::V15(metadata) => (pallets!(metadata), metadata.types, None),
+    _ => Err(SeraiError::InvalidRuntime("unsupported metadata version".to_string()))?,
+  })
+}
+
+// The variants of an enum within the registry, or an empty slice if it isn't an enum
+pub(crate) fn variants(types: &PortableRegistry, ty: Option<u32>) -> &[Variant<PortableForm>] {
+  match ty.and_then(|ty| types.resolve(ty)).map(|ty| &ty.type_def) {
+    Some(TypeDef::Variant(variants)) => &variants.variants,
+    _ => &[],
+  }
+}
+
+/// A way a runtime is incompatible with this library.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+pub enum RuntimeMismatch {
+  /// The runtime's spec version differs from the one this library was built for.
+  SpecVersion { expected: u32, found: u32 },
+  /// The runtime's transaction version differs from the one this library was built for.
+  TransactionVersion { expected: u32, found: u32 },
+  /// A pallet this library builds calls for isn't present in the runtime.
+  MissingPallet(String),
+  /// A call this library builds isn't present in the runtime.
+  MissingCall { pallet: String, call: String },
+  /// A pallet this library builds calls for is encoded with a different index by the runtime.
+  PalletIndex { pallet: String, expected: u8, found: u8 },
+  /// A call this library builds is encoded with a different index by the runtime.
+  CallIndex { pallet: String, call: String, expected: u8, found: u8 },
+  /// A call this library builds has fields the runtime encodes differently.
+  CallFields { pallet: String, call: String },
+  /// A pallet this library decodes events from emits its events with a different index.
+  EventPalletIndex { pallet: String, expected: u8, found: u8 },
+  /// An event this library decodes isn't present in the runtime.
+  MissingEvent { pallet: String, event: String },
+  /// An event this library decodes is encoded with a different index by the runtime.
+  EventIndex { pallet: String, event: String, expected: u8, found: u8 },
+  /// An event this library decodes has fields the runtime encodes differently.
+  EventFields { pallet: String, event: String },
+}
+
+impl RuntimeMismatch {
+  /// If this mismatch changes how calls or events are encoded.
+  ///
+  /// A runtime with a different version yet the same calls and events may still be interacted
+  /// with, as a runtime upgrade doesn't necessarily change either.
+  pub fn changes_encoding(&self) -> bool {
+    !matches!(
+      self,
+      RuntimeMismatch::SpecVersion { .. } | RuntimeMismatch::TransactionVersion { .. }
+    )
+  }
+}
+
+impl fmt::Display for RuntimeMismatch {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      RuntimeMismatch::SpecVersion { expected, found } => {
+        write!(f, "expected spec version {expected}, found {found}")
+      }
+      RuntimeMismatch::TransactionVersion { expected, found } => {
+        write!(f, "expected transaction version {expected}, found {found}")
+      }
+      RuntimeMismatch::MissingPallet(pallet) => write!(f, "pallet {pallet} is missing"),
+      RuntimeMismatch::MissingCall { pallet, call } => {
+        write!(f, "call {pallet}::{call} is missing")
+      }
+      RuntimeMismatch::PalletIndex { pallet, expected, found } => {
+        write!(f, "expected pallet {pallet} to have index {expected}, found {found}")
+      }
+      RuntimeMismatch::CallIndex { pallet, call, expected, found } => {
+        write!(f, "expected call {pallet}::{call} to have index {expected}, found {found}")
+      }
+      RuntimeMismatch::CallFields { pallet, call } => {
+        write!(f, "call {pallet}::{call} has different fields")
+      }
+      RuntimeMismatch::EventPalletIndex { pallet, expected, found } => {
+        write!(f, "expected pallet {pallet}'s events to have index {expected}, found {found}")
+      }
+      RuntimeMismatch::MissingEvent { pallet, event } => {
+        write!(f, "event {pallet}::{event} is missing")
+      }
+      RuntimeMismatch::EventIndex { pallet, event, expected, found } => {
+        write!(f, "expected event {pallet}::{event} to have index {expected}, found {found}")
+      }
+      RuntimeMismatch::EventFields { pallet, event } => {
+        write!(f, "event {pallet}::{event} has different fields")
+      }
+    }
+  }
+}
+
+// The registry of one of Serai's ABI's types, and the type's ID within it
+fn abi_registry<T: TypeInfo + 'static>() -> (PortableRegistry, u32) {
+  let mut registry = Registry::new();
+  let ty = registry.register_type(&meta_type::<T>()).id;
+  (registry.into(), ty)
+}
+
+// The definition of a type, without any wrappers which don't affect its encoding
+fn definition(types: &PortableRegistry, mut ty: u32) -> Option<&TypeDef<PortableForm>> {
+  loop {
+    let def = &types.resolve(ty)?.type_def;
+    match def {
+      TypeDef::Composite(composite) if composite.fields.len() == 1 => {
+        ty = composite.fields[0].ty.id;
+      }
+      TypeDef::Tuple(tuple) if tuple.fields.len() == 1 => ty = tuple.fields[0].id,
+      _ => return Some(def),
+    }
+  }
+}
+
+// The types of the fields of a composite or tuple
+fn field_types(def: &TypeDef<PortableForm>) -> Option<Vec<u32>> {
+  match def {
+    TypeDef::Composite(composite) => {
+      Some(composite.fields.iter().map(|field| field.ty.id).collect())
+    }
+    TypeDef::Tuple(tuple) => Some(tuple.fields.iter().map(|field| field.id).collect()),
+    _ => None,
+  }
+}
+
+// A comparison of if our types, as described by our registry, are encoded the same as their
+// types, as described by theirs
+//
+// Names aren't compared, solely the encoding, so types which are renamed or wrapped by the runtime
+// are still compatible.
+struct Compatibility<'a> {
+  ours: &'a PortableRegistry,
+  theirs: &'a PortableRegistry,
+  // The pairs of types already compared, with pairs still being compared presumed compatible so
+  // recursive types terminate
+  compared: HashMap<(u32, u32), bool>,
+}
+
+impl Compatibility<'_> {
+  fn types(&mut self, ours: u32, theirs: u32) -> bool {
+    if let Some(compatible) = self.compared.get(&(ours, theirs)) {
+      return *compatible;
+    }
+    self.compared.insert((ours, theirs), true);
+    let compatible = self.definitions(ours, theirs);
+    self.compared.insert((ours, theirs), compatible);
+    compatible
+  }
+
+  fn definitions(&mut self, ours: u32, theirs: u32) -> bool {
+    let (Some(our_def), Some(their_def)) =
+      (definition(self.ours, ours), definition(self.theirs, theirs))
+    else {
+      return false;
+    };
+    if let (Some(our_fields), Some(their_fields)) = (field_types(our_def), field_types(their_def)) {
+      return (our_fields.len() == their_fields.len()) &&
+        our_fields.into_iter().zip(their_fields).all(|(ours, theirs)| self.types(ours, theirs));
+    }
+    match (our_def, their_def) {
+      (TypeDef::Variant(ours), TypeDef::Variant(theirs)) => {
+        (ours.variants.len() == theirs.variants.len()) &&
+          ours.variants.iter().all(|our_variant| {
+            theirs.variants.iter().any(|their_variant| {
+              (our_variant.index == their_variant.index) &&
+                self.fields(&our_variant.fields, &their_variant.fields)
+            })
+          })
+      }
+      (TypeDef::Sequence(ours), TypeDef::Sequence(theirs)) => {
+        self.types(ours.type_param.id, theirs.type_param.id)
+      }
+      (TypeDef::Array(ours), TypeDef::Array(theirs)) => {
+        (ours.len == theirs.len) && self.types(ours.type_param.id, theirs.type_param.id)
+      }
+      (TypeDef::Primitive(ours), TypeDef::Primitive(theirs)) => ours == theirs,
+      (TypeDef::Compact(ours), TypeDef::Compact(theirs)) => {
+        self.types(ours.type_param.id, theirs.type_param.id)
+      }
+      (TypeDef::BitSequence(ours), TypeDef::BitSequence(theirs)) => {
+        self.types(ours.bit_store_type.id, theirs.bit_store_type.id) &&
+          self.types(ours.bit_order_type.id, theirs.bit_order_type.id)
+      }
+      _ => false,
+    }
+  }
+
+  fn fields(&mut self, ours: &[Field<PortableForm>], theirs: &[Field<PortableForm>]) -> bool {
+    (ours.len() == theirs.len()) &&
+      ours.iter().zip(theirs).all(|(ours, theirs)| self.types(ours.ty.id, theirs.ty.id))
+  }
+}
+
+/// The calls this library builds which aren't present in the runtime described by the metadata,
+/// or which the runtime encodes differently.
+///
+/// Serai's ABI has its own encoding of calls, which the runtime maps to its own calls, so calls
+/// are checked against the calls within the runtime's transactions. Pallets and calls are matched
+/// by name, then checked to have the same indices and fields, as a call with a different index
+/// would be decoded as a different call.
+pub fn missing_calls(metadata: &[u8]) -> Result<Vec<RuntimeMismatch>, SeraiError> {
+  let (_, types, calls) = decode_metadata(metadata)?;
+  let Some(calls) = calls else {
+    Err(SeraiError::InvalidRuntime(
+      "metadata didn't describe the calls within transactions".to_string(),
+    ))?
+  };
+  let pallets = variants(&types, Some(calls));
+
+  let (abi, abi_calls) = abi_registry::<serai_abi::Call>();
+  let mut compatibility = Compatibility { ours: &abi, theirs: &types, compared: HashMap::new() };
+  let mut res = vec![];
+  for pallet in variants(&abi, Some(abi_calls)) {
+    let Some(theirs) = pallets.iter().find(|theirs| theirs.name == pallet.name) else {
+      res.push(RuntimeMismatch::MissingPallet(pallet.name.clone()));
+      continue;
+    };
+    if theirs.index != pallet.index {
+      res.push(RuntimeMismatch::PalletIndex {
+        pallet: pallet.name.clone(),
+        expected: pallet.index,
+        found: theirs.index,
+      });
+      continue;
+    }
+    let theirs = variants(&types, theirs.fields.first().map(|calls| calls.ty.id));
+
+    for call in variants(&abi, pallet.fields.first().map(|calls| calls.ty.id)) {
+      match theirs.iter().find(|theirs| theirs.name == call.name) {
+        None => res.push(RuntimeMismatch::MissingCall {
+          pallet: pallet.name.clone(),
+          call: call.name.clone(),
+        }),
+        Some(theirs) if theirs.index != call.index => res.push(RuntimeMismatch::CallIndex {
+          pallet: pallet.name.clone(),
+          call: call.name.clone(),
+          expected: call.index,
+          found: theirs.index,
+        }),
+        Some(theirs) if !compatibility.fields(&call.fields, &theirs.fields) => {
+          res.push(RuntimeMismatch::CallFields {
+            pallet: pallet.name.clone(),
+            call: call.name.clone(),
+          })
+        }
+        Some(_) => {}
+      }
+    }
+  }
+  Ok(res)
+}
+
+/// The events this library decodes which aren't present in the runtime described by the
+/// metadata, or which the runtime encodes differently.
+///
+/// Events are decoded directly from the runtime's encoding, so each pallet's events must have the
+/// pallet's index within the runtime, and each event must have the same index and fields.
+pub fn missing_events(metadata: &[u8]) -> Result<Vec<RuntimeMismatch>, SeraiError> {
+  let (pallets, types, _) = decode_metadata(metadata)?;
+
+  let (abi, abi_events) = abi_registry::<serai_abi::Event>();
+  let mut compatibility = Compatibility { ours: &abi, theirs: &types, compared: HashMap::new() };
+  let mut res = vec![];
+  for pallet in variants(&abi, Some(abi_events)) {
+    // Pallets without events
+    let Some(events) = pallet.fields.first() else { continue };
+    let theirs = pallets.iter().find(|theirs| theirs.name == pallet.name);
+    if let Some(theirs) = theirs.filter(|theirs| theirs.index != pallet.index) {
+      res.push(RuntimeMismatch::EventPalletIndex {
+        pallet: pallet.name.clone(),
+        expected: pallet.index,
+        found: theirs.index,
+      });
+      continue;
+    }
+    let theirs = variants(&types, theirs.and_then(|theirs| theirs.events));
+
+    for event in variants(&abi, Some(events.ty.id)) {
+      match theirs.iter().find(|theirs| theirs.name == event.name) {
+        None => res.push(RuntimeMismatch::MissingEvent {
+          pallet: pallet.name.clone(),
+          event: event.name.clone(),
+        }),
+        Some(theirs) if theirs.index != event.index => res.push(RuntimeMismatch::EventIndex {
+          pallet: pallet.name.clone(),
+          event: event.name.clone(),
+          expected: event.index,
+          found: theirs.index,
+        }),
+        Some(theirs) if !compatibility.fields(&event.fields, &theirs.fields) => {
+          res.push(RuntimeMismatch::EventFields {
+            pallet: pallet.name.clone(),
+            event: event.name.clone(),
+          })
+        }
+        Some(_) => {}
+      }
+    }
+  }
+  Ok(res)
+}
+
+impl Serai {
+  /// The SCALE-encoded metadata of the runtime as of the specified block.
+  pub async fn metadata(&self, block: [u8; 32]) -> Result<Vec<u8>, SeraiError> {
+    let metadata: String = self.call("state_getMetadata", [hex::encode(block)]).await?;
+    Self::hex_decode(metadata)
+  }
+
+  /// Check the runtime as of the latest finalized block is compatible with this library.
+  ///
+  /// This checks the runtime's versions are those this library was built for and every call this
+  /// library builds, and every event it decodes, is present and encoded the same, returning
+  /// `SeraiError::IncompatibleRuntime` with every mismatch found otherwise. Clients solely check
+  /// for missing calls when created, so this should be checked by callers requiring the exact
+  /// runtime, and again after a runtime upgrade. `RuntimeMismatch::changes_encoding` distinguishes
+  /// mismatches which require an updated client from differing versions alone.
+  pub async fn check_compatibility(&self) -> Result<(), SeraiError> {
+    self.check_compatibility_as_of(self.latest_finalized_block_hash().await?).await
+  }
+
+  /// Check the runtime as of the specified block is compatible with this library.
+  ///
+  /// As the runtime as of a block is the runtime after its execution, checking a block's parent
+  /// checks the runtime which executed the block, and accordingly emitted its events.
+  pub async fn check_compatibility_as_of(&self, block: [u8; 32]) -> Result<(), SeraiError> {
+    let version = self.runtime_version(block).await?;
+
+    let mut mismatches = vec![];
+    if version.spec_version != SPEC_VERSION {
+      mismatches
+        .push(RuntimeMismatch::SpecVersion { expected: SPEC_VERSION, found: version.spec_version });
+    }
+    if version.transaction_version != TX_VERSION {
+      mismatches.push(RuntimeMismatch::TransactionVersion {
+        expected: TX_VERSION,
+        found: version.transaction_version,
+      });
+    }
+    let metadata = self.metadata(block).await?;
+    mismatches.extend(missing_calls(&metadata)?);
+    mismatches.extend(missing_events(&metadata)?);
+
+    if !mismatches.is_empty() {
+      Err(SeraiError::IncompatibleRuntime(mismatches))?;
+    }
+    Ok(())
+  }
+}
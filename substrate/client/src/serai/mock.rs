@@ -0,0 +1,308 @@
+use std::{sync::Mutex, collections::HashMap};
+
+use scale::{Encode, Decode};
+use serde_json::{json, Value};
+
+use sp_core::{hashing::blake2_256, sr25519::Public, H256};
+use sp_runtime::traits::Header as HeaderTrait;
+
+use frame_system::{Phase, EventRecord};
+use serai_abi::{Call, Event, system, timestamp};
+
+use crate::{
+  primitives::Header,
+  rpc::{SeraiRpc, RpcFuture},
+  upgrades::{SPEC_VERSION, TX_VERSION},
+  Block, Transaction, SeraiError, TemporalSerai,
+};
+
+// The time between blocks, in milliseconds
+const BLOCK_TIME: u64 = 6000;
+
+#[derive(Default)]
+struct MockState {
+  // The blocks produced, with the storage as of each
+  blocks: Vec<(Block, HashMap<Vec<u8>, Vec<u8>>)>,
+  finalized: usize,
+  // The number of the first block whose state hasn't been discarded
+  pruned: usize,
+  // The storage and events for the next block
+  storage: HashMap<Vec<u8>, Vec<u8>>,
+  events: Vec<EventRecord<Event, [u8; 32]>>,
+  pool: Vec<Transaction>,
+}
+
+impl MockState {
+  fn block(&self, hash: Option<[u8; 32]>) -> Option<&(Block, HashMap<Vec<u8>, Vec<u8>>)> {
+    match hash {
+      Some(hash) => self.blocks.iter().find(|(block, _)| block.hash() == hash),
+      None => self.blocks.last(),
+    }
+  }
+
+  // The block, with its storage, if its state hasn't been discarded
+  fn state(
+    &self,
+    hash: Option<[u8; 32]>,
+  ) -> Result<Option<&(Block, HashMap<Vec<u8>, Vec<u8>>)>, SeraiError> {
+    let block = self.block(hash);
+    if let Some((block, _)) = block {
+      if block.number() < u64::try_from(self.pruned).unwrap() {
+        Err(SeraiError::ErrorInResponse(format!(
+          "State already discarded for Hash(0x{})",
+          hex::encode(block.hash())
+        )))?;
+      }
+    }
+    Ok(block)
+  }
+}
+
+/// An in-memory mock of a Serai node, for unit testing code which consumes `Serai`.
+///
+/// Storage and events are scripted for the next block, which is then produced with
+/// `produce_block`. Storage persists across blocks, while events are solely present in the block
+/// they were scripted for. Transactions published are included in the next block produced, after
+/// the timestamp which is always its first transaction.
+///
+/// `Serai::with_rpc` creates a client backed by the mock. Only the RPC methods needed to query
+/// storage, events, and blocks, and to publish transactions and fetch the nonces to sign them
+/// with, are supported.
+pub struct MockSerai(Mutex<MockState>);
+
+impl Default for MockSerai {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl MockSerai {
+  /// Create a mock with solely a genesis block.
+  pub fn new() -> MockSerai {
+    let mock = MockSerai(Mutex::new(MockState::default()));
+    mock.produce_block();
+    mock
+  }
+
+  /// Set a storage value, as of the next block produced.
+  ///
+  /// The key is the same as would be passed to query the value, with any hashing performed.
+  pub fn set_storage(
+    &self,
+    pallet: &'static str,
+    name: &'static str,
+    key: impl Encode,
+    value: impl Encode,
+  ) {
+    let key = TemporalSerai::storage_key(pallet, name, key);
+    self.0.lock().unwrap().storage.insert(key, value.encode());
+  }
+
+  /// Remove a storage value, as of the next block produced.
+  pub fn remove_storage(&self, pallet: &'static str, name: &'static str, key: impl Encode) {
+    let key = TemporalSerai::storage_key(pallet, name, key);
+    self.0.lock().unwrap().storage.remove(&key);
+  }
+
+  /// Add an event to the next block produced.
+  pub fn push_event(&self, event: Event) {
+    self.push_event_with_phase(Phase::Finalization, event);
+  }
+
+  /// Add an event emitted by the transaction with the specified index to the next block produced.
+  ///
+  /// The timestamp is always the first transaction, so the first transaction published has an
+  /// index of 1.
+  pub fn push_extrinsic_event(&self, index: u32, event: Event) {
+    self.push_event_with_phase(Phase::ApplyExtrinsic(index), event);
+  }
+
+  /// Add an event emitted in the specified phase to the next block produced.
+  pub fn push_event_with_phase(&self, phase: Phase, event: Event) {
+    self.0.lock().unwrap().events.push(EventRecord { phase, event, topics: vec![] });
+  }
+
+  /// The transactions published yet to be included in a block.
+  pub fn pending(&self) -> Vec<Transaction> {
+    self.0.lock().unwrap().pool.clone()
+  }
+
+  /// Drop the transactions published yet to be included in a block, as if they expired.
+  pub fn drop_pending(&self) {
+    self.0.lock().unwrap().pool.clear();
+  }
+
+  /// Produce and finalize a block, returning its hash.
+  pub fn produce_block(&self) -> [u8; 32] {
+    let hash = self.produce_unfinalized_block();
+    self.finalize();
+    hash
+  }
+
+  /// Produce a block without finalizing it, returning its hash.
+  pub fn produce_unfinalized_block(&self) -> [u8; 32] {
+    let mut state = self.0.lock().unwrap();
+    let number = u64::try_from(state.blocks.len()).unwrap();
+    let parent_hash = state.blocks.last().map(|(block, _)| block.hash()).unwrap_or([0; 32]);
+
+    let mut transactions = vec![Transaction::new(
+      Call::Timestamp(timestamp::Call::set { now: number * BLOCK_TIME }),
+      None,
+    )];
+    transactions.append(&mut state.pool);
+
+    // Every transaction succeeds
+    let mut events = vec![];
+    for i in 0 .. transactions.len() {
+      events.push(EventRecord {
+        phase: Phase::ApplyExtrinsic(u32::try_from(i).unwrap()),
+        event: Event::System(system::Event::ExtrinsicSuccess { dispatch_info: Default::default() }),
+        topics: vec![],
+      });
+    }
+    events.append(&mut state.events);
+
+    let mut storage = state.storage.clone();
+    storage.insert(TemporalSerai::storage_key("System", "Events", ()), events.encode());
+
+    let header = Header::new(
+      number,
+      H256(blake2_256(&transactions.encode())),
+      // The state root is arbitrary, as the mock doesn't merkelize its storage
+      H256(blake2_256(&(number, parent_hash).encode())),
+      H256(parent_hash),
+      Default::default(),
+    );
+    let block = Block { header, transactions };
+    let hash = block.hash();
+    state.blocks.push((block, storage));
+    hash
+  }
+
+  /// Discard the state of every block before the specified block, as a pruned node does.
+  pub fn discard_state(&self, before: u64) {
+    self.0.lock().unwrap().pruned = usize::try_from(before).unwrap();
+  }
+
+  /// Finalize every block produced.
+  pub fn finalize(&self) {
+    let mut state = self.0.lock().unwrap();
+    state.finalized = state.blocks.len() - 1;
+  }
+
+  fn handle(&self, method: &str, params: &Value) -> Result<Value, SeraiError> {
+    let param = |i: usize| params.get(i).cloned().unwrap_or(Value::Null);
+    let bytes = |i: usize| -> Result<Option<Vec<u8>>, SeraiError> {
+      let Some(hex) = param(i).as_str().map(ToString::to_string) else { return Ok(None) };
+      hex::decode(hex.strip_prefix("0x").unwrap_or(&hex))
+        .map(Some)
+        .map_err(|_| SeraiError::ErrorInResponse(format!("parameter {i} wasn't hex")))
+    };
+    let hash = |i: usize| -> Result<Option<[u8; 32]>, SeraiError> {
+      bytes(i)?
+        .map(|hash| hash.try_into())
+        .transpose()
+        .map_err(|_| SeraiError::ErrorInResponse(format!("parameter {i} wasn't a hash")))
+    };
+    let encode = |bytes: &[u8]| format!("0x{}", hex::encode(bytes));
+
+    let mut state = self.0.lock().unwrap();
+    Ok(match method {
+      "chain_getBlockHash" => {
+        let number = param(0).as_u64().and_then(|number| usize::try_from(number).ok());
+        json!(number
+          .and_then(|number| state.blocks.get(number))
+          .map(|(block, _)| encode(&block.hash())))
+      }
+      "chain_getFinalizedHead" => json!(encode(&state.blocks[state.finalized].0.hash())),
+      "chain_getHeader" => {
+        json!(state.block(hash(0)?).map(|(block, _)| block.header.clone()))
+      }
+      "chain_getBlockBin" => json!(state.block(hash(0)?).map(|(block, _)| encode(&block.encode()))),
+      "state_getStorage" => {
+        let key = bytes(0)?.unwrap_or_default();
+        let storage = state.state(hash(1)?)?.map(|(_, storage)| storage);
+        json!(storage.and_then(|storage| storage.get(&key)).map(|value| encode(value)))
+      }
+      "state_queryStorageAt" => {
+        let at = hash(1)?;
+        let Some((block, storage)) = state.state(at)? else { return Ok(json!([])) };
+        let changes = param(0)
+          .as_array()
+          .cloned()
+          .unwrap_or_default()
+          .into_iter()
+          .map(|key| {
+            let key = key.as_str().unwrap_or_default().to_string();
+            let value = hex::decode(key.strip_prefix("0x").unwrap_or(&key))
+              .ok()
+              .and_then(|key| storage.get(&key))
+              .map(|value| encode(value));
+            json!([key, value])
+          })
+          .collect::<Vec<_>>();
+        json!([{ "block": encode(&block.hash()), "changes": changes }])
+      }
+      "state_getKeysPaged" => {
+        let prefix = bytes(0)?.unwrap_or_default();
+        let count = usize::try_from(param(1).as_u64().unwrap_or(0)).unwrap();
+        let start = bytes(2)?;
+        let storage = state.state(hash(3)?)?.map(|(_, storage)| storage);
+        let mut keys = storage
+          .map(|storage| storage.keys().filter(|key| key.starts_with(&prefix)).collect::<Vec<_>>())
+          .unwrap_or_default();
+        keys.sort();
+        json!(keys
+          .into_iter()
+          .filter(|key| start.as_ref().map_or(true, |start| *key > start))
+          .take(count)
+          .map(|key| encode(key))
+          .collect::<Vec<_>>())
+      }
+      "system_health" => json!({ "peers": 0, "isSyncing": false, "shouldHavePeers": false }),
+      "system_chain" => json!("Mock"),
+      "state_getRuntimeVersion" => json!({
+        "specName": "serai",
+        "implName": "mock",
+        "authoringVersion": 1,
+        "specVersion": SPEC_VERSION,
+        "implVersion": 1,
+        "apis": [],
+        "transactionVersion": TX_VERSION,
+        "stateVersion": 1,
+      }),
+      "author_submitExtrinsic" => {
+        let tx = bytes(0)?.unwrap_or_default();
+        let hash = blake2_256(&tx);
+        let tx = Transaction::decode(&mut tx.as_slice())
+          .map_err(|_| SeraiError::ErrorInResponse("invalid transaction".to_string()))?;
+        state.pool.push(tx);
+        json!(encode(&hash))
+      }
+      // Every transaction succeeds, so an account's nonce is the amount of transactions it signed,
+      // including those still pending
+      "system_accountNextIndex" => {
+        let account = param(0).as_str().map(ToString::to_string);
+        let signed = state
+          .blocks
+          .iter()
+          .flat_map(|(block, _)| &block.transactions)
+          .chain(&state.pool)
+          .filter(|tx| tx.signer().map(|signer| Public::from(signer).to_string()) == account)
+          .count();
+        json!(signed)
+      }
+      "author_pendingExtrinsics" => {
+        json!(state.pool.iter().map(|tx| encode(&tx.encode())).collect::<Vec<_>>())
+      }
+      _ => Err(SeraiError::ErrorInResponse(format!("MockSerai doesn't support {method}")))?,
+    })
+  }
+}
+
+impl SeraiRpc for MockSerai {
+  fn call<'a>(&'a self, method: &'a str, params: Value) -> RpcFuture<'a> {
+    let res = self.handle(method, &params);
+    Box::pin(async move { res })
+  }
+}
@@ -1,7 +1,14 @@
+use core::{
+  sync::atomic::{AtomicUsize, Ordering},
+  time::Duration,
+};
+use std::{sync::Arc, collections::HashMap};
+
 use thiserror::Error;
 
 use async_lock::RwLock;
-use simple_request::{hyper, Request, Client};
+use futures_util::{stream, Stream};
+use patchable_async_sleep::sleep;
 
 use scale::{Decode, Encode};
 use serde::{Serialize, Deserialize, de::DeserializeOwned};
@@ -11,6 +18,9 @@ pub use sp_core::{
   sr25519::{Public, Pair},
 };
 
+use sp_runtime::generic::Era;
+pub use sp_runtime::ApplyExtrinsicResult;
+
 pub use serai_abi as abi;
 pub use abi::{primitives, Transaction};
 use abi::*;
@@ -25,11 +35,60 @@ pub use dex::SeraiDex;
 pub mod in_instructions;
 pub use in_instructions::SeraiInInstructions;
 pub mod validator_sets;
-pub use validator_sets::SeraiValidatorSets;
+pub use validator_sets::{KeyRotation, SeraiValidatorSets};
 pub mod genesis_liquidity;
 pub use genesis_liquidity::SeraiGenesisLiquidity;
 pub mod liquidity_tokens;
 pub use liquidity_tokens::SeraiLiquidityTokens;
+pub mod emissions;
+pub use emissions::{RewardPayout, EmissionPhase, EmissionSchedule, SeraiEmissions};
+pub mod grandpa;
+pub use grandpa::SeraiGrandpa;
+pub mod analytics;
+pub use analytics::PoolAnalytics;
+pub mod budget;
+pub use budget::{QueryCost, QueryBudget};
+pub mod limits;
+pub use limits::CoinLimits;
+pub mod extensions;
+pub use extensions::{Extensions, ExtendedEvent, ExtendedCall};
+pub mod upgrades;
+pub use upgrades::{RuntimeVersion, Compatibility, RuntimeUpgrade};
+pub mod watch;
+pub use watch::TransactionStatus;
+pub mod nonces;
+pub use nonces::NonceManager;
+pub mod backoff;
+pub use backoff::Backoff;
+pub mod failover;
+pub mod fees;
+pub use fees::FeeEstimate;
+pub mod errors;
+pub use errors::{PalletError, DecodedDispatchError, ErrorRegistry};
+pub mod events;
+pub use events::{SeraiEvent, BlockEvents};
+pub mod offline;
+pub use offline::UnsignedTransaction;
+pub mod rpc;
+pub use rpc::SeraiRpc;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
+#[cfg(any(test, feature = "mock"))]
+pub use mock::MockSerai;
+pub mod proofs;
+pub use proofs::StorageProof;
+pub mod ratelimit;
+pub use ratelimit::{MethodClass, RateLimit, RateLimiter};
+pub mod metadata;
+pub use metadata::RuntimeMismatch;
+pub mod health;
+pub use health::NodeHealth;
+#[cfg(feature = "indexer")]
+pub mod indexer;
+#[cfg(feature = "indexer")]
+pub use indexer::{IndexedEvent, IndexedTransaction, Indexer};
+mod transport;
+use transport::Transport;
 
 #[derive(Clone, PartialEq, Eq, Debug, scale::Encode, scale::Decode)]
 pub struct Block {
@@ -65,16 +124,48 @@ pub enum SeraiError {
   ErrorInResponse(String),
   #[error("serai-client library was intended for a different runtime version: {0}")]
   InvalidRuntime(String),
+  #[error("node discarded the state requested: {0}")]
+  StateDiscarded(String),
+  #[error("query exceeded its budget: {0}")]
+  BudgetExceeded(String),
+  #[error(
+    "runtime is incompatible with this library: {}",
+    .0.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+  )]
+  IncompatibleRuntime(Vec<RuntimeMismatch>),
 }
 
 #[derive(Clone)]
 pub struct Serai {
-  url: String,
-  client: Client,
+  // The handler of RPC calls, if the client isn't backed by nodes
+  rpc: Option<Arc<dyn SeraiRpc>>,
+  urls: Arc<Vec<String>>,
+  // The index of the endpoint currently used
+  endpoint: Arc<AtomicUsize>,
+  transport: Transport,
   genesis: [u8; 32],
+  extensions: Arc<Extensions>,
+  backoff: Backoff,
+  rate_limiter: Option<Arc<RateLimiter>>,
+  // The error registry for the runtime with the specified spec version
+  errors: Arc<RwLock<Option<(u32, Arc<ErrorRegistry>)>>>,
 }
 
 type EventsInBlock = Vec<frame_system::EventRecord<Event, [u8; 32]>>;
+
+// The longest mortality period possible, as the runtime only keeps the hashes of the last 2400
+// blocks and periods must be powers of two
+const MAX_MORTALITY_PERIOD: u64 = 2048;
+
+// The message Substrate responds with when state is requested for a block it's pruned the state of
+const STATE_DISCARDED: &str = "State already discarded";
+
+// The message Substrate responds with when a submitted transaction is already in its pool
+const ALREADY_IMPORTED: &str = "Transaction Already Imported";
+
+// The amount of keys to request per page when iterating storage, and per batched storage query
+const STORAGE_PAGE_SIZE: usize = 1000;
+
 pub struct TemporalSerai<'a> {
   serai: &'a Serai,
   block: [u8; 32],
@@ -92,19 +183,74 @@ impl Serai {
     method: &str,
     params: Req,
   ) -> Result<Res, SeraiError> {
-    let request = Request::from(
-      hyper::Request::post(&self.url)
-        .header("Content-Type", "application/json")
-        .body(
-          serde_json::to_vec(
-            &serde_json::json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }),
-          )
-          .unwrap()
-          .into(),
-        )
-        .unwrap(),
-    );
+    if let Some(rate_limiter) = &self.rate_limiter {
+      rate_limiter.wait(method).await;
+    }
+
+    if let Some(rpc) = &self.rpc {
+      let params = serde_json::to_value(params).unwrap();
+      let res = rpc.call(method, params).await.map_err(|e| match e {
+        SeraiError::ErrorInResponse(message) => Self::response_error(message),
+        e => e,
+      })?;
+      return serde_json::from_value(res).map_err(|e| {
+        SeraiError::InvalidRuntime(format!("response was a different type than expected: {e}"))
+      });
+    }
+
+    let body = Self::request_body(method, params);
+
+    // If an endpoint is unreachable even after retrying, rotate to the next endpoint
+    let endpoints = self.urls.len();
+    let mut endpoint = self.endpoint.load(Ordering::Relaxed);
+    for _ in 0 .. endpoints {
+      match self.call_with_backoff(endpoint, &body).await {
+        Err(SeraiError::ConnectionError) => {
+          let next = (endpoint + 1) % endpoints;
+          // If another call already rotated away from this endpoint, don't rotate again
+          let _ =
+            self.endpoint.compare_exchange(endpoint, next, Ordering::Relaxed, Ordering::Relaxed);
+          endpoint = next;
+        }
+        res => return res,
+      }
+    }
+    Err(SeraiError::ConnectionError)
+  }
 
+  fn request_body<Req: Serialize>(method: &str, params: Req) -> Vec<u8> {
+    serde_json::to_vec(
+      &serde_json::json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }),
+    )
+    .unwrap()
+  }
+
+  async fn call_with_backoff<Res: DeserializeOwned>(
+    &self,
+    endpoint: usize,
+    body: &[u8],
+  ) -> Result<Res, SeraiError> {
+    // Retry requests which failed to reach the node, as the connection may have been dropped
+    // Since the connection pool won't reuse a closed connection, retrying will reconnect
+    // Callers polling the node, such as `finalized_blocks`, resume from where they were as
+    // they're retried
+    let mut retry = 0;
+    loop {
+      match self.call_once(endpoint, body.to_vec()).await {
+        Err(SeraiError::ConnectionError) if retry < self.backoff.retries => {
+          sleep(self.backoff.jittered_delay(retry)).await;
+          retry += 1;
+        }
+        res => return res,
+      }
+    }
+  }
+
+  async fn call_once<Res: DeserializeOwned>(
+    &self,
+    endpoint: usize,
+    body: Vec<u8>,
+  ) -> Result<Res, SeraiError> {
     #[derive(Deserialize)]
     pub struct Error {
       message: String,
@@ -117,16 +263,8 @@ impl Serai {
       Err { error: Error },
     }
 
-    let mut res = self
-      .client
-      .request(request)
-      .await
-      .map_err(|_| SeraiError::ConnectionError)?
-      .body()
-      .await
-      .map_err(|_| SeraiError::ConnectionError)?;
-
-    let res: RpcResponse<Res> = serde_json::from_reader(&mut res).map_err(|e| {
+    let res = self.transport.request(&self.urls[endpoint], body).await?;
+    let res: RpcResponse<Res> = serde_json::from_slice(&res).map_err(|e| {
       SeraiError::InvalidRuntime(format!(
         "response was a different type than expected: {:?}",
         e.classify()
@@ -134,7 +272,28 @@ impl Serai {
     })?;
     match res {
       RpcResponse::Ok { result } => Ok(result),
-      RpcResponse::Err { error } => Err(SeraiError::ErrorInResponse(error.message)),
+      RpcResponse::Err { error } => Err(Self::response_error(error.message)),
+    }
+  }
+
+  fn response_error(message: String) -> SeraiError {
+    if message.contains(STATE_DISCARDED) {
+      SeraiError::StateDiscarded(message)
+    } else {
+      SeraiError::ErrorInResponse(message)
+    }
+  }
+
+  /// If the node is an archive node, retaining the state of every block.
+  ///
+  /// Queries made via a `TemporalSerai` for a block whose state the node discarded return
+  /// `SeraiError::StateDiscarded`. This checks if the state of the genesis block is still
+  /// available, allowing callers to check before starting jobs which query historical state.
+  pub async fn is_archive_node(&self) -> Result<bool, SeraiError> {
+    match self.as_of(self.genesis).storage::<_, u64>("System", "Number", ()).await {
+      Ok(_) => Ok(true),
+      Err(SeraiError::StateDiscarded(_)) => Ok(false),
+      Err(e) => Err(e),
     }
   }
 
@@ -156,45 +315,262 @@ impl Serai {
       .map(Some)
   }
 
+  /// Create a client for the node at the specified URL.
+  ///
+  /// This returns `SeraiError::IncompatibleRuntime` if calls this library builds aren't present
+  /// in the node's runtime, as transactions built by this library would be invalid. Differing
+  /// runtime versions alone aren't an error, and may be checked for with `check_compatibility`.
   pub async fn new(url: String) -> Result<Self, SeraiError> {
-    let client = Client::with_connection_pool();
-    let mut res = Serai { url, client, genesis: [0xfe; 32] };
+    Self::new_with_failover(vec![url]).await
+  }
+
+  /// Create a client which fails over between the specified endpoints.
+  ///
+  /// Requests are made to one endpoint at a time. If it's unreachable, even after retrying per the
+  /// client's `Backoff`, the client rotates to the next endpoint.
+  pub async fn new_with_failover(urls: Vec<String>) -> Result<Self, SeraiError> {
+    assert!(!urls.is_empty(), "no endpoints were specified");
+    Self::new_internal(None, urls).await
+  }
+
+  /// Create a client whose RPC calls are handled by the specified handler, instead of a node.
+  pub async fn with_rpc(rpc: Arc<dyn SeraiRpc>) -> Result<Self, SeraiError> {
+    Self::new_internal(Some(rpc), vec![]).await
+  }
+
+  // Create a client without fetching the genesis block's hash or checking compatibility
+  pub(crate) fn unchecked(rpc: Option<Arc<dyn SeraiRpc>>, urls: Vec<String>) -> Self {
+    Serai {
+      rpc,
+      urls: Arc::new(urls),
+      endpoint: Arc::new(AtomicUsize::new(0)),
+      transport: Transport::new(),
+      genesis: [0xfe; 32],
+      extensions: Arc::new(Extensions::new()),
+      backoff: Backoff::default(),
+      rate_limiter: None,
+      errors: Arc::new(RwLock::new(None)),
+    }
+  }
+
+  async fn new_internal(
+    rpc: Option<Arc<dyn SeraiRpc>>,
+    urls: Vec<String>,
+  ) -> Result<Self, SeraiError> {
+    let mut res = Self::unchecked(rpc, urls);
     res.genesis = res.block_hash(0).await?.ok_or_else(|| {
       SeraiError::InvalidNode("node didn't have the first block's hash".to_string())
     })?;
+    // Handlers other than nodes, such as `MockSerai`, aren't expected to serve metadata
+    if res.rpc.is_none() {
+      let metadata = res.metadata(res.latest_finalized_block_hash().await?).await?;
+      let missing = metadata::missing_calls(&metadata)?;
+      if !missing.is_empty() {
+        Err(SeraiError::IncompatibleRuntime(missing))?;
+      }
+    }
     Ok(res)
   }
 
+  /// Use the specified extensions to decode events and calls from pallets Serai doesn't have.
+  pub fn with_extensions(mut self, extensions: Extensions) -> Self {
+    self.extensions = Arc::new(extensions);
+    self
+  }
+
+  /// Use the specified policy to retry requests which failed to reach the node.
+  ///
+  /// By default, requests are retried with `Backoff::default()`.
+  pub fn with_backoff(mut self, backoff: Backoff) -> Self {
+    self.backoff = backoff;
+    self
+  }
+
+  /// Limit the rate of RPC calls made by this client, queueing calls exceeding the limits.
+  ///
+  /// By default, calls aren't rate limited. Retries of calls which failed to reach the node aren't
+  /// counted against the limits.
+  pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+    self.rate_limiter = Some(Arc::new(rate_limiter));
+    self
+  }
+
+  /// The extensions registered with this client.
+  pub fn extensions(&self) -> &Extensions {
+    &self.extensions
+  }
+
   fn unsigned(call: Call) -> Transaction {
     Transaction::new(call, None)
   }
 
+  /// Batch several calls into a single call, which executes them atomically.
+  ///
+  /// If any call fails, the entire batch is reverted. Batches may not be nested, nor may their
+  /// fees be paid via a fee conversion.
+  pub fn batch(calls: Vec<Call>) -> Call {
+    Call::Utility(serai_abi::utility::Call::batch_all { calls })
+  }
+
   pub fn sign(&self, signer: &Pair, call: Call, nonce: u32, tip: u64) -> Transaction {
-    const SPEC_VERSION: u32 = 1;
-    const TX_VERSION: u32 = 1;
-
-    let extra = Extra { era: sp_runtime::generic::Era::Immortal, nonce, tip };
-    let signature_payload = (
-      &call,
-      &extra,
-      SignedPayloadExtra {
-        spec_version: SPEC_VERSION,
-        tx_version: TX_VERSION,
-        genesis: self.genesis,
-        mortality_checkpoint: self.genesis,
-      },
-    )
-      .encode();
-    let signature = signer.sign(&signature_payload);
+    self.sign_with_fee_conversion(signer, call, nonce, tip, None)
+  }
+
+  /// Sign a transaction which, if requested, sells some of an external coin for the SRI needed to
+  /// pay its fee.
+  ///
+  /// Fee conversions are only allowed for calls to the DEX or moving coins. The tip is not
+  /// covered by the conversion and must be paid from the signer's existing SRI.
+  pub fn sign_with_fee_conversion(
+    &self,
+    signer: &Pair,
+    call: Call,
+    nonce: u32,
+    tip: u64,
+    fee_conversion: Option<dex::FeeConversion>,
+  ) -> Transaction {
+    self.sign_with_era(signer, call, nonce, tip, fee_conversion, Era::Immortal, self.genesis)
+  }
+
+  /// The period of the mortal era to use for a transaction with the specified deadline.
+  ///
+  /// This is the longest period which doesn't extend past the deadline, as Substrate only allows
+  /// periods which are powers of two.
+  pub(crate) fn deadline_period(checkpoint: u64, deadline: u64) -> Option<u64> {
+    // The transaction is valid for blocks `checkpoint .. (checkpoint + period)`
+    // This saturates as a deadline of `u64::MAX` is effectively unbounded, and will be bounded to
+    // the maximum period regardless
+    let blocks = deadline.checked_sub(checkpoint)?.saturating_add(1);
+    // The largest power of two <= blocks, bounded by the amount of block hashes the runtime keeps
+    // (as the checkpoint's hash must be available for the transaction to be validated)
+    let period = (1 << blocks.ilog2()).min(MAX_MORTALITY_PERIOD);
+    // Substrate requires periods be at least 4
+    if period < 4 {
+      return None;
+    }
+    Some(period)
+  }
+
+  /// Sign a transaction which may not be included on-chain after the specified block.
+  ///
+  /// The transaction is valid starting with the checkpoint block, which should be a recent
+  /// finalized block. As Substrate only allows expressing mortality with certain periods, the
+  /// transaction may expire before the deadline, yet it will never be valid after it.
+  ///
+  /// As with `sign_with_fee_conversion`, some of an external coin may be sold for the SRI needed
+  /// to pay the fee.
+  ///
+  /// Returns `None` if the deadline is less than three blocks after the checkpoint.
+  #[allow(clippy::too_many_arguments)]
+  pub fn sign_with_deadline(
+    &self,
+    signer: &Pair,
+    call: Call,
+    nonce: u32,
+    tip: u64,
+    fee_conversion: Option<dex::FeeConversion>,
+    checkpoint: &Header,
+    deadline: u64,
+  ) -> Option<Transaction> {
+    let unsigned = self.unsigned_transaction_with_deadline(
+      call,
+      nonce,
+      tip,
+      fee_conversion,
+      checkpoint,
+      deadline,
+    )?;
+    Some(unsigned.sign(signer))
+  }
 
-    Transaction::new(call, Some((signer.public().into(), signature, extra)))
+  /// The mortal era to use for a transaction valid for `period` blocks, starting with the
+  /// checkpoint block, and the number of the first block it'll no longer be valid for.
+  pub(crate) fn mortal_era(checkpoint: u64, period: u64) -> (Era, u64) {
+    // Substrate rounds the period up to a power of two, which we bound by the amount of block
+    // hashes the runtime keeps
+    let era = Era::mortal(period.min(MAX_MORTALITY_PERIOD), checkpoint);
+    (era, era.death(checkpoint))
   }
 
+  /// Sign a transaction which is valid for `period` blocks, starting with the checkpoint block.
+  ///
+  /// The checkpoint should be a recent finalized block. The period is rounded up to a power of
+  /// two, with a minimum of 4 blocks and a maximum of 2048 blocks.
+  pub fn sign_mortal(
+    &self,
+    signer: &Pair,
+    call: Call,
+    nonce: u32,
+    tip: u64,
+    checkpoint: &Header,
+    period: u64,
+  ) -> Transaction {
+    let (era, _) = Self::mortal_era(checkpoint.number, period);
+    self.sign_with_era(signer, call, nonce, tip, None, era, checkpoint.hash().into())
+  }
+
+  #[allow(clippy::too_many_arguments)]
+  fn sign_with_era(
+    &self,
+    signer: &Pair,
+    call: Call,
+    nonce: u32,
+    tip: u64,
+    fee_conversion: Option<dex::FeeConversion>,
+    era: Era,
+    mortality_checkpoint: [u8; 32],
+  ) -> Transaction {
+    self.unsigned_with_era(call, nonce, tip, fee_conversion, era, mortality_checkpoint).sign(signer)
+  }
+
+  /// Publish a transaction.
+  ///
+  /// If the node couldn't be reached, the transaction is only submitted again once it's confirmed
+  /// to not be in the node's transaction pool, as the node may have received it before the
+  /// connection failed. If the pool couldn't be checked either, the transaction is submitted
+  /// again, until the retries allowed are exhausted.
   pub async fn publish(&self, tx: &Transaction) -> Result<(), SeraiError> {
+    let tx = tx.encode();
+    let mut retry = 0;
+    loop {
+      match self.submit(&tx).await {
+        Err(SeraiError::ConnectionError) if retry < self.backoff.retries => {
+          sleep(self.backoff.jittered_delay(retry)).await;
+          retry += 1;
+          match self.in_pool(&tx).await {
+            Ok(true) => return Ok(()),
+            // If we couldn't check the pool, whether the node received it is unknown
+            Ok(false) | Err(SeraiError::ConnectionError) => {}
+            Err(e) => return Err(e),
+          }
+        }
+        // A resubmitted transaction may have been received by the node after all
+        Err(SeraiError::ErrorInResponse(message))
+          if (retry != 0) && message.contains(ALREADY_IMPORTED) =>
+        {
+          return Ok(())
+        }
+        res => return res,
+      }
+    }
+  }
+
+  // Submit a transaction, without retrying if the node couldn't be reached
+  async fn submit(&self, tx: &[u8]) -> Result<(), SeraiError> {
+    const METHOD: &str = "author_submitExtrinsic";
+    let params = [hex::encode(tx)];
     // Drop the returned hash, which is the hash of the raw extrinsic, as extrinsics are allowed
     // to share hashes and this hash is accordingly useless/unsafe
     // If we are to return something, it should be block included in and position within block
-    let _: String = self.call("author_submitExtrinsic", [hex::encode(tx.encode())]).await?;
+    let _: String = if self.rpc.is_some() {
+      self.call(METHOD, params).await?
+    } else {
+      if let Some(rate_limiter) = &self.rate_limiter {
+        rate_limiter.wait(METHOD).await;
+      }
+      let endpoint = self.endpoint.load(Ordering::Relaxed);
+      self.call_once(endpoint, Self::request_body(METHOD, params)).await?
+    };
     Ok(())
   }
 
@@ -229,6 +605,13 @@ impl Serai {
     Ok(block)
   }
 
+  async fn latest_finalized_block_number(&self) -> Result<u64, SeraiError> {
+    let Some(header) = self.header(self.latest_finalized_block_hash().await?).await? else {
+      Err(SeraiError::InvalidNode("node didn't have its finalized block's header".to_string()))?
+    };
+    Ok(header.number)
+  }
+
   // There is no provided method for this
   // TODO: Add one to Serai
   pub async fn is_finalized(&self, header: &Header) -> Result<bool, SeraiError> {
@@ -272,29 +655,69 @@ impl Serai {
     Ok(Some(block))
   }
 
-  /*
-  /// A stream which yields whenever new block(s) have been finalized.
-  pub async fn newly_finalized_block(
+  /// A stream of finalized blocks, starting with the block with the specified number.
+  ///
+  /// This polls the node for the next finalized block every `poll_interval`, yielding blocks in
+  /// order and without gaps. If an error is yielded, the stream will retry fetching the same block
+  /// after `poll_interval`.
+  pub fn finalized_blocks(
     &self,
-  ) -> Result<impl Stream<Item = Result<(), SeraiError>>, SeraiError> {
-    Ok(self.0.rpc().subscribe_finalized_block_headers().await
-    .map_err(|_| SeraiError::ConnectionError)?.map(
-      |next| {
-        next.map_err(|_| SeraiError::ConnectionError)?;
-        Ok(())
-      },
-    ))
+    start: u64,
+    poll_interval: Duration,
+  ) -> impl Stream<Item = Result<Block, SeraiError>> + '_ {
+    stream::unfold((start, false), move |(next, errored)| async move {
+      if errored {
+        sleep(poll_interval).await;
+      }
+      loop {
+        match self.finalized_block_by_number(next).await {
+          Ok(Some(block)) => return Some((Ok(block), (next + 1, false))),
+          Ok(None) => sleep(poll_interval).await,
+          Err(e) => return Some((Err(e), (next, true))),
+        }
+      }
+    })
   }
 
-  pub async fn nonce(&self, address: &SeraiAddress) -> Result<u32, SeraiError> {
-    self
-      .0
-      .rpc()
-      .system_account_next_index(&sp_core::sr25519::Public(address.0).to_string())
-      .await
-      .map_err(|_| SeraiError::ConnectionError)
+  /// A stream of the numbers and hashes of blocks as they're finalized, starting with the block
+  /// after the latest finalized block.
+  ///
+  /// Unlike `finalized_blocks`, this doesn't fetch the blocks themselves. This polls the node for
+  /// its latest finalized block every `poll_interval`, yielding every block finalized since, in
+  /// order and without gaps. If an error is yielded, the stream will retry after `poll_interval`.
+  pub fn subscribe_finalized_blocks(
+    &self,
+    poll_interval: Duration,
+  ) -> impl Stream<Item = Result<(u64, [u8; 32]), SeraiError>> + '_ {
+    // The next block to yield and the number of the latest finalized block, once known
+    stream::unfold((None, 0, false), move |(mut next, mut finalized, errored)| async move {
+      if errored {
+        sleep(poll_interval).await;
+      }
+      loop {
+        if let Some(number) = next.filter(|next| *next <= finalized) {
+          return Some(match self.block_hash(number).await {
+            Ok(Some(hash)) => (Ok((number, hash)), (Some(number + 1), finalized, false)),
+            Ok(None) => (
+              Err(SeraiError::InvalidNode("node didn't have a finalized block's hash".to_string())),
+              (next, finalized, true),
+            ),
+            Err(e) => (Err(e), (next, finalized, true)),
+          });
+        }
+
+        let latest = match self.latest_finalized_block_number().await {
+          Ok(latest) => latest,
+          Err(e) => return Some((Err(e), (next, finalized, true))),
+        };
+        if next.is_some() && (latest == finalized) {
+          sleep(poll_interval).await;
+        }
+        finalized = latest;
+        next = next.or(Some(latest + 1));
+      }
+    })
   }
-  */
 
   /// Create a TemporalSerai bound to whatever is currently the latest finalized block.
   ///
@@ -310,6 +733,11 @@ impl Serai {
     TemporalSerai { serai: self, block, events: RwLock::new(None) }
   }
 
+  /// The next nonce for an account, accounting for its transactions in the node's pool.
+  pub async fn next_nonce(&self, address: SeraiAddress) -> Result<u32, SeraiError> {
+    self.call("system_accountNextIndex", [Public::from(address).to_string()]).await
+  }
+
   /// Return the P2P Multiaddrs for the validators of the specified network.
   pub async fn p2p_validators(
     &self,
@@ -323,6 +751,13 @@ impl<'a> TemporalSerai<'a> {
   async fn events<E>(
     &self,
     filter_map: impl Fn(&Event) -> Option<E>,
+  ) -> Result<Vec<E>, SeraiError> {
+    self.event_records(|record| filter_map(&record.event)).await
+  }
+
+  async fn event_records<E>(
+    &self,
+    filter_map: impl Fn(&frame_system::EventRecord<Event, [u8; 32]>) -> Option<E>,
   ) -> Result<Vec<E>, SeraiError> {
     let mut events = self.events.read().await;
     if events.is_none() {
@@ -336,35 +771,140 @@ impl<'a> TemporalSerai<'a> {
     }
 
     let mut res = vec![];
-    for event in events.as_ref().unwrap() {
-      if let Some(event) = filter_map(&event.event) {
+    for record in events.as_ref().unwrap() {
+      if let Some(event) = filter_map(record) {
         res.push(event);
       }
     }
     Ok(res)
   }
 
+  /// The events emitted by the extrinsic at the specified index within this block.
+  pub async fn extrinsic_events(&self, index: u32) -> Result<Vec<Event>, SeraiError> {
+    self
+      .event_records(|record| {
+        (record.phase == frame_system::Phase::ApplyExtrinsic(index)).then(|| record.event.clone())
+      })
+      .await
+  }
+
+  fn storage_key<K: Encode>(pallet: &'static str, name: &'static str, key: K) -> Vec<u8> {
+    // TODO: Make this const?
+    let mut full_key = sp_core::hashing::twox_128(pallet.as_bytes()).to_vec();
+    full_key.extend(sp_core::hashing::twox_128(name.as_bytes()));
+    full_key.extend(key.encode());
+    full_key
+  }
+
+  fn decode_storage<R: Decode>(value: String) -> Result<R, SeraiError> {
+    let value = Serai::hex_decode(value)?;
+    R::decode(&mut value.as_slice()).map_err(|_| {
+      SeraiError::InvalidRuntime(format!(
+        "different type present at storage location, raw value: {}",
+        hex::encode(value)
+      ))
+    })
+  }
+
   async fn storage<K: Encode, R: Decode>(
     &self,
     pallet: &'static str,
     name: &'static str,
     key: K,
   ) -> Result<Option<R>, SeraiError> {
-    // TODO: Make this const?
-    let mut full_key = sp_core::hashing::twox_128(pallet.as_bytes()).to_vec();
-    full_key.extend(sp_core::hashing::twox_128(name.as_bytes()));
-    full_key.extend(key.encode());
-
+    let full_key = Self::storage_key(pallet, name, key);
     let res: Option<String> =
       self.serai.call("state_getStorage", [hex::encode(full_key), hex::encode(self.block)]).await?;
     let Some(res) = res else { return Ok(None) };
-    let res = Serai::hex_decode(res)?;
-    Ok(Some(R::decode(&mut res.as_slice()).map_err(|_| {
-      SeraiError::InvalidRuntime(format!(
-        "different type present at storage location, raw value: {}",
-        hex::encode(res)
-      ))
-    })?))
+    Ok(Some(Self::decode_storage(res)?))
+  }
+
+  /// The full keys of every entry within a storage map.
+  async fn storage_keys(
+    &self,
+    pallet: &'static str,
+    name: &'static str,
+  ) -> Result<Vec<Vec<u8>>, SeraiError> {
+    let mut res = vec![];
+    loop {
+      let mut page = self.storage_keys_page(pallet, name, res.last()).await?;
+      let len = page.len();
+      res.append(&mut page);
+      if len < STORAGE_PAGE_SIZE {
+        break;
+      }
+    }
+    Ok(res)
+  }
+
+  /// The full keys of up to `STORAGE_PAGE_SIZE` entries within a storage map, after the
+  /// specified key.
+  async fn storage_keys_page(
+    &self,
+    pallet: &'static str,
+    name: &'static str,
+    start: Option<&Vec<u8>>,
+  ) -> Result<Vec<Vec<u8>>, SeraiError> {
+    let prefix = hex::encode(Self::storage_key(pallet, name, ()));
+    let page: Vec<String> = self
+      .serai
+      .call(
+        "state_getKeysPaged",
+        (&prefix, STORAGE_PAGE_SIZE, start.map(hex::encode), hex::encode(self.block)),
+      )
+      .await?;
+    page.into_iter().map(Serai::hex_decode).collect()
+  }
+
+  /// Fetch multiple entries from a storage map, batching the queries made.
+  ///
+  /// The keys are the same as would be passed to query each value, with any hashing performed. Up
+  /// to 1000 entries are fetched per call to the node. The values are returned in the same order
+  /// as the keys.
+  pub async fn storage_multi<K: Encode, R: Decode>(
+    &self,
+    pallet: &'static str,
+    name: &'static str,
+    keys: Vec<K>,
+  ) -> Result<Vec<Option<R>>, SeraiError> {
+    let keys = keys.into_iter().map(|key| Self::storage_key(pallet, name, key)).collect::<Vec<_>>();
+    self.raw_storage_batch(keys).await
+  }
+
+  /// Fetch the values at multiple full storage keys, batching the queries made.
+  ///
+  /// The values are returned in the same order as the keys.
+  async fn raw_storage_batch<R: Decode>(
+    &self,
+    keys: Vec<Vec<u8>>,
+  ) -> Result<Vec<Option<R>>, SeraiError> {
+    #[derive(Deserialize)]
+    struct StorageChangeSet {
+      changes: Vec<(String, Option<String>)>,
+    }
+
+    let mut values = HashMap::new();
+    for chunk in keys.chunks(STORAGE_PAGE_SIZE) {
+      let sets: Vec<StorageChangeSet> = self
+        .serai
+        .call(
+          "state_queryStorageAt",
+          (chunk.iter().map(hex::encode).collect::<Vec<_>>(), hex::encode(self.block)),
+        )
+        .await?;
+      for (key, value) in sets.into_iter().flat_map(|set| set.changes) {
+        values.insert(Serai::hex_decode(key)?, value);
+      }
+    }
+
+    let mut res = Vec::with_capacity(keys.len());
+    for key in keys {
+      res.push(match values.remove(&key).flatten() {
+        Some(value) => Some(Self::decode_storage(value)?),
+        None => None,
+      });
+    }
+    Ok(res)
   }
 
   async fn runtime_api<P: Encode, R: Decode>(
@@ -389,6 +929,54 @@ impl<'a> TemporalSerai<'a> {
     })
   }
 
+  /// The events within this block, decoded using the registered extensions.
+  pub async fn extended_events(&self) -> Result<Vec<ExtendedEvent>, SeraiError> {
+    let key = Self::storage_key("System", "Events", ());
+    let res: Option<String> =
+      self.serai.call("state_getStorage", [hex::encode(key), hex::encode(self.block)]).await?;
+    let Some(res) = res else { return Ok(vec![]) };
+    self.serai.extensions.decode_event_records(&Serai::hex_decode(res)?).map_err(|_| {
+      SeraiError::InvalidRuntime(
+        "events couldn't be decoded with the registered extensions".to_string(),
+      )
+    })
+  }
+
+  /// Read a value from storage, such as from the storage of an extension pallet.
+  ///
+  /// The key is expected to already be hashed as the storage item requires.
+  pub async fn extension_storage<K: Encode, R: Decode>(
+    &self,
+    pallet: &'static str,
+    name: &'static str,
+    key: K,
+  ) -> Result<Option<R>, SeraiError> {
+    self.storage(pallet, name, key).await
+  }
+
+  /// The nonce of an account as of this block.
+  pub async fn nonce(&self, address: SeraiAddress) -> Result<u32, SeraiError> {
+    // The account's information is prefixed by its nonce, allowing us to solely decode that
+    Ok(
+      self
+        .storage("System", "Account", (sp_core::hashing::blake2_128(&address.encode()), &address.0))
+        .await?
+        .unwrap_or(0),
+    )
+  }
+
+  /// Dry-run a transaction on top of this block, returning the result of applying it.
+  ///
+  /// This lets callers detect failures, such as a swap's slippage being exceeded, before
+  /// publishing the transaction. This requires the node expose its unsafe RPC methods.
+  pub async fn dry_run(&self, tx: &Transaction) -> Result<ApplyExtrinsicResult, SeraiError> {
+    let result: String =
+      self.serai.call("system_dryRun", [hex::encode(tx.encode()), hex::encode(self.block)]).await?;
+    let bytes = Serai::hex_decode(result)?;
+    ApplyExtrinsicResult::decode(&mut bytes.as_slice())
+      .map_err(|_| SeraiError::InvalidNode("returned an invalid dry-run result".to_string()))
+  }
+
   pub fn coins(&'a self) -> SeraiCoins<'a> {
     SeraiCoins(self)
   }
@@ -412,4 +1000,12 @@ impl<'a> TemporalSerai<'a> {
   pub fn liquidity_tokens(&'a self) -> SeraiLiquidityTokens {
     SeraiLiquidityTokens(self)
   }
+
+  pub fn grandpa(&'a self) -> SeraiGrandpa<'a> {
+    SeraiGrandpa(self)
+  }
+
+  pub fn emissions(&'a self) -> SeraiEmissions<'a> {
+    SeraiEmissions(self)
+  }
 }
@@ -0,0 +1,56 @@
+use std::{sync::Arc, collections::HashMap};
+
+use async_lock::Mutex;
+
+use serai_abi::Call;
+
+use crate::{primitives::SeraiAddress, Pair, PairTrait, Transaction, SeraiError, Serai};
+
+/// A manager of the nonces for signers submitting several transactions concurrently.
+///
+/// The nonce of an account on-chain only increments once a transaction is included, so signing
+/// multiple transactions in a row with the on-chain nonce will cause all but one to be rejected.
+/// This tracks the next nonce for each signer, handing out sequential nonces without waiting for
+/// the prior transactions to be included.
+#[derive(Clone)]
+pub struct NonceManager {
+  serai: Serai,
+  nonces: Arc<Mutex<HashMap<SeraiAddress, u32>>>,
+}
+
+impl NonceManager {
+  pub fn new(serai: Serai) -> NonceManager {
+    NonceManager { serai, nonces: Arc::new(Mutex::new(HashMap::new())) }
+  }
+
+  /// Take the next nonce for a signer.
+  ///
+  /// The first nonce taken for a signer is synced from the node.
+  pub async fn next(&self, signer: SeraiAddress) -> Result<u32, SeraiError> {
+    let mut nonces = self.nonces.lock().await;
+    let nonce = match nonces.get(&signer) {
+      Some(nonce) => *nonce,
+      None => self.serai.next_nonce(signer).await?,
+    };
+    nonces.insert(signer, nonce + 1);
+    Ok(nonce)
+  }
+
+  /// Resync a signer's nonce from the node, returning the next nonce it'll be handed.
+  ///
+  /// This should be called after a transaction is dropped, as the nonces handed out after its
+  /// nonce won't be valid until its nonce is used. Transactions already signed with those nonces
+  /// should be signed again.
+  pub async fn resync(&self, signer: SeraiAddress) -> Result<u32, SeraiError> {
+    let mut nonces = self.nonces.lock().await;
+    let nonce = self.serai.next_nonce(signer).await?;
+    nonces.insert(signer, nonce);
+    Ok(nonce)
+  }
+
+  /// Sign a transaction with the signer's next nonce.
+  pub async fn sign(&self, signer: &Pair, call: Call, tip: u64) -> Result<Transaction, SeraiError> {
+    let nonce = self.next(signer.public().into()).await?;
+    Ok(self.serai.sign(signer, call, nonce, tip))
+  }
+}
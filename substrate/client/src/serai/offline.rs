@@ -0,0 +1,109 @@
+use scale::{Encode, Decode};
+
+use sp_runtime::generic::Era;
+
+use serai_abi::{Call, Extra, SignedPayloadExtra};
+
+use crate::{
+  primitives::Header, dex::FeeConversion, upgrades, Pair, PairTrait, Public, SeraiAddress,
+  Signature, Transaction, Serai,
+};
+
+/// A transaction yet to be signed.
+///
+/// This lets a transaction be built on a machine connected to a node, which knows the signer's
+/// nonce and a recent block, then SCALE-encoded and signed on an airgapped machine holding the
+/// signer's key. The signature can then be attached and the transaction published later.
+#[derive(Clone, PartialEq, Eq, Debug, Encode, Decode)]
+pub struct UnsignedTransaction {
+  pub call: Call,
+  pub extra: Extra,
+  pub signed_extra: SignedPayloadExtra,
+}
+
+impl UnsignedTransaction {
+  /// The message the signer signs.
+  pub fn signing_payload(&self) -> Vec<u8> {
+    (&self.call, &self.extra, &self.signed_extra).encode()
+  }
+
+  /// Sign this transaction, producing its signature.
+  ///
+  /// This doesn't require a connection to a node.
+  pub fn signature(&self, signer: &Pair) -> Signature {
+    signer.sign(&self.signing_payload())
+  }
+
+  /// Sign this transaction.
+  ///
+  /// This doesn't require a connection to a node.
+  pub fn sign(self, signer: &Pair) -> Transaction {
+    let signature = self.signature(signer);
+    Transaction::new(self.call, Some((signer.public().into(), signature, self.extra)))
+  }
+
+  /// Attach a signature produced elsewhere, such as by an airgapped machine.
+  ///
+  /// Returns `None` if the signature isn't valid for this transaction and signer.
+  pub fn with_signature(self, signer: SeraiAddress, signature: Signature) -> Option<Transaction> {
+    if !Pair::verify(&signature, self.signing_payload(), &Public::from(signer)) {
+      return None;
+    }
+    Some(Transaction::new(self.call, Some((signer, signature, self.extra))))
+  }
+}
+
+impl Serai {
+  pub(crate) fn unsigned_with_era(
+    &self,
+    call: Call,
+    nonce: u32,
+    tip: u64,
+    fee_conversion: Option<FeeConversion>,
+    era: Era,
+    mortality_checkpoint: [u8; 32],
+  ) -> UnsignedTransaction {
+    UnsignedTransaction {
+      call,
+      extra: Extra { era, nonce, fee_conversion, tip },
+      signed_extra: SignedPayloadExtra {
+        spec_version: upgrades::SPEC_VERSION,
+        tx_version: upgrades::TX_VERSION,
+        genesis: self.genesis,
+        mortality_checkpoint,
+      },
+    }
+  }
+
+  /// Build a transaction to be signed later, as by an airgapped machine.
+  ///
+  /// The transaction never expires, as with `Serai::sign`.
+  pub fn unsigned_transaction(
+    &self,
+    call: Call,
+    nonce: u32,
+    tip: u64,
+    fee_conversion: Option<FeeConversion>,
+  ) -> UnsignedTransaction {
+    self.unsigned_with_era(call, nonce, tip, fee_conversion, Era::Immortal, self.genesis)
+  }
+
+  /// Build a transaction to be signed later, which may not be included on-chain after the
+  /// specified block.
+  ///
+  /// This follows the same rules as `Serai::sign_with_deadline`, returning `None` if the deadline
+  /// is less than three blocks after the checkpoint.
+  pub fn unsigned_transaction_with_deadline(
+    &self,
+    call: Call,
+    nonce: u32,
+    tip: u64,
+    fee_conversion: Option<FeeConversion>,
+    checkpoint: &Header,
+    deadline: u64,
+  ) -> Option<UnsignedTransaction> {
+    let period = Self::deadline_period(checkpoint.number, deadline)?;
+    let era = Era::mortal(period, checkpoint.number);
+    Some(self.unsigned_with_era(call, nonce, tip, fee_conversion, era, checkpoint.hash().into()))
+  }
+}
@@ -0,0 +1,84 @@
+use scale::{Encode, Decode};
+use serde::Deserialize;
+
+use sp_runtime::traits::BlakeTwo256;
+use sp_trie::LayoutV1;
+
+use crate::{primitives::Header, grandpa::AuthoritySet, SeraiError, Serai, TemporalSerai};
+
+/// A proof of the values within a block's state, as returned by the node.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct StorageProof(pub Vec<Vec<u8>>);
+
+impl StorageProof {
+  /// Verify this proves the value at the specified key under the specified state root.
+  ///
+  /// This returns `None` if the proof proves there's no value at the key, and errors if the proof
+  /// doesn't prove anything about the key.
+  pub fn verify(&self, state_root: [u8; 32], key: &[u8]) -> Result<Option<Vec<u8>>, SeraiError> {
+    let db = sp_trie::StorageProof::new(self.0.iter().cloned()).into_memory_db::<BlakeTwo256>();
+    sp_trie::read_trie_value::<LayoutV1<BlakeTwo256>, _>(&db, &state_root.into(), key, None, None)
+      .map_err(|_| SeraiError::InvalidNode("storage proof didn't prove the key".to_string()))
+  }
+}
+
+#[derive(Deserialize)]
+struct ReadProof {
+  proof: Vec<String>,
+}
+
+impl Serai {
+  /// Fetch the header of the finalized block with the specified number, verifying its finality
+  /// under the specified authority set.
+  ///
+  /// The authority set MUST be the one which finalized the block, such as tracked from a trusted
+  /// checkpoint with `AuthoritySet::apply`. This will return `None` if the block hasn't been
+  /// finalized.
+  pub async fn verified_finalized_header(
+    &self,
+    number: u64,
+    set: &AuthoritySet,
+  ) -> Result<Option<Header>, SeraiError> {
+    let Some(proof) = self.finality_proof(number).await? else { return Ok(None) };
+    let Some(hash) = self.block_hash(number).await? else {
+      Err(SeraiError::InvalidNode("node didn't have a finalized block's hash".to_string()))?
+    };
+    proof.verify(hash, set)?;
+    let Some(header) = self.header(hash).await? else {
+      Err(SeraiError::InvalidNode("node didn't have a finalized block's header".to_string()))?
+    };
+    if (<[u8; 32]>::from(header.hash()) != hash) || (header.number != number) {
+      Err(SeraiError::InvalidNode("node returned a header other than requested".to_string()))?;
+    }
+    Ok(Some(header))
+  }
+}
+
+impl<'a> TemporalSerai<'a> {
+  /// Fetch a proof of the value at a storage location, as of this block.
+  pub async fn storage_proof(&self, key: &[u8]) -> Result<StorageProof, SeraiError> {
+    let proof: ReadProof =
+      self.serai.call("state_getReadProof", ([hex::encode(key)], hex::encode(self.block))).await?;
+    Ok(StorageProof(proof.proof.into_iter().map(Serai::hex_decode).collect::<Result<_, _>>()?))
+  }
+
+  /// Read a value from storage, as of this block, verifying it against the block's header.
+  ///
+  /// The header should be verified, as with `Serai::verified_finalized_header`, for this to not
+  /// require trusting the node.
+  pub async fn verified_storage<K: Encode, R: Decode>(
+    &self,
+    header: &Header,
+    pallet: &'static str,
+    name: &'static str,
+    key: K,
+  ) -> Result<Option<R>, SeraiError> {
+    if <[u8; 32]>::from(header.hash()) != self.block {
+      Err(SeraiError::ErrorInResponse("header wasn't for this block".to_string()))?;
+    }
+    let key = Self::storage_key(pallet, name, key);
+    let proof = self.storage_proof(&key).await?;
+    let Some(value) = proof.verify(header.state_root.into(), &key)? else { return Ok(None) };
+    Self::decode_storage(hex::encode(value)).map(Some)
+  }
+}
@@ -0,0 +1,114 @@
+use core::time::Duration;
+use std::{sync::Mutex, collections::HashMap};
+
+use patchable_async_sleep::{sleep, Instant};
+
+/// A class of RPC methods, with each class rate limited independently.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum MethodClass {
+  /// Reads of storage, such as `state_getStorage`.
+  Storage,
+  /// Calls of runtime APIs, via `state_call`.
+  RuntimeApi,
+  /// Reads of blocks and headers, such as `chain_getBlockBin`.
+  Chain,
+  /// Submission of transactions and inspection of the transaction pool.
+  Author,
+  /// Every other method.
+  Other,
+}
+
+impl MethodClass {
+  /// The class of an RPC method.
+  pub fn of(method: &str) -> MethodClass {
+    match method {
+      "state_getStorage" | "state_queryStorageAt" | "state_getKeysPaged" | "state_getReadProof" => {
+        MethodClass::Storage
+      }
+      "state_call" => MethodClass::RuntimeApi,
+      _ if method.starts_with("chain_") => MethodClass::Chain,
+      _ if method.starts_with("author_") => MethodClass::Author,
+      _ => MethodClass::Other,
+    }
+  }
+}
+
+/// A token bucket limiting the rate of requests.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RateLimit {
+  /// The interval at which the bucket gains another request.
+  pub interval: Duration,
+  /// The amount of requests the bucket holds, which may be made at once.
+  pub burst: u32,
+}
+
+impl RateLimit {
+  /// A limit of the specified amount of requests per second, allowing bursts of that many
+  /// requests.
+  pub fn per_second(requests: u32) -> RateLimit {
+    assert!(requests != 0, "rate limit of zero requests per second");
+    RateLimit { interval: Duration::from_secs(1) / requests, burst: requests }
+  }
+}
+
+/// A client-side rate limiter for RPC calls, with a token bucket per class of methods.
+///
+/// Calls exceeding their limit are queued until the bucket refills, instead of erroring. Classes
+/// without a limit aren't rate limited.
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+  limits: HashMap<MethodClass, RateLimit>,
+  // The time the next request would be made at if the bucket was empty, per class
+  //
+  // This is the generic cell rate algorithm, which is equivalent to a token bucket while only
+  // tracking a single instant.
+  next: Mutex<HashMap<MethodClass, Instant>>,
+}
+
+impl RateLimiter {
+  /// A rate limiter with the same limit for every class of methods.
+  pub fn new(limit: RateLimit) -> RateLimiter {
+    let classes = [
+      MethodClass::Storage,
+      MethodClass::RuntimeApi,
+      MethodClass::Chain,
+      MethodClass::Author,
+      MethodClass::Other,
+    ];
+    RateLimiter {
+      limits: classes.into_iter().map(|class| (class, limit)).collect(),
+      next: Mutex::new(HashMap::new()),
+    }
+  }
+
+  /// Set the limit for a class of methods.
+  pub fn with_limit(mut self, class: MethodClass, limit: RateLimit) -> RateLimiter {
+    self.limits.insert(class, limit);
+    self
+  }
+
+  /// Remove the limit for a class of methods.
+  pub fn without_limit(mut self, class: MethodClass) -> RateLimiter {
+    self.limits.remove(&class);
+    self
+  }
+
+  // Reserve a request of the specified class, returning how long to wait before making it
+  pub(crate) fn reserve(&self, class: MethodClass, now: Instant) -> Duration {
+    let Some(limit) = self.limits.get(&class) else { return Duration::ZERO };
+    let mut next = self.next.lock().unwrap();
+    let next = next.entry(class).or_insert(now);
+    // If no requests were made for a while, the bucket is full, without accruing further requests
+    *next = (*next).max(now) + limit.interval;
+    // The request may be made once the bucket has refilled enough to hold it
+    let allowed = next.checked_sub(limit.interval * limit.burst).unwrap_or(now);
+    allowed.saturating_duration_since(now)
+  }
+
+  pub(crate) async fn wait(&self, method: &str) {
+    let delay = self.reserve(MethodClass::of(method), Instant::now());
+    if !delay.is_zero() {
+      sleep(delay).await;
+    }
+  }
+}
@@ -0,0 +1,16 @@
+use core::{future::Future, pin::Pin};
+
+use crate::SeraiError;
+
+/// The result of a JSON-RPC call.
+pub type RpcFuture<'a> =
+  Pin<Box<dyn 'a + Send + Future<Output = Result<serde_json::Value, SeraiError>>>>;
+
+/// A handler of Serai's JSON-RPC methods, letting `Serai` be backed by something other than a
+/// node, such as a `MockSerai` within unit tests.
+///
+/// Errors returned by the node in response to a call should be returned as
+/// `SeraiError::ErrorInResponse`.
+pub trait SeraiRpc: Send + Sync {
+  fn call<'a>(&'a self, method: &'a str, params: serde_json::Value) -> RpcFuture<'a>;
+}
@@ -0,0 +1,83 @@
+#[cfg(all(target_arch = "wasm32", not(feature = "wasm")))]
+compile_error!("serai-client's wasm feature is required to use a Serai client on wasm32");
+
+#[cfg(not(target_arch = "wasm32"))]
+mod native {
+  use std::io::Read;
+
+  use simple_request::{hyper, Request, Client};
+
+  use crate::SeraiError;
+
+  /// The transport used to make requests to nodes, which is HTTP on native targets.
+  #[derive(Clone)]
+  pub(crate) struct Transport(Client);
+
+  impl Transport {
+    pub(crate) fn new() -> Self {
+      Transport(Client::with_connection_pool())
+    }
+
+    pub(crate) async fn request(&self, url: &str, body: Vec<u8>) -> Result<Vec<u8>, SeraiError> {
+      let request = Request::from(
+        hyper::Request::post(url)
+          .header("Content-Type", "application/json")
+          .body(body.into())
+          .unwrap(),
+      );
+
+      let mut res = vec![];
+      self
+        .0
+        .request(request)
+        .await
+        .map_err(|_| SeraiError::ConnectionError)?
+        .body()
+        .await
+        .map_err(|_| SeraiError::ConnectionError)?
+        .read_to_end(&mut res)
+        .map_err(|_| SeraiError::ConnectionError)?;
+      Ok(res)
+    }
+  }
+}
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use native::Transport;
+
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+mod wasm {
+  use futures_util::{SinkExt, StreamExt};
+  use gloo_net::websocket::{Message, futures::WebSocket};
+
+  use crate::SeraiError;
+
+  /// The transport used to make requests to nodes, which is a browser's WebSocket on wasm32.
+  ///
+  /// The endpoints are expected to be `ws://` or `wss://` URLs.
+  #[derive(Clone)]
+  pub(crate) struct Transport;
+
+  impl Transport {
+    pub(crate) fn new() -> Self {
+      Transport
+    }
+
+    pub(crate) async fn request(&self, url: &str, body: Vec<u8>) -> Result<Vec<u8>, SeraiError> {
+      // A socket is opened per request, so concurrent requests don't have to have their responses
+      // demultiplexed
+      let mut socket = WebSocket::open(url).map_err(|_| SeraiError::ConnectionError)?;
+      let body = String::from_utf8(body).expect("JSON-RPC request wasn't UTF-8");
+      socket.send(Message::Text(body)).await.map_err(|_| SeraiError::ConnectionError)?;
+      let res = match socket.next().await {
+        Some(Ok(Message::Text(res))) => res.into_bytes(),
+        Some(Ok(Message::Bytes(res))) => res,
+        Some(Err(_)) | None => Err(SeraiError::ConnectionError)?,
+      };
+      // The response was received, so if the socket fails to close, there's nothing to be done
+      let _ = socket.close(None, None);
+      Ok(res)
+    }
+  }
+}
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+pub(crate) use wasm::Transport;
@@ -0,0 +1,140 @@
+use core::time::Duration;
+
+use serde::{Serialize, Deserialize};
+
+use futures_util::{stream, Stream};
+use patchable_async_sleep::sleep;
+
+use serai_abi::{system, Event};
+
+use crate::{SeraiError, Block, Serai};
+
+/// The spec version of the runtime this library was built for.
+pub const SPEC_VERSION: u32 = 3;
+/// The transaction version of the runtime this library was built for.
+pub const TX_VERSION: u32 = 2;
+
+/// The version of a runtime, as reported by a node.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimeVersion {
+  pub spec_name: String,
+  pub spec_version: u32,
+  pub transaction_version: u32,
+}
+
+/// How compatible this library is with a runtime.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize)]
+pub enum Compatibility {
+  /// This library was built for this runtime.
+  Compatible,
+  /// The runtime is newer than the one this library was built for.
+  ///
+  /// Transactions signed by this library will be rejected and decoding may fail. Services should
+  /// be updated to a version of this library built for the new runtime.
+  ClientOutdated,
+  /// The runtime is older than the one this library was built for.
+  ///
+  /// This is presumably a node which has yet to sync past the upgrade, or a network which has yet
+  /// to enact it.
+  RuntimeOutdated,
+}
+
+impl RuntimeVersion {
+  /// How compatible this library is with this runtime.
+  pub fn compatibility(&self) -> Compatibility {
+    let ours = (SPEC_VERSION, TX_VERSION);
+    let theirs = (self.spec_version, self.transaction_version);
+    if theirs == ours {
+      Compatibility::Compatible
+    } else if (theirs.0 > ours.0) || ((theirs.0 == ours.0) && (theirs.1 > ours.1)) {
+      Compatibility::ClientOutdated
+    } else {
+      Compatibility::RuntimeOutdated
+    }
+  }
+}
+
+/// An upgrade of the runtime.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+pub struct RuntimeUpgrade {
+  /// The number of the block which set the new runtime.
+  pub block: u64,
+  /// The number of the first block executed with the new runtime.
+  pub enacted: u64,
+  pub previous: RuntimeVersion,
+  pub version: RuntimeVersion,
+}
+
+impl RuntimeUpgrade {
+  /// How compatible this library is with the new runtime.
+  pub fn compatibility(&self) -> Compatibility {
+    self.version.compatibility()
+  }
+}
+
+impl Serai {
+  /// The version of the runtime as of the specified block.
+  pub async fn runtime_version(&self, block: [u8; 32]) -> Result<RuntimeVersion, SeraiError> {
+    self.call("state_getRuntimeVersion", [hex::encode(block)]).await
+  }
+
+  /// The version of the runtime as of the latest finalized block.
+  pub async fn latest_runtime_version(&self) -> Result<RuntimeVersion, SeraiError> {
+    self.runtime_version(self.latest_finalized_block_hash().await?).await
+  }
+
+  async fn runtime_upgrade(&self, block: &Block) -> Result<Option<RuntimeUpgrade>, SeraiError> {
+    let updated = self
+      .as_of(block.hash())
+      .events(|event| matches!(event, Event::System(system::Event::CodeUpdated)).then_some(()))
+      .await?;
+    if updated.is_empty() {
+      return Ok(None);
+    }
+
+    // The runtime version as of a block is the version after its execution
+    let previous = self.runtime_version(block.header.parent_hash.into()).await?;
+    let version = self.runtime_version(block.hash()).await?;
+    Ok(Some(RuntimeUpgrade {
+      block: block.number(),
+      enacted: block.number() + 1,
+      previous,
+      version,
+    }))
+  }
+
+  /// A stream of the runtime upgrades within finalized blocks, starting with the block with the
+  /// specified number.
+  ///
+  /// Each upgrade is yielded upon the finalization of the block which set the new runtime, before
+  /// any block is executed with it, giving services a chance to alert their operators before
+  /// decoding starts failing. As with `finalized_blocks`, this polls every `poll_interval`, and if
+  /// an error is yielded, the stream will retry the same block after `poll_interval`.
+  pub fn runtime_upgrades(
+    &self,
+    start: u64,
+    poll_interval: Duration,
+  ) -> impl Stream<Item = Result<RuntimeUpgrade, SeraiError>> + '_ {
+    stream::unfold((start, false), move |(mut next, errored)| async move {
+      if errored {
+        sleep(poll_interval).await;
+      }
+      loop {
+        let block = match self.finalized_block_by_number(next).await {
+          Ok(Some(block)) => block,
+          Ok(None) => {
+            sleep(poll_interval).await;
+            continue;
+          }
+          Err(e) => return Some((Err(e), (next, true))),
+        };
+        match self.runtime_upgrade(&block).await {
+          Ok(Some(upgrade)) => return Some((Ok(upgrade), (next + 1, false))),
+          Ok(None) => next += 1,
+          Err(e) => return Some((Err(e), (next, true))),
+        }
+      }
+    })
+  }
+}
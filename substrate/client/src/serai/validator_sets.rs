@@ -1,13 +1,27 @@
+use core::time::Duration;
+use std::collections::{VecDeque, HashMap};
+
 use scale::Encode;
 
+use futures_util::{stream, Stream};
+use patchable_async_sleep::sleep;
+
+use serde::Serialize;
+
 use sp_core::sr25519::{Public, Signature};
 
-use serai_abi::{primitives::Amount, validator_sets::primitives::ExternalValidatorSet};
+use serai_abi::{
+  primitives::{Amount, ExternalBalance},
+  validator_sets::primitives::ExternalValidatorSet,
+};
 pub use serai_abi::validator_sets::primitives;
-use primitives::{Session, KeyPair};
+use primitives::{Session, ValidatorSet, KeyPair, ExternalKey, AttemptWindow, BatchEquivocation};
 
 use crate::{
-  primitives::{NetworkId, ExternalNetworkId, SeraiAddress},
+  primitives::{
+    NetworkId, ExternalNetworkId, EXTERNAL_NETWORKS, Coin, ExternalCoin, SeraiAddress,
+    system_address,
+  },
   Transaction, Serai, TemporalSerai, SeraiError,
 };
 
@@ -15,6 +29,19 @@ const PALLET: &str = "ValidatorSets";
 
 pub type ValidatorSetsEvent = serai_abi::validator_sets::Event;
 
+/// A validator set for an external network having set its keys.
+///
+/// Once the set accepts the handover from the prior set, deposits should be made to the new key.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+pub struct KeyRotation {
+  /// The number of the block the keys were set in.
+  pub block: u64,
+  pub network: ExternalNetworkId,
+  pub session: Session,
+  /// The set's key on the external network.
+  pub key: ExternalKey,
+}
+
 #[derive(Clone, Copy)]
 pub struct SeraiValidatorSets<'a>(pub(crate) &'a TemporalSerai<'a>);
 impl<'a> SeraiValidatorSets<'a> {
@@ -69,6 +96,28 @@ impl<'a> SeraiValidatorSets<'a> {
       .await
   }
 
+  /// The key rotations within this block.
+  pub async fn key_rotations(&self) -> Result<Vec<KeyRotation>, SeraiError> {
+    let Some(header) = self.0.serai.header(self.0.block).await? else {
+      Err(SeraiError::InvalidNode("fetching key rotations within a missing block".to_string()))?
+    };
+    self
+      .0
+      .events(|event| {
+        let serai_abi::Event::ValidatorSets(ValidatorSetsEvent::KeyGen { set, key_pair }) = event
+        else {
+          return None;
+        };
+        Some(KeyRotation {
+          block: header.number,
+          network: set.network,
+          session: set.session,
+          key: key_pair.1.clone(),
+        })
+      })
+      .await
+  }
+
   pub async fn accepted_handover_events(&self) -> Result<Vec<ValidatorSetsEvent>, SeraiError> {
     self
       .0
@@ -103,10 +152,66 @@ impl<'a> SeraiValidatorSets<'a> {
       .await
   }
 
+  pub async fn loss_reported_events(&self) -> Result<Vec<ValidatorSetsEvent>, SeraiError> {
+    self
+      .0
+      .events(|event| {
+        if let serai_abi::Event::ValidatorSets(event) = event {
+          if matches!(event, ValidatorSetsEvent::LossReported { .. }) {
+            Some(event.clone())
+          } else {
+            None
+          }
+        } else {
+          None
+        }
+      })
+      .await
+  }
+
+  pub async fn compensation_claimed_events(&self) -> Result<Vec<ValidatorSetsEvent>, SeraiError> {
+    self
+      .0
+      .events(|event| {
+        if let serai_abi::Event::ValidatorSets(event) = event {
+          if matches!(event, ValidatorSetsEvent::CompensationClaimed { .. }) {
+            Some(event.clone())
+          } else {
+            None
+          }
+        } else {
+          None
+        }
+      })
+      .await
+  }
+
+  pub async fn network_address_set_events(&self) -> Result<Vec<ValidatorSetsEvent>, SeraiError> {
+    self
+      .0
+      .events(|event| {
+        if let serai_abi::Event::ValidatorSets(event) = event {
+          if matches!(event, ValidatorSetsEvent::NetworkAddressSet { .. }) {
+            Some(event.clone())
+          } else {
+            None
+          }
+        } else {
+          None
+        }
+      })
+      .await
+  }
+
   pub async fn session(&self, network: NetworkId) -> Result<Option<Session>, SeraiError> {
     self.0.storage(PALLET, "CurrentSession", network).await
   }
 
+  /// The current validator set for a network.
+  pub async fn current_set(&self, network: NetworkId) -> Result<Option<ValidatorSet>, SeraiError> {
+    Ok(self.session(network).await?.map(|session| ValidatorSet { network, session }))
+  }
+
   pub async fn participants(
     &self,
     network: NetworkId,
@@ -143,6 +248,19 @@ impl<'a> SeraiValidatorSets<'a> {
       .await
   }
 
+  /// The allocations of the current validator set's participants.
+  pub async fn participant_allocations(
+    &self,
+    network: NetworkId,
+  ) -> Result<Vec<(Public, Amount)>, SeraiError> {
+    let mut res = vec![];
+    for (participant, _) in self.participants(network).await?.unwrap_or_default() {
+      let allocation = self.allocation(network, participant).await?.unwrap_or(Amount(0));
+      res.push((participant, allocation));
+    }
+    Ok(res)
+  }
+
   pub async fn pending_deallocations(
     &self,
     network: NetworkId,
@@ -166,11 +284,44 @@ impl<'a> SeraiValidatorSets<'a> {
     self.0.runtime_api("SeraiRuntimeApi_validators", network).await
   }
 
+  pub async fn attempt_window(
+    &self,
+    network: ExternalNetworkId,
+  ) -> Result<AttemptWindow, SeraiError> {
+    self.0.runtime_api("SeraiRuntimeApi_attempt_window", network).await
+  }
+
+  /// The amount of `coin` which may currently be minted without exceeding the network's economic
+  /// security.
+  pub async fn mint_headroom(&self, coin: ExternalCoin) -> Result<Amount, SeraiError> {
+    self.0.runtime_api("SeraiRuntimeApi_mint_headroom", coin).await
+  }
+
   // TODO: Store these separately since we almost never need both at once?
   pub async fn keys(&self, set: ExternalValidatorSet) -> Result<Option<KeyPair>, SeraiError> {
     self.0.storage(PALLET, "Keys", (sp_core::hashing::twox_64(&set.encode()), set)).await
   }
 
+  /// The keys of the current validator set for an external network, if it has set them.
+  pub async fn active_keys(
+    &self,
+    network: ExternalNetworkId,
+  ) -> Result<Option<KeyPair>, SeraiError> {
+    let Some(session) = self.session(network.into()).await? else { return Ok(None) };
+    self.keys(ExternalValidatorSet { network, session }).await
+  }
+
+  /// The keys of the current validator set for every external network which has set them.
+  pub async fn all_active_keys(&self) -> Result<HashMap<ExternalNetworkId, KeyPair>, SeraiError> {
+    let mut res = HashMap::new();
+    for network in EXTERNAL_NETWORKS {
+      if let Some(keys) = self.active_keys(network).await? {
+        res.insert(network, keys);
+      }
+    }
+    Ok(res)
+  }
+
   pub async fn key_pending_slash_report(
     &self,
     network: ExternalNetworkId,
@@ -186,6 +337,54 @@ impl<'a> SeraiValidatorSets<'a> {
     self.0.storage(PALLET, "SessionBeginBlock", (network, session)).await
   }
 
+  /// If losses have already been reported against this set.
+  pub async fn loss_reported(&self, set: ExternalValidatorSet) -> Result<bool, SeraiError> {
+    Ok(
+      self
+        .0
+        .storage::<_, ()>(PALLET, "ReportedLosses", (sp_core::hashing::twox_64(&set.encode()), set))
+        .await?
+        .is_some(),
+    )
+  }
+
+  /// The compensation pending claim by an account affected by a proven loss.
+  pub async fn pending_compensation(&self, account: Public) -> Result<Option<Amount>, SeraiError> {
+    self
+      .0
+      .storage(
+        PALLET,
+        "PendingCompensation",
+        (sp_core::hashing::blake2_128(&account.encode()), account),
+      )
+      .await
+  }
+
+  /// The network address a validator published for their coordinator, if they published one.
+  pub async fn network_address(&self, validator: Public) -> Result<Option<Vec<u8>>, SeraiError> {
+    self
+      .0
+      .storage(
+        PALLET,
+        "NetworkAddresses",
+        (sp_core::hashing::blake2_128(&validator.encode()), validator),
+      )
+      .await
+  }
+
+  /// The account holding the funds used to compensate proven losses.
+  pub fn compensation_account() -> SeraiAddress {
+    system_address(b"ValidatorSets-compensation")
+  }
+
+  /// The funds available to compensate future losses, excluding those already owed.
+  pub async fn available_compensation(&self) -> Result<Amount, SeraiError> {
+    let pool = self.0.coins().coin_balance(Coin::Serai, Self::compensation_account()).await?;
+    let owed =
+      self.0.storage::<_, Amount>(PALLET, "OwedCompensation", ()).await?.unwrap_or(Amount(0));
+    Ok(Amount(pool.0.saturating_sub(owed.0)))
+  }
+
   pub fn set_keys(
     network: ExternalNetworkId,
     removed_participants: sp_runtime::BoundedVec<
@@ -211,6 +410,13 @@ impl<'a> SeraiValidatorSets<'a> {
     serai_abi::Call::ValidatorSets(serai_abi::validator_sets::Call::deallocate { network, amount })
   }
 
+  pub fn claim_deallocation(network: NetworkId, session: Session) -> serai_abi::Call {
+    serai_abi::Call::ValidatorSets(serai_abi::validator_sets::Call::claim_deallocation {
+      network,
+      session,
+    })
+  }
+
   pub fn report_slashes(
     network: ExternalNetworkId,
     slashes: sp_runtime::BoundedVec<
@@ -223,4 +429,86 @@ impl<'a> SeraiValidatorSets<'a> {
       serai_abi::validator_sets::Call::report_slashes { network, slashes, signature },
     ))
   }
+
+  /// Report the losses caused by a retired validator set, proven faulty by the evidence.
+  ///
+  /// The signature is the current validator set's, over `report_loss_message`.
+  pub fn report_loss(
+    network: ExternalNetworkId,
+    session: Session,
+    evidence: BatchEquivocation,
+    losses: sp_runtime::BoundedVec<
+      (SeraiAddress, ExternalBalance),
+      sp_core::ConstU32<{ primitives::MAX_LOSS_REPORT_ACCOUNTS }>,
+    >,
+    signature: Signature,
+  ) -> Transaction {
+    Serai::unsigned(serai_abi::Call::ValidatorSets(serai_abi::validator_sets::Call::report_loss {
+      network,
+      session,
+      evidence,
+      losses,
+      signature,
+    }))
+  }
+
+  pub fn claim_compensation() -> serai_abi::Call {
+    serai_abi::Call::ValidatorSets(serai_abi::validator_sets::Call::claim_compensation)
+  }
+
+  pub fn fund_compensation(amount: Amount) -> serai_abi::Call {
+    serai_abi::Call::ValidatorSets(serai_abi::validator_sets::Call::fund_compensation { amount })
+  }
+
+  /// Publish the network address the signer's coordinator may be reached at.
+  ///
+  /// An empty address removes the signer's published address.
+  pub fn set_network_address(
+    address: sp_runtime::BoundedVec<u8, sp_core::ConstU32<{ primitives::MAX_NETWORK_ADDRESS_LEN }>>,
+  ) -> serai_abi::Call {
+    serai_abi::Call::ValidatorSets(serai_abi::validator_sets::Call::set_network_address { address })
+  }
+}
+
+impl Serai {
+  /// A stream of the key rotations within finalized blocks, starting with the block with the
+  /// specified number.
+  ///
+  /// As with `finalized_blocks`, this polls every `poll_interval`, and if an error is yielded, the
+  /// stream will retry the same block after `poll_interval`.
+  pub fn key_rotations(
+    &self,
+    start: u64,
+    poll_interval: Duration,
+  ) -> impl Stream<Item = Result<KeyRotation, SeraiError>> + '_ {
+    // The next block to check and the rotations from the prior block yet to be yielded
+    stream::unfold(
+      (start, VecDeque::new(), false),
+      move |(mut next, mut pending, errored)| async move {
+        if errored {
+          sleep(poll_interval).await;
+        }
+        loop {
+          if let Some(rotation) = pending.pop_front() {
+            return Some((Ok(rotation), (next, pending, false)));
+          }
+          let block = match self.finalized_block_by_number(next).await {
+            Ok(Some(block)) => block,
+            Ok(None) => {
+              sleep(poll_interval).await;
+              continue;
+            }
+            Err(e) => return Some((Err(e), (next, pending, true))),
+          };
+          match self.as_of(block.hash()).validator_sets().key_rotations().await {
+            Ok(rotations) => {
+              pending = rotations.into();
+              next += 1;
+            }
+            Err(e) => return Some((Err(e), (next, pending, true))),
+          }
+        }
+      },
+    )
+  }
 }
@@ -0,0 +1,241 @@
+use core::time::Duration;
+
+use scale::Encode;
+
+use futures_util::{stream, Stream};
+use patchable_async_sleep::sleep;
+
+use serde::Serialize;
+
+use sp_runtime::DispatchError;
+
+use serai_abi::{system, Call, Event};
+
+use crate::{primitives::Header, Pair, PairTrait, Transaction, SeraiError, Serai};
+
+/// The status of a transaction submitted with `Serai::submit_and_watch`.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+pub enum TransactionStatus {
+  /// The transaction was accepted into the node's transaction pool.
+  Broadcast,
+  /// The transaction was included in a block on the best chain, yet to be finalized.
+  InBlock { block: [u8; 32], index: u32, events: Vec<Event> },
+  /// The block the transaction was included in is no longer on the best chain.
+  ///
+  /// The transaction may be included again in a later block.
+  Retracted { block: [u8; 32] },
+  /// The transaction was included in a finalized block.
+  ///
+  /// This is the final status yielded.
+  Finalized { block: [u8; 32], index: u32, events: Vec<Event> },
+  /// The transaction was dropped from the node's transaction pool without being included.
+  ///
+  /// This is the final status yielded.
+  Dropped,
+}
+
+impl TransactionStatus {
+  /// The error the transaction failed with, if it was included and failed.
+  ///
+  /// This may be decoded with `Serai::decode_dispatch_error`.
+  pub fn dispatch_error(&self) -> Option<&DispatchError> {
+    let (TransactionStatus::InBlock { events, .. } | TransactionStatus::Finalized { events, .. }) =
+      self
+    else {
+      return None;
+    };
+    events.iter().find_map(|event| match event {
+      Event::System(system::Event::ExtrinsicFailed { dispatch_error, .. }) => Some(dispatch_error),
+      _ => None,
+    })
+  }
+}
+
+enum State {
+  Submit,
+  // The transaction is pending, and the next block on the best chain to check for it
+  Pending { next: u64 },
+  Included { block: [u8; 32], number: u64, index: u32 },
+  // The transaction expired without being included
+  Expired,
+  Done,
+}
+
+impl Serai {
+  pub(crate) async fn best_header(&self) -> Result<Header, SeraiError> {
+    let header: Option<Header> = self.call("chain_getHeader", ()).await?;
+    header.ok_or_else(|| SeraiError::InvalidNode("node didn't have a best block".to_string()))
+  }
+
+  pub(crate) async fn in_pool(&self, tx: &[u8]) -> Result<bool, SeraiError> {
+    let pending: Vec<String> = self.call("author_pendingExtrinsics", ()).await?;
+    for pending in pending {
+      if Self::hex_decode(pending)? == tx {
+        return Ok(true);
+      }
+    }
+    Ok(false)
+  }
+
+  // Find the transaction within the best chain, from the block numbered `next` onwards
+  //
+  // If it isn't found, `next` is updated to the next block to check.
+  async fn find_in_best_chain(
+    &self,
+    tx: &Transaction,
+    next: &mut u64,
+  ) -> Result<Option<([u8; 32], u64, u32)>, SeraiError> {
+    let best = self.best_header().await?.number;
+    while *next <= best {
+      // If this block was re-orged out since we fetched the best block, we'll simply check its
+      // replacement
+      let Some(hash) = self.block_hash(*next).await? else { break };
+      let Some(block) = self.block(hash).await? else { break };
+      if let Some(index) = block.transactions.iter().position(|included| included == tx) {
+        return Ok(Some((hash, *next, u32::try_from(index).unwrap())));
+      }
+      *next += 1;
+    }
+    Ok(None)
+  }
+
+  // Step watching a transaction, which may no longer be included as of the block numbered `death`
+  // if it's mortal
+  async fn watch_step(
+    &self,
+    tx: &Transaction,
+    death: Option<u64>,
+    poll_interval: Duration,
+    state: State,
+  ) -> Result<(TransactionStatus, State), SeraiError> {
+    match state {
+      State::Submit => {
+        let next = self.best_header().await?.number + 1;
+        self.publish(tx).await?;
+        Ok((TransactionStatus::Broadcast, State::Pending { next }))
+      }
+      State::Pending { mut next } => {
+        let encoded = tx.encode();
+        loop {
+          // Check if the transaction is still pending before scanning the chain, so if it's
+          // removed from the pool due to its inclusion, the scan will find it
+          let in_pool = self.in_pool(&encoded).await?;
+          if let Some((block, number, index)) = self.find_in_best_chain(tx, &mut next).await? {
+            let events = self.as_of(block).extrinsic_events(index).await?;
+            return Ok((
+              TransactionStatus::InBlock { block, index, events },
+              State::Included { block, number, index },
+            ));
+          }
+          // If every block the transaction was valid for has been checked, it expired
+          if death.is_some_and(|death| next >= death) {
+            return Ok((TransactionStatus::Dropped, State::Expired));
+          }
+          if !in_pool {
+            return Ok((TransactionStatus::Dropped, State::Done));
+          }
+          sleep(poll_interval).await;
+        }
+      }
+      State::Included { block, number, index } => loop {
+        if self.block_hash(number).await? != Some(block) {
+          return Ok((TransactionStatus::Retracted { block }, State::Pending { next: number }));
+        }
+        let Some(header) = self.header(block).await? else {
+          Err(SeraiError::InvalidNode("node didn't have a block on its best chain".to_string()))?
+        };
+        if self.is_finalized(&header).await? {
+          let events = self.as_of(block).extrinsic_events(index).await?;
+          return Ok((TransactionStatus::Finalized { block, index, events }, State::Done));
+        }
+        sleep(poll_interval).await;
+      },
+      State::Expired | State::Done => unreachable!("stepped a finished watch"),
+    }
+  }
+
+  /// Submit a transaction, then watch it until it's finalized or dropped.
+  ///
+  /// This polls the node every `poll_interval`. Upon the transaction's inclusion, the events it
+  /// emitted are yielded alongside the block it was included in. If an error is yielded, the
+  /// stream ends and the transaction should be watched for by other means, as it may have still
+  /// been submitted.
+  pub fn submit_and_watch<'a>(
+    &'a self,
+    tx: &'a Transaction,
+    poll_interval: Duration,
+  ) -> impl Stream<Item = Result<TransactionStatus, SeraiError>> + 'a {
+    stream::unfold(State::Submit, move |state| async move {
+      if matches!(state, State::Done) {
+        return None;
+      }
+      Some(match self.watch_step(tx, None, poll_interval, state).await {
+        Ok((status, state)) => (Ok(status), state),
+        Err(e) => (Err(e), State::Done),
+      })
+    })
+  }
+  /// Sign a mortal transaction, submit it, then watch it until it's finalized or dropped.
+  ///
+  /// The transaction is signed as with `Serai::sign_mortal`, with the latest finalized block as
+  /// its checkpoint. If it expires without being included, it's signed again with the same nonce
+  /// and the then-latest finalized block as its checkpoint, then resubmitted, with
+  /// `TransactionStatus::Broadcast` yielded again. If the nonce was used by another transaction,
+  /// `TransactionStatus::Dropped` is yielded instead. If an error is yielded, the stream ends.
+  #[allow(clippy::too_many_arguments)]
+  pub fn submit_mortal_and_watch<'a>(
+    &'a self,
+    signer: &'a Pair,
+    call: Call,
+    nonce: u32,
+    tip: u64,
+    period: u64,
+    poll_interval: Duration,
+  ) -> impl Stream<Item = Result<TransactionStatus, SeraiError>> + 'a {
+    // The transaction and the number of the first block it's no longer valid for, once signed,
+    // and the state of watching it
+    stream::unfold((None, State::Submit), move |(mut tx, mut state)| {
+      let call = call.clone();
+      async move {
+        loop {
+          match state {
+            State::Done => return None,
+            State::Expired => {
+              let finalized = match self.as_of_latest_finalized_block().await {
+                Ok(finalized) => finalized,
+                Err(e) => return Some((Err(e), (None, State::Done))),
+              };
+              match finalized.nonce(signer.public().into()).await {
+                Ok(next_nonce) if next_nonce > nonce => {
+                  return Some((Ok(TransactionStatus::Dropped), (None, State::Done)))
+                }
+                Ok(_) => state = State::Submit,
+                Err(e) => return Some((Err(e), (None, State::Done))),
+              }
+            }
+            _ => {}
+          }
+
+          let (transaction, death) = match tx.take() {
+            Some(tx) => tx,
+            None => {
+              let checkpoint = match self.latest_finalized_block().await {
+                Ok(block) => block.header,
+                Err(e) => return Some((Err(e), (None, State::Done))),
+              };
+              let (_, death) = Self::mortal_era(checkpoint.number, period);
+              let transaction =
+                self.sign_mortal(signer, call.clone(), nonce, tip, &checkpoint, period);
+              (transaction, death)
+            }
+          };
+          match self.watch_step(&transaction, Some(death), poll_interval, state).await {
+            Ok((_, State::Expired)) => state = State::Expired,
+            Ok((status, next)) => return Some((Ok(status), (Some((transaction, death)), next))),
+            Err(e) => return Some((Err(e), (Some((transaction, death)), State::Done))),
+          }
+        }
+      }
+    })
+  }
+}
@@ -0,0 +1,171 @@
+use sp_core::bounded_vec::BoundedVec;
+
+use crate::{
+  SeraiError, SeraiDex, QueryCost, QueryBudget,
+  primitives::{Amount, Coin, ExternalCoin, SeraiAddress},
+  dex::DexEvent,
+  analytics::{Period, PoolStats, PoolAnalytics, ReserveSnapshot, PoolHistory},
+};
+
+const HOUR: u64 = 60 * 60 * 1000;
+
+fn swap(who: u8, path: Vec<Coin>, amount_in: u64, amount_out: u64) -> DexEvent {
+  DexEvent::SwapExecuted {
+    who: SeraiAddress([who; 32]),
+    send_to: SeraiAddress([who; 32]),
+    path: BoundedVec::try_from(path).unwrap(),
+    amount_in,
+    amount_out,
+  }
+}
+
+#[test]
+fn pool_analytics() {
+  let btc = ExternalCoin::Bitcoin;
+  let xmr = ExternalCoin::Monero;
+
+  let mut analytics = PoolAnalytics::new();
+  let start = 1_000 * HOUR;
+  analytics.ingest(
+    start,
+    &[
+      swap(1, vec![Coin::from(btc), Coin::Serai], 1_000_000, 500),
+      swap(2, vec![Coin::Serai, Coin::from(btc)], 997, 2_000_000),
+    ],
+  );
+  assert_eq!(
+    analytics.stats(btc, Period::Day),
+    PoolStats { volume: 3_000_000, fees_earned: 3_000 + 6_018, unique_traders: 2 }
+  );
+
+  // A multi-hop swap should count towards both pools, yet only once per pool
+  analytics.ingest(
+    start + (2 * HOUR),
+    &[swap(1, vec![Coin::from(btc), Coin::Serai, Coin::from(xmr)], 1_000_000, 50_000)],
+  );
+  assert_eq!(
+    analytics.stats(btc, Period::Day),
+    PoolStats { volume: 4_000_000, fees_earned: 12_018, unique_traders: 2 }
+  );
+  assert_eq!(
+    analytics.stats(xmr, Period::Day),
+    PoolStats { volume: 50_000, fees_earned: 150, unique_traders: 1 }
+  );
+
+  // Two days later, the first day's activity should only be present in the weekly stats
+  analytics.ingest(start + (50 * HOUR), &[swap(3, vec![Coin::Serai, Coin::from(xmr)], 1, 997)]);
+  assert_eq!(
+    analytics.stats(btc, Period::Day),
+    PoolStats { volume: 0, fees_earned: 0, unique_traders: 0 }
+  );
+  assert_eq!(
+    analytics.stats(xmr, Period::Day),
+    PoolStats { volume: 997, fees_earned: 3, unique_traders: 1 }
+  );
+  assert_eq!(
+    analytics.stats(btc, Period::Week),
+    PoolStats { volume: 4_000_000, fees_earned: 12_018, unique_traders: 2 }
+  );
+  assert_eq!(
+    analytics.stats(xmr, Period::Week),
+    PoolStats { volume: 50_997, fees_earned: 153, unique_traders: 2 }
+  );
+
+  // A week later, it should all be pruned
+  analytics.ingest(start + (10 * 24 * HOUR), &[]);
+  assert_eq!(analytics.stats(xmr, Period::Week), PoolStats::default());
+  assert_eq!(analytics.summary_json(), serde_json::json!({}));
+}
+
+#[test]
+fn pool_history() {
+  let btc = ExternalCoin::Bitcoin;
+  let xmr = ExternalCoin::Monero;
+
+  let mut history = PoolHistory::default();
+  // A block before the pool had liquidity doesn't yield a snapshot
+  history.ingest(btc, 1, HOUR, &[], None);
+  history.ingest(
+    btc,
+    2,
+    2 * HOUR,
+    &[
+      DexEvent::LiquidityAdded {
+        who: SeraiAddress([1; 32]),
+        mint_to: SeraiAddress([1; 32]),
+        pool_id: btc,
+        coin_amount: 10_000,
+        sri_amount: 20_000,
+        lp_token_minted: 14_000,
+      },
+      // Liquidity for another pool is ignored
+      DexEvent::LiquidityAdded {
+        who: SeraiAddress([1; 32]),
+        mint_to: SeraiAddress([1; 32]),
+        pool_id: xmr,
+        coin_amount: 5,
+        sri_amount: 5,
+        lp_token_minted: 5,
+      },
+      swap(2, vec![Coin::from(btc), Coin::Serai], 1000, 1500),
+    ],
+    Some((Amount(11_000), Amount(18_500))),
+  );
+  history.ingest(
+    btc,
+    3,
+    3 * HOUR,
+    &[
+      // Only the first hop of this swap is within the BTC pool
+      swap(3, vec![Coin::from(btc), Coin::Serai, Coin::from(xmr)], 2000, 1),
+      // This swap isn't within the BTC pool at all
+      swap(3, vec![Coin::Serai, Coin::from(xmr)], 1, 997),
+      DexEvent::LiquidityRemoved {
+        who: SeraiAddress([1; 32]),
+        withdraw_to: SeraiAddress([1; 32]),
+        pool_id: btc,
+        coin_amount: 1_000,
+        sri_amount: 1_500,
+        lp_token_burned: 1_000,
+      },
+    ],
+    Some((Amount(12_000), Amount(14_000))),
+  );
+
+  assert_eq!(
+    history,
+    PoolHistory {
+      volume: 3000,
+      fees_earned: 9,
+      swaps: 2,
+      coin_added: 10_000,
+      sri_added: 20_000,
+      coin_removed: 1_000,
+      sri_removed: 1_500,
+      snapshots: vec![
+        ReserveSnapshot { block: 2, time: 2 * HOUR, coin_reserve: 11_000, sri_reserve: 18_500 },
+        ReserveSnapshot { block: 3, time: 3 * HOUR, coin_reserve: 12_000, sri_reserve: 14_000 },
+      ],
+    }
+  );
+}
+
+#[test]
+fn history_budget() {
+  assert_eq!(SeraiDex::history_cost(10, 9), QueryCost::default());
+  let cost = SeraiDex::history_cost(1, 100);
+  assert_eq!(cost, SeraiDex::history_cost(1, 1).times(100));
+
+  let mut budget = QueryBudget::unlimited(10);
+  budget.check(cost).unwrap();
+  budget.max_calls = Some(cost.calls);
+  budget.check(cost).unwrap();
+  budget.max_calls = Some(cost.calls - 1);
+  assert!(matches!(budget.check(cost), Err(SeraiError::BudgetExceeded(_))));
+
+  let mut budget = QueryBudget::unlimited(10);
+  budget.max_bytes = Some(cost.bytes - 1);
+  assert!(matches!(budget.check(cost), Err(SeraiError::BudgetExceeded(_))));
+
+  assert!(matches!(QueryBudget::unlimited(0).check(cost), Err(SeraiError::BudgetExceeded(_))));
+}
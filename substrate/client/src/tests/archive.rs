@@ -0,0 +1,27 @@
+use std::sync::Arc;
+
+use crate::{primitives::ExternalNetworkId, SeraiError, Serai, MockSerai};
+
+#[tokio::test]
+async fn state_discarded() {
+  let mock = Arc::new(MockSerai::new());
+  let serai = Serai::with_rpc(mock.clone()).await.unwrap();
+
+  let network = ExternalNetworkId::Bitcoin;
+  mock.set_storage("InInstructions", "LastBatch", network, 0u32);
+  let old = mock.produce_block();
+  mock.produce_block();
+  assert!(serai.is_archive_node().await.unwrap());
+
+  mock.discard_state(2);
+  assert!(!serai.is_archive_node().await.unwrap());
+  assert!(matches!(
+    serai.as_of(old).in_instructions().last_batch_for_network(network).await,
+    Err(SeraiError::StateDiscarded(_))
+  ));
+
+  // Blocks which weren't pruned, and queries which don't read state, are unaffected
+  let latest = serai.as_of_latest_finalized_block().await.unwrap();
+  assert_eq!(latest.in_instructions().last_batch_for_network(network).await.unwrap(), Some(0));
+  assert!(serai.block(old).await.unwrap().is_some());
+}
@@ -0,0 +1,26 @@
+use core::time::Duration;
+
+use crate::Backoff;
+
+#[test]
+fn backoff() {
+  let backoff = Backoff::default();
+
+  // The delay doubles with each retry, jittered within its upper half
+  assert_eq!(backoff.delay(0, 0), Duration::from_millis(50));
+  assert!(backoff.delay(0, u64::MAX) < Duration::from_millis(100));
+  assert_eq!(backoff.delay(1, 0), Duration::from_millis(100));
+  assert_eq!(backoff.delay(3, 0), Duration::from_millis(400));
+  assert_eq!(backoff.delay(3, u64::MAX / 2), Duration::from_millis(600) - Duration::from_nanos(1));
+
+  // The delay is capped
+  assert_eq!(backoff.delay(10, 0), backoff.max / 2);
+  assert!(backoff.delay(u32::MAX, u64::MAX) <= backoff.max);
+
+  // The jittered delay is within the expected bounds
+  for retry in 0 .. 10 {
+    let delay = backoff.jittered_delay(retry);
+    assert!(delay >= backoff.delay(retry, 0));
+    assert!(delay <= backoff.delay(retry, u64::MAX));
+  }
+}
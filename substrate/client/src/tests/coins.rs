@@ -0,0 +1,137 @@
+use core::time::Duration;
+use std::{sync::Arc, collections::BTreeMap};
+
+use scale::Encode;
+
+use futures_util::{StreamExt, TryStreamExt};
+
+use crate::{
+  primitives::{Amount, Coin, Balance, ExternalCoin, SeraiAddress},
+  abi::{Event, TransactionPaymentEvent},
+  coins::CoinsEvent,
+  Serai, SeraiDex, MockSerai,
+};
+
+fn set_balance(mock: &MockSerai, address: SeraiAddress, coin: Coin, amount: u64) {
+  mock.set_storage(
+    "Coins",
+    "Balances",
+    (sp_core::hashing::blake2_128(&address.encode()), &address.0, coin),
+    Amount(amount),
+  );
+}
+
+#[tokio::test]
+async fn snapshot_balances() {
+  let mock = Arc::new(MockSerai::new());
+  let serai = Serai::with_rpc(mock.clone()).await.unwrap();
+
+  let btc = Coin::External(ExternalCoin::Bitcoin);
+  let mut expected = BTreeMap::new();
+  for i in 1 ..= 10 {
+    let address = SeraiAddress::new([i; 32]);
+    set_balance(&mock, address, Coin::Serai, 1);
+    if i % 2 == 0 {
+      set_balance(&mock, address, btc, u64::from(i));
+      expected.insert(address, Amount(u64::from(i)));
+    }
+  }
+  let snapshot = mock.produce_block();
+
+  // Balances changed after the snapshot aren't reflected
+  set_balance(&mock, SeraiAddress::new([1; 32]), btc, 100);
+  mock.produce_block();
+
+  let balances =
+    serai.snapshot_balances(btc, snapshot).try_collect::<BTreeMap<_, _>>().await.unwrap();
+  assert_eq!(balances, expected);
+}
+
+#[tokio::test]
+async fn storage_multi() {
+  let mock = Arc::new(MockSerai::new());
+  let serai = Serai::with_rpc(mock.clone()).await.unwrap();
+
+  let addresses = (0 .. 5).map(|i| SeraiAddress::new([i; 32])).collect::<Vec<_>>();
+  for (i, address) in addresses.iter().enumerate().skip(1) {
+    set_balance(&mock, *address, Coin::Serai, u64::try_from(i).unwrap());
+  }
+
+  // The BTC pool exists and has reserves, the ETH pool exists yet is empty, and the XMR pool
+  // doesn't exist
+  for coin in [ExternalCoin::Bitcoin, ExternalCoin::Ether] {
+    mock.set_storage("Dex", "Pools", (sp_core::hashing::blake2_128(&coin.encode()), coin), ());
+  }
+  let btc_pool = SeraiDex::pool_account(ExternalCoin::Bitcoin);
+  set_balance(&mock, btc_pool, Coin::External(ExternalCoin::Bitcoin), 10);
+  set_balance(&mock, btc_pool, Coin::Serai, 20);
+  set_balance(&mock, SeraiDex::pool_account(ExternalCoin::Ether), Coin::Serai, 30);
+  let latest = serai.as_of(mock.produce_block());
+
+  assert_eq!(
+    latest.coins().coin_balances(Coin::Serai, &addresses).await.unwrap(),
+    (0 .. 5).map(Amount).collect::<Vec<_>>()
+  );
+  assert_eq!(
+    latest
+      .dex()
+      .reserves_multi(&[ExternalCoin::Bitcoin, ExternalCoin::Ether, ExternalCoin::Monero])
+      .await
+      .unwrap(),
+    vec![Some((Amount(10), Amount(20))), None, None]
+  );
+}
+
+#[tokio::test]
+async fn subscribe_balance() {
+  let mock = Arc::new(MockSerai::new());
+  let serai = Serai::with_rpc(mock.clone()).await.unwrap();
+
+  let btc = Coin::External(ExternalCoin::Bitcoin);
+  let address = SeraiAddress::new([1; 32]);
+  let other = SeraiAddress::new([2; 32]);
+  set_balance(&mock, address, btc, 5);
+  mock.produce_block();
+
+  let mut balances = Box::pin(serai.subscribe_balance(btc, address, Duration::from_millis(1)));
+  assert_eq!(balances.next().await.unwrap().unwrap(), (1, Amount(5)));
+
+  // Events for other addresses, and fees paid in SRI, don't cause the balance to be fetched
+  mock.push_event(Event::Coins(CoinsEvent::Transfer {
+    from: other,
+    to: other,
+    balance: Balance { coin: btc, amount: Amount(1) },
+  }));
+  mock.push_event(Event::TransactionPayment(TransactionPaymentEvent::TransactionFeePaid {
+    who: address,
+    actual_fee: 1,
+    tip: 0,
+  }));
+  mock.produce_block();
+
+  set_balance(&mock, address, btc, 8);
+  mock.push_event(Event::Coins(CoinsEvent::Mint {
+    to: address,
+    balance: Balance { coin: btc, amount: Amount(3) },
+  }));
+  mock.produce_block();
+
+  // A transfer to oneself doesn't change the balance, and isn't yielded
+  mock.push_event(Event::Coins(CoinsEvent::Transfer {
+    from: address,
+    to: address,
+    balance: Balance { coin: btc, amount: Amount(1) },
+  }));
+  mock.produce_block();
+
+  set_balance(&mock, address, btc, 2);
+  mock.push_event(Event::Coins(CoinsEvent::Transfer {
+    from: address,
+    to: other,
+    balance: Balance { coin: btc, amount: Amount(6) },
+  }));
+  mock.produce_block();
+
+  assert_eq!(balances.next().await.unwrap().unwrap(), (3, Amount(8)));
+  assert_eq!(balances.next().await.unwrap().unwrap(), (5, Amount(2)));
+}
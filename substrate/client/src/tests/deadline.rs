@@ -0,0 +1,29 @@
+use crate::Serai;
+
+#[test]
+fn deadline_period() {
+  // Too soon for any period
+  assert_eq!(Serai::deadline_period(100, 99), None);
+  assert_eq!(Serai::deadline_period(100, 100), None);
+  assert_eq!(Serai::deadline_period(100, 102), None);
+
+  // The transaction is valid for 100 ..= 103
+  assert_eq!(Serai::deadline_period(100, 103), Some(4));
+  // The period never extends past the deadline
+  assert_eq!(Serai::deadline_period(100, 106), Some(4));
+  assert_eq!(Serai::deadline_period(100, 107), Some(8));
+  assert_eq!(Serai::deadline_period(100, 100 + 1000), Some(512));
+
+  // The period is bounded by the amount of block hashes kept
+  assert_eq!(Serai::deadline_period(100, 100 + 100_000), Some(2048));
+  assert_eq!(Serai::deadline_period(0, u64::MAX), Some(2048));
+}
+
+#[test]
+fn mortal_era() {
+  // Periods are rounded up to a power of two
+  assert_eq!(Serai::mortal_era(100, 3).1, 104);
+  assert_eq!(Serai::mortal_era(100, 5).1, 108);
+  // The period is bounded by the amount of block hashes kept
+  assert_eq!(Serai::mortal_era(100, 100_000).1, 100 + 2048);
+}
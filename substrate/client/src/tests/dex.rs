@@ -0,0 +1,250 @@
+use scale::Encode;
+
+use sp_core::bounded_vec::BoundedVec;
+use sp_runtime::{DispatchError, ModuleError};
+
+use crate::{
+  primitives::{Amount, Coin, ExternalCoin, SeraiAddress},
+  dex::{DexEvent, DexError, PositionValue, PALLET_INDEX},
+  SeraiDex,
+};
+
+// These values are from the DEX integration tests, which execute these swaps on-chain
+#[test]
+fn amount_out() {
+  let reserve = Amount(50_000_000_000_000);
+  assert_eq!(
+    SeraiDex::get_amount_out(Amount(25_000_000_000_000), reserve, reserve),
+    Some(Amount(16_633_299_966_633))
+  );
+
+  // Two hops, as performed when swapping an external coin to another external coin
+  let sri = SeraiDex::get_amount_out(Amount(25_000_000_000_000), reserve, reserve).unwrap();
+  assert_eq!(SeraiDex::get_amount_out(sri, reserve, reserve), Some(Amount(12_453_103_964_435)));
+
+  assert_eq!(SeraiDex::get_amount_out(Amount(1), Amount(0), reserve), None);
+  assert_eq!(SeraiDex::get_amount_out(Amount(1), reserve, Amount(0)), None);
+}
+
+#[test]
+fn amount_in() {
+  let reserve_in = Amount(50_000_000_000_000);
+  let reserve_out = Amount(30_000_000_000_000);
+  for amount_out in [1, 1_000, 7_777_777_777, 29_000_000_000_000] {
+    let amount_out = Amount(amount_out);
+    let amount_in = SeraiDex::get_amount_in(amount_out, reserve_in, reserve_out).unwrap();
+    // The amount in should be the minimal amount to receive at least the amount out
+    assert!(SeraiDex::get_amount_out(amount_in, reserve_in, reserve_out).unwrap() >= amount_out);
+    assert!(
+      SeraiDex::get_amount_out(Amount(amount_in.0 - 1), reserve_in, reserve_out).unwrap() <
+        amount_out
+    );
+  }
+
+  // The entire reserve can't be bought
+  assert_eq!(SeraiDex::get_amount_in(reserve_out, reserve_in, reserve_out), None);
+}
+
+#[test]
+fn position_value() {
+  let reserves = (Amount(50_000_000_000_000), Amount(100_000_000_000_000));
+  let total_issuance = Amount(70_710_678_118_654);
+
+  // The entire supply redeems for the entire reserves
+  assert_eq!(SeraiDex::redeem_value(total_issuance, total_issuance, reserves), Some(reserves));
+  assert_eq!(
+    SeraiDex::redeem_value(Amount(total_issuance.0 / 2), total_issuance, reserves),
+    Some((Amount(25_000_000_000_000), Amount(50_000_000_000_000)))
+  );
+  // Redemptions are rounded down
+  assert_eq!(
+    SeraiDex::redeem_value(Amount(1), Amount(3), (Amount(10), Amount(20))),
+    Some((Amount(3), Amount(6)))
+  );
+  assert_eq!(SeraiDex::redeem_value(Amount(1), Amount(0), reserves), None);
+
+  let position = |lp_tokens, total_issuance| PositionValue {
+    lp_tokens: Amount(lp_tokens),
+    total_issuance: Amount(total_issuance),
+    coin: Amount(0),
+    sri: Amount(0),
+  };
+  assert!((position(1, 3).share_percentage() - 33.3333).abs() < 1e-9);
+  assert!((position(3, 3).share_percentage() - 100.0).abs() < 1e-9);
+  assert!(position(0, 0).share_percentage().abs() < 1e-9);
+}
+
+#[test]
+fn slippage() {
+  let amount = Amount(16_633_299_966_633);
+  assert_eq!(SeraiDex::apply_slippage(amount, 0), amount);
+  // 0.5%
+  assert_eq!(SeraiDex::apply_slippage(amount, 50), Amount(16_550_133_466_799));
+  assert_eq!(SeraiDex::apply_slippage(amount, 10_000), Amount(0));
+  // Tolerances beyond 100% are capped
+  assert_eq!(SeraiDex::apply_slippage(amount, u16::MAX), Amount(0));
+}
+
+#[test]
+fn event_filters() {
+  let who = SeraiAddress([1; 32]);
+  let send_to = SeraiAddress([2; 32]);
+  let other = SeraiAddress([3; 32]);
+
+  let swap = DexEvent::SwapExecuted {
+    who,
+    send_to,
+    path: BoundedVec::try_from(vec![
+      ExternalCoin::Bitcoin.into(),
+      Coin::Serai,
+      ExternalCoin::Monero.into(),
+    ])
+    .unwrap(),
+    amount_in: 1,
+    amount_out: 1,
+  };
+  assert!(SeraiDex::event_involves_account(&swap, who));
+  assert!(SeraiDex::event_involves_account(&swap, send_to));
+  assert!(!SeraiDex::event_involves_account(&swap, other));
+  // Swaps involve every pool along their path
+  assert!(SeraiDex::event_involves_pool(&swap, ExternalCoin::Bitcoin));
+  assert!(SeraiDex::event_involves_pool(&swap, ExternalCoin::Monero));
+  assert!(!SeraiDex::event_involves_pool(&swap, ExternalCoin::Ether));
+
+  let added = DexEvent::LiquidityAdded {
+    who,
+    mint_to: send_to,
+    pool_id: ExternalCoin::Monero,
+    coin_amount: 1,
+    sri_amount: 1,
+    lp_token_minted: 1,
+  };
+  assert!(SeraiDex::event_involves_account(&added, send_to));
+  assert!(!SeraiDex::event_involves_account(&added, other));
+  assert!(SeraiDex::event_involves_pool(&added, ExternalCoin::Monero));
+  assert!(!SeraiDex::event_involves_pool(&added, ExternalCoin::Bitcoin));
+
+  let pool_account = SeraiDex::pool_account(ExternalCoin::Bitcoin);
+  let created = DexEvent::PoolCreated { pool_id: ExternalCoin::Bitcoin, pool_account };
+  assert!(SeraiDex::event_involves_account(&created, pool_account));
+  assert!(!SeraiDex::event_involves_account(&created, who));
+}
+
+#[test]
+fn swap_paths() {
+  let btc = Coin::External(ExternalCoin::Bitcoin);
+  let eth = Coin::External(ExternalCoin::Ether);
+
+  assert!(SeraiDex::is_valid_swap_path(&[btc, Coin::Serai]));
+  assert!(SeraiDex::is_valid_swap_path(&[Coin::Serai, eth]));
+  assert!(SeraiDex::is_valid_swap_path(&[btc, Coin::Serai, eth]));
+
+  // Too short
+  assert!(!SeraiDex::is_valid_swap_path(&[]));
+  assert!(!SeraiDex::is_valid_swap_path(&[btc]));
+  // Pools which don't exist
+  assert!(!SeraiDex::is_valid_swap_path(&[btc, eth]));
+  assert!(!SeraiDex::is_valid_swap_path(&[btc, btc]));
+  assert!(!SeraiDex::is_valid_swap_path(&[Coin::Serai, Coin::Serai]));
+  // Reusing a pool
+  assert!(!SeraiDex::is_valid_swap_path(&[btc, Coin::Serai, btc]));
+
+  let address = SeraiAddress([0xff; 32]);
+  assert!(SeraiDex::swap_with_path(vec![btc, eth], Amount(1), Amount(1), address).is_none());
+  // Valid, yet exceeding the maximum path length
+  assert!(SeraiDex::swap_with_path(
+    vec![btc, Coin::Serai, eth, Coin::Serai],
+    Amount(1),
+    Amount(1),
+    address
+  )
+  .is_none());
+  assert_eq!(
+    SeraiDex::swap_with_path(vec![btc, Coin::Serai, eth], Amount(1), Amount(1), address),
+    SeraiDex::swap(btc, eth, Amount(1), Amount(1), address)
+  );
+
+  // Swapping a coin for itself has no path
+  assert!(SeraiDex::swap(btc, btc, Amount(1), Amount(1), address).is_none());
+  assert!(SeraiDex::swap(Coin::Serai, Coin::Serai, Amount(1), Amount(1), address).is_none());
+}
+
+#[test]
+fn zap() {
+  let coin = ExternalCoin::Bitcoin;
+  let address = SeraiAddress([0xff; 32]);
+  let reserves = (Amount(50_000_000_000_000), Amount(50_000_000_000_000));
+  let lp_supply = Amount(50_000_000_000_000);
+  let amount = Amount(10_000_000_000_000);
+
+  let zap = |min_lp| {
+    SeraiDex::zap_add_liquidity_with_reserves(coin, amount, min_lp, address, reserves, lp_supply)
+  };
+
+  let swapped = Amount(5_000_000_000_000);
+  let sri = SeraiDex::get_amount_out(swapped, reserves.0, reserves.1).unwrap();
+  let min_lp = Amount(4_000_000_000_000);
+  let Some(serai_abi::Call::Utility(serai_abi::utility::Call::batch_all { calls })) = zap(min_lp)
+  else {
+    panic!("zap wasn't a batch");
+  };
+  assert_eq!(calls.len(), 2);
+  assert_eq!(calls[0], SeraiDex::swap(coin.into(), Coin::Serai, swapped, sri, address).unwrap());
+  let serai_abi::Call::Dex(serai_abi::dex::Call::add_liquidity {
+    coin: added,
+    coin_desired,
+    sri_desired,
+    coin_min,
+    sri_min,
+    mint_to,
+  }) = calls[1].clone()
+  else {
+    panic!("zap didn't add liquidity");
+  };
+  assert_eq!(added, coin);
+  assert_eq!(coin_desired, amount.0 - swapped.0);
+  assert_eq!(sri_desired, sri.0);
+  assert_eq!(mint_to, address);
+
+  // Depositing the minimum amounts, into the post-swap reserves, should mint at least min_lp
+  let coin_reserve = u128::from(reserves.0 .0 + swapped.0);
+  let sri_reserve = u128::from(reserves.1 .0 - sri.0);
+  let lp_supply_u128 = u128::from(lp_supply.0);
+  assert!(((u128::from(coin_min) * lp_supply_u128) / coin_reserve) >= u128::from(min_lp.0));
+  assert!(((u128::from(sri_min) * lp_supply_u128) / sri_reserve) >= u128::from(min_lp.0));
+  // While not requiring more than is deposited
+  assert!(coin_min <= coin_desired);
+  assert!(sri_min <= sri_desired);
+
+  // An unachievable amount of LP tokens shouldn't produce a zap
+  assert!(zap(Amount(5_000_000_000_000)).is_none());
+  // Nor should a pool without liquidity
+  assert!(SeraiDex::zap_add_liquidity_with_reserves(
+    coin,
+    amount,
+    Amount(1),
+    address,
+    reserves,
+    Amount(0)
+  )
+  .is_none());
+}
+
+#[test]
+fn decode_error() {
+  // The pallet index is the index of the DEX's events within the runtime's events
+  let event = serai_abi::Event::Dex(DexEvent::PoolCreated {
+    pool_id: ExternalCoin::Bitcoin,
+    pool_account: SeraiDex::pool_account(ExternalCoin::Bitcoin),
+  });
+  assert_eq!(event.encode()[0], PALLET_INDEX);
+
+  let module_error = |index, error: DexError| {
+    DispatchError::Module(ModuleError { index, error: [error.encode()[0], 0, 0, 0], message: None })
+  };
+  for error in [DexError::EqualCoins, DexError::PoolNotFound, DexError::CorrespondenceError] {
+    assert_eq!(SeraiDex::decode_error(&module_error(PALLET_INDEX, error)), Some(error));
+    assert_eq!(SeraiDex::decode_error(&module_error(PALLET_INDEX + 1, error)), None);
+  }
+  assert_eq!(SeraiDex::decode_error(&DispatchError::BadOrigin), None);
+}
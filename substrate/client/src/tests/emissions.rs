@@ -0,0 +1,99 @@
+use std::sync::Arc;
+
+use sp_core::sr25519::Public;
+
+use frame_system::Phase;
+
+use crate::{
+  primitives::{Amount, NetworkId, ExternalNetworkId, SeraiAddress},
+  abi::{Event, validator_sets},
+  validator_sets::primitives::Session,
+  emissions::primitives::INITIAL_REWARD_PER_BLOCK,
+  EmissionPhase, RewardPayout, Serai, MockSerai,
+};
+
+fn allocation(validator: u8, network: NetworkId, amount: u64) -> validator_sets::Event {
+  validator_sets::Event::AllocationIncreased {
+    validator: SeraiAddress::new([validator; 32]),
+    network,
+    amount: Amount(amount),
+  }
+}
+
+#[tokio::test]
+async fn session_rewards() {
+  let mock = Arc::new(MockSerai::new());
+  let serai = Serai::with_rpc(mock.clone()).await.unwrap();
+
+  mock.produce_block();
+  mock.set_storage("ValidatorSets", "SessionBeginBlock", (NetworkId::Serai, Session(1)), 2u64);
+  let rewarded = [
+    allocation(1, NetworkId::Serai, 100),
+    allocation(2, ExternalNetworkId::Bitcoin.into(), 200),
+    allocation(1, ExternalNetworkId::Bitcoin.into(), 300),
+  ];
+  for event in &rewarded {
+    mock.push_event_with_phase(Phase::Initialization, Event::ValidatorSets(event.clone()));
+  }
+  // Allocations made by transactions aren't rewards
+  mock.push_extrinsic_event(1, Event::ValidatorSets(allocation(1, NetworkId::Serai, 400)));
+  let latest = mock.produce_block();
+
+  let latest = serai.as_of(latest);
+  let emissions = latest.emissions();
+  let payouts = emissions.session_rewards(Session(0)).await.unwrap().unwrap();
+  assert_eq!(
+    payouts,
+    rewarded
+      .iter()
+      .map(|event| {
+        let validator_sets::Event::AllocationIncreased { validator, network, amount } = event
+        else {
+          panic!("reward wasn't an allocation")
+        };
+        RewardPayout { validator: *validator, network: *network, amount: *amount }
+      })
+      .collect::<Vec<_>>()
+  );
+  // The current session's rewards have yet to be paid
+  assert!(emissions.session_rewards(Session(1)).await.unwrap().is_none());
+
+  let validator = SeraiAddress::new([1; 32]);
+  assert_eq!(
+    emissions.accrued_rewards(validator, Session(0), Session(1)).await.unwrap(),
+    Amount(400)
+  );
+}
+
+#[tokio::test]
+async fn pending_reward_share() {
+  let mock = Arc::new(MockSerai::new());
+  let serai = Serai::with_rpc(mock.clone()).await.unwrap();
+
+  let network = NetworkId::Serai;
+  mock.set_storage("ValidatorSets", "AllocationPerKeyShare", network, Amount(100));
+  mock.set_storage(
+    "Emissions",
+    "Participants",
+    network,
+    vec![(Public::from_raw([1; 32]), 250u64), (Public::from_raw([2; 32]), 100u64)],
+  );
+  mock.set_storage("GenesisLiquidity", "GenesisCompleteBlock", (), 1u64);
+  let latest = mock.produce_block();
+
+  let latest = serai.as_of(latest);
+  let emissions = latest.emissions();
+  // The 50 which didn't earn a key share is discounted by half
+  assert_eq!(
+    emissions.pending_reward_share(network, SeraiAddress::new([1; 32])).await.unwrap(),
+    Some((225, 325))
+  );
+  assert_eq!(
+    emissions.pending_reward_share(network, SeraiAddress::new([3; 32])).await.unwrap(),
+    None
+  );
+
+  let schedule = emissions.schedule().await.unwrap();
+  assert_eq!(schedule.phase, EmissionPhase::Initial);
+  assert_eq!(schedule.reward_per_block(), Some(Amount(INITIAL_REWARD_PER_BLOCK)));
+}
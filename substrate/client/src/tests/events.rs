@@ -0,0 +1,58 @@
+use std::sync::Arc;
+
+use futures_util::TryStreamExt;
+
+use crate::{
+  primitives::{Amount, Coin, Balance, ExternalNetworkId, SeraiAddress},
+  abi::{Event, coins, in_instructions, grandpa},
+  SeraiEvent, Serai, MockSerai,
+};
+
+#[test]
+fn serai_event() {
+  let mint = coins::Event::Mint {
+    to: SeraiAddress::new([1; 32]),
+    balance: Balance { coin: Coin::Serai, amount: Amount(1) },
+  };
+  assert_eq!(SeraiEvent::from(Event::Coins(mint.clone())), SeraiEvent::Coins(mint));
+
+  let halt = in_instructions::Event::Halt { network: ExternalNetworkId::Bitcoin };
+  assert_eq!(
+    SeraiEvent::from(Event::InInstructions(halt.clone())),
+    SeraiEvent::InInstructions(halt)
+  );
+
+  // Events from other pallets are preserved as-is
+  let paused = Event::Grandpa(grandpa::Event::Paused);
+  assert_eq!(SeraiEvent::from(paused.clone()), SeraiEvent::Other(paused));
+}
+
+#[tokio::test]
+async fn events_between() {
+  let mock = Arc::new(MockSerai::new());
+  let serai = Serai::with_rpc(mock.clone()).await.unwrap();
+
+  let halt = |network| in_instructions::Event::Halt { network };
+  for _ in 0 .. 40 {
+    mock.push_event(Event::InInstructions(halt(ExternalNetworkId::Bitcoin)));
+    mock.push_event(Event::Grandpa(grandpa::Event::Paused));
+    mock.produce_block();
+  }
+  mock.push_event(Event::InInstructions(halt(ExternalNetworkId::Monero)));
+  mock.produce_unfinalized_block();
+
+  let filter = |event: &SeraiEvent| matches!(event, SeraiEvent::InInstructions(_));
+  let pages = serai.events_between(1, 40, filter).try_collect::<Vec<_>>().await.unwrap();
+  // The range is split into pages of 32 blocks
+  assert_eq!(pages.iter().map(Vec::len).collect::<Vec<_>>(), vec![32, 8]);
+  for (number, block) in (1 ..= 40).zip(pages.into_iter().flatten()) {
+    assert_eq!(block.number, number);
+    assert_eq!(Some(block.hash), serai.block_hash(number).await.unwrap());
+    assert_eq!(block.events, vec![SeraiEvent::InInstructions(halt(ExternalNetworkId::Bitcoin))]);
+  }
+
+  // Ranges which aren't finalized error
+  let mut pages = Box::pin(serai.events_between(40, 41, filter));
+  assert!(pages.try_next().await.is_err());
+  assert!(pages.try_next().await.unwrap().is_none());
+}
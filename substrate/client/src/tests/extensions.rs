@@ -0,0 +1,77 @@
+use scale::{Encode, Decode, Compact};
+
+use frame_system::Phase;
+
+use crate::{
+  abi::{Event, Call, grandpa, timestamp},
+  Extensions, ExtendedEvent, ExtendedCall,
+};
+
+// The index of a pallet a fork of the runtime added
+const FORK_PALLET: u8 = 200;
+
+#[derive(PartialEq, Eq, Debug, Encode, Decode)]
+enum ForkEvent {
+  Minted { amount: u64 },
+  Paused,
+}
+
+#[derive(PartialEq, Eq, Debug, Encode, Decode)]
+struct ForkCall {
+  data: Vec<u8>,
+}
+
+fn fork_encode(item: &impl Encode) -> Vec<u8> {
+  let mut bytes = vec![FORK_PALLET];
+  item.encode_to(&mut bytes);
+  bytes
+}
+
+#[test]
+fn extensions() {
+  let mut extensions = Extensions::new();
+  extensions.register_event::<ForkEvent>(FORK_PALLET).register_call::<ForkCall>(FORK_PALLET);
+
+  // Serai's own events and calls should decode as usual
+  let serai_event = Event::Grandpa(grandpa::Event::Paused);
+  assert!(matches!(
+    extensions.decode_event(&mut serai_event.encode().as_slice()).unwrap(),
+    ExtendedEvent::Serai(event) if event == serai_event
+  ));
+  let serai_call = Call::Timestamp(timestamp::Call::set { now: 1 });
+  assert!(matches!(
+    extensions.decode_call(&mut serai_call.encode().as_slice()).unwrap(),
+    ExtendedCall::Serai(call) if call == serai_call
+  ));
+
+  // Extension events and calls should decode to their registered types
+  let event = ForkEvent::Minted { amount: 5 };
+  let decoded = extensions.decode_event(&mut fork_encode(&event).as_slice()).unwrap();
+  assert_eq!(decoded.extension::<ForkEvent>(FORK_PALLET), Some(&event));
+  assert_eq!(decoded.extension::<ForkEvent>(FORK_PALLET + 1), None);
+  assert_eq!(decoded.extension::<ForkCall>(FORK_PALLET), None);
+
+  let call = ForkCall { data: vec![1, 2, 3] };
+  let decoded = extensions.decode_call(&mut fork_encode(&call).as_slice()).unwrap();
+  assert_eq!(decoded.extension::<ForkCall>(FORK_PALLET), Some(&call));
+
+  // Without a registration, the extension's event can't be decoded
+  assert!(Extensions::new().decode_event(&mut fork_encode(&event).as_slice()).is_err());
+
+  // Decode the events of a block, as stored by the System pallet
+  let mut records = Compact(2u32).encode();
+  Phase::Initialization.encode_to(&mut records);
+  serai_event.encode_to(&mut records);
+  Vec::<[u8; 32]>::new().encode_to(&mut records);
+  Phase::ApplyExtrinsic(1).encode_to(&mut records);
+  records.extend(fork_encode(&ForkEvent::Paused));
+  vec![[0xff; 32]].encode_to(&mut records);
+
+  let decoded = extensions.decode_event_records(&records).unwrap();
+  assert_eq!(decoded.len(), 2);
+  assert!(matches!(&decoded[0], ExtendedEvent::Serai(event) if event == &serai_event));
+  assert_eq!(decoded[1].extension::<ForkEvent>(FORK_PALLET), Some(&ForkEvent::Paused));
+
+  records.push(0);
+  assert!(extensions.decode_event_records(&records).is_err());
+}
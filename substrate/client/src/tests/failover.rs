@@ -0,0 +1,184 @@
+use core::{
+  sync::atomic::{AtomicUsize, Ordering},
+  time::Duration,
+};
+use std::sync::Arc;
+
+use serde_json::{json, Value};
+use tokio::{
+  io::{AsyncReadExt, AsyncWriteExt},
+  net::TcpListener,
+};
+
+use crate::{Transaction, SeraiError, Serai, SeraiRpc, Backoff, MockSerai};
+
+// Read a HTTP request's body
+async fn body(stream: &mut tokio::net::TcpStream) -> Option<Vec<u8>> {
+  let mut request = vec![];
+  let mut buf = [0; 4096];
+  loop {
+    let read = stream.read(&mut buf).await.ok()?;
+    if read == 0 {
+      None?;
+    }
+    request.extend(&buf[.. read]);
+
+    let Some(end) = request.windows(4).position(|window| window == b"\r\n\r\n") else { continue };
+    let len = String::from_utf8_lossy(&request[.. end])
+      .to_lowercase()
+      .lines()
+      .find_map(|line| line.strip_prefix("content-length:").map(|len| len.trim().parse().unwrap()))
+      .unwrap_or(0);
+    let body = (end + 4) .. (end + 4 + len);
+    if request.len() >= body.end {
+      return Some(request[body].to_vec());
+    }
+  }
+}
+
+// Serve a mock over HTTP, as a node would, returning its URL
+async fn serve(mock: Arc<MockSerai>) -> String {
+  serve_dropping(mock, "author_submitExtrinsic", 0).await
+}
+
+// Serve a mock over HTTP, dropping the connection instead of responding to the specified amount
+// of requests for `dropped`, as if the connection failed after the node received them
+async fn serve_dropping(mock: Arc<MockSerai>, dropped: &'static str, drops: usize) -> String {
+  let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+  let url = format!("http://{}", listener.local_addr().unwrap());
+  let drops = Arc::new(AtomicUsize::new(drops));
+  tokio::spawn(async move {
+    while let Ok((mut stream, _)) = listener.accept().await {
+      let mock = mock.clone();
+      let drops = drops.clone();
+      tokio::spawn(async move {
+        // Each connection is used for a single request
+        let Some(body) = body(&mut stream).await else { return };
+        let request: Value = serde_json::from_slice(&body).unwrap();
+        let method = request["method"].as_str().unwrap();
+        let res = mock.call(method, request["params"].clone()).await;
+        if method == dropped {
+          let remaining = |n: usize| n.checked_sub(1);
+          if drops.fetch_update(Ordering::Relaxed, Ordering::Relaxed, remaining).is_ok() {
+            return;
+          }
+        }
+        let res = match res {
+          Ok(result) => json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }),
+          Err(e) => json!({
+            "jsonrpc": "2.0",
+            "id": request["id"],
+            "error": { "code": 1, "message": e.to_string() },
+          }),
+        };
+        let res = serde_json::to_vec(&res).unwrap();
+        let head =
+          format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", res.len());
+        let _ = stream.write_all(&[head.as_bytes(), &res].concat()).await;
+      });
+    }
+  });
+  url
+}
+
+// The URL of an endpoint which refuses connections
+async fn unreachable() -> String {
+  let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+  format!("http://{}", listener.local_addr().unwrap())
+}
+
+fn client(urls: Vec<String>) -> Serai {
+  Serai::unchecked(None, urls).with_backoff(Backoff::none())
+}
+
+#[tokio::test]
+async fn failover() {
+  let mock = Arc::new(MockSerai::new());
+  let live = serve(mock.clone()).await;
+  let dead = unreachable().await;
+  let hash = mock.produce_block();
+
+  // A client whose first endpoint is live never rotates away from it
+  let serai = client(vec![live.clone(), dead.clone()]);
+  assert_eq!(serai.latest_finalized_block_hash().await.unwrap(), hash);
+  assert_eq!(serai.current_endpoint(), Some(live.as_str()));
+
+  // A client whose first endpoint is unreachable rotates to the next, and stays there
+  let serai = client(vec![dead.clone(), live.clone()]);
+  assert_eq!(serai.endpoints(), &[dead.clone(), live.clone()]);
+  assert_eq!(serai.current_endpoint(), Some(dead.as_str()));
+  assert_eq!(serai.latest_finalized_block_hash().await.unwrap(), hash);
+  assert_eq!(serai.current_endpoint(), Some(live.as_str()));
+  assert_eq!(serai.block_hash(1).await.unwrap(), Some(hash));
+  assert_eq!(serai.current_endpoint(), Some(live.as_str()));
+
+  // Errors other than failing to connect don't cause a rotation
+  let serai = client(vec![live.clone(), dead.clone()]);
+  assert!(matches!(
+    serai.call::<_, Value>("unsupported_method", ()).await,
+    Err(SeraiError::ErrorInResponse(_))
+  ));
+  assert_eq!(serai.current_endpoint(), Some(live.as_str()));
+
+  // If every endpoint is unreachable, the error is returned
+  let serai = client(vec![dead.clone(), unreachable().await]);
+  assert!(matches!(serai.latest_finalized_block_hash().await, Err(SeraiError::ConnectionError)));
+}
+
+#[tokio::test]
+async fn cross_check_endpoints() {
+  // The mocks produce the same blocks, unless transactions are published to one
+  let first = Arc::new(MockSerai::new());
+  let second = Arc::new(MockSerai::new());
+  let serai = client(vec![serve(first.clone()).await, serve(second.clone()).await]);
+  serai.cross_check_endpoints().await.unwrap();
+
+  // Endpoints behind the others are checked as of the block they've finalized
+  first.produce_block();
+  first.produce_block();
+  second.produce_block();
+  serai.cross_check_endpoints().await.unwrap();
+
+  // Endpoints which disagree on a finalized block are detected
+  let tx = Transaction::new(Serai::batch(vec![]), None);
+  Serai::with_rpc(second.clone()).await.unwrap().publish(&tx).await.unwrap();
+  second.produce_block();
+  assert!(matches!(serai.cross_check_endpoints().await, Err(SeraiError::InvalidNode(_))));
+
+  // Unreachable endpoints cause the check to fail
+  let serai = client(vec![serve(first).await, unreachable().await]);
+  assert!(matches!(serai.cross_check_endpoints().await, Err(SeraiError::ConnectionError)));
+}
+
+#[tokio::test]
+async fn publish_retries() {
+  let mock = Arc::new(MockSerai::new());
+  let backoff =
+    Backoff { initial: Duration::from_millis(10), max: Duration::from_millis(10), retries: 2 };
+  let tx = Transaction::new(Serai::batch(vec![]), None);
+
+  // A transaction received by the node before the connection failed isn't submitted again
+  let dropping = serve_dropping(mock.clone(), "author_submitExtrinsic", 1).await;
+  let serai = Serai::unchecked(None, vec![dropping]).with_backoff(backoff);
+  serai.publish(&tx).await.unwrap();
+  assert_eq!(mock.pending(), vec![tx.clone()]);
+
+  // A transaction which isn't in the pool is submitted again
+  mock.drop_pending();
+  let live = serve(mock.clone()).await;
+  let serai = Serai::unchecked(None, vec![unreachable().await, live]).with_backoff(backoff);
+  serai.publish(&tx).await.unwrap();
+  assert_eq!(mock.pending(), vec![tx.clone()]);
+
+  // If the pool couldn't be checked, the transaction is treated as unknown and submitted again
+  mock.drop_pending();
+  let lookups = usize::try_from(backoff.retries).unwrap() + 1;
+  let dropping = serve_dropping(mock.clone(), "author_pendingExtrinsics", lookups).await;
+  let serai = Serai::unchecked(None, vec![unreachable().await, dropping]).with_backoff(backoff);
+  serai.publish(&tx).await.unwrap();
+  assert_eq!(mock.pending(), vec![tx.clone()]);
+
+  // If the node can't be reached within the retries allowed, the error is returned
+  let serai = Serai::unchecked(None, vec![unreachable().await]).with_backoff(backoff);
+  assert!(matches!(serai.publish(&tx).await, Err(SeraiError::ConnectionError)));
+}
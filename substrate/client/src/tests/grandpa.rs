@@ -0,0 +1,121 @@
+use scale::Encode;
+
+use sp_core::{ed25519, Pair as _};
+use sp_runtime::Digest;
+
+use crate::{
+  primitives::Header,
+  grandpa::{
+    AuthoritySet, GrandpaEvent, Precommit, SignedPrecommit, Commit, GrandpaJustification,
+    FinalityProof,
+  },
+};
+
+fn header(number: u64, parent_hash: [u8; 32]) -> Header {
+  Header {
+    parent_hash: parent_hash.into(),
+    number,
+    state_root: [0; 32].into(),
+    extrinsics_root: [0; 32].into(),
+    digest: Digest::default(),
+  }
+}
+
+fn precommit(
+  pair: &ed25519::Pair,
+  round: u64,
+  set_id: u64,
+  target_hash: [u8; 32],
+  target_number: u64,
+) -> SignedPrecommit {
+  let precommit = Precommit { target_hash, target_number };
+  SignedPrecommit {
+    signature: pair.sign(&precommit.signature_message(round, set_id)).0,
+    id: pair.public().0,
+    precommit,
+  }
+}
+
+fn proof(
+  justified: &Header,
+  unknown_headers: Vec<Header>,
+  precommits: Vec<SignedPrecommit>,
+  votes_ancestries: Vec<Header>,
+) -> FinalityProof {
+  let justification = GrandpaJustification {
+    round: 3,
+    commit: Commit {
+      target_hash: justified.hash().into(),
+      target_number: justified.number,
+      precommits,
+    },
+    votes_ancestries,
+  };
+  FinalityProof {
+    block: justified.hash().into(),
+    justification: justification.encode(),
+    unknown_headers,
+  }
+}
+
+#[test]
+fn finality_proof() {
+  let pairs = (0 .. 4u8).map(|i| ed25519::Pair::from_seed(&[i; 32])).collect::<Vec<_>>();
+  let set = AuthoritySet {
+    set_id: 7,
+    authorities: pairs.iter().map(|pair| (pair.public().0, 1)).collect(),
+  };
+
+  let a = header(1, [0xff; 32]);
+  let b = header(2, a.hash().into());
+  let c = header(3, b.hash().into());
+
+  let b_precommits = |signers: &[usize], set_id| {
+    signers.iter().map(|i| precommit(&pairs[*i], 3, set_id, b.hash().into(), 2)).collect::<Vec<_>>()
+  };
+
+  // Three of the four authorities is a supermajority
+  let valid = proof(&b, vec![b.clone()], b_precommits(&[0, 1, 2], 7), vec![]);
+  valid.verify(b.hash().into(), &set).unwrap();
+  // The proof for B also proves the finality of its ancestor A
+  valid.verify(a.hash().into(), &set).unwrap();
+  // Yet not of its descendant, nor an unrelated block
+  assert!(valid.verify(c.hash().into(), &set).is_err());
+  assert!(valid.verify([0xaa; 32], &set).is_err());
+
+  // Two authorities isn't a supermajority, even if one of them signs twice
+  assert!(proof(&b, vec![], b_precommits(&[0, 1], 7), vec![])
+    .verify(b.hash().into(), &set)
+    .is_err());
+  assert!(proof(&b, vec![], b_precommits(&[0, 1, 1], 7), vec![])
+    .verify(b.hash().into(), &set)
+    .is_err());
+
+  // Signatures from a different set shouldn't verify
+  assert!(proof(&b, vec![], b_precommits(&[0, 1, 2], 6), vec![])
+    .verify(b.hash().into(), &set)
+    .is_err());
+
+  // Nor should precommits from non-authorities
+  let mut precommits = b_precommits(&[0, 1], 7);
+  precommits.push(precommit(&ed25519::Pair::from_seed(&[0xff; 32]), 3, 7, b.hash().into(), 2));
+  assert!(proof(&b, vec![], precommits, vec![]).verify(b.hash().into(), &set).is_err());
+
+  // Precommits for descendants of the target count, if the ancestry is included
+  let mut precommits = b_precommits(&[0, 1], 7);
+  precommits.push(precommit(&pairs[2], 3, 7, c.hash().into(), 3));
+  proof(&b, vec![], precommits.clone(), vec![c.clone()]).verify(b.hash().into(), &set).unwrap();
+  assert!(proof(&b, vec![], precommits, vec![]).verify(b.hash().into(), &set).is_err());
+}
+
+#[test]
+fn authority_set_changes() {
+  let mut set = AuthoritySet { set_id: 0, authorities: vec![([1; 32], 1)] };
+  set.apply(&[GrandpaEvent::Paused]);
+  assert_eq!(set.set_id, 0);
+
+  set.apply(&[GrandpaEvent::NewAuthorities {
+    authority_set: vec![([2; 32].into(), 1), ([3; 32].into(), 2)],
+  }]);
+  assert_eq!(set, AuthoritySet { set_id: 1, authorities: vec![([2; 32], 1), ([3; 32], 2)] });
+}
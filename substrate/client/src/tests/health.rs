@@ -0,0 +1,38 @@
+use std::sync::Arc;
+
+use crate::{Serai, MockSerai};
+
+#[tokio::test]
+async fn health() {
+  let mock = Arc::new(MockSerai::new());
+  let serai = Serai::with_rpc(mock.clone()).await.unwrap();
+
+  mock.produce_block();
+  mock.produce_unfinalized_block();
+  mock.produce_unfinalized_block();
+
+  let health = serai.health().await.unwrap();
+  assert_eq!(health.chain, "Mock");
+  assert_eq!(Some(health.genesis), serai.block_hash(0).await.unwrap());
+  assert_eq!(health.best_block, 3);
+  assert_eq!(health.finalized_block, 1);
+  assert_eq!(health.finalization_lag(), 2);
+  // The mock doesn't have peers, yet also shouldn't
+  assert!(health.is_healthy(2));
+  assert!(!health.is_healthy(1));
+
+  mock.finalize();
+  let mut health = serai.health().await.unwrap();
+  assert_eq!(health.finalization_lag(), 0);
+  assert!(health.is_healthy(0));
+
+  health.syncing = true;
+  assert!(!health.is_healthy(0));
+  health.syncing = false;
+  health.should_have_peers = true;
+  assert!(!health.is_healthy(0));
+  health.peers = 1;
+  assert!(health.is_healthy(0));
+  health.runtime.spec_version += 1;
+  assert!(!health.is_healthy(0));
+}
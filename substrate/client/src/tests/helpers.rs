@@ -0,0 +1,191 @@
+use scale::{Encode, Decode};
+
+use serai_abi::{Call, Transaction, coins};
+
+use crate::{
+  primitives::{
+    Coin, ExternalNetworkId, ExternalCoin, ExternalBalance, Amount, Balance, SeraiAddress,
+    EXTERNAL_NETWORKS,
+  },
+  in_instructions::primitives::{
+    Shorthand, RefundableInInstruction, InInstruction, DexCall, OutAddress,
+  },
+  helpers::*,
+};
+
+#[cfg(feature = "serai")]
+#[test]
+fn serai_address() {
+  let address = SeraiAddress::new([0xaa; 32]);
+  let encoded = format_serai_address(address);
+  assert_eq!(parse_serai_address(&encoded), Ok(address));
+
+  // Corrupting the checksum should cause the address to fail to parse
+  let mut corrupted = encoded.into_bytes();
+  let last = corrupted.last_mut().unwrap();
+  *last = if *last == b'a' { b'b' } else { b'a' };
+  let corrupted = String::from_utf8(corrupted).unwrap();
+  assert_eq!(parse_serai_address(&corrupted), Err(AddressError::InvalidSeraiAddress));
+}
+
+#[cfg(feature = "bitcoin")]
+#[test]
+fn bitcoin_address() {
+  let network = ExternalNetworkId::Bitcoin;
+  // Bech32 addresses are normalized to lowercase
+  assert_eq!(
+    normalize_external_address(network, "BC1QW508D6QEJXTDG4Y5R3ZARVARY0C5XW7KV8F3T4").unwrap(),
+    "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"
+  );
+  let taproot = "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0";
+  assert_eq!(normalize_external_address(network, taproot).unwrap(), taproot);
+
+  // Testnet addresses aren't valid
+  assert_eq!(
+    parse_external_address(network, "tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx"),
+    Err(AddressError::InvalidAddress(network))
+  );
+
+  // Only addresses for the coin's network are valid within an instruction
+  #[cfg(feature = "monero")]
+  {
+    let balance = ExternalBalance { coin: ExternalCoin::Monero, amount: Amount(1) };
+    assert_eq!(
+      out_instruction(taproot, balance),
+      Err(AddressError::InvalidAddress(ExternalNetworkId::Monero))
+    );
+  }
+  let balance = ExternalBalance { coin: ExternalCoin::Bitcoin, amount: Amount(1) };
+  let instruction = out_instruction(taproot, balance).unwrap();
+  assert_eq!(instruction.balance, balance);
+  assert_eq!(format_external_address(network, &instruction.instruction.address).unwrap(), taproot);
+}
+
+#[cfg(feature = "ethereum")]
+#[test]
+fn ethereum_address() {
+  let network = ExternalNetworkId::Ethereum;
+  let checksummed = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+  assert_eq!(normalize_external_address(network, checksummed).unwrap(), checksummed);
+  // Addresses without a checksum are accepted and checksummed
+  assert_eq!(
+    normalize_external_address(network, &checksummed.to_lowercase()).unwrap(),
+    checksummed
+  );
+  // Addresses with an invalid checksum aren't
+  assert_eq!(
+    parse_external_address(network, "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD"),
+    Err(AddressError::InvalidAddress(network))
+  );
+  assert_eq!(
+    parse_external_address(network, "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeA"),
+    Err(AddressError::InvalidAddress(network))
+  );
+}
+
+#[cfg(feature = "monero")]
+#[test]
+fn monero_address() {
+  let network = ExternalNetworkId::Monero;
+  let standard =
+    "4B33mFPMq6mKi7Eiyd5XuyKRVMGVZz1Rqb9ZTyGApXW5d1aT7UBDZ89ewmnWFkzJ5wPd2SFbn313vCT8a4E2Qf4\
+    KQH4pNey";
+  assert_eq!(normalize_external_address(network, standard).unwrap(), standard);
+  let subaddress =
+    "8C5zHM5ud8nGC4hC2ULiBLSWx9infi8JUUmWEat4fcTf8J4H38iWYVdFmPCA9UmfLTZxD43RsyKnGEdZkoGij\
+    6csDeUnbEB";
+  assert_eq!(normalize_external_address(network, subaddress).unwrap(), subaddress);
+
+  let integrated =
+    "4Ljin4CrSNHKi7Eiyd5XuyKRVMGVZz1Rqb9ZTyGApXW5d1aT7UBDZ89ewmnWFkzJ5wPd2SFbn313vCT8a4E2Qf4\
+    KbaTH6MnpXSn88oBX35";
+  assert_eq!(
+    parse_external_address(network, integrated),
+    Err(AddressError::UnsupportedAddress(network))
+  );
+}
+
+#[test]
+fn add_liquidity_instruction_encoding() {
+  let address = SeraiAddress::new([0xaa; 32]);
+  for network in EXTERNAL_NETWORKS {
+    let data = add_liquidity_instruction(network, None, address).unwrap();
+    assert!(data.len() <= max_instruction_len(network));
+    assert_eq!(
+      Shorthand::decode(&mut data.as_slice()).unwrap(),
+      Shorthand::Raw(RefundableInInstruction {
+        origin: None,
+        instruction: InInstruction::Dex(DexCall::SwapAndAddLiquidity(address)),
+      })
+    );
+  }
+}
+
+#[cfg(all(feature = "bitcoin", feature = "ethereum", feature = "monero"))]
+#[test]
+fn swap_instruction_encoding() {
+  let taproot = "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0";
+  let standard =
+    "4B33mFPMq6mKi7Eiyd5XuyKRVMGVZz1Rqb9ZTyGApXW5d1aT7UBDZ89ewmnWFkzJ5wPd2SFbn313vCT8a4E2Qf4\
+    KQH4pNey";
+
+  // Swapping BTC for SRI, with a refund address, fits within an OP_RETURN output
+  let address = SeraiAddress::new([0xaa; 32]);
+  let data =
+    swap_to_sri_instruction(ExternalNetworkId::Bitcoin, Some(taproot), Amount(1), address).unwrap();
+  assert!(data.len() <= 80);
+  assert_eq!(
+    Shorthand::decode(&mut data.as_slice()).unwrap(),
+    Shorthand::Raw(RefundableInInstruction {
+      origin: Some(parse_external_address(ExternalNetworkId::Bitcoin, taproot).unwrap()),
+      instruction: InInstruction::Dex(DexCall::Swap(
+        Balance { coin: Coin::Serai, amount: Amount(1) },
+        OutAddress::Serai(address),
+      )),
+    })
+  );
+
+  // The out address must be for the network of the coin swapped to
+  let minimum = ExternalBalance { coin: ExternalCoin::Monero, amount: Amount(1) };
+  assert_eq!(
+    swap_instruction(ExternalNetworkId::Bitcoin, None, minimum, taproot),
+    Err(InstructionError::Address(AddressError::InvalidAddress(ExternalNetworkId::Monero)))
+  );
+  // The refund address must be for the network deposited to
+  assert_eq!(
+    swap_instruction(ExternalNetworkId::Ethereum, Some(taproot), minimum, standard),
+    Err(InstructionError::Address(AddressError::InvalidAddress(ExternalNetworkId::Ethereum)))
+  );
+
+  // Monero addresses are too large to swap to from Bitcoin, yet not from Ethereum
+  assert!(matches!(
+    swap_instruction(ExternalNetworkId::Bitcoin, None, minimum, standard),
+    Err(InstructionError::TooLarge { network: ExternalNetworkId::Bitcoin, limit: 80, .. })
+  ));
+  let data = swap_instruction(ExternalNetworkId::Ethereum, None, minimum, standard).unwrap();
+  assert_eq!(
+    Shorthand::decode(&mut data.as_slice()).unwrap(),
+    Shorthand::Raw(RefundableInInstruction {
+      origin: None,
+      instruction: InInstruction::Dex(DexCall::Swap(
+        minimum.into(),
+        OutAddress::External(parse_external_address(ExternalNetworkId::Monero, standard).unwrap()),
+      )),
+    })
+  );
+}
+
+#[test]
+fn extrinsic_decoding() {
+  let call = Call::Coins(coins::Call::transfer {
+    to: SeraiAddress::new([0xaa; 32]),
+    balance: Balance { coin: Coin::Serai, amount: Amount(1) },
+  });
+  let mut extrinsic = Transaction::new(call.clone(), None).encode();
+  assert_eq!(decode_extrinsic(&extrinsic).unwrap(), call);
+
+  // Extrinsics with trailing bytes are rejected
+  extrinsic.push(0);
+  assert!(decode_extrinsic(&extrinsic).is_err());
+  assert!(decode_extrinsic(&[]).is_err());
+}
@@ -0,0 +1,63 @@
+use std::sync::Arc;
+
+use sp_core::sr25519::Signature;
+
+use crate::{
+  primitives::{BlockHash, ExternalNetworkId},
+  in_instructions::{
+    primitives::{Batch, SignedBatch},
+    PendingBatch,
+  },
+  Serai, SeraiInInstructions, MockSerai,
+};
+
+fn batch(network: ExternalNetworkId, id: u32) -> SignedBatch {
+  SignedBatch {
+    batch: Batch {
+      network,
+      id,
+      block: BlockHash([u8::try_from(id).unwrap(); 32]),
+      instructions: vec![],
+    },
+    signature: Signature::from_raw([0; 64]),
+  }
+}
+
+#[tokio::test]
+async fn pending_batches() {
+  let mock = Arc::new(MockSerai::new());
+  let serai = Serai::with_rpc(mock.clone()).await.unwrap();
+  let network = ExternalNetworkId::Bitcoin;
+
+  // Batch 0 was already executed
+  mock.set_storage("InInstructions", "LastBatch", network, 0u32);
+  mock.set_storage("InInstructions", "LastBatchBlock", network, 1u64);
+  mock.produce_block();
+  let latest = serai.as_of_latest_finalized_block().await.unwrap();
+  assert_eq!(latest.in_instructions().next_batch(network).await.unwrap(), 1);
+  assert_eq!(latest.in_instructions().last_batch_block(network).await.unwrap(), Some(1));
+  assert_eq!(latest.in_instructions().next_batch(ExternalNetworkId::Monero).await.unwrap(), 0);
+
+  // Batch 1 is included in a block which isn't finalized, while batch 2 is in the pool
+  serai.publish(&SeraiInInstructions::execute_batch(batch(network, 1))).await.unwrap();
+  let included = mock.produce_unfinalized_block();
+  for batch in [batch(network, 0), batch(network, 2), batch(ExternalNetworkId::Monero, 0)] {
+    serai.publish(&SeraiInInstructions::execute_batch(batch)).await.unwrap();
+  }
+
+  let pending = |id, included| PendingBatch {
+    network,
+    id,
+    block: BlockHash([u8::try_from(id).unwrap(); 32]),
+    included,
+  };
+  assert_eq!(
+    serai.pending_batches(network).await.unwrap(),
+    vec![pending(1, Some(included)), pending(2, None)]
+  );
+
+  // Once executed by a finalized block, neither is pending
+  mock.set_storage("InInstructions", "LastBatch", network, 2u32);
+  mock.produce_block();
+  assert!(serai.pending_batches(network).await.unwrap().is_empty());
+}
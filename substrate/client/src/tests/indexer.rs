@@ -0,0 +1,63 @@
+use std::sync::Arc;
+
+use serai_db::MemDb;
+
+use crate::{
+  primitives::{Amount, Balance, BlockHash, Coin, ExternalNetworkId, SeraiAddress},
+  abi::{Event, coins, in_instructions},
+  Pair, PairTrait, Indexer, Serai, SeraiCoins, MockSerai,
+};
+
+#[tokio::test]
+async fn indexer() {
+  let mock = Arc::new(MockSerai::new());
+  let serai = Serai::with_rpc(mock.clone()).await.unwrap();
+
+  let pair = Pair::from_seed(&[1; 32]);
+  let signer = SeraiAddress::from(pair.public());
+  let recipient = SeraiAddress::new([2; 32]);
+  let balance = Balance { coin: Coin::Serai, amount: Amount(1) };
+  let tx = serai.sign(&pair, SeraiCoins::transfer(recipient, balance), 0, 0);
+  serai.publish(&tx).await.unwrap();
+  let transfer = coins::Event::Transfer { from: signer, to: recipient, balance };
+  mock.push_extrinsic_event(1, Event::Coins(transfer.clone()));
+  let batch = in_instructions::Event::Batch {
+    network: ExternalNetworkId::Bitcoin,
+    id: 0,
+    block: BlockHash([0xaa; 32]),
+    instructions_hash: [0xbb; 32],
+  };
+  mock.push_event(Event::InInstructions(batch.clone()));
+  mock.produce_block();
+  mock.produce_unfinalized_block();
+
+  let db = MemDb::new();
+  let mut indexer = Indexer::new(db.clone());
+  assert!(indexer.index_next_block(&serai).await.unwrap());
+  assert!(indexer.index_next_block(&serai).await.unwrap());
+  // Blocks which aren't finalized aren't indexed
+  assert!(!indexer.index_next_block(&serai).await.unwrap());
+
+  // The index is persisted to the database
+  let indexer = Indexer::new(db);
+  assert_eq!(indexer.next_block(), 2);
+  assert_eq!(indexer.block_hash(1), serai.block_hash(1).await.unwrap());
+
+  let events = indexer.events_by_account(recipient).unwrap();
+  assert_eq!(events.len(), 1);
+  assert_eq!((events[0].block, events[0].transaction), (1, Some(1)));
+  assert_eq!(events[0].event, Event::Coins(transfer));
+  assert_eq!(indexer.events_by_account(signer).unwrap(), events);
+
+  let transactions = indexer.transactions_by_account(signer).unwrap();
+  assert_eq!(transactions.len(), 1);
+  assert_eq!((transactions[0].block, transactions[0].index), (1, 1));
+  assert_eq!(transactions[0].transaction, tx);
+  // The timestamp and the transfer
+  assert_eq!(indexer.block_transactions(1).unwrap().unwrap().len(), 2);
+
+  let batches = indexer.batches(ExternalNetworkId::Bitcoin).unwrap();
+  assert_eq!(batches.len(), 1);
+  assert_eq!(batches[0].event, Event::InInstructions(batch));
+  assert!(indexer.batches(ExternalNetworkId::Monero).unwrap().is_empty());
+}
@@ -0,0 +1,43 @@
+use serde_json::json;
+
+use crate::{
+  primitives::{Amount, Coin, Balance, SeraiAddress},
+  abi::{Event, system},
+  coins::CoinsEvent,
+  TransactionStatus,
+};
+
+#[test]
+fn event_json() {
+  let transfer = Event::Coins(CoinsEvent::Transfer {
+    from: SeraiAddress::new([1; 32]),
+    to: SeraiAddress::new([2; 32]),
+    balance: Balance { coin: Coin::Serai, amount: Amount(3) },
+  });
+  let json = serde_json::to_value(&transfer).unwrap();
+  assert_eq!(json["Coins"]["Transfer"]["balance"]["amount"], json!(3));
+  assert_eq!(serde_json::from_value::<Event>(json).unwrap(), transfer);
+
+  let success =
+    Event::System(system::Event::ExtrinsicSuccess { dispatch_info: Default::default() });
+  let json = serde_json::to_value(&success).unwrap();
+  assert_eq!(
+    json,
+    json!({
+      "System": {
+        "ExtrinsicSuccess": {
+          "dispatch_info": { "ref_time": 0, "proof_size": 0, "class": "normal", "pays_fee": true },
+        },
+      },
+    })
+  );
+  assert_eq!(serde_json::from_value::<Event>(json).unwrap(), success);
+
+  let status = TransactionStatus::Finalized { block: [0xaa; 32], index: 1, events: vec![success] };
+  let json = serde_json::to_value(&status).unwrap();
+  assert_eq!(json["Finalized"]["index"], json!(1));
+  assert_eq!(
+    json["Finalized"]["events"][0]["System"]["ExtrinsicSuccess"]["dispatch_info"]["class"],
+    "normal"
+  );
+}
@@ -0,0 +1,187 @@
+use scale::Encode;
+
+use scale_info::meta_type;
+use frame_metadata::{
+  v14::{RuntimeMetadataV14, PalletMetadata, PalletEventMetadata, ExtrinsicMetadata},
+  RuntimeMetadataPrefixed,
+};
+
+use serai_abi::{
+  primitives::{SeraiAddress, Balance},
+  system, timestamp,
+  coins::primitives::OutInstructionWithBalance,
+  liquidity_tokens,
+};
+
+use crate::{metadata, RuntimeMismatch, SeraiError};
+
+fn pallet(name: &'static str, index: u8) -> PalletMetadata {
+  PalletMetadata {
+    name,
+    storage: None,
+    calls: None,
+    event: None,
+    constants: vec![],
+    error: None,
+    index,
+  }
+}
+
+fn pallet_with_events(
+  name: &'static str,
+  index: u8,
+  events: scale_info::MetaType,
+) -> PalletMetadata {
+  PalletMetadata { event: Some(PalletEventMetadata { ty: events }), ..pallet(name, index) }
+}
+
+fn metadata_with_pallets(pallets: Vec<PalletMetadata>, extrinsic: scale_info::MetaType) -> Vec<u8> {
+  let metadata = RuntimeMetadataV14::new(
+    pallets,
+    ExtrinsicMetadata { ty: extrinsic, version: 4, signed_extensions: vec![] },
+    meta_type::<()>(),
+  );
+  RuntimeMetadataPrefixed::from(metadata).encode()
+}
+
+fn runtime_metadata(extrinsic: scale_info::MetaType) -> Vec<u8> {
+  metadata_with_pallets(
+    vec![pallet("Timestamp", 1), pallet("Coins", 3), pallet("LiquidityTokens", 4)],
+    extrinsic,
+  )
+}
+
+// The calls within transactions, as described by a runtime whose calls don't match Serai's ABI
+#[allow(dead_code)]
+#[derive(scale_info::TypeInfo)]
+enum Calls {
+  Timestamp(timestamp::Call),
+  // Coins is described with a call type lacking `burn_with_instruction`, with `burn` and
+  // `transfer` in the opposite order
+  Coins(liquidity_tokens::Call),
+  // LiquidityTokens is described with a different index
+  #[codec(index = 3)]
+  LiquidityTokens(liquidity_tokens::Call),
+}
+
+#[test]
+fn missing_calls() {
+  // Transactions are described as a tuple of their call and their signature
+  let mismatches = metadata::missing_calls(&runtime_metadata(meta_type::<(Calls, ())>())).unwrap();
+  let call_index = |call: &str, expected, found| RuntimeMismatch::CallIndex {
+    pallet: "Coins".to_string(),
+    call: call.to_string(),
+    expected,
+    found,
+  };
+  assert_eq!(
+    mismatches[.. 4],
+    [
+      call_index("transfer", 0, 1),
+      call_index("burn", 1, 0),
+      RuntimeMismatch::MissingCall {
+        pallet: "Coins".to_string(),
+        call: "burn_with_instruction".to_string()
+      },
+      RuntimeMismatch::PalletIndex { pallet: "LiquidityTokens".to_string(), expected: 2, found: 3 },
+    ]
+  );
+  assert!(mismatches.contains(&RuntimeMismatch::MissingPallet("Dex".to_string())));
+  // Timestamp, whose index and calls match, isn't reported, despite the pallet itself having a
+  // different index within the runtime
+  assert!(!mismatches.iter().any(|mismatch| mismatch.to_string().contains("Timestamp")));
+
+  // Serai's ABI has no mismatches against itself
+  let abi = runtime_metadata(meta_type::<(serai_abi::Call, ())>());
+  assert!(metadata::missing_calls(&abi).unwrap().is_empty());
+
+  // Metadata which doesn't describe the calls within transactions can't be checked
+  assert!(metadata::missing_calls(&runtime_metadata(meta_type::<()>())).is_err());
+  assert!(metadata::missing_calls(&[0xff; 8]).is_err());
+}
+
+// The events of a runtime whose Coins events don't match Serai's ABI
+#[allow(dead_code)]
+#[derive(scale_info::TypeInfo)]
+enum CoinsEvent {
+  // The same encoding, despite the field being described as a raw array
+  Mint {
+    to: [u8; 32],
+    balance: Balance,
+  },
+  // A different index
+  #[codec(index = 1)]
+  BurnWithInstruction {
+    from: SeraiAddress,
+    instruction: OutInstructionWithBalance,
+  },
+  // Different fields, without `Burn`
+  #[codec(index = 3)]
+  Transfer {
+    from: SeraiAddress,
+    to: SeraiAddress,
+  },
+}
+
+#[test]
+fn missing_events() {
+  let metadata = metadata_with_pallets(
+    vec![
+      pallet_with_events("System", 0, meta_type::<system::Event>()),
+      pallet_with_events("Coins", 3, meta_type::<CoinsEvent>()),
+      pallet_with_events("LiquidityTokens", 5, meta_type::<liquidity_tokens::Event>()),
+    ],
+    meta_type::<(serai_abi::Call, ())>(),
+  );
+  let mismatches = metadata::missing_events(&metadata).unwrap();
+  for mismatch in [
+    RuntimeMismatch::MissingEvent { pallet: "Coins".to_string(), event: "Burn".to_string() },
+    RuntimeMismatch::EventIndex {
+      pallet: "Coins".to_string(),
+      event: "BurnWithInstruction".to_string(),
+      expected: 2,
+      found: 1,
+    },
+    RuntimeMismatch::EventFields { pallet: "Coins".to_string(), event: "Transfer".to_string() },
+    RuntimeMismatch::EventPalletIndex {
+      pallet: "LiquidityTokens".to_string(),
+      expected: 4,
+      found: 5,
+    },
+  ] {
+    assert!(mismatches.contains(&mismatch));
+  }
+  // Pallets absent from the runtime have every event reported as missing
+  assert!(mismatches.iter().any(
+    |mismatch| matches!(mismatch, RuntimeMismatch::MissingEvent { pallet, .. } if pallet == "Dex")
+  ));
+  // System, whose events match, and Coins::Mint, whose encoding matches, aren't reported
+  assert!(!mismatches.iter().any(|mismatch| {
+    let mismatch = mismatch.to_string();
+    mismatch.contains("System") || mismatch.contains("Mint")
+  }));
+
+  // Solely mismatches in the encoding of calls and events require an updated client
+  assert!(!RuntimeMismatch::SpecVersion { expected: 1, found: 2 }.changes_encoding());
+  assert!(!RuntimeMismatch::TransactionVersion { expected: 1, found: 2 }.changes_encoding());
+  assert!(mismatches.iter().all(RuntimeMismatch::changes_encoding));
+}
+
+#[test]
+fn incompatible_runtime_display() {
+  let error = SeraiError::IncompatibleRuntime(vec![
+    RuntimeMismatch::SpecVersion { expected: 1, found: 2 },
+    RuntimeMismatch::MissingCall { pallet: "Dex".to_string(), call: "swap".to_string() },
+    RuntimeMismatch::CallIndex {
+      pallet: "Coins".to_string(),
+      call: "burn".to_string(),
+      expected: 1,
+      found: 0,
+    },
+  ]);
+  assert_eq!(
+    error.to_string(),
+    "runtime is incompatible with this library: expected spec version 1, found 2, \
+     call Dex::swap is missing, expected call Coins::burn to have index 1, found 0"
+  );
+}
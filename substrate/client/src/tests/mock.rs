@@ -0,0 +1,56 @@
+use std::sync::Arc;
+
+use crate::{
+  primitives::{BlockHash, ExternalNetworkId},
+  abi::{Event, in_instructions},
+  Transaction, Serai, MockSerai,
+};
+
+#[tokio::test]
+async fn mock_serai() {
+  let mock = Arc::new(MockSerai::new());
+  let serai = Serai::with_rpc(mock.clone()).await.unwrap();
+  assert_eq!(serai.latest_finalized_block().await.unwrap().number(), 0);
+
+  let network = ExternalNetworkId::Bitcoin;
+  let batch = in_instructions::Event::Batch {
+    network,
+    id: 0,
+    block: BlockHash([0xaa; 32]),
+    instructions_hash: [0xbb; 32],
+  };
+  mock.set_storage("InInstructions", "LastBatch", network, 0u32);
+  mock.push_event(Event::InInstructions(batch.clone()));
+
+  // Nothing is visible until the block is produced
+  {
+    let serai = serai.as_of_latest_finalized_block().await.unwrap();
+    assert_eq!(serai.in_instructions().last_batch_for_network(network).await.unwrap(), None);
+  }
+
+  let hash = mock.produce_block();
+  {
+    let serai = serai.as_of_latest_finalized_block().await.unwrap();
+    assert_eq!(serai.in_instructions().last_batch_for_network(network).await.unwrap(), Some(0));
+    assert_eq!(serai.in_instructions().batch_events().await.unwrap(), vec![batch]);
+  }
+
+  // Storage persists into the next block, while events don't
+  let tx = Transaction::new(Serai::batch(vec![]), None);
+  serai.publish(&tx).await.unwrap();
+  assert_eq!(mock.pending(), vec![tx.clone()]);
+
+  let next = mock.produce_unfinalized_block();
+  assert!(mock.pending().is_empty());
+  assert_eq!(serai.latest_finalized_block_hash().await.unwrap(), hash);
+  mock.finalize();
+  assert_eq!(serai.latest_finalized_block_hash().await.unwrap(), next);
+
+  let block = serai.block(next).await.unwrap().unwrap();
+  assert_eq!(block.header.parent_hash.0, hash);
+  assert_eq!(block.transactions[1], tx);
+
+  let serai = serai.as_of(next);
+  assert_eq!(serai.in_instructions().last_batch_for_network(network).await.unwrap(), Some(0));
+  assert!(serai.in_instructions().batch_events().await.unwrap().is_empty());
+}
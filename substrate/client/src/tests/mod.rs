@@ -1,2 +1,69 @@
 #[cfg(feature = "networks")]
 mod networks;
+
+#[cfg(feature = "serai")]
+mod dex;
+
+#[cfg(feature = "serai")]
+mod analytics;
+
+#[cfg(feature = "serai")]
+mod grandpa;
+
+#[cfg(feature = "serai")]
+mod deadline;
+
+#[cfg(feature = "serai")]
+mod extensions;
+
+#[cfg(feature = "serai")]
+mod upgrades;
+
+#[cfg(feature = "serai")]
+mod backoff;
+
+#[cfg(feature = "serai")]
+mod events;
+
+#[cfg(feature = "serai")]
+mod offline;
+
+#[cfg(feature = "serai")]
+mod mock;
+
+#[cfg(feature = "serai")]
+mod proofs;
+
+mod helpers;
+
+#[cfg(feature = "serai")]
+mod ratelimit;
+
+#[cfg(feature = "serai")]
+mod metadata;
+
+#[cfg(feature = "serai")]
+mod validator_sets;
+
+#[cfg(feature = "serai")]
+mod emissions;
+
+#[cfg(feature = "serai")]
+mod coins;
+
+#[cfg(feature = "indexer")]
+mod indexer;
+#[cfg(feature = "serai")]
+mod in_instructions;
+#[cfg(feature = "serai")]
+mod watch;
+#[cfg(feature = "serai")]
+mod health;
+#[cfg(feature = "serai")]
+mod archive;
+#[cfg(feature = "serai")]
+mod json;
+#[cfg(feature = "serai")]
+mod nonces;
+#[cfg(feature = "serai")]
+mod failover;
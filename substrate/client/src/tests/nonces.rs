@@ -0,0 +1,50 @@
+use std::sync::Arc;
+
+use crate::{
+  primitives::{SeraiAddress, insecure_pair_from_name},
+  PairTrait, Serai, NonceManager, MockSerai,
+};
+
+#[tokio::test]
+async fn nonces() {
+  let mock = Arc::new(MockSerai::new());
+  let serai = Serai::with_rpc(mock.clone()).await.unwrap();
+  let nonces = NonceManager::new(serai.clone());
+
+  let alice = insecure_pair_from_name("Alice");
+  let bob = insecure_pair_from_name("Bob");
+  let alice_address = SeraiAddress::from(alice.public());
+  let bob_address = SeraiAddress::from(bob.public());
+
+  // Nonces are handed out sequentially, without waiting for inclusion
+  for expected in 0 .. 3 {
+    let tx = nonces.sign(&alice, Serai::batch(vec![]), 0).await.unwrap();
+    serai.publish(&tx).await.unwrap();
+    assert_eq!(serai.next_nonce(alice_address).await.unwrap(), expected + 1);
+  }
+  assert_eq!(nonces.next(alice_address).await.unwrap(), 3);
+
+  // Each signer's nonces are independent
+  assert_eq!(nonces.next(bob_address).await.unwrap(), 0);
+  assert_eq!(nonces.next(bob_address).await.unwrap(), 1);
+
+  mock.produce_block();
+  assert_eq!(serai.next_nonce(alice_address).await.unwrap(), 3);
+  assert_eq!(nonces.next(alice_address).await.unwrap(), 4);
+
+  // Nonces taken yet never used are reclaimed by resyncing
+  assert_eq!(nonces.resync(alice_address).await.unwrap(), 3);
+  assert_eq!(nonces.next(alice_address).await.unwrap(), 3);
+  assert_eq!(nonces.resync(bob_address).await.unwrap(), 0);
+
+  // As are the nonces after a transaction which was dropped
+  let tx = nonces.sign(&bob, Serai::batch(vec![]), 0).await.unwrap();
+  serai.publish(&tx).await.unwrap();
+  assert_eq!(nonces.next(bob_address).await.unwrap(), 1);
+  mock.drop_pending();
+  assert_eq!(nonces.resync(bob_address).await.unwrap(), 0);
+
+  // Clones share the nonces handed out
+  assert_eq!(nonces.clone().next(alice_address).await.unwrap(), 4);
+  assert_eq!(nonces.next(alice_address).await.unwrap(), 5);
+}
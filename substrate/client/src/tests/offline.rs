@@ -0,0 +1,48 @@
+use scale::{Encode, Decode};
+
+use sp_runtime::generic::Era;
+
+use crate::{
+  primitives::{Amount, Coin, Balance, SeraiAddress, insecure_pair_from_name},
+  abi::{Extra, SignedPayloadExtra},
+  PairTrait, SeraiCoins, UnsignedTransaction,
+};
+
+#[test]
+fn offline_signing() {
+  let unsigned = UnsignedTransaction {
+    call: SeraiCoins::transfer(
+      SeraiAddress::new([1; 32]),
+      Balance { coin: Coin::Serai, amount: Amount(1) },
+    ),
+    extra: Extra { era: Era::Immortal, nonce: 5, fee_conversion: None, tip: 0 },
+    signed_extra: SignedPayloadExtra {
+      spec_version: 1,
+      tx_version: 1,
+      genesis: [0xff; 32],
+      mortality_checkpoint: [0xff; 32],
+    },
+  };
+
+  // The unsigned transaction is transferred to the airgapped machine as SCALE
+  let airgapped = UnsignedTransaction::decode(&mut unsigned.encode().as_slice()).unwrap();
+  assert_eq!(airgapped, unsigned);
+
+  let pair = insecure_pair_from_name("Alice");
+
+  // The signature is then attached to the transaction online
+  let signer = SeraiAddress::from(pair.public());
+  let tx = unsigned.clone().with_signature(signer, airgapped.signature(&pair)).unwrap();
+  assert_eq!(tx.call(), &unsigned.call);
+  assert_eq!(unsigned.clone().sign(&pair).call(), &unsigned.call);
+
+  // Signatures for another signer or another transaction are rejected
+  let other = insecure_pair_from_name("Bob");
+  assert!(unsigned
+    .clone()
+    .with_signature(other.public().into(), airgapped.signature(&pair))
+    .is_none());
+  let mut modified = unsigned;
+  modified.extra.nonce += 1;
+  assert!(modified.with_signature(signer, airgapped.signature(&pair)).is_none());
+}
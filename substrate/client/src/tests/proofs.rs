@@ -0,0 +1,33 @@
+use sp_runtime::traits::BlakeTwo256;
+use sp_trie::{LayoutV1, MemoryDB, TrieDBMutBuilder, TrieMut};
+
+use crate::StorageProof;
+
+#[test]
+fn storage_proof() {
+  let mut db = MemoryDB::<BlakeTwo256>::default();
+  let mut root = Default::default();
+  {
+    let mut trie = TrieDBMutBuilder::<LayoutV1<BlakeTwo256>>::new(&mut db, &mut root).build();
+    trie.insert(b"short", b"value").unwrap();
+    // Values longer than 32 bytes are hashed into the trie, as of state version 1
+    trie.insert(b"long", &[0xff; 64]).unwrap();
+    trie.insert(b"longer", b"value").unwrap();
+  }
+  let root = root.0;
+  let proof = StorageProof(db.drain().into_values().map(|(node, _)| node).collect());
+
+  assert_eq!(proof.verify(root, b"short").unwrap(), Some(b"value".to_vec()));
+  assert_eq!(proof.verify(root, b"long").unwrap(), Some(vec![0xff; 64]));
+  assert_eq!(proof.verify(root, b"longer").unwrap(), Some(b"value".to_vec()));
+  // The proof proves the absence of keys within the trie
+  assert_eq!(proof.verify(root, b"missing").unwrap(), None);
+
+  // A proof for another root doesn't verify
+  assert!(proof.verify([0xff; 32], b"short").is_err());
+  // Nor does an incomplete proof, for at least one of the keys
+  let mut incomplete = proof.clone();
+  incomplete.0.pop();
+  let keys: [&[u8]; 3] = [b"short", b"long", b"longer"];
+  assert!(keys.iter().any(|key| incomplete.verify(root, key).is_err()));
+}
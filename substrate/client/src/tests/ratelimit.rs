@@ -0,0 +1,53 @@
+use core::time::Duration;
+
+use patchable_async_sleep::Instant;
+
+use crate::{MethodClass, RateLimit, RateLimiter};
+
+#[test]
+fn method_class() {
+  assert_eq!(MethodClass::of("state_getStorage"), MethodClass::Storage);
+  assert_eq!(MethodClass::of("state_call"), MethodClass::RuntimeApi);
+  assert_eq!(MethodClass::of("chain_getBlockBin"), MethodClass::Chain);
+  assert_eq!(MethodClass::of("author_submitExtrinsic"), MethodClass::Author);
+  assert_eq!(MethodClass::of("system_accountNextIndex"), MethodClass::Other);
+}
+
+#[test]
+fn rate_limiter() {
+  let limit = RateLimit { interval: Duration::from_millis(100), burst: 3 };
+  let limiter = RateLimiter::new(limit).without_limit(MethodClass::Author);
+  let now = Instant::now();
+
+  // A burst is allowed immediately
+  for _ in 0 .. 3 {
+    assert_eq!(limiter.reserve(MethodClass::Storage, now), Duration::ZERO);
+  }
+  // Further requests are queued, each after the one prior
+  assert_eq!(limiter.reserve(MethodClass::Storage, now), Duration::from_millis(100));
+  assert_eq!(limiter.reserve(MethodClass::Storage, now), Duration::from_millis(200));
+
+  // Other classes have their own buckets
+  assert_eq!(limiter.reserve(MethodClass::Chain, now), Duration::ZERO);
+  // Classes without a limit are never queued
+  for _ in 0 .. 10 {
+    assert_eq!(limiter.reserve(MethodClass::Author, now), Duration::ZERO);
+  }
+
+  // The last request queued is made 200ms from now, after which the bucket refills
+  let later = now + Duration::from_millis(300);
+  assert_eq!(limiter.reserve(MethodClass::Storage, later), Duration::ZERO);
+  assert_eq!(limiter.reserve(MethodClass::Storage, later), Duration::from_millis(100));
+
+  // Yet it never holds more than a burst
+  let much_later = later + Duration::from_secs(60);
+  for _ in 0 .. 3 {
+    assert_eq!(limiter.reserve(MethodClass::Storage, much_later), Duration::ZERO);
+  }
+  assert_eq!(limiter.reserve(MethodClass::Storage, much_later), Duration::from_millis(100));
+
+  assert_eq!(
+    RateLimit::per_second(4),
+    RateLimit { interval: Duration::from_millis(250), burst: 4 }
+  );
+}
@@ -0,0 +1,33 @@
+use crate::{
+  RuntimeVersion, Compatibility,
+  upgrades::{SPEC_VERSION, TX_VERSION},
+};
+
+#[test]
+fn compatibility() {
+  // As returned by state_getRuntimeVersion
+  let version: RuntimeVersion = serde_json::from_value(serde_json::json!({
+    "specName": "serai",
+    "implName": "core",
+    "authoringVersion": 1,
+    "specVersion": SPEC_VERSION,
+    "implVersion": 0,
+    "apis": [],
+    "transactionVersion": TX_VERSION,
+    "stateVersion": 1,
+  }))
+  .unwrap();
+  assert_eq!(version.spec_name, "serai");
+  assert_eq!(version.compatibility(), Compatibility::Compatible);
+
+  let with = |spec_version, transaction_version| RuntimeVersion {
+    spec_version,
+    transaction_version,
+    ..version.clone()
+  };
+  assert_eq!(with(SPEC_VERSION + 1, TX_VERSION).compatibility(), Compatibility::ClientOutdated);
+  assert_eq!(with(SPEC_VERSION, TX_VERSION + 1).compatibility(), Compatibility::ClientOutdated);
+  assert_eq!(with(SPEC_VERSION + 1, 0).compatibility(), Compatibility::ClientOutdated);
+  assert_eq!(with(SPEC_VERSION - 1, TX_VERSION).compatibility(), Compatibility::RuntimeOutdated);
+  assert_eq!(with(SPEC_VERSION, TX_VERSION - 1).compatibility(), Compatibility::RuntimeOutdated);
+}
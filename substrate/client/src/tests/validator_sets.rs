@@ -0,0 +1,59 @@
+use core::time::Duration;
+use std::sync::Arc;
+
+use futures_util::StreamExt;
+
+use sp_core::sr25519::Public;
+
+use crate::{
+  primitives::{ExternalNetworkId, NetworkId},
+  abi::{Event, validator_sets},
+  validator_sets::primitives::{Session, ValidatorSet, ExternalValidatorSet, KeyPair},
+  KeyRotation, Serai, MockSerai,
+};
+
+fn key_gen(network: ExternalNetworkId, session: u32, key: u8) -> Event {
+  Event::ValidatorSets(validator_sets::Event::KeyGen {
+    set: ExternalValidatorSet { network, session: Session(session) },
+    key_pair: KeyPair(Public::from_raw([0xff; 32]), vec![key; 33].try_into().unwrap()),
+  })
+}
+
+fn rotation(block: u64, network: ExternalNetworkId, session: u32, key: u8) -> KeyRotation {
+  KeyRotation { block, network, session: Session(session), key: vec![key; 33].try_into().unwrap() }
+}
+
+#[tokio::test]
+async fn key_rotations() {
+  let mock = Arc::new(MockSerai::new());
+  let serai = Serai::with_rpc(mock.clone()).await.unwrap();
+
+  mock.push_event(key_gen(ExternalNetworkId::Bitcoin, 1, 1));
+  mock.push_event(key_gen(ExternalNetworkId::Monero, 1, 2));
+  // Other events from the pallet aren't key rotations
+  mock.push_event(Event::ValidatorSets(validator_sets::Event::NewSet {
+    set: ValidatorSet { network: NetworkId::Serai, session: Session(1) },
+  }));
+  let first = mock.produce_block();
+  mock.produce_block();
+  mock.push_event(key_gen(ExternalNetworkId::Bitcoin, 2, 3));
+  mock.produce_block();
+
+  assert_eq!(
+    serai.as_of(first).validator_sets().key_rotations().await.unwrap(),
+    vec![
+      rotation(1, ExternalNetworkId::Bitcoin, 1, 1),
+      rotation(1, ExternalNetworkId::Monero, 1, 2)
+    ]
+  );
+
+  let rotations = serai.key_rotations(0, Duration::from_millis(10)).take(3);
+  assert_eq!(
+    rotations.map(Result::unwrap).collect::<Vec<_>>().await,
+    vec![
+      rotation(1, ExternalNetworkId::Bitcoin, 1, 1),
+      rotation(1, ExternalNetworkId::Monero, 1, 2),
+      rotation(3, ExternalNetworkId::Bitcoin, 2, 3),
+    ]
+  );
+}
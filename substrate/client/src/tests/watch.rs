@@ -0,0 +1,49 @@
+use core::time::Duration;
+use std::sync::Arc;
+
+use futures_util::StreamExt;
+
+use crate::{Pair, PairTrait, Serai, TransactionStatus, MockSerai};
+
+#[tokio::test]
+async fn mortal_resubmission() {
+  let mock = Arc::new(MockSerai::new());
+  let serai = Serai::with_rpc(mock.clone()).await.unwrap();
+
+  let pair = Pair::from_seed(&[0xaa; 32]);
+  let mut statuses = Box::pin(serai.submit_mortal_and_watch(
+    &pair,
+    Serai::batch(vec![]),
+    0,
+    0,
+    4,
+    Duration::from_millis(1),
+  ));
+  assert_eq!(statuses.next().await.unwrap().unwrap(), TransactionStatus::Broadcast);
+  let expired = mock.pending();
+  assert_eq!(expired.len(), 1);
+
+  // Drop the transaction and produce every block it was valid for
+  mock.drop_pending();
+  for _ in 0 .. 4 {
+    mock.produce_block();
+  }
+
+  // It should be signed again, as of the latest finalized block, and resubmitted
+  assert_eq!(statuses.next().await.unwrap().unwrap(), TransactionStatus::Broadcast);
+  let resubmitted = mock.pending();
+  assert_eq!(resubmitted.len(), 1);
+  assert!(resubmitted != expired);
+  assert_eq!(resubmitted[0].signer(), expired[0].signer());
+
+  let block = mock.produce_block();
+  assert!(matches!(
+    statuses.next().await.unwrap().unwrap(),
+    TransactionStatus::InBlock { block: included, index: 1, .. } if included == block
+  ));
+  assert!(matches!(
+    statuses.next().await.unwrap().unwrap(),
+    TransactionStatus::Finalized { block: included, index: 1, .. } if included == block
+  ));
+  assert!(statuses.next().await.is_none());
+}
@@ -11,7 +11,7 @@ use serai_client::{
   primitives::{Amount, BlockHash, ExternalBalance, ExternalCoin, SeraiAddress},
   in_instructions::{
     primitives::{InInstruction, InInstructionWithBalance, Batch},
-    InInstructionsEvent,
+    InInstructionsEvent, ExecutedInstruction, ExecutedBatch,
   },
   coins::CoinsEvent,
   Serai,
@@ -61,6 +61,19 @@ serai_test!(
           instructions_hash: Blake2b::<U32>::digest(batch.instructions.encode()).into(),
         }]
       );
+      assert_eq!(serai.latest_blocks().await.unwrap().get(&network), Some(&block_hash));
+      assert_eq!(
+        serai.executed_batches().await.unwrap(),
+        vec![ExecutedBatch {
+          network,
+          id,
+          block: block_hash,
+          instructions: vec![ExecutedInstruction {
+            instruction: batch.instructions[0].clone(),
+            succeeded: true,
+          }],
+        }]
+      );
     }
 
     let serai = serai.coins();
@@ -88,17 +88,22 @@ serai_test!(
     }
 };
 
-    let block = publish_tx(
-      &serai,
-      &serai.sign(&pair, SeraiCoins::burn_with_instruction(instruction.clone()), 0, 0),
-    )
-    .await;
+    let burn = serai.sign(&pair, SeraiCoins::burn_with_instruction(instruction.clone()), 0, 0);
+    let block = publish_tx(&serai, &burn).await;
+    let transactions = serai.block(block).await.unwrap().unwrap().transactions;
 
     let serai = serai.as_of(block);
     let serai = serai.coins();
     let events = serai.burn_with_instruction_events().await.unwrap();
     assert_eq!(events, vec![CoinsEvent::BurnWithInstruction { from: address, instruction }]);
+
+    // The burn should be attributed to the extrinsic which made it
+    let events = serai.burn_with_instruction_events_by_extrinsic().await.unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(transactions[usize::try_from(events[0].0).unwrap()].hash(), burn.hash());
     assert_eq!(serai.coin_supply(coin.into()).await.unwrap(), Amount(0));
     assert_eq!(serai.coin_balance(coin.into(), address).await.unwrap(), Amount(0));
+    assert_eq!(serai.coin_minted(coin.into()).await.unwrap(), u128::from(amount.0));
+    assert_eq!(serai.coin_burned(coin.into()).await.unwrap(), u128::from(amount.0));
   })
 );
@@ -40,7 +40,7 @@ pub async fn swap(
 
   let tx = serai.sign(
     &pair,
-    SeraiDex::swap(from_coin, to_coin, amount_in, amount_out_min, address.into()),
+    SeraiDex::swap(from_coin, to_coin, amount_in, amount_out_min, address.into()).unwrap(),
     nonce,
     Default::default(),
   );
@@ -12,14 +12,15 @@ use serai_client::{
   in_instructions::primitives::{
     InInstruction, InInstructionWithBalance, Batch, IN_INSTRUCTION_EXECUTOR, OutAddress,
   },
-  dex::DexEvent,
-  Serai,
+  dex::{DexEvent, DexError, SwapOutcome},
+  Serai, SeraiDex, PalletError, DecodedDispatchError,
 };
 
 mod common;
 use common::{
   in_instructions::{provide_batch, mint_coin},
   dex::{add_liquidity as common_add_liquidity, swap as common_swap},
+  tx::publish_tx,
 };
 
 // TODO: Calculate all constants in the following tests
@@ -66,6 +67,55 @@ serai_test!(
         lp_token_minted: 49_999999990000
       }]
     );
+
+    // the typed and filtered queries should agree
+    let dex = serai.as_of(block).dex();
+    assert_eq!(dex.liquidity_added_events().await.unwrap(), events);
+    assert_eq!(dex.events_for(pair.public().into()).await.unwrap(), events);
+    let alice = insecure_pair_from_name("Alice").public().into();
+    assert!(dex.events_for(alice).await.unwrap().is_empty());
+
+    // the pool's reserves should be exactly what was added
+    assert_eq!(
+      serai.as_of(block).dex().reserves(coin).await.unwrap(),
+      Some((coin_amount, sri_amount))
+    );
+    assert!(serai.as_of(block).dex().pool_exists(coin).await.unwrap());
+    assert_eq!(
+      serai.as_of(block).dex().all_reserves().await.unwrap().get(&coin),
+      Some(&(coin_amount, sri_amount))
+    );
+
+    // the runtime's quote should match the client-side mirror of the pallet's formula
+    let amount_in = Amount(1_000_000_000);
+    assert_eq!(
+      serai
+        .as_of(block)
+        .dex()
+        .quote_amount_out(Coin::External(coin), Coin::Serai, amount_in)
+        .await
+        .unwrap(),
+      SeraiDex::get_amount_out(amount_in, coin_amount, sri_amount)
+    );
+
+    // the LP tokens should have been minted to us, with the minimum liquidity held by the pool
+    let lp_balance =
+      serai.as_of(block).dex().lp_balance(coin, pair.public().into()).await.unwrap();
+    assert_eq!(lp_balance, Amount(49_999999990000));
+    assert_eq!(
+      serai.as_of(block).dex().lp_total_issuance(coin).await.unwrap(),
+      Amount(lp_balance.0 + 10_000)
+    );
+
+    // the oracle should've recorded the pool's price, in SRI per whole XMR, at the end of the block
+    assert_eq!(
+      serai.as_of(block).dex().oracle_price(coin).await.unwrap(),
+      Some(Amount(1_000_000_000_000))
+    );
+    assert_eq!(
+      serai.as_of(block).dex().median_price(coin).await.unwrap(),
+      Some(Amount(1_000_000_000_000))
+    );
   })
 
   // Tests coin -> SRI and SRI -> coin swaps.
@@ -120,6 +170,33 @@ serai_test!(
       }]
     );
 
+    // dry-running a swap with an unachievable minimum should fail due to the slippage
+    {
+      let call = SeraiDex::swap(
+        Coin::Serai,
+        coin.into(),
+        Amount(10_000_000_000_000),
+        Amount(u64::MAX),
+        pair.public().into(),
+      )
+      .unwrap();
+      let result = serai.as_of(block).dex().dry_run(call, &pair).await.unwrap();
+      let Ok(Err(error)) = result else { panic!("dry-run didn't fail to dispatch: {result:?}") };
+      assert_eq!(
+        SeraiDex::decode_error(&error),
+        Some(DexError::ProvidedMinimumNotSufficientForSwap)
+      );
+
+      // the same error should be recoverable from the runtime's metadata
+      let DecodedDispatchError::Pallet(PalletError { pallet, error, .. }) =
+        serai.decode_dispatch_error(&error).await.unwrap()
+      else {
+        panic!("dispatch error wasn't decoded as a pallet's")
+      };
+      assert_eq!(pallet, "Dex");
+      assert_eq!(error, "ProvidedMinimumNotSufficientForSwap");
+    }
+
     // now swap some SRI to coin
     amount_in = Amount(10_000_000_000_000);
     block = common_swap(
@@ -147,6 +224,51 @@ serai_test!(
         amount_out: 17254428681101
       }]
     );
+
+    // publishing a swap should yield its realized amounts
+    let dex = serai.as_of(block);
+    let dex = dex.dex();
+    let swap = |amount_out_min, nonce| {
+      serai.sign(
+        &pair,
+        SeraiDex::swap(Coin::Serai, coin.into(), amount_in, amount_out_min, pair.public().into())
+          .unwrap(),
+        nonce,
+        0,
+      )
+    };
+    let (block, outcome) = dex.publish_swap(&swap(Amount(1), 3), 10).await.unwrap().unwrap();
+    let mut events = serai.as_of(block.hash()).dex().events().await.unwrap();
+    events.retain(|e| matches!(e, DexEvent::SwapExecuted { .. }));
+    let [DexEvent::SwapExecuted { amount_out, .. }] = events.as_slice() else {
+      panic!("swap wasn't executed: {events:?}")
+    };
+    assert_eq!(
+      outcome,
+      SwapOutcome::Executed {
+        path: vec![Coin::Serai, coin.into()],
+        amount_in,
+        amount_out: Amount(*amount_out),
+      }
+    );
+
+    // and a swap which fails should yield its error
+    let (_, outcome) = serai
+      .as_of(block.hash())
+      .dex()
+      .publish_swap(&swap(Amount(u64::MAX), 4), 10)
+      .await
+      .unwrap()
+      .unwrap();
+    assert_eq!(outcome.dex_error(), Some(DexError::ProvidedMinimumNotSufficientForSwap));
+    let SwapOutcome::Failed { error, .. } = outcome else {
+      panic!("swap didn't fail: {outcome:?}")
+    };
+    let DecodedDispatchError::Pallet(PalletError { pallet, error, .. }) = error else {
+      panic!("swap didn't fail with a pallet's error: {error:?}")
+    };
+    assert_eq!(pallet, "Dex");
+    assert_eq!(error, "ProvidedMinimumNotSufficientForSwap");
   })
 
   swap_coin_to_coin: (|serai: Serai| async move {
@@ -431,4 +553,66 @@ serai_test!(
       );
     }
   })
+
+  batch_calls: (|serai: Serai| async move {
+    let coin = ExternalCoin::Bitcoin;
+    let pair = insecure_pair_from_name("Ferdie");
+    let address = SeraiAddress::from(pair.public());
+
+    mint_coin(
+      &serai,
+      ExternalBalance { coin, amount: Amount(100_000_000_000_000) },
+      0,
+      address,
+    )
+    .await;
+
+    // add liquidity and swap against it within a single transaction
+    let reserve = Amount(50_000_000_000_000);
+    let amount_in = Amount(25_000_000_000_000);
+    let tx = serai.sign(
+      &pair,
+      Serai::batch(vec![
+        SeraiDex::add_liquidity(coin, reserve, reserve, Amount(1), Amount(1), address),
+        SeraiDex::swap(coin.into(), Coin::Serai, amount_in, Amount(1), address).unwrap(),
+      ]),
+      0,
+      0,
+    );
+    let block = publish_tx(&serai, &tx).await;
+
+    let dex = serai.as_of(block).dex();
+    assert_eq!(dex.liquidity_added_events().await.unwrap().len(), 1);
+    let amount_out = Amount(16_633_299_966_633);
+    assert_eq!(
+      dex.swap_events().await.unwrap(),
+      vec![DexEvent::SwapExecuted {
+        who: address,
+        send_to: address,
+        path: BoundedVec::try_from(vec![coin.into(), Coin::Serai]).unwrap(),
+        amount_in: amount_in.0,
+        amount_out: amount_out.0,
+      }]
+    );
+    let reserves = Some((Amount(reserve.0 + amount_in.0), Amount(reserve.0 - amount_out.0)));
+    assert_eq!(dex.reserves(coin).await.unwrap(), reserves);
+
+    // if any call within the batch fails, none of them should take effect
+    let amount = Amount(1_000_000);
+    let tx = serai.sign(
+      &pair,
+      Serai::batch(vec![
+        SeraiDex::add_liquidity(coin, amount, amount, Amount(1), Amount(1), address),
+        SeraiDex::swap(coin.into(), Coin::Serai, amount, Amount(u64::MAX), address).unwrap(),
+      ]),
+      1,
+      0,
+    );
+    let block = publish_tx(&serai, &tx).await;
+
+    let dex = serai.as_of(block).dex();
+    assert!(dex.liquidity_added_events().await.unwrap().is_empty());
+    assert!(dex.swap_events().await.unwrap().is_empty());
+    assert_eq!(dex.reserves(coin).await.unwrap(), reserves);
+  })
 );
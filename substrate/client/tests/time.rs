@@ -1,6 +1,7 @@
 use std::time::{Duration, SystemTime};
 
-use tokio::time::sleep;
+use futures_util::StreamExt;
+use tokio::time::{sleep, timeout};
 
 use serai_client::Serai;
 
@@ -25,4 +26,22 @@ serai_test!(
       done += 1;
     }
   })
+
+  finalized_block_subscription: (|serai: Serai| async move {
+    let start = serai.latest_finalized_block().await.unwrap().number();
+    let mut blocks = core::pin::pin!(serai.subscribe_finalized_blocks(Duration::from_secs(1)));
+    let mut last = None;
+    for _ in 0 .. 3 {
+      let (number, hash) =
+        timeout(Duration::from_secs(30), blocks.next()).await.unwrap().unwrap().unwrap();
+      // The blocks should be yielded in order, without gaps
+      assert!(number > start);
+      if let Some(last) = last {
+        assert_eq!(number, last + 1);
+      }
+      last = Some(number);
+      assert_eq!(serai.block_hash(number).await.unwrap(), Some(hash));
+      assert!(serai.is_finalized(&serai.header(hash).await.unwrap().unwrap()).await.unwrap());
+    }
+  })
 );
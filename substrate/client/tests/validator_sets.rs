@@ -12,7 +12,7 @@ use serai_client::{
   },
   validator_sets::{
     primitives::{Session, ValidatorSet, ExternalValidatorSet, KeyPair},
-    ValidatorSetsEvent,
+    ValidatorSetsEvent, SeraiValidatorSets,
   },
   in_instructions::{
     primitives::{Batch, SignedBatch, batch_message},
@@ -110,7 +110,40 @@ serai_test!(
       serai.key_gen_events().await.unwrap(),
       vec![ValidatorSetsEvent::KeyGen { set, key_pair: key_pair.clone() }]
     );
-    assert_eq!(serai.keys(set).await.unwrap(), Some(key_pair));
+    assert_eq!(serai.keys(set).await.unwrap(), Some(key_pair.clone()));
+    assert_eq!(
+      serai.current_set(network.into()).await.unwrap(),
+      Some(ValidatorSet { session: Session(0), network: network.into() })
+    );
+    assert_eq!(serai.active_keys(network).await.unwrap(), Some(key_pair.clone()));
+    assert_eq!(serai.all_active_keys().await.unwrap().get(&network), Some(&key_pair));
+  })
+);
+
+serai_test!(
+  network_address_test: (|serai: Serai| async move {
+    let pair = insecure_pair_from_name("Alice");
+    let public = pair.public();
+
+    let latest = serai.as_of_latest_finalized_block().await.unwrap();
+    assert_eq!(latest.validator_sets().network_address(public).await.unwrap(), None);
+
+    let address = b"/ip4/127.0.0.1/tcp/30563".to_vec();
+    let tx = serai.sign(
+      &pair,
+      SeraiValidatorSets::set_network_address(address.clone().try_into().unwrap()),
+      0,
+      0,
+    );
+    let block = publish_tx(&serai, &tx).await;
+
+    let serai = serai.as_of(block);
+    let serai = serai.validator_sets();
+    assert_eq!(serai.network_address(public).await.unwrap(), Some(address));
+    assert_eq!(
+      serai.network_address_set_events().await.unwrap(),
+      vec![ValidatorSetsEvent::NetworkAddressSet { validator: public.into() }]
+    );
   })
 );
 
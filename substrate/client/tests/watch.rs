@@ -0,0 +1,94 @@
+use core::time::Duration;
+
+use rand_core::{RngCore, OsRng};
+
+use futures_util::StreamExt;
+use tokio::time::timeout;
+
+use serai_abi::coins::primitives::OutInstructionWithBalance;
+use sp_core::Pair;
+
+use serai_client::{
+  primitives::{
+    Amount, ExternalCoin, ExternalBalance, BlockHash, SeraiAddress, ExternalAddress,
+    insecure_pair_from_name,
+  },
+  in_instructions::primitives::{InInstruction, InInstructionWithBalance, Batch},
+  coins::{primitives::OutInstruction, CoinsEvent},
+  abi::Event,
+  Serai, SeraiCoins, TransactionStatus,
+};
+
+mod common;
+use common::in_instructions::provide_batch;
+
+serai_test!(
+  submit_and_watch: (|serai: Serai| async move {
+    let mut block_hash = BlockHash([0; 32]);
+    OsRng.fill_bytes(&mut block_hash.0);
+
+    let pair = insecure_pair_from_name("Eve");
+    let address = SeraiAddress::from(pair.public());
+
+    let coin = ExternalCoin::Bitcoin;
+    let balance = ExternalBalance { coin, amount: Amount(OsRng.next_u64().saturating_add(1)) };
+    provide_batch(
+      &serai,
+      Batch {
+        network: coin.network(),
+        id: 0,
+        block: block_hash,
+        instructions: vec![InInstructionWithBalance {
+          instruction: InInstruction::Transfer(address),
+          balance,
+        }],
+      },
+    )
+    .await;
+
+    let mut rand_bytes = vec![0; 32];
+    OsRng.fill_bytes(&mut rand_bytes);
+    let instruction = OutInstructionWithBalance {
+      balance,
+      instruction: OutInstruction { address: ExternalAddress::new(rand_bytes).unwrap(), data: None },
+    };
+
+    let estimate = serai
+      .as_of_latest_finalized_block()
+      .await
+      .unwrap()
+      .estimate_fee(SeraiCoins::burn_with_instruction(instruction.clone()), address)
+      .await
+      .unwrap();
+    assert!(estimate.fee.0 > 0);
+
+    // Watch the burn from its broadcast until its finalization, checking its events are yielded
+    let tx = serai.sign(&pair, SeraiCoins::burn_with_instruction(instruction.clone()), 0, 0);
+    let mut statuses = core::pin::pin!(serai.submit_and_watch(&tx, Duration::from_secs(1)));
+    let watch_timeout = Duration::from_secs(60);
+    assert_eq!(
+      timeout(watch_timeout, statuses.next()).await.unwrap().unwrap().unwrap(),
+      TransactionStatus::Broadcast
+    );
+    let burn_event = Event::Coins(CoinsEvent::BurnWithInstruction {
+      from: address,
+      instruction: instruction.clone(),
+    });
+    let block = loop {
+      match timeout(watch_timeout, statuses.next()).await.unwrap().unwrap().unwrap() {
+        TransactionStatus::InBlock { events, .. } => assert!(events.contains(&burn_event)),
+        TransactionStatus::Retracted { .. } => {}
+        TransactionStatus::Finalized { block, events, .. } => {
+          assert!(events.contains(&burn_event));
+          break block;
+        }
+        status => panic!("unexpected status: {status:?}"),
+      }
+    };
+
+    assert_eq!(
+      serai.as_of(block).coins().burn_with_instruction_events().await.unwrap(),
+      vec![CoinsEvent::BurnWithInstruction { from: address, instruction }]
+    );
+  })
+);
@@ -95,6 +95,18 @@ pub mod pallet {
   pub type Supply<T: Config<I>, I: 'static = ()> =
     StorageMap<_, Identity, Coin, SubstrateAmount, ValueQuery>;
 
+  /// The total amount of each coin ever minted.
+  // This is tracked as a u128 as it's cumulative, and may exceed SubstrateAmount's range even
+  // while the supply doesn't.
+  #[pallet::storage]
+  #[pallet::getter(fn minted)]
+  pub type Minted<T: Config<I>, I: 'static = ()> = StorageMap<_, Identity, Coin, u128, ValueQuery>;
+
+  /// The total amount of each coin ever burned.
+  #[pallet::storage]
+  #[pallet::getter(fn burned)]
+  pub type Burned<T: Config<I>, I: 'static = ()> = StorageMap<_, Identity, Coin, u128, ValueQuery>;
+
   #[pallet::genesis_build]
   impl<T: Config<I>, I: 'static> BuildGenesisConfig for GenesisConfig<T, I> {
     fn build(&self) {
@@ -123,6 +135,11 @@ pub mod pallet {
       Self::burn_internal(FEE_ACCOUNT.into(), Balance { coin, amount }).unwrap();
       Weight::zero() // TODO
     }
+
+    #[cfg(feature = "try-runtime")]
+    fn try_state(_: BlockNumberFor<T>) -> Result<(), sp_runtime::TryRuntimeError> {
+      Self::check_supply_accounting()
+    }
   }
 
   impl<T: Config<I>, I: 'static> Pallet<T, I> {
@@ -182,6 +199,7 @@ pub mod pallet {
         .checked_add(balance.amount.0)
         .ok_or(Error::<T, I>::AmountOverflowed)?;
       Supply::<T, I>::set(balance.coin, new_supply);
+      Minted::<T, I>::mutate(balance.coin, |minted| *minted += u128::from(balance.amount.0));
 
       Self::deposit_event(Event::Mint { to, balance });
       Ok(())
@@ -200,7 +218,34 @@ pub mod pallet {
       // update the supply
       let new_supply = Self::supply(balance.coin).checked_sub(balance.amount.0).unwrap();
       Supply::<T, I>::set(balance.coin, new_supply);
+      Burned::<T, I>::mutate(balance.coin, |burned| *burned += u128::from(balance.amount.0));
+
+      Ok(())
+    }
 
+    /// Check the supply of every coin is consistent with the amounts minted and burned, and with
+    /// the balances of every account.
+    ///
+    /// For external coins, which are only minted in response to `InInstruction`s and only leave
+    /// via burns, this is the invariant that the supply equals the net inflow to Serai.
+    pub fn check_supply_accounting() -> Result<(), sp_runtime::TryRuntimeError> {
+      let mut balances = sp_std::collections::btree_map::BTreeMap::<Coin, u128>::new();
+      for (_, coin, amount) in Balances::<T, I>::iter() {
+        *balances.entry(coin).or_default() += u128::from(amount);
+      }
+
+      for (coin, supply) in Supply::<T, I>::iter() {
+        let supply = u128::from(supply);
+        if Self::minted(coin).checked_sub(Self::burned(coin)) != Some(supply) {
+          Err("supply didn't equal the amount minted minus the amount burned")?;
+        }
+        if balances.remove(&coin).unwrap_or(0) != supply {
+          Err("supply didn't equal the sum of all balances")?;
+        }
+      }
+      if !balances.is_empty() {
+        Err("balances existed for a coin without a supply")?;
+      }
       Ok(())
     }
 
@@ -127,3 +127,39 @@ fn transfer() {
     assert_eq!(Coins::supply(coin), balance.amount.0);
   })
 }
+
+#[test]
+fn supply_accounting() {
+  new_test_ext().execute_with(|| {
+    let coin = Coin::External(ExternalCoin::Bitcoin);
+    let from = insecure_pair_from_name("random1").public();
+    let to = insecure_pair_from_name("random2").public();
+    let balance = Balance { coin, amount: Amount(10 * 10u64.pow(coin.decimals())) };
+
+    Coins::mint(from, balance).unwrap();
+    Coins::transfer(
+      RawOrigin::Signed(from).into(),
+      to,
+      Balance { coin, amount: Amount(balance.amount.0 / 2) },
+    )
+    .unwrap();
+    Coins::check_supply_accounting().unwrap();
+
+    let burn = Balance { coin, amount: Amount(balance.amount.0 / 4) };
+    Coins::burn(RawOrigin::Signed(to).into(), burn).unwrap();
+    let instruction = OutInstructionWithBalance {
+      instruction: OutInstruction { address: ExternalAddress::new(vec![]).unwrap(), data: None },
+      balance: ExternalBalance { coin: coin.try_into().unwrap(), amount: burn.amount },
+    };
+    Coins::burn_with_instruction(RawOrigin::Signed(from).into(), instruction).unwrap();
+
+    assert_eq!(Coins::minted(coin), u128::from(balance.amount.0));
+    assert_eq!(Coins::burned(coin), u128::from(2 * burn.amount.0));
+    assert_eq!(Coins::supply(coin), balance.amount.0 - (2 * burn.amount.0));
+    Coins::check_supply_accounting().unwrap();
+
+    // If the supply is desynchronized from the balances, the check should fail
+    crate::Supply::<Test, ()>::set(coin, Coins::supply(coin) + 1);
+    assert!(Coins::check_supply_accounting().is_err());
+  })
+}
@@ -0,0 +1,118 @@
+use core::marker::PhantomData;
+
+use sp_runtime::{
+  traits::{SignedExtension, DispatchInfoOf, Dispatchable, Zero},
+  transaction_validity::{
+    TransactionValidity, TransactionValidityError, InvalidTransaction, ValidTransaction,
+  },
+};
+use frame_support::{dispatch::DispatchInfo, traits::IsSubType};
+
+use super::*;
+
+type RuntimeCallOf<T> = <T as frame_system::Config>::RuntimeCall;
+
+/// A request to sell some amount of an external coin for the SRI needed to pay a transaction's
+/// fee.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Encode, Decode, TypeInfo)]
+pub struct FeeConversion {
+  /// The coin to sell for SRI.
+  pub coin: ExternalCoin,
+  /// The maximum amount of `coin` which may be sold, bounding the conversion's slippage.
+  #[codec(compact)]
+  pub max_coin_in: SubstrateAmount,
+}
+
+/// A signed extension which, if requested, sells some of an external coin for the SRI needed to
+/// pay the transaction's fee.
+///
+/// This MUST be placed immediately before `ChargeTransactionPayment` in the runtime's signed
+/// extensions, so the SRI is present once the fee is withdrawn. Only the fee itself is covered,
+/// not any tip, and only the shortfall of the signer's existing SRI balance is converted.
+#[derive(Clone, PartialEq, Eq, Encode, Decode, TypeInfo)]
+#[scale_info(skip_type_params(T))]
+pub struct ConvertFee<T: Config + Send + Sync>(pub Option<FeeConversion>, PhantomData<T>);
+
+impl<T: Config + Send + Sync> ConvertFee<T> {
+  /// Create a new instance of this extension.
+  pub fn new(conversion: Option<FeeConversion>) -> Self {
+    Self(conversion, PhantomData)
+  }
+}
+
+impl<T: Config + Send + Sync> core::fmt::Debug for ConvertFee<T> {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    f.debug_tuple("ConvertFee").field(&self.0).finish()
+  }
+}
+
+impl<T: Config + pallet_transaction_payment::Config + Send + Sync> ConvertFee<T>
+where
+  RuntimeCallOf<T>:
+    Dispatchable<Info = DispatchInfo> + IsSubType<Call<T>> + IsSubType<coins_pallet::Call<T>>,
+  pallet_transaction_payment::BalanceOf<T>: Into<SubstrateAmount>,
+{
+  fn convert(
+    &self,
+    who: &T::AccountId,
+    call: &RuntimeCallOf<T>,
+    info: &DispatchInfoOf<RuntimeCallOf<T>>,
+    len: usize,
+  ) -> Result<(), TransactionValidityError> {
+    let Some(FeeConversion { coin, max_coin_in }) = self.0 else { return Ok(()) };
+
+    // Only allow conversions for calls interacting with the DEX or moving coins
+    let is_dex = <RuntimeCallOf<T> as IsSubType<Call<T>>>::is_sub_type(call).is_some();
+    let is_coins =
+      <RuntimeCallOf<T> as IsSubType<coins_pallet::Call<T>>>::is_sub_type(call).is_some();
+    if !(is_dex || is_coins) {
+      Err(InvalidTransaction::Call)?;
+    }
+
+    let len = u32::try_from(len).map_err(|_| InvalidTransaction::ExhaustsResources)?;
+    let fee = pallet_transaction_payment::Pallet::<T>::compute_fee(len, info, Zero::zero()).into();
+    Pallet::<T>::convert_for_fee(*who, coin, fee, max_coin_in)
+      .map_err(|_| InvalidTransaction::Payment)?;
+    Ok(())
+  }
+}
+
+impl<T: Config + pallet_transaction_payment::Config + Send + Sync> SignedExtension for ConvertFee<T>
+where
+  RuntimeCallOf<T>:
+    Dispatchable<Info = DispatchInfo> + IsSubType<Call<T>> + IsSubType<coins_pallet::Call<T>>,
+  pallet_transaction_payment::BalanceOf<T>: Into<SubstrateAmount>,
+{
+  const IDENTIFIER: &'static str = "ConvertFee";
+  type AccountId = T::AccountId;
+  type Call = RuntimeCallOf<T>;
+  type AdditionalSigned = ();
+  type Pre = ();
+
+  fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+    Ok(())
+  }
+
+  // This mutates state, as `ChargeTransactionPayment::validate` does, as the conversion has to be
+  // present for the fee's withdrawal to be validated
+  fn validate(
+    &self,
+    who: &Self::AccountId,
+    call: &Self::Call,
+    info: &DispatchInfoOf<Self::Call>,
+    len: usize,
+  ) -> TransactionValidity {
+    self.convert(who, call, info, len)?;
+    Ok(ValidTransaction::default())
+  }
+
+  fn pre_dispatch(
+    self,
+    who: &Self::AccountId,
+    call: &Self::Call,
+    info: &DispatchInfoOf<Self::Call>,
+    len: usize,
+  ) -> Result<(), TransactionValidityError> {
+    self.convert(who, call, info, len)
+  }
+}
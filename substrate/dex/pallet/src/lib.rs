@@ -72,6 +72,9 @@ mod benchmarking;
 mod types;
 pub mod weights;
 
+mod fee_conversion;
+pub use fee_conversion::*;
+
 #[cfg(test)]
 mod tests;
 
@@ -822,6 +825,26 @@ pub mod pallet {
       Ok(amount_in)
     }
 
+    /// Sell up to `max_coin_in` of `coin` for the SRI `who` lacks in order to pay `fee`.
+    ///
+    /// If successful, returns the amount of `coin` sold, which is 0 if `who` already has enough
+    /// SRI.
+    pub fn convert_for_fee(
+      who: T::AccountId,
+      coin: ExternalCoin,
+      fee: SubstrateAmount,
+      max_coin_in: SubstrateAmount,
+    ) -> Result<SubstrateAmount, DispatchError> {
+      let shortfall = fee.saturating_sub(Self::get_balance(&who, Coin::Serai));
+      if shortfall == 0 {
+        return Ok(0);
+      }
+
+      let path = BoundedVec::try_from(vec![Coin::from(coin), Coin::Serai])
+        .map_err(|_| Error::<T>::PathError)?;
+      Self::do_swap_tokens_for_exact_tokens(who, path, shortfall, Some(max_coin_in), who)
+    }
+
     /// Transfer an `amount` of `coin_id`.
     fn transfer(
       from: &T::AccountId,
@@ -1066,6 +1066,51 @@ fn swap_tokens_for_exact_tokens_should_not_work_if_too_much_slippage() {
   });
 }
 
+#[test]
+fn can_convert_for_fee() {
+  new_test_ext().execute_with(|| {
+    let user = system_address(b"user1").into();
+    let payer = system_address(b"user2").into();
+    let sri = Coin::native();
+    let eth = ExternalCoin::Ether;
+
+    assert_ok!(Dex::create_pool(eth));
+
+    assert_ok!(CoinsPallet::<Test>::mint(user, Balance { coin: sri, amount: Amount(20000) }));
+    assert_ok!(CoinsPallet::<Test>::mint(user, Balance { coin: eth.into(), amount: Amount(1000) }));
+    assert_ok!(CoinsPallet::<Test>::mint(payer, Balance { coin: eth.into(), amount: Amount(100) }));
+
+    let liquidity1 = 10000;
+    let liquidity2 = 200;
+    assert_ok!(Dex::add_liquidity(
+      RuntimeOrigin::signed(user),
+      eth,
+      liquidity2,
+      liquidity1,
+      1,
+      1,
+      user,
+    ));
+
+    // A conversion exceeding the max amount in should fail
+    let fee = 500;
+    let expected_in = Dex::get_amount_in(fee, liquidity2, liquidity1).unwrap();
+    assert_noop!(
+      Dex::convert_for_fee(payer, eth, fee, expected_in - 1),
+      Error::<Test>::ProvidedMaximumNotSufficientForSwap
+    );
+
+    // Converting should provide exactly the fee
+    assert_eq!(Dex::convert_for_fee(payer, eth, fee, expected_in), Ok(expected_in));
+    assert_eq!(balance(payer, sri), fee);
+    assert_eq!(balance(payer, eth.into()), 100 - expected_in);
+
+    // Now that the payer has the SRI, no further conversion should occur
+    assert_eq!(Dex::convert_for_fee(payer, eth, fee, expected_in), Ok(0));
+    assert_eq!(balance(payer, eth.into()), 100 - expected_in);
+  });
+}
+
 #[test]
 fn swap_exact_tokens_for_tokens_in_multi_hops() {
   new_test_ext().execute_with(|| {
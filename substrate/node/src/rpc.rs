@@ -18,12 +18,16 @@ use jsonrpsee::RpcModule;
 pub use sc_rpc_api::DenyUnsafe;
 use sc_transaction_pool_api::TransactionPool;
 
+type FinalityProofProvider =
+  sc_consensus_grandpa::FinalityProofProvider<crate::service::FullBackend, Block>;
+
 pub struct FullDeps<C, P> {
   pub id: String,
   pub client: Arc<C>,
   pub pool: Arc<P>,
   pub deny_unsafe: DenyUnsafe,
   pub authority_discovery: Option<sc_authority_discovery::Service>,
+  pub finality_proof_provider: Arc<FinalityProofProvider>,
 }
 
 pub fn create_full<
@@ -47,11 +51,26 @@ where
   use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApiServer};
 
   let mut module = RpcModule::new(());
-  let FullDeps { id, client, pool, deny_unsafe, authority_discovery } = deps;
+  let FullDeps { id, client, pool, deny_unsafe, authority_discovery, finality_proof_provider } =
+    deps;
 
   module.merge(System::new(client.clone(), pool, deny_unsafe).into_rpc())?;
   module.merge(TransactionPayment::new(client.clone()).into_rpc())?;
 
+  // Prove the finality of a block, returning the hex-encoded proof
+  // This is the encoding of `sc_consensus_grandpa::FinalityProof`, which contains a justification
+  // for the block (or a descendant of it, with the headers needed to connect them)
+  let mut finality_module = RpcModule::new(finality_proof_provider);
+  finality_module.register_method("grandpa_proveFinality", |params, provider| {
+    let block: u64 = params.one()?;
+    provider.prove_finality(block).map(|proof| proof.map(hex::encode)).map_err(|e| {
+      jsonrpsee::core::Error::to_call_error(std::io::Error::other(format!(
+        "couldn't prove finality: {e:?}"
+      )))
+    })
+  })?;
+  module.merge(finality_module)?;
+
   if let Some(authority_discovery) = authority_discovery {
     let mut authority_discovery_module =
       RpcModule::new((id, client, RwLock::new(authority_discovery)));
@@ -29,7 +29,7 @@ pub type Executor = WasmExecutor<
   ExtendedHostFunctions<SubstrateHostFunctions, frame_benchmarking::benchmarking::HostFunctions>,
 >;
 
-type FullBackend = sc_service::TFullBackend<Block>;
+pub(crate) type FullBackend = sc_service::TFullBackend<Block>;
 pub type FullClient = TFullClient<Block, RuntimeApi, Executor>;
 
 type SelectChain = sc_consensus::LongestChain<FullBackend, Block>;
@@ -319,6 +319,10 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
     let id = config.chain_spec.id().to_string();
     let client = client.clone();
     let pool = transaction_pool.clone();
+    let finality_proof_provider = grandpa::FinalityProofProvider::new_for_service(
+      backend.clone(),
+      Some(grandpa_link.shared_authority_set().clone()),
+    );
 
     Box::new(move |deny_unsafe, _| {
       crate::rpc::create_full(crate::rpc::FullDeps {
@@ -327,6 +331,7 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
         pool: pool.clone(),
         deny_unsafe,
         authority_discovery: authority_discovery.clone(),
+        finality_proof_provider: finality_proof_provider.clone(),
       })
       .map_err(Into::into)
     })
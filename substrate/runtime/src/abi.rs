@@ -9,7 +9,7 @@ use crate::{
   primitives::{PublicKey, SeraiAddress},
   timestamp, coins, dex, genesis_liquidity,
   validator_sets::{self, MembershipProof},
-  in_instructions, signals, babe, grandpa, RuntimeCall,
+  in_instructions, signals, babe, grandpa, utility, RuntimeCall,
 };
 
 impl From<Call> for RuntimeCall {
@@ -126,6 +126,37 @@ impl From<Call> for RuntimeCall {
         serai_abi::validator_sets::Call::claim_deallocation { network, session } => {
           RuntimeCall::ValidatorSets(validator_sets::Call::claim_deallocation { network, session })
         }
+        serai_abi::validator_sets::Call::report_loss {
+          network,
+          session,
+          evidence,
+          losses,
+          signature,
+        } => RuntimeCall::ValidatorSets(validator_sets::Call::report_loss {
+          network,
+          session,
+          evidence,
+          losses: <_>::try_from(
+            losses
+              .into_iter()
+              .map(|(addr, balance)| (PublicKey::from(addr), balance))
+              .collect::<Vec<_>>(),
+          )
+          .unwrap(),
+          signature,
+        }),
+        serai_abi::validator_sets::Call::claim_compensation => {
+          RuntimeCall::ValidatorSets(validator_sets::Call::claim_compensation {})
+        }
+        serai_abi::validator_sets::Call::fund_compensation { amount } => {
+          RuntimeCall::ValidatorSets(validator_sets::Call::fund_compensation { amount })
+        }
+        serai_abi::validator_sets::Call::set_network_address { address } => {
+          RuntimeCall::ValidatorSets(validator_sets::Call::set_network_address { address })
+        }
+        serai_abi::validator_sets::Call::set_attempt_window { network, window } => {
+          RuntimeCall::ValidatorSets(validator_sets::Call::set_attempt_window { network, window })
+        }
       },
       Call::GenesisLiquidity(gl) => match gl {
         serai_abi::genesis_liquidity::Call::remove_coin_liquidity { balance } => {
@@ -196,6 +227,13 @@ impl From<Call> for RuntimeCall {
           })
         }
       },
+      Call::Utility(utility) => match utility {
+        serai_abi::utility::Call::batch_all { calls } => {
+          RuntimeCall::Utility(utility::Call::batch_all {
+            calls: calls.into_iter().map(Into::into).collect(),
+          })
+        }
+      },
     }
   }
 }
@@ -315,6 +353,33 @@ impl TryInto<Call> for RuntimeCall {
         validator_sets::Call::claim_deallocation { network, session } => {
           serai_abi::validator_sets::Call::claim_deallocation { network, session }
         }
+        validator_sets::Call::report_loss { network, session, evidence, losses, signature } => {
+          serai_abi::validator_sets::Call::report_loss {
+            network,
+            session,
+            evidence,
+            losses: <_>::try_from(
+              losses
+                .into_iter()
+                .map(|(addr, balance)| (SeraiAddress::from(addr), balance))
+                .collect::<Vec<_>>(),
+            )
+            .unwrap(),
+            signature,
+          }
+        }
+        validator_sets::Call::claim_compensation {} => {
+          serai_abi::validator_sets::Call::claim_compensation
+        }
+        validator_sets::Call::fund_compensation { amount } => {
+          serai_abi::validator_sets::Call::fund_compensation { amount }
+        }
+        validator_sets::Call::set_network_address { address } => {
+          serai_abi::validator_sets::Call::set_network_address { address }
+        }
+        validator_sets::Call::set_attempt_window { network, window } => {
+          serai_abi::validator_sets::Call::set_attempt_window { network, window }
+        }
         _ => Err(())?,
       }),
       RuntimeCall::InInstructions(call) => Call::InInstructions(match call {
@@ -377,6 +442,19 @@ impl TryInto<Call> for RuntimeCall {
         }
         _ => Err(())?,
       }),
+      RuntimeCall::Utility(call) => Call::Utility(match call {
+        utility::Call::batch_all { calls } => serai_abi::utility::Call::batch_all {
+          calls: calls
+            .into_iter()
+            .map(|call| match call {
+              // Nested batches aren't allowed
+              RuntimeCall::Utility(_) => Err(()),
+              call => call.try_into(),
+            })
+            .collect::<Result<_, _>>()?,
+        },
+        _ => Err(())?,
+      }),
       _ => Err(())?,
     })
   }
@@ -31,6 +31,8 @@ pub use signals_pallet as signals;
 pub use pallet_babe as babe;
 pub use pallet_grandpa as grandpa;
 
+pub use pallet_utility as utility;
+
 pub use genesis_liquidity_pallet as genesis_liquidity;
 pub use emissions_pallet as emissions;
 
@@ -53,9 +55,9 @@ use sp_runtime::{
 
 #[allow(unused_imports)]
 use primitives::{
-  NetworkId, PublicKey, AccountLookup, SubstrateAmount, Coin, EXTERNAL_NETWORKS,
-  MEDIAN_PRICE_WINDOW_LENGTH, HOURS, DAYS, MINUTES, TARGET_BLOCK_TIME, BLOCK_SIZE,
-  FAST_EPOCH_DURATION,
+  NetworkId, ExternalNetworkId, PublicKey, AccountLookup, SubstrateAmount, Coin, ExternalCoin,
+  EXTERNAL_NETWORKS, MEDIAN_PRICE_WINDOW_LENGTH, HOURS, DAYS, MINUTES, TARGET_BLOCK_TIME,
+  BLOCK_SIZE, FAST_EPOCH_DURATION,
 };
 
 use support::{
@@ -67,7 +69,7 @@ use support::{
   parameter_types, construct_runtime,
 };
 
-use validator_sets::MembershipProof;
+use validator_sets::{MembershipProof, primitives::AttemptWindow};
 
 use sp_authority_discovery::AuthorityId as AuthorityDiscoveryId;
 use babe::AuthorityId as BabeId;
@@ -75,6 +77,9 @@ use grandpa::AuthorityId as GrandpaId;
 
 mod abi;
 
+#[cfg(test)]
+mod tests;
+
 /// Nonce of a transaction in the chain, for a given account.
 pub type Nonce = u32;
 
@@ -89,6 +94,8 @@ pub type SignedExtra = (
   system::CheckEra<Runtime>,
   system::CheckNonce<Runtime>,
   system::CheckWeight<Runtime>,
+  // This has to be before ChargeTransactionPayment so any conversion occurs before the fee is paid
+  dex::ConvertFee<Runtime>,
   transaction_payment::ChargeTransactionPayment<Runtime>,
 );
 
@@ -111,10 +118,10 @@ pub mod opaque {
 pub const VERSION: RuntimeVersion = RuntimeVersion {
   spec_name: create_runtime_str!("serai"),
   impl_name: create_runtime_str!("core"),
-  spec_version: 1,
+  spec_version: 3,
   impl_version: 1,
   apis: RUNTIME_API_VERSIONS,
-  transaction_version: 1,
+  transaction_version: 2,
   state_version: 1,
 };
 
@@ -229,6 +236,8 @@ impl validator_sets::Config for Runtime {
   type RuntimeEvent = RuntimeEvent;
 
   type ShouldEndSession = Babe;
+
+  type AttemptWindowOrigin = system::EnsureRoot<PublicKey>;
 }
 
 pub struct IdentityValidatorIdOf;
@@ -319,6 +328,13 @@ impl grandpa::Config for Runtime {
     grandpa::EquivocationReportSystem<Self, ValidatorSets, ValidatorSets, ReportLongevity>;
 }
 
+impl utility::Config for Runtime {
+  type RuntimeEvent = RuntimeEvent;
+  type RuntimeCall = RuntimeCall;
+  type PalletsOrigin = OriginCaller;
+  type WeightInfo = ();
+}
+
 pub type Executive = frame_executive::Executive<
   Runtime,
   Block,
@@ -351,6 +367,8 @@ construct_runtime!(
 
     Babe: babe,
     Grandpa: grandpa,
+
+    Utility: utility,
   }
 );
 
@@ -378,6 +396,8 @@ sp_api::decl_runtime_apis! {
   #[api_version(1)]
   pub trait SeraiRuntimeApi {
     fn validators(network_id: NetworkId) -> Vec<PublicKey>;
+    fn attempt_window(network: ExternalNetworkId) -> AttemptWindow;
+    fn mint_headroom(coin: ExternalCoin) -> SubstrateAmount;
   }
 }
 
@@ -604,6 +624,14 @@ sp_api::impl_runtime_apis! {
           )
       }
     }
+
+    fn attempt_window(network: ExternalNetworkId) -> AttemptWindow {
+      ValidatorSets::attempt_window(network)
+    }
+
+    fn mint_headroom(coin: ExternalCoin) -> SubstrateAmount {
+      ValidatorSets::mint_headroom(coin)
+    }
   }
 
   impl dex::DexApi<Block> for Runtime {
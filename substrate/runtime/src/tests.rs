@@ -0,0 +1,111 @@
+use sp_runtime::{
+  generic::Era,
+  traits::{Dispatchable, SignedExtension},
+  transaction_validity::InvalidTransaction,
+  BuildStorage,
+};
+use frame_support::dispatch::GetDispatchInfo;
+
+use serai_primitives::{system_address, Amount, Balance};
+use coins_pallet::primitives::FEE_ACCOUNT;
+use dex_pallet::FeeConversion;
+
+use super::*;
+
+// The length of the transaction fees are calculated for
+const LEN: usize = 128;
+
+const ETH: ExternalCoin = ExternalCoin::Ether;
+
+// The extensions a transaction is validated with, as a signer would create them
+fn extra(conversion: Option<FeeConversion>) -> SignedExtra {
+  (
+    system::CheckNonZeroSender::new(),
+    system::CheckSpecVersion::new(),
+    system::CheckTxVersion::new(),
+    system::CheckGenesis::new(),
+    system::CheckEra::from(Era::Immortal),
+    system::CheckNonce::from(0),
+    system::CheckWeight::new(),
+    dex::ConvertFee::new(conversion),
+    transaction_payment::ChargeTransactionPayment::from(0),
+  )
+}
+
+// A chain with an ETH pool, where `payer` has ETH yet no SRI
+fn new_test_ext(provider: PublicKey, payer: PublicKey) -> sp_io::TestExternalities {
+  let mut t = system::GenesisConfig::<Runtime>::default().build_storage().unwrap();
+  coins::GenesisConfig::<Runtime> {
+    accounts: vec![(provider, Balance { coin: Coin::Serai, amount: Amount(1 << 40) })],
+    _ignore: Default::default(),
+  }
+  .assimilate_storage(&mut t)
+  .unwrap();
+
+  let mut ext = sp_io::TestExternalities::new(t);
+  ext.execute_with(|| {
+    System::set_block_number(1);
+
+    // Minting external coins requires economic security, so credit them directly
+    coins::Balances::<Runtime>::set(provider, Coin::from(ETH), 1 << 40);
+    coins::Balances::<Runtime>::set(payer, Coin::from(ETH), 1 << 20);
+    coins::Supply::<Runtime>::set(Coin::from(ETH), (1 << 40) + (1 << 20));
+
+    Dex::add_liquidity(RuntimeOrigin::signed(provider), ETH, 1 << 30, 1 << 30, 1, 1, provider)
+      .unwrap();
+  });
+  ext
+}
+
+#[test]
+fn convert_fee() {
+  let provider: PublicKey = system_address(b"provider").into();
+  let payer: PublicKey = system_address(b"payer").into();
+
+  // A coin-moving call, paid for with the payer's ETH
+  let call = RuntimeCall::Coins(coins::Call::transfer {
+    to: provider,
+    balance: Balance { coin: Coin::from(ETH), amount: Amount(1) },
+  });
+  let info = call.get_dispatch_info();
+  let fee = TransactionPayment::compute_fee(u32::try_from(LEN).unwrap(), &info, 0);
+  let conversion = Some(FeeConversion { coin: ETH, max_coin_in: 1 << 20 });
+
+  // Without a conversion, the payer can't pay the fee
+  new_test_ext(provider, payer).execute_with(|| {
+    assert_eq!(
+      extra(None).validate(&payer, &call, &info, LEN),
+      Err(InvalidTransaction::Payment.into())
+    );
+  });
+
+  // A conversion bounded below the amount needed is rejected
+  new_test_ext(provider, payer).execute_with(|| {
+    let conversion = Some(FeeConversion { coin: ETH, max_coin_in: 1 });
+    assert_eq!(
+      extra(conversion).validate(&payer, &call, &info, LEN),
+      Err(InvalidTransaction::Payment.into())
+    );
+  });
+
+  // The conversion occurs before ChargeTransactionPayment, so the transaction validates
+  new_test_ext(provider, payer).execute_with(|| {
+    assert!(extra(conversion).validate(&payer, &call, &info, LEN).is_ok());
+  });
+
+  // And so the fee is paid with the SRI converted to when the transaction is dispatched
+  new_test_ext(provider, payer).execute_with(|| {
+    let (coin_reserve, sri_reserve) = Dex::get_reserves(&Coin::from(ETH), &Coin::Serai).unwrap();
+    let coin_in = Dex::get_amount_in(fee, coin_reserve, sri_reserve).unwrap();
+
+    let pre = extra(conversion).pre_dispatch(&payer, &call, &info, LEN).unwrap();
+    assert_eq!(Coins::balance(payer, Coin::Serai), Amount(0));
+    assert_eq!(Coins::balance(FEE_ACCOUNT.into(), Coin::Serai), Amount(fee));
+    assert_eq!(Coins::balance(payer, Coin::from(ETH)), Amount((1 << 20) - coin_in));
+
+    let post_info = call.clone().dispatch(RuntimeOrigin::signed(payer)).unwrap();
+    SignedExtra::post_dispatch(Some(pre), &info, &post_info, LEN, &Ok(())).unwrap();
+    assert_eq!(Coins::balance(provider, Coin::from(ETH)), Amount((1 << 40) - (1 << 30) + 1));
+    assert_eq!(Coins::balance(payer, Coin::from(ETH)), Amount((1 << 20) - coin_in - 1));
+  });
+}
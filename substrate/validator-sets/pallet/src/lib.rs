@@ -1,5 +1,11 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
 use core::marker::PhantomData;
 
 use scale::{Encode, Decode};
@@ -12,7 +18,7 @@ use sp_session::{ShouldEndSession, GetSessionNumber, GetValidatorCount};
 use sp_runtime::{KeyTypeId, ConsensusEngineId, traits::IsMember};
 use sp_staking::offence::{ReportOffence, Offence, OffenceError};
 
-use frame_system::{pallet_prelude::*, RawOrigin};
+use frame_system::pallet_prelude::*;
 use frame_support::{
   pallet_prelude::*,
   sp_runtime::SaturatedConversion,
@@ -81,6 +87,9 @@ pub mod pallet {
     type RuntimeEvent: IsType<<Self as frame_system::Config>::RuntimeEvent> + From<Event<Self>>;
 
     type ShouldEndSession: ShouldEndSession<BlockNumberFor<Self>>;
+
+    /// The origin which may set the networks' attempt windows.
+    type AttemptWindowOrigin: EnsureOrigin<Self::RuntimeOrigin>;
   }
 
   #[pallet::genesis_config]
@@ -334,6 +343,54 @@ pub mod pallet {
   pub type SessionBeginBlock<T: Config> =
     StorageDoubleMap<_, Identity, NetworkId, Identity, Session, u64, ValueQuery>;
 
+  /// The keys of retired validator sets, kept so evidence of their faults may be verified.
+  ///
+  /// Keys are removed once losses were reported against their set, or `LOSS_REPORT_SESSIONS`
+  /// sessions after their set retired.
+  #[pallet::storage]
+  pub type RetiredKeys<T: Config> =
+    StorageMap<_, Twox64Concat, ExternalValidatorSet, Public, OptionQuery>;
+
+  /// The retired validator sets which have had losses reported against them.
+  #[pallet::storage]
+  pub type ReportedLosses<T: Config> =
+    StorageMap<_, Twox64Concat, ExternalValidatorSet, (), OptionQuery>;
+
+  /// Compensation for proven losses, pending being claimed by the affected account.
+  #[pallet::storage]
+  #[pallet::getter(fn pending_compensation)]
+  pub type PendingCompensation<T: Config> =
+    StorageMap<_, Blake2_128Concat, Public, Amount, OptionQuery>;
+
+  /// The sum of all pending compensation, which is reserved within the compensation pool.
+  #[pallet::storage]
+  pub type OwedCompensation<T: Config> = StorageValue<_, Amount, ValueQuery>;
+
+  /// The network address each validator's coordinator may be reached at, as published by the
+  /// validator.
+  #[pallet::storage]
+  #[pallet::getter(fn network_address)]
+  pub type NetworkAddresses<T: Config> = StorageMap<
+    _,
+    Blake2_128Concat,
+    Public,
+    BoundedVec<u8, ConstU32<MAX_NETWORK_ADDRESS_LEN>>,
+    OptionQuery,
+  >;
+
+  /// The window in which the coordinators re-attempt a network's signing protocols.
+  // Uses Identity for the lookup to avoid a hash of a severely limited fixed key-space.
+  #[pallet::storage]
+  pub type AttemptWindows<T: Config> =
+    StorageMap<_, Identity, ExternalNetworkId, AttemptWindow, OptionQuery>;
+
+  impl<T: Config> Pallet<T> {
+    /// The window in which the coordinators re-attempt a network's signing protocols.
+    pub fn attempt_window(network: ExternalNetworkId) -> AttemptWindow {
+      AttemptWindows::<T>::get(network).unwrap_or(AttemptWindow::DEFAULT)
+    }
+  }
+
   #[pallet::event]
   #[pallet::generate_deposit(pub(super) fn deposit_event)]
   pub enum Event<T: Config> {
@@ -370,6 +427,21 @@ pub mod pallet {
       network: NetworkId,
       session: Session,
     },
+    LossReported {
+      set: ExternalValidatorSet,
+      compensation: Amount,
+    },
+    CompensationClaimed {
+      account: T::AccountId,
+      amount: Amount,
+    },
+    NetworkAddressSet {
+      validator: T::AccountId,
+    },
+    AttemptWindowSet {
+      network: ExternalNetworkId,
+      window: AttemptWindow,
+    },
   }
 
   impl<T: Config> Pallet<T> {
@@ -451,6 +523,10 @@ pub mod pallet {
     NonExistentValidator,
     /// Deallocation would take the stake below what is required.
     DeallocationWouldRemoveEconomicSecurity,
+    /// Compensation to be claimed doesn't exist.
+    NonExistentCompensation,
+    /// The attempt window would have protocols immediately re-attempted.
+    InvalidAttemptWindow,
   }
 
   #[pallet::hooks]
@@ -471,6 +547,9 @@ pub mod pallet {
     fn build(&self) {
       for (id, stake) in self.networks.clone() {
         AllocationPerKeyShare::<T>::set(id, Some(stake));
+        if let NetworkId::External(network) = id {
+          AttemptWindows::<T>::set(network, Some(AttemptWindow::DEFAULT));
+        }
         for participant in self.participants.clone() {
           if Pallet::<T>::set_allocation(id, participant, stake) {
             panic!("participants contained duplicates");
@@ -486,6 +565,13 @@ pub mod pallet {
       system_address(b"ValidatorSets").into()
     }
 
+    /// The account holding the funds available to compensate those affected by proven losses.
+    ///
+    /// This is funded by the allocations of slashed Serai validators, and via `fund_compensation`.
+    pub fn compensation_account() -> T::AccountId {
+      system_address(b"ValidatorSets-compensation").into()
+    }
+
     // is_bft returns if the network is able to survive any single node becoming byzantine.
     fn is_bft(network: NetworkId) -> bool {
       let allocation_per_key_share = AllocationPerKeyShare::<T>::get(network).unwrap().0;
@@ -751,9 +837,14 @@ pub mod pallet {
 
         // This overwrites the prior value as the prior to-report set's stake presumably just
         // unlocked, making their report unenforceable
-        let keys =
-          Keys::<T>::take(ExternalValidatorSet { network: n, session: set.session }).unwrap();
+        let set = ExternalValidatorSet { network: n, session: set.session };
+        let keys = Keys::<T>::take(set).unwrap();
         PendingSlashReport::<T>::set(n, Some(keys.0));
+        RetiredKeys::<T>::set(set, Some(keys.0));
+        // Losses may no longer be reported against the set which retired LOSS_REPORT_SESSIONS ago
+        if let Some(expired) = set.session.0.checked_sub(LOSS_REPORT_SESSIONS) {
+          RetiredKeys::<T>::remove(ExternalValidatorSet { network: n, session: Session(expired) });
+        }
       } else {
         // emit the event for serai network
         Self::deposit_event(Event::SetRetired { set });
@@ -827,8 +918,8 @@ pub mod pallet {
       }
     }
 
-    /// Returns the required stake in terms SRI for a given `Balance`.
-    pub fn required_stake(balance: &ExternalBalance) -> SubstrateAmount {
+    /// Returns the value of a given `Balance` in terms of SRI, per the security oracle.
+    pub fn sri_value(balance: &ExternalBalance) -> SubstrateAmount {
       use dex_pallet::HigherPrecisionBalance;
 
       // This is inclusive to an increase in accuracy
@@ -838,12 +929,17 @@ pub mod pallet {
       let coin_decimals = balance.coin.decimals().max(5);
       let accuracy_increase = HigherPrecisionBalance::from(SubstrateAmount::pow(10, coin_decimals));
 
-      let total_coin_value = u64::try_from(
+      u64::try_from(
         HigherPrecisionBalance::from(balance.amount.0) *
           HigherPrecisionBalance::from(sri_per_coin.0) /
           accuracy_increase,
       )
-      .unwrap_or(u64::MAX);
+      .unwrap_or(u64::MAX)
+    }
+
+    /// Returns the required stake in terms SRI for a given `Balance`.
+    pub fn required_stake(balance: &ExternalBalance) -> SubstrateAmount {
+      let total_coin_value = Self::sri_value(balance);
 
       // required stake formula (COIN_VALUE * 1.5) + margin(20%)
       let required_stake = total_coin_value.saturating_mul(3).saturating_div(2);
@@ -860,6 +956,29 @@ pub mod pallet {
       total_required
     }
 
+    /// Returns the amount of `coin` which may currently be minted without the network's required
+    /// stake exceeding its allocated stake.
+    pub fn mint_headroom(coin: ExternalCoin) -> SubstrateAmount {
+      let staked =
+        Self::total_allocated_stake(NetworkId::from(coin.network())).unwrap_or(Amount(0)).0;
+      let Some(slack) = staked.checked_sub(Self::required_stake_for_network(coin.network())) else {
+        return 0;
+      };
+
+      // The required stake is monotonic with regards to the amount, so binary search for the
+      // largest amount whose required stake fits within the slack
+      let (mut low, mut high) = (0, SubstrateAmount::MAX);
+      while low < high {
+        let mid = low + ((high - low) / 2) + 1;
+        if Self::required_stake(&ExternalBalance { coin, amount: Amount(mid) }) <= slack {
+          low = mid;
+        } else {
+          high = mid - 1;
+        }
+      }
+      low
+    }
+
     pub fn distribute_block_rewards(
       network: NetworkId,
       account: T::AccountId,
@@ -914,14 +1033,51 @@ pub mod pallet {
         allocation.0 += pending.0;
       }
 
-      // burn the allocation from the stake account
-      Coins::<T>::burn(
-        RawOrigin::Signed(Self::account()).into(),
+      // Move the allocation from the stake account to the compensation pool, so it may compensate
+      // those affected by proven losses
+      Coins::<T>::transfer_internal(
+        Self::account(),
+        Self::compensation_account(),
         Balance { coin: Coin::Serai, amount: allocation },
       )
       .unwrap();
     }
 
+    /// Assign compensation for losses to the affected accounts, from the compensation pool.
+    ///
+    /// Losses are valued in SRI and the available funds are distributed pro-rata, with no account
+    /// receiving more than the value of its loss.
+    fn compensate(losses: &[(Public, ExternalBalance)]) -> Amount {
+      let losses = losses
+        .iter()
+        .map(|(account, balance)| (*account, u128::from(Self::sri_value(balance))))
+        .collect::<Vec<_>>();
+      let total_loss = losses.iter().map(|(_, value)| value).sum::<u128>();
+
+      let owed = OwedCompensation::<T>::get();
+      let available =
+        Coins::<T>::balance(Self::compensation_account(), Coin::Serai).0.saturating_sub(owed.0);
+      let distributable = u128::from(available).min(total_loss);
+      if distributable == 0 {
+        return Amount(0);
+      }
+
+      let mut compensation = 0;
+      for (account, loss) in losses {
+        // This is bounded by distributable, which is bounded by available, a u64
+        let amount = u64::try_from((loss * distributable) / total_loss).unwrap();
+        if amount == 0 {
+          continue;
+        }
+        PendingCompensation::<T>::mutate(account, |pending| {
+          *pending = Some(Amount(pending.unwrap_or(Amount(0)).0 + amount));
+        });
+        compensation += amount;
+      }
+      OwedCompensation::<T>::set(Amount(owed.0 + compensation));
+      Amount(compensation)
+    }
+
     /// Disable a Serai validator, preventing them from further authoring blocks.
     ///
     /// Returns true if the validator-to-disable was actually a validator.
@@ -1064,6 +1220,117 @@ pub mod pallet {
       Self::deposit_event(Event::DeallocationClaimed { validator: account, network, session });
       Ok(())
     }
+
+    /// Report the losses caused by a retired validator set, proven faulty by the evidence.
+    ///
+    /// The losses must be attested to by the network's current validator set.
+    #[pallet::call_index(5)]
+    #[pallet::weight(
+      T::DbWeight::get().reads_writes(6, 3).saturating_add(
+        T::DbWeight::get().reads_writes(1, 1).saturating_mul(losses.len().saturated_into())
+      )
+    )]
+    pub fn report_loss(
+      origin: OriginFor<T>,
+      network: ExternalNetworkId,
+      session: Session,
+      evidence: BatchEquivocation,
+      losses: BoundedVec<(Public, ExternalBalance), ConstU32<MAX_LOSS_REPORT_ACCOUNTS>>,
+      signature: Signature,
+    ) -> DispatchResult {
+      ensure_none(origin)?;
+
+      // evidence and signature aren't checked as this is an unsigned transaction, and
+      // validate_unsigned (called by pre_dispatch) checks them
+      let _ = evidence;
+      let _ = signature;
+
+      let set = ExternalValidatorSet { network, session };
+      ReportedLosses::<T>::set(set, Some(()));
+      // The set's key is no longer needed, as losses may only be reported against a set once
+      RetiredKeys::<T>::remove(set);
+
+      let compensation = Self::compensate(&losses);
+      Self::deposit_event(Event::LossReported { set, compensation });
+
+      Ok(())
+    }
+
+    #[pallet::call_index(6)]
+    #[pallet::weight((T::DbWeight::get().reads_writes(4, 4), DispatchClass::Normal))]
+    pub fn claim_compensation(origin: OriginFor<T>) -> DispatchResult {
+      let account = ensure_signed(origin)?;
+      let Some(amount) = PendingCompensation::<T>::take(account) else {
+        Err(Error::<T>::NonExistentCompensation)?
+      };
+      OwedCompensation::<T>::mutate(|owed| owed.0 -= amount.0);
+      Coins::<T>::transfer_internal(
+        Self::compensation_account(),
+        account,
+        Balance { coin: Coin::Serai, amount },
+      )?;
+      Self::deposit_event(Event::CompensationClaimed { account, amount });
+      Ok(())
+    }
+
+    #[pallet::call_index(7)]
+    #[pallet::weight((T::DbWeight::get().reads_writes(2, 2), DispatchClass::Normal))]
+    pub fn fund_compensation(origin: OriginFor<T>, amount: Amount) -> DispatchResult {
+      let account = ensure_signed(origin)?;
+      Coins::<T>::transfer_internal(
+        account,
+        Self::compensation_account(),
+        Balance { coin: Coin::Serai, amount },
+      )?;
+      Ok(())
+    }
+
+    /// Publish the network address this validator's coordinator may be reached at.
+    ///
+    /// An empty address removes the validator's published address.
+    #[pallet::call_index(8)]
+    #[pallet::weight(0)] // TODO
+    pub fn set_network_address(
+      origin: OriginFor<T>,
+      address: BoundedVec<u8, ConstU32<MAX_NETWORK_ADDRESS_LEN>>,
+    ) -> DispatchResult {
+      let validator = ensure_signed(origin)?;
+
+      // Only those with an allocation may publish an address, preventing this from being spammed
+      if !NETWORKS.iter().any(|network| Self::allocation((*network, validator)).is_some()) {
+        Err(Error::<T>::NonExistentValidator)?;
+      }
+
+      if address.is_empty() {
+        NetworkAddresses::<T>::remove(validator);
+      } else {
+        NetworkAddresses::<T>::set(validator, Some(address));
+      }
+      Self::deposit_event(Event::NetworkAddressSet { validator });
+      Ok(())
+    }
+
+    /// Set the window in which the coordinators re-attempt a network's signing protocols.
+    ///
+    /// This is only read by the coordinators when a Tributary is created, so it takes effect
+    /// with the network's next validator set.
+    #[pallet::call_index(9)]
+    #[pallet::weight(T::DbWeight::get().writes(1))]
+    pub fn set_attempt_window(
+      origin: OriginFor<T>,
+      network: ExternalNetworkId,
+      window: AttemptWindow,
+    ) -> DispatchResult {
+      T::AttemptWindowOrigin::ensure_origin(origin)?;
+
+      if window.initial_delay == 0 {
+        Err(Error::<T>::InvalidAttemptWindow)?;
+      }
+
+      AttemptWindows::<T>::set(network, Some(window));
+      Self::deposit_event(Event::AttemptWindowSet { network, window });
+      Ok(())
+    }
   }
 
   #[pallet::validate_unsigned]
@@ -1167,9 +1434,60 @@ pub mod pallet {
             .propagate(true)
             .build()
         }
-        Call::allocate { .. } | Call::deallocate { .. } | Call::claim_deallocation { .. } => {
-          Err(InvalidTransaction::Call)?
+        Call::report_loss { network, session, ref evidence, ref losses, ref signature } => {
+          let network = *network;
+          let set = ExternalValidatorSet { network, session: *session };
+
+          // Each set may only have losses reported against it once
+          if ReportedLosses::<T>::contains_key(set) {
+            Err(InvalidTransaction::Stale)?;
+          }
+
+          // Losses may only be reported against retired sets, as they're attested to by the
+          // current set
+          let Some(faulty_key) = RetiredKeys::<T>::get(set) else {
+            Err(InvalidTransaction::Custom(5))?
+          };
+          let Some(current) = Self::session(NetworkId::from(network)) else {
+            Err(InvalidTransaction::Custom(1))?
+          };
+          let current = ExternalValidatorSet { network, session: current };
+          let Some(current_key) = Keys::<T>::get(current) else { Err(InvalidTransaction::Future)? };
+
+          // The retired set must be proven faulty, by having signed conflicting batches
+          if evidence.conflict().map(|(batch_network, _)| batch_network) != Some(network) {
+            Err(InvalidTransaction::BadProof)?;
+          }
+          for (batch, batch_signature) in [&evidence.first, &evidence.second] {
+            if !faulty_key.verify(&batch_message(batch), batch_signature) {
+              Err(InvalidTransaction::BadProof)?;
+            }
+          }
+
+          // The losses must be of coins from this network
+          if losses.iter().any(|(_, balance)| balance.coin.network() != network) {
+            Err(InvalidTransaction::Custom(4))?;
+          }
+
+          // The losses must be attested to by the current set's key, which requires a threshold
+          // of the current set
+          if !current_key.0.verify(&report_loss_message(&set, losses), signature) {
+            Err(InvalidTransaction::BadProof)?;
+          }
+
+          ValidTransaction::with_tag_prefix("ValidatorSets")
+            .and_provides((2, set))
+            .longevity(MAX_KEY_SHARES_PER_SET.into())
+            .propagate(true)
+            .build()
         }
+        Call::allocate { .. } |
+        Call::deallocate { .. } |
+        Call::claim_deallocation { .. } |
+        Call::claim_compensation { .. } |
+        Call::fund_compensation { .. } |
+        Call::set_network_address { .. } |
+        Call::set_attempt_window { .. } => Err(InvalidTransaction::Call)?,
         Call::__Ignore(_, _) => unreachable!(),
       }
     }
@@ -0,0 +1,257 @@
+use crate::{mock::*, primitives::*, *};
+
+use frame_support::{assert_noop, assert_ok, dispatch::GetDispatchInfo, traits::ValidateUnsigned};
+use sp_core::{sr25519::Pair, Pair as PairTrait};
+use sp_runtime::transaction_validity::{
+  TransactionSource, TransactionValidityError, InvalidTransaction,
+};
+
+const NETWORK: ExternalNetworkId = ExternalNetworkId::Bitcoin;
+const RETIRED: ExternalValidatorSet =
+  ExternalValidatorSet { network: NETWORK, session: Session(0) };
+
+fn btc(amount: u64) -> ExternalBalance {
+  ExternalBalance { coin: ExternalCoin::Bitcoin, amount: Amount(amount) }
+}
+
+fn sri(amount: u64) -> Balance {
+  Balance { coin: Coin::Serai, amount: Amount(amount) }
+}
+
+// Set the retired set's key, and the key of the current set which succeeded it
+fn set_keys(retired: &Pair, current: &Pair) {
+  RetiredKeys::<Test>::set(RETIRED, Some(retired.public()));
+  CurrentSession::<Test>::set(NetworkId::from(NETWORK), Some(Session(1)));
+  Keys::<Test>::set(
+    ExternalValidatorSet { network: NETWORK, session: Session(1) },
+    Some(KeyPair(current.public(), vec![].try_into().unwrap())),
+  );
+  // Value BTC 1:1 with SRI
+  dex::SecurityOracleValue::<Test>::set(ExternalCoin::Bitcoin, Some(Amount(10u64.pow(8))));
+}
+
+// Two distinct batches with the same ID, as signed by the specified key
+fn equivocation(key: &Pair, id: u32) -> BatchEquivocation {
+  let batch = |block: u8| {
+    let batch = (NETWORK, id, [block; 32], Vec::<u8>::new()).encode();
+    let signature = key.sign(&batch_message(&batch));
+    (batch.try_into().unwrap(), signature)
+  };
+  BatchEquivocation { first: batch(0), second: batch(1) }
+}
+
+fn report_loss(
+  evidence: BatchEquivocation,
+  losses: Vec<(Public, ExternalBalance)>,
+  attester: &Pair,
+) -> Call<Test> {
+  let signature = attester.sign(&report_loss_message(&RETIRED, &losses));
+  Call::report_loss {
+    network: NETWORK,
+    session: RETIRED.session,
+    evidence,
+    losses: losses.try_into().unwrap(),
+    signature,
+  }
+}
+
+fn validate(call: &Call<Test>) -> Result<(), InvalidTransaction> {
+  ValidatorSets::validate_unsigned(TransactionSource::External, call).map(|_| ()).map_err(|e| {
+    let TransactionValidityError::Invalid(e) = e else { panic!("unknown validity error: {e:?}") };
+    e
+  })
+}
+
+#[test]
+fn report_loss_evidence() {
+  new_test_ext(vec![]).execute_with(|| {
+    let retired = insecure_pair_from_name("retired");
+    let current = insecure_pair_from_name("current");
+    set_keys(&retired, &current);
+
+    let affected = insecure_pair_from_name("affected").public();
+    let losses = vec![(affected, btc(100))];
+
+    // Proven faults attested to by the current set are accepted
+    assert_ok!(validate(&report_loss(equivocation(&retired, 0), losses.clone(), &current)));
+
+    // The retired set can't attest to losses itself
+    assert_eq!(
+      validate(&report_loss(equivocation(&retired, 0), losses.clone(), &retired)),
+      Err(InvalidTransaction::BadProof)
+    );
+
+    // The evidence must be signed by the retired set
+    assert_eq!(
+      validate(&report_loss(equivocation(&current, 0), losses.clone(), &current)),
+      Err(InvalidTransaction::BadProof)
+    );
+
+    // The evidence must be for conflicting batches
+    let mut identical = equivocation(&retired, 0);
+    identical.second = identical.first.clone();
+    assert_eq!(
+      validate(&report_loss(identical, losses.clone(), &current)),
+      Err(InvalidTransaction::BadProof)
+    );
+    let mut distinct_ids = equivocation(&retired, 0);
+    distinct_ids.second = equivocation(&retired, 1).second;
+    assert_eq!(
+      validate(&report_loss(distinct_ids, losses.clone(), &current)),
+      Err(InvalidTransaction::BadProof)
+    );
+
+    // The losses must be of the network's coins
+    assert_eq!(
+      validate(&report_loss(
+        equivocation(&retired, 0),
+        vec![(affected, ExternalBalance { coin: ExternalCoin::Ether, amount: Amount(100) })],
+        &current
+      )),
+      Err(InvalidTransaction::Custom(4))
+    );
+
+    // Sets which haven't retired can't have losses reported against them
+    RetiredKeys::<Test>::remove(RETIRED);
+    assert_eq!(
+      validate(&report_loss(equivocation(&retired, 0), losses, &current)),
+      Err(InvalidTransaction::Custom(5))
+    );
+  });
+}
+
+#[test]
+fn compensation() {
+  let funder = insecure_pair_from_name("funder").public();
+  new_test_ext(vec![(funder, sri(1000))]).execute_with(|| {
+    let retired = insecure_pair_from_name("retired");
+    let current = insecure_pair_from_name("current");
+    set_keys(&retired, &current);
+
+    // The pool may be explicitly funded
+    assert_eq!(Coins::balance(ValidatorSets::compensation_account(), Coin::Serai), Amount(0));
+    assert_ok!(ValidatorSets::fund_compensation(RuntimeOrigin::signed(funder), Amount(200)));
+    assert_eq!(Coins::balance(ValidatorSets::compensation_account(), Coin::Serai), Amount(200));
+
+    // The available funds are distributed pro-rata
+    let alice = insecure_pair_from_name("alice").public();
+    let bob = insecure_pair_from_name("bob").public();
+    let call =
+      report_loss(equivocation(&retired, 0), vec![(alice, btc(100)), (bob, btc(300))], &current);
+    assert_ok!(validate(&call));
+    let Call::report_loss { network, session, evidence, losses, signature } = call.clone() else {
+      unreachable!()
+    };
+    assert_ok!(ValidatorSets::report_loss(
+      RuntimeOrigin::none(),
+      network,
+      session,
+      evidence,
+      losses,
+      signature
+    ));
+    assert_eq!(ValidatorSets::pending_compensation(alice), Some(Amount(50)));
+    assert_eq!(ValidatorSets::pending_compensation(bob), Some(Amount(150)));
+
+    // Losses may only be reported against a set once, so its key is no longer retained
+    assert_eq!(validate(&call), Err(InvalidTransaction::Stale));
+    assert!(RetiredKeys::<Test>::get(RETIRED).is_none());
+
+    // Compensation may be claimed once
+    assert_ok!(ValidatorSets::claim_compensation(RuntimeOrigin::signed(alice)));
+    assert_eq!(Coins::balance(alice, Coin::Serai), Amount(50));
+    assert_noop!(
+      ValidatorSets::claim_compensation(RuntimeOrigin::signed(alice)),
+      Error::<Test>::NonExistentCompensation
+    );
+    assert_eq!(Coins::balance(ValidatorSets::compensation_account(), Coin::Serai), Amount(150));
+  });
+}
+
+#[test]
+fn slashes_fund_compensation() {
+  new_test_ext(vec![(system_address(b"ValidatorSets").into(), sri(1000))]).execute_with(|| {
+    let validator = insecure_pair_from_name("validator").public();
+    CurrentSession::<Test>::set(NetworkId::Serai, Some(Session(0)));
+    Allocations::<Test>::set((NetworkId::Serai, validator), Some(Amount(600)));
+
+    let offence = GrandpaEquivocationOffence {
+      time_slot: pallet_grandpa::TimeSlot { set_id: 0, round: 0 },
+      session_index: 0,
+      validator_set_count: 1,
+      offender: validator,
+    };
+    assert_ok!(<ValidatorSets as ReportOffence<_, _, _>>::report_offence(vec![], offence));
+
+    // The slashed allocation should've been moved to the compensation pool
+    assert_eq!(ValidatorSets::allocation((NetworkId::Serai, validator)), None);
+    assert_eq!(Coins::balance(ValidatorSets::compensation_account(), Coin::Serai), Amount(600));
+    assert_eq!(Coins::balance(system_address(b"ValidatorSets").into(), Coin::Serai), Amount(400));
+  });
+}
+
+#[test]
+fn retired_keys_are_pruned() {
+  new_test_ext(vec![]).execute_with(|| {
+    let network = NetworkId::from(NETWORK);
+    Participants::<Test>::set(network, Some(vec![].try_into().unwrap()));
+    let key = insecure_pair_from_name("key").public();
+    for session in 0 ..= LOSS_REPORT_SESSIONS {
+      Keys::<Test>::set(
+        ExternalValidatorSet { network: NETWORK, session: Session(session) },
+        Some(KeyPair(key, vec![].try_into().unwrap())),
+      );
+      ValidatorSets::retire_set(ValidatorSet { network, session: Session(session) });
+    }
+
+    // The first set's key should've been pruned once LOSS_REPORT_SESSIONS further sets retired
+    assert!(RetiredKeys::<Test>::get(RETIRED).is_none());
+    for session in 1 ..= LOSS_REPORT_SESSIONS {
+      let set = ExternalValidatorSet { network: NETWORK, session: Session(session) };
+      assert_eq!(RetiredKeys::<Test>::get(set), Some(key));
+    }
+  });
+}
+
+#[test]
+fn compensation_calls_are_normal() {
+  let claim = Call::<Test>::claim_compensation {}.get_dispatch_info();
+  assert_eq!(claim.class, DispatchClass::Normal);
+  let fund = Call::<Test>::fund_compensation { amount: Amount(1) }.get_dispatch_info();
+  assert_eq!(fund.class, DispatchClass::Normal);
+}
+
+#[test]
+fn set_attempt_window() {
+  new_test_ext(vec![]).execute_with(|| {
+    System::set_block_number(1);
+    let window = AttemptWindow {
+      initial_delay: 60,
+      spacing: 30,
+      attempts_before_escalation: 2,
+      max_escalations: 4,
+    };
+
+    // Only the configured origin may set the attempt window
+    let signer = insecure_pair_from_name("signer").public();
+    assert_noop!(
+      ValidatorSets::set_attempt_window(RuntimeOrigin::signed(signer), NETWORK, window),
+      sp_runtime::DispatchError::BadOrigin
+    );
+
+    // Windows which would have protocols immediately re-attempted are rejected
+    assert_noop!(
+      ValidatorSets::set_attempt_window(
+        RuntimeOrigin::root(),
+        NETWORK,
+        AttemptWindow { initial_delay: 0, ..window }
+      ),
+      Error::<Test>::InvalidAttemptWindow
+    );
+
+    assert_eq!(ValidatorSets::attempt_window(NETWORK), AttemptWindow::DEFAULT);
+    assert_ok!(ValidatorSets::set_attempt_window(RuntimeOrigin::root(), NETWORK, window));
+    assert_eq!(ValidatorSets::attempt_window(NETWORK), window);
+    System::assert_last_event(Event::AttemptWindowSet { network: NETWORK, window }.into());
+  });
+}
@@ -13,16 +13,29 @@ use borsh::{BorshSerialize, BorshDeserialize};
 #[cfg(feature = "serde")]
 use serde::{Serialize, Deserialize};
 
-use sp_core::{ConstU32, sr25519::Public, bounded::BoundedVec};
+use sp_core::{
+  ConstU32,
+  sr25519::{Public, Signature},
+  bounded::BoundedVec,
+};
 #[cfg(not(feature = "std"))]
 use sp_std::vec::Vec;
 
-use serai_primitives::{ExternalNetworkId, NetworkId};
+use serai_primitives::{ExternalNetworkId, NetworkId, ExternalBalance};
 
 /// The maximum amount of key shares per set.
 pub const MAX_KEY_SHARES_PER_SET: u32 = 150;
 // Support keys up to 96 bytes (BLS12-381 G2).
 pub const MAX_KEY_LEN: u32 = 96;
+/// The maximum amount of affected accounts a single loss report may compensate.
+pub const MAX_LOSS_REPORT_ACCOUNTS: u32 = 256;
+/// The amount of sessions after a validator set retires during which losses may be reported
+/// against it.
+pub const LOSS_REPORT_SESSIONS: u32 = 2;
+/// The maximum length of a SCALE-encoded batch included within a `BatchEquivocation`.
+pub const MAX_EQUIVOCATED_BATCH_LEN: u32 = 25_000;
+/// The maximum length of the network address a validator may publish for their coordinator.
+pub const MAX_NETWORK_ADDRESS_LEN: u32 = 128;
 
 /// The type used to identify a specific session of validators.
 #[derive(
@@ -106,6 +119,45 @@ impl Zeroize for KeyPair {
   }
 }
 
+/// The window in which the coordinators re-attempt a network's signing protocols.
+///
+/// This is defined on-chain, and read by the coordinators when a set's Tributary is created, so
+/// it may be tuned without all coordinators having to upgrade in coordination.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Encode, Decode, TypeInfo, MaxEncodedLen)]
+#[cfg_attr(feature = "borsh", derive(BorshSerialize, BorshDeserialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AttemptWindow {
+  /// The delay, in seconds, before the first re-attempt.
+  pub initial_delay: u32,
+  /// The delay, in seconds, added to the delay every time it escalates.
+  pub spacing: u32,
+  /// The amount of attempts made before the delay escalates.
+  pub attempts_before_escalation: u32,
+  /// The maximum amount of times the delay may escalate.
+  pub max_escalations: u32,
+}
+
+impl AttemptWindow {
+  /// The window networks are initialized with at genesis.
+  ///
+  /// This is 5 minutes for attempts 0 ..= 2, 10 minutes for attempts 3 ..= 5, 15 minutes for
+  /// attempts > 5. This assumes no protocol will take longer than 15 minutes, yet grows the time
+  /// in case there are network bandwidth issues. It's also the window used by Tributaries created
+  /// before attempt windows were defined on-chain.
+  pub const DEFAULT: AttemptWindow = AttemptWindow {
+    initial_delay: 5 * 60,
+    spacing: 5 * 60,
+    attempts_before_escalation: 3,
+    max_escalations: 2,
+  };
+
+  /// The delay, in seconds, before re-attempting a protocol currently on the specified attempt.
+  pub fn delay(&self, attempt: u32) -> u32 {
+    let escalations = (attempt / self.attempts_before_escalation.max(1)).min(self.max_escalations);
+    self.initial_delay.saturating_add(self.spacing.saturating_mul(escalations))
+  }
+}
+
 /// The MuSig context for a validator set.
 pub fn musig_context(set: ValidatorSet) -> Vec<u8> {
   [b"ValidatorSets-musig_key".as_ref(), &set.encode()].concat()
@@ -138,6 +190,51 @@ pub fn report_slashes_message(set: &ExternalValidatorSet, slashes: &[(Public, u3
   (b"ValidatorSets-report_slashes", set, slashes).encode()
 }
 
+/// The message a validator set's key signs to attest to a SCALE-encoded batch.
+///
+/// This matches `in_instructions_primitives::batch_message`.
+pub fn batch_message(batch: &[u8]) -> Vec<u8> {
+  [b"InInstructions-batch".as_ref(), batch].concat()
+}
+
+/// Evidence a validator set's key signed two distinct batches with the same ID.
+///
+/// A set only signs one batch per ID, as each batch is executed on-chain to mint the coins the set
+/// received. Signing a conflicting batch proves the set faulty, as coins may have been minted for
+/// coins which were never received.
+#[derive(Clone, PartialEq, Eq, Debug, Encode, Decode, TypeInfo)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(all(feature = "std", feature = "serde"), derive(Deserialize))]
+pub struct BatchEquivocation {
+  /// The first SCALE-encoded batch, and the set's signature for it.
+  pub first: (BoundedVec<u8, ConstU32<MAX_EQUIVOCATED_BATCH_LEN>>, Signature),
+  /// The second SCALE-encoded batch, and the set's signature for it.
+  pub second: (BoundedVec<u8, ConstU32<MAX_EQUIVOCATED_BATCH_LEN>>, Signature),
+}
+
+impl BatchEquivocation {
+  /// The network and ID of the batches, if they're distinct batches with the same ID.
+  ///
+  /// This doesn't verify the signatures.
+  pub fn conflict(&self) -> Option<(ExternalNetworkId, u32)> {
+    // Batches are encoded with their network and ID as their prefix
+    let header = |batch: &[u8]| <(ExternalNetworkId, u32)>::decode(&mut &*batch).ok();
+    let first = header(&self.first.0)?;
+    if (first != header(&self.second.0)?) || (self.first.0 == self.second.0) {
+      None?;
+    }
+    Some(first)
+  }
+}
+
+/// The message for the report_loss signature.
+pub fn report_loss_message(
+  set: &ExternalValidatorSet,
+  losses: &[(Public, ExternalBalance)],
+) -> Vec<u8> {
+  (b"ValidatorSets-report_loss", set, losses).encode()
+}
+
 /// For a set of validators whose key shares may exceed the maximum, reduce until they equal the
 /// maximum.
 ///
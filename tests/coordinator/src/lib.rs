@@ -3,6 +3,7 @@
 use std::{
   sync::{OnceLock, Arc},
   time::Duration,
+  collections::HashMap,
 };
 
 use tokio::{
@@ -115,7 +116,7 @@ pub struct Processor {
   handles: Handles,
 
   msgs: mpsc::UnboundedReceiver<messages::CoordinatorMessage>,
-  queue_for_sending: MessageQueue,
+  queue_for_sending: Arc<MessageQueue>,
   abort_handle: Option<Arc<AbortHandle>>,
 
   substrate_key: Arc<AsyncMutex<Option<Zeroizing<<Ristretto as Ciphersuite>::F>>>>,
@@ -136,6 +137,7 @@ impl Processor {
     ops: &DockerOperations,
     handles: Handles,
     processor_key: <Ristretto as Ciphersuite>::F,
+    coordinator_key: <Ristretto as Ciphersuite>::G,
   ) -> Processor {
     let message_queue_rpc = ops.handle(&handles.message_queue).host_port(2287).unwrap();
     let message_queue_rpc = format!("{}:{}", message_queue_rpc.0, message_queue_rpc.1);
@@ -157,12 +159,14 @@ impl Processor {
     // Assume it is and continue, so if it's a few seconds late, it's still within tolerance
 
     // Create the queue
+    let coordinator_keys = HashMap::from([(Service::Coordinator, coordinator_key)]);
     let mut queue = (
       0,
       Arc::new(MessageQueue::new(
         Service::Processor(network),
-        message_queue_rpc.clone(),
+        message_queue_rpc,
         Zeroizing::new(processor_key),
+        coordinator_keys,
       )),
     );
 
@@ -175,11 +179,8 @@ impl Processor {
       serai_rpc,
       handles,
 
-      queue_for_sending: MessageQueue::new(
-        Service::Processor(network),
-        message_queue_rpc,
-        Zeroizing::new(processor_key),
-      ),
+      // Share the queue, as messages are numbered per sender by the order they were queued in
+      queue_for_sending: queue.1.clone(),
       msgs: msg_recv,
       abort_handle: None,
 
@@ -240,6 +240,7 @@ pub async fn batch(
           },
           block: last_serai_block,
           burns: vec![],
+          burn_extrinsics: vec![],
           batches: vec![batch.batch.id],
         }
       )
@@ -64,7 +64,15 @@ pub(crate) async fn new_test(test_body: impl TestBody, fast_epoch: bool) {
     let (processor_key, message_queue_keys, message_queue_composition) =
       serai_message_queue_tests::instance();
 
-    let coordinator_composition = coordinator_instance(name, processor_key);
+    let mut coordinator_composition = coordinator_instance(name, processor_key);
+    // The coordinator seals messages to, and verifies messages against, the processor's key
+    coordinator_composition.modify_env(
+      "BITCOIN_KEY",
+      hex::encode(
+        (Ristretto::generator() * message_queue_keys[&ExternalNetworkId::Bitcoin]).to_bytes(),
+      ),
+    );
+    let coordinator_key = Ristretto::generator() * processor_key;
 
     // Give every item in this stack a unique ID
     // Uses a Mutex as we can't generate a 8-byte random ID without hitting hostname length limits
@@ -112,6 +120,7 @@ pub(crate) async fn new_test(test_body: impl TestBody, fast_epoch: bool) {
         message_queue: handles.remove("message_queue").unwrap(),
       },
       processor_key,
+      coordinator_key,
     ));
     coordinator_compositions.push(compositions.pop().unwrap());
     for composition in compositions {
@@ -121,7 +130,7 @@ pub(crate) async fn new_test(test_body: impl TestBody, fast_epoch: bool) {
 
   struct Context {
     pending_coordinator_compositions: Mutex<Vec<TestBodySpecification>>,
-    handles_and_keys: Vec<(Handles, <Ristretto as Ciphersuite>::F)>,
+    handles_and_keys: Vec<(Handles, <Ristretto as Ciphersuite>::F, <Ristretto as Ciphersuite>::G)>,
     test_body: Box<dyn TestBody>,
   }
   static CONTEXT: OnceLock<Mutex<Option<Context>>> = OnceLock::new();
@@ -194,7 +203,7 @@ pub(crate) async fn new_test(test_body: impl TestBody, fast_epoch: bool) {
 
       // Connect to the Message Queues as the processor
       let mut processors: Vec<Processor> = vec![];
-      for (i, (handles, key)) in coordinators.iter().enumerate() {
+      for (i, (handles, key, coordinator_key)) in coordinators.iter().enumerate() {
         processors.push(
           Processor::new(
             i.try_into().unwrap(),
@@ -202,6 +211,7 @@ pub(crate) async fn new_test(test_body: impl TestBody, fast_epoch: bool) {
             &outer_ops,
             handles.clone(),
             *key,
+            *coordinator_key,
           )
           .await,
         );
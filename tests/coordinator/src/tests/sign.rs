@@ -252,15 +252,13 @@ async fn sign_test() {
           data: None,
         },
       };
-      serai
-        .publish(&serai.sign(
-          &serai_pair,
-          SeraiCoins::burn_with_instruction(out_instruction.clone()),
-          0,
-          Default::default(),
-        ))
-        .await
-        .unwrap();
+      let burn = serai.sign(
+        &serai_pair,
+        SeraiCoins::burn_with_instruction(out_instruction.clone()),
+        0,
+        Default::default(),
+      );
+      serai.publish(&burn).await.unwrap();
 
       // TODO: We *really* need a helper for this pattern
       let mut last_serai_block = block_included_in;
@@ -320,6 +318,7 @@ async fn sign_test() {
               },
               block: last_serai_block.number(),
               burns: vec![out_instruction.clone()],
+              burn_extrinsics: vec![burn.hash()],
               batches: vec![],
             }
           )
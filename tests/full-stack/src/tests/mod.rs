@@ -12,7 +12,7 @@ use dockertest::{
 
 use serai_docker_tests::fresh_logs_folder;
 use serai_processor_tests::{network_instance, processor_instance};
-use serai_message_queue_tests::instance as message_queue_instance;
+use serai_message_queue_tests::{instance as message_queue_instance, public_key_hex};
 use serai_coordinator_tests::{coordinator_instance, serai_composition};
 
 use crate::*;
@@ -63,7 +63,8 @@ pub(crate) async fn new_test(test_body: impl TestBody) {
       message_queue_keys[&ExternalNetworkId::Bitcoin],
     );
     assert_eq!(bitcoin_processor_composition.len(), 1);
-    let bitcoin_processor_composition = bitcoin_processor_composition.swap_remove(0);
+    let mut bitcoin_processor_composition = bitcoin_processor_composition.swap_remove(0);
+    bitcoin_processor_composition.modify_env("COORDINATOR_KEY", public_key_hex(coord_key));
 
     let (monero_composition, monero_port) = network_instance(ExternalNetworkId::Monero);
     let mut monero_processor_composition = processor_instance(
@@ -72,9 +73,14 @@ pub(crate) async fn new_test(test_body: impl TestBody) {
       message_queue_keys[&ExternalNetworkId::Monero],
     );
     assert_eq!(monero_processor_composition.len(), 1);
-    let monero_processor_composition = monero_processor_composition.swap_remove(0);
-
-    let coordinator_composition = coordinator_instance(name, coord_key);
+    let mut monero_processor_composition = monero_processor_composition.swap_remove(0);
+    monero_processor_composition.modify_env("COORDINATOR_KEY", public_key_hex(coord_key));
+
+    let mut coordinator_composition = coordinator_instance(name, coord_key);
+    coordinator_composition
+      .modify_env("BITCOIN_KEY", public_key_hex(message_queue_keys[&ExternalNetworkId::Bitcoin]));
+    coordinator_composition
+      .modify_env("MONERO_KEY", public_key_hex(message_queue_keys[&ExternalNetworkId::Monero]));
     let serai_composition = serai_composition(name, false);
 
     // Give every item in this stack a unique ID
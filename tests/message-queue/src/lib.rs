@@ -14,6 +14,12 @@ use dockertest::{
 };
 
 pub type MessageQueuePrivateKey = <Ristretto as Ciphersuite>::F;
+
+/// The hex-encoded public key for a message-queue private key, as expected in the environment.
+pub fn public_key_hex(key: MessageQueuePrivateKey) -> String {
+  hex::encode((Ristretto::generator() * key).to_bytes())
+}
+
 pub fn instance() -> (
   MessageQueuePrivateKey,
   HashMap<ExternalNetworkId, MessageQueuePrivateKey>,
@@ -80,9 +86,20 @@ fn basic_functionality() {
       let rpc = ops.handle("serai-dev-message-queue").host_port(2287).unwrap();
       let rpc = rpc.0.to_string() + ":" + &rpc.1.to_string();
 
+      // Messages are sealed to, and verified against, the keys of the other services
+      let coord_pub_key = Ristretto::generator() * coord_key;
+      let processor_pub_keys = priv_keys
+        .iter()
+        .map(|(network, key)| (Service::Processor(*network), Ristretto::generator() * *key))
+        .collect::<HashMap<_, _>>();
+
       // Queue some messages
-      let coordinator =
-        MessageQueue::new(Service::Coordinator, rpc.clone(), Zeroizing::new(coord_key));
+      let coordinator = MessageQueue::new(
+        Service::Coordinator,
+        rpc.clone(),
+        Zeroizing::new(coord_key),
+        processor_pub_keys,
+      );
       coordinator
         .queue(
           Metadata {
@@ -113,6 +130,7 @@ fn basic_functionality() {
         Service::Processor(ExternalNetworkId::Bitcoin),
         rpc.clone(),
         Zeroizing::new(priv_keys[&ExternalNetworkId::Bitcoin]),
+        HashMap::from([(Service::Coordinator, coord_pub_key)]),
       );
       let msg = bitcoin.next(Service::Coordinator).await;
       assert_eq!(msg.from, Service::Coordinator);
@@ -154,6 +172,7 @@ fn basic_functionality() {
         Service::Processor(ExternalNetworkId::Monero),
         rpc,
         Zeroizing::new(priv_keys[&ExternalNetworkId::Monero]),
+        HashMap::from([(Service::Coordinator, coord_pub_key)]),
       );
       assert_eq!(monero.next(Service::Coordinator).await.id, 0);
       monero.ack(Service::Coordinator, 0).await;
@@ -1,11 +1,17 @@
 #![allow(clippy::needless_pass_by_ref_mut)] // False positives
 
-use std::sync::{OnceLock, Mutex};
+use std::{
+  sync::{OnceLock, Mutex},
+  collections::HashMap,
+};
 
 use zeroize::Zeroizing;
 use rand_core::{RngCore, OsRng};
 
-use ciphersuite::{group::ff::PrimeField, Ciphersuite, Ristretto};
+use ciphersuite::{
+  group::{ff::PrimeField, GroupEncoding},
+  Ciphersuite, Ristretto,
+};
 
 use serai_client::primitives::ExternalNetworkId;
 use messages::{ProcessorMessage, CoordinatorMessage};
@@ -81,7 +87,12 @@ pub type Handles = (String, String, String, String);
 pub fn processor_stack(
   network: ExternalNetworkId,
   network_hostname_override: Option<String>,
-) -> (Handles, <Ristretto as Ciphersuite>::F, Vec<TestBodySpecification>) {
+) -> (
+  Handles,
+  <Ristretto as Ciphersuite>::F,
+  <Ristretto as Ciphersuite>::G,
+  Vec<TestBodySpecification>,
+) {
   let (network_composition, network_rpc_port) = network_instance(network);
 
   let (coord_key, message_queue_keys, message_queue_composition) =
@@ -89,6 +100,10 @@ pub fn processor_stack(
 
   let mut processor_compositions =
     processor_instance(network, network_rpc_port, message_queue_keys[&network]);
+  // The processor seals messages to, and verifies messages against, the coordinator's key
+  processor_compositions[0]
+    .modify_env("COORDINATOR_KEY", hex::encode((Ristretto::generator() * coord_key).to_bytes()));
+  let processor_key = Ristretto::generator() * message_queue_keys[&network];
 
   // Give every item in this stack a unique ID
   // Uses a Mutex as we can't generate a 8-byte random ID without hitting hostname length limits
@@ -154,6 +169,7 @@ pub fn processor_stack(
       handles.get(3).cloned().unwrap_or(String::new()),
     ),
     coord_key,
+    processor_key,
     compositions,
   )
 }
@@ -179,6 +195,7 @@ impl Coordinator {
     ops: &DockerOperations,
     handles: Handles,
     coord_key: <Ristretto as Ciphersuite>::F,
+    processor_key: <Ristretto as Ciphersuite>::G,
   ) -> Coordinator {
     let rpc = ops.handle(&handles.1).host_port(2287).unwrap();
     let rpc = rpc.0.to_string() + ":" + &rpc.1.to_string();
@@ -193,7 +210,12 @@ impl Coordinator {
 
       next_send_id: 0,
       next_recv_id: 0,
-      queue: MessageQueue::new(Service::Coordinator, rpc, Zeroizing::new(coord_key)),
+      queue: MessageQueue::new(
+        Service::Coordinator,
+        rpc,
+        Zeroizing::new(coord_key),
+        HashMap::from([(Service::Processor(network), processor_key)]),
+      ),
     };
 
     // Sleep for up to a minute in case the external network's RPC has yet to start
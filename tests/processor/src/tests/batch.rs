@@ -171,6 +171,7 @@ pub(crate) async fn substrate_block(
       context: _,
       block: sent_block,
       burns: _,
+      burn_extrinsics: _,
       batches: _,
     } => {
       coordinator.send_message(block).await;
@@ -198,7 +199,9 @@ fn batch_test() {
 
       let mut coordinators = coordinators
         .into_iter()
-        .map(|(handles, key)| Coordinator::new(network, &ops, handles, key))
+        .map(|(handles, key, processor_key)| {
+          Coordinator::new(network, &ops, handles, key, processor_key)
+        })
         .collect::<Vec<_>>();
 
       // Create a wallet before we start generating keys
@@ -318,6 +321,7 @@ fn batch_test() {
               },
               block: substrate_block_num + u64::from(i),
               burns: vec![],
+              burn_extrinsics: vec![],
               batches: vec![batch.batch.id],
             },
           )
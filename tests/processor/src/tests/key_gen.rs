@@ -155,7 +155,9 @@ fn key_gen_test() {
       // Connect to the Message Queues as the coordinator
       let mut coordinators = coordinators
         .into_iter()
-        .map(|(handles, key)| Coordinator::new(network, &ops, handles, key))
+        .map(|(handles, key, processor_key)| {
+          Coordinator::new(network, &ops, handles, key, processor_key)
+        })
         .collect::<Vec<_>>();
 
       key_gen(&mut coordinators).await;
@@ -17,18 +17,19 @@ pub(crate) const THRESHOLD: usize = ((COORDINATORS * 2) / 3) + 1;
 
 fn new_test(
   network: ExternalNetworkId,
-) -> (Vec<(Handles, <Ristretto as Ciphersuite>::F)>, DockerTest) {
+) -> (Vec<(Handles, <Ristretto as Ciphersuite>::F, <Ristretto as Ciphersuite>::G)>, DockerTest) {
   let mut coordinators = vec![];
   let mut test = DockerTest::new().with_network(dockertest::Network::Isolated);
   let mut eth_handle = None;
   for _ in 0 .. COORDINATORS {
-    let (handles, coord_key, compositions) = processor_stack(network, eth_handle.clone());
+    let (handles, coord_key, processor_key, compositions) =
+      processor_stack(network, eth_handle.clone());
     // TODO: Remove this once https://github.com/foundry-rs/foundry/issues/7955
     // This has all processors share an Ethereum node until we can sync controlled nodes
     if network == ExternalNetworkId::Ethereum {
       eth_handle = eth_handle.or_else(|| Some(handles.0.clone()));
     }
-    coordinators.push((handles, coord_key));
+    coordinators.push((handles, coord_key, processor_key));
     for composition in compositions {
       test.provide_container(composition);
     }
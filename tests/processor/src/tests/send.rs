@@ -155,7 +155,9 @@ fn send_test() {
 
       let mut coordinators = coordinators
         .into_iter()
-        .map(|(handles, key)| Coordinator::new(network, &ops, handles, key))
+        .map(|(handles, key, processor_key)| {
+          Coordinator::new(network, &ops, handles, key, processor_key)
+        })
         .collect::<Vec<_>>();
 
       // Create a wallet before we start generating keys
@@ -246,6 +248,7 @@ fn send_test() {
               instruction: OutInstruction { address: wallet.address(), data: None },
               balance: ExternalBalance { coin: balance_sent.coin, amount: amount_minted },
             }],
+            burn_extrinsics: vec![[0; 32]],
             batches: vec![batch.batch.id],
           },
         )